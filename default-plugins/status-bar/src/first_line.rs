@@ -274,7 +274,7 @@ pub fn ctrl_keys(help: &ModeInfo, max_len: usize, separator: &str) -> LinePart {
             colored_elements,
             separator,
         ),
-        InputMode::Pane => key_indicators(
+        InputMode::Pane | InputMode::RenamePane => key_indicators(
             max_len,
             &[
                 CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Lock),
@@ -288,7 +288,7 @@ pub fn ctrl_keys(help: &ModeInfo, max_len: usize, separator: &str) -> LinePart {
             colored_elements,
             separator,
         ),
-        InputMode::Tab | InputMode::RenameTab => key_indicators(
+        InputMode::Tab | InputMode::RenameTab | InputMode::RenameTabGroup => key_indicators(
             max_len,
             &[
                 CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Lock),
@@ -302,7 +302,11 @@ pub fn ctrl_keys(help: &ModeInfo, max_len: usize, separator: &str) -> LinePart {
             colored_elements,
             separator,
         ),
-        InputMode::Scroll => key_indicators(
+        InputMode::Scroll
+        | InputMode::Search
+        | InputMode::TabSearch
+        | InputMode::CommandHistory
+        | InputMode::Copy => key_indicators(
             max_len,
             &[
                 CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Lock),
@@ -330,19 +334,21 @@ pub fn ctrl_keys(help: &ModeInfo, max_len: usize, separator: &str) -> LinePart {
             colored_elements,
             separator,
         ),
-        InputMode::Session => key_indicators(
-            max_len,
-            &[
-                CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Lock),
-                CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Pane),
-                CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Tab),
-                CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Resize),
-                CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Scroll),
-                CtrlKeyShortcut::new(CtrlKeyMode::Selected, CtrlKeyAction::Session),
-                CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Quit),
-            ],
-            colored_elements,
-            separator,
-        ),
+        InputMode::Session | InputMode::RenameSession | InputMode::SessionManager => {
+            key_indicators(
+                max_len,
+                &[
+                    CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Lock),
+                    CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Pane),
+                    CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Tab),
+                    CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Resize),
+                    CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Scroll),
+                    CtrlKeyShortcut::new(CtrlKeyMode::Selected, CtrlKeyAction::Session),
+                    CtrlKeyShortcut::new(CtrlKeyMode::Unselected, CtrlKeyAction::Quit),
+                ],
+                colored_elements,
+                separator,
+            )
+        }
     }
 }
@@ -114,6 +114,7 @@ fn left_more_message(tab_count_to_the_left: usize, palette: Palette, separator:
     LinePart {
         part: more_styled_text,
         len: more_text_len,
+        tab_index: None,
     }
 }
 
@@ -144,6 +145,7 @@ fn right_more_message(
     LinePart {
         part: more_styled_text,
         len: more_text_len,
+        tab_index: None,
     }
 }
 
@@ -157,6 +159,7 @@ fn tab_line_prefix(session_name: Option<&str>, palette: Palette, cols: usize) ->
     let mut parts = vec![LinePart {
         part: format!("{}", prefix_styled_text),
         len: prefix_text_len,
+        tab_index: None,
     }];
     if let Some(name) = session_name {
         let name_part = format!("({}) ", name);
@@ -166,6 +169,7 @@ fn tab_line_prefix(session_name: Option<&str>, palette: Palette, cols: usize) ->
             parts.push(LinePart {
                 part: format!("{}", name_part_styled_text),
                 len: name_part_len,
+                tab_index: None,
             })
         }
     }
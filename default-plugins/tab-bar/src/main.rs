@@ -4,18 +4,20 @@ mod tab;
 use zellij_tile::prelude::*;
 
 use crate::line::tab_line;
-use crate::tab::tab_style;
+use crate::tab::{group_label, tab_style};
 
 #[derive(Debug, Default)]
 pub struct LinePart {
     part: String,
     len: usize,
+    tab_index: Option<usize>,
 }
 
 #[derive(Default)]
 struct State {
     tabs: Vec<TabInfo>,
     mode_info: ModeInfo,
+    tab_line: Vec<LinePart>,
 }
 
 static ARROW_SEPARATOR: &str = "";
@@ -25,13 +27,27 @@ register_plugin!(State);
 impl ZellijPlugin for State {
     fn load(&mut self) {
         set_selectable(false);
-        subscribe(&[EventType::TabUpdate, EventType::ModeUpdate]);
+        subscribe(&[
+            EventType::TabUpdate,
+            EventType::ModeUpdate,
+            EventType::Mouse,
+        ]);
     }
 
     fn update(&mut self, event: Event) {
         match event {
             Event::ModeUpdate(mode_info) => self.mode_info = mode_info,
             Event::TabUpdate(tabs) => self.tabs = tabs,
+            Event::Mouse(Mouse::LeftClick(_, col, _)) => {
+                if let Some(tab_index) = self.tab_index_at(col) {
+                    switch_tab_to(tab_index as u32 + 1);
+                }
+            }
+            Event::Mouse(Mouse::MiddleClick(_, col, _)) => {
+                if let Some(tab_index) = self.tab_index_at(col) {
+                    close_tab_at(tab_index as u32 + 1);
+                }
+            }
             _ => unimplemented!(), // FIXME: This should be unreachable, but this could be cleaner
         }
     }
@@ -42,24 +58,58 @@ impl ZellijPlugin for State {
         }
         let mut all_tabs: Vec<LinePart> = vec![];
         let mut active_tab_index = 0;
-        for t in self.tabs.iter_mut() {
-            let mut tabname = t.name.clone();
-            if t.active && self.mode_info.mode == InputMode::RenameTab {
-                if tabname.is_empty() {
+        for run in group_runs(&self.tabs) {
+            let has_active_tab = run.iter().any(|t| t.active);
+            if let Some(group) = &run[0].group {
+                let mut group_name = group.clone();
+                if has_active_tab
+                    && self.mode_info.mode == InputMode::RenameTabGroup
+                    && group_name.is_empty()
+                {
+                    group_name = String::from("Enter name...");
+                }
+                if run[0].is_group_collapsed {
+                    if has_active_tab {
+                        active_tab_index = all_tabs.len();
+                    }
+                    let mut label = group_label(
+                        &group_name,
+                        Some(run.len()),
+                        has_active_tab,
+                        self.mode_info.palette,
+                        self.mode_info.capabilities,
+                    );
+                    // clicking a collapsed group goes to its first tab
+                    label.tab_index = Some(run[0].position);
+                    all_tabs.push(label);
+                    continue;
+                }
+                all_tabs.push(group_label(
+                    &group_name,
+                    None,
+                    false,
+                    self.mode_info.palette,
+                    self.mode_info.capabilities,
+                ));
+            }
+            for t in run {
+                let mut tabname = t.name.clone();
+                if t.active && self.mode_info.mode == InputMode::RenameTab && tabname.is_empty() {
                     tabname = String::from("Enter name...");
                 }
-                active_tab_index = t.position;
-            } else if t.active {
-                active_tab_index = t.position;
+                if t.active {
+                    active_tab_index = all_tabs.len();
+                }
+                let mut tab = tab_style(
+                    tabname,
+                    t.active,
+                    t.is_sync_panes_active,
+                    self.mode_info.palette,
+                    self.mode_info.capabilities,
+                );
+                tab.tab_index = Some(t.position);
+                all_tabs.push(tab);
             }
-            let tab = tab_style(
-                tabname,
-                t.active,
-                t.is_sync_panes_active,
-                self.mode_info.palette,
-                self.mode_info.capabilities,
-            );
-            all_tabs.push(tab);
         }
         let tab_line = tab_line(
             self.mode_info.session_name.as_deref(),
@@ -70,9 +120,10 @@ impl ZellijPlugin for State {
             self.mode_info.capabilities,
         );
         let mut s = String::new();
-        for bar_part in tab_line {
+        for bar_part in tab_line.iter() {
             s = format!("{}{}", s, bar_part.part);
         }
+        self.tab_line = tab_line;
         match self.mode_info.palette.cyan {
             PaletteColor::Rgb((r, g, b)) => {
                 println!("{}\u{1b}[48;2;{};{};{}m\u{1b}[0K", s, r, g, b);
@@ -83,3 +134,31 @@ impl ZellijPlugin for State {
         }
     }
 }
+
+/// Splits the tabs into runs of neighbouring tabs in the same group, each ungrouped tab being
+/// a run of its own.
+fn group_runs(tabs: &[TabInfo]) -> Vec<&[TabInfo]> {
+    let mut runs = vec![];
+    let mut run_start = 0;
+    for i in 1..=tabs.len() {
+        if i == tabs.len() || tabs[i].group.is_none() || tabs[i].group != tabs[run_start].group {
+            runs.push(&tabs[run_start..i]);
+            run_start = i;
+        }
+    }
+    runs
+}
+
+impl State {
+    /// The position of the tab drawn at the given column of the last rendered tab line.
+    fn tab_index_at(&self, col: usize) -> Option<usize> {
+        let mut part_start = 0;
+        for part in self.tab_line.iter() {
+            if col < part_start + part.len {
+                return part.tab_index;
+            }
+            part_start += part.len;
+        }
+        None
+    }
+}
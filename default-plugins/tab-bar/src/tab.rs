@@ -18,6 +18,7 @@ pub fn active_tab(text: String, palette: Palette, separator: &str) -> LinePart {
     LinePart {
         part: tab_styled_text,
         len: tab_text_len,
+        tab_index: None,
     }
 }
 
@@ -35,6 +36,7 @@ pub fn non_active_tab(text: String, palette: Palette, separator: &str) -> LinePa
     LinePart {
         part: tab_styled_text,
         len: tab_text_len,
+        tab_index: None,
     }
 }
 
@@ -56,3 +58,36 @@ pub fn tab_style(
         non_active_tab(tab_text, palette, separator)
     }
 }
+
+/// The label drawn in front of the tabs of a group, or in their place with the amount of tabs
+/// in it when the group is collapsed.
+pub fn group_label(
+    name: &str,
+    collapsed_tab_count: Option<usize>,
+    has_active_tab: bool,
+    palette: Palette,
+    capabilities: PluginCapabilities,
+) -> LinePart {
+    let separator = tab_separator(capabilities);
+    let text = match collapsed_tab_count {
+        Some(count) => format!(" ▸ {} ({}) ", name, count),
+        None => format!(" ▾ {} ", name),
+    };
+    let text_len = text.width() + 2 * separator.width();
+    let background = if has_active_tab {
+        palette.green
+    } else {
+        palette.magenta
+    };
+    let left_separator = style!(palette.cyan, background).paint(separator);
+    let styled_text = style!(palette.black, background).bold().paint(text);
+    let right_separator = style!(background, palette.cyan).paint(separator);
+    LinePart {
+        part: format!(
+            "{}",
+            ANSIStrings(&[left_separator, styled_text, right_separator,])
+        ),
+        len: text_len,
+        tab_index: None,
+    }
+}
@@ -4,26 +4,166 @@ mod sessions;
 mod tests;
 
 use crate::install::populate_data_dir;
-use sessions::{assert_session, assert_session_ne, get_active_session, list_sessions};
+use sessions::{
+    assert_session, assert_session_ne, delete_session, export_session, generate_session_name,
+    get_active_session, is_live_session, kill_all_sessions, kill_session, list_sessions,
+    resurrection_bundle, send_action, send_action_to_session,
+};
+use std::convert::TryFrom;
 use std::process;
 use zellij_client::{os_input_output::get_client_os_input, start_client, ClientInfo};
 use zellij_server::{os_input_output::get_server_os_input, start_server};
 use zellij_utils::{
-    cli::{CliArgs, Command, SessionCommand, Sessions},
-    consts::{ZELLIJ_TMP_DIR, ZELLIJ_TMP_LOG_DIR},
+    cli::{CliAction, CliArgs, Command, SessionCommand, Sessions},
+    consts::{custom_socket_dir, SOCKET_DIR_OPTION, ZELLIJ_TMP_DIR, ZELLIJ_TMP_LOG_DIR},
+    input::{
+        actions::Action,
+        bundle::{resurrection_path, SessionBundle},
+        config::Config,
+    },
     logging::*,
     setup::{get_default_data_dir, Setup},
+    shared::check_socket_dir,
     structopt::StructOpt,
 };
 
 pub fn main() {
     configure_logger();
-    let opts = CliArgs::from_args();
+    let mut opts = CliArgs::from_args();
+
+    // the sessions are found through their sockets, so this comes before everything else
+    if let Some(socket_dir) = Config::try_from(&opts)
+        .ok()
+        .and_then(|config| config.options.from_cli(opts.command.clone()).socket_dir)
+    {
+        SOCKET_DIR_OPTION.set(socket_dir).unwrap();
+    }
+    if let Some(socket_dir) = custom_socket_dir() {
+        if let Err(e) = check_socket_dir(&socket_dir) {
+            eprintln!("Refusing to use the socket directory: {}", e);
+            process::exit(1);
+        }
+    }
 
     if let Some(Command::Sessions(Sessions::ListSessions)) = opts.command {
         list_sessions();
     }
 
+    if let Some(Command::Sessions(Sessions::DeleteSession { session_name })) = opts.command.clone()
+    {
+        delete_session(&session_name);
+    }
+
+    if let Some(Command::Sessions(Sessions::KillSession { session_name })) = opts.command.clone() {
+        kill_session(&session_name);
+    }
+
+    if let Some(Command::Sessions(Sessions::KillAllSessions { yes })) = opts.command {
+        kill_all_sessions(yes);
+    }
+
+    if let Some(Command::Sessions(Sessions::Action { json, action })) = opts.command.clone() {
+        let current_session = std::env::var("ZELLIJ_SESSION_NAME").ok();
+        match &action {
+            // focusing something in another session takes this terminal there as well
+            CliAction::Focus {
+                session: Some(session_name),
+                force,
+                ..
+            } if Some(session_name) != current_session.as_ref() => {
+                let (session_name, force) = (session_name.clone(), *force);
+                assert_session(&session_name);
+                let exit_code = send_action(&session_name, action.into(), json);
+                if exit_code != 0 {
+                    process::exit(exit_code);
+                }
+                match current_session {
+                    // the client of the session we're in switches over
+                    Some(current_session) => send_action_to_session(
+                        &current_session,
+                        Action::SwitchSession(session_name, force),
+                        json,
+                    ),
+                    None => {
+                        opts.command = Some(Command::Sessions(Sessions::Attach {
+                            session_name: Some(session_name),
+                            force,
+                            read_only: false,
+                            options: None,
+                        }));
+                    }
+                }
+            }
+            _ => {
+                // target the session we're running in, unless told otherwise
+                let session_name = opts
+                    .session
+                    .clone()
+                    .or(current_session)
+                    .unwrap_or_else(get_active_session);
+                assert_session(&session_name);
+                send_action_to_session(&session_name, action.into(), json);
+            }
+        }
+    }
+
+    if let Some(Command::Sessions(Sessions::Edit { reuse, file })) = opts.command.clone() {
+        let session_name = opts
+            .session
+            .clone()
+            .or_else(|| std::env::var("ZELLIJ_SESSION_NAME").ok())
+            .unwrap_or_else(get_active_session);
+        assert_session(&session_name);
+        // the path is taken from where the command is run, rather than where the server runs
+        let file = std::env::current_dir()
+            .map(|cwd| cwd.join(&file))
+            .unwrap_or(file);
+        send_action_to_session(&session_name, Action::EditFile(file, reuse), false);
+    }
+
+    // an imported session starts like a new one, from the layout and options of the bundle
+    let bundle = match opts.command.clone() {
+        Some(Command::Sessions(Sessions::ImportSession { path, session_name })) => {
+            let bundle = match SessionBundle::from_path(&path) {
+                Ok(bundle) => bundle,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            };
+            for plugin in bundle.missing_plugins() {
+                eprintln!("Plugin {:?} of the session is missing here.", plugin);
+            }
+            opts.session = session_name.or_else(|| opts.session.clone());
+            opts.command = None;
+            Some(bundle)
+        }
+        // a resurrected session starts the same way, under the name it had
+        Some(Command::Sessions(Sessions::Resurrect { session_name })) => {
+            assert_session_ne(&session_name);
+            let bundle = resurrection_bundle(&session_name);
+            opts.session = Some(session_name);
+            opts.command = None;
+            Some(bundle)
+        }
+        // as does a session attached to after its server is gone
+        Some(Command::Sessions(Sessions::Attach {
+            session_name: Some(session_name),
+            options,
+            ..
+        })) if !is_live_session(&session_name) && resurrection_path(&session_name).exists() => {
+            assert_session_ne(&session_name);
+            let mut bundle = resurrection_bundle(&session_name);
+            if let Some(SessionCommand::Options(options)) = options {
+                bundle.options = bundle.options.merge(options);
+            }
+            opts.session = Some(session_name);
+            opts.command = None;
+            Some(bundle)
+        }
+        _ => None,
+    };
+
     atomic_create_dir(&*ZELLIJ_TMP_DIR).unwrap();
     atomic_create_dir(&*ZELLIJ_TMP_LOG_DIR).unwrap();
     if let Some(path) = opts.server {
@@ -36,7 +176,7 @@ pub fn main() {
         };
         start_server(Box::new(os_input), path);
     } else {
-        let (config, layout, config_options) = match Setup::from_options(&opts) {
+        let (mut config, mut layout, mut config_options) = match Setup::from_options(&opts) {
             Ok(results) => results,
             Err(e) => {
                 eprintln!("{}", e);
@@ -44,6 +184,21 @@ pub fn main() {
             }
         };
 
+        if let Some(Command::Sessions(Sessions::ExportSession { session_name, path })) =
+            opts.command.clone()
+        {
+            assert_session(&session_name);
+            export_session(&session_name, &path, &config, &config_options);
+        }
+        if let Some(bundle) = bundle {
+            layout = Some(bundle.layout);
+            config_options = config_options.merge(bundle.options);
+            config.themes = match (config.themes, bundle.themes) {
+                (Some(themes), Some(bundle_themes)) => Some(themes.merge(bundle_themes)),
+                (themes, bundle_themes) => bundle_themes.or(themes),
+            };
+        }
+
         let os_input = match get_client_os_input() {
             Ok(os_input) => os_input,
             Err(e) => {
@@ -54,6 +209,7 @@ pub fn main() {
         if let Some(Command::Sessions(Sessions::Attach {
             mut session_name,
             force,
+            read_only,
             options,
         })) = opts.command.clone()
         {
@@ -73,14 +229,14 @@ pub fn main() {
                 opts,
                 config,
                 config_options.clone(),
-                ClientInfo::Attach(session_name.unwrap(), force, config_options),
+                ClientInfo::Attach(session_name.unwrap(), force, read_only, config_options),
                 None,
             );
         } else {
             let session_name = opts
                 .session
                 .clone()
-                .unwrap_or_else(|| names::Generator::default().next().unwrap());
+                .unwrap_or_else(|| generate_session_name(&config_options));
             assert_session_ne(&session_name);
 
             // Determine and initialize the data directory
@@ -1,20 +1,50 @@
+use rand::{seq::SliceRandom, Rng};
 use std::os::unix::fs::FileTypeExt;
-use std::{fs, io, process};
+use std::path::Path;
+use std::time::Duration;
+use std::{fs, io, process, thread};
 use zellij_utils::{
-    consts::ZELLIJ_SOCK_DIR,
+    action_token, channels,
+    consts::{ZELLIJ_RESURRECTION_DIR, ZELLIJ_SOCK_DIR, ZELLIJ_TMP_DIR},
+    input::{
+        actions::Action,
+        bundle::{resurrection_path, SessionBundle},
+        config::Config,
+        layout::LayoutFromYaml,
+        options::Options,
+    },
     interprocess::local_socket::LocalSocketStream,
-    ipc::{ClientToServerMsg, IpcSenderWithContext},
+    ipc::{ActionResult, ClientToServerMsg, IpcSenderWithContext, ServerToClientMsg},
 };
 
-fn get_sessions() -> Result<Vec<String>, io::ErrorKind> {
+const DEFAULT_SESSION_NAME_TEMPLATE: &str = "{adjective}-{noun}";
+
+/// How many random names to try before numbering one that's taken.
+const SESSION_NAME_ATTEMPTS: usize = 100;
+
+/// How long a session has to answer a ping before we consider it dead.
+const PING_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SessionStatus {
+    Alive,
+    /// The socket accepts connections but the server behind it doesn't respond
+    Dead,
+}
+
+/// Returns the sessions in the socket directory along with whether they still
+/// respond. Sockets nobody listens on anymore are removed along the way.
+fn get_sessions() -> Result<Vec<(String, SessionStatus)>, io::ErrorKind> {
     match fs::read_dir(&*ZELLIJ_SOCK_DIR) {
         Ok(files) => {
             let mut sessions = Vec::new();
             files.for_each(|file| {
                 let file = file.unwrap();
                 let file_name = file.file_name().into_string().unwrap();
-                if file.file_type().unwrap().is_socket() && assert_socket(&file_name) {
-                    sessions.push(file_name);
+                if file.file_type().unwrap().is_socket() {
+                    if let Some(status) = assert_socket(&file_name) {
+                        sessions.push((file_name, status));
+                    }
                 }
             });
             Ok(sessions)
@@ -29,28 +59,96 @@ fn get_sessions() -> Result<Vec<String>, io::ErrorKind> {
     }
 }
 
-fn assert_socket(name: &str) -> bool {
+fn get_live_sessions() -> Result<Vec<String>, io::ErrorKind> {
+    get_sessions().map(|sessions| {
+        sessions
+            .into_iter()
+            .filter(|(_, status)| *status == SessionStatus::Alive)
+            .map(|(name, _)| name)
+            .collect()
+    })
+}
+
+pub(crate) fn is_live_session(name: &str) -> bool {
+    get_live_sessions().map_or(false, |sessions| sessions.iter().any(|s| s == name))
+}
+
+/// The sessions whose server is gone but that were written to disk, and can be resurrected.
+fn get_resurrectable_sessions(live_sessions: &[(String, SessionStatus)]) -> Vec<String> {
+    let files = match fs::read_dir(&*ZELLIJ_RESURRECTION_DIR) {
+        Ok(files) => files,
+        Err(_) => return vec![],
+    };
+    let mut sessions: Vec<String> = files
+        .filter_map(|file| {
+            let path = file.ok()?.path();
+            if path.extension()? != "yaml" {
+                return None;
+            }
+            path.file_stem()?.to_str().map(String::from)
+        })
+        .filter(|name| !live_sessions.iter().any(|(session, _)| session == name))
+        .collect();
+    sessions.sort();
+    sessions
+}
+
+/// The bundle the session `name` was last written to, to start it again.
+pub(crate) fn resurrection_bundle(name: &str) -> SessionBundle {
+    let path = resurrection_path(name);
+    if !path.exists() {
+        println!("No session named {:?} to resurrect.", name);
+        process::exit(1);
+    }
+    match SessionBundle::from_path(&path) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            eprintln!("Error occured: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Pings the session listening on the socket `name`. Returns `None` (and removes
+/// the socket) if nothing listens on it anymore.
+fn assert_socket(name: &str) -> Option<SessionStatus> {
     let path = &*ZELLIJ_SOCK_DIR.join(name);
     match LocalSocketStream::connect(path) {
         Ok(stream) => {
-            IpcSenderWithContext::new(stream).send(ClientToServerMsg::ClientExited);
-            true
+            let mut sender = IpcSenderWithContext::new(stream);
+            let mut receiver = sender.get_receiver::<ServerToClientMsg>();
+            sender.send(ClientToServerMsg::Ping);
+            // a hung server would block us forever, so wait for the reply on
+            // another thread
+            let (pong_sender, pong_receiver) = channels::bounded(1);
+            thread::spawn(move || {
+                if let Some((ServerToClientMsg::Pong, _)) = receiver.try_recv() {
+                    let _ = pong_sender.send(());
+                }
+            });
+            match pong_receiver.recv_timeout(PING_TIMEOUT) {
+                Ok(()) => Some(SessionStatus::Alive),
+                Err(_) => Some(SessionStatus::Dead),
+            }
         }
         Err(e) => {
             if e.kind() == io::ErrorKind::ConnectionRefused {
                 drop(fs::remove_file(path));
-                false
+                drop(fs::remove_file(action_token::token_path(path)));
+                None
             } else {
-                true
+                Some(SessionStatus::Dead)
             }
         }
     }
 }
 
-fn print_sessions(sessions: Vec<String>) {
+fn print_sessions(sessions: Vec<(String, SessionStatus)>) {
     let curr_session = std::env::var("ZELLIJ_SESSION_NAME").unwrap_or_else(|_| "".into());
-    sessions.iter().for_each(|session| {
-        let suffix = if curr_session == *session {
+    sessions.iter().for_each(|(session, status)| {
+        let suffix = if *status == SessionStatus::Dead {
+            " (dead)"
+        } else if curr_session == *session {
             " (current)"
         } else {
             ""
@@ -60,7 +158,7 @@ fn print_sessions(sessions: Vec<String>) {
 }
 
 pub(crate) fn get_active_session() -> String {
-    match get_sessions() {
+    match get_live_sessions() {
         Ok(mut sessions) => {
             if sessions.len() == 1 {
                 return sessions.pop().unwrap();
@@ -69,7 +167,12 @@ pub(crate) fn get_active_session() -> String {
                 println!("No active zellij sessions found.");
             } else {
                 println!("Please specify the session name to attach to. The following sessions are active:");
-                print_sessions(sessions);
+                print_sessions(
+                    sessions
+                        .into_iter()
+                        .map(|session| (session, SessionStatus::Alive))
+                        .collect(),
+                );
             }
         }
         Err(e) => eprintln!("Error occured: {:?}", e),
@@ -80,10 +183,14 @@ pub(crate) fn get_active_session() -> String {
 pub(crate) fn list_sessions() {
     let exit_code = match get_sessions() {
         Ok(sessions) => {
-            if sessions.is_empty() {
+            let resurrectable = get_resurrectable_sessions(&sessions);
+            if sessions.is_empty() && resurrectable.is_empty() {
                 println!("No active zellij sessions found.");
             } else {
                 print_sessions(sessions);
+                for session in resurrectable {
+                    println!("{} (exited, resurrect with `zellij resurrect`)", session);
+                }
             }
             0
         }
@@ -96,7 +203,7 @@ pub(crate) fn list_sessions() {
 }
 
 pub(crate) fn assert_session(name: &str) {
-    match get_sessions() {
+    match get_live_sessions() {
         Ok(sessions) => {
             if sessions.iter().any(|s| s == name) {
                 return;
@@ -110,13 +217,238 @@ pub(crate) fn assert_session(name: &str) {
 
 pub(crate) fn assert_session_ne(name: &str) {
     match get_sessions() {
-        Ok(sessions) => {
-            if sessions.iter().all(|s| s != name) {
+        Ok(sessions) => match sessions.iter().find(|(s, _)| s == name) {
+            None => return,
+            // a dead session shouldn't keep its name from being reused
+            Some((_, SessionStatus::Dead)) => {
+                drop(fs::remove_file(&*ZELLIJ_SOCK_DIR.join(name)));
+                drop(fs::remove_file(action_token::token_path(
+                    &ZELLIJ_SOCK_DIR.join(name),
+                )));
                 return;
             }
-            println!("Session with name {:?} aleady exists. Use attach command to connect to it or specify a different name.", name);
-        }
+            Some((_, SessionStatus::Alive)) => {
+                println!("Session with name {:?} aleady exists. Use attach command to connect to it or specify a different name.", name);
+            }
+        },
         Err(e) => eprintln!("Error occured: {:?}", e),
     };
     process::exit(1);
 }
+
+pub(crate) fn delete_session(name: &str) {
+    let had_socket = ZELLIJ_SOCK_DIR.join(name).exists();
+    // a session that exited is gone for good once deleted
+    let had_resurrection_file =
+        !is_live_session(name) && fs::remove_file(resurrection_path(name)).is_ok();
+    let exit_code = match get_sessions() {
+        Ok(sessions) => match sessions.iter().find(|(s, _)| s == name) {
+            Some((_, SessionStatus::Dead)) => match fs::remove_file(&*ZELLIJ_SOCK_DIR.join(name)) {
+                Ok(()) => {
+                    drop(fs::remove_file(action_token::token_path(
+                        &ZELLIJ_SOCK_DIR.join(name),
+                    )));
+                    0
+                }
+                Err(e) => {
+                    eprintln!("Error occured: {:?}", e.kind());
+                    1
+                }
+            },
+            Some((_, SessionStatus::Alive)) => {
+                println!("Session {:?} is still running and can't be deleted.", name);
+                1
+            }
+            // sockets nobody listens on were already cleaned up by `get_sessions`
+            None if had_socket || had_resurrection_file => 0,
+            None => {
+                println!("No session named {:?} found.", name);
+                1
+            }
+        },
+        Err(e) => {
+            eprintln!("Error occured: {:?}", e);
+            1
+        }
+    };
+    process::exit(exit_code);
+}
+
+pub(crate) fn kill_session(name: &str) {
+    assert_session(name);
+    send_action_to_session(name, Action::Quit, false);
+}
+
+pub(crate) fn kill_all_sessions(yes: bool) {
+    let sessions = match get_live_sessions() {
+        Ok(sessions) if sessions.is_empty() => {
+            println!("No active zellij sessions found.");
+            process::exit(1);
+        }
+        Ok(sessions) => sessions,
+        Err(e) => {
+            eprintln!("Error occured: {:?}", e);
+            process::exit(1);
+        }
+    };
+    if !yes {
+        println!(
+            "This kills the sessions {}. Continue? [y/N]",
+            sessions.join(", ")
+        );
+        let mut answer = String::new();
+        drop(io::stdin().read_line(&mut answer));
+        if !matches!(answer.trim(), "y" | "Y") {
+            process::exit(1);
+        }
+    }
+    let exit_code = sessions
+        .iter()
+        .map(|session| send_action(session, Action::Quit, false))
+        .max()
+        .unwrap_or(0);
+    process::exit(exit_code);
+}
+
+/// Prints the result of an action, returning the exit code.
+fn print_action_result(result: ActionResult, json: bool) -> i32 {
+    match result {
+        ActionResult::NewPane(pane_id) => {
+            if json {
+                println!("{}", serde_json::json!({ "pane_id": pane_id }));
+            } else {
+                println!("{}", pane_id);
+            }
+        }
+        ActionResult::NewTab { position, panes } => {
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({ "tab_position": position, "pane_ids": panes })
+                );
+            } else {
+                let panes: Vec<String> = panes.iter().map(|id| id.to_string()).collect();
+                println!("{} {}", position, panes.join(" "));
+            }
+        }
+        ActionResult::Panes(panes) => {
+            for pane in panes {
+                if json {
+                    println!(
+                        "{}",
+                        serde_json::json!({
+                            "pane_id": pane.id,
+                            "tab_position": pane.tab_position,
+                            "title": pane.title,
+                            "tags": pane.tags,
+                        })
+                    );
+                } else {
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        pane.id,
+                        pane.tab_position,
+                        pane.tags.join(","),
+                        pane.title
+                    );
+                }
+            }
+        }
+        ActionResult::Empty => {}
+        ActionResult::Refused(reason) => {
+            if json {
+                eprintln!("{}", serde_json::json!({ "refused": reason }));
+            } else {
+                eprintln!("{}", reason);
+            }
+            return 1;
+        }
+    }
+    0
+}
+
+pub(crate) fn send_action_to_session(name: &str, action: Action, json: bool) {
+    process::exit(send_action(name, action, json));
+}
+
+/// Sends `action` to the session `name` and prints its result, returning the exit code.
+pub(crate) fn send_action(name: &str, action: Action, json: bool) -> i32 {
+    let path = &*ZELLIJ_SOCK_DIR.join(name);
+    match LocalSocketStream::connect(path) {
+        Ok(stream) => {
+            let mut sender = IpcSenderWithContext::new(stream);
+            let mut receiver = sender.get_receiver::<ServerToClientMsg>();
+            let token = action_token::read(name, path);
+            sender.send(ClientToServerMsg::CliAction(action, token));
+            match receiver.recv() {
+                (ServerToClientMsg::ActionResult(result), _) => print_action_result(result, json),
+                _ => 0,
+            }
+        }
+        Err(e) => {
+            eprintln!("Error occured: {:?}", e);
+            1
+        }
+    }
+}
+
+/// Writes the layout of the session `name` along with `options` and the themes of `config` to
+/// `path`, for `import-session` to start it again.
+pub(crate) fn export_session(name: &str, path: &Path, config: &Config, options: &Options) {
+    let layout_path = ZELLIJ_TMP_DIR.join(format!("export-{}.yaml", name));
+    let exit_code = send_action(name, Action::DumpLayout(layout_path.clone()), false);
+    if exit_code != 0 {
+        process::exit(exit_code);
+    }
+    let exported = LayoutFromYaml::new(&layout_path).and_then(|layout| {
+        SessionBundle::new(layout, options.clone(), config.themes.clone()).write_to(path)
+    });
+    let _ = fs::remove_file(&layout_path);
+    match exported {
+        Ok(()) => process::exit(0),
+        Err(e) => {
+            eprintln!("Error occured: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Generates a session name from the `session_name_template` option that no
+/// socket in the socket directory uses yet.
+pub(crate) fn generate_session_name(options: &Options) -> String {
+    let template = options
+        .session_name_template
+        .as_deref()
+        .unwrap_or(DEFAULT_SESSION_NAME_TEMPLATE);
+    let word_list = |words: &Option<Vec<String>>, default: &[&'static str]| match words {
+        Some(words) if !words.is_empty() => words.clone(),
+        _ => default
+            .iter()
+            .map(|word| word.to_string())
+            .collect::<Vec<String>>(),
+    };
+    let adjectives = word_list(&options.session_name_adjectives, names::ADJECTIVES);
+    let nouns = word_list(&options.session_name_nouns, names::NOUNS);
+
+    let mut rng = rand::thread_rng();
+    let mut generate = || {
+        template
+            .replace("{adjective}", adjectives.choose(&mut rng).unwrap())
+            .replace("{noun}", nouns.choose(&mut rng).unwrap())
+            .replace("{number}", &rng.gen_range(1..10000).to_string())
+    };
+    let is_taken = |name: &str| ZELLIJ_SOCK_DIR.join(name).exists();
+
+    for _ in 0..SESSION_NAME_ATTEMPTS {
+        let name = generate();
+        if !is_taken(&name) {
+            return name;
+        }
+    }
+    // the template leaves little room for variation, number the name instead
+    let name = generate();
+    (2..)
+        .map(|n| format!("{}-{}", name, n))
+        .find(|name| !is_taken(name))
+        .unwrap()
+}
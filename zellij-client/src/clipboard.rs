@@ -0,0 +1,134 @@
+//! Putting the text copied in a session in the clipboard of the machine the client runs on, and
+//! reading the text to paste from it.
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use zellij_utils::input::options::{Clipboard, Options};
+
+/// Where copied text goes: to the terminal the client runs in, which puts it in one of its
+/// clipboards (OSC 52), or to a command like `wl-copy`, `xclip` or `pbcopy`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ClipboardProvider {
+    Terminal(Clipboard),
+    Command(String),
+}
+
+impl ClipboardProvider {
+    /// The `copy_command` option when it is set, or else the terminal with the `copy_clipboard`
+    /// option.
+    pub fn from_options(options: &Options) -> Self {
+        match &options.copy_command {
+            Some(copy_command) => ClipboardProvider::Command(copy_command.clone()),
+            None => ClipboardProvider::Terminal(options.copy_clipboard.unwrap_or_default()),
+        }
+    }
+    pub fn set_content(&self, content: &str, stdout: &mut dyn Write) -> io::Result<()> {
+        match self {
+            ClipboardProvider::Terminal(clipboard) => {
+                write!(
+                    stdout,
+                    "\u{1b}]52;{};{}\u{1b}\\",
+                    selection_char(*clipboard),
+                    base64::encode(content)
+                )?;
+                stdout.flush()
+            }
+            ClipboardProvider::Command(copy_command) => run_copy_command(copy_command, content),
+        }
+    }
+}
+
+/// Where the text to paste comes from: the terminal the client runs in, asked for the content of
+/// one of its clipboards (OSC 52), or the output of a command like `wl-paste` or `pbpaste`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PasteSource {
+    Terminal(Clipboard),
+    Command(String),
+}
+
+impl PasteSource {
+    /// The `paste_command` option when it is set, or else the terminal with the `copy_clipboard`
+    /// option.
+    pub fn from_options(options: &Options) -> Self {
+        match &options.paste_command {
+            Some(paste_command) => PasteSource::Command(paste_command.clone()),
+            None => PasteSource::Terminal(options.copy_clipboard.unwrap_or_default()),
+        }
+    }
+}
+
+/// Asks the terminal for the content of `clipboard`, it answers through stdin with the same
+/// sequence holding the content, see [`decode_clipboard_report`].
+pub(crate) fn query_clipboard(clipboard: Clipboard, stdout: &mut dyn Write) -> io::Result<()> {
+    write!(stdout, "\u{1b}]52;{};?\u{1b}\\", selection_char(clipboard))?;
+    stdout.flush()
+}
+
+/// The content of the clipboard in the `<selection>;<base64>` parameters of the OSC 52 report of
+/// the terminal.
+pub(crate) fn decode_clipboard_report(parameters: &[u8]) -> Option<Vec<u8>> {
+    let separator = parameters.iter().position(|&byte| byte == b';')?;
+    base64::decode(&parameters[separator + 1..]).ok()
+}
+
+/// The output of `paste_command`, waiting for it to finish.
+pub(crate) fn run_paste_command(paste_command: &str) -> io::Result<Vec<u8>> {
+    let mut args = paste_command.split_whitespace();
+    let program = match args.next() {
+        Some(program) => program,
+        None => return Ok(vec![]),
+    };
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("{:?} exited with {}", paste_command, output.status),
+        ))
+    }
+}
+
+fn selection_char(clipboard: Clipboard) -> char {
+    match clipboard {
+        Clipboard::System => 'c',
+        Clipboard::Primary => 'p',
+    }
+}
+
+// the command runs in the background, commands like xclip keep running to serve the clipboard
+fn run_copy_command(copy_command: &str, text: &str) -> io::Result<()> {
+    let mut args = copy_command.split_whitespace();
+    let program = match args.next() {
+        Some(program) => program,
+        None => return Ok(()),
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdin = child.stdin.take();
+    let text = text.to_owned();
+    thread::spawn(move || {
+        if let Some(stdin) = stdin.as_mut() {
+            if let Err(e) = stdin.write_all(text.as_bytes()) {
+                log::error!("Failed to write to the copy command: {}", e);
+            }
+        }
+        // closing its input lets the command know the text is complete
+        drop(stdin);
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "./unit/clipboard_tests.rs"]
+mod clipboard_tests;
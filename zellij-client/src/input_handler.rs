@@ -2,23 +2,34 @@
 
 use zellij_utils::{
     input::{
-        mouse::{MouseButton, MouseEvent},
+        keyboard_layout::KeyAliases,
+        mouse::{modifiers_from_raw_sequence, MouseEvent},
         options::Options,
     },
     termion, zellij_tile,
 };
 
-use crate::{os_input_output::ClientOsApi, ClientInstruction, CommandIsExecuting};
+use crate::{
+    clipboard::{self, PasteSource},
+    os_input_output::ClientOsApi,
+    ClientInstruction, CommandIsExecuting,
+};
 use zellij_utils::{
-    channels::{SenderWithContext, OPENCALLS},
+    channels::{Receiver, SenderWithContext, OPENCALLS},
     errors::ContextType,
-    input::{actions::Action, cast_termion_key, config::Config, keybinds::Keybinds},
+    input::{actions::Action, cast_termion_key, config::Config, keybinds::Keybinds, InputError},
     ipc::{ClientToServerMsg, ExitReason},
 };
 
+use std::io::Write;
+use std::time::{Duration, Instant};
 use termion::input::TermReadEventsAndRaw;
 use zellij_tile::data::{InputMode, Key};
 
+/// How long after asking the terminal for its clipboard its answer is looked for in the input,
+/// terminals that don't allow reading the clipboard never answer.
+const CLIPBOARD_REPORT_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// Handles the dispatching of [`Action`]s according to the current
 /// [`InputMode`], and keep tracks of the current [`InputMode`].
 struct InputHandler {
@@ -27,10 +38,18 @@ struct InputHandler {
     os_input: Box<dyn ClientOsApi>,
     config: Config,
     options: Options,
+    /// The aliases of the keys of the `keyboard_layout` option
+    key_aliases: KeyAliases,
     command_is_executing: CommandIsExecuting,
     send_client_instructions: SenderWithContext<ClientInstruction>,
     should_exit: bool,
-    pasting: bool,
+    /// The text pasted so far, while inside a bracketed paste
+    paste: Option<Vec<u8>>,
+    paste_source: PasteSource,
+    /// When the terminal was last asked for its clipboard, until it answers
+    clipboard_queried_at: Option<Instant>,
+    /// The modes the session tells the client to switch to, eg. when it joins other clients
+    mode_updates: Receiver<InputMode>,
 }
 
 impl InputHandler {
@@ -42,16 +61,33 @@ impl InputHandler {
         options: Options,
         send_client_instructions: SenderWithContext<ClientInstruction>,
         mode: InputMode,
+        mode_updates: Receiver<InputMode>,
     ) -> Self {
+        let key_aliases = match options.keyboard_layout.as_deref() {
+            Some(name) => config
+                .keyboard_layouts
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| {
+                    log::warn!("Unknown keyboard layout {:?}, keys are not aliased", name);
+                    KeyAliases::default()
+                }),
+            None => KeyAliases::default(),
+        };
+        let paste_source = PasteSource::from_options(&options);
         InputHandler {
             mode,
             os_input,
             config,
             options,
+            key_aliases,
             command_is_executing,
             send_client_instructions,
             should_exit: false,
-            pasting: false,
+            paste: None,
+            paste_source,
+            clipboard_queried_at: None,
+            mode_updates,
         }
     }
 
@@ -60,71 +96,155 @@ impl InputHandler {
     fn handle_input(&mut self) {
         let mut err_ctx = OPENCALLS.with(|ctx| *ctx.borrow());
         err_ctx.add_call(ContextType::StdinHandler);
-        let alt_left_bracket = vec![27, 91];
-        let bracketed_paste_start = vec![27, 91, 50, 48, 48, 126]; // \u{1b}[200~
-        let bracketed_paste_end = vec![27, 91, 50, 48, 49, 126]; // \u{1b}[201
 
         if !self.options.disable_mouse_mode {
             self.os_input.enable_mouse();
+            if self.options.focus_follows_mouse {
+                self.os_input.enable_mouse_motion();
+            }
         }
+        if self.options.pause_rendering_when_unfocused {
+            let enable_focus_reporting = "\u{1b}[?1004h";
+            let mut stdout = self.os_input.get_stdout_writer();
+            let _ = stdout
+                .write_all(enable_focus_reporting.as_bytes())
+                .and_then(|_| stdout.flush());
+        }
+        // a character or escape sequence that was cut off at the end of the last read
+        let mut incomplete_input = Vec::new();
         loop {
             if self.should_exit {
                 break;
             }
-            let stdin_buffer = self.os_input.read_from_stdin();
-            for key_result in stdin_buffer.events_and_raw() {
-                match key_result {
-                    Ok((event, raw_bytes)) => match event {
-                        termion::event::Event::Key(key) => {
-                            let key = cast_termion_key(key);
-                            self.handle_key(&key, raw_bytes);
-                        }
-                        termion::event::Event::Mouse(me) => {
-                            let mouse_event = zellij_utils::input::mouse::MouseEvent::from(me);
-                            self.handle_mouse_event(&mouse_event);
-                        }
-                        termion::event::Event::Unsupported(unsupported_key) => {
-                            // we have to do this because of a bug in termion
-                            // this should be a key event and not an unsupported event
-                            if unsupported_key == alt_left_bracket {
-                                let key = Key::Alt('[');
-                                self.handle_key(&key, raw_bytes);
-                            } else if unsupported_key == bracketed_paste_start {
-                                self.pasting = true;
-                                self.handle_unknown_key(raw_bytes);
-                            } else if unsupported_key == bracketed_paste_end {
-                                self.pasting = false;
-                                self.handle_unknown_key(raw_bytes);
-                            } else {
-                                // this is a hack because termion doesn't recognize certain keys
-                                // in this case we just forward it to the terminal
-                                self.handle_unknown_key(raw_bytes);
+            let mut stdin_buffer = std::mem::take(&mut incomplete_input);
+            stdin_buffer.extend(self.os_input.read_from_stdin());
+            if let Some(mode) = self.mode_updates.try_iter().last() {
+                self.mode = mode;
+            }
+            // only the last of the motion events read at once matters, this keeps a quick
+            // mouse movement from sending a focus change for every cell it crosses
+            let mut last_mouse_motion = None;
+            let expect_clipboard_report = self.clipboard_queried_at.map_or(false, |queried_at| {
+                queried_at.elapsed() < CLIPBOARD_REPORT_TIMEOUT
+            });
+            let (chunks, complete_len) = split_input(&stdin_buffer, expect_clipboard_report);
+            for chunk in chunks {
+                match chunk {
+                    InputChunk::Parseable(bytes) => {
+                        for key_result in bytes.events_and_raw() {
+                            match key_result {
+                                Ok((event, raw_bytes)) => {
+                                    self.handle_event(event, raw_bytes, &mut last_mouse_motion)
+                                }
+                                Err(err) => self.handle_input_error(err.into()),
                             }
                         }
-                    },
-                    Err(err) => panic!("Encountered read error: {:?}", err),
+                    }
+                    InputChunk::ClipboardReport(parameters) => {
+                        self.clipboard_queried_at = None;
+                        if let Some(text) = clipboard::decode_clipboard_report(parameters)
+                            .filter(|text| !text.is_empty())
+                        {
+                            self.dispatch_action(Action::Paste(text));
+                        }
+                    }
+                    InputChunk::Focus(focused) => {
+                        self.send_client_instructions
+                            .send(ClientInstruction::TerminalFocus(focused))
+                            .unwrap();
+                    }
+                    InputChunk::Malformed(bytes) => {
+                        // the application in the pane might know what to do with it
+                        log::warn!("{}", InputError::MalformedSequence(bytes.to_vec()));
+                        self.handle_unknown_key(bytes.to_vec());
+                    }
+                }
+            }
+            // characters typed through an IME or pasted can be split between two reads, we
+            // hold back the first half until the rest arrives
+            incomplete_input = stdin_buffer.split_off(complete_len);
+            if let Some(mouse_motion) = last_mouse_motion {
+                self.handle_mouse_event(&mouse_motion);
+            }
+        }
+    }
+    fn handle_event(
+        &mut self,
+        event: termion::event::Event,
+        raw_bytes: Vec<u8>,
+        last_mouse_motion: &mut Option<MouseEvent>,
+    ) {
+        let alt_left_bracket = vec![27, 91];
+        let bracketed_paste_start = vec![27, 91, 50, 48, 48, 126]; // \u{1b}[200~
+        let bracketed_paste_end = vec![27, 91, 50, 48, 49, 126]; // \u{1b}[201
+        match event {
+            termion::event::Event::Key(key) => match cast_termion_key(key) {
+                Ok(key) => self.handle_key(&key, raw_bytes),
+                Err(InputError::UnknownKey(key)) => {
+                    // the application in the pane might know what to do with it
+                    log::warn!("Passing unknown key {:?} through to the pane", key);
+                    self.handle_unknown_key(raw_bytes);
+                }
+                Err(err) => self.handle_input_error(err),
+            },
+            termion::event::Event::Mouse(me) => {
+                let modifiers = modifiers_from_raw_sequence(&raw_bytes);
+                let mouse_event = MouseEvent::from(me).with_modifiers(modifiers);
+                self.handle_mouse_event(&mouse_event);
+            }
+            termion::event::Event::Unsupported(unsupported_key) => {
+                // we have to do this because of a bug in termion
+                // this should be a key event and not an unsupported event
+                if unsupported_key == alt_left_bracket {
+                    let key = Key::Alt('[');
+                    self.handle_key(&key, raw_bytes);
+                } else if unsupported_key == bracketed_paste_start {
+                    self.paste = Some(vec![]);
+                } else if unsupported_key == bracketed_paste_end {
+                    self.handle_paste();
+                } else if let Some(mouse_event) =
+                    MouseEvent::press_from_raw_sequence(&unsupported_key)
+                {
+                    self.handle_mouse_event(&mouse_event);
+                } else if let Some(mouse_motion) =
+                    MouseEvent::motion_from_raw_sequence(&unsupported_key)
+                {
+                    *last_mouse_motion = Some(mouse_motion);
+                } else {
+                    // this is a hack because termion doesn't recognize certain keys
+                    // in this case we just forward it to the terminal
+                    self.handle_unknown_key(raw_bytes);
                 }
             }
         }
     }
+    /// Input we can't make sense of is skipped, the user is told about it instead
+    /// of the client going down.
+    fn handle_input_error(&mut self, err: InputError) {
+        log::warn!("{}", err);
+        self.send_client_instructions
+            .send(ClientInstruction::Diagnostic(err.to_string()))
+            .unwrap();
+    }
     fn handle_unknown_key(&mut self, raw_bytes: Vec<u8>) {
-        if self.mode == InputMode::Normal || self.mode == InputMode::Locked {
+        if let Some(paste) = self.paste.as_mut() {
+            paste.extend(raw_bytes);
+        } else if self.mode == InputMode::Normal || self.mode == InputMode::Locked {
             let action = Action::Write(raw_bytes);
             self.dispatch_action(action);
         }
     }
     fn handle_key(&mut self, key: &Key, raw_bytes: Vec<u8>) {
         let keybinds = &self.config.keybinds;
-        if self.pasting {
-            // we're inside a paste block, if we're in a mode that allows sending text to the
-            // terminal, send all text directly without interpreting it
-            // otherwise, just discard the input
-            if self.mode == InputMode::Normal || self.mode == InputMode::Locked {
-                let action = Action::Write(raw_bytes);
-                self.dispatch_action(action);
-            }
+        if let Some(paste) = self.paste.as_mut() {
+            // we're inside a paste block, the text is sent as a whole once it ends
+            paste.extend(raw_bytes);
         } else {
-            for action in Keybinds::key_to_actions(key, raw_bytes, &self.mode, keybinds) {
+            let macros = &self.config.macros;
+            let aliases = &self.key_aliases;
+            for action in
+                Keybinds::key_to_actions(key, raw_bytes, &self.mode, keybinds, macros, aliases)
+            {
                 let should_exit = self.dispatch_action(action);
                 if should_exit {
                     self.should_exit = true;
@@ -132,27 +252,20 @@ impl InputHandler {
             }
         }
     }
-    fn handle_mouse_event(&mut self, mouse_event: &MouseEvent) {
-        match *mouse_event {
-            MouseEvent::Press(button, point) => match button {
-                MouseButton::WheelUp => {
-                    self.dispatch_action(Action::ScrollUpAt(point));
-                }
-                MouseButton::WheelDown => {
-                    self.dispatch_action(Action::ScrollDownAt(point));
-                }
-                MouseButton::Left => {
-                    self.dispatch_action(Action::LeftClick(point));
-                }
-                _ => {}
-            },
-            MouseEvent::Release(point) => {
-                self.dispatch_action(Action::MouseRelease(point));
+    fn handle_paste(&mut self) {
+        // if we're in a mode that allows sending text to the terminal, send the pasted text
+        // without interpreting it, otherwise just discard it
+        if let Some(paste) = self.paste.take() {
+            if self.mode == InputMode::Normal || self.mode == InputMode::Locked {
+                self.dispatch_action(Action::Paste(paste));
             }
-            MouseEvent::Hold(point) => {
-                self.dispatch_action(Action::MouseHold(point));
-                self.os_input
-                    .start_action_repeater(Action::MouseHold(point));
+        }
+    }
+    fn handle_mouse_event(&mut self, mouse_event: &MouseEvent) {
+        for action in Keybinds::mouse_to_actions(mouse_event, &self.mode, &self.options) {
+            self.dispatch_action(action.clone());
+            if let Action::MouseHold(_) = action {
+                self.os_input.start_action_repeater(action);
             }
         }
     }
@@ -172,17 +285,23 @@ impl InputHandler {
         let mut should_break = false;
 
         match action {
-            Action::Quit | Action::Detach => {
+            Action::Quit | Action::Detach(None) => {
                 self.os_input
                     .send_to_server(ClientToServerMsg::Action(action));
                 self.exit();
                 should_break = true;
             }
             Action::SwitchToMode(mode) => {
+                // wait until the server has switched modes as well, so that the keys typed right
+                // after this one aren't handled by the server in the old mode
                 self.mode = mode;
+                self.command_is_executing.blocking_input_thread();
                 self.os_input
                     .send_to_server(ClientToServerMsg::Action(action));
+                self.command_is_executing
+                    .wait_until_input_thread_is_unblocked();
             }
+            Action::PasteFromClipboard => self.paste_from_clipboard(),
             Action::CloseFocus
             | Action::NewPane(_)
             | Action::NewTab(_)
@@ -206,6 +325,34 @@ impl InputHandler {
         should_break
     }
 
+    /// Pastes the output of the paste command right away, or asks the terminal for its clipboard
+    /// and pastes it once it answers.
+    fn paste_from_clipboard(&mut self) {
+        match self.paste_source.clone() {
+            PasteSource::Command(paste_command) => {
+                match clipboard::run_paste_command(&paste_command) {
+                    Ok(text) if !text.is_empty() => {
+                        self.dispatch_action(Action::Paste(text));
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        log::error!("Failed to run the paste command: {}", e);
+                        self.send_client_instructions
+                            .send(ClientInstruction::Diagnostic(e.to_string()))
+                            .unwrap();
+                    }
+                }
+            }
+            PasteSource::Terminal(selection) => {
+                let mut stdout = self.os_input.get_stdout_writer();
+                match clipboard::query_clipboard(selection, &mut *stdout) {
+                    Ok(()) => self.clipboard_queried_at = Some(Instant::now()),
+                    Err(e) => log::error!("Failed to ask the terminal for the clipboard: {}", e),
+                }
+            }
+        }
+    }
+
     /// Routine to be called when the input handler exits (at the moment this is the
     /// same as quitting Zellij).
     fn exit(&mut self) {
@@ -215,6 +362,253 @@ impl InputHandler {
     }
 }
 
+/// Raw input, split around the escape sequences termion's parser would panic on or doesn't
+/// know.
+enum InputChunk<'a> {
+    Parseable(&'a [u8]),
+    Malformed(&'a [u8]),
+    /// The terminal got (`true`) or lost the focus
+    Focus(bool),
+    /// The parameters of the OSC 52 sequence the terminal answers a query of its clipboard with
+    ClipboardReport(&'a [u8]),
+}
+
+/// What termion's parser would make of the escape sequence at the start of some input.
+enum CsiCheck {
+    /// It can be parsed, and is this long
+    Fine(usize),
+    /// Parsing it would panic, it is this long
+    Malformed(usize),
+    /// The rest of it hasn't been read yet
+    Incomplete,
+    /// It reports the terminal getting (`true`) or losing the focus, and is 3 bytes long
+    Focus(bool),
+}
+
+/// What the `ESC ]` sequence at the start of some input is, when looking for the answer of the
+/// terminal to a query of its clipboard.
+enum OscCheck {
+    /// An OSC 52 clipboard report this long, its parameters end at `parameters_end`
+    ClipboardReport { len: usize, parameters_end: usize },
+    /// It might be a clipboard report, the rest of it hasn't been read yet
+    Incomplete,
+    /// Something else, eg. `Alt-]`
+    Other,
+}
+
+const CLIPBOARD_REPORT_START: &[u8] = b"\x1b]52;";
+
+fn check_osc(input: &[u8]) -> OscCheck {
+    if input.len() < CLIPBOARD_REPORT_START.len() {
+        return if CLIPBOARD_REPORT_START.starts_with(input) {
+            OscCheck::Incomplete
+        } else {
+            OscCheck::Other
+        };
+    }
+    if !input.starts_with(CLIPBOARD_REPORT_START) {
+        return OscCheck::Other;
+    }
+    // terminated by BEL or ST (`ESC \`)
+    let terminator = input[CLIPBOARD_REPORT_START.len()..]
+        .iter()
+        .position(|&byte| byte == 0x07 || byte == 0x1b)
+        .map(|position| position + CLIPBOARD_REPORT_START.len());
+    match terminator {
+        None => OscCheck::Incomplete,
+        Some(end) if input[end] == 0x07 => OscCheck::ClipboardReport {
+            len: end + 1,
+            parameters_end: end,
+        },
+        Some(end) => match input.get(end + 1) {
+            None => OscCheck::Incomplete,
+            Some(b'\\') => OscCheck::ClipboardReport {
+                len: end + 2,
+                parameters_end: end,
+            },
+            Some(_) => OscCheck::Other,
+        },
+    }
+}
+
+/// Checks the `ESC [` sequence at the start of `input` for the shapes termion panics on
+/// instead of reporting them as unsupported: cut off sequences and numbers out of range.
+fn check_csi(input: &[u8]) -> CsiCheck {
+    let parse_numbers = |params: &[u8]| -> Option<Vec<Option<u16>>> {
+        let params = std::str::from_utf8(params).ok()?;
+        Some(params.split(';').map(|n| n.parse().ok()).collect())
+    };
+    let final_byte_position = |is_final_byte: &dyn Fn(u8) -> bool| {
+        input
+            .iter()
+            .skip(3)
+            .position(|&byte| is_final_byte(byte))
+            .map(|position| position + 3)
+    };
+    match input.get(2) {
+        // X10 mouse report, three bytes follow
+        Some(b'M') => {
+            if input.len() < 6 {
+                CsiCheck::Incomplete
+            } else if (input[3] as i8).checked_sub(32).is_none() {
+                CsiCheck::Malformed(6)
+            } else {
+                CsiCheck::Fine(6)
+            }
+        }
+        // SGR mouse report, eg. `ESC [ < 0 ; 12 ; 5 M`
+        Some(b'<') => match final_byte_position(&|byte| byte == b'm' || byte == b'M') {
+            None => CsiCheck::Incomplete,
+            Some(end) => {
+                let fits = parse_numbers(&input[3..end]).map_or(false, |numbers| {
+                    numbers.len() >= 3 && numbers.iter().take(3).all(Option::is_some)
+                });
+                if fits {
+                    CsiCheck::Fine(end + 1)
+                } else {
+                    CsiCheck::Malformed(end + 1)
+                }
+            }
+        },
+        // numbered sequence, eg. `ESC [ 2 0 0 ~`
+        Some(b'0'..=b'9') => match final_byte_position(&|byte| (64..=126).contains(&byte)) {
+            None => CsiCheck::Incomplete,
+            Some(end) => {
+                let numbers = parse_numbers(&input[2..end]);
+                let fits = match input[end] {
+                    b'~' => numbers.map_or(false, |numbers| {
+                        numbers
+                            .iter()
+                            .all(|n| n.map_or(false, |n| n <= u8::MAX as u16))
+                    }),
+                    b'M' => numbers.map_or(false, |numbers| {
+                        numbers.len() >= 3 && numbers.iter().all(Option::is_some)
+                    }),
+                    _ => true,
+                };
+                if fits {
+                    CsiCheck::Fine(end + 1)
+                } else {
+                    CsiCheck::Malformed(end + 1)
+                }
+            }
+        },
+        Some(b'I') => CsiCheck::Focus(true),
+        Some(b'O') => CsiCheck::Focus(false),
+        Some(_) => CsiCheck::Fine(3),
+        None => CsiCheck::Fine(2),
+    }
+}
+
+/// Splits off the escape sequences termion can't parse from the rest of the input, and the
+/// answer of the terminal to a query of its clipboard when `expect_clipboard_report` is set.
+/// Also returns how much of the input is complete, the rest is a character or escape sequence
+/// that was cut off and should wait for the next read.
+fn split_input(buffer: &[u8], expect_clipboard_report: bool) -> (Vec<InputChunk>, usize) {
+    let mut chunks = vec![];
+    let mut chunk_start = 0;
+    let mut sequence_end = 0;
+    let mut position = 0;
+    while position + 1 < buffer.len() {
+        if expect_clipboard_report && buffer[position] == 0x1b && buffer[position + 1] == b']' {
+            match check_osc(&buffer[position..]) {
+                OscCheck::ClipboardReport {
+                    len,
+                    parameters_end,
+                } => {
+                    if chunk_start < position {
+                        chunks.push(InputChunk::Parseable(&buffer[chunk_start..position]));
+                    }
+                    let parameters_start = position + CLIPBOARD_REPORT_START.len();
+                    chunks.push(InputChunk::ClipboardReport(
+                        &buffer[parameters_start..position + parameters_end],
+                    ));
+                    position += len;
+                    chunk_start = position;
+                    sequence_end = position;
+                    continue;
+                }
+                OscCheck::Incomplete => {
+                    if chunk_start < position {
+                        chunks.push(InputChunk::Parseable(&buffer[chunk_start..position]));
+                    }
+                    return (chunks, position);
+                }
+                OscCheck::Other => {}
+            }
+        }
+        if buffer[position] != 0x1b || buffer[position + 1] != b'[' {
+            position += 1;
+            continue;
+        }
+        match check_csi(&buffer[position..]) {
+            CsiCheck::Fine(len) => {
+                position += len;
+                sequence_end = position;
+            }
+            CsiCheck::Malformed(len) => {
+                if chunk_start < position {
+                    chunks.push(InputChunk::Parseable(&buffer[chunk_start..position]));
+                }
+                chunks.push(InputChunk::Malformed(&buffer[position..position + len]));
+                position += len;
+                chunk_start = position;
+                sequence_end = position;
+            }
+            CsiCheck::Focus(focused) => {
+                if chunk_start < position {
+                    chunks.push(InputChunk::Parseable(&buffer[chunk_start..position]));
+                }
+                chunks.push(InputChunk::Focus(focused));
+                position += 3;
+                chunk_start = position;
+                sequence_end = position;
+            }
+            CsiCheck::Incomplete => {
+                if chunk_start < position {
+                    chunks.push(InputChunk::Parseable(&buffer[chunk_start..position]));
+                }
+                return (chunks, position);
+            }
+        }
+    }
+    // the bytes of an escape sequence aren't the start of a character
+    let complete_len = complete_utf8_len(buffer).max(sequence_end);
+    if chunk_start < complete_len {
+        chunks.push(InputChunk::Parseable(&buffer[chunk_start..complete_len]));
+    }
+    (chunks, complete_len)
+}
+
+/// Returns the length of `buffer` without a multi-byte UTF-8 character cut off at its end.
+fn complete_utf8_len(buffer: &[u8]) -> usize {
+    let is_continuation_byte = |byte: u8| byte & 0b1100_0000 == 0b1000_0000;
+    // a character is at most 4 bytes long, anything further back is complete
+    let tail_start = buffer.len().saturating_sub(3);
+    let last_char_start = match buffer[tail_start..]
+        .iter()
+        .rposition(|byte| !is_continuation_byte(*byte))
+    {
+        Some(position) => tail_start + position,
+        None => return buffer.len(),
+    };
+    let lead_byte = buffer[last_char_start];
+    let char_len = if lead_byte & 0b1110_0000 == 0b1100_0000 {
+        2
+    } else if lead_byte & 0b1111_0000 == 0b1110_0000 {
+        3
+    } else if lead_byte & 0b1111_1000 == 0b1111_0000 {
+        4
+    } else {
+        1
+    };
+    if buffer.len() - last_char_start < char_len {
+        last_char_start
+    } else {
+        buffer.len()
+    }
+}
+
 /// Entry point to the module. Instantiates an [`InputHandler`] and starts
 /// its [`InputHandler::handle_input()`] loop.
 pub(crate) fn input_loop(
@@ -224,6 +618,7 @@ pub(crate) fn input_loop(
     command_is_executing: CommandIsExecuting,
     send_client_instructions: SenderWithContext<ClientInstruction>,
     default_mode: InputMode,
+    mode_updates: Receiver<InputMode>,
 ) {
     let _handler = InputHandler::new(
         os_input,
@@ -232,6 +627,7 @@ pub(crate) fn input_loop(
         options,
         send_client_instructions,
         default_mode,
+        mode_updates,
     )
     .handle_input();
 }
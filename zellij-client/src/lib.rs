@@ -1,27 +1,34 @@
 pub mod os_input_output;
 
+mod clipboard;
 mod command_is_executing;
 mod input_handler;
+mod links;
 
 use log::info;
 use std::env::current_exe;
 use std::io::{self, Write};
-use std::path::Path;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::thread;
+use std::thread::{self, JoinHandle};
 
 use crate::{
-    command_is_executing::CommandIsExecuting, input_handler::input_loop,
-    os_input_output::ClientOsApi,
+    clipboard::ClipboardProvider, command_is_executing::CommandIsExecuting,
+    input_handler::input_loop, os_input_output::ClientOsApi,
 };
 use zellij_utils::{
-    channels::{self, ChannelWithContext, SenderWithContext},
-    consts::{SESSION_NAME, ZELLIJ_IPC_PIPE},
+    action_token,
+    channels::{self, ChannelWithContext, RecvTimeoutError, Sender, SenderWithContext},
+    consts::{
+        custom_socket_dir, SESSION_NAME, ZELLIJ_CONFIG_DIR_ENV, ZELLIJ_CONFIG_FILE_ENV,
+        ZELLIJ_IPC_PIPE, ZELLIJ_SOCKET_DIR_ENV, ZELLIJ_SOCK_DIR,
+    },
     errors::{ClientContext, ContextType, ErrorInstruction},
-    input::{actions::Action, config::Config, options::Options},
-    ipc::{ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg},
+    input::{actions::Action, bundle::resurrection_path, config::Config, options::Options},
+    ipc::{ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg, HEARTBEAT_INTERVAL},
 };
-use zellij_utils::{cli::CliArgs, input::layout::LayoutFromYaml};
+use zellij_utils::{cli::CliArgs, input::layout::LayoutFromYaml, zellij_tile::data::InputMode};
 
 /// Instructions related to the client-side application
 #[derive(Debug, Clone)]
@@ -30,6 +37,12 @@ pub(crate) enum ClientInstruction {
     Render(String),
     UnblockInputThread,
     Exit(ExitReason),
+    Diagnostic(String),
+    CopyToClipboard(String),
+    TerminalFocus(bool),
+    OpenLink(String),
+    SwitchSession(String, bool),
+    SwitchToMode(InputMode),
 }
 
 impl From<ServerToClientMsg> for ClientInstruction {
@@ -38,6 +51,14 @@ impl From<ServerToClientMsg> for ClientInstruction {
             ServerToClientMsg::Exit(e) => ClientInstruction::Exit(e),
             ServerToClientMsg::Render(buffer) => ClientInstruction::Render(buffer),
             ServerToClientMsg::UnblockInputThread => ClientInstruction::UnblockInputThread,
+            ServerToClientMsg::Diagnostic(message) => ClientInstruction::Diagnostic(message),
+            ServerToClientMsg::CopyToClipboard(text) => ClientInstruction::CopyToClipboard(text),
+            ServerToClientMsg::OpenLink(link) => ClientInstruction::OpenLink(link),
+            ServerToClientMsg::SwitchSession(name, force) => {
+                ClientInstruction::SwitchSession(name, force)
+            }
+            ServerToClientMsg::SwitchToMode(mode) => ClientInstruction::SwitchToMode(mode),
+            ServerToClientMsg::ActionResult(_) | ServerToClientMsg::Pong => unreachable!(),
         }
     }
 }
@@ -49,6 +70,12 @@ impl From<&ClientInstruction> for ClientContext {
             ClientInstruction::Error(_) => ClientContext::Error,
             ClientInstruction::Render(_) => ClientContext::Render,
             ClientInstruction::UnblockInputThread => ClientContext::UnblockInputThread,
+            ClientInstruction::Diagnostic(_) => ClientContext::Diagnostic,
+            ClientInstruction::CopyToClipboard(_) => ClientContext::CopyToClipboard,
+            ClientInstruction::TerminalFocus(_) => ClientContext::TerminalFocus,
+            ClientInstruction::OpenLink(_) => ClientContext::OpenLink,
+            ClientInstruction::SwitchSession(..) => ClientContext::SwitchSession,
+            ClientInstruction::SwitchToMode(_) => ClientContext::SwitchToMode,
         }
     }
 }
@@ -60,10 +87,13 @@ impl ErrorInstruction for ClientInstruction {
 }
 
 fn spawn_server(socket_path: &Path) -> io::Result<()> {
-    let status = Command::new(current_exe()?)
-        .arg("--server")
-        .arg(socket_path)
-        .status()?;
+    let mut command = Command::new(current_exe()?);
+    command.arg("--server").arg(socket_path);
+    // the server looks for the other sessions in the same socket directory
+    if let Some(socket_dir) = custom_socket_dir() {
+        command.env(ZELLIJ_SOCKET_DIR_ENV, socket_dir);
+    }
+    let status = command.status()?;
     if status.success() {
         Ok(())
     } else {
@@ -78,7 +108,9 @@ fn spawn_server(socket_path: &Path) -> io::Result<()> {
 
 #[derive(Debug, Clone)]
 pub enum ClientInfo {
-    Attach(String, bool, Options),
+    // the name of the session, whether to detach the other clients and whether to attach
+    // read-only
+    Attach(String, bool, bool, Options),
     New(String),
 }
 
@@ -105,6 +137,8 @@ pub fn start_client(
         .write(clear_client_terminal_attributes.as_bytes())
         .unwrap();
     std::env::set_var(&"ZELLIJ", "0");
+    // kept for the client that takes over when switching sessions
+    let config_paths = (opts.config.clone(), opts.config_dir.clone());
 
     let palette = config.themes.clone().map_or_else(
         || os_input.load_palette(),
@@ -115,17 +149,20 @@ pub fn start_client(
     );
 
     let full_screen_ws = os_input.get_terminal_size_using_fd(0);
+    let read_only = matches!(info, ClientInfo::Attach(_, _, true, _));
     let client_attributes = ClientAttributes {
         size: full_screen_ws,
         palette,
+        read_only,
     };
 
     let first_msg = match info {
-        ClientInfo::Attach(name, force, config_options) => {
+        ClientInfo::Attach(name, force, _, config_options) => {
             SESSION_NAME.set(name).unwrap();
+            let token = action_token::read(SESSION_NAME.get().unwrap(), &*ZELLIJ_IPC_PIPE);
             std::env::set_var(&"ZELLIJ_SESSION_NAME", SESSION_NAME.get().unwrap());
 
-            ClientToServerMsg::AttachClient(client_attributes, force, config_options)
+            ClientToServerMsg::AttachClient(client_attributes, force, config_options, token)
         }
         ClientInfo::New(name) => {
             SESSION_NAME.set(name).unwrap();
@@ -167,7 +204,12 @@ pub fn start_client(
     });
 
     let on_force_close = config_options.on_force_close.unwrap_or_default();
+    // sent along when attaching to another session without leaving the terminal
+    let attach_options = config_options.clone();
+    let clipboard_provider = ClipboardProvider::from_options(&config_options);
+    let link_opener = config_options.link_opener.clone();
 
+    let (send_mode_updates, mode_updates) = channels::unbounded();
     let _stdin_thread = thread::Builder::new()
         .name("stdin_handler".to_string())
         .spawn({
@@ -183,6 +225,7 @@ pub fn start_client(
                     command_is_executing,
                     send_client_instructions,
                     default_mode,
+                    mode_updates,
                 )
             }
         });
@@ -212,24 +255,10 @@ pub fn start_client(
         })
         .unwrap();
 
-    let router_thread = thread::Builder::new()
-        .name("router".to_string())
-        .spawn({
-            let os_input = os_input.clone();
-            let mut should_break = false;
-            move || loop {
-                let (instruction, err_ctx) = os_input.recv_from_server();
-                err_ctx.update_thread_ctx();
-                if let ServerToClientMsg::Exit(_) = instruction {
-                    should_break = true;
-                }
-                send_client_instructions.send(instruction.into()).unwrap();
-                if should_break {
-                    break;
-                }
-            }
-        })
-        .unwrap();
+    let mut router_thread = spawn_router(os_input.clone(), send_client_instructions.clone());
+    // dropping `stop_heartbeat` ends the thread, so that no ping is sent after we
+    // said goodbye to the server
+    let (mut stop_heartbeat, mut heartbeat_thread) = spawn_heartbeat(os_input.clone());
 
     let handle_error = |backtrace: String| {
         os_input.unset_raw_mode(0);
@@ -247,7 +276,37 @@ pub fn start_client(
         std::process::exit(1);
     };
 
+    let copy_to_clipboard = |text: &str| {
+        let mut stdout = os_input.get_stdout_writer();
+        if let Err(e) = clipboard_provider.set_content(text, &mut *stdout) {
+            log::error!("Failed to copy to the clipboard: {}", e);
+        }
+    };
+
+    // shown over the last line until the next render covers it up
+    let show_diagnostic = |message: &str| {
+        let size = os_input.get_terminal_size_using_fd(0);
+        let message: String = format!(" {} ", message).chars().take(size.cols).collect();
+        let diagnostic = format!(
+            "\u{1b}7\u{1b}[{};1H\u{1b}[7m{}\u{1b}[m\u{1b}[K\u{1b}8",
+            size.rows, message
+        );
+        let mut stdout = os_input.get_stdout_writer();
+        stdout
+            .write_all(diagnostic.as_bytes())
+            .expect("cannot write to stdout");
+        stdout.flush().expect("could not flush");
+    };
+
     let exit_msg: String;
+    let mut switch_to_session = None;
+    // whether the session being switched to is attached to forcibly
+    let mut force_switch = false;
+    // only the last copy made while the terminal is unfocused matters once it gets the focus back
+    let mut terminal_focused = true;
+    let mut held_back_copy = None;
+    // shown once the session switched to has rendered
+    let mut pending_diagnostic = None;
 
     loop {
         let (client_instruction, mut err_ctx) = receive_client_instructions
@@ -257,10 +316,60 @@ pub fn start_client(
         err_ctx.add_call(ContextType::Client((&client_instruction).into()));
         match client_instruction {
             ClientInstruction::Exit(reason) => {
+                drop(stop_heartbeat);
+                heartbeat_thread.join().unwrap();
                 os_input.send_to_server(ClientToServerMsg::ClientExited);
-
-                if let ExitReason::Error(_) = reason {
-                    handle_error(format!("{}", reason));
+                // the router stops once the server says goodbye
+                router_thread.join().unwrap();
+
+                match &reason {
+                    ExitReason::Error(_) => handle_error(format!("{}", reason)),
+                    ExitReason::SwitchSession(name) => {
+                        let left_session = std::env::var("ZELLIJ_SESSION_NAME").ok();
+                        // attaches over a new connection, staying in raw mode and on the
+                        // alternate screen the other session renders to
+                        let session = if ZELLIJ_SOCK_DIR.join(name).exists() {
+                            connect_to_switched_session(
+                                &*os_input,
+                                &ZELLIJ_SOCK_DIR,
+                                name,
+                                left_session.as_deref(),
+                            )
+                        } else {
+                            None
+                        };
+                        if let Some(session) = session {
+                            if &session != name {
+                                pending_diagnostic =
+                                    Some(format!("Session {:?} is not responding", name));
+                                force_switch = false;
+                            }
+                            let socket_path = ZELLIJ_SOCK_DIR.join(&session);
+                            std::env::set_var(&"ZELLIJ_SESSION_NAME", session.as_str());
+                            let client_attributes = ClientAttributes {
+                                size: os_input.get_terminal_size_using_fd(0),
+                                palette,
+                                read_only,
+                            };
+                            os_input.send_to_server(ClientToServerMsg::AttachClient(
+                                client_attributes,
+                                force_switch,
+                                attach_options.clone(),
+                                action_token::read(&session, &socket_path),
+                            ));
+                            router_thread =
+                                spawn_router(os_input.clone(), send_client_instructions.clone());
+                            let heartbeat = spawn_heartbeat(os_input.clone());
+                            stop_heartbeat = heartbeat.0;
+                            heartbeat_thread = heartbeat.1;
+                            // the input thread may be waiting on the session that was left
+                            command_is_executing.unblock_input_thread();
+                            continue;
+                        }
+                        // a session that exited is started again by attaching to it
+                        switch_to_session = Some((name.clone(), force_switch));
+                    }
+                    _ => {}
                 }
                 exit_msg = format!("{}", reason);
                 break;
@@ -275,23 +384,71 @@ pub fn start_client(
                     .write_all(output.as_bytes())
                     .expect("cannot write to stdout");
                 stdout.flush().expect("could not flush");
+                if let Some(message) = pending_diagnostic.take() {
+                    show_diagnostic(&message);
+                }
             }
             ClientInstruction::UnblockInputThread => {
                 command_is_executing.unblock_input_thread();
             }
+            ClientInstruction::Diagnostic(message) => {
+                show_diagnostic(&message);
+            }
+            ClientInstruction::CopyToClipboard(text) => {
+                if terminal_focused {
+                    copy_to_clipboard(&text);
+                } else {
+                    held_back_copy = Some(text);
+                }
+            }
+            ClientInstruction::TerminalFocus(focused) => {
+                terminal_focused = focused;
+                os_input.send_to_server(ClientToServerMsg::TerminalFocus(focused));
+                if focused {
+                    if let Some(text) = held_back_copy.take() {
+                        copy_to_clipboard(&text);
+                    }
+                }
+            }
+            ClientInstruction::OpenLink(link) => {
+                if let Err(e) = links::open_link(link_opener.as_deref(), &link) {
+                    log::error!("Failed to open {:?}: {}", link, e);
+                }
+            }
+            ClientInstruction::SwitchToMode(mode) => {
+                let _ = send_mode_updates.send(mode);
+            }
+            ClientInstruction::SwitchSession(name, force) => {
+                let current = std::env::var("ZELLIJ_SESSION_NAME").ok();
+                let exists =
+                    ZELLIJ_SOCK_DIR.join(&name).exists() || resurrection_path(&name).exists();
+                if current.as_ref() == Some(&name) {
+                    // already there
+                } else if exists {
+                    // the session detaches this client, which then attaches to the other one
+                    force_switch = force;
+                    os_input.send_to_server(ClientToServerMsg::SwitchSession(name));
+                } else {
+                    show_diagnostic(&format!("Session {:?} not found", name));
+                }
+            }
         }
     }
 
-    router_thread.join().unwrap();
-
     // cleanup();
     let reset_style = "\u{1b}[m";
     let show_cursor = "\u{1b}[?25h";
+    let disable_focus_reporting = "\u{1b}[?1004l";
     let restore_snapshot = "\u{1b}[?1049l";
     let goto_start_of_last_line = format!("\u{1b}[{};{}H", full_screen_ws.rows, 1);
     let goodbye_message = format!(
-        "{}\n{}{}{}{}\n",
-        goto_start_of_last_line, restore_snapshot, reset_style, show_cursor, exit_msg
+        "{}\n{}{}{}{}{}\n",
+        goto_start_of_last_line,
+        restore_snapshot,
+        reset_style,
+        show_cursor,
+        disable_focus_reporting,
+        exit_msg
     );
 
     os_input.disable_mouse();
@@ -300,4 +457,97 @@ pub fn start_client(
     let mut stdout = os_input.get_stdout_writer();
     let _ = stdout.write(goodbye_message.as_bytes()).unwrap();
     stdout.flush().unwrap();
+
+    if let Some((session_name, force)) = switch_to_session {
+        attach_in_place(&session_name, force, read_only, config_paths);
+    }
+}
+
+/// Forwards the messages of the server the client is connected to, until it says goodbye.
+fn spawn_router(
+    os_input: Box<dyn ClientOsApi>,
+    send_client_instructions: SenderWithContext<ClientInstruction>,
+) -> JoinHandle<()> {
+    thread::Builder::new()
+        .name("router".to_string())
+        .spawn(move || loop {
+            let (instruction, err_ctx) = os_input.recv_from_server();
+            err_ctx.update_thread_ctx();
+            let should_break = matches!(instruction, ServerToClientMsg::Exit(_));
+            send_client_instructions.send(instruction.into()).unwrap();
+            if should_break {
+                break;
+            }
+        })
+        .unwrap()
+}
+
+/// Pings the server the client is connected to until the returned sender is dropped.
+fn spawn_heartbeat(os_input: Box<dyn ClientOsApi>) -> (Sender<()>, JoinHandle<()>) {
+    let (stop_heartbeat, heartbeat_stopped) = channels::bounded::<()>(0);
+    let heartbeat_thread = thread::Builder::new()
+        .name("heartbeat".to_string())
+        .spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) =
+                heartbeat_stopped.recv_timeout(HEARTBEAT_INTERVAL)
+            {
+                os_input.send_to_server(ClientToServerMsg::Ping);
+            }
+        })
+        .unwrap();
+    (stop_heartbeat, heartbeat_thread)
+}
+
+/// Connects to the session `name` this client is switching to, or back to the session `left` it
+/// came from if the server of `name` doesn't answer (a crashed server leaves its socket behind).
+/// Returns the session connected to, if any.
+fn connect_to_switched_session(
+    os_input: &dyn ClientOsApi,
+    socket_dir: &Path,
+    name: &str,
+    left: Option<&str>,
+) -> Option<String> {
+    if os_input.try_connect_to_server(&socket_dir.join(name)) {
+        return Some(name.to_string());
+    }
+    log::error!("Session {:?} is not responding", name);
+    left.filter(|left| os_input.try_connect_to_server(&socket_dir.join(left)))
+        .map(|left| left.to_string())
+}
+
+/// Replaces this client with one attached to the session `session_name`, in the same terminal.
+fn attach_in_place(
+    session_name: &str,
+    force: bool,
+    read_only: bool,
+    (config, config_dir): (Option<PathBuf>, Option<PathBuf>),
+) {
+    let exe = match current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!("Failed to switch to session {:?}: {}", session_name, e);
+            return;
+        }
+    };
+    let mut command = Command::new(exe);
+    command.arg("attach").arg(session_name);
+    if force {
+        command.arg("--force");
+    }
+    if read_only {
+        command.arg("--read-only");
+    }
+    if let Some(config) = config {
+        command.env(ZELLIJ_CONFIG_FILE_ENV, config);
+    }
+    if let Some(config_dir) = config_dir {
+        command.env(ZELLIJ_CONFIG_DIR_ENV, config_dir);
+    }
+    // only returns if the client could not be started
+    let e = command.exec();
+    eprintln!("Failed to switch to session {:?}: {}", session_name, e);
 }
+
+#[cfg(test)]
+#[path = "./unit/switch_session_tests.rs"]
+mod switch_session_tests;
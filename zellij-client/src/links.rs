@@ -0,0 +1,50 @@
+//! Opening the targets of the hyperlinks (OSC 8) clicked in panes on the machine the client runs
+//! on.
+use std::io;
+use std::process::{Command, Stdio};
+use std::thread;
+
+#[cfg(target_os = "macos")]
+const DEFAULT_LINK_OPENER: &str = "open";
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_LINK_OPENER: &str = "xdg-open";
+
+/// Opens `link` with `link_opener` (the `link_opener` option, which can come with arguments), or
+/// the opener of the desktop without one. The link is passed as an argument of its own, never
+/// through a shell, and links the opener would take for one of its options are refused.
+pub(crate) fn open_link(link_opener: Option<&str>, link: &str) -> io::Result<()> {
+    if link.starts_with('-') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the link looks like an option",
+        ));
+    }
+    let mut args = opener_args(link_opener.unwrap_or(DEFAULT_LINK_OPENER), link);
+    let program = match args.first() {
+        Some(_) => args.remove(0),
+        None => return Ok(()),
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // openers like xdg-open can take a while to hand the link over, don't hold up the client
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+fn opener_args(link_opener: &str, link: &str) -> Vec<String> {
+    let mut args: Vec<String> = link_opener.split_whitespace().map(String::from).collect();
+    if !args.is_empty() {
+        args.push(link.to_string());
+    }
+    args
+}
+
+#[cfg(test)]
+#[path = "./unit/links_tests.rs"]
+mod links_tests;
@@ -60,6 +60,9 @@ pub(crate) fn get_terminal_size_using_fd(fd: RawFd) -> Size {
     }
 }
 
+/// How many times [`ClientOsApi::try_connect_to_server`] tries to connect, 50ms apart
+const CONNECT_ATTEMPTS: usize = 20;
+
 #[derive(Clone)]
 pub struct ClientOsInputOutput {
     orig_termios: Arc<Mutex<termios::Termios>>,
@@ -93,8 +96,13 @@ pub trait ClientOsApi: Send + Sync {
     fn handle_signals(&self, sigwinch_cb: Box<dyn Fn()>, quit_cb: Box<dyn Fn()>);
     /// Establish a connection with the server socket.
     fn connect_to_server(&self, path: &Path);
+    /// Like [`Self::connect_to_server`], but gives up after a few attempts, returning whether
+    /// it connected (the socket of a server that is gone can be left behind).
+    fn try_connect_to_server(&self, path: &Path) -> bool;
     fn load_palette(&self) -> Palette;
     fn enable_mouse(&self);
+    /// Also report the mouse moving while no button is held (needs [`Self::enable_mouse`]).
+    fn enable_mouse_motion(&self);
     fn disable_mouse(&self);
     // Repeatedly send action, until stdin is readable again
     fn start_action_repeater(&mut self, action: Action);
@@ -159,22 +167,24 @@ impl ClientOsApi for ClientOsInputOutput {
         }
     }
     fn connect_to_server(&self, path: &Path) {
-        let socket;
-        loop {
+        while !self.try_connect_to_server(path) {}
+    }
+    fn try_connect_to_server(&self, path: &Path) -> bool {
+        for _ in 0..CONNECT_ATTEMPTS {
             match LocalSocketStream::connect(path) {
-                Ok(sock) => {
-                    socket = sock;
-                    break;
+                Ok(socket) => {
+                    let sender = IpcSenderWithContext::new(socket);
+                    let receiver = sender.get_receiver();
+                    *self.send_instructions_to_server.lock().unwrap() = Some(sender);
+                    *self.receive_instructions_from_server.lock().unwrap() = Some(receiver);
+                    return true;
                 }
                 Err(_) => {
                     std::thread::sleep(std::time::Duration::from_millis(50));
                 }
             }
         }
-        let sender = IpcSenderWithContext::new(socket);
-        let receiver = sender.get_receiver();
-        *self.send_instructions_to_server.lock().unwrap() = Some(sender);
-        *self.receive_instructions_from_server.lock().unwrap() = Some(receiver);
+        false
     }
     fn load_palette(&self) -> Palette {
         // this was removed because termbg doesn't release stdin in certain scenarios (we know of
@@ -197,9 +207,23 @@ impl ClientOsApi for ClientOsInputOutput {
         }
     }
 
+    fn enable_mouse_motion(&self) {
+        let enable_any_event_tracking = "\u{1b}[?1003h";
+        let mut stdout = std::io::stdout();
+        let _ = stdout
+            .write_all(enable_any_event_tracking.as_bytes())
+            .and_then(|_| stdout.flush());
+    }
+
     fn disable_mouse(&self) {
         let mut mouse_term = self.mouse_term.lock().unwrap();
         if mouse_term.is_some() {
+            // termion's MouseTerminal does not know about any-event tracking
+            let disable_any_event_tracking = "\u{1b}[?1003l";
+            let mut stdout = std::io::stdout();
+            let _ = stdout
+                .write_all(disable_any_event_tracking.as_bytes())
+                .and_then(|_| stdout.flush());
             *mouse_term = None;
         }
     }
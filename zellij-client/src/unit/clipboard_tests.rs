@@ -0,0 +1,49 @@
+use super::*;
+
+#[test]
+fn copies_to_the_clipboard_of_the_terminal_by_default() {
+    let provider = ClipboardProvider::from_options(&Options::default());
+    assert_eq!(provider, ClipboardProvider::Terminal(Clipboard::System));
+    let mut stdout = vec![];
+    provider.set_content("hello", &mut stdout).unwrap();
+    assert_eq!(
+        String::from_utf8(stdout).unwrap(),
+        "\u{1b}]52;c;aGVsbG8=\u{1b}\\"
+    );
+}
+
+#[test]
+fn copies_to_the_primary_selection_or_a_command_when_configured() {
+    let options = Options {
+        copy_clipboard: Some(Clipboard::Primary),
+        ..Default::default()
+    };
+    let mut stdout = vec![];
+    ClipboardProvider::from_options(&options)
+        .set_content("hello", &mut stdout)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(stdout).unwrap(),
+        "\u{1b}]52;p;aGVsbG8=\u{1b}\\"
+    );
+
+    let options = Options {
+        copy_command: Some("wl-copy".into()),
+        ..options
+    };
+    assert_eq!(
+        ClipboardProvider::from_options(&options),
+        ClipboardProvider::Command("wl-copy".into()),
+        "The command takes precedence over the terminal"
+    );
+}
+
+#[test]
+fn clipboard_report_holds_the_base64_content() {
+    assert_eq!(
+        decode_clipboard_report(b"c;aGVsbG8="),
+        Some("hello".as_bytes().to_vec())
+    );
+    assert_eq!(decode_clipboard_report(b"c;"), Some(vec![]));
+    assert_eq!(decode_clipboard_report(b"c"), None);
+}
@@ -3,7 +3,8 @@ use zellij_utils::input::actions::{Action, Direction};
 use zellij_utils::input::config::Config;
 use zellij_utils::input::options::Options;
 use zellij_utils::pane_size::Size;
-use zellij_utils::zellij_tile::data::Palette;
+use zellij_utils::position::Position;
+use zellij_utils::zellij_tile::data::{MouseModifiers, Palette};
 
 use crate::{os_input_output::ClientOsApi, ClientInstruction, CommandIsExecuting};
 
@@ -63,6 +64,10 @@ pub mod commands {
 
     pub const BRACKETED_PASTE_START: [u8; 6] = [27, 91, 50, 48, 48, 126]; // \u{1b}[200~
     pub const BRACKETED_PASTE_END: [u8; 6] = [27, 91, 50, 48, 49, 126]; // \u{1b}[201
+    pub const LEFT_CLICK: [u8; 10] = [27, 91, 60, 48, 59, 49, 48, 59, 53, 77]; // \u{1b}[<0;10;5M
+    pub const SHIFT_LEFT_CLICK: [u8; 10] = [27, 91, 60, 52, 59, 49, 48, 59, 53, 77]; // \u{1b}[<4;10;5M
+    pub const WHEEL_UP: [u8; 11] = [27, 91, 60, 54, 52, 59, 49, 48, 59, 53, 77]; // \u{1b}[<64;10;5M
+    pub const WHEEL_DOWN: [u8; 11] = [27, 91, 60, 54, 53, 59, 49, 48, 59, 53, 77]; // \u{1b}[<65;10;5M
     pub const SLEEP: [u8; 0] = [];
 }
 
@@ -103,7 +108,7 @@ impl ClientOsApi for FakeClientOsApi {
         unimplemented!()
     }
     fn get_stdout_writer(&self) -> Box<dyn io::Write> {
-        unimplemented!()
+        Box::new(io::sink())
     }
     fn read_from_stdin(&self) -> Vec<u8> {
         let mut stdin_events = self.stdin_events.lock().unwrap();
@@ -134,10 +139,14 @@ impl ClientOsApi for FakeClientOsApi {
     fn connect_to_server(&self, _path: &Path) {
         unimplemented!()
     }
+    fn try_connect_to_server(&self, _path: &Path) -> bool {
+        unimplemented!()
+    }
     fn load_palette(&self) -> Palette {
         unimplemented!()
     }
     fn enable_mouse(&self) {}
+    fn enable_mouse_motion(&self) {}
     fn disable_mouse(&self) {}
     fn start_action_repeater(&mut self, _action: Action) {}
 }
@@ -180,6 +189,7 @@ pub fn quit_breaks_input_loop() {
         command_is_executing,
         send_client_instructions,
         default_mode,
+        channels::unbounded().1,
     );
     let expected_actions_sent_to_server = vec![Action::Quit];
     let received_actions = extract_actions_sent_to_server(events_sent_to_server);
@@ -215,6 +225,7 @@ pub fn move_focus_left_in_pane_mode() {
         command_is_executing,
         send_client_instructions,
         default_mode,
+        channels::unbounded().1,
     );
     let expected_actions_sent_to_server =
         vec![Action::MoveFocusOrTab(Direction::Left), Action::Quit];
@@ -225,6 +236,45 @@ pub fn move_focus_left_in_pane_mode() {
     );
 }
 
+#[test]
+pub fn keys_are_handled_in_the_mode_the_session_switches_the_client_to() {
+    let stdin_events = vec![b"h".to_vec()];
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api = Box::new(FakeClientOsApi::new(
+        stdin_events,
+        events_sent_to_server.clone(),
+        command_is_executing.clone(),
+    ));
+    let config = Config::from_default_assets().unwrap();
+    let options = Options::default();
+
+    let (send_client_instructions, _receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    // the other clients of the session are in pane mode
+    let (send_mode_updates, mode_updates) = channels::unbounded();
+    send_mode_updates.send(InputMode::Pane).unwrap();
+    let default_mode = InputMode::Normal;
+    input_loop(
+        client_os_api,
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        default_mode,
+        mode_updates,
+    );
+    let expected_actions_sent_to_server = vec![Action::MoveFocus(Direction::Left), Action::Quit];
+    let received_actions = extract_actions_sent_to_server(events_sent_to_server);
+    assert_eq!(
+        expected_actions_sent_to_server, received_actions,
+        "All actions sent to server properly"
+    );
+}
+
 #[test]
 pub fn bracketed_paste() {
     let stdin_events = vec![
@@ -255,11 +305,415 @@ pub fn bracketed_paste() {
         command_is_executing,
         send_client_instructions,
         default_mode,
+        channels::unbounded().1,
+    );
+    let expected_actions_sent_to_server = vec![
+        Action::Paste(commands::MOVE_FOCUS_LEFT_IN_NORMAL_MODE.to_vec()), // keys were sent to server as pasted text and not interpreted
+        Action::Quit,
+    ];
+    let received_actions = extract_actions_sent_to_server(events_sent_to_server);
+    assert_eq!(
+        expected_actions_sent_to_server, received_actions,
+        "All actions sent to server properly"
+    );
+}
+
+#[test]
+pub fn shift_click_is_distinguished_from_click() {
+    let stdin_events = vec![
+        commands::LEFT_CLICK.to_vec(),
+        commands::SHIFT_LEFT_CLICK.to_vec(),
+    ];
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api = Box::new(FakeClientOsApi::new(
+        stdin_events,
+        events_sent_to_server.clone(),
+        command_is_executing.clone(),
+    ));
+    let config = Config::from_default_assets().unwrap();
+    let options = Options::default();
+
+    let (send_client_instructions, _receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    let default_mode = InputMode::Normal;
+    input_loop(
+        client_os_api,
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        default_mode,
+        channels::unbounded().1,
+    );
+    let expected_actions_sent_to_server = vec![
+        Action::LeftClick(Position::new(4, 9), MouseModifiers::default()),
+        Action::LeftClick(
+            Position::new(4, 9),
+            MouseModifiers {
+                shift: true,
+                ..Default::default()
+            },
+        ),
+        Action::Quit,
+    ];
+    let received_actions = extract_actions_sent_to_server(events_sent_to_server);
+    assert_eq!(
+        expected_actions_sent_to_server, received_actions,
+        "All actions sent to server properly"
+    );
+}
+
+#[test]
+pub fn reverse_scroll_swaps_wheel_directions() {
+    let stdin_events = vec![commands::WHEEL_UP.to_vec(), commands::WHEEL_DOWN.to_vec()];
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api = Box::new(FakeClientOsApi::new(
+        stdin_events,
+        events_sent_to_server.clone(),
+        command_is_executing.clone(),
+    ));
+    let config = Config::from_default_assets().unwrap();
+    let options = Options {
+        reverse_scroll: true,
+        ..Default::default()
+    };
+
+    let (send_client_instructions, _receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    let default_mode = InputMode::Normal;
+    input_loop(
+        client_os_api,
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        default_mode,
+        channels::unbounded().1,
+    );
+    let expected_actions_sent_to_server = vec![
+        Action::ScrollDownAt(Position::new(4, 9)),
+        Action::ScrollUpAt(Position::new(4, 9)),
+        Action::Quit,
+    ];
+    let received_actions = extract_actions_sent_to_server(events_sent_to_server);
+    assert_eq!(
+        expected_actions_sent_to_server, received_actions,
+        "All actions sent to server properly"
+    );
+}
+
+#[test]
+pub fn key_right_after_mode_switch_is_handled_in_new_mode() {
+    let mut mode_switch_and_key = commands::PANE_MODE.to_vec();
+    mode_switch_and_key.extend_from_slice(&commands::SPAWN_TERMINAL_IN_PANE_MODE);
+    let stdin_events = vec![mode_switch_and_key];
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api = Box::new(FakeClientOsApi::new(
+        stdin_events,
+        events_sent_to_server.clone(),
+        command_is_executing.clone(),
+    ));
+    let config = Config::from_default_assets().unwrap();
+    let options = Options::default();
+
+    let (send_client_instructions, _receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    let default_mode = InputMode::Normal;
+    input_loop(
+        client_os_api,
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        default_mode,
+        channels::unbounded().1,
+    );
+    let expected_actions_sent_to_server = vec![
+        Action::SwitchToMode(InputMode::Pane),
+        Action::NewPane(None),
+        Action::Quit,
+    ];
+    let received_actions = extract_actions_sent_to_server(events_sent_to_server);
+    assert_eq!(
+        expected_actions_sent_to_server, received_actions,
+        "All actions sent to server properly"
+    );
+}
+
+#[test]
+pub fn focus_follows_mouse_sends_only_the_last_motion_of_a_read() {
+    let mouse_motions = "\u{1b}[<35;1;1M\u{1b}[<35;5;2M\u{1b}[<35;10;5M"
+        .as_bytes()
+        .to_vec();
+    let stdin_events = vec![mouse_motions];
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api = Box::new(FakeClientOsApi::new(
+        stdin_events,
+        events_sent_to_server.clone(),
+        command_is_executing.clone(),
+    ));
+    let config = Config::from_default_assets().unwrap();
+    let options = Options {
+        focus_follows_mouse: true,
+        ..Default::default()
+    };
+
+    let (send_client_instructions, _receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    let default_mode = InputMode::Normal;
+    input_loop(
+        client_os_api,
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        default_mode,
+        channels::unbounded().1,
+    );
+    let expected_actions_sent_to_server =
+        vec![Action::MouseMotion(Position::new(4, 9)), Action::Quit];
+    let received_actions = extract_actions_sent_to_server(events_sent_to_server);
+    assert_eq!(
+        expected_actions_sent_to_server, received_actions,
+        "All actions sent to server properly"
+    );
+}
+
+#[test]
+pub fn characters_split_between_reads_are_written_whole() {
+    let emoji = "🦀".as_bytes().to_vec();
+    let stdin_events = vec![
+        vec![b'a', emoji[0]],
+        vec![emoji[1], emoji[2]],
+        vec![emoji[3], "é".as_bytes()[0]],
+        vec!["é".as_bytes()[1]],
+    ];
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api = Box::new(FakeClientOsApi::new(
+        stdin_events,
+        events_sent_to_server.clone(),
+        command_is_executing.clone(),
+    ));
+    let config = Config::from_default_assets().unwrap();
+    let options = Options::default();
+
+    let (send_client_instructions, _receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    let default_mode = InputMode::Normal;
+    input_loop(
+        client_os_api,
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        default_mode,
+        channels::unbounded().1,
+    );
+    let expected_actions_sent_to_server = vec![
+        Action::Write(vec![b'a']),
+        Action::Write(emoji),
+        Action::Write("é".as_bytes().to_vec()),
+        Action::Quit,
+    ];
+    let received_actions = extract_actions_sent_to_server(events_sent_to_server);
+    assert_eq!(
+        expected_actions_sent_to_server, received_actions,
+        "All actions sent to server properly"
+    );
+}
+
+#[test]
+pub fn escape_sequences_split_between_reads_are_parsed_whole() {
+    let stdin_events = vec![
+        "\u{1b}[<35;5".as_bytes().to_vec(),
+        ";2M".as_bytes().to_vec(),
+    ];
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api = Box::new(FakeClientOsApi::new(
+        stdin_events,
+        events_sent_to_server.clone(),
+        command_is_executing.clone(),
+    ));
+    let config = Config::from_default_assets().unwrap();
+    let options = Options {
+        focus_follows_mouse: true,
+        ..Default::default()
+    };
+
+    let (send_client_instructions, _receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    let default_mode = InputMode::Normal;
+    input_loop(
+        client_os_api,
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        default_mode,
+        channels::unbounded().1,
+    );
+    let expected_actions_sent_to_server =
+        vec![Action::MouseMotion(Position::new(1, 4)), Action::Quit];
+    let received_actions = extract_actions_sent_to_server(events_sent_to_server);
+    assert_eq!(
+        expected_actions_sent_to_server, received_actions,
+        "All actions sent to server properly"
+    );
+}
+
+#[test]
+pub fn unknown_escape_sequences_are_passed_through() {
+    // termion doesn't know the first one, and would choke on the number in the second
+    let stdin_events = vec!["\u{1b}[99~\u{1b}[300~a".as_bytes().to_vec()];
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api = Box::new(FakeClientOsApi::new(
+        stdin_events,
+        events_sent_to_server.clone(),
+        command_is_executing.clone(),
+    ));
+    let config = Config::from_default_assets().unwrap();
+    let options = Options::default();
+
+    let (send_client_instructions, _receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    let default_mode = InputMode::Normal;
+    input_loop(
+        client_os_api,
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        default_mode,
+        channels::unbounded().1,
+    );
+    let expected_actions_sent_to_server = vec![
+        Action::Write("\u{1b}[99~".as_bytes().to_vec()),
+        Action::Write("\u{1b}[300~".as_bytes().to_vec()),
+        Action::Write(vec![b'a']),
+        Action::Quit,
+    ];
+    let received_actions = extract_actions_sent_to_server(events_sent_to_server);
+    assert_eq!(
+        expected_actions_sent_to_server, received_actions,
+        "All actions sent to server properly"
+    );
+}
+
+#[test]
+pub fn terminal_focus_changes_are_not_passed_to_the_pane() {
+    let stdin_events = vec!["\u{1b}[Oa\u{1b}[I".as_bytes().to_vec()];
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api = Box::new(FakeClientOsApi::new(
+        stdin_events,
+        events_sent_to_server.clone(),
+        command_is_executing.clone(),
+    ));
+    let config = Config::from_default_assets().unwrap();
+    let options = Options {
+        pause_rendering_when_unfocused: true,
+        ..Default::default()
+    };
+
+    let (send_client_instructions, receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    let default_mode = InputMode::Normal;
+    input_loop(
+        client_os_api,
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        default_mode,
+        channels::unbounded().1,
+    );
+    let expected_actions_sent_to_server = vec![Action::Write(vec![b'a']), Action::Quit];
+    let received_actions = extract_actions_sent_to_server(events_sent_to_server);
+    assert_eq!(
+        expected_actions_sent_to_server, received_actions,
+        "All actions sent to server properly"
+    );
+    let focus_changes: Vec<bool> = receive_client_instructions
+        .try_iter()
+        .filter_map(|(instruction, _)| match instruction {
+            ClientInstruction::TerminalFocus(focused) => Some(focused),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        focus_changes,
+        vec![false, true],
+        "The focus changes go to the client"
+    );
+}
+
+#[test]
+pub fn paste_from_clipboard_pastes_the_answer_of_the_terminal() {
+    let stdin_events = vec![
+        commands::SCROLL_MODE.to_vec(),
+        vec![b'p'],
+        "\u{1b}]52;c;aGVsbG8=\u{07}".as_bytes().to_vec(),
+    ];
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api = Box::new(FakeClientOsApi::new(
+        stdin_events,
+        events_sent_to_server.clone(),
+        command_is_executing.clone(),
+    ));
+    let config = Config::from_default_assets().unwrap();
+    let options = Options::default();
+
+    let (send_client_instructions, _receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    let default_mode = InputMode::Normal;
+    input_loop(
+        client_os_api,
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        default_mode,
+        channels::unbounded().1,
     );
     let expected_actions_sent_to_server = vec![
-        Action::Write(commands::BRACKETED_PASTE_START.to_vec()),
-        Action::Write(commands::MOVE_FOCUS_LEFT_IN_NORMAL_MODE.to_vec()), // keys were directly written to server and not interpreted
-        Action::Write(commands::BRACKETED_PASTE_END.to_vec()),
+        Action::SwitchToMode(InputMode::Scroll),
+        Action::SwitchToMode(InputMode::Normal),
+        Action::Paste("hello".as_bytes().to_vec()),
         Action::Quit,
     ];
     let received_actions = extract_actions_sent_to_server(events_sent_to_server);
@@ -0,0 +1,16 @@
+use super::*;
+
+#[test]
+fn passes_the_link_as_the_last_argument_of_its_own() {
+    assert_eq!(
+        opener_args("firefox --new-tab", "http://example.com/a b;rm -rf ~"),
+        vec!["firefox", "--new-tab", "http://example.com/a b;rm -rf ~"]
+    );
+    assert!(opener_args("  ", "http://example.com").is_empty());
+}
+
+#[test]
+fn refuses_links_that_look_like_options() {
+    let error = open_link(Some("true"), "--version").unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,113 @@
+use super::connect_to_switched_session;
+use crate::os_input_output::ClientOsApi;
+
+use std::io;
+use std::os::unix::io::RawFd;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use zellij_utils::{
+    errors::ErrorContext,
+    input::actions::Action,
+    ipc::{ClientToServerMsg, ServerToClientMsg},
+    pane_size::Size,
+    zellij_tile::data::Palette,
+};
+
+/// Connects only to the sockets it is given, and records every attempt.
+#[derive(Clone)]
+struct FakeClientOsApi {
+    answering_sockets: Vec<PathBuf>,
+    connection_attempts: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+impl FakeClientOsApi {
+    fn new(answering_sockets: Vec<PathBuf>) -> Self {
+        FakeClientOsApi {
+            answering_sockets,
+            connection_attempts: Arc::new(Mutex::new(vec![])),
+        }
+    }
+}
+
+impl ClientOsApi for FakeClientOsApi {
+    fn get_terminal_size_using_fd(&self, _fd: RawFd) -> Size {
+        unimplemented!()
+    }
+    fn set_raw_mode(&mut self, _fd: RawFd) {
+        unimplemented!()
+    }
+    fn unset_raw_mode(&self, _fd: RawFd) {
+        unimplemented!()
+    }
+    fn get_stdout_writer(&self) -> Box<dyn io::Write> {
+        Box::new(io::sink())
+    }
+    fn read_from_stdin(&self) -> Vec<u8> {
+        unimplemented!()
+    }
+    fn box_clone(&self) -> Box<dyn ClientOsApi> {
+        Box::new(self.clone())
+    }
+    fn send_to_server(&self, _msg: ClientToServerMsg) {
+        unimplemented!()
+    }
+    fn recv_from_server(&self) -> (ServerToClientMsg, ErrorContext) {
+        unimplemented!()
+    }
+    fn handle_signals(&self, _sigwinch_cb: Box<dyn Fn()>, _quit_cb: Box<dyn Fn()>) {
+        unimplemented!()
+    }
+    fn connect_to_server(&self, _path: &Path) {
+        panic!("a switching client must not wait for a server forever");
+    }
+    fn try_connect_to_server(&self, path: &Path) -> bool {
+        self.connection_attempts
+            .lock()
+            .unwrap()
+            .push(path.to_path_buf());
+        self.answering_sockets.iter().any(|socket| socket == path)
+    }
+    fn load_palette(&self) -> Palette {
+        unimplemented!()
+    }
+    fn enable_mouse(&self) {}
+    fn enable_mouse_motion(&self) {}
+    fn disable_mouse(&self) {}
+    fn start_action_repeater(&mut self, _action: Action) {}
+}
+
+#[test]
+fn switching_connects_to_the_other_session() {
+    let socket_dir = Path::new("/sockets");
+    let os_api = FakeClientOsApi::new(vec![socket_dir.join("other")]);
+    let session = connect_to_switched_session(&os_api, socket_dir, "other", Some("first"));
+    assert_eq!(session.as_deref(), Some("other"));
+    assert_eq!(
+        *os_api.connection_attempts.lock().unwrap(),
+        vec![socket_dir.join("other")]
+    );
+}
+
+#[test]
+fn switching_to_a_session_that_does_not_answer_goes_back() {
+    let socket_dir = Path::new("/sockets");
+    let os_api = FakeClientOsApi::new(vec![socket_dir.join("first")]);
+    let session = connect_to_switched_session(&os_api, socket_dir, "stale", Some("first"));
+    assert_eq!(
+        session.as_deref(),
+        Some("first"),
+        "The client stays in the session it was in"
+    );
+}
+
+#[test]
+fn switching_gives_up_when_no_session_answers() {
+    let socket_dir = Path::new("/sockets");
+    let os_api = FakeClientOsApi::new(vec![]);
+    let session = connect_to_switched_session(&os_api, socket_dir, "stale", Some("first"));
+    assert_eq!(session, None);
+    assert_eq!(
+        *os_api.connection_attempts.lock().unwrap(),
+        vec![socket_dir.join("stale"), socket_dir.join("first")]
+    );
+}
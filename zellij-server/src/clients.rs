@@ -0,0 +1,218 @@
+//! The clients attached to the session, each with the size of its terminal, and how the session
+//! is sized and drawn for them when those sizes differ. Read-only clients (guests) watch the
+//! session and move their own focus around it, without changing it.
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::os_input_output::ClientId;
+use zellij_utils::{
+    input::{actions::Action, options::ClientSizing},
+    ipc::HEARTBEAT_INTERVAL,
+    pane_size::Size,
+    position::Position,
+};
+
+/// How long a client goes without pinging the server before it is taken for gone or hung.
+pub(crate) const CLIENT_TIMEOUT: Duration = Duration::from_secs(3 * HEARTBEAT_INTERVAL.as_secs());
+
+#[derive(Debug, Clone, Copy)]
+struct Client {
+    size: Size,
+    focused: bool,
+    read_only: bool,
+    heard_from_at: Instant,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Clients {
+    clients: BTreeMap<ClientId, Client>,
+    // the client that sent the latest action
+    active: Option<ClientId>,
+    pub sizing: ClientSizing,
+}
+
+impl Clients {
+    pub fn attach(&mut self, client_id: ClientId, size: Size) {
+        self.clients.insert(
+            client_id,
+            Client {
+                size,
+                focused: true,
+                read_only: false,
+                heard_from_at: Instant::now(),
+            },
+        );
+    }
+    /// Returns whether the client was still attached.
+    pub fn detach(&mut self, client_id: ClientId) -> bool {
+        self.clients.remove(&client_id).is_some()
+    }
+    pub fn resize(&mut self, client_id: ClientId, size: Size) {
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            client.size = size;
+        }
+    }
+    pub fn set_focus(&mut self, client_id: ClientId, focused: bool) {
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            client.focused = focused;
+        }
+    }
+    pub fn set_read_only(&mut self, client_id: ClientId, read_only: bool) {
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            client.read_only = read_only;
+        }
+    }
+    /// The client pinged the server, it is still there.
+    pub fn heard_from(&mut self, client_id: ClientId) {
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            client.heard_from_at = Instant::now();
+        }
+    }
+    /// The clients that didn't ping the server for `CLIENT_TIMEOUT` by `now`.
+    pub fn silent(&self, now: Instant) -> Vec<ClientId> {
+        self.clients
+            .iter()
+            .filter(|(_, client)| {
+                now.saturating_duration_since(client.heard_from_at) >= CLIENT_TIMEOUT
+            })
+            .map(|(&client_id, _)| client_id)
+            .collect()
+    }
+    /// Whether the client may do `action`, guests only move their focus between the panes and
+    /// tabs, or detach themselves.
+    pub fn allows(&self, client_id: ClientId, action: &Action) -> bool {
+        let read_only = self
+            .clients
+            .get(&client_id)
+            .map_or(false, |client| client.read_only);
+        !read_only || moves_focus_only(action)
+    }
+    /// Whether the terminal of any of the clients is focused.
+    pub fn focused(&self) -> bool {
+        self.clients.values().any(|client| client.focused)
+    }
+    /// The client that sent the latest action, the one to copy to the clipboard of and to open
+    /// links for, if it is still attached.
+    pub fn active(&self) -> Option<ClientId> {
+        self.active
+            .filter(|client_id| self.clients.contains_key(client_id))
+    }
+    pub fn ids(&self) -> Vec<ClientId> {
+        self.clients.keys().copied().collect()
+    }
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+    /// The size of the session, the fewest rows and the fewest columns of the clients, so that
+    /// all of it fits in each of them.
+    pub fn session_size(&self) -> Option<Size> {
+        let rows = self.clients.values().map(|client| client.size.rows).min()?;
+        let cols = self.clients.values().map(|client| client.size.cols).min()?;
+        Some(Size { rows, cols })
+    }
+    /// How many rows and columns into the terminal of the client the session is drawn.
+    fn offset(&self, client_id: ClientId) -> (usize, usize) {
+        match (
+            self.sizing,
+            self.clients.get(&client_id),
+            self.session_size(),
+        ) {
+            (ClientSizing::Letterbox, Some(client), Some(session_size)) => (
+                client.size.rows.saturating_sub(session_size.rows) / 2,
+                client.size.cols.saturating_sub(session_size.cols) / 2,
+            ),
+            _ => (0, 0),
+        }
+    }
+    /// The `output` of the screen, moved to where the session is drawn in the terminal of the
+    /// client.
+    pub fn render_for(&self, client_id: ClientId, output: &str) -> String {
+        match self.offset(client_id) {
+            (0, 0) => output.to_string(),
+            (rows, cols) => move_cursor_positions(output, rows, cols),
+        }
+    }
+    /// The `action` of the client, with the positions of the mouse in its terminal made relative
+    /// to the session drawn in it. The client becomes the active one.
+    pub fn action_in_session(&mut self, client_id: ClientId, action: Action) -> Action {
+        self.active = Some(client_id);
+        let (rows, cols) = self.offset(client_id);
+        if (rows, cols) == (0, 0) {
+            return action;
+        }
+        let relative = |point: Position| point.relative_to(rows, cols);
+        match action {
+            Action::ScrollUpAt(point) => Action::ScrollUpAt(relative(point)),
+            Action::ScrollDownAt(point) => Action::ScrollDownAt(relative(point)),
+            Action::LeftClick(point, modifiers) => Action::LeftClick(relative(point), modifiers),
+            Action::MiddleClick(point, modifiers) => {
+                Action::MiddleClick(relative(point), modifiers)
+            }
+            Action::MouseRelease(point) => Action::MouseRelease(relative(point)),
+            Action::MouseHold(point) => Action::MouseHold(relative(point)),
+            Action::MouseMotion(point) => Action::MouseMotion(relative(point)),
+            action => action,
+        }
+    }
+}
+
+fn moves_focus_only(action: &Action) -> bool {
+    matches!(
+        action,
+        Action::SwitchFocus
+            | Action::FocusNextPane
+            | Action::FocusPreviousPane
+            | Action::FocusPane(_)
+            | Action::MoveFocus(_)
+            | Action::MoveFocusOrTab(_)
+            | Action::GoToNextTab
+            | Action::GoToPreviousTab
+            | Action::GoToTab(_)
+            | Action::ToggleTab
+            | Action::Detach(None)
+            | Action::NoOp
+    )
+}
+
+/// Moves each cursor position (CUP, `ESC [ row ; col H`) of `output` down by `rows` and right by
+/// `cols`, the screen goes to every cell it draws this way.
+fn move_cursor_positions(output: &str, rows: usize, cols: usize) -> String {
+    let mut moved = String::with_capacity(output.len());
+    let mut rest = output;
+    while let Some(start) = rest.find("\u{1b}[") {
+        moved.push_str(&rest[..start]);
+        let params = &rest[start + 2..];
+        let end = params
+            .find(|c: char| !c.is_ascii_digit() && c != ';')
+            .unwrap_or(params.len());
+        // a missing row or column is the first one
+        let parse = |n: Option<&str>| match n {
+            None | Some("") => Some(1),
+            Some(n) => n.parse::<usize>().ok(),
+        };
+        let mut numbers = params[..end].splitn(2, ';');
+        let position = match (parse(numbers.next()), parse(numbers.next())) {
+            (Some(row), Some(col)) if params[end..].starts_with('H') => Some((row, col)),
+            _ => None,
+        };
+        match position {
+            Some((row, col)) => {
+                moved.push_str(&format!("\u{1b}[{};{}H", row + rows, col + cols));
+                rest = &params[end + 1..];
+            }
+            None => {
+                moved.push_str("\u{1b}[");
+                rest = params;
+            }
+        }
+    }
+    moved.push_str(rest);
+    moved
+}
+
+#[cfg(test)]
+#[path = "./unit/clients_tests.rs"]
+mod clients_tests;
@@ -0,0 +1,44 @@
+//! Opening files in an editor already running in a pane, by typing the command that opens a
+//! file into it the way the user would.
+use std::path::Path;
+
+/// The keys making the editor that runs as `command` (its arguments, `command[0]` being the
+/// program) open `file`, provided it is `editor` (the `EDITOR` of the session, which can come
+/// with arguments) and one of the editors zellij knows how to drive.
+pub(crate) fn open_file_keys(editor: &str, command: &[String], file: &Path) -> Option<Vec<u8>> {
+    let program = program_name(command.first()?);
+    if program != program_name(editor.split_whitespace().next()?) {
+        return None;
+    }
+    let file = file.to_str()?;
+    let keys = match program {
+        // leave insert mode or a pending command first, then `:edit`
+        "vi" | "vim" | "nvim" => format!("\u{1b}:e {}\r", escape_spaces(file)),
+        "hx" | "helix" => format!("\u{1b}:open {}\r", escape_spaces(file)),
+        "kak" => format!("\u{1b}:edit '{}'\r", file.replace('\'', "''")),
+        // quit whatever is going on with C-g, then C-x C-f - a path starting with `/` replaces
+        // the directory the prompt starts with
+        "emacs" => format!("\u{7}\u{18}\u{6}{}\r", file),
+        _ => return None,
+    };
+    Some(keys.into_bytes())
+}
+
+fn program_name(program: &str) -> &str {
+    program.rsplit('/').next().unwrap_or(program)
+}
+
+fn escape_spaces(file: &str) -> String {
+    let mut escaped = String::with_capacity(file.len());
+    for c in file.chars() {
+        if matches!(c, ' ' | '\\' | '%' | '#' | '|' | '"') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+#[path = "./unit/editor_tests.rs"]
+mod editor_tests;
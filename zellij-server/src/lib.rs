@@ -2,71 +2,94 @@ pub mod os_input_output;
 pub mod panes;
 pub mod tab;
 
+mod clients;
+mod editor;
 mod logging_pipe;
 mod pty;
 mod route;
 mod screen;
+mod serialization;
+mod session_manager;
 mod thread_bus;
 mod ui;
 mod wasm_vm;
 
 use log::info;
 use std::{
-    path::PathBuf,
+    fs,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex, RwLock},
     thread,
+    time::Instant,
 };
 use zellij_utils::zellij_tile;
 
 use wasmer::Store;
-use zellij_tile::data::{Event, Palette, PluginCapabilities};
+use zellij_tile::data::{InputMode, Palette, PluginCapabilities};
 
 use crate::{
-    os_input_output::ServerOsApi,
-    pty::{pty_thread_main, Pty, PtyInstruction},
+    clients::{Clients, CLIENT_TIMEOUT},
+    os_input_output::{ClientId, ServerOsApi},
+    pty::{pty_thread_main, Pty, PtyInstruction, ShutdownPolicy},
     screen::{screen_thread_main, ScreenInstruction},
-    thread_bus::{Bus, ThreadSenders},
+    serialization::{IdleExit, SessionSerialization, IDLE_CHECK_INTERVAL, SERIALIZATION_TIMEOUT},
+    thread_bus::{ActionReply, Bus, ThreadSenders},
     wasm_vm::{wasm_thread_main, PluginInstruction},
 };
-use route::route_thread_main;
+use route::{route_thread_main, ActionRateLimiter};
 use zellij_utils::{
+    action_token,
     channels::{self, ChannelWithContext, SenderWithContext},
     cli::CliArgs,
+    consts::ZELLIJ_ACTION_TOKEN_ENV,
     errors::{ContextType, ErrorInstruction, ServerContext},
     input::{
+        bundle::resurrection_path,
         command::{RunCommand, TerminalAction},
         get_mode_info,
         layout::LayoutFromYaml,
         options::Options,
     },
-    ipc::{ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg},
-    setup::get_default_data_dir,
+    ipc::{ActionResult, ClientAttributes, ExitReason, ServerToClientMsg, HEARTBEAT_INTERVAL},
+    messages::Locale,
+    pane_size::Size,
+    setup::{find_default_config_dir, get_default_data_dir, get_layout_dir},
 };
 
 /// Instructions related to server-side application
 #[derive(Debug, Clone)]
 pub(crate) enum ServerInstruction {
-    NewClient(ClientAttributes, Box<CliArgs>, Box<Options>, LayoutFromYaml),
+    NewClient(
+        ClientAttributes,
+        Box<CliArgs>,
+        Box<Options>,
+        LayoutFromYaml,
+        ClientId,
+    ),
     Render(Option<String>),
-    UnblockInputThread,
+    // a tab drawn for one of several clients, with the cursors of the others
+    RenderClient(ClientId, String),
+    // unblocks the input thread of the client that sent the action, or of the one that sent the
+    // latest action when that isn't known
+    UnblockInputThread(Option<ClientId>),
     ClientExit,
     Error(String),
-    DetachSession,
-    AttachClient(ClientAttributes, bool, Options),
-}
-
-impl From<ClientToServerMsg> for ServerInstruction {
-    fn from(instruction: ClientToServerMsg) -> Self {
-        match instruction {
-            ClientToServerMsg::NewClient(attrs, opts, options, layout) => {
-                ServerInstruction::NewClient(attrs, opts, options, layout)
-            }
-            ClientToServerMsg::AttachClient(attrs, force, options) => {
-                ServerInstruction::AttachClient(attrs, force, options)
-            }
-            _ => unreachable!(),
-        }
-    }
+    // detaches the client, or the one that sent the latest action, telling it why
+    DetachSession(Option<ClientId>, ExitReason),
+    // detaches the clients other than this one, or than the one that sent the latest action
+    DetachOtherClients(Option<ClientId>),
+    // switches the client, or the one that sent the latest action, over to another session
+    SwitchSession(String, bool, Option<ClientId>),
+    LeaveForSession(String, ClientId),
+    AttachClient(ClientAttributes, bool, Options, ClientId),
+    ResizeClient(ClientId, Size),
+    FocusClient(ClientId, bool),
+    Diagnostic(String),
+    CopyToClipboard(String),
+    OpenLink(String),
+    RenameSession(String, Option<ActionReply>),
+    // tells a client joining the others the mode they are in
+    SwitchToMode(ClientId, InputMode),
 }
 
 impl From<&ServerInstruction> for ServerContext {
@@ -74,11 +97,22 @@ impl From<&ServerInstruction> for ServerContext {
         match *server_instruction {
             ServerInstruction::NewClient(..) => ServerContext::NewClient,
             ServerInstruction::Render(_) => ServerContext::Render,
-            ServerInstruction::UnblockInputThread => ServerContext::UnblockInputThread,
+            ServerInstruction::RenderClient(..) => ServerContext::RenderClient,
+            ServerInstruction::UnblockInputThread(_) => ServerContext::UnblockInputThread,
             ServerInstruction::ClientExit => ServerContext::ClientExit,
             ServerInstruction::Error(_) => ServerContext::Error,
-            ServerInstruction::DetachSession => ServerContext::DetachSession,
+            ServerInstruction::DetachSession(..) => ServerContext::DetachSession,
+            ServerInstruction::DetachOtherClients(_) => ServerContext::DetachOtherClients,
+            ServerInstruction::SwitchSession(..) => ServerContext::SwitchSession,
+            ServerInstruction::LeaveForSession(..) => ServerContext::LeaveForSession,
             ServerInstruction::AttachClient(..) => ServerContext::AttachClient,
+            ServerInstruction::ResizeClient(..) => ServerContext::ResizeClient,
+            ServerInstruction::FocusClient(..) => ServerContext::FocusClient,
+            ServerInstruction::Diagnostic(_) => ServerContext::Diagnostic,
+            ServerInstruction::CopyToClipboard(_) => ServerContext::CopyToClipboard,
+            ServerInstruction::OpenLink(_) => ServerContext::OpenLink,
+            ServerInstruction::RenameSession(..) => ServerContext::RenameSession,
+            ServerInstruction::SwitchToMode(..) => ServerContext::SwitchToMode,
         }
     }
 }
@@ -94,19 +128,49 @@ pub(crate) struct SessionMetaData {
     pub capabilities: PluginCapabilities,
     pub palette: Palette,
     pub default_shell: Option<TerminalAction>,
+    pub locale: Locale,
+    pub cli_action_limiter: Option<Mutex<ActionRateLimiter>>,
     screen_thread: Option<thread::JoinHandle<()>>,
-    pty_thread: Option<thread::JoinHandle<()>>,
+    pty_thread: Option<thread::JoinHandle<Vec<String>>>,
     wasm_thread: Option<thread::JoinHandle<()>>,
 }
 
-impl Drop for SessionMetaData {
-    fn drop(&mut self) {
+impl SessionMetaData {
+    /// Stops the threads of the session and the programs running in its panes, returns the panes
+    /// that had to be killed.
+    fn shut_down(&mut self) -> Vec<String> {
+        // the session is written as it is before its panes go away
+        if self.screen_thread.is_some() {
+            let (reply, written) = channels::bounded(1);
+            if self
+                .senders
+                .send_to_screen(ScreenInstruction::SerializeSession(Some(reply)))
+                .is_ok()
+            {
+                let _ = written.recv_timeout(SERIALIZATION_TIMEOUT);
+            }
+        }
         let _ = self.senders.send_to_pty(PtyInstruction::Exit);
         let _ = self.senders.send_to_screen(ScreenInstruction::Exit);
         let _ = self.senders.send_to_plugin(PluginInstruction::Exit);
-        let _ = self.screen_thread.take().unwrap().join();
-        let _ = self.pty_thread.take().unwrap().join();
-        let _ = self.wasm_thread.take().unwrap().join();
+        if let Some(screen_thread) = self.screen_thread.take() {
+            let _ = screen_thread.join();
+        }
+        let killed_panes = self
+            .pty_thread
+            .take()
+            .and_then(|pty_thread| pty_thread.join().ok())
+            .unwrap_or_default();
+        if let Some(wasm_thread) = self.wasm_thread.take() {
+            let _ = wasm_thread.join();
+        }
+        killed_panes
+    }
+}
+
+impl Drop for SessionMetaData {
+    fn drop(&mut self) {
+        self.shut_down();
     }
 }
 
@@ -117,7 +181,7 @@ pub(crate) enum SessionState {
     Uninitialized,
 }
 
-pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
+pub fn start_server(os_input: Box<dyn ServerOsApi>, mut socket_path: PathBuf) {
     info!("Starting Zellij server!");
     daemonize::Daemonize::new()
         .working_directory(std::env::current_dir().unwrap())
@@ -127,10 +191,25 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
 
     std::env::set_var(&"ZELLIJ", "0");
 
+    // the panes inherit the token from the environment, other processes of the user read it from
+    // the token file
+    let action_token = action_token::generate().expect("could not generate the action token");
+    let mut token_path = action_token::token_path(&socket_path);
+    action_token::write(&token_path, &action_token).expect("could not write the action token");
+    std::env::set_var(ZELLIJ_ACTION_TOKEN_ENV, &action_token);
+    let action_token = Arc::new(action_token);
+
     let (to_server, server_receiver): ChannelWithContext<ServerInstruction> = channels::bounded(50);
     let to_server = SenderWithContext::new(to_server);
     let session_data: Arc<RwLock<Option<SessionMetaData>>> = Arc::new(RwLock::new(None));
     let session_state = Arc::new(RwLock::new(SessionState::Uninitialized));
+    let clients = Arc::new(RwLock::new(Clients::default()));
+    // sessions are named after their socket
+    let mut session_name = socket_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_string();
 
     std::panic::set_hook({
         use zellij_utils::errors::handle_panic;
@@ -152,9 +231,11 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
             let os_input = os_input.clone();
             let session_data = session_data.clone();
             let session_state = session_state.clone();
+            let clients = clients.clone();
             let to_server = to_server.clone();
             let socket_path = socket_path.clone();
             let thread_handles = thread_handles.clone();
+            let action_token = action_token.clone();
             move || {
                 drop(std::fs::remove_file(&socket_path));
                 let listener = LocalSocketListener::bind(&*socket_path).unwrap();
@@ -166,7 +247,9 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                             os_input.update_receiver(stream);
                             let session_data = session_data.clone();
                             let session_state = session_state.clone();
+                            let clients = clients.clone();
                             let to_server = to_server.clone();
+                            let action_token = action_token.clone();
                             thread_handles.lock().unwrap().push(
                                 thread::Builder::new()
                                     .name("server_router".to_string())
@@ -179,8 +262,10 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                                             route_thread_main(
                                                 session_data,
                                                 session_state,
+                                                clients,
                                                 os_input,
                                                 to_server,
+                                                action_token,
                                             )
                                         }
                                     })
@@ -195,13 +280,63 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
             }
         });
 
+    // clients that stop pinging the server are gone or hung, they leave the session to the others
+    let _ = thread::Builder::new()
+        .name("client_timeout".to_string())
+        .spawn({
+            let clients = clients.clone();
+            let to_server = to_server.clone();
+            move || loop {
+                thread::sleep(HEARTBEAT_INTERVAL);
+                let silent_clients = clients.read().unwrap().silent(Instant::now());
+                for client_id in silent_clients {
+                    info!(
+                        "Client {} didn't ping for {:?}, detaching it",
+                        client_id, CLIENT_TIMEOUT
+                    );
+                    let detach =
+                        ServerInstruction::DetachSession(Some(client_id), ExitReason::TimedOut);
+                    if to_server.send(detach).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+    let send_to_all_clients = |msg: ServerToClientMsg| {
+        for client_id in clients.read().unwrap().ids() {
+            os_input.send_to_client(client_id, msg.clone());
+        }
+    };
+    // the client that sent the latest action, or all of them once it is gone
+    let send_to_active_client = |msg: ServerToClientMsg| {
+        let active_client = clients.read().unwrap().active();
+        match active_client {
+            Some(client_id) => os_input.send_to_client(client_id, msg),
+            None => send_to_all_clients(msg),
+        }
+    };
+
     loop {
         let (instruction, mut err_ctx) = server_receiver.recv().unwrap();
         err_ctx.add_call(ContextType::IPCServer((&instruction).into()));
         match instruction {
-            ServerInstruction::NewClient(client_attributes, opts, config_options, layout) => {
+            ServerInstruction::NewClient(
+                client_attributes,
+                opts,
+                config_options,
+                layout,
+                client_id,
+            ) => {
+                {
+                    let mut clients = clients.write().unwrap();
+                    clients.sizing = config_options.client_sizing.unwrap_or_default();
+                    clients.attach(client_id, client_attributes.size);
+                    clients.set_read_only(client_id, client_attributes.read_only);
+                }
                 let session = init_session(
                     os_input.clone(),
+                    &session_name,
                     opts,
                     config_options.clone(),
                     to_server.clone(),
@@ -209,6 +344,11 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     session_state.clone(),
                     layout.clone(),
                 );
+                // the screen already starts in the mode of its first client
+                session
+                    .senders
+                    .send_to_screen(ScreenInstruction::AddClient(client_id, None))
+                    .unwrap();
                 *session_data.write().unwrap() = Some(session);
                 *session_state.write().unwrap() = SessionState::Attached;
 
@@ -226,55 +366,215 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                         .as_ref()
                         .unwrap()
                         .senders
-                        .send_to_pty(PtyInstruction::NewTab(default_shell.clone(), tab_layout))
+                        .send_to_pty(PtyInstruction::NewTab(
+                            default_shell.clone(),
+                            tab_layout,
+                            None,
+                        ))
                         .unwrap()
                 };
 
                 if !&layout.tabs.is_empty() {
+                    let focused_tab = layout.tabs.iter().position(|tab_layout| tab_layout.focus);
                     for tab_layout in layout.tabs {
                         spawn_tabs(Some(tab_layout.clone()));
                     }
+                    // new tabs take the focus, so this goes after them through the pty thread
+                    if let Some(position) = focused_tab {
+                        session_data
+                            .read()
+                            .unwrap()
+                            .as_ref()
+                            .unwrap()
+                            .senders
+                            .send_to_pty(PtyInstruction::GoToTab(position as u32 + 1))
+                            .unwrap();
+                    }
                 } else {
                     spawn_tabs(None);
                 }
             }
-            ServerInstruction::AttachClient(attrs, _, options) => {
+            ServerInstruction::AttachClient(attrs, force, options, client_id) => {
+                if force {
+                    let other_clients = clients.read().unwrap().ids();
+                    for other_client in other_clients {
+                        detach_client(
+                            other_client,
+                            Some(ExitReason::ForceDetached),
+                            &*os_input,
+                            &clients,
+                            &session_data,
+                            &session_state,
+                        );
+                    }
+                }
+                let session_size = {
+                    let mut clients = clients.write().unwrap();
+                    let other_clients = clients.ids();
+                    clients.attach(client_id, attrs.size);
+                    clients.set_read_only(client_id, attrs.read_only);
+                    let attached = attached_clients(clients.len());
+                    for other_client in &other_clients {
+                        os_input.send_to_client(
+                            *other_client,
+                            ServerToClientMsg::Diagnostic(format!(
+                                "Client {} attached to the session, {}",
+                                client_id, attached
+                            )),
+                        );
+                    }
+                    if !other_clients.is_empty() {
+                        os_input.send_to_client(
+                            client_id,
+                            ServerToClientMsg::Diagnostic(format!(
+                                "Attached as client {} alongside other clients, {}",
+                                client_id, attached
+                            )),
+                        );
+                    }
+                    clients.session_size().unwrap_or(attrs.size)
+                };
                 *session_state.write().unwrap() = SessionState::Attached;
                 let rlock = session_data.read().unwrap();
                 let session_data = rlock.as_ref().unwrap();
+                // the client starts out with the focus and the mode the others have, or in its
+                // default mode when it is the only one
+                let mode_info = get_mode_info(
+                    options.default_mode.unwrap_or_default(),
+                    attrs.palette,
+                    session_data.capabilities,
+                    session_data.locale,
+                );
                 session_data
                     .senders
-                    .send_to_screen(ScreenInstruction::TerminalResize(attrs.size))
+                    .send_to_screen(ScreenInstruction::AddClient(client_id, Some(mode_info)))
                     .unwrap();
-                let default_mode = options.default_mode.unwrap_or_default();
-                let mode_info =
-                    get_mode_info(default_mode, attrs.palette, session_data.capabilities);
+                // a full render, for the new client and for the others in the new size
                 session_data
                     .senders
-                    .send_to_screen(ScreenInstruction::ChangeMode(mode_info.clone()))
+                    .send_to_screen(ScreenInstruction::TerminalResize(session_size))
                     .unwrap();
+                // the client of the session before might have left it unfocused
                 session_data
                     .senders
-                    .send_to_plugin(PluginInstruction::Update(
-                        None,
-                        Event::ModeUpdate(mode_info),
-                    ))
+                    .send_to_screen(ScreenInstruction::TerminalFocus(true))
                     .unwrap();
             }
-            ServerInstruction::UnblockInputThread => {
+            ServerInstruction::ResizeClient(client_id, size) => {
+                let session_size = {
+                    let mut clients = clients.write().unwrap();
+                    clients.resize(client_id, size);
+                    clients.session_size()
+                };
+                // renders again even when the session keeps its size, the client might show it
+                // elsewhere now
+                if let (Some(session_size), Some(session_data)) =
+                    (session_size, session_data.read().unwrap().as_ref())
+                {
+                    session_data
+                        .senders
+                        .send_to_screen(ScreenInstruction::TerminalResize(session_size))
+                        .unwrap();
+                }
+            }
+            ServerInstruction::FocusClient(client_id, focused) => {
+                let focused = {
+                    let mut clients = clients.write().unwrap();
+                    clients.set_focus(client_id, focused);
+                    clients.focused()
+                };
+                if let Some(session_data) = session_data.read().unwrap().as_ref() {
+                    session_data
+                        .senders
+                        .send_to_screen(ScreenInstruction::TerminalFocus(focused))
+                        .unwrap();
+                }
+            }
+            ServerInstruction::UnblockInputThread(client_id) => {
                 if *session_state.read().unwrap() == SessionState::Attached {
-                    os_input.send_to_client(ServerToClientMsg::UnblockInputThread);
+                    match client_id {
+                        Some(client_id) => os_input
+                            .send_to_client(client_id, ServerToClientMsg::UnblockInputThread),
+                        None => send_to_active_client(ServerToClientMsg::UnblockInputThread),
+                    }
                 }
             }
             ServerInstruction::ClientExit => {
-                *session_data.write().unwrap() = None;
-                os_input.send_to_client(ServerToClientMsg::Exit(ExitReason::Normal));
+                let killed_panes = session_data
+                    .write()
+                    .unwrap()
+                    .take()
+                    .map(|mut session| session.shut_down())
+                    .unwrap_or_default();
+                let reason = if killed_panes.is_empty() {
+                    ExitReason::Normal
+                } else {
+                    ExitReason::KilledPanes(killed_panes)
+                };
+                send_to_all_clients(ServerToClientMsg::Exit(reason));
                 break;
             }
-            ServerInstruction::DetachSession => {
-                *session_state.write().unwrap() = SessionState::Detached;
-                os_input.send_to_client(ServerToClientMsg::Exit(ExitReason::Normal));
-                os_input.remove_client_sender();
+            ServerInstruction::DetachSession(client_id, reason) => {
+                // all of them once the one that sent the latest action is gone
+                let detached_clients = {
+                    let clients = clients.read().unwrap();
+                    match client_id.or_else(|| clients.active()) {
+                        Some(client_id) => vec![client_id],
+                        None => clients.ids(),
+                    }
+                };
+                for client_id in detached_clients {
+                    detach_client(
+                        client_id,
+                        Some(reason.clone()),
+                        &*os_input,
+                        &clients,
+                        &session_data,
+                        &session_state,
+                    );
+                }
+            }
+            ServerInstruction::DetachOtherClients(client_id) => {
+                let other_clients: Vec<ClientId> = {
+                    let clients = clients.read().unwrap();
+                    match client_id.or_else(|| clients.active()) {
+                        Some(client_id) => clients
+                            .ids()
+                            .into_iter()
+                            .filter(|other_client| *other_client != client_id)
+                            .collect(),
+                        None => vec![],
+                    }
+                };
+                for other_client in other_clients {
+                    detach_client(
+                        other_client,
+                        Some(ExitReason::DetachedByOtherClient),
+                        &*os_input,
+                        &clients,
+                        &session_data,
+                        &session_state,
+                    );
+                }
+            }
+            ServerInstruction::SwitchSession(name, force, client_id) => {
+                // the client makes sure the other session is there before it leaves this one
+                let msg = ServerToClientMsg::SwitchSession(name, force);
+                match client_id {
+                    Some(client_id) => os_input.send_to_client(client_id, msg),
+                    None => send_to_active_client(msg),
+                }
+            }
+            ServerInstruction::LeaveForSession(name, client_id) => {
+                // detaches like above, the client attaches to the other session by itself
+                detach_client(
+                    client_id,
+                    Some(ExitReason::SwitchSession(name)),
+                    &*os_input,
+                    &clients,
+                    &session_data,
+                    &session_state,
+                );
             }
             ServerInstruction::Render(output) => {
                 if *session_state.read().unwrap() == SessionState::Attached {
@@ -282,16 +582,80 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     // If `Some(_)`- unwrap it and forward it to the client to render.
                     // If `None`- Send an exit instruction. This is the case when the user closes last Tab/Pane.
                     if let Some(op) = output {
-                        os_input.send_to_client(ServerToClientMsg::Render(op));
+                        // each client gets it where it shows the session
+                        let clients = clients.read().unwrap();
+                        for client_id in clients.ids() {
+                            os_input.send_to_client(
+                                client_id,
+                                ServerToClientMsg::Render(clients.render_for(client_id, &op)),
+                            );
+                        }
                     } else {
-                        os_input.send_to_client(ServerToClientMsg::Exit(ExitReason::Normal));
+                        // the last tab is closed, there is nothing left to resurrect
+                        drop(std::fs::remove_file(resurrection_path(&session_name)));
+                        send_to_all_clients(ServerToClientMsg::Exit(ExitReason::Normal));
                         break;
                     }
                 }
             }
+            ServerInstruction::RenderClient(client_id, output) => {
+                if *session_state.read().unwrap() == SessionState::Attached {
+                    let clients = clients.read().unwrap();
+                    os_input.send_to_client(
+                        client_id,
+                        ServerToClientMsg::Render(clients.render_for(client_id, &output)),
+                    );
+                }
+            }
+            ServerInstruction::Diagnostic(message) => {
+                if *session_state.read().unwrap() == SessionState::Attached {
+                    send_to_all_clients(ServerToClientMsg::Diagnostic(message));
+                }
+            }
+            ServerInstruction::CopyToClipboard(text) => {
+                if *session_state.read().unwrap() == SessionState::Attached {
+                    send_to_active_client(ServerToClientMsg::CopyToClipboard(text));
+                }
+            }
+            ServerInstruction::OpenLink(link) => {
+                if *session_state.read().unwrap() == SessionState::Attached {
+                    send_to_active_client(ServerToClientMsg::OpenLink(link));
+                }
+            }
+            ServerInstruction::SwitchToMode(client_id, mode) => {
+                os_input.send_to_client(client_id, ServerToClientMsg::SwitchToMode(mode));
+            }
+            ServerInstruction::RenameSession(name, reply) => {
+                match rename_session_files(&socket_path, &session_name, &name) {
+                    Ok(new_socket_path) => {
+                        socket_path = new_socket_path;
+                        token_path = action_token::token_path(&socket_path);
+                        session_name = name;
+                        // the panes opened from now on and the mode info get the new name
+                        std::env::set_var("ZELLIJ_SESSION_NAME", &session_name);
+                        if let Some(session_data) = session_data.read().unwrap().as_ref() {
+                            session_data
+                                .senders
+                                .send_to_screen(ScreenInstruction::RenameSession(
+                                    session_name.clone(),
+                                ))
+                                .unwrap();
+                        }
+                    }
+                    Err(reason) => match reply {
+                        Some(reply) => {
+                            let _ = reply.send(ActionResult::Refused(reason));
+                        }
+                        None if *session_state.read().unwrap() == SessionState::Attached => {
+                            send_to_all_clients(ServerToClientMsg::Diagnostic(reason));
+                        }
+                        None => {}
+                    },
+                }
+            }
             ServerInstruction::Error(backtrace) => {
                 if *session_state.read().unwrap() == SessionState::Attached {
-                    os_input.send_to_client(ServerToClientMsg::Exit(ExitReason::Error(backtrace)));
+                    send_to_all_clients(ServerToClientMsg::Exit(ExitReason::Error(backtrace)));
                 }
                 break;
             }
@@ -303,10 +667,104 @@ pub fn start_server(os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
         .drain(..)
         .for_each(|h| drop(h.join()));
     drop(std::fs::remove_file(&socket_path));
+    drop(std::fs::remove_file(&token_path));
+}
+
+/// Detaches the client `client_id` from the session, telling it why with `exit_reason` unless
+/// it is gone. The other clients are told, and the session takes their size.
+fn detach_client(
+    client_id: ClientId,
+    exit_reason: Option<ExitReason>,
+    os_input: &dyn ServerOsApi,
+    clients: &RwLock<Clients>,
+    session_data: &RwLock<Option<SessionMetaData>>,
+    session_state: &RwLock<SessionState>,
+) {
+    let (session_size, focused) = {
+        let mut clients = clients.write().unwrap();
+        if !clients.detach(client_id) {
+            return;
+        }
+        if let Some(exit_reason) = exit_reason {
+            os_input.send_to_client(client_id, ServerToClientMsg::Exit(exit_reason));
+        }
+        os_input.remove_client_sender(client_id);
+        if clients.is_empty() {
+            *session_state.write().unwrap() = SessionState::Detached;
+        }
+        let attached = attached_clients(clients.len());
+        for other_client in clients.ids() {
+            os_input.send_to_client(
+                other_client,
+                ServerToClientMsg::Diagnostic(format!(
+                    "Client {} detached from the session, {}",
+                    client_id, attached
+                )),
+            );
+        }
+        (clients.session_size(), clients.focused())
+    };
+    let session_data = session_data.read().unwrap();
+    let session_data = match session_data.as_ref() {
+        Some(session_data) => session_data,
+        None => return,
+    };
+    let _ = session_data
+        .senders
+        .send_to_screen(ScreenInstruction::RemoveClient(client_id));
+    if let Some(session_size) = session_size {
+        let _ = session_data
+            .senders
+            .send_to_screen(ScreenInstruction::TerminalResize(session_size));
+        let _ = session_data
+            .senders
+            .send_to_screen(ScreenInstruction::TerminalFocus(focused));
+    }
+}
+
+fn attached_clients(count: usize) -> String {
+    if count == 1 {
+        "1 client attached".to_string()
+    } else {
+        format!("{} clients attached", count)
+    }
+}
+
+/// Moves the socket, the token and the resurrection file of the session `old_name` listening on
+/// `socket_path` over to the name `name`, returning the new path of the socket.
+fn rename_session_files(socket_path: &Path, old_name: &str, name: &str) -> Result<PathBuf, String> {
+    if name == old_name {
+        return Ok(socket_path.to_path_buf());
+    }
+    if name.is_empty() || name.contains('/') {
+        return Err(format!("{:?} can't name a session", name));
+    }
+    let new_socket_path = socket_path.with_file_name(name);
+    if new_socket_path.exists() {
+        return Err(format!("A session named {:?} already exists", name));
+    }
+    if resurrection_path(name).exists() {
+        return Err(format!(
+            "A session named {:?} exited and can be resurrected, delete it first",
+            name
+        ));
+    }
+    fs::rename(socket_path, &new_socket_path)
+        .map_err(|e| format!("Failed to rename the session: {}", e))?;
+    drop(fs::rename(
+        action_token::token_path(socket_path),
+        action_token::token_path(&new_socket_path),
+    ));
+    drop(fs::rename(
+        resurrection_path(old_name),
+        resurrection_path(name),
+    ));
+    Ok(new_socket_path)
 }
 
 fn init_session(
     os_input: Box<dyn ServerOsApi>,
+    session_name: &str,
     opts: Box<CliArgs>,
     config_options: Box<Options>,
     to_server: SenderWithContext<ServerInstruction>,
@@ -340,6 +798,15 @@ fn init_session(
         })
     });
 
+    let locale = Locale::from_option(config_options.locale);
+    let cli_action_limiter = ActionRateLimiter::from_options(&config_options).map(Mutex::new);
+    let session_serialization = SessionSerialization::from_options(session_name, &config_options);
+    let serialization_interval = session_serialization
+        .as_ref()
+        .map(|serialization| serialization.interval);
+    let idle_exit = IdleExit::from_options(&config_options);
+    let checks_idle = idle_exit.is_some();
+
     let pty_thread = thread::Builder::new()
         .name("pty".to_string())
         .spawn({
@@ -353,9 +820,19 @@ fn init_session(
                     Some(os_input.clone()),
                 ),
                 opts.debug,
+                ShutdownPolicy::from_options(&config_options),
+                config_options.inherit_cwd.unwrap_or(true),
             );
 
-            move || pty_thread_main(pty, layout)
+            let config_dir = opts.config_dir.clone();
+            let layout_dir = config_options
+                .layout_dir
+                .clone()
+                .or_else(|| get_layout_dir(config_dir.or_else(find_default_config_dir)));
+
+            let layout_vars = opts.layout_vars.clone();
+
+            move || pty_thread_main(pty, layout, layout_dir, layout_vars)
         })
         .unwrap();
 
@@ -364,7 +841,7 @@ fn init_session(
         .spawn({
             let screen_bus = Bus::new(
                 vec![screen_receiver, bounded_screen_receiver],
-                None,
+                Some(&to_screen),
                 Some(&to_pty),
                 Some(&to_plugin),
                 Some(&to_server),
@@ -378,12 +855,46 @@ fn init_session(
                     max_panes,
                     client_attributes,
                     config_options,
+                    locale,
                     session_state,
+                    session_serialization,
+                    idle_exit,
                 );
             }
         })
         .unwrap();
 
+    if let Some(interval) = serialization_interval {
+        // stops once the screen thread is gone with the session
+        let to_screen = to_screen.clone();
+        thread::Builder::new()
+            .name("serialization".to_string())
+            .spawn(move || loop {
+                thread::sleep(interval);
+                if to_screen
+                    .send(ScreenInstruction::SerializeSession(None))
+                    .is_err()
+                {
+                    break;
+                }
+            })
+            .unwrap();
+    }
+
+    if checks_idle {
+        // stops once the screen thread is gone with the session
+        let to_screen = to_screen.clone();
+        thread::Builder::new()
+            .name("idle_exit".to_string())
+            .spawn(move || loop {
+                thread::sleep(IDLE_CHECK_INTERVAL);
+                if to_screen.send(ScreenInstruction::CheckIdle).is_err() {
+                    break;
+                }
+            })
+            .unwrap();
+    }
+
     let wasm_thread = thread::Builder::new()
         .name("wasm".to_string())
         .spawn({
@@ -407,12 +918,19 @@ fn init_session(
             to_plugin: Some(to_plugin),
             to_server: None,
             should_silently_fail: false,
+            client_id: None,
         },
         capabilities,
         default_shell,
+        locale,
+        cli_action_limiter,
         palette: client_attributes.palette,
         screen_thread: Some(screen_thread),
         pty_thread: Some(pty_thread),
         wasm_thread: Some(wasm_thread),
     }
 }
+
+#[cfg(test)]
+#[path = "./unit/rename_session_tests.rs"]
+mod rename_session_tests;
@@ -1,6 +1,7 @@
 #[cfg(target_os = "macos")]
 use darwin_libproc;
 
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::os::unix::io::RawFd;
@@ -17,17 +18,14 @@ use interprocess::local_socket::LocalSocketStream;
 use nix::pty::{forkpty, ForkptyResult, Winsize};
 use nix::sys::signal::{kill, Signal};
 use nix::sys::termios;
-use nix::sys::wait::waitpid;
+use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
 use nix::unistd::{self, ForkResult};
 use signal_hook::consts::*;
 use zellij_tile::data::Palette;
 use zellij_utils::{
     errors::ErrorContext,
     input::command::{RunCommand, TerminalAction},
-    ipc::{
-        ClientToServerMsg, ExitReason, IpcReceiverWithContext, IpcSenderWithContext,
-        ServerToClientMsg,
-    },
+    ipc::{ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext, ServerToClientMsg},
     shared::default_palette,
 };
 
@@ -100,6 +98,7 @@ fn handle_command_exit(mut child: Child) {
 fn handle_fork_pty(
     fork_pty_res: ForkptyResult,
     cmd: RunCommand,
+    stdin: Option<PathBuf>,
     parent_fd: RawFd,
     child_fd: RawFd,
 ) -> (RawFd, ChildId) {
@@ -115,8 +114,12 @@ fn handle_fork_pty(
                 if let Some(current_dir) = cmd.cwd {
                     command.current_dir(current_dir);
                 }
+                if let Some(stdin) = stdin {
+                    command.stdin(fs::File::open(stdin).expect("failed to open the input file"));
+                }
                 command
                     .args(&cmd.args)
+                    .envs(&cmd.env)
                     .pre_exec(|| -> std::io::Result<()> {
                         // this is the "unsafe" part, for more details please see:
                         // https://doc.rust-lang.org/std/os/unix/process/trait.CommandExt.html#notes-and-safety
@@ -147,12 +150,17 @@ fn handle_fork_pty(
 /// Spawns a new terminal from the parent terminal with [`termios`](termios::Termios)
 /// `orig_termios`.
 ///
-fn handle_terminal(cmd: RunCommand, orig_termios: termios::Termios) -> (RawFd, ChildId) {
+/// The command reads from the file `stdin` rather than from the terminal, if one is given.
+fn handle_terminal(
+    cmd: RunCommand,
+    stdin: Option<PathBuf>,
+    orig_termios: termios::Termios,
+) -> (RawFd, ChildId) {
     // Create a pipe to allow the child the communicate the shell's pid to it's
     // parent.
     let (parent_fd, child_fd) = unistd::pipe().expect("failed to create pipe");
     match forkpty(None, Some(&orig_termios)) {
-        Ok(fork_pty_res) => handle_fork_pty(fork_pty_res, cmd, parent_fd, child_fd),
+        Ok(fork_pty_res) => handle_fork_pty(fork_pty_res, cmd, stdin, parent_fd, child_fd),
         Err(e) => {
             panic!("failed to fork {:?}", e);
         }
@@ -190,8 +198,13 @@ fn read_from_pipe(parent_fd: RawFd, child_fd: RawFd) -> Option<u32> {
 /// If a [`TerminalAction::OpenFile(file)`] is given, the text editor specified by environment variable `EDITOR`
 /// (or `VISUAL`, if `EDITOR` is not set) will be started in the new terminal, with the given
 /// file open.
+/// If a [`TerminalAction::PageFile(file)`] is given, the pager specified by environment variable
+/// `PAGER` (or `less -R`, if `PAGER` is not set) will be started in the new terminal, showing
+/// the given file.
 /// If [`TerminalAction::RunCommand(RunCommand)`] is given, the command will be started
 /// in the new terminal.
+/// If [`TerminalAction::PipeFile(file, RunCommand)`] is given, the command will be started
+/// in the new terminal, reading the given file as its standard input.
 /// If None is given, the shell specified by environment variable `SHELL` will
 /// be started in the new terminal.
 ///
@@ -203,6 +216,7 @@ pub fn spawn_terminal(
     terminal_action: TerminalAction,
     orig_termios: termios::Termios,
 ) -> (RawFd, ChildId) {
+    let mut stdin = None;
     let cmd = match terminal_action {
         TerminalAction::OpenFile(file_to_open) => {
             if env::var("EDITOR").is_err() && env::var("VISUAL").is_err() {
@@ -218,20 +232,51 @@ pub fn spawn_terminal(
             RunCommand {
                 command,
                 args,
-                cwd: None,
+                ..Default::default()
+            }
+        }
+        TerminalAction::PageFile(file_to_page) => {
+            let pager = env::var("PAGER").unwrap_or_else(|_| "less -R".to_string());
+            let mut args: Vec<String> = pager.split_whitespace().map(String::from).collect();
+            if args.is_empty() {
+                args = vec!["less".to_string(), "-R".to_string()];
+            }
+            let command = PathBuf::from(args.remove(0));
+            args.push(
+                file_to_page
+                    .into_os_string()
+                    .into_string()
+                    .expect("Not valid Utf8 Encoding"),
+            );
+            RunCommand {
+                command,
+                args,
+                ..Default::default()
             }
         }
         TerminalAction::RunCommand(command) => command,
+        TerminalAction::PipeFile(file_to_read, command) => {
+            stdin = Some(file_to_read);
+            command
+        }
     };
 
-    handle_terminal(cmd, orig_termios)
+    handle_terminal(cmd, stdin, orig_termios)
+}
+
+pub use zellij_utils::ipc::ClientId;
+
+#[derive(Default)]
+struct ClientSenders {
+    senders: BTreeMap<ClientId, IpcSenderWithContext<ServerToClientMsg>>,
+    next_client_id: ClientId,
 }
 
 #[derive(Clone)]
 pub struct ServerOsInputOutput {
     orig_termios: Arc<Mutex<termios::Termios>>,
     receive_instructions_from_client: Option<Arc<Mutex<IpcReceiverWithContext<ClientToServerMsg>>>>,
-    send_instructions_to_client: Arc<Mutex<Option<IpcSenderWithContext<ServerToClientMsg>>>>,
+    send_instructions_to_clients: Arc<Mutex<ClientSenders>>,
 }
 
 // async fn in traits is not supported by rust, so dtolnay's excellent async_trait macro is being
@@ -285,25 +330,36 @@ pub trait ServerOsApi: Send + Sync {
     fn force_kill(&self, pid: Pid) -> Result<(), nix::Error>;
     /// Returns a [`Box`] pointer to this [`ServerOsApi`] struct.
     fn box_clone(&self) -> Box<dyn ServerOsApi>;
-    /// Receives a message on server-side IPC channel
-    fn recv_from_client(&self) -> (ClientToServerMsg, ErrorContext);
-    /// Sends a message to client
-    fn send_to_client(&self, msg: ServerToClientMsg);
-    /// Adds a sender to client
-    fn add_client_sender(&self);
+    /// Receives a message on server-side IPC channel, `None` once the client is gone
+    fn recv_from_client(&self) -> Option<(ClientToServerMsg, ErrorContext)>;
+    /// Sends a message to the client `client_id`, forgetting about it if it is gone
+    fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg);
+    /// Adds a sender to the client of the receiver, returning the id of the client
+    fn add_client_sender(&self) -> ClientId;
     /// Send to the temporary client
     // A temporary client is the one that hasn't been registered as a client yet.
     // Only the corresponding router thread has access to send messages to it.
     // This can be the case when the client cannot attach to the session,
     // so it tries to connect and then exits, hence temporary.
     fn send_to_temp_client(&self, msg: ServerToClientMsg);
-    /// Removes the sender to client
-    fn remove_client_sender(&self);
+    /// Removes the sender to the client `client_id`
+    fn remove_client_sender(&self, client_id: ClientId);
     /// Update the receiver socket for the client
     fn update_receiver(&mut self, stream: LocalSocketStream);
     fn load_palette(&self) -> Palette;
     /// Returns the current working directory for a given pid
     fn get_cwd(&self, pid: Pid) -> Option<PathBuf>;
+    /// Returns the arguments of the process in the foreground of the terminal `fd`, eg. an
+    /// editor started from the shell of the terminal
+    fn get_foreground_command(&self, fd: RawFd) -> Option<Vec<String>>;
+    /// Returns the process group in the foreground of the terminal `fd`, the one of its shell
+    /// unless the shell runs a program
+    fn get_foreground_process_group(&self, fd: RawFd) -> Option<Pid>;
+    /// Sends `signal` to the process `pid`, or to the process group `-pid` if it is negative
+    fn send_signal(&self, pid: Pid, signal: Signal);
+    /// Whether the process `pid` (or the process group `-pid`) is still running, reaping it if it
+    /// is a child of the server that exited
+    fn is_running(&self, pid: Pid) -> bool;
 }
 
 impl ServerOsApi for ServerOsInputOutput {
@@ -340,23 +396,26 @@ impl ServerOsApi for ServerOsInputOutput {
         let _ = kill(pid, Some(Signal::SIGKILL));
         Ok(())
     }
-    fn recv_from_client(&self) -> (ClientToServerMsg, ErrorContext) {
+    fn recv_from_client(&self) -> Option<(ClientToServerMsg, ErrorContext)> {
         self.receive_instructions_from_client
             .as_ref()
             .unwrap()
             .lock()
             .unwrap()
-            .recv()
+            .try_recv()
     }
-    fn send_to_client(&self, msg: ServerToClientMsg) {
-        self.send_instructions_to_client
-            .lock()
-            .unwrap()
-            .as_mut()
-            .unwrap()
-            .send(msg);
+    fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) {
+        let mut clients = self.send_instructions_to_clients.lock().unwrap();
+        let sent = clients
+            .senders
+            .get_mut(&client_id)
+            .map_or(true, |sender| sender.try_send(msg));
+        // its router hears about it too, and detaches it
+        if !sent {
+            clients.senders.remove(&client_id);
+        }
     }
-    fn add_client_sender(&self) {
+    fn add_client_sender(&self) -> ClientId {
         let sender = self
             .receive_instructions_from_client
             .as_ref()
@@ -364,14 +423,11 @@ impl ServerOsApi for ServerOsInputOutput {
             .lock()
             .unwrap()
             .get_sender();
-        let old_sender = self
-            .send_instructions_to_client
-            .lock()
-            .unwrap()
-            .replace(sender);
-        if let Some(mut sender) = old_sender {
-            sender.send(ServerToClientMsg::Exit(ExitReason::ForceDetached));
-        }
+        let mut clients = self.send_instructions_to_clients.lock().unwrap();
+        let client_id = clients.next_client_id;
+        clients.next_client_id += 1;
+        clients.senders.insert(client_id, sender);
+        client_id
     }
     fn send_to_temp_client(&self, msg: ServerToClientMsg) {
         self.receive_instructions_from_client
@@ -382,9 +438,12 @@ impl ServerOsApi for ServerOsInputOutput {
             .get_sender()
             .send(msg);
     }
-    fn remove_client_sender(&self) {
-        assert!(self.send_instructions_to_client.lock().unwrap().is_some());
-        *self.send_instructions_to_client.lock().unwrap() = None;
+    fn remove_client_sender(&self, client_id: ClientId) {
+        self.send_instructions_to_clients
+            .lock()
+            .unwrap()
+            .senders
+            .remove(&client_id);
     }
     fn update_receiver(&mut self, stream: LocalSocketStream) {
         self.receive_instructions_from_client =
@@ -405,6 +464,37 @@ impl ServerOsApi for ServerOsInputOutput {
     fn get_cwd(&self, _pid: Pid) -> Option<PathBuf> {
         None
     }
+    #[cfg(target_os = "linux")]
+    fn get_foreground_command(&self, fd: RawFd) -> Option<Vec<String>> {
+        // the leader of the foreground process group is the program that was started
+        let process_group = unistd::tcgetpgrp(fd).ok()?;
+        let cmdline = fs::read(format!("/proc/{}/cmdline", process_group)).ok()?;
+        Some(
+            cmdline
+                .split(|&byte| byte == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect(),
+        )
+    }
+    #[cfg(not(target_os = "linux"))]
+    fn get_foreground_command(&self, _fd: RawFd) -> Option<Vec<String>> {
+        None
+    }
+    fn get_foreground_process_group(&self, fd: RawFd) -> Option<Pid> {
+        unistd::tcgetpgrp(fd).ok()
+    }
+    fn send_signal(&self, pid: Pid, signal: Signal) {
+        let _ = kill(pid, Some(signal));
+    }
+    fn is_running(&self, pid: Pid) -> bool {
+        match waitpid(pid, Some(WaitPidFlag::WNOHANG)) {
+            Ok(WaitStatus::StillAlive) => true,
+            Ok(_) => false,
+            // not a child of the server, see whether it is there at all
+            Err(_) => kill(pid, None).is_ok(),
+        }
+    }
 }
 
 impl Clone for Box<dyn ServerOsApi> {
@@ -419,7 +509,7 @@ pub fn get_server_os_input() -> Result<ServerOsInputOutput, nix::Error> {
     Ok(ServerOsInputOutput {
         orig_termios,
         receive_instructions_from_client: None,
-        send_instructions_to_client: Arc::new(Mutex::new(None)),
+        send_instructions_to_clients: Arc::new(Mutex::new(ClientSenders::default())),
     })
 }
 
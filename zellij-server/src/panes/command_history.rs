@@ -0,0 +1,198 @@
+//! The commands run in the panes, as their shells mark them (OSC 133), and picking one of those
+//! of the whole session to run it again.
+
+use std::time::Instant;
+
+use crate::panes::{search::render_list, PaneId, SearchDirection};
+use zellij_utils::nix::unistd::gethostname;
+use zellij_utils::pane_size::Viewport;
+
+/// The most commands a pane remembers, the oldest are forgotten first.
+pub const MAX_COMMAND_HISTORY: usize = 100;
+
+/// A command run in a pane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub command: String,
+    /// The working directory of the shell when the command started, as it reports it (OSC 7)
+    pub cwd: Option<String>,
+    /// `None` while the command runs, or when the shell doesn't report it
+    pub exit_code: Option<i32>,
+    pub started: Instant,
+}
+
+/// A command of the session history, with the pane it was run in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryItem {
+    pub pane_id: PaneId,
+    pub pane_title: String,
+    pub entry: HistoryEntry,
+}
+
+/// Picking one of the commands run in any pane of the session by typing some of it.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryPicker {
+    pub filter: String,
+    // the commands, the most recent first
+    items: Vec<HistoryItem>,
+    // the indices of the items the filter matches
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+impl HistoryPicker {
+    /// Replaces the commands to pick from, keeping the most recent run of each command.
+    pub fn set_items(&mut self, mut items: Vec<HistoryItem>) {
+        items.sort_by(|a, b| b.entry.started.cmp(&a.entry.started));
+        let mut seen = vec![];
+        items.retain(|item| {
+            if seen.contains(&item.entry.command) {
+                false
+            } else {
+                seen.push(item.entry.command.clone());
+                true
+            }
+        });
+        self.items = items;
+        self.find_matches();
+    }
+    /// Types into the filter, a `0` clears it.
+    pub fn update_filter(&mut self, input: &str) {
+        match input {
+            "\0" => self.filter.clear(),
+            "\u{007F}" | "\u{0008}" => {
+                //delete and backspace keys
+                self.filter.pop();
+            }
+            c => self.filter.push_str(c),
+        }
+        self.find_matches();
+    }
+    // an item matches when its command has every word of the filter, ignoring case
+    fn find_matches(&mut self) {
+        let words: Vec<String> = self
+            .filter
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        self.matches = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                let command = item.entry.command.to_lowercase();
+                words.iter().all(|word| command.contains(word.as_str()))
+            })
+            .map(|(index, _)| index)
+            .collect();
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+    /// Selects the match above or below the selected one, wrapping around.
+    pub fn move_selection(&mut self, direction: SearchDirection) {
+        let count = self.matches.len();
+        self.selected = match direction {
+            SearchDirection::Up if self.selected > 0 => self.selected - 1,
+            SearchDirection::Up => count.saturating_sub(1),
+            SearchDirection::Down if self.selected + 1 < count => self.selected + 1,
+            SearchDirection::Down => 0,
+        };
+    }
+    pub fn selected_item(&self) -> Option<&HistoryItem> {
+        self.matches
+            .get(self.selected)
+            .and_then(|index| self.items.get(*index))
+    }
+    /// The filter and as many matching commands around the selected one as fit, drawn over the
+    /// top left corner of `viewport`.
+    pub fn render(&self, viewport: &Viewport) -> String {
+        let selected = if self.matches.is_empty() {
+            0
+        } else {
+            self.selected + 1
+        };
+        let header = format!(
+            " COMMAND HISTORY: {} {}/{} ",
+            self.filter,
+            selected,
+            self.matches.len()
+        );
+        let rows: Vec<String> = self
+            .matches
+            .iter()
+            .filter_map(|index| self.items.get(*index))
+            .map(|item| {
+                let exit_code = match item.entry.exit_code {
+                    Some(0) | None => String::new(),
+                    Some(code) => format!(" [{}]", code),
+                };
+                format!(
+                    " {}{}  ({}{})",
+                    item.entry.command,
+                    exit_code,
+                    item.pane_title,
+                    item.entry
+                        .cwd
+                        .as_ref()
+                        .map(|cwd| format!(", {}", cwd))
+                        .unwrap_or_default()
+                )
+            })
+            .collect();
+        render_list(viewport, &header, &rows, self.selected)
+    }
+}
+
+/// Whether the host of a `file://` URL is this machine: left out, `localhost` or its hostname
+/// (a shell on another machine over ssh reports its own).
+pub(crate) fn is_local_url(url: &str) -> bool {
+    let host = match url
+        .strip_prefix("file://")
+        .and_then(|rest| rest.split('/').next())
+    {
+        Some(host) => host,
+        None => return false,
+    };
+    if host.is_empty() || host == "localhost" {
+        return true;
+    }
+    let mut buffer = [0u8; 256];
+    gethostname(&mut buffer)
+        .ok()
+        .and_then(|hostname| hostname.to_str().ok())
+        == Some(host)
+}
+
+/// The working directory a shell reports with OSC 7, a `file://` URL whose host is left out.
+pub(crate) fn cwd_from_url(url: &str) -> Option<String> {
+    let path = url.strip_prefix("file://")?;
+    let path = &path[path.find('/')?..];
+    // the path is percent-encoded
+    let mut bytes = vec![];
+    let mut rest = path.as_bytes();
+    while let Some((byte, tail)) = rest.split_first() {
+        let decoded = match (byte, tail) {
+            (b'%', [high, low, ..]) => {
+                let hex = [*high, *low];
+                std::str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            }
+            _ => None,
+        };
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(*byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+#[path = "./unit/command_history_tests.rs"]
+mod command_history_tests;
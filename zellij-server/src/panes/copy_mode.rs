@@ -0,0 +1,88 @@
+//! Selecting the text of a pane with the keyboard, moving a cursor over its viewport and
+//! scrolling through its scrollback.
+
+use zellij_utils::input::actions::SelectionKind;
+use zellij_utils::position::{Column, Position};
+
+use super::selection::Selection;
+
+#[derive(Debug, Clone)]
+pub struct CopyMode {
+    /// Where the cursor is, relative to the top of the viewport as the positions of a [`Selection`]
+    pub cursor: Position,
+    /// Where the selection started and what it takes in, while selecting
+    anchor: Option<(Position, SelectionKind)>,
+}
+
+impl CopyMode {
+    pub fn new(cursor: Position) -> Self {
+        CopyMode {
+            cursor,
+            anchor: None,
+        }
+    }
+    // the text moved down in the viewport
+    pub fn move_down(&mut self, lines: usize) {
+        self.cursor.line.0 += lines as isize;
+        if let Some((anchor, _)) = self.anchor.as_mut() {
+            anchor.line.0 += lines as isize;
+        }
+    }
+    // the text moved up in the viewport
+    pub fn move_up(&mut self, lines: usize) {
+        self.cursor.line.0 -= lines as isize;
+        if let Some((anchor, _)) = self.anchor.as_mut() {
+            anchor.line.0 -= lines as isize;
+        }
+    }
+    /// Starts selecting at the cursor, stops if already selecting this kind, or changes the kind
+    /// of the selection from the same place.
+    pub fn toggle_selection(&mut self, kind: SelectionKind) {
+        self.anchor = match self.anchor {
+            Some((_, selected_kind)) if selected_kind == kind => None,
+            Some((anchor, _)) => Some((anchor, kind)),
+            None => Some((self.cursor, kind)),
+        };
+    }
+    pub fn clear_selection(&mut self) {
+        self.anchor = None;
+    }
+    /// The selection between where it started and the cursor. `word_at` gives the columns of the
+    /// first character of the word at a position and of the one after it, and `width` is the
+    /// width of the lines.
+    pub fn selection(
+        &self,
+        word_at: impl Fn(Position) -> (usize, usize),
+        width: usize,
+    ) -> Selection {
+        let (anchor, kind) = match self.anchor {
+            Some(anchor) => anchor,
+            None => return Selection::default(),
+        };
+        let (first, last) = if anchor <= self.cursor {
+            (anchor, self.cursor)
+        } else {
+            (self.cursor, anchor)
+        };
+        let at = |position: Position, column: usize| Position {
+            line: position.line,
+            column: Column(column),
+        };
+        match kind {
+            SelectionKind::Character => {
+                Selection::between(first, at(last, last.column.0 + 1), false)
+            }
+            SelectionKind::Word => {
+                let (start, _) = word_at(first);
+                let (_, end) = word_at(last);
+                Selection::between(at(first, start), at(last, end), false)
+            }
+            SelectionKind::Line => Selection::between(at(first, 0), at(last, width), false),
+            SelectionKind::Block => {
+                let left = anchor.column.0.min(self.cursor.column.0);
+                let right = anchor.column.0.max(self.cursor.column.0) + 1;
+                Selection::between(at(first, left), at(last, right), true)
+            }
+        }
+    }
+}
@@ -2,16 +2,25 @@ use unicode_width::UnicodeWidthChar;
 
 use std::{
     cmp::Ordering,
-    collections::{BTreeSet, VecDeque},
+    collections::{BTreeSet, HashMap, VecDeque},
     fmt::{self, Debug, Formatter},
-    str,
+    mem, str,
+    time::Instant,
 };
 
-use zellij_utils::{position::Position, vte, zellij_tile};
+use zellij_utils::{
+    input::{
+        actions::{CopyMotion, SelectionKind},
+        options::AmbiguousWidth,
+    },
+    position::{Column, Line, Position},
+    vte, zellij_tile,
+};
 
-const TABSTOP_WIDTH: usize = 8; // TODO: is this always right?
+pub const DEFAULT_TABSTOP_WIDTH: usize = 8;
 pub const SCROLL_BACK: usize = 10_000;
 pub const MAX_TITLE_STACK_SIZE: usize = 1000;
+pub const MAX_COMMAND_OUTPUT_SIZE: usize = 1_000_000; // in bytes, the rest is dropped
 
 use vte::{Params, Perform};
 use zellij_tile::data::{Palette, PaletteColor};
@@ -19,10 +28,13 @@ use zellij_utils::{consts::VERSION, logging::debug_log_to_file, shared::version_
 
 use crate::panes::alacritty_functions::{parse_number, xparse_color};
 use crate::panes::terminal_character::{
-    AnsiCode, CharacterStyles, CharsetIndex, Cursor, CursorShape, StandardCharset,
+    AnsiCode, CharacterStyles, CharsetIndex, Cursor, CursorShape, HardTab, StandardCharset,
     TerminalCharacter, EMPTY_TERMINAL_CHARACTER,
 };
 
+use super::command_history::{cwd_from_url, is_local_url, HistoryEntry, MAX_COMMAND_HISTORY};
+use super::copy_mode::CopyMode;
+use super::search::{Search, SearchDirection, SearchMatch, SearchOption};
 use super::selection::Selection;
 
 fn get_top_non_canonical_rows(rows: &mut Vec<Row>) -> Vec<Row> {
@@ -106,6 +118,7 @@ fn transfer_rows_from_lines_above_to_viewport(
     viewport: &mut Vec<Row>,
     count: usize,
     max_viewport_width: usize,
+    scroll_buffer_size: usize,
 ) {
     let mut next_lines: Vec<Row> = vec![];
     let mut lines_added_to_viewport: isize = 0;
@@ -135,7 +148,7 @@ fn transfer_rows_from_lines_above_to_viewport(
     }
     if !next_lines.is_empty() {
         let excess_row = Row::from_rows(next_lines, 0);
-        bounded_push(lines_above, excess_row);
+        bounded_push(lines_above, excess_row, scroll_buffer_size);
     }
 }
 
@@ -144,6 +157,7 @@ fn transfer_rows_from_viewport_to_lines_above(
     lines_above: &mut VecDeque<Row>,
     count: usize,
     max_viewport_width: usize,
+    scroll_buffer_size: usize,
 ) {
     let mut next_lines: Vec<Row> = vec![];
     for _ in 0..count {
@@ -161,7 +175,7 @@ fn transfer_rows_from_viewport_to_lines_above(
                 break; // no more rows
             }
         }
-        bounded_push(lines_above, next_lines.remove(0));
+        bounded_push(lines_above, next_lines.remove(0), scroll_buffer_size);
     }
     if !next_lines.is_empty() {
         let excess_rows = Row::from_rows(next_lines, max_viewport_width)
@@ -212,22 +226,140 @@ fn transfer_rows_from_lines_below_to_viewport(
     }
 }
 
-fn bounded_push(vec: &mut VecDeque<Row>, value: Row) {
-    if vec.len() >= SCROLL_BACK {
+/// The number of columns `c` takes up, characters of ambiguous East Asian width taking up as
+/// many as `ambiguous_width` says.
+fn character_width(c: char, ambiguous_width: AmbiguousWidth) -> usize {
+    match ambiguous_width {
+        AmbiguousWidth::Narrow => c.width(),
+        AmbiguousWidth::Wide => c.width_cjk(),
+    }
+    .unwrap_or(0)
+}
+
+/// The text of `rows`, wrapped rows joined with the row before them, without trailing blanks.
+fn plain_text<'a>(rows: impl Iterator<Item = &'a Row>) -> String {
+    let mut lines: Vec<String> = vec![];
+    for row in rows {
+        let text: String = row.columns.iter().map(|c| c.character).collect();
+        match lines.last_mut() {
+            Some(line) if !row.is_canonical => line.push_str(&text),
+            _ => lines.push(text),
+        }
+    }
+    let lines: Vec<&str> = lines.iter().map(|line| line.trim_end()).collect();
+    let mut output = lines.join("\n");
+    output.truncate(output.trim_end().len());
+    output.push('\n');
+    output
+}
+
+/// Joins the rows with the wraps that follow them, returning the characters of each line and the
+/// number of characters in each of its rows.
+fn join_wrapped_rows<'a>(
+    rows: impl Iterator<Item = &'a Row>,
+) -> (Vec<Vec<TerminalCharacter>>, Vec<Vec<usize>>) {
+    let mut lines: Vec<Vec<TerminalCharacter>> = vec![];
+    let mut row_lengths: Vec<Vec<usize>> = vec![];
+    for row in rows {
+        match (lines.last_mut(), row_lengths.last_mut()) {
+            (Some(line), Some(lengths)) if !row.is_canonical => {
+                line.extend(row.columns.iter().copied());
+                lengths.push(row.columns.len());
+            }
+            _ => {
+                lines.push(row.columns.clone());
+                row_lengths.push(vec![row.columns.len()]);
+            }
+        }
+    }
+    (lines, row_lengths)
+}
+
+// the column of the last character of a line that isn't blank
+fn line_end(cells: &[(usize, char)]) -> usize {
+    cells
+        .iter()
+        .rev()
+        .find(|(_, c)| !c.is_whitespace())
+        .map(|(x, _)| *x)
+        .unwrap_or(0)
+}
+
+// the number of rows it takes to show a row of the scrollback, which is kept unwrapped
+fn wrapped_height(row: &Row, width: usize) -> usize {
+    let width = width.max(1);
+    ((row.width() + width - 1) / width).max(1)
+}
+
+// the last line is always kept: it may be the start of a line wrapped into the viewport, which
+// scrolling and reflowing join back onto
+fn bounded_push(vec: &mut VecDeque<Row>, value: Row, max_len: usize) {
+    vec.push_back(value);
+    while vec.len() > max_len.max(1) {
         vec.pop_front();
     }
-    vec.push_back(value)
 }
 
-pub fn create_horizontal_tabstops(columns: usize) -> BTreeSet<usize> {
-    let mut i = TABSTOP_WIDTH;
+/// The rgb value xterm uses by default for the given 256 color index.
+fn default_indexed_color(index: u8) -> (u8, u8, u8) {
+    const ANSI_COLORS: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    match index {
+        0..=15 => ANSI_COLORS[index as usize],
+        16..=231 => {
+            let cube_level = |level: u8| if level == 0 { 0 } else { 55 + level * 40 };
+            let index = index - 16;
+            (
+                cube_level(index / 36),
+                cube_level((index / 6) % 6),
+                cube_level(index % 6),
+            )
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn palette_color_to_rgb(color: PaletteColor) -> (u8, u8, u8) {
+    match color {
+        PaletteColor::Rgb(rgb) => rgb,
+        PaletteColor::EightBit(index) => default_indexed_color(index),
+    }
+}
+
+/// The body of a reply to an OSC color query, eg. "rgb:ffff/0000/0000".
+fn color_query_response(rgb: (u8, u8, u8)) -> String {
+    let (r, g, b) = rgb;
+    format!("rgb:{0:02x}{0:02x}/{1:02x}{1:02x}/{2:02x}{2:02x}", r, g, b)
+}
+
+pub fn create_horizontal_tabstops(columns: usize, tabstop_width: usize) -> BTreeSet<usize> {
+    let mut i = tabstop_width;
     let mut horizontal_tabstops = BTreeSet::new();
     loop {
         if i > columns {
             break;
         }
         horizontal_tabstops.insert(i);
-        i += TABSTOP_WIDTH;
+        i += tabstop_width;
     }
     horizontal_tabstops
 }
@@ -339,6 +471,7 @@ pub struct Grid {
     viewport: Vec<Row>,
     lines_below: Vec<Row>,
     horizontal_tabstops: BTreeSet<usize>,
+    tabstop_width: usize,
     alternative_lines_above_viewport_and_cursor: Option<(VecDeque<Row>, Vec<Row>, Cursor)>,
     cursor: Cursor,
     saved_cursor_position: Option<Cursor>,
@@ -359,8 +492,36 @@ pub struct Grid {
     pub width: usize,
     pub height: usize,
     pub pending_messages_to_pty: Vec<Vec<u8>>,
+    pub pending_clipboard_write: Option<String>, // set by the app with OSC 52
+    pub pending_cwd: Option<String>,             // a new working directory reported with OSC 7
     pub selection: Selection,
     pub title: Option<String>,
+    pub copy_tabs_as_spaces: bool,
+    pub reflow_on_resize: bool,
+    pub accept_titles: bool,             // set by the app with OSC 0 and 2
+    pub redraw_in_place: bool, // rather than blanking the viewport before drawing it again
+    pub scroll_buffer_size: usize, // the most lines kept above the viewport
+    pub ambiguous_width: AmbiguousWidth, // of the characters printed from now on
+    search: Search,
+    copy_mode: Option<CopyMode>,
+    // the output of the running command and of the last one, as the shell marks it with OSC 133
+    command_output: Option<String>,
+    last_command_output: Option<String>,
+    // the column the command line starts at, after the prompt
+    prompt_end: Option<usize>,
+    // the working directory the shell reports with OSC 7
+    cwd: Option<String>,
+    command_history: VecDeque<HistoryEntry>,
+    // whether the running command is the last one of the history, an empty one is left out
+    command_in_history: bool,
+    // the targets of the hyperlinks printed with OSC 8, the characters of a link hold the index
+    // of its target
+    links: Vec<String>,
+    link_indices: HashMap<String, u16>,
+    // the link the characters printed now are part of
+    active_link: Option<u16>,
+    // the link under the mouse pointer, it is underlined
+    hovered_link: Option<u16>,
 }
 
 impl Debug for Grid {
@@ -382,7 +543,14 @@ impl Grid {
             lines_above: VecDeque::with_capacity(SCROLL_BACK),
             viewport: vec![Row::new(columns).canonical()],
             lines_below: vec![],
-            horizontal_tabstops: create_horizontal_tabstops(columns),
+            horizontal_tabstops: create_horizontal_tabstops(columns, DEFAULT_TABSTOP_WIDTH),
+            tabstop_width: DEFAULT_TABSTOP_WIDTH,
+            copy_tabs_as_spaces: false,
+            reflow_on_resize: true,
+            accept_titles: true,
+            redraw_in_place: false,
+            scroll_buffer_size: SCROLL_BACK,
+            ambiguous_width: AmbiguousWidth::Narrow,
             cursor: Cursor::new(0, 0),
             saved_cursor_position: None,
             scroll_region: None,
@@ -399,12 +567,26 @@ impl Grid {
             clear_viewport_before_rendering: false,
             active_charset: Default::default(),
             pending_messages_to_pty: vec![],
+            pending_clipboard_write: None,
+            pending_cwd: None,
             colors,
             output_buffer: Default::default(),
             selection: Default::default(),
             title_stack: vec![],
             title: None,
             changed_colors: None,
+            search: Search::default(),
+            copy_mode: None,
+            command_output: None,
+            last_command_output: None,
+            prompt_end: None,
+            cwd: None,
+            command_history: VecDeque::new(),
+            command_in_history: false,
+            links: vec![],
+            link_indices: HashMap::new(),
+            active_link: None,
+            hovered_link: None,
         }
     }
     pub fn render_full_viewport(&mut self) {
@@ -431,6 +613,25 @@ impl Grid {
         self.pad_current_line_until(self.cursor.x);
         self.output_buffer.update_line(self.cursor.y);
     }
+    // the blanks a tab character moved the cursor over remember that they were left by the tab,
+    // unless some of them are not blank
+    fn mark_hard_tab(&mut self, start: usize, end: usize) {
+        if let Some(row) = self.viewport.get_mut(self.cursor.y) {
+            let start = row.position_accounting_for_widechars(start);
+            let end = row.position_accounting_for_widechars(end);
+            if let Some(blanks) = row.columns.get_mut(start..end) {
+                if blanks.iter().all(|blank| blank.character == ' ') {
+                    for (i, blank) in blanks.iter_mut().enumerate() {
+                        blank.hard_tab = Some(if i == 0 {
+                            HardTab::Start
+                        } else {
+                            HardTab::Rest
+                        });
+                    }
+                }
+            }
+        }
+    }
     pub fn move_to_previous_tabstop(&mut self) {
         let mut previous_tabstop = None;
         for tabstop in self.horizontal_tabstops.iter() {
@@ -451,6 +652,9 @@ impl Grid {
     pub fn cursor_shape(&self) -> CursorShape {
         self.cursor.get_shape()
     }
+    pub fn is_alternate_mode_active(&self) -> bool {
+        self.alternative_lines_above_viewport_and_cursor.is_some()
+    }
     pub fn scrollback_position_and_length(&self) -> (usize, usize) {
         // (position, length)
         let mut scrollback_buffer_count = 0;
@@ -477,6 +681,30 @@ impl Grid {
     fn clear_all_tabstops(&mut self) {
         self.horizontal_tabstops.clear();
     }
+    pub fn set_tabstop_width(&mut self, tabstop_width: usize) {
+        self.tabstop_width = tabstop_width;
+        self.horizontal_tabstops = create_horizontal_tabstops(self.width, tabstop_width);
+    }
+    // the lines in the viewport keep their wrapping, losing what doesn't fit in the new width -
+    // the lines above are re-wrapped as they are scrolled back into view, like always
+    fn cut_off_lines_at(&mut self, new_columns: usize) {
+        for row in self.viewport.iter_mut() {
+            row.truncate(new_columns);
+        }
+        self.cursor.x = self.cursor.x.min(new_columns - 1);
+    }
+    // tab stops set by the application are kept as long as they fit, new columns get the default
+    // ones
+    fn resize_horizontal_tabstops(&mut self, new_columns: usize) {
+        let old_columns = self.width;
+        self.horizontal_tabstops
+            .retain(|&tabstop| tabstop <= new_columns);
+        self.horizontal_tabstops.extend(
+            create_horizontal_tabstops(new_columns, self.tabstop_width)
+                .into_iter()
+                .filter(|&tabstop| tabstop > old_columns),
+        );
+    }
     fn save_cursor_position(&mut self) {
         self.saved_cursor_position = Some(self.cursor.clone());
     }
@@ -545,9 +773,13 @@ impl Grid {
                 &mut self.viewport,
                 1,
                 self.width,
+                self.scroll_buffer_size,
             );
 
             self.selection.move_down(1);
+            if let Some(copy_mode) = self.copy_mode.as_mut() {
+                copy_mode.move_down(1);
+            }
         }
         self.output_buffer.update_all_lines();
     }
@@ -555,11 +787,19 @@ impl Grid {
         if !self.lines_below.is_empty() && self.viewport.len() == self.height {
             let mut line_to_push_up = self.viewport.remove(0);
             if line_to_push_up.is_canonical {
-                bounded_push(&mut self.lines_above, line_to_push_up);
+                bounded_push(
+                    &mut self.lines_above,
+                    line_to_push_up,
+                    self.scroll_buffer_size,
+                );
             } else {
                 let mut last_line_above = self.lines_above.pop_back().unwrap();
                 last_line_above.append(&mut line_to_push_up.columns);
-                bounded_push(&mut self.lines_above, last_line_above);
+                bounded_push(
+                    &mut self.lines_above,
+                    last_line_above,
+                    self.scroll_buffer_size,
+                );
             }
 
             transfer_rows_from_lines_below_to_viewport(
@@ -570,6 +810,9 @@ impl Grid {
             );
 
             self.selection.move_up(1);
+            if let Some(copy_mode) = self.copy_mode.as_mut() {
+                copy_mode.move_up(1);
+            }
             self.output_buffer.update_all_lines();
         }
     }
@@ -597,8 +840,11 @@ impl Grid {
             return;
         }
         self.selection.reset();
-        if new_columns != self.width {
-            self.horizontal_tabstops = create_horizontal_tabstops(new_columns);
+        if new_columns != self.width && !self.reflow_on_resize {
+            self.resize_horizontal_tabstops(new_columns);
+            self.cut_off_lines_at(new_columns);
+        } else if new_columns != self.width {
+            self.resize_horizontal_tabstops(new_columns);
             let mut cursor_canonical_line_index = self.cursor_canonical_line_index();
             let cursor_index_in_canonical_line = self.cursor_index_in_canonical_line();
             let mut viewport_canonical_lines = vec![];
@@ -673,6 +919,7 @@ impl Grid {
                         &mut self.viewport,
                         row_count_to_transfer,
                         new_columns,
+                        self.scroll_buffer_size,
                     );
                     let rows_pulled = self.viewport.len() - current_viewport_row_count;
                     new_cursor_y += rows_pulled;
@@ -689,6 +936,7 @@ impl Grid {
                         &mut self.lines_above,
                         row_count_to_transfer,
                         new_columns,
+                        self.scroll_buffer_size,
                     );
                 }
                 Ordering::Equal => {}
@@ -706,6 +954,7 @@ impl Grid {
                         &mut self.viewport,
                         row_count_to_transfer,
                         new_columns,
+                        self.scroll_buffer_size,
                     );
                     let rows_pulled = self.viewport.len() - current_viewport_row_count;
                     self.cursor.y += rows_pulled;
@@ -722,6 +971,7 @@ impl Grid {
                         &mut self.lines_above,
                         row_count_to_transfer,
                         new_columns,
+                        self.scroll_buffer_size,
                     );
                 }
                 Ordering::Equal => {}
@@ -735,9 +985,8 @@ impl Grid {
         self.output_buffer.update_all_lines();
     }
     pub fn as_character_lines(&self) -> Vec<Vec<TerminalCharacter>> {
-        // this is only used in the tests
-        // it's not part of testing the app, but rather is used to interpret the snapshots created
-        // by it
+        // this is used to interpret the snapshots created by the tests, as well as to look for
+        // quick copy hints in the viewport
         let mut lines: Vec<Vec<TerminalCharacter>> = self
             .viewport
             .iter()
@@ -766,6 +1015,15 @@ impl Grid {
         changes
     }
     pub fn cursor_coordinates(&self) -> Option<(usize, usize)> {
+        if let Some(copy_mode) = &self.copy_mode {
+            // the cursor of the copy mode is shown in place of the one of the terminal
+            let Position { line, column } = copy_mode.cursor;
+            return if line.0 >= 0 && (line.0 as usize) < self.height {
+                Some((column.0, line.0 as usize))
+            } else {
+                None
+            };
+        }
         if self.cursor.is_hidden {
             None
         } else {
@@ -870,8 +1128,12 @@ impl Grid {
                 &mut self.lines_above,
                 row_count_to_transfer,
                 self.width,
+                self.scroll_buffer_size,
             );
             self.selection.move_up(1);
+            if let Some(copy_mode) = self.copy_mode.as_mut() {
+                copy_mode.move_up(1);
+            }
             self.output_buffer.update_all_lines();
         } else {
             self.cursor.y += 1;
@@ -944,10 +1206,14 @@ impl Grid {
                     &mut self.lines_above,
                     row_count_to_transfer,
                     self.width,
+                    self.scroll_buffer_size,
                 );
                 let wrapped_row = Row::new(self.width);
                 self.viewport.push(wrapped_row);
                 self.selection.move_up(1);
+                if let Some(copy_mode) = self.copy_mode.as_mut() {
+                    copy_mode.move_up(1);
+                }
                 self.output_buffer.update_all_lines();
             } else {
                 self.cursor.y += 1;
@@ -1009,6 +1275,77 @@ impl Grid {
         }
         self.output_buffer.update_all_lines();
     }
+    /// The color a pane app gets for the given 256 color index: the one it set itself, or the
+    /// theme's.
+    fn indexed_color(&self, index: u8) -> (u8, u8, u8) {
+        let changed_color = self
+            .changed_colors
+            .and_then(|changed_colors| changed_colors[index as usize]);
+        match changed_color {
+            Some(AnsiCode::RgbCode(rgb)) => rgb,
+            Some(AnsiCode::ColorIndex(index)) => default_indexed_color(index),
+            _ => {
+                let theme_color = match index {
+                    0 | 8 => self.colors.black,
+                    1 | 9 => self.colors.red,
+                    2 | 10 => self.colors.green,
+                    3 | 11 => self.colors.yellow,
+                    4 | 12 => self.colors.blue,
+                    5 | 13 => self.colors.magenta,
+                    6 | 14 => self.colors.cyan,
+                    7 | 15 => self.colors.white,
+                    _ => PaletteColor::EightBit(index),
+                };
+                palette_color_to_rgb(theme_color)
+            }
+        }
+    }
+    /// Clears the viewport, moving the line the cursor is on (eg. a shell prompt) to the top.
+    /// The cleared lines go to the scrollback, unless `clear_scrollback` is set in which case the
+    /// scrollback is emptied as well.
+    pub fn clear_screen(&mut self, clear_scrollback: bool) {
+        if self.alternative_lines_above_viewport_and_cursor.is_some() {
+            // a fullscreen app owns the viewport, leave it alone
+            if clear_scrollback {
+                if let Some((alternative_lines_above, _, _)) =
+                    self.alternative_lines_above_viewport_and_cursor.as_mut()
+                {
+                    alternative_lines_above.clear();
+                }
+            }
+            return;
+        }
+        self.reset_viewport();
+        // the cursor can be moved below the last line inside a scroll region
+        self.pad_lines_until(self.cursor.y, EMPTY_TERMINAL_CHARACTER);
+        // keep the whole (possibly wrapped) line the cursor is on
+        let cursor_line_start = self.viewport[..=self.cursor.y]
+            .iter()
+            .rposition(|row| row.is_canonical)
+            .unwrap_or(0);
+        let cursor_line_end = self.viewport[self.cursor.y + 1..]
+            .iter()
+            .position(|row| row.is_canonical)
+            .map(|offset| self.cursor.y + 1 + offset)
+            .unwrap_or_else(|| self.viewport.len());
+        self.viewport.truncate(cursor_line_end);
+        let cleared_rows: Vec<Row> = self.viewport.drain(..cursor_line_start).collect();
+        if clear_scrollback {
+            self.lines_above.clear();
+        } else {
+            for row in cleared_rows {
+                bounded_push(&mut self.lines_above, row, self.scroll_buffer_size);
+            }
+        }
+        self.cursor.y -= cursor_line_start;
+        if let Some(saved_cursor_position) = self.saved_cursor_position.as_mut() {
+            saved_cursor_position.y = saved_cursor_position.y.saturating_sub(cursor_line_start);
+        }
+        self.selection.reset();
+        self.clear_viewport_before_rendering = true;
+        self.output_buffer.update_all_lines();
+        self.mark_for_rerender();
+    }
     fn pad_current_line_until(&mut self, position: usize) {
         let current_row = self.viewport.get_mut(self.cursor.y).unwrap();
         for _ in current_row.len()..position {
@@ -1219,6 +1556,7 @@ impl Grid {
         self.lines_above = VecDeque::with_capacity(SCROLL_BACK);
         self.lines_below = vec![];
         self.viewport = vec![Row::new(self.width).canonical()];
+        self.horizontal_tabstops = create_horizontal_tabstops(self.width, self.tabstop_width);
         self.alternative_lines_above_viewport_and_cursor = None;
         self.cursor_key_mode = false;
         self.scroll_region = None;
@@ -1231,6 +1569,7 @@ impl Grid {
         self.cursor.change_shape(CursorShape::Initial);
         self.output_buffer.update_all_lines();
         self.changed_colors = None;
+        self.active_link = None;
     }
     fn set_preceding_character(&mut self, terminal_character: TerminalCharacter) {
         self.preceding_char = Some(terminal_character);
@@ -1261,6 +1600,556 @@ impl Grid {
         self.update_selected_lines(&old_selection, &self.selection.clone());
         self.mark_for_rerender();
     }
+    /// Roughly the memory the lines scrolled out of the top of the viewport take up, in bytes.
+    pub fn scrollback_memory(&self) -> usize {
+        let characters: usize = self.lines_above.iter().map(|row| row.columns.len()).sum();
+        characters * mem::size_of::<TerminalCharacter>()
+    }
+    /// Drops the oldest lines of the scrollback until at least `bytes` are freed or none are
+    /// left, returning how many were freed.
+    pub fn trim_scrollback(&mut self, bytes: usize) -> usize {
+        let mut freed = 0;
+        while freed < bytes {
+            match self.lines_above.pop_front() {
+                Some(row) => freed += row.columns.len() * mem::size_of::<TerminalCharacter>(),
+                None => break,
+            }
+        }
+        freed
+    }
+    /// Returns all lines of the grid, scrollback included, with their ANSI styles.
+    pub fn dump_scrollback(&self) -> String {
+        self.dump_screen(true, true)
+    }
+    /// Returns all lines of the grid, scrollback included, as plain text without trailing
+    /// blanks.
+    pub fn dump_scrollback_text(&self) -> String {
+        self.dump_screen(true, false)
+    }
+    /// Returns the lines of the viewport, or of the whole grid when `full` is set, with their
+    /// ANSI styles when `ansi` is set or else as plain text without trailing blanks.
+    pub fn dump_screen(&self, full: bool, ansi: bool) -> String {
+        let rows: Box<dyn Iterator<Item = &Row> + '_> = if full {
+            Box::new(self.all_rows())
+        } else {
+            Box::new(self.viewport.iter())
+        };
+        if ansi {
+            self.styled_text(rows)
+        } else {
+            plain_text(rows)
+        }
+    }
+    /// The last `count` rows of the scrollback and viewport with their styles, leaving out the
+    /// empty rows at the bottom of the viewport.
+    pub fn dump_last_rows(&self, count: usize) -> String {
+        let rows: Vec<&Row> = self.all_rows().collect();
+        let end = match rows
+            .iter()
+            .rposition(|row| row.columns.iter().any(|c| c.character != ' '))
+        {
+            Some(last) => last + 1,
+            None => return String::new(),
+        };
+        self.styled_text(rows[end.saturating_sub(count)..end].iter().copied())
+    }
+    fn styled_text<'a>(&self, rows: impl Iterator<Item = &'a Row>) -> String {
+        let mut output = String::new();
+        let mut character_styles = CharacterStyles::new();
+        let mut last_styles: Option<String> = None;
+        for (i, row) in rows.enumerate() {
+            if i > 0 && row.is_canonical {
+                output.push('\n');
+            }
+            for t_character in row.columns.iter() {
+                if let Some(new_styles) = character_styles
+                    .update_and_return_diff(&t_character.styles, self.changed_colors)
+                {
+                    // a reset is reported as a diff again for every character that follows it
+                    let new_styles = new_styles.to_string();
+                    if last_styles.as_ref() != Some(&new_styles) {
+                        output.push_str(&new_styles);
+                        last_styles = Some(new_styles);
+                    }
+                }
+                output.push(t_character.character);
+            }
+        }
+        output.push_str("\u{1b}[m\n");
+        output
+    }
+    fn all_rows(&self) -> impl Iterator<Item = &Row> {
+        self.lines_above
+            .iter()
+            .chain(self.viewport.iter())
+            .chain(self.lines_below.iter())
+    }
+    /// Types into the text searched for in the grid, scrollback included, and scrolls to the
+    /// last match above the bottom of the viewport. A `0` clears the text.
+    pub fn update_search(&mut self, input: &str) {
+        self.search.update_needle(input);
+        let (lines, row_lengths) = join_wrapped_rows(self.all_rows());
+        self.search.find_matches(&lines);
+        let rows_until_viewport_end = self.lines_above.len() + self.viewport.len();
+        let mut rows = 0;
+        let lines_until_viewport_end = row_lengths
+            .iter()
+            .take_while(|lengths| {
+                rows += lengths.len();
+                rows <= rows_until_viewport_end
+            })
+            .count();
+        self.search.select_last_before(lines_until_viewport_end);
+        self.scroll_to_active_match(&lines, &row_lengths);
+    }
+    /// Makes the next match up or down the active one, scrolling to it.
+    pub fn search(&mut self, direction: SearchDirection) {
+        let (lines, row_lengths) = join_wrapped_rows(self.all_rows());
+        self.search.find_matches(&lines);
+        if self.search.active_match().is_none() {
+            // eg. the active match was dropped from the scrollback
+            self.search.select_last_before(lines.len());
+        } else {
+            self.search.move_active(direction);
+        }
+        self.scroll_to_active_match(&lines, &row_lengths);
+    }
+    pub fn toggle_search_option(&mut self, option: SearchOption) {
+        self.search.toggle(option);
+        if self.search.is_active() {
+            self.update_search("");
+        }
+    }
+    pub fn end_search(&mut self) {
+        if self.search.is_active() {
+            self.search.end();
+            self.output_buffer.update_all_lines();
+            self.should_render = true;
+        }
+    }
+    /// The line of each match of `search` in the grid, scrollback included, in order.
+    pub fn search_matches(&self, search: &Search) -> Vec<String> {
+        let (lines, _) = join_wrapped_rows(self.all_rows());
+        search
+            .matches_in(&lines)
+            .iter()
+            .map(|m| lines[m.line].iter().map(|c| c.character).collect())
+            .collect()
+    }
+    /// Searches the grid for what `search` searches and scrolls to the match with the given
+    /// index, as if it was reached by searching the grid.
+    pub fn show_search_match(&mut self, search: &Search, index: usize) {
+        self.search = search.clone();
+        let (lines, row_lengths) = join_wrapped_rows(self.all_rows());
+        self.search.find_matches(&lines);
+        self.search.select(index);
+        self.scroll_to_active_match(&lines, &row_lengths);
+    }
+    /// What the pane frame shows about the search, if searching.
+    pub fn search_indication(&self) -> Option<String> {
+        self.search.indication()
+    }
+    fn scroll_to_active_match(
+        &mut self,
+        lines: &[Vec<TerminalCharacter>],
+        row_lengths: &[Vec<usize>],
+    ) {
+        // the highlights move along with the text, so the whole viewport is drawn again
+        self.output_buffer.update_all_lines();
+        self.should_render = true;
+        let active_match = match self.search.active_match() {
+            Some(active_match) => active_match,
+            None => return,
+        };
+        let mut row: usize = row_lengths[..active_match.line]
+            .iter()
+            .map(|lengths| lengths.len())
+            .sum();
+        let mut row_start = 0;
+        for length in &row_lengths[active_match.line] {
+            if active_match.start < row_start + length {
+                break;
+            }
+            row_start += length;
+            row += 1;
+        }
+        let column: usize = lines[active_match.line][row_start..active_match.start]
+            .iter()
+            .map(|c| c.width)
+            .sum();
+        let viewport_start = self.lines_above.len();
+        let viewport_end = viewport_start + self.viewport.len();
+        if row < viewport_start {
+            let width = self.width;
+            let rows_above_match: usize = self
+                .lines_above
+                .iter()
+                .skip(row)
+                .map(|row| wrapped_height(row, width))
+                .sum();
+            self.move_viewport_up(rows_above_match.saturating_sub(column / width.max(1)));
+        } else if row >= viewport_end {
+            let width = self.width;
+            let rows_until_match: usize = self
+                .lines_below
+                .iter()
+                .take(row - viewport_end + 1)
+                .map(|row| wrapped_height(row, width))
+                .sum();
+            self.move_viewport_down(rows_until_match);
+        }
+    }
+    /// The parts of the viewport matching the search, each with whether it is the active match.
+    pub fn search_highlights(&self) -> Vec<(CharacterChunk, bool)> {
+        if !self.search.is_active() || self.viewport.is_empty() {
+            return vec![];
+        }
+        // the first line of the viewport can start in the scrollback
+        let mut first_row = self.lines_above.len();
+        if !self.viewport[0].is_canonical {
+            while first_row > 0 {
+                first_row -= 1;
+                if self.lines_above[first_row].is_canonical {
+                    break;
+                }
+            }
+        }
+        let rows_before_viewport = self.lines_above.len() - first_row;
+        let lines_before = self
+            .lines_above
+            .iter()
+            .take(first_row)
+            .enumerate()
+            .filter(|(i, row)| row.is_canonical || *i == 0)
+            .count();
+        let (lines, row_lengths) = join_wrapped_rows(
+            self.lines_above
+                .iter()
+                .skip(first_row)
+                .chain(self.viewport.iter()),
+        );
+        let mut line_first_rows = Vec::with_capacity(row_lengths.len());
+        let mut rows = 0;
+        for lengths in &row_lengths {
+            line_first_rows.push(rows);
+            rows += lengths.len();
+        }
+        let active_match = self.search.active_match();
+        let mut highlights = vec![];
+        for m in self.search.matches_in(&lines) {
+            let line = &lines[m.line];
+            let is_active = active_match
+                == Some(SearchMatch {
+                    line: m.line + lines_before,
+                    ..m
+                });
+            // a match can go on in the wraps of its line
+            let mut row_start = 0;
+            for (i, length) in row_lengths[m.line].iter().enumerate() {
+                let start = m.start.max(row_start);
+                let end = m.end.min(row_start + length);
+                let y = (line_first_rows[m.line] + i).checked_sub(rows_before_viewport);
+                match y {
+                    Some(y) if start < end && y < self.height => {
+                        let chunk = CharacterChunk {
+                            terminal_characters: line[start..end].to_vec(),
+                            x: line[row_start..start].iter().map(|c| c.width).sum(),
+                            y,
+                        };
+                        highlights.push((chunk, is_active));
+                    }
+                    _ => {}
+                }
+                row_start += length;
+            }
+        }
+        highlights
+    }
+    /// Starts selecting text with the keyboard, from the cursor of the terminal if it is in
+    /// view, or the start of the last line otherwise.
+    pub fn start_copy_mode(&mut self) {
+        let last_line = self.height.saturating_sub(1);
+        let cursor = if self.lines_below.is_empty() && self.cursor.y <= last_line {
+            Position::new(
+                self.cursor.y as i32,
+                self.cursor.x.min(self.width.saturating_sub(1)) as u16,
+            )
+        } else {
+            Position::new(last_line as i32, 0)
+        };
+        self.copy_mode = Some(CopyMode::new(cursor));
+        self.update_copy_selection();
+    }
+    pub fn end_copy_mode(&mut self) {
+        if self.copy_mode.take().is_some() {
+            self.selection.reset();
+            self.output_buffer.update_all_lines();
+            self.should_render = true;
+        }
+    }
+    /// Moves the cursor of the copy mode, scrolling when it goes past the top or the bottom of
+    /// the viewport.
+    pub fn move_copy_cursor(&mut self, motion: CopyMotion) {
+        let last_line = self.height.saturating_sub(1) as isize;
+        match (motion, self.copy_mode.as_ref().map(|c| c.cursor.line.0)) {
+            (CopyMotion::Up, Some(line)) if line <= 0 => self.move_viewport_up(1),
+            (CopyMotion::Down, Some(line)) if line >= last_line => self.move_viewport_down(1),
+            (_, None) => return,
+            _ => {}
+        }
+        // scrolling moved the cursor along with the text
+        let cursor = match &self.copy_mode {
+            Some(copy_mode) => copy_mode.cursor,
+            None => return,
+        };
+        let (line, column) = (cursor.line.0, cursor.column.0);
+        let cells = self.cells_of_line(line);
+        let (line, column) = match motion {
+            CopyMotion::Left => (line, column.saturating_sub(1)),
+            CopyMotion::Right => (line, (column + 1).min(self.width.saturating_sub(1))),
+            CopyMotion::Up => ((line - 1).max(0), column),
+            CopyMotion::Down => ((line + 1).min(last_line), column),
+            CopyMotion::NextWord => {
+                let next_word_start = cells.windows(2).find_map(|pair| match pair {
+                    [(_, previous), (x, c)]
+                        if *x > column && previous.is_whitespace() && !c.is_whitespace() =>
+                    {
+                        Some(*x)
+                    }
+                    _ => None,
+                });
+                (line, next_word_start.unwrap_or_else(|| line_end(&cells)))
+            }
+            CopyMotion::PreviousWord => {
+                let word_start = cells
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, (x, c))| {
+                        *x < column
+                            && !c.is_whitespace()
+                            && (*i == 0 || cells[i - 1].1.is_whitespace())
+                    })
+                    .map(|(_, (x, _))| *x)
+                    .last();
+                (line, word_start.unwrap_or(0))
+            }
+            CopyMotion::LineStart => (line, 0),
+            CopyMotion::LineEnd => (line, line_end(&cells)),
+        };
+        if let Some(copy_mode) = self.copy_mode.as_mut() {
+            copy_mode.cursor = Position {
+                line: Line(line),
+                column: Column(column),
+            };
+        }
+        self.update_copy_selection();
+    }
+    pub fn toggle_copy_selection(&mut self, kind: SelectionKind) {
+        if let Some(copy_mode) = self.copy_mode.as_mut() {
+            copy_mode.toggle_selection(kind);
+            self.update_copy_selection();
+        }
+    }
+    /// The text selected in copy mode, if any, ending the selection.
+    pub fn yank_copy_selection(&mut self) -> Option<String> {
+        let copy_mode = self.copy_mode.as_mut()?;
+        copy_mode.clear_selection();
+        let selected_text = self.get_selected_text();
+        self.update_copy_selection();
+        selected_text
+    }
+    fn update_copy_selection(&mut self) {
+        if let Some(copy_mode) = &self.copy_mode {
+            let selection = copy_mode.selection(|position| self.word_at(position), self.width);
+            self.selection = selection;
+            // a block can change on every one of its lines
+            self.output_buffer.update_all_lines();
+            self.should_render = true;
+        }
+    }
+    // the row shown at `line` of the viewport, or that would be shown there when scrolled
+    fn row_at_line(&self, line: isize) -> Option<&Row> {
+        if line < 0 {
+            let offset_from_end = line.abs() as usize;
+            self.lines_above
+                .len()
+                .checked_sub(offset_from_end)
+                .map(|index| &self.lines_above[index])
+        } else if (line as usize) < self.viewport.len() {
+            self.viewport.get(line as usize)
+        } else if (line as usize) < self.height {
+            None
+        } else {
+            self.lines_below.get(line as usize - self.viewport.len())
+        }
+    }
+    // the column each character of the row at `line` starts at, along with the character
+    fn cells_of_line(&self, line: isize) -> Vec<(usize, char)> {
+        let mut cells = vec![];
+        let mut x = 0;
+        if let Some(row) = self.row_at_line(line) {
+            for terminal_character in &row.columns {
+                cells.push((x, terminal_character.character));
+                x += terminal_character.width;
+            }
+        }
+        cells
+    }
+    // the columns of the first character of the word at `position` and of the one after it,
+    // the blank at `position` counting as a word of its own
+    fn word_at(&self, position: Position) -> (usize, usize) {
+        let cells = self.cells_of_line(position.line.0);
+        let column = position.column.0;
+        let end_of = |i: usize| cells.get(i + 1).map(|(x, _)| *x).unwrap_or(cells[i].0 + 1);
+        let index = match cells.iter().rposition(|(x, _)| *x <= column) {
+            Some(index) if column < end_of(index) => index,
+            _ => return (column, column + 1),
+        };
+        if cells[index].1.is_whitespace() {
+            return (cells[index].0, end_of(index));
+        }
+        let mut first = index;
+        while first > 0 && !cells[first - 1].1.is_whitespace() {
+            first -= 1;
+        }
+        let mut last = index;
+        while last + 1 < cells.len() && !cells[last + 1].1.is_whitespace() {
+            last += 1;
+        }
+        (cells[first].0, end_of(last))
+    }
+    // the characters printed next are part of the link to `target`, or of none if it is empty
+    fn start_link(&mut self, target: String) {
+        // the target is sent on to the terminal when rendering, it can't end the sequence early
+        if target.is_empty() || target.chars().any(char::is_control) {
+            self.active_link = None;
+            return;
+        }
+        // programs like `ls --hyperlink` print the same links over and over
+        self.active_link = match self.link_indices.get(&target) {
+            Some(index) => Some(*index),
+            None if self.links.len() <= u16::MAX as usize => {
+                let index = self.links.len() as u16;
+                self.links.push(target.clone());
+                self.link_indices.insert(target, index);
+                Some(index)
+            }
+            None => None,
+        };
+    }
+    fn link_index_at(&self, position: &Position) -> Option<u16> {
+        let row = self.row_at_line(position.line.0)?;
+        let column = position.column.0;
+        let mut x = 0;
+        for terminal_character in &row.columns {
+            if x <= column && column < x + terminal_character.width {
+                return terminal_character.link;
+            }
+            x += terminal_character.width;
+        }
+        None
+    }
+    /// The target of the hyperlink at `position`, if there is one.
+    pub fn link_at(&self, position: &Position) -> Option<&str> {
+        self.link_index_at(position)
+            .and_then(|index| self.link_target(index))
+    }
+    pub fn link_target(&self, index: u16) -> Option<&str> {
+        self.links.get(index as usize).map(String::as_str)
+    }
+    /// Underlines the hyperlink at `position`, or none when the mouse pointer isn't over the pane.
+    /// Returns whether that changed what is underlined.
+    pub fn hover(&mut self, position: Option<&Position>) -> bool {
+        let hovered_link = position.and_then(|position| self.link_index_at(position));
+        if hovered_link == self.hovered_link {
+            return false;
+        }
+        self.hovered_link = hovered_link;
+        self.render_full_viewport();
+        self.mark_for_rerender();
+        true
+    }
+    pub fn is_hovered_link(&self, link: Option<u16>) -> bool {
+        link.is_some() && link == self.hovered_link
+    }
+    fn capture_command_output(&mut self, c: char) {
+        if let Some(command_output) = self.command_output.as_mut() {
+            if command_output.len() < MAX_COMMAND_OUTPUT_SIZE {
+                command_output.push(c);
+            }
+        }
+    }
+    /// The text printed by the last command the shell marked, without trailing blanks.
+    pub fn last_command_output(&self) -> Option<String> {
+        let command_output = self.last_command_output.as_ref()?.trim_end();
+        if command_output.is_empty() {
+            None
+        } else {
+            Some(format!("{}\n", command_output))
+        }
+    }
+    /// The working directory the shell reported last with OSC 7.
+    pub fn cwd(&self) -> Option<&str> {
+        self.cwd.as_deref()
+    }
+    /// The commands the shell marked, the oldest first.
+    pub fn command_history(&self) -> Vec<HistoryEntry> {
+        self.command_history.iter().cloned().collect()
+    }
+    // the command line typed after the prompt, on the row the cursor left for the output of the
+    // command (or is still on) and the rows it wraps from
+    fn typed_command(&self) -> Option<String> {
+        let prompt_end = self.prompt_end?;
+        let mut last_line = self.cursor.y as isize;
+        if self.cursor.x == 0 {
+            last_line -= 1;
+        }
+        let mut first_line = last_line;
+        while !self.row_at_line(first_line)?.is_canonical {
+            first_line -= 1;
+        }
+        let command: String = (first_line..=last_line)
+            .flat_map(|line| {
+                self.cells_of_line(line)
+                    .into_iter()
+                    .filter(move |(x, _)| line > first_line || *x >= prompt_end)
+            })
+            .map(|(_, c)| c)
+            .collect();
+        let command = command.trim();
+        if command.is_empty() {
+            None
+        } else {
+            Some(command.to_owned())
+        }
+    }
+    fn start_command(&mut self) {
+        self.command_output = Some(String::new());
+        self.command_in_history = false;
+        if let Some(command) = self.typed_command() {
+            if self.command_history.len() == MAX_COMMAND_HISTORY {
+                self.command_history.pop_front();
+            }
+            self.command_history.push_back(HistoryEntry {
+                command,
+                cwd: self.cwd.clone(),
+                exit_code: None,
+                started: Instant::now(),
+            });
+            self.command_in_history = true;
+        }
+        self.prompt_end = None;
+    }
+    fn end_command(&mut self, exit_code: Option<i32>) {
+        if let Some(command_output) = self.command_output.take() {
+            self.last_command_output = Some(command_output);
+        }
+        if mem::replace(&mut self.command_in_history, false) {
+            if let Some(entry) = self.command_history.back_mut() {
+                entry.exit_code = exit_code;
+            }
+        }
+    }
     pub fn get_selected_text(&self) -> Option<String> {
         if self.selection.is_empty() {
             return None;
@@ -1274,11 +2163,16 @@ impl Grid {
             let mut line_selection = String::new();
 
             // on the first line of the selection, use the selection start column
-            // otherwise, start at the beginning of the line
-            let start_column = if l == start.line.0 { start.column.0 } else { 0 };
+            // otherwise, start at the beginning of the line (a block keeps its columns on all
+            // of its lines)
+            let start_column = if l == start.line.0 || sorted_selection.block {
+                start.column.0
+            } else {
+                0
+            };
 
             // same thing on the last line, but with the selection end column
-            let end_column = if l == end.line.0 {
+            let end_column = if l == end.line.0 || sorted_selection.block {
                 end.column.0
             } else {
                 self.width
@@ -1321,7 +2215,19 @@ impl Grid {
             let mut terminal_col = 0;
             for terminal_character in line {
                 if (start_column..end_column).contains(&terminal_col) {
-                    line_selection.push(terminal_character.character);
+                    match terminal_character.hard_tab {
+                        // the space left by a tab is copied as the tab, also when only part of
+                        // it is selected
+                        Some(HardTab::Start) if !self.copy_tabs_as_spaces => {
+                            line_selection.push('\t')
+                        }
+                        Some(HardTab::Rest) if !self.copy_tabs_as_spaces => {
+                            if terminal_col == start_column {
+                                line_selection.push('\t');
+                            }
+                        }
+                        _ => line_selection.push(terminal_character.character),
+                    }
                 }
 
                 terminal_col += terminal_character.width;
@@ -1363,11 +2269,14 @@ impl Perform for Grid {
         // is a little faster
         let terminal_character = TerminalCharacter {
             character: c,
-            width: c.width().unwrap_or(0),
+            width: character_width(c, self.ambiguous_width),
             styles: self.cursor.pending_styles,
+            hard_tab: None,
+            link: self.active_link,
         };
         self.set_preceding_character(terminal_character);
         self.add_character(terminal_character);
+        self.capture_command_output(c);
     }
 
     fn execute(&mut self, byte: u8) {
@@ -1378,13 +2287,17 @@ impl Perform for Grid {
             }
             9 => {
                 // tab
+                let tab_start = self.cursor.x;
                 self.advance_to_next_tabstop(self.cursor.pending_styles);
+                self.mark_hard_tab(tab_start, self.cursor.x);
+                self.capture_command_output('\t');
             }
             10 | 11 | 12 => {
                 // 0a, newline
                 // 0b, vertical tabulation
                 // 0c, form feed
                 self.add_newline();
+                self.capture_command_output('\n');
             }
             13 => {
                 // 0d, carriage return
@@ -1422,7 +2335,7 @@ impl Perform for Grid {
         match params[0] {
             // Set window title.
             b"0" | b"2" => {
-                if params.len() >= 2 {
+                if params.len() >= 2 && self.accept_titles {
                     let title = params[1..]
                         .iter()
                         .flat_map(|x| str::from_utf8(x))
@@ -1434,11 +2347,22 @@ impl Perform for Grid {
                 }
             }
 
-            // Set color index.
+            // Get/set color index.
             b"4" => {
                 for chunk in params[1..].chunks(2) {
                     let index = parse_number(chunk[0]);
-                    let color = xparse_color(chunk[1]);
+                    if let (Some(i), Some(b"?")) = (index, chunk.get(1).copied()) {
+                        let color_response_message = format!(
+                            "\u{1b}]4;{};{}{}",
+                            i,
+                            color_query_response(self.indexed_color(i)),
+                            terminator
+                        );
+                        self.pending_messages_to_pty
+                            .push(color_response_message.as_bytes().to_vec());
+                        continue;
+                    }
+                    let color = chunk.get(1).and_then(|color| xparse_color(color));
                     if let (Some(i), Some(c)) = (index, color) {
                         if self.changed_colors.is_none() {
                             self.changed_colors = Some([None; 256]);
@@ -1457,22 +2381,17 @@ impl Perform for Grid {
                             // currently only getting the color sequence is supported,
                             // setting still isn't
                             if param == b"?" {
-                                let color_response_message = match self.colors.bg {
-                                    PaletteColor::Rgb((r, g, b)) => {
-                                        format!(
-                                            "\u{1b}]{};rgb:{1:02x}{1:02x}/{2:02x}{2:02x}/{3:02x}{3:02x}{4}",
-                                            // dynamic_code, color.r, color.g, color.b, terminator
-                                            dynamic_code, r, g, b, terminator
-                                        )
-                                    }
-                                    _ => {
-                                        format!(
-                                            "\u{1b}]{};rgb:{1:02x}{1:02x}/{2:02x}{2:02x}/{3:02x}{3:02x}{4}",
-                                            // dynamic_code, color.r, color.g, color.b, terminator
-                                            dynamic_code, 0, 0, 0, terminator
-                                        )
-                                    }
+                                // the cursor is drawn in the foreground color
+                                let color = match dynamic_code {
+                                    11 => self.colors.bg,
+                                    _ => self.colors.fg,
                                 };
+                                let color_response_message = format!(
+                                    "\u{1b}]{};{}{}",
+                                    dynamic_code,
+                                    color_query_response(palette_color_to_rgb(color)),
+                                    terminator
+                                );
                                 self.pending_messages_to_pty
                                     .push(color_response_message.as_bytes().to_vec());
                             }
@@ -1512,12 +2431,32 @@ impl Perform for Grid {
                     b"?" => {
                         // TBD: paste from own clipboard - currently unsupported
                     }
-                    _base64 => {
-                        // TBD: copy to own clipboard - currently unsupported
+                    base64_text => {
+                        // whether this is honored is up to the tab's clipboard policy
+                        match base64::decode(base64_text) {
+                            Ok(text) => {
+                                self.pending_clipboard_write =
+                                    Some(String::from_utf8_lossy(&text).into_owned());
+                            }
+                            Err(e) => log::warn!("Invalid OSC 52 clipboard content: {}", e),
+                        }
                     }
                 }
             }
 
+            // Hyperlink, the parameters before the target (like its `id`) aren't used.
+            b"8" => {
+                if params.len() >= 3 {
+                    // the target can have semicolons of its own
+                    let target = params[2..]
+                        .iter()
+                        .map(|part| String::from_utf8_lossy(part))
+                        .collect::<Vec<_>>()
+                        .join(";");
+                    self.start_link(target);
+                }
+            }
+
             // Reset color index.
             b"104" => {
                 // Reset all color indexes when no parameters are given.
@@ -1549,6 +2488,36 @@ impl Perform for Grid {
                 }
             }
 
+            // Semantic prompt marks, the command line starts at B, the output of the command
+            // starts at C and ends at D (which can come with the exit code), or at the next prompt
+            // (A) if the shell doesn't send D.
+            b"133" => match params.get(1).and_then(|mark| mark.first()) {
+                Some(b'B') => self.prompt_end = Some(self.cursor.x),
+                Some(b'C') => self.start_command(),
+                Some(b'D') => {
+                    let exit_code = params
+                        .get(2)
+                        .and_then(|code| str::from_utf8(code).ok())
+                        .and_then(|code| code.parse().ok());
+                    self.end_command(exit_code);
+                }
+                Some(b'A') => self.end_command(None),
+                _ => {}
+            },
+
+            // Current working directory, as a `file://` URL.
+            b"7" => {
+                if let Some(url) = params.get(1).and_then(|url| str::from_utf8(url).ok()) {
+                    if let Some(cwd) = cwd_from_url(url) {
+                        // new panes can't start in the directory of another machine
+                        if self.cwd.as_ref() != Some(&cwd) && is_local_url(url) {
+                            self.pending_cwd = Some(cwd.clone());
+                        }
+                        self.cwd = Some(cwd);
+                    }
+                }
+            }
+
             // Reset foreground color.
             b"110" => {
                 // TBD - reset foreground color - currently unimplemented
@@ -1847,6 +2816,22 @@ impl Perform for Grid {
             for _ in 0..next_param_or(1) {
                 self.move_to_previous_tabstop();
             }
+        } else if c == 'W' {
+            if intermediates.get(0) == Some(&b'?') {
+                // DECST8C - reset the tab stops to every 8 columns
+                if next_param_or(0) == 5 {
+                    self.horizontal_tabstops =
+                        create_horizontal_tabstops(self.width, DEFAULT_TABSTOP_WIDTH);
+                }
+            } else {
+                // CTC - cursor tabulation control
+                match next_param_or(0) {
+                    0 => self.set_horizontal_tabstop(),
+                    2 => self.clear_tabstop(self.cursor.x),
+                    5 => self.clear_all_tabstops(),
+                    _ => {}
+                }
+            }
         } else if c == 'c' {
             // identify terminal
             // https://vt100.net/docs/vt510-rm/DA1.html
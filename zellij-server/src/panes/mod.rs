@@ -1,12 +1,22 @@
 mod alacritty_functions;
+mod command_history;
+mod copy_mode;
 mod grid;
 mod plugin_pane;
+mod quick_copy;
+mod search;
 mod selection;
 mod terminal_character;
 mod terminal_pane;
+mod timer;
 
 pub use alacritty_functions::*;
+pub use command_history::{HistoryEntry, HistoryItem, HistoryPicker};
 pub use grid::*;
 pub(crate) use plugin_pane::*;
+pub use quick_copy::*;
+pub(crate) use search::render_list;
+pub use search::{Search, SearchDirection, SearchOption, TabSearch, TabSearchResult};
 pub use terminal_character::*;
 pub use terminal_pane::*;
+pub use timer::{run_on_elapsed, RunningTimer};
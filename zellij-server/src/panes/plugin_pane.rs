@@ -11,8 +11,7 @@ use zellij_utils::pane_size::Offset;
 use zellij_utils::shared::ansi_len;
 use zellij_utils::zellij_tile::prelude::PaletteColor;
 use zellij_utils::{
-    channels::SenderWithContext,
-    pane_size::{Dimension, PaneGeom},
+    channels::SenderWithContext, input::options::PaneFrameStyle, pane_size::PaneGeom,
 };
 
 pub(crate) struct PluginPane {
@@ -25,9 +24,13 @@ pub(crate) struct PluginPane {
     pub send_plugin_instructions: SenderWithContext<PluginInstruction>,
     pub active_at: Instant,
     pub pane_title: String,
+    pane_name: String, // given by the user, shown instead of the title
     frame: bool,
     frame_color: Option<PaletteColor>,
     borderless: bool,
+    read_only: bool,
+    fullscreen: bool,
+    frame_style: PaneFrameStyle,
 }
 
 impl PluginPane {
@@ -49,7 +52,11 @@ impl PluginPane {
             frame_color: None,
             content_offset: Offset::default(),
             pane_title: title,
+            pane_name: String::new(),
             borderless: false,
+            read_only: false,
+            fullscreen: false,
+            frame_style: PaneFrameStyle::default(),
         }
     }
 }
@@ -156,8 +163,11 @@ impl Pane for PluginPane {
             if self.frame && !(self.geom.rows.is_fixed() || self.geom.cols.is_fixed()) {
                 let frame = PaneFrame {
                     geom: self.current_geom().into(),
-                    title: self.pane_title.clone(),
+                    title: self.title(),
                     color: self.frame_color,
+                    read_only: self.read_only,
+                    fullscreen: self.fullscreen,
+                    style: self.frame_style,
                     ..Default::default()
                 };
                 vte_output.push_str(&frame.render());
@@ -216,25 +226,25 @@ impl Pane for PluginPane {
     }
     fn reduce_height(&mut self, percent: f64) {
         if let Some(p) = self.geom.rows.as_percent() {
-            self.geom.rows = Dimension::percent(p - percent);
+            self.geom.rows.set_percent(p - percent);
             self.should_render = true;
         }
     }
     fn increase_height(&mut self, percent: f64) {
         if let Some(p) = self.geom.rows.as_percent() {
-            self.geom.rows = Dimension::percent(p + percent);
+            self.geom.rows.set_percent(p + percent);
             self.should_render = true;
         }
     }
     fn reduce_width(&mut self, percent: f64) {
         if let Some(p) = self.geom.cols.as_percent() {
-            self.geom.cols = Dimension::percent(p - percent);
+            self.geom.cols.set_percent(p - percent);
             self.should_render = true;
         }
     }
     fn increase_width(&mut self, percent: f64) {
         if let Some(p) = self.geom.cols.as_percent() {
-            self.geom.cols = Dimension::percent(p + percent);
+            self.geom.cols.set_percent(p + percent);
             self.should_render = true;
         }
     }
@@ -287,4 +297,46 @@ impl Pane for PluginPane {
     fn borderless(&self) -> bool {
         self.borderless
     }
+    fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+        self.should_render = true;
+    }
+    fn read_only(&self) -> bool {
+        self.read_only
+    }
+    fn set_frame_style(&mut self, style: PaneFrameStyle) {
+        if self.frame_style != style {
+            self.frame_style = style;
+            self.should_render = true;
+        }
+    }
+    fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen;
+        self.should_render = true;
+    }
+    fn title(&self) -> String {
+        if self.pane_name.is_empty() {
+            self.pane_title.clone()
+        } else {
+            self.pane_name.clone()
+        }
+    }
+    fn custom_title(&self) -> Option<String> {
+        Some(self.pane_name.clone()).filter(|name| !name.is_empty())
+    }
+    fn update_name(&mut self, name: &str) {
+        match name {
+            "\0" => {
+                self.pane_name = String::new();
+            }
+            "\u{007F}" | "\u{0008}" => {
+                //delete and backspace keys
+                self.pane_name.pop();
+            }
+            c => {
+                self.pane_name.push_str(c);
+            }
+        }
+        self.should_render = true;
+    }
 }
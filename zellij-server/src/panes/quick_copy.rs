@@ -0,0 +1,223 @@
+//! Hints for quickly copying the text matching one of a set of patterns (eg. hashes or urls)
+//! from the viewport of a pane, by typing the label shown next to it, or for opening the links in
+//! it the same way.
+
+use regex::Regex;
+
+use crate::panes::terminal_character::TerminalCharacter;
+
+const LABEL_ALPHABET: &str = "asdfghjklqwertyuiopzxcvbnm";
+
+const DEFAULT_PATTERNS: &[&str] = &[
+    // urls
+    r"(https?://|git@|git://|ssh://|ftp://|file:///)[^\s\x22'<>]+",
+    // uuids
+    r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}",
+    // ipv4 addresses
+    r"\b\d{1,3}\.\d{1,3}\.\d{1,3}\.\d{1,3}\b",
+    // sha hashes
+    r"\b[0-9a-f]{7,64}\b",
+];
+
+const DEFAULT_LINK_PATTERNS: &[&str] = &[
+    // urls
+    r"(https?://|ftp://|file:///)[^\s\x22'<>]+",
+    // absolute paths, the ones in the middle of a word (eg. `a/b`) aren't
+    r"(?:^|[\s\x22'(=:])(?P<hint>(?:/[\w.~@+-]+)+/?)",
+];
+
+/// Compiles the given patterns, falling back to the default ones if there are none.
+/// Invalid patterns are logged and skipped.
+pub fn compile_quick_copy_patterns(patterns: Option<&[String]>) -> Vec<Regex> {
+    compile_patterns(patterns, DEFAULT_PATTERNS, "quick copy")
+}
+
+/// Compiles the given patterns for the links offered by link hints, falling back to urls and
+/// absolute paths if there are none.
+pub fn compile_link_hint_patterns(patterns: Option<&[String]>) -> Vec<Regex> {
+    compile_patterns(patterns, DEFAULT_LINK_PATTERNS, "link hint")
+}
+
+fn compile_patterns(patterns: Option<&[String]>, defaults: &[&str], kind: &str) -> Vec<Regex> {
+    let default_patterns: Vec<String>;
+    let patterns = match patterns {
+        Some(patterns) => patterns,
+        None => {
+            default_patterns = defaults.iter().map(|p| p.to_string()).collect();
+            &default_patterns
+        }
+    };
+    patterns
+        .iter()
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                log::error!("Invalid {} pattern {:?}: {}", kind, pattern, e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickCopyHint {
+    pub label: String,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuickCopyInput {
+    /// The typed keys are the start of a label, wait for more
+    Pending,
+    /// The typed keys picked the hint with this text
+    Copy(String),
+    /// The typed keys picked the link hint with this text
+    Open(String),
+    /// The typed keys don't match any label
+    Cancel,
+}
+
+#[derive(Debug, Clone)]
+pub struct QuickCopy {
+    hints: Vec<QuickCopyHint>,
+    typed: String,
+    opens_links: bool,
+    copy_link: bool, // a link hint label was typed in capitals
+}
+
+impl QuickCopy {
+    pub fn new(lines: &[Vec<TerminalCharacter>], patterns: &[Regex]) -> Self {
+        let mut matches = vec![];
+        for (line_index, line) in lines.iter().enumerate() {
+            let mut text = String::new();
+            // the column of the character each byte of `text` belongs to
+            let mut byte_columns = vec![];
+            let mut column = 0;
+            for character in line {
+                text.push(character.character);
+                byte_columns.resize(text.len(), column);
+                column += character.width;
+            }
+            byte_columns.push(column);
+
+            // the text of a match is its `hint` group, if the pattern has one
+            let mut line_matches: Vec<(usize, usize)> = patterns
+                .iter()
+                .flat_map(|pattern| pattern.captures_iter(&text))
+                .filter_map(|captures| captures.name("hint").or_else(|| captures.get(0)))
+                .map(|m| (m.start(), m.end()))
+                .collect();
+            // prefer the longest of the matches starting at the same place, and drop overlaps
+            line_matches.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+            let mut last_end = 0;
+            for (start, end) in line_matches {
+                if start < last_end {
+                    continue;
+                }
+                last_end = end;
+                matches.push((
+                    line_index,
+                    byte_columns[start],
+                    text[start..end].to_string(),
+                ));
+            }
+        }
+        let labels = labels(matches.len());
+        let hints = matches
+            .into_iter()
+            .zip(labels)
+            .map(|((line, column, text), label)| QuickCopyHint {
+                label,
+                line,
+                column,
+                text,
+            })
+            .collect();
+        QuickCopy {
+            hints,
+            typed: String::new(),
+            opens_links: false,
+            copy_link: false,
+        }
+    }
+    /// Hints for the links matching `patterns`, typing a label opens its link, or copies it when
+    /// typed in capitals.
+    pub fn for_links(lines: &[Vec<TerminalCharacter>], patterns: &[Regex]) -> Self {
+        QuickCopy {
+            opens_links: true,
+            ..QuickCopy::new(lines, patterns)
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.hints.is_empty()
+    }
+    /// The hints whose label starts with what was typed so far.
+    pub fn hints(&self) -> impl Iterator<Item = &QuickCopyHint> {
+        let typed = &self.typed;
+        self.hints
+            .iter()
+            .filter(move |h| h.label.starts_with(typed))
+    }
+    pub fn handle_input(&mut self, input_bytes: &[u8]) -> QuickCopyInput {
+        for &byte in input_bytes {
+            let mut character = byte as char;
+            if self.opens_links && character.is_ascii_uppercase() {
+                character = character.to_ascii_lowercase();
+                self.copy_link = true;
+            }
+            if !LABEL_ALPHABET.contains(character) {
+                return QuickCopyInput::Cancel;
+            }
+            self.typed.push(character);
+            if let Some(hint) = self.hints.iter().find(|h| h.label == self.typed) {
+                return if self.opens_links && !self.copy_link {
+                    QuickCopyInput::Open(hint.text.clone())
+                } else {
+                    QuickCopyInput::Copy(hint.text.clone())
+                };
+            }
+            if self.hints().next().is_none() {
+                return QuickCopyInput::Cancel;
+            }
+        }
+        QuickCopyInput::Pending
+    }
+    /// Renders the labels of the hints on top of the pane content starting at (`x`, `y`).
+    pub fn render(&self, x: usize, y: usize) -> String {
+        let mut vte_output = String::new();
+        for hint in self.hints() {
+            vte_output.push_str(&format!(
+                "\u{1b}[{};{}H\u{1b}[m\u{1b}[1;30;43m{}\u{1b}[m",
+                y + hint.line + 1,
+                x + hint.column + 1,
+                hint.label
+            )); // goto row/col, then draw the label in black on yellow
+        }
+        vte_output
+    }
+}
+
+/// Labels for `count` hints, single letters when they are enough, otherwise two letters so
+/// that no label is the prefix of another.
+fn labels(count: usize) -> Vec<String> {
+    let alphabet: Vec<char> = LABEL_ALPHABET.chars().collect();
+    if count <= alphabet.len() {
+        alphabet.iter().take(count).map(|c| c.to_string()).collect()
+    } else {
+        alphabet
+            .iter()
+            .flat_map(|first| {
+                alphabet
+                    .iter()
+                    .map(move |second| format!("{}{}", first, second))
+            })
+            .take(count)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/quick_copy_tests.rs"]
+mod quick_copy_tests;
@@ -0,0 +1,292 @@
+//! Searching the scrollback and viewport of a pane for some text, moving from one match to the
+//! next.
+
+use std::iter;
+
+use crate::panes::{terminal_character::TerminalCharacter, PaneId};
+use zellij_utils::pane_size::Viewport;
+
+// the lists drawn over the panes (like the results of a tab search) are no wider than this, and
+// show at most this many items
+const LIST_MAX_WIDTH: usize = 100;
+const LIST_MAX_ITEMS: usize = 10;
+
+/// Which way to move from the active match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchDirection {
+    /// Towards the top of the scrollback
+    Up,
+    /// Towards the bottom of the viewport
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchOption {
+    CaseSensitivity,
+    Wrap,
+}
+
+/// A match of the searched text, as the indices of its first character and of the one after its
+/// last in one of the searched lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchMatch {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Search {
+    /// The text searched for, `None` when not searching
+    pub needle: Option<String>,
+    pub case_sensitive: bool,
+    /// Whether moving past the last match goes back to the first one, and the other way around
+    pub wrap: bool,
+    matches: Vec<SearchMatch>,
+    active: Option<usize>,
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Search {
+            needle: None,
+            case_sensitive: false,
+            wrap: true,
+            matches: vec![],
+            active: None,
+        }
+    }
+}
+
+impl Search {
+    pub fn is_active(&self) -> bool {
+        self.needle.is_some()
+    }
+    /// Types into the searched text, a `0` clears it.
+    pub fn update_needle(&mut self, input: &str) {
+        let needle = self.needle.get_or_insert_with(String::new);
+        match input {
+            "\0" => {
+                needle.clear();
+            }
+            "\u{007F}" | "\u{0008}" => {
+                //delete and backspace keys
+                needle.pop();
+            }
+            c => {
+                needle.push_str(c);
+            }
+        }
+    }
+    pub fn end(&mut self) {
+        self.needle = None;
+        self.matches.clear();
+        self.active = None;
+    }
+    pub fn toggle(&mut self, option: SearchOption) {
+        match option {
+            SearchOption::CaseSensitivity => self.case_sensitive = !self.case_sensitive,
+            SearchOption::Wrap => self.wrap = !self.wrap,
+        }
+    }
+    /// Looks for the matches in `lines` again, keeping the active match if it is still there.
+    pub fn find_matches(&mut self, lines: &[Vec<TerminalCharacter>]) {
+        let active_match = self.active_match();
+        self.matches = self.matches_in(lines);
+        self.active = active_match.and_then(|m| self.matches.iter().position(|n| *n == m));
+    }
+    /// The matches in `lines`, which don't have to be the lines searched by
+    /// [`Search::find_matches`].
+    pub fn matches_in(&self, lines: &[Vec<TerminalCharacter>]) -> Vec<SearchMatch> {
+        let needle = match self.needle.as_deref() {
+            Some(needle) if !needle.is_empty() => needle,
+            _ => return vec![],
+        };
+        let case_sensitive = self.case_sensitive;
+        let fold = |c: char| {
+            if case_sensitive {
+                c
+            } else {
+                c.to_lowercase().next().unwrap_or(c)
+            }
+        };
+        let needle: Vec<char> = needle.chars().map(fold).collect();
+        let mut matches = vec![];
+        for (line_index, line) in lines.iter().enumerate() {
+            let haystack: Vec<char> = line.iter().map(|c| fold(c.character)).collect();
+            let mut start = 0;
+            while start + needle.len() <= haystack.len() {
+                if haystack[start..start + needle.len()] == needle[..] {
+                    matches.push(SearchMatch {
+                        line: line_index,
+                        start,
+                        end: start + needle.len(),
+                    });
+                    start += needle.len();
+                } else {
+                    start += 1;
+                }
+            }
+        }
+        matches
+    }
+    /// Makes the match with the given index the active one, if there is one.
+    pub fn select(&mut self, index: usize) {
+        if index < self.matches.len() {
+            self.active = Some(index);
+        }
+    }
+    pub fn active_match(&self) -> Option<SearchMatch> {
+        self.active
+            .and_then(|active| self.matches.get(active))
+            .copied()
+    }
+    /// Makes the last match starting before line `line` the active one, or the first match if
+    /// they all come after it.
+    pub fn select_last_before(&mut self, line: usize) {
+        if self.matches.is_empty() {
+            self.active = None;
+        } else {
+            let matches_before = self.matches.iter().filter(|m| m.line < line).count();
+            self.active = Some(matches_before.saturating_sub(1));
+        }
+    }
+    /// Moves the active match one match up or down, returns whether it moved.
+    pub fn move_active(&mut self, direction: SearchDirection) -> bool {
+        let count = self.matches.len();
+        let active = match self.active {
+            Some(active) => active,
+            None => return false,
+        };
+        let next = match direction {
+            SearchDirection::Up if active > 0 => Some(active - 1),
+            SearchDirection::Up if self.wrap => Some(count - 1),
+            SearchDirection::Down if active + 1 < count => Some(active + 1),
+            SearchDirection::Down if self.wrap => Some(0),
+            _ => None,
+        };
+        match next {
+            Some(next) if next != active => {
+                self.active = Some(next);
+                true
+            }
+            _ => false,
+        }
+    }
+    /// What the pane frame shows about the search, eg. ` SEARCH: foo 2/5 [wrap] `.
+    pub fn indication(&self) -> Option<String> {
+        let needle = self.needle.as_ref()?;
+        let active = self.active.map(|active| active + 1).unwrap_or(0);
+        Some(format!(
+            " SEARCH: {} {}/{}{}{} ",
+            needle,
+            active,
+            self.matches.len(),
+            if self.case_sensitive { " [case]" } else { "" },
+            if self.wrap { " [wrap]" } else { "" },
+        ))
+    }
+}
+
+/// A match found by searching all the panes of a tab.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabSearchResult {
+    pub pane_id: PaneId,
+    pub pane_title: String,
+    /// The index of the match among those of its pane
+    pub index: usize,
+    /// The line the match is in
+    pub line: String,
+}
+
+/// Searching all the panes of a tab at once, to pick one of the matches and jump to it.
+#[derive(Debug, Clone, Default)]
+pub struct TabSearch {
+    pub search: Search,
+    pub results: Vec<TabSearchResult>,
+    pub selected: usize,
+}
+
+impl TabSearch {
+    /// Selects the result above or below the selected one, wrapping around if the search does.
+    pub fn move_selection(&mut self, direction: SearchDirection) {
+        let count = self.results.len();
+        self.selected = match direction {
+            SearchDirection::Up if self.selected > 0 => self.selected - 1,
+            SearchDirection::Up if self.search.wrap => count.saturating_sub(1),
+            SearchDirection::Down if self.selected + 1 < count => self.selected + 1,
+            SearchDirection::Down if self.search.wrap => 0,
+            _ => self.selected,
+        };
+    }
+    pub fn selected_result(&self) -> Option<&TabSearchResult> {
+        self.results.get(self.selected)
+    }
+    /// The searched text and as many results around the selected one as fit, drawn over the
+    /// top left corner of `viewport`.
+    pub fn render(&self, viewport: &Viewport) -> String {
+        let selected = if self.results.is_empty() {
+            0
+        } else {
+            self.selected + 1
+        };
+        let header = format!(
+            " SEARCH TAB: {} {}/{} ",
+            self.search.needle.as_deref().unwrap_or_default(),
+            selected,
+            self.results.len()
+        );
+        let rows: Vec<String> = self
+            .results
+            .iter()
+            .map(|result| format!(" {}: {}", result.pane_title, result.line.trim()))
+            .collect();
+        render_list(viewport, &header, &rows, self.selected)
+    }
+}
+
+/// Draws `header` and as many of `items` around the `selected` one as fit over the top left
+/// corner of `viewport`, the selected item in black on yellow.
+pub(crate) fn render_list(
+    viewport: &Viewport,
+    header: &str,
+    items: &[String],
+    selected: usize,
+) -> String {
+    let width = viewport.cols.min(LIST_MAX_WIDTH);
+    // the rows are padded to the width of the list, to cover the panes below
+    let fit = |text: &str| -> String {
+        let text: String = text.chars().take(width).collect();
+        let padding = width - text.chars().count();
+        text.chars()
+            .chain(iter::repeat(' ').take(padding))
+            .collect()
+    };
+    let mut output = format!(
+        "\u{1b}[{};{}H\u{1b}[m\u{1b}[1;7m{}\u{1b}[m",
+        viewport.y + 1,
+        viewport.x + 1,
+        fit(header)
+    );
+    let rows = viewport.rows.saturating_sub(1).min(LIST_MAX_ITEMS);
+    let first = (selected + 1).saturating_sub(rows);
+    for (row, (index, item)) in items.iter().enumerate().skip(first).take(rows).enumerate() {
+        let style = if index == selected {
+            "\u{1b}[1;30;43m"
+        } else {
+            "\u{1b}[7m"
+        };
+        output.push_str(&format!(
+            "\u{1b}[{};{}H\u{1b}[m{}{}\u{1b}[m",
+            viewport.y + row + 2,
+            viewport.x + 1,
+            style,
+            fit(item)
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+#[path = "./unit/search_tests.rs"]
+mod search_tests;
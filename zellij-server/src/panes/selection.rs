@@ -8,7 +8,8 @@ use zellij_utils::position::Position;
 pub struct Selection {
     pub start: Position,
     pub end: Position,
-    active: bool, // used to handle moving the selection up and down
+    active: bool,    // used to handle moving the selection up and down
+    pub block: bool, // the rectangle between start and end, rather than the text
 }
 
 impl Default for Selection {
@@ -17,13 +18,25 @@ impl Default for Selection {
             start: Position::new(0, 0),
             end: Position::new(0, 0),
             active: false,
+            block: false,
         }
     }
 }
 
 impl Selection {
+    /// A selection that is already done, as one made with the keyboard.
+    pub fn between(start: Position, end: Position, block: bool) -> Self {
+        Self {
+            start,
+            end,
+            active: false,
+            block,
+        }
+    }
+
     pub fn start(&mut self, start: Position) {
         self.active = true;
+        self.block = false;
         self.start = start;
         self.end = start;
     }
@@ -47,6 +60,12 @@ impl Selection {
             (self.end, self.start)
         };
 
+        if self.block {
+            return start.line.0 <= row
+                && row <= end.line.0
+                && start.column.0 <= col
+                && col < end.column.0;
+        }
         if (start.line.0) < row && row < end.line.0 {
             return true;
         }
@@ -66,6 +85,7 @@ impl Selection {
     pub fn reset(&mut self) {
         self.start = Position::new(0, 0);
         self.end = self.start;
+        self.block = false;
     }
 
     pub fn sorted(&self) -> Self {
@@ -78,6 +98,7 @@ impl Selection {
             start,
             end,
             active: self.active,
+            block: self.block,
         }
     }
 
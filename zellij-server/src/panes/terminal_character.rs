@@ -9,6 +9,8 @@ use crate::panes::alacritty_functions::parse_sgr_color;
 pub const EMPTY_TERMINAL_CHARACTER: TerminalCharacter = TerminalCharacter {
     character: ' ',
     width: 1,
+    hard_tab: None,
+    link: None,
     styles: CharacterStyles {
         foreground: Some(AnsiCode::Reset),
         background: Some(AnsiCode::Reset),
@@ -54,6 +56,10 @@ pub enum NamedColor {
 }
 
 impl NamedColor {
+    // the index of the color among the sixteen colors of the terminal
+    pub fn index(self) -> u8 {
+        self as u8
+    }
     fn to_foreground_ansi_code(self) -> String {
         match self {
             NamedColor::Black => format!("{}", 30),
@@ -762,11 +768,23 @@ impl Cursor {
     }
 }
 
+/// The part of the space left by a tab character that a blank is in, so that copying it gives
+/// back the tab.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HardTab {
+    Start,
+    Rest,
+}
+
 #[derive(Clone, Copy)]
 pub struct TerminalCharacter {
     pub character: char,
     pub styles: CharacterStyles,
     pub width: usize,
+    pub hard_tab: Option<HardTab>,
+    /// The hyperlink (OSC 8) the character is part of, as the index of its target in the links
+    /// of the grid
+    pub link: Option<u16>,
 }
 
 impl ::std::fmt::Debug for TerminalCharacter {
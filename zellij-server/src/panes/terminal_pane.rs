@@ -1,25 +1,43 @@
 use crate::panes::AnsiCode;
 use crate::panes::{
+    command_history::HistoryEntry,
     grid::Grid,
+    quick_copy::{QuickCopy, QuickCopyInput},
+    search::{Search, SearchDirection, SearchOption},
     terminal_character::{
         CharacterStyles, CursorShape, TerminalCharacter, EMPTY_TERMINAL_CHARACTER,
     },
+    timer::RunningTimer,
 };
 use crate::pty::VteBytes;
-use crate::tab::Pane;
+use crate::tab::{Pane, PaneGridOptions};
+use regex::Regex;
 use std::fmt::Debug;
 use std::os::unix::io::RawFd;
+use std::path::PathBuf;
 use std::time::{self, Instant};
 use zellij_utils::pane_size::Offset;
 use zellij_utils::{
-    pane_size::{Dimension, PaneGeom},
-    position::Position,
+    input::{
+        actions::{CopyMotion, SelectionKind},
+        options::{AmbiguousWidth, PaneFrameStyle},
+        theme::{PaletteColorFromYaml, PaletteOverride},
+    },
+    pane_size::PaneGeom,
+    position::{Column, Line, Position},
     vte,
     zellij_tile::data::{Palette, PaletteColor},
 };
 
 pub const SELECTION_SCROLL_INTERVAL_MS: u64 = 10;
 
+fn palette_color_to_ansi_code(color: PaletteColorFromYaml) -> AnsiCode {
+    match color {
+        PaletteColorFromYaml::Rgb(rgb) => AnsiCode::RgbCode(rgb),
+        PaletteColorFromYaml::EightBit(index) => AnsiCode::ColorIndex(index),
+    }
+}
+
 use crate::ui::pane_boundaries_frame::PaneFrame;
 
 #[derive(PartialEq, Eq, Ord, PartialOrd, Hash, Clone, Copy, Debug)]
@@ -42,9 +60,21 @@ pub struct TerminalPane {
     selection_scrolled_at: time::Instant,
     content_offset: Offset,
     pane_title: String,
+    pane_name: String, // given by the user, shown instead of the title
     frame: Option<PaneFrame>,
     frame_color: Option<PaletteColor>,
     borderless: bool,
+    read_only: bool,
+    fullscreen: bool,
+    quick_copy: Option<QuickCopy>,
+    presentation: bool, // drawn at double size, without its frame
+    pinned: bool,       // follows the user from tab to tab
+    frame_style: PaneFrameStyle,
+    palette_override: Option<PaletteOverride>,
+    tags: Vec<String>,
+    timer: Option<RunningTimer>,     // shown in the frame
+    ligature_safe_boundaries: bool,  // rows end with a reset and a cursor move at the pane's edge
+    ambiguous_width: AmbiguousWidth, // of the session, the grid can measure characters otherwise
 }
 
 impl Pane for TerminalPane {
@@ -61,18 +91,32 @@ impl Pane for TerminalPane {
         self.get_columns()
     }
     fn get_content_x(&self) -> usize {
+        if self.presentation {
+            return self.get_x();
+        }
         self.get_x() + self.content_offset.left
     }
     fn get_content_y(&self) -> usize {
+        if self.presentation {
+            return self.get_y();
+        }
         self.get_y() + self.content_offset.top
     }
     fn get_content_columns(&self) -> usize {
+        if self.presentation {
+            // every character takes up two columns
+            return self.get_columns() / 2;
+        }
         // content columns might differ from the pane's columns if the pane has a frame
         // in that case they would be 2 less
         self.get_columns()
             .saturating_sub(self.content_offset.left + self.content_offset.right)
     }
     fn get_content_rows(&self) -> usize {
+        if self.presentation {
+            // every line takes up two rows
+            return self.get_rows() / 2;
+        }
         // content rows might differ from the pane's rows if the pane has a frame
         // in that case they would be 2 less
         self.get_rows()
@@ -98,6 +142,10 @@ impl Pane for TerminalPane {
     }
     fn cursor_coordinates(&self) -> Option<(usize, usize)> {
         // (x, y)
+        if self.presentation {
+            // columns are counted in double width characters on double size lines
+            return self.grid.cursor_coordinates().map(|(x, y)| (x, y * 2));
+        }
         let Offset { top, left, .. } = self.content_offset;
         self.grid
             .cursor_coordinates()
@@ -184,17 +232,26 @@ impl Pane for TerminalPane {
         self.selectable = selectable;
     }
     fn render(&mut self) -> Option<String> {
-        if self.should_render() {
+        if self.should_render() && self.presentation {
+            let vte_output = self.render_double_size();
+            self.set_should_render(false);
+            Some(vte_output)
+        } else if self.should_render() {
             let mut vte_output = String::new();
             let mut character_styles = CharacterStyles::new();
-            if self.grid.clear_viewport_before_rendering {
+            if self.grid.clear_viewport_before_rendering && self.grid.redraw_in_place {
+                // the lines are padded to the width of the pane, drawing them all covers it
+                self.grid.render_full_viewport();
+                self.grid.clear_viewport_before_rendering = false;
+            } else if self.grid.clear_viewport_before_rendering {
                 for line_index in 0..self.grid.height {
                     let x = self.get_content_x();
                     let y = self.get_content_y();
                     vte_output.push_str(&format!(
-                        "\u{1b}[{};{}H\u{1b}[m",
+                        "\u{1b}[{};{}H\u{1b}[m{}",
                         y + line_index + 1,
-                        x + 1
+                        x + 1,
+                        self.with_palette_override(CharacterStyles::new())
                     )); // goto row/col and reset styles
                     for _col_index in 0..self.grid.width {
                         vte_output.push(EMPTY_TERMINAL_CHARACTER.character);
@@ -215,42 +272,43 @@ impl Pane for TerminalPane {
                     chunk_absolute_x + 1
                 )); // goto row/col and reset styles
 
-                let mut chunk_width = character_chunk.x;
-                for mut t_character in terminal_characters {
-                    // adjust the background of currently selected characters
-                    // doing it here is much easier than in grid
-                    if self.grid.selection.contains(character_chunk.y, chunk_width) {
-                        let color = match self.colors.bg {
-                            PaletteColor::Rgb(rgb) => AnsiCode::RgbCode(rgb),
-                            PaletteColor::EightBit(col) => AnsiCode::ColorIndex(col),
-                        };
-
-                        t_character.styles = t_character.styles.background(Some(color));
-                    }
-                    chunk_width += t_character.width;
-                    if chunk_width > max_width {
-                        break;
-                    }
-
-                    if let Some(new_styles) = character_styles
-                        .update_and_return_diff(&t_character.styles, self.grid.changed_colors)
-                    {
-                        vte_output.push_str(&new_styles.to_string());
-                    }
-                    vte_output.push(t_character.character);
-                }
+                vte_output.push_str(&self.render_characters(
+                    terminal_characters,
+                    character_chunk.x,
+                    character_chunk.y,
+                    max_width,
+                    &mut character_styles,
+                ));
                 character_styles.clear();
+                if self.ligature_safe_boundaries {
+                    // the run of text ends here, whatever is drawn next to the pane is not
+                    // sent as its continuation
+                    vte_output.push_str(&format!(
+                        "\u{1b}[m\u{1b}[{};{}H",
+                        chunk_absolute_y + 1,
+                        pane_x + max_width + 1
+                    ));
+                }
             }
+            if let Some(quick_copy) = &self.quick_copy {
+                vte_output.push_str(&quick_copy.render(self.get_content_x(), self.get_content_y()));
+            }
+            vte_output.push_str(&self.render_search_highlights());
             if let Some(last_frame) = &self.frame {
                 let frame = PaneFrame {
                     geom: self.current_geom().into(),
-                    title: self
-                        .grid
-                        .title
-                        .clone()
-                        .unwrap_or_else(|| self.pane_title.clone()),
+                    title: self.title(),
                     scroll_position: self.grid.scrollback_position_and_length(),
                     color: self.frame_color,
+                    read_only: self.read_only,
+                    fullscreen: self.fullscreen,
+                    pinned: self.pinned,
+                    style: self.frame_style,
+                    search: self.grid.search_indication(),
+                    timer: self
+                        .timer
+                        .as_ref()
+                        .map(|timer| timer.indication(Instant::now())),
                 };
                 if &frame != last_frame {
                     if !self.borderless {
@@ -270,25 +328,25 @@ impl Pane for TerminalPane {
     }
     fn reduce_height(&mut self, percent: f64) {
         if let Some(p) = self.geom.rows.as_percent() {
-            self.geom.rows = Dimension::percent(p - percent);
+            self.geom.rows.set_percent(p - percent);
             self.set_should_render(true);
         }
     }
     fn increase_height(&mut self, percent: f64) {
         if let Some(p) = self.geom.rows.as_percent() {
-            self.geom.rows = Dimension::percent(p + percent);
+            self.geom.rows.set_percent(p + percent);
             self.set_should_render(true);
         }
     }
     fn reduce_width(&mut self, percent: f64) {
         if let Some(p) = self.geom.cols.as_percent() {
-            self.geom.cols = Dimension::percent(p - percent);
+            self.geom.cols.set_percent(p - percent);
             self.set_should_render(true);
         }
     }
     fn increase_width(&mut self, percent: f64) {
         if let Some(p) = self.geom.cols.as_percent() {
-            self.geom.cols = Dimension::percent(p + percent);
+            self.geom.cols.set_percent(p + percent);
             self.set_should_render(true);
         }
     }
@@ -343,6 +401,18 @@ impl Pane for TerminalPane {
         self.grid.pending_messages_to_pty.drain(..).collect()
     }
 
+    fn take_clipboard_write(&mut self) -> Option<String> {
+        self.grid.pending_clipboard_write.take()
+    }
+
+    fn take_cwd_change(&mut self) -> Option<PathBuf> {
+        self.grid.pending_cwd.take().map(PathBuf::from)
+    }
+
+    fn cwd(&self) -> Option<PathBuf> {
+        self.grid.cwd().map(PathBuf::from)
+    }
+
     fn start_selection(&mut self, start: &Position) {
         self.grid.start_selection(start);
         self.set_should_render(true);
@@ -377,6 +447,107 @@ impl Pane for TerminalPane {
     fn get_selected_text(&self) -> Option<String> {
         self.grid.get_selected_text()
     }
+    fn link_at(&self, position: &Position) -> Option<String> {
+        self.grid.link_at(position).map(String::from)
+    }
+    fn hover(&mut self, position: Option<&Position>) -> bool {
+        self.grid.hover(position)
+    }
+
+    fn dump_scrollback(&self) -> Option<String> {
+        Some(self.grid.dump_scrollback())
+    }
+    fn dump_scrollback_text(&self) -> Option<String> {
+        Some(self.grid.dump_scrollback_text())
+    }
+    fn dump_screen(&self, full: bool, ansi: bool) -> Option<String> {
+        Some(self.grid.dump_screen(full, ansi))
+    }
+    fn dump_last_rows(&self, count: usize) -> Option<String> {
+        Some(self.grid.dump_last_rows(count))
+    }
+    fn last_command_output(&self) -> Option<String> {
+        self.grid.last_command_output()
+    }
+    fn command_history(&self) -> Vec<HistoryEntry> {
+        self.grid.command_history()
+    }
+
+    fn scrollback_memory(&self) -> usize {
+        self.grid.scrollback_memory()
+    }
+
+    fn trim_scrollback(&mut self, bytes: usize) -> usize {
+        self.grid.trim_scrollback(bytes)
+    }
+
+    fn clear_screen(&mut self, clear_scrollback: bool) {
+        self.grid.clear_screen(clear_scrollback);
+        self.set_should_render(true);
+    }
+
+    fn start_quick_copy(&mut self, patterns: &[Regex]) {
+        let quick_copy = QuickCopy::new(&self.grid.as_character_lines(), patterns);
+        self.show_quick_copy_hints(quick_copy);
+    }
+
+    fn start_link_hints(&mut self, patterns: &[Regex]) {
+        let link_hints = QuickCopy::for_links(&self.grid.as_character_lines(), patterns);
+        self.show_quick_copy_hints(link_hints);
+    }
+
+    fn handle_quick_copy_input(&mut self, input_bytes: &[u8]) -> Option<QuickCopyInput> {
+        let quick_copy_input = self.quick_copy.as_mut()?.handle_input(input_bytes);
+        if quick_copy_input != QuickCopyInput::Pending {
+            self.quick_copy = None;
+        }
+        // labels that were filtered out or the whole overlay have to be drawn over
+        self.render_full_viewport();
+        self.set_should_render(true);
+        Some(quick_copy_input)
+    }
+
+    fn update_search(&mut self, input: &str) {
+        self.grid.update_search(input);
+    }
+
+    fn search(&mut self, direction: SearchDirection) {
+        self.grid.search(direction);
+    }
+
+    fn toggle_search_option(&mut self, option: SearchOption) {
+        self.grid.toggle_search_option(option);
+    }
+
+    fn search_matches(&self, search: &Search) -> Vec<String> {
+        self.grid.search_matches(search)
+    }
+    fn show_search_match(&mut self, search: &Search, index: usize) {
+        self.grid.show_search_match(search, index);
+    }
+    fn end_search(&mut self) {
+        self.grid.end_search();
+    }
+
+    fn start_copy_mode(&mut self) {
+        self.grid.start_copy_mode();
+    }
+
+    fn end_copy_mode(&mut self) {
+        self.grid.end_copy_mode();
+    }
+
+    fn move_copy_cursor(&mut self, motion: CopyMotion) {
+        self.grid.move_copy_cursor(motion);
+    }
+
+    fn toggle_copy_selection(&mut self, kind: SelectionKind) {
+        self.grid.toggle_copy_selection(kind);
+    }
+
+    fn yank_copy_selection(&mut self) -> Option<String> {
+        self.grid.yank_copy_selection()
+    }
 
     fn set_frame(&mut self, frame: bool) {
         self.frame = if frame {
@@ -401,6 +572,112 @@ impl Pane for TerminalPane {
     fn borderless(&self) -> bool {
         self.borderless
     }
+    fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+        self.set_should_render(true);
+    }
+    fn read_only(&self) -> bool {
+        self.read_only
+    }
+    fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen;
+        self.set_should_render(true);
+    }
+    fn set_presentation(&mut self, presentation: bool) {
+        self.presentation = presentation;
+        self.reflow_lines();
+        self.render_full_viewport();
+    }
+    fn presentation(&self) -> bool {
+        self.presentation
+    }
+    fn set_frame_style(&mut self, style: PaneFrameStyle) {
+        if self.frame_style != style {
+            self.frame_style = style;
+            self.set_should_render(true);
+        }
+    }
+    fn set_pinned(&mut self, pinned: bool) {
+        self.pinned = pinned;
+        self.set_should_render(true);
+    }
+    fn set_palette_override(&mut self, palette: Option<PaletteOverride>) {
+        if self.palette_override != palette {
+            self.palette_override = palette;
+            // the blank parts of the pane are drawn in the new colors as well
+            self.grid.clear_viewport_before_rendering = true;
+            self.render_full_viewport();
+            self.set_should_render(true);
+        }
+    }
+    fn set_ambiguous_width(&mut self, ambiguous_width: Option<AmbiguousWidth>) {
+        // the characters already in the pane keep the width they were printed with
+        self.grid.ambiguous_width = ambiguous_width.unwrap_or(self.ambiguous_width);
+    }
+    fn pinned(&self) -> bool {
+        self.pinned
+    }
+    fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+    fn set_timer(&mut self, timer: Option<RunningTimer>) {
+        self.timer = timer;
+        self.set_should_render(true);
+    }
+    fn timer_mut(&mut self) -> Option<&mut RunningTimer> {
+        self.timer.as_mut()
+    }
+    fn executes_pasted_lines(&self) -> bool {
+        // full screen applications use the alternate screen, and shells that know about bracketed
+        // paste don't run what is pasted before enter is pressed
+        !self.grid.is_alternate_mode_active() && !self.grid.bracketed_paste_mode
+    }
+    fn relative_position(&self, position_on_screen: &Position) -> Position {
+        let relative_position =
+            position_on_screen.relative_to(self.get_content_y(), self.get_content_x());
+        if self.presentation {
+            Position {
+                line: Line(relative_position.line.0.div_euclid(2)),
+                column: Column(relative_position.column.0 / 2),
+            }
+        } else {
+            relative_position
+        }
+    }
+    fn title(&self) -> String {
+        if !self.pane_name.is_empty() {
+            return self.pane_name.clone();
+        }
+        self.grid
+            .title
+            .clone()
+            .unwrap_or_else(|| self.pane_title.clone())
+    }
+    fn custom_title(&self) -> Option<String> {
+        if self.pane_name.is_empty() {
+            self.grid.title.clone()
+        } else {
+            Some(self.pane_name.clone())
+        }
+    }
+    fn update_name(&mut self, name: &str) {
+        match name {
+            "\0" => {
+                self.pane_name = String::new();
+            }
+            "\u{007F}" | "\u{0008}" => {
+                //delete and backspace keys
+                self.pane_name.pop();
+            }
+            c => {
+                self.pane_name.push_str(c);
+            }
+        }
+        self.set_should_render(true);
+    }
 }
 
 impl TerminalPane {
@@ -419,6 +696,7 @@ impl TerminalPane {
         TerminalPane {
             frame: None,
             frame_color: None,
+            quick_copy: None,
             content_offset: Offset::default(),
             pid,
             grid,
@@ -430,7 +708,18 @@ impl TerminalPane {
             colors: palette,
             selection_scrolled_at: time::Instant::now(),
             pane_title: initial_pane_title,
+            pane_name: String::new(),
             borderless: false,
+            read_only: false,
+            fullscreen: false,
+            presentation: false,
+            pinned: false,
+            frame_style: PaneFrameStyle::default(),
+            palette_override: None,
+            tags: vec![],
+            timer: None,
+            ligature_safe_boundaries: false,
+            ambiguous_width: AmbiguousWidth::Narrow,
         }
     }
     pub fn get_x(&self) -> usize {
@@ -457,12 +746,172 @@ impl TerminalPane {
             None => self.geom.rows.as_usize(),
         }
     }
+    fn render_characters(
+        &self,
+        terminal_characters: Vec<TerminalCharacter>,
+        x: usize,
+        y: usize,
+        max_width: usize,
+        character_styles: &mut CharacterStyles,
+    ) -> String {
+        let mut vte_output = String::new();
+        let mut chunk_width = x;
+        // the hyperlinks go on to the terminal, for it to show and open them as well
+        let mut link = None;
+        for mut t_character in terminal_characters {
+            // adjust the background of currently selected characters
+            // doing it here is much easier than in grid
+            if self.grid.selection.contains(y, chunk_width) {
+                let color = match self.colors.bg {
+                    PaletteColor::Rgb(rgb) => AnsiCode::RgbCode(rgb),
+                    PaletteColor::EightBit(col) => AnsiCode::ColorIndex(col),
+                };
+
+                t_character.styles = t_character.styles.background(Some(color));
+            }
+            chunk_width += t_character.width;
+            if chunk_width > max_width {
+                break;
+            }
+
+            if t_character.link != link {
+                let target = t_character
+                    .link
+                    .and_then(|index| self.grid.link_target(index));
+                vte_output.push_str(&format!("\u{1b}]8;;{}\u{1b}\\", target.unwrap_or("")));
+                link = t_character.link;
+            }
+            if self.grid.is_hovered_link(t_character.link) {
+                t_character.styles = t_character.styles.underline(Some(AnsiCode::On));
+            }
+            t_character.styles = self.with_palette_override(t_character.styles);
+            if let Some(new_styles) = character_styles
+                .update_and_return_diff(&t_character.styles, self.grid.changed_colors)
+            {
+                vte_output.push_str(&new_styles.to_string());
+            }
+            vte_output.push(t_character.character);
+        }
+        if link.is_some() {
+            vte_output.push_str("\u{1b}]8;;\u{1b}\\");
+        }
+        vte_output
+    }
+    // the styles with the default colors and the sixteen colors of the terminal replaced by
+    // those of the palette override of the pane, if any
+    fn with_palette_override(&self, mut styles: CharacterStyles) -> CharacterStyles {
+        let palette = match &self.palette_override {
+            Some(palette) => palette,
+            None => return styles,
+        };
+        let replace = |code: Option<AnsiCode>, default: Option<PaletteColorFromYaml>| {
+            let replacement = match code {
+                None | Some(AnsiCode::Reset) => default,
+                Some(AnsiCode::NamedColor(color)) => palette.color(color.index()),
+                Some(AnsiCode::ColorIndex(index)) => palette.color(index),
+                _ => None,
+            };
+            replacement.map(palette_color_to_ansi_code).or(code)
+        };
+        styles.foreground = replace(styles.foreground, palette.fg);
+        styles.background = replace(styles.background, palette.bg);
+        styles
+    }
+    // the matches of the search drawn on top of the pane content, the active one in black on
+    // yellow and the others in reverse video
+    fn render_search_highlights(&self) -> String {
+        let mut vte_output = String::new();
+        for (chunk, is_active) in self.grid.search_highlights() {
+            let style = if is_active { "1;30;43" } else { "7" };
+            let text: String = chunk
+                .terminal_characters
+                .iter()
+                .map(|c| c.character)
+                .collect();
+            vte_output.push_str(&format!(
+                "\u{1b}[{};{}H\u{1b}[m\u{1b}[{}m{}\u{1b}[m",
+                self.get_content_y() + chunk.y + 1,
+                self.get_content_x() + chunk.x + 1,
+                style,
+                text
+            ));
+        }
+        vte_output
+    }
+    // every line is drawn twice, as the top and the bottom half of a double size line (DECDHL), so
+    // that it takes up two rows and each of its characters two columns
+    fn render_double_size(&mut self) -> String {
+        let mut vte_output = String::new();
+        let mut character_styles = CharacterStyles::new();
+        let pane_x = self.get_x() / 2;
+        let pane_y = self.get_y();
+        let double_size_line = |line_y: usize, x: usize, line_contents: &str| {
+            let top_row = pane_y + line_y * 2 + 1;
+            [(top_row, '3'), (top_row + 1, '4')]
+                .iter()
+                .map(|(row, half)| {
+                    // make the row a double size line, then goto row/col and reset styles
+                    format!(
+                        "\u{1b}[{row};1H\u{1b}#{half}\u{1b}[{row};{col}H\u{1b}[m{contents}",
+                        row = row,
+                        half = half,
+                        col = pane_x + x + 1,
+                        contents = line_contents
+                    )
+                })
+                .collect::<String>()
+        };
+        if self.grid.clear_viewport_before_rendering {
+            let empty_line: String = (0..self.grid.width)
+                .map(|_| EMPTY_TERMINAL_CHARACTER.character)
+                .collect();
+            for line_index in 0..self.grid.height {
+                vte_output.push_str(&double_size_line(line_index, 0, &empty_line));
+            }
+            self.grid.clear_viewport_before_rendering = false;
+        }
+        let max_width = self.get_content_columns();
+        for character_chunk in self.grid.read_changes() {
+            let line_contents = self.render_characters(
+                character_chunk.terminal_characters,
+                character_chunk.x,
+                character_chunk.y,
+                max_width,
+                &mut character_styles,
+            );
+            character_styles.clear();
+            vte_output.push_str(&double_size_line(
+                character_chunk.y,
+                character_chunk.x,
+                &line_contents,
+            ));
+        }
+        vte_output
+    }
+    fn show_quick_copy_hints(&mut self, quick_copy: QuickCopy) {
+        if !quick_copy.is_empty() {
+            self.quick_copy = Some(quick_copy);
+            self.render_full_viewport();
+            self.set_should_render(true);
+        }
+    }
     fn reflow_lines(&mut self) {
         let rows = self.get_content_rows();
         let cols = self.get_content_columns();
         self.grid.change_size(rows, cols);
         self.set_should_render(true);
     }
+    pub(crate) fn set_grid_options(&mut self, grid_options: PaneGridOptions) {
+        self.grid.set_tabstop_width(grid_options.tab_width);
+        self.grid.copy_tabs_as_spaces = grid_options.copy_tabs_as_spaces;
+        self.grid.reflow_on_resize = grid_options.reflow_on_resize;
+        self.grid.accept_titles = grid_options.accept_titles;
+        self.grid.redraw_in_place = grid_options.reduced_motion;
+        self.grid.scroll_buffer_size = grid_options.scroll_buffer_size;
+        self.ligature_safe_boundaries = grid_options.ligature_safe_boundaries;
+        self.ambiguous_width = grid_options.ambiguous_width;
+        self.grid.ambiguous_width = grid_options.ambiguous_width;
+    }
     pub fn read_buffer_as_lines(&self) -> Vec<Vec<TerminalCharacter>> {
         self.grid.as_character_lines()
     }
@@ -0,0 +1,99 @@
+//! The countdowns and stopwatches shown in the frames of panes, eg. to keep an eye on a deploy
+//! window or for a pomodoro, with a command run when a countdown elapses.
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use zellij_utils::input::actions::PaneTimer;
+
+/// A [`PaneTimer`] that started at some point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunningTimer {
+    countdown: Option<Duration>,
+    on_elapsed: Option<String>,
+    started_at: Instant,
+    elapsed: bool,
+}
+
+impl RunningTimer {
+    pub fn new(timer: PaneTimer, now: Instant) -> Self {
+        RunningTimer {
+            countdown: timer.countdown.map(Duration::from_secs),
+            on_elapsed: timer.on_elapsed,
+            started_at: now,
+            elapsed: false,
+        }
+    }
+    /// What the pane frame shows, eg. ` TIMER: 04:59 `, ` TIMER: DONE ` once the countdown
+    /// elapsed, or ` STOPWATCH: 1:02:03 `.
+    pub fn indication(&self, now: Instant) -> String {
+        let running_for = now.saturating_duration_since(self.started_at);
+        match self.countdown {
+            Some(_) if self.elapsed => " TIMER: DONE ".to_string(),
+            Some(countdown) => {
+                // 00:00 is never shown, the countdown reads DONE by then
+                let left = countdown.saturating_sub(running_for);
+                let seconds = left.as_secs() + if left.subsec_nanos() > 0 { 1 } else { 0 };
+                format!(" TIMER: {} ", clock(seconds))
+            }
+            None => format!(" STOPWATCH: {} ", clock(running_for.as_secs())),
+        }
+    }
+    /// Marks the countdown elapsed once it is over, returns whether it just did.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        match self.countdown {
+            // a countdown too long for the clock never elapses
+            Some(countdown)
+                if !self.elapsed
+                    && self
+                        .started_at
+                        .checked_add(countdown)
+                        .map_or(false, |end| now >= end) =>
+            {
+                self.elapsed = true;
+                true
+            }
+            _ => false,
+        }
+    }
+    /// Whether the timer still changes, a countdown that elapsed doesn't.
+    pub fn is_ticking(&self) -> bool {
+        !self.elapsed
+    }
+    pub fn on_elapsed(&self) -> Option<&str> {
+        self.on_elapsed.as_deref()
+    }
+}
+
+/// Runs the command of a countdown that elapsed in the background, with `sh -c`.
+pub fn run_on_elapsed(command: &str) {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match child {
+        Ok(mut child) => {
+            thread::spawn(move || {
+                let _ = child.wait();
+            });
+        }
+        Err(e) => log::error!("Failed to run {:?} for an elapsed timer: {}", command, e),
+    }
+}
+
+// `mm:ss`, or `h:mm:ss` past an hour
+fn clock(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/timer_tests.rs"]
+mod timer_tests;
@@ -0,0 +1,108 @@
+use super::*;
+use std::os::unix::io::RawFd;
+use std::time::Duration;
+
+fn history(commands: &[(RawFd, &str)]) -> Vec<HistoryItem> {
+    let start = Instant::now();
+    commands
+        .iter()
+        .enumerate()
+        .map(|(index, (pane, command))| HistoryItem {
+            pane_id: PaneId::Terminal(*pane),
+            pane_title: format!("Pane #{}", pane),
+            entry: HistoryEntry {
+                command: command.to_string(),
+                cwd: None,
+                exit_code: None,
+                started: start + Duration::from_secs(index as u64),
+            },
+        })
+        .collect()
+}
+
+fn commands(picker: &HistoryPicker) -> Vec<String> {
+    let mut picker = picker.clone();
+    picker.selected = 0;
+    let mut commands = vec![];
+    for _ in 0..picker.matches.len() {
+        commands.push(picker.selected_item().unwrap().entry.command.clone());
+        picker.move_selection(SearchDirection::Down);
+    }
+    commands
+}
+
+#[test]
+fn lists_the_most_recent_run_of_each_command_first() {
+    let mut picker = HistoryPicker::default();
+    picker.set_items(history(&[
+        (1, "make"),
+        (2, "git status"),
+        (1, "cargo test"),
+        (2, "make"),
+    ]));
+    assert_eq!(commands(&picker), vec!["make", "cargo test", "git status"]);
+    assert_eq!(
+        picker.selected_item().map(|item| item.pane_id),
+        Some(PaneId::Terminal(2)),
+        "the latest make ran in the second pane"
+    );
+}
+
+#[test]
+fn the_filter_matches_commands_having_each_of_its_words() {
+    let mut picker = HistoryPicker::default();
+    picker.set_items(history(&[
+        (1, "cargo test --all"),
+        (1, "cargo build"),
+        (1, "git log --all"),
+    ]));
+    for c in "ALL carg".chars() {
+        picker.update_filter(&c.to_string());
+    }
+    assert_eq!(commands(&picker), vec!["cargo test --all"]);
+    picker.update_filter("\0");
+    assert_eq!(
+        commands(&picker).len(),
+        3,
+        "clearing the filter lists them all"
+    );
+}
+
+#[test]
+fn the_list_shows_failed_exit_codes_and_working_directories() {
+    let mut items = history(&[(1, "false")]);
+    items[0].entry.exit_code = Some(1);
+    items[0].entry.cwd = Some("/tmp".into());
+    let mut picker = HistoryPicker::default();
+    picker.set_items(items);
+    let viewport = Viewport {
+        x: 0,
+        y: 0,
+        rows: 5,
+        cols: 40,
+    };
+    let list = picker.render(&viewport);
+    assert!(list.contains(" COMMAND HISTORY:  1/1 "));
+    assert!(list.contains("\u{1b}[1;30;43m false [1]  (Pane #1, /tmp) "));
+}
+
+#[test]
+fn working_directories_are_decoded_from_file_urls() {
+    assert_eq!(
+        cwd_from_url("file://host/home/me/a%20b"),
+        Some("/home/me/a b".to_string())
+    );
+    assert_eq!(
+        cwd_from_url("file:///tmp/100%"),
+        Some("/tmp/100%".to_string())
+    );
+    assert_eq!(cwd_from_url("http://host/tmp"), None);
+}
+
+#[test]
+fn only_working_directories_of_this_machine_are_local() {
+    assert!(is_local_url("file:///tmp"));
+    assert!(is_local_url("file://localhost/tmp"));
+    assert!(!is_local_url("file://some-other-machine.example/tmp"));
+    assert!(!is_local_url("http:///tmp"));
+}
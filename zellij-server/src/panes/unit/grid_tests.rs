@@ -1,6 +1,11 @@
 use super::super::Grid;
 use ::insta::assert_snapshot;
-use zellij_utils::{position::Position, vte, zellij_tile::data::Palette};
+use zellij_utils::{
+    input::options::AmbiguousWidth,
+    position::Position,
+    vte,
+    zellij_tile::data::{Palette, PaletteColor},
+};
 
 fn read_fixture(fixture_name: &str) -> Vec<u8> {
     let mut path_to_file = std::path::PathBuf::new();
@@ -998,3 +1003,438 @@ pub fn scroll_up_increase_width_and_scroll_down() {
     }
     assert_snapshot!(format!("{:?}", grid));
 }
+
+#[test]
+fn dump_scrollback_keeps_styles_and_lines() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(2, 20, Palette::default());
+    let content = "\u{1b}[31mred\u{1b}[m\r\nplain\r\nlast".as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    let scrollback = grid.dump_scrollback();
+    let lines: Vec<&str> = scrollback.lines().collect();
+    assert_eq!(lines.len(), 3, "lines above the viewport are included");
+    assert!(
+        lines[0].starts_with("\u{1b}[") && lines[0].contains("red"),
+        "styles are kept"
+    );
+    assert!(lines[1].contains("plain"));
+    assert!(lines[2].contains("last"));
+}
+
+#[test]
+fn dump_scrollback_text_leaves_out_styles_and_joins_wrapped_lines() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(3, 10, Palette::default());
+    let content = "\u{1b}[31mred\u{1b}[m\r\nwrapped over two rows\r\nlast".as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    let scrollback = grid.dump_scrollback_text();
+    assert_eq!(scrollback, "red\nwrapped over two rows\nlast\n");
+}
+
+#[test]
+fn dump_screen_leaves_out_the_scrollback_unless_full() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(2, 20, Palette::default());
+    let content = "above\r\n\u{1b}[31mred\u{1b}[m\r\nlast".as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    assert_eq!(grid.dump_screen(false, false), "red\nlast\n");
+    assert_eq!(grid.dump_screen(true, false), "above\nred\nlast\n");
+    let screen = grid.dump_screen(false, true);
+    assert!(
+        screen.starts_with("\u{1b}[") && screen.contains("red") && !screen.contains("above"),
+        "styles are kept"
+    );
+}
+
+#[test]
+fn dump_last_rows_skips_the_empty_rows_at_the_bottom() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 20, Palette::default());
+    let content = "one\r\ntwo\r\nthree\r\nfour\r\nfive\r\n$ ls\r\n".as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    assert_eq!(
+        grid.dump_last_rows(5),
+        "two\nthree\nfour\nfive\n$ ls\u{1b}[m\n",
+        "the scrollback is reached into"
+    );
+    assert_eq!(Grid::new(5, 20, Palette::default()).dump_last_rows(3), "");
+}
+
+#[test]
+fn clear_screen_keeps_cursor_line_and_scrollback() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(3, 20, Palette::default());
+    let content = "one\r\ntwo\r\nthree\r\nprompt $ ".as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    grid.clear_screen(false);
+    assert_eq!(grid.cursor_coordinates(), Some((9, 0)));
+    let scrollback = grid.dump_scrollback();
+    let lines: Vec<&str> = scrollback.lines().collect();
+    assert_eq!(lines.len(), 4, "cleared lines are moved to the scrollback");
+    assert!(lines[2].contains("three"));
+    assert!(lines[3].contains("prompt $"));
+}
+
+#[test]
+fn clear_screen_and_scrollback() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(3, 20, Palette::default());
+    let content = "one\r\ntwo\r\nthree\r\nprompt $ ".as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    grid.clear_screen(true);
+    let scrollback = grid.dump_scrollback();
+    let lines: Vec<&str> = scrollback.lines().collect();
+    assert_eq!(lines.len(), 1, "only the cursor line is left");
+    assert!(lines[0].contains("prompt $"));
+}
+
+#[test]
+fn clear_screen_with_the_cursor_below_the_lines_of_a_scroll_region() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 20, Palette::default());
+    // the cursor moves down within the scroll region, past the lines written so far
+    let content = "one\r\n\u{1b}[2;8r\u{1b}[5B".as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    grid.clear_screen(false);
+    assert_eq!(grid.cursor_coordinates(), Some((0, 0)));
+    assert!(grid.dump_scrollback().contains("one"));
+}
+
+#[test]
+fn color_queries_are_answered_with_theme_colors() {
+    let mut vte_parser = vte::Parser::new();
+    let palette = Palette {
+        fg: PaletteColor::Rgb((255, 255, 255)),
+        bg: PaletteColor::Rgb((16, 32, 48)),
+        red: PaletteColor::Rgb((200, 10, 20)),
+        ..Default::default()
+    };
+    let mut grid = Grid::new(10, 20, palette);
+    let queries = "\u{1b}]10;?\u{07}\u{1b}]11;?\u{1b}\\\u{1b}]4;1;?;232;?\u{07}".as_bytes();
+    for &byte in queries {
+        vte_parser.advance(&mut grid, byte);
+    }
+    assert_eq!(
+        grid.pending_messages_to_pty,
+        vec![
+            b"\x1b]10;rgb:ffff/ffff/ffff\x07".to_vec(),
+            b"\x1b]11;rgb:1010/2020/3030\x1b\\".to_vec(),
+            b"\x1b]4;1;rgb:c8c8/0a0a/1414\x07".to_vec(),
+            b"\x1b]4;232;rgb:0808/0808/0808\x07".to_vec(),
+        ]
+    );
+}
+
+#[test]
+fn osc_52_sets_pending_clipboard_write() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 20, Palette::default());
+    let content = "\u{1b}]52;c;aGVsbG8=\u{07}".as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    assert_eq!(grid.pending_clipboard_write, Some(String::from("hello")));
+}
+
+#[test]
+fn copy_space_left_by_tabs_as_tabs() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 40, Palette::default());
+    for byte in "a\tb\t\tc".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.start_selection(&Position::new(0, 0));
+    grid.end_selection(Some(&Position::new(0, 30)));
+    assert_eq!(grid.get_selected_text().unwrap(), "a\tb\t\tc");
+
+    grid.copy_tabs_as_spaces = true;
+    assert_eq!(
+        grid.get_selected_text().unwrap(),
+        "a       b               c"
+    );
+}
+
+#[test]
+fn tabstops_set_by_the_application_survive_resizing() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 40, Palette::default());
+    // clear all tab stops, then set one on the fifth column
+    for byte in "\u{1b}[3g\u{1b}[5G\u{1b}H\r".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.change_size(10, 50);
+    vte_parser.advance(&mut grid, b'\t');
+    assert_eq!(grid.cursor_coordinates(), Some((4, 0)));
+    vte_parser.advance(&mut grid, b'\t');
+    assert_eq!(
+        grid.cursor_coordinates(),
+        Some((48, 0)),
+        "the new columns get the default tab stops"
+    );
+
+    // DECST8C resets the tab stops to every 8 columns
+    for byte in "\u{1b}[?5W\r\t".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.cursor_coordinates(), Some((8, 0)));
+}
+
+#[test]
+fn ambiguous_width_characters_take_up_as_many_columns_as_set() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 40, Palette::default());
+    for byte in "○─x".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.cursor_coordinates(), Some((3, 0)));
+
+    grid.ambiguous_width = AmbiguousWidth::Wide;
+    for byte in "○─x".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.cursor_coordinates(),
+        Some((8, 0)),
+        "wide, they take up two columns each"
+    );
+}
+
+#[test]
+fn lines_keep_their_wrapping_on_resize_without_reflow() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(10, 20, Palette::default());
+    grid.reflow_on_resize = false;
+    for byte in "abcdefghijklmnopqrstuvwxyz".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.change_size(10, 10);
+    let lines: Vec<String> = grid
+        .as_character_lines()
+        .iter()
+        .map(|line| line.iter().map(|c| c.character).collect())
+        .collect();
+    assert_eq!(lines[0], "abcdefghij");
+    assert_eq!(lines[1], "uvwxyz    ");
+}
+
+#[test]
+fn trim_scrollback_drops_the_oldest_lines() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 20, Palette::default());
+    for i in 0..20 {
+        for byte in format!("line {}\r\n", i).as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+    }
+    let scrollback_memory = grid.scrollback_memory();
+    assert!(scrollback_memory > 0);
+    let freed = grid.trim_scrollback(scrollback_memory / 2);
+    assert!(freed >= scrollback_memory / 2);
+    assert_eq!(grid.scrollback_memory(), scrollback_memory - freed);
+    let scrollback = grid.dump_scrollback();
+    assert!(!scrollback.contains("line 0"), "the oldest lines are gone");
+    assert!(scrollback.contains("line 19"), "the newest lines are kept");
+}
+
+#[test]
+fn scrollback_keeps_at_most_scroll_buffer_size_lines() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 20, Palette::default());
+    grid.scroll_buffer_size = 3;
+    for i in 0..20 {
+        for byte in format!("line {}\r\n", i).as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+    }
+    assert_eq!(grid.lines_above.len(), 3);
+    let scrollback = grid.dump_scrollback();
+    assert!(!scrollback.contains("line 12"), "the oldest lines are gone");
+    assert!(scrollback.contains("line 13"), "the newest lines are kept");
+}
+
+#[test]
+fn scrollback_of_zero_lines_keeps_the_last_line_through_resizing() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 20, Palette::default());
+    grid.scroll_buffer_size = 0;
+    for i in 0..20 {
+        let line = format!("line {} is longer than the pane is wide\r\n", i);
+        for byte in line.as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+    }
+    assert_eq!(grid.lines_above.len(), 1);
+    grid.change_size(5, 10);
+    grid.change_size(3, 40);
+    grid.change_size(8, 15);
+    grid.scroll_up_one_line();
+    grid.scroll_down_one_line();
+    assert!(grid.lines_above.len() <= 1);
+    let scrollback = grid.dump_scrollback();
+    assert!(
+        scrollback.contains("line 19 is longer than the pane is wide"),
+        "Wrapped lines are whole after reflowing: {:?}",
+        scrollback
+    );
+}
+
+#[test]
+fn search_scrolls_to_matches_and_highlights_them() {
+    use crate::panes::SearchDirection;
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(3, 20, Palette::default());
+    let content = "needle one\r\nhay\r\nhay\r\nneedle two\r\nhay\r\nhay\r\nhay".as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    for c in "NEEDLE".chars() {
+        grid.update_search(&c.to_string());
+    }
+    assert_eq!(grid.scrollback_position_and_length().0, 1);
+    assert_eq!(
+        grid.search_indication(),
+        Some(" SEARCH: NEEDLE 2/2 [wrap] ".to_string())
+    );
+    let highlights = grid.search_highlights();
+    assert_eq!(highlights.len(), 1);
+    let (chunk, is_active) = &highlights[0];
+    assert_eq!((chunk.x, chunk.y, *is_active), (0, 0, true));
+    assert_eq!(chunk.terminal_characters.len(), 6);
+
+    grid.search(SearchDirection::Up);
+    assert_eq!(grid.scrollback_position_and_length().0, 4);
+    assert_eq!(
+        grid.search_indication(),
+        Some(" SEARCH: NEEDLE 1/2 [wrap] ".to_string())
+    );
+
+    grid.end_search();
+    assert!(grid.search_highlights().is_empty());
+    assert_eq!(grid.search_indication(), None);
+}
+
+#[test]
+fn copy_mode_selects_words_and_blocks_with_the_keyboard() {
+    use zellij_utils::input::actions::{CopyMotion, SelectionKind};
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(3, 20, Palette::default());
+    let content = "hay\r\nfirst line\r\nsecond word here\r\nthird".as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    grid.start_copy_mode();
+    grid.move_copy_cursor(CopyMotion::Up);
+    grid.toggle_copy_selection(SelectionKind::Word);
+    grid.move_copy_cursor(CopyMotion::NextWord);
+    assert_eq!(grid.yank_copy_selection(), Some("second word".to_string()));
+    assert!(grid.selection.is_empty(), "yanking ends the selection");
+
+    grid.move_copy_cursor(CopyMotion::LineStart);
+    grid.toggle_copy_selection(SelectionKind::Block);
+    grid.move_copy_cursor(CopyMotion::Up);
+    grid.move_copy_cursor(CopyMotion::Up);
+    assert_eq!(
+        grid.scrollback_position_and_length().0,
+        1,
+        "moving past the top scrolls"
+    );
+    grid.move_copy_cursor(CopyMotion::Right);
+    grid.move_copy_cursor(CopyMotion::Right);
+    assert_eq!(
+        grid.yank_copy_selection(),
+        Some("hay\nfir\nsec".to_string())
+    );
+
+    grid.end_copy_mode();
+    assert_eq!(grid.yank_copy_selection(), None);
+}
+
+#[test]
+fn last_command_output_is_taken_from_the_semantic_prompt_marks() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 20, Palette::default());
+    assert_eq!(grid.last_command_output(), None);
+    let content = "\u{1b}]133;A\u{7}$ ls\r\n\u{1b}]133;C\u{7}a.txt\tb.txt\r\nc.txt\r\n\u{1b}]133;D;0\u{7}\u{1b}]133;A\u{7}$ "
+        .as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    assert_eq!(
+        grid.last_command_output(),
+        Some("a.txt\tb.txt\nc.txt\n".to_string())
+    );
+
+    let content = "clear\r\n\u{1b}]133;C\u{7}\u{1b}[2J\u{1b}]133;A\u{7}$ ".as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    assert_eq!(
+        grid.last_command_output(),
+        None,
+        "a command printing nothing has no output"
+    );
+}
+
+#[test]
+fn commands_are_remembered_with_their_working_directory_and_exit_code() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 10, Palette::default());
+    let content = "\u{1b}]7;file://host/tmp/my%20dir\u{7}\u{1b}]133;A\u{7}$ \u{1b}]133;B\u{7}ls   --all\r\n\u{1b}]133;C\u{7}nope\r\n\u{1b}]133;D;2\u{7}\u{1b}]133;A\u{7}$ \u{1b}]133;B\u{7}\r\n\u{1b}]133;C\u{7}\u{1b}]133;D;0\u{7}"
+        .as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    let history = grid.command_history();
+    assert_eq!(history.len(), 1, "an empty command line is left out");
+    assert_eq!(
+        history[0].command, "ls   --all",
+        "the command wraps to the next row"
+    );
+    assert_eq!(history[0].cwd.as_deref(), Some("/tmp/my dir"));
+    assert_eq!(history[0].exit_code, Some(2));
+}
+
+#[test]
+fn hyperlinks_cover_the_text_printed_between_their_start_and_end() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(5, 40, Palette::default());
+    let content = "see \u{1b}]8;id=1;http://example.com/a;b\u{1b}\\the docs\u{1b}]8;;\u{1b}\\ or \u{1b}]8;;http://example.com/a;b\u{7}these\u{1b}]8;;\u{7}"
+        .as_bytes();
+    for &byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    assert_eq!(grid.link_at(&Position::new(0, 3)), None);
+    assert_eq!(
+        grid.link_at(&Position::new(0, 4)),
+        Some("http://example.com/a;b")
+    );
+    assert_eq!(
+        grid.link_at(&Position::new(0, 11)),
+        Some("http://example.com/a;b")
+    );
+    assert_eq!(grid.link_at(&Position::new(0, 12)), None);
+    assert_eq!(
+        grid.link_at(&Position::new(0, 16)),
+        Some("http://example.com/a;b"),
+        "the same target can be linked again"
+    );
+    assert_eq!(grid.links.len(), 1, "targets are stored once");
+
+    assert!(grid.hover(Some(&Position::new(0, 5))));
+    assert!(!grid.hover(Some(&Position::new(0, 6))), "same link");
+    assert!(grid.hover(None));
+}
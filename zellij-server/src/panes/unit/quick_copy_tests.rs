@@ -0,0 +1,121 @@
+use super::*;
+use crate::panes::terminal_character::EMPTY_TERMINAL_CHARACTER;
+
+fn line(text: &str) -> Vec<TerminalCharacter> {
+    text.chars()
+        .map(|character| TerminalCharacter {
+            character,
+            ..EMPTY_TERMINAL_CHARACTER
+        })
+        .collect()
+}
+
+#[test]
+fn hints_for_default_patterns() {
+    let lines = vec![
+        line("commit 8c27009 baseline"),
+        line("connect to 10.0.0.1 or https://zellij.dev/docs"),
+    ];
+    let quick_copy = QuickCopy::new(&lines, &compile_quick_copy_patterns(None));
+    let hints: Vec<(&str, usize, usize, &str)> = quick_copy
+        .hints()
+        .map(|h| (h.label.as_str(), h.line, h.column, h.text.as_str()))
+        .collect();
+    assert_eq!(
+        hints,
+        vec![
+            ("a", 0, 7, "8c27009"),
+            ("s", 1, 11, "10.0.0.1"),
+            ("d", 1, 23, "https://zellij.dev/docs"),
+        ]
+    );
+}
+
+#[test]
+fn typing_a_label_copies_its_text() {
+    let lines = vec![line("8c27009 e5c1cd9")];
+    let mut quick_copy = QuickCopy::new(&lines, &compile_quick_copy_patterns(None));
+    assert_eq!(
+        quick_copy.handle_input(b"s"),
+        QuickCopyInput::Copy("e5c1cd9".to_string())
+    );
+}
+
+#[test]
+fn typing_anything_else_cancels() {
+    let lines = vec![line("8c27009")];
+    let mut quick_copy = QuickCopy::new(&lines, &compile_quick_copy_patterns(None));
+    assert_eq!(quick_copy.handle_input(&[27]), QuickCopyInput::Cancel);
+}
+
+#[test]
+fn two_letter_labels_when_there_are_many_hints() {
+    let text = vec!["1234567"; 30].join(" ");
+    let lines = vec![line(&text)];
+    let mut quick_copy = QuickCopy::new(&lines, &compile_quick_copy_patterns(None));
+    assert_eq!(quick_copy.hints().count(), 30);
+    assert_eq!(quick_copy.handle_input(b"a"), QuickCopyInput::Pending);
+    assert_eq!(
+        quick_copy.hints().count(),
+        26,
+        "only hints starting with 'a' are left"
+    );
+    assert_eq!(
+        quick_copy.handle_input(b"s"),
+        QuickCopyInput::Copy("1234567".to_string())
+    );
+}
+
+#[test]
+fn custom_patterns_replace_default_ones() {
+    let lines = vec![line("token=abc123 8c27009")];
+    let patterns = compile_quick_copy_patterns(Some(&[r"token=\w+".to_string()][..]));
+    let quick_copy = QuickCopy::new(&lines, &patterns);
+    let texts: Vec<&str> = quick_copy.hints().map(|h| h.text.as_str()).collect();
+    assert_eq!(texts, vec!["token=abc123"]);
+}
+
+#[test]
+fn link_hints_open_or_copy_urls_and_absolute_paths() {
+    let lines = vec![line(
+        "see https://zellij.dev/docs and /etc/zellij/config.yaml, not a/b",
+    )];
+    let patterns = compile_link_hint_patterns(None);
+    let mut link_hints = QuickCopy::for_links(&lines, &patterns);
+    let hints: Vec<(usize, &str)> = link_hints
+        .hints()
+        .map(|h| (h.column, h.text.as_str()))
+        .collect();
+    assert_eq!(
+        hints,
+        vec![
+            (4, "https://zellij.dev/docs"),
+            (32, "/etc/zellij/config.yaml"),
+        ]
+    );
+    assert_eq!(
+        link_hints.handle_input(b"a"),
+        QuickCopyInput::Open("https://zellij.dev/docs".to_string())
+    );
+
+    let mut link_hints = QuickCopy::for_links(&lines, &patterns);
+    assert_eq!(
+        link_hints.handle_input(b"S"),
+        QuickCopyInput::Copy("/etc/zellij/config.yaml".to_string()),
+        "capitals copy"
+    );
+}
+
+#[test]
+fn only_a_hint_group_narrows_the_text_of_a_match() {
+    let lines = vec![line("see https://zellij.dev token=abc123")];
+    let patterns = compile_quick_copy_patterns(Some(
+        &[
+            r"(https?)://\S+".to_string(),
+            r"token=(?P<hint>\w+)".to_string(),
+        ][..],
+    ));
+    let quick_copy = QuickCopy::new(&lines, &patterns);
+    let texts: Vec<&str> = quick_copy.hints().map(|h| h.text.as_str()).collect();
+    assert_eq!(texts, vec!["https://zellij.dev", "abc123"]);
+}
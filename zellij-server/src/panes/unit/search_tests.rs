@@ -0,0 +1,125 @@
+use super::*;
+use crate::panes::terminal_character::EMPTY_TERMINAL_CHARACTER;
+
+fn line(text: &str) -> Vec<TerminalCharacter> {
+    text.chars()
+        .map(|character| TerminalCharacter {
+            character,
+            ..EMPTY_TERMINAL_CHARACTER
+        })
+        .collect()
+}
+
+fn search_for(needle: &str) -> Search {
+    let mut search = Search::default();
+    for c in needle.chars() {
+        search.update_needle(&c.to_string());
+    }
+    search
+}
+
+#[test]
+fn finds_matches_ignoring_case_by_default() {
+    let lines = vec![line("Error: disk full"), line("no error, error again")];
+    let mut search = search_for("error");
+    search.find_matches(&lines);
+    let starts: Vec<(usize, usize)> = search
+        .matches_in(&lines)
+        .iter()
+        .map(|m| (m.line, m.start))
+        .collect();
+    assert_eq!(starts, vec![(0, 0), (1, 3), (1, 10)]);
+
+    search.toggle(SearchOption::CaseSensitivity);
+    search.find_matches(&lines);
+    assert_eq!(
+        search.matches_in(&lines).len(),
+        2,
+        "Error no longer matches"
+    );
+}
+
+#[test]
+fn moves_between_matches_wrapping_around() {
+    let lines = vec![line("a"), line("b a"), line("a")];
+    let mut search = search_for("a");
+    search.find_matches(&lines);
+    search.select_last_before(2);
+    assert_eq!(search.active_match().map(|m| m.line), Some(1));
+
+    assert!(search.move_active(SearchDirection::Down));
+    assert_eq!(search.active_match().map(|m| m.line), Some(2));
+    assert!(
+        search.move_active(SearchDirection::Down),
+        "wraps to the top"
+    );
+    assert_eq!(search.active_match().map(|m| m.line), Some(0));
+
+    search.toggle(SearchOption::Wrap);
+    assert!(!search.move_active(SearchDirection::Up), "stays at the top");
+    assert_eq!(search.indication(), Some(" SEARCH: a 1/3 ".to_string()));
+}
+
+#[test]
+fn clearing_the_needle_drops_the_matches() {
+    let lines = vec![line("abc")];
+    let mut search = search_for("b");
+    search.find_matches(&lines);
+    search.update_needle("\0");
+    search.find_matches(&lines);
+    assert!(search.is_active());
+    assert_eq!(
+        search.indication(),
+        Some(" SEARCH:  0/0 [wrap] ".to_string())
+    );
+    search.end();
+    assert_eq!(search.indication(), None);
+}
+
+fn tab_search_with_results(count: usize) -> TabSearch {
+    TabSearch {
+        search: search_for("a"),
+        results: (0..count)
+            .map(|index| TabSearchResult {
+                pane_id: PaneId::Terminal(1),
+                pane_title: "Pane #1".into(),
+                index,
+                line: format!("  line {}", index),
+            })
+            .collect(),
+        selected: 0,
+    }
+}
+
+#[test]
+fn tab_search_selection_wraps_around_the_list() {
+    let mut tab_search = tab_search_with_results(3);
+    tab_search.move_selection(SearchDirection::Up);
+    assert_eq!(tab_search.selected, 2, "wraps to the bottom");
+    tab_search.move_selection(SearchDirection::Down);
+    assert_eq!(tab_search.selected, 0, "wraps to the top");
+
+    tab_search.search.toggle(SearchOption::Wrap);
+    tab_search.move_selection(SearchDirection::Up);
+    assert_eq!(tab_search.selected, 0, "stays at the top");
+}
+
+#[test]
+fn tab_search_list_keeps_the_selected_result_in_view() {
+    let mut tab_search = tab_search_with_results(20);
+    tab_search.selected = 15;
+    let viewport = Viewport {
+        x: 0,
+        y: 0,
+        rows: 5,
+        cols: 30,
+    };
+    let list = tab_search.render(&viewport);
+    assert!(list.contains(" SEARCH TAB: a 16/20 "));
+    assert!(
+        list.contains(" Pane #1: line 12 "),
+        "results above fill the list"
+    );
+    assert!(list.contains("\u{1b}[1;30;43m Pane #1: line 15 "));
+    assert!(!list.contains("line 16"), "the list ends at the selection");
+}
@@ -54,6 +54,7 @@ fn contains() {
         start: Position::new(10, 5),
         end: Position::new(40, 20),
         active: false,
+        block: false,
     };
 
     let test_cases = vec![
@@ -104,6 +105,7 @@ fn sorted() {
         start: Position::new(1, 1),
         end: Position::new(10, 2),
         active: false,
+        block: false,
     };
     let sorted_selection = selection.sorted();
     assert_eq!(selection.start, sorted_selection.start);
@@ -113,6 +115,7 @@ fn sorted() {
         start: Position::new(10, 2),
         end: Position::new(1, 1),
         active: false,
+        block: false,
     };
     let sorted_selection = selection.sorted();
     assert_eq!(selection.end, sorted_selection.start);
@@ -125,6 +128,7 @@ fn line_indices() {
         start: Position::new(1, 1),
         end: Position::new(10, 2),
         active: false,
+        block: false,
     };
 
     assert_eq!(selection.line_indices(), (1..=10))
@@ -138,6 +142,7 @@ fn move_up_inactive() {
         start,
         end,
         active: false,
+        block: false,
     };
 
     inactive_selection.move_up(2);
@@ -156,6 +161,7 @@ fn move_up_active() {
         start,
         end,
         active: true,
+        block: false,
     };
 
     inactive_selection.move_up(2);
@@ -171,6 +177,7 @@ fn move_down_inactive() {
         start,
         end,
         active: false,
+        block: false,
     };
 
     inactive_selection.move_down(2);
@@ -189,6 +196,7 @@ fn move_down_active() {
         start,
         end,
         active: true,
+        block: false,
     };
 
     inactive_selection.move_down(2);
@@ -1,6 +1,8 @@
 use super::super::TerminalPane;
-use crate::tab::Pane;
+use crate::tab::{Pane, PaneGridOptions};
 use ::insta::assert_snapshot;
+use zellij_utils::input::options::Options;
+use zellij_utils::input::theme::{PaletteColorFromYaml, PaletteOverride};
 use zellij_utils::pane_size::PaneGeom;
 use zellij_utils::zellij_tile::data::Palette;
 
@@ -25,3 +27,107 @@ pub fn scrolling_inside_a_pane() {
     terminal_pane.clear_scroll();
     assert_snapshot!(format!("{:?}", terminal_pane.grid));
 }
+
+#[test]
+pub fn redrawing_in_place_does_not_blank_the_pane_first() {
+    let mut fake_win_size = PaneGeom::default();
+    fake_win_size.cols.set_inner(121);
+    fake_win_size.rows.set_inner(20);
+
+    let pid = 1;
+    let palette = Palette::default();
+    let mut terminal_pane = TerminalPane::new(pid, fake_win_size, palette, 0); // 0 is the pane index
+    terminal_pane.grid.redraw_in_place = true;
+    terminal_pane.handle_pty_bytes("line 1\r\nline 2".as_bytes().to_vec());
+    terminal_pane.render();
+    terminal_pane.grid.clear_viewport_before_rendering = true;
+    terminal_pane.set_should_render(true);
+
+    let output = terminal_pane.render().unwrap();
+    assert_eq!(
+        output.matches("H\u{1b}[m").count(),
+        20,
+        "Each line is drawn once, over what was there"
+    );
+    assert!(output.contains("line 2"));
+}
+
+#[test]
+pub fn ligature_safe_boundaries_end_each_row_at_the_edge_of_the_pane() {
+    let mut fake_win_size = PaneGeom::default();
+    fake_win_size.cols.set_inner(121);
+    fake_win_size.rows.set_inner(20);
+
+    let pid = 1;
+    let palette = Palette::default();
+    let mut terminal_pane = TerminalPane::new(pid, fake_win_size, palette, 0); // 0 is the pane index
+    terminal_pane.set_grid_options(PaneGridOptions::from_options(&Options {
+        ligature_safe_boundaries: true,
+        ..Default::default()
+    }));
+    terminal_pane.handle_pty_bytes("line 1\r\nline 2".as_bytes().to_vec());
+
+    let output = terminal_pane.render().unwrap();
+    for row in 1..=20 {
+        assert!(
+            output.contains(&format!("\u{1b}[m\u{1b}[{};122H", row)),
+            "Row {} ends with a cursor move past the pane",
+            row
+        );
+    }
+}
+
+#[test]
+pub fn palette_override_replaces_the_default_and_terminal_colors() {
+    let mut fake_win_size = PaneGeom::default();
+    fake_win_size.cols.set_inner(121);
+    fake_win_size.rows.set_inner(20);
+
+    let pid = 1;
+    let palette = Palette::default();
+    let mut terminal_pane = TerminalPane::new(pid, fake_win_size, palette, 0); // 0 is the pane index
+    terminal_pane.set_palette_override(Some(PaletteOverride {
+        bg: Some(PaletteColorFromYaml::Rgb((64, 0, 0))),
+        red: Some(PaletteColorFromYaml::Rgb((255, 128, 128))),
+        ..Default::default()
+    }));
+    terminal_pane.handle_pty_bytes("plain \u{1b}[31mred\u{1b}[m".as_bytes().to_vec());
+
+    let output = terminal_pane.render().unwrap();
+    assert!(
+        output.contains("\u{1b}[48;2;64;0;0m"),
+        "Default background is replaced"
+    );
+    assert!(
+        output.contains("\u{1b}[38;2;255;128;128m"),
+        "Red is replaced"
+    );
+    assert!(!output.contains("\u{1b}[31m"));
+}
+
+#[test]
+pub fn the_working_directory_reported_by_the_shell_is_taken_once_per_change() {
+    let mut fake_win_size = PaneGeom::default();
+    fake_win_size.cols.set_inner(121);
+    fake_win_size.rows.set_inner(20);
+
+    let mut terminal_pane = TerminalPane::new(1, fake_win_size, Palette::default(), 0);
+    assert_eq!(terminal_pane.cwd(), None);
+    terminal_pane.handle_pty_bytes(b"\x1b]7;file://localhost/tmp/my%20dir\x07$ ".to_vec());
+    assert_eq!(terminal_pane.take_cwd_change(), Some("/tmp/my dir".into()));
+    assert_eq!(terminal_pane.take_cwd_change(), None);
+    terminal_pane.handle_pty_bytes(b"\x1b]7;file://localhost/tmp/my%20dir\x07$ ".to_vec());
+    assert_eq!(
+        terminal_pane.take_cwd_change(),
+        None,
+        "the prompt reports the same directory again"
+    );
+    assert_eq!(terminal_pane.cwd(), Some("/tmp/my dir".into()));
+    terminal_pane.handle_pty_bytes(b"\x1b]7;file://remote.example/home/me\x07$ ".to_vec());
+    assert_eq!(
+        terminal_pane.take_cwd_change(),
+        None,
+        "the directory of a shell on another machine is not taken"
+    );
+    assert_eq!(terminal_pane.cwd(), Some("/home/me".into()));
+}
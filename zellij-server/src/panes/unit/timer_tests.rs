@@ -0,0 +1,49 @@
+use super::*;
+
+#[test]
+fn countdown_reads_done_once_it_elapsed() {
+    let start = Instant::now();
+    let mut timer = RunningTimer::new(
+        PaneTimer {
+            countdown: Some(300),
+            on_elapsed: Some("notify-send done".into()),
+        },
+        start,
+    );
+    assert_eq!(timer.indication(start), " TIMER: 05:00 ");
+    let later = start + Duration::from_millis(500);
+    assert_eq!(timer.indication(later), " TIMER: 05:00 ", "rounds up");
+    assert!(!timer.tick(later));
+
+    let end = start + Duration::from_secs(300);
+    assert!(timer.tick(end));
+    assert!(!timer.tick(end), "elapses once");
+    assert!(!timer.is_ticking());
+    assert_eq!(timer.indication(end), " TIMER: DONE ");
+    assert_eq!(timer.on_elapsed(), Some("notify-send done"));
+}
+
+#[test]
+fn stopwatch_counts_up_without_elapsing() {
+    let start = Instant::now();
+    let mut timer = RunningTimer::new(PaneTimer::default(), start);
+    let later = start + Duration::from_secs(3723);
+    assert!(!timer.tick(later));
+    assert!(timer.is_ticking());
+    assert_eq!(timer.indication(later), " STOPWATCH: 1:02:03 ");
+}
+
+#[test]
+fn countdown_too_long_for_the_clock_does_not_panic() {
+    let start = Instant::now();
+    let mut timer = RunningTimer::new(
+        PaneTimer {
+            countdown: Some(u64::MAX),
+            on_elapsed: None,
+        },
+        start,
+    );
+    let later = start + Duration::from_secs(3600);
+    assert!(!timer.tick(later));
+    assert!(timer.is_ticking());
+}
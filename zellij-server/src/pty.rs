@@ -1,8 +1,10 @@
 use crate::{
-    os_input_output::{AsyncReader, ChildId, ServerOsApi},
+    editor::open_file_keys,
+    os_input_output::{AsyncReader, ChildId, ClientId, Pid, ServerOsApi},
     panes::PaneId,
     screen::ScreenInstruction,
-    thread_bus::{Bus, ThreadSenders},
+    serialization::SessionSerialization,
+    thread_bus::{ActionReply, Bus, ThreadSenders},
     wasm_vm::PluginInstruction,
     ServerInstruction,
 };
@@ -12,9 +14,10 @@ use async_std::{
 };
 use std::{
     collections::HashMap,
-    env,
+    env, fs,
     os::unix::io::RawFd,
-    path::PathBuf,
+    path::{Path, PathBuf},
+    thread,
     time::{Duration, Instant},
 };
 use zellij_utils::{
@@ -23,78 +26,266 @@ use zellij_utils::{
     input::{
         command::{RunCommand, TerminalAction},
         layout::{Layout, LayoutFromYaml, Run, TabLayout},
+        options::{Options, ShutdownSignal},
     },
+    ipc::ActionResult,
     logging::debug_to_file,
+    nix::sys::signal::Signal,
+    serde_yaml,
 };
 
+const DEFAULT_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(3);
+// how often the panes are checked on while waiting for them to exit
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
 pub type VteBytes = Vec<u8>;
 
 /// Instructions related to PTYs (pseudoterminals).
 #[derive(Clone, Debug)]
 pub(crate) enum PtyInstruction {
-    SpawnTerminal(Option<TerminalAction>),
-    SpawnTerminalVertically(Option<TerminalAction>),
-    SpawnTerminalHorizontally(Option<TerminalAction>),
+    SpawnTerminal(Option<TerminalAction>, Option<ActionReply>),
+    // opens a file written for the new pane, which is deleted once the pane is closed
+    SpawnTerminalOnTempFile(TerminalAction, Option<ActionReply>),
+    SpawnTerminalVertically(Option<TerminalAction>, Option<ActionReply>),
+    SpawnTerminalHorizontally(Option<TerminalAction>, Option<ActionReply>),
+    SpawnTerminalAutoSplit(Option<TerminalAction>, Option<ActionReply>),
+    SpawnTerminalSuppressed(Option<TerminalAction>, Option<ActionReply>),
+    SpawnViewer(PaneId, Option<ActionReply>), // a read only pane showing the output of this one
     UpdateActivePane(Option<PaneId>),
-    NewTab(Option<TerminalAction>, Option<TabLayout>),
+    UpdatePaneCwd(RawFd, PathBuf), // the working directory the shell of the pane reported
+    NewTab(
+        Option<TerminalAction>,
+        Option<TabLayout>,
+        Option<ActionReply>,
+    ),
+    NewTabForPane(RawFd), // a new tab with this terminal, moved from its tab, instead of a shell
+    DuplicateTab(
+        Option<TerminalAction>,
+        TabLayout,
+        Vec<PaneId>, // the panes of the duplicated tab, in the order its layout spawns them
+        bool,        // whether to run the commands of the panes again
+        Option<ActionReply>,
+    ),
+    ApplyLayoutToTab(
+        Option<TerminalAction>,
+        TabLayout, // loaded from its layout file, if it has one
+        usize,     // the index of the tab
+        usize,     // the number of terminals the tab shows, taking the first places of the layout
+        Option<ActionReply>,
+    ),
+    DumpLayout(
+        PathBuf,
+        Vec<(TabLayout, Vec<PaneId>)>, // the panes of each tab, in the order its layout spawns them
+        Option<ActionReply>,
+    ),
+    SerializeSession(
+        Box<SessionSerialization>,
+        Vec<(TabLayout, Vec<PaneId>)>, // the panes of each tab, in the order its layout spawns them
+        HashMap<PaneId, String>,       // the end of the scrollback of the panes
+        Option<ActionReply>,           // dropped once the session is written
+    ),
+    EditFile(PathBuf, bool, Option<ActionReply>), // whether to reuse a pane running the editor
+    GoToTab(u32), // goes to the tab once the tabs opened before are there
     ClosePane(PaneId),
     CloseTab(Vec<PaneId>),
+    // handled for the client, the screen opening what it asked for with its focus
+    FromClient(ClientId, Box<PtyInstruction>),
     Exit,
 }
 
 impl From<&PtyInstruction> for PtyContext {
     fn from(pty_instruction: &PtyInstruction) -> Self {
         match *pty_instruction {
-            PtyInstruction::SpawnTerminal(_) => PtyContext::SpawnTerminal,
-            PtyInstruction::SpawnTerminalVertically(_) => PtyContext::SpawnTerminalVertically,
-            PtyInstruction::SpawnTerminalHorizontally(_) => PtyContext::SpawnTerminalHorizontally,
+            PtyInstruction::SpawnTerminal(..) => PtyContext::SpawnTerminal,
+            PtyInstruction::SpawnTerminalOnTempFile(..) => PtyContext::SpawnTerminalOnTempFile,
+            PtyInstruction::SpawnTerminalVertically(..) => PtyContext::SpawnTerminalVertically,
+            PtyInstruction::SpawnTerminalHorizontally(..) => PtyContext::SpawnTerminalHorizontally,
+            PtyInstruction::SpawnTerminalAutoSplit(..) => PtyContext::SpawnTerminalAutoSplit,
+            PtyInstruction::SpawnTerminalSuppressed(..) => PtyContext::SpawnTerminalSuppressed,
+            PtyInstruction::SpawnViewer(..) => PtyContext::SpawnViewer,
             PtyInstruction::UpdateActivePane(_) => PtyContext::UpdateActivePane,
+            PtyInstruction::UpdatePaneCwd(..) => PtyContext::UpdatePaneCwd,
+            PtyInstruction::NewTabForPane(_) => PtyContext::NewTabForPane,
+            PtyInstruction::DuplicateTab(..) => PtyContext::DuplicateTab,
+            PtyInstruction::ApplyLayoutToTab(..) => PtyContext::ApplyLayoutToTab,
+            PtyInstruction::DumpLayout(..) => PtyContext::DumpLayout,
+            PtyInstruction::SerializeSession(..) => PtyContext::SerializeSession,
+            PtyInstruction::EditFile(..) => PtyContext::EditFile,
+            PtyInstruction::GoToTab(_) => PtyContext::GoToTab,
             PtyInstruction::ClosePane(_) => PtyContext::ClosePane,
             PtyInstruction::CloseTab(_) => PtyContext::CloseTab,
             PtyInstruction::NewTab(..) => PtyContext::NewTab,
+            PtyInstruction::FromClient(_, ref instruction) => (&**instruction).into(),
             PtyInstruction::Exit => PtyContext::Exit,
         }
     }
 }
 
+/// How the programs running in the panes are stopped when the session quits.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ShutdownPolicy {
+    /// The signal of the programs the shells of the panes run
+    pub signal: ShutdownSignal,
+    pub grace_period: Duration,
+}
+
+impl ShutdownPolicy {
+    pub fn from_options(options: &Options) -> Self {
+        ShutdownPolicy {
+            signal: options.shutdown_signal.unwrap_or_default(),
+            grace_period: options
+                .shutdown_grace_period
+                .map_or(DEFAULT_SHUTDOWN_GRACE_PERIOD, Duration::from_millis),
+        }
+    }
+}
+
+// a pane on its way out when the session quits
+struct ExitingPane {
+    child: ChildId,
+    // the process group of the program running in the foreground, until it is gone
+    program: Option<Pid>,
+    description: String,
+}
+
 pub(crate) struct Pty {
     pub active_pane: Option<PaneId>,
     pub bus: Bus<PtyInstruction>,
     pub id_to_child_pid: HashMap<RawFd, ChildId>,
+    // what each terminal was started with, to start it again in a duplicated tab
+    id_to_terminal_action: HashMap<RawFd, TerminalAction>,
+    // the working directories the shells of the terminals reported with OSC 7
+    id_to_cwd: HashMap<RawFd, PathBuf>,
+    // the files written for the terminals to open, deleted once they are closed
+    id_to_temp_file: HashMap<RawFd, PathBuf>,
     debug_to_file: bool,
     task_handles: HashMap<RawFd, JoinHandle<()>>,
+    shutdown: ShutdownPolicy,
+    // whether new panes start in the working directory of the focused pane
+    inherit_cwd: bool,
 }
 
-pub(crate) fn pty_thread_main(mut pty: Pty, layout: LayoutFromYaml) {
+/// Returns the panes that had to be killed when the session quit.
+pub(crate) fn pty_thread_main(
+    mut pty: Pty,
+    layout: LayoutFromYaml,
+    layout_dir: Option<PathBuf>,
+    layout_vars: Vec<(String, String)>,
+) -> Vec<String> {
     loop {
         let (event, mut err_ctx) = pty.bus.recv().expect("failed to receive event on channel");
         err_ctx.add_call(ContextType::Pty((&event).into()));
+        // what is sent to the screen handling the instruction is for its client, if any
+        let event = match event {
+            PtyInstruction::FromClient(client_id, instruction) => {
+                pty.bus.senders.client_id = Some(client_id);
+                *instruction
+            }
+            event => {
+                pty.bus.senders.client_id = None;
+                event
+            }
+        };
         match event {
-            PtyInstruction::SpawnTerminal(terminal_action) => {
+            PtyInstruction::SpawnTerminal(terminal_action, reply) => {
                 let pid = pty.spawn_terminal(terminal_action);
                 pty.bus
                     .senders
-                    .send_to_screen(ScreenInstruction::NewPane(PaneId::Terminal(pid)))
+                    .send_to_screen(ScreenInstruction::NewPane(PaneId::Terminal(pid), reply))
                     .unwrap();
             }
-            PtyInstruction::SpawnTerminalVertically(terminal_action) => {
+            PtyInstruction::SpawnTerminalOnTempFile(terminal_action, reply) => {
+                let temp_file = match &terminal_action {
+                    TerminalAction::OpenFile(file) | TerminalAction::PageFile(file) => {
+                        Some(file.clone())
+                    }
+                    TerminalAction::PipeFile(file, _) => Some(file.clone()),
+                    TerminalAction::RunCommand(_) => None,
+                };
+                let pid = pty.spawn_terminal(Some(terminal_action));
+                if let Some(temp_file) = temp_file {
+                    pty.id_to_temp_file.insert(pid, temp_file);
+                }
+                pty.bus
+                    .senders
+                    .send_to_screen(ScreenInstruction::NewPane(PaneId::Terminal(pid), reply))
+                    .unwrap();
+            }
+            PtyInstruction::SpawnTerminalVertically(terminal_action, reply) => {
+                let pid = pty.spawn_terminal(terminal_action);
+                pty.bus
+                    .senders
+                    .send_to_screen(ScreenInstruction::VerticalSplit(
+                        PaneId::Terminal(pid),
+                        reply,
+                    ))
+                    .unwrap();
+            }
+            PtyInstruction::SpawnTerminalHorizontally(terminal_action, reply) => {
                 let pid = pty.spawn_terminal(terminal_action);
                 pty.bus
                     .senders
-                    .send_to_screen(ScreenInstruction::VerticalSplit(PaneId::Terminal(pid)))
+                    .send_to_screen(ScreenInstruction::HorizontalSplit(
+                        PaneId::Terminal(pid),
+                        reply,
+                    ))
                     .unwrap();
             }
-            PtyInstruction::SpawnTerminalHorizontally(terminal_action) => {
+            PtyInstruction::SpawnTerminalAutoSplit(terminal_action, reply) => {
                 let pid = pty.spawn_terminal(terminal_action);
                 pty.bus
                     .senders
-                    .send_to_screen(ScreenInstruction::HorizontalSplit(PaneId::Terminal(pid)))
+                    .send_to_screen(ScreenInstruction::AutoSplit(PaneId::Terminal(pid), reply))
+                    .unwrap();
+            }
+            PtyInstruction::SpawnTerminalSuppressed(terminal_action, reply) => {
+                let pid = pty.spawn_terminal(terminal_action);
+                pty.bus
+                    .senders
+                    .send_to_screen(ScreenInstruction::NewSuppressedPane(
+                        PaneId::Terminal(pid),
+                        reply,
+                    ))
+                    .unwrap();
+            }
+            PtyInstruction::SpawnViewer(viewed_pane_id, reply) => {
+                // the terminal of a viewer runs a command that waits without printing anything,
+                // what it shows comes from the viewed pane
+                let waiting_command = RunCommand {
+                    command: PathBuf::from("cat"),
+                    ..Default::default()
+                };
+                let pid = pty.spawn_terminal(Some(TerminalAction::RunCommand(waiting_command)));
+                pty.bus
+                    .senders
+                    .send_to_screen(ScreenInstruction::NewViewerPane(
+                        PaneId::Terminal(pid),
+                        viewed_pane_id,
+                        reply,
+                    ))
                     .unwrap();
             }
             PtyInstruction::UpdateActivePane(pane_id) => {
                 pty.set_active_pane(pane_id);
             }
-            PtyInstruction::NewTab(terminal_action, tab_layout) => {
+            PtyInstruction::UpdatePaneCwd(id, cwd) => {
+                pty.id_to_cwd.insert(id, cwd);
+            }
+            PtyInstruction::NewTab(terminal_action, tab_layout, reply) => {
+                let tab_layout = tab_layout.map(|tab_layout| {
+                    tab_layout
+                        .clone()
+                        .load_layout_file(layout_dir.as_ref(), &layout_vars)
+                        .unwrap_or_else(|e| {
+                            log::error!("Failed to load the layout of the new tab: {}", e);
+                            tab_layout
+                        })
+                });
+                let terminal_action = pty.shell_in_tab(terminal_action, tab_layout.as_ref());
+                let tab_layout = tab_layout.map(|mut tab_layout| {
+                    tab_layout.pass_cwd_and_env_to_commands();
+                    tab_layout
+                });
                 let tab_name = tab_layout.as_ref().and_then(|layout| {
                     if layout.name.is_empty() {
                         None
@@ -104,37 +295,161 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: LayoutFromYaml) {
                 });
 
                 let merged_layout = layout.template.clone().insert_tab_layout(tab_layout);
-                pty.spawn_terminals_for_layout(merged_layout.into(), terminal_action.clone());
-
-                if let Some(tab_name) = tab_name {
-                    // clear current name at first
-                    pty.bus
-                        .senders
-                        .send_to_screen(ScreenInstruction::UpdateTabName(vec![0]))
-                        .unwrap();
-                    pty.bus
-                        .senders
-                        .send_to_screen(ScreenInstruction::UpdateTabName(tab_name.into_bytes()))
-                        .unwrap();
+                pty.spawn_terminals_for_layout(
+                    merged_layout.into(),
+                    terminal_action.clone(),
+                    None,
+                    tab_name,
+                    reply,
+                );
+            }
+            PtyInstruction::NewTabForPane(pid) => {
+                let merged_layout = layout.template.clone().insert_tab_layout(None);
+                pty.spawn_terminals_for_layout(merged_layout.into(), None, Some(pid), None, None);
+            }
+            PtyInstruction::DuplicateTab(
+                terminal_action,
+                mut tab_layout,
+                pane_ids,
+                rerun,
+                reply,
+            ) => {
+                let runs: Vec<Option<Run>> = pane_ids
+                    .into_iter()
+                    .map(|pane_id| pty.run_duplicate_of(pane_id, terminal_action.clone(), rerun))
+                    .collect();
+                set_leaf_runs(&mut tab_layout, &mut runs.into_iter());
+                let merged_layout = layout.template.clone().insert_tab_layout(Some(tab_layout));
+                pty.spawn_terminals_for_layout(
+                    merged_layout.into(),
+                    terminal_action,
+                    None,
+                    None,
+                    reply,
+                );
+            }
+            PtyInstruction::ApplyLayoutToTab(
+                terminal_action,
+                tab_layout,
+                tab_index,
+                pane_count,
+                reply,
+            ) => {
+                match tab_layout.load_layout_file(layout_dir.as_ref(), &layout_vars) {
+                    Ok(mut tab_layout) => {
+                        let shell = pty.shell_in_tab(terminal_action, Some(&tab_layout));
+                        tab_layout.pass_cwd_and_env_to_commands();
+                        let merged_layout: Layout = layout
+                            .template
+                            .clone()
+                            .insert_tab_layout(Some(tab_layout))
+                            .into();
+                        // the places of plugins are taken by the plugins of the tab
+                        let new_pids = merged_layout
+                            .extract_run_instructions()
+                            .into_iter()
+                            .filter(|run| !matches!(run, Some(Run::Plugin(Some(_)))))
+                            .skip(pane_count)
+                            .map(|run| match run {
+                                Some(Run::Command(command)) => {
+                                    pty.spawn_terminal(Some(TerminalAction::RunCommand(command)))
+                                }
+                                _ => pty.spawn_terminal(shell.clone()),
+                            })
+                            .collect();
+                        pty.bus
+                            .senders
+                            .send_to_screen(ScreenInstruction::ApplyLayoutToTab(
+                                tab_index,
+                                merged_layout,
+                                new_pids,
+                                reply,
+                            ))
+                            .unwrap();
+                    }
+                    Err(e) => {
+                        log::error!("Failed to load the layout to apply: {}", e);
+                        pty.refuse(format!("Can't apply the layout: {}", e), reply);
+                    }
+                }
+            }
+            PtyInstruction::EditFile(file, reuse, reply) => {
+                pty.edit_file(file, reuse, reply);
+            }
+            PtyInstruction::DumpLayout(path, tabs, reply) => {
+                let dumped_layout = LayoutFromYaml {
+                    template: layout.template.clone(),
+                    tabs: pty.layouts_with_runs(tabs, HashMap::new()),
+                    ..Default::default()
+                };
+                let written = serde_yaml::to_string(&dumped_layout)
+                    .map_err(|e| e.to_string())
+                    .and_then(|yaml| fs::write(&path, yaml).map_err(|e| e.to_string()));
+                if let Err(e) = written {
+                    log::error!("Failed to write the layout to {:?}: {}", path, e);
+                    pty.refuse(format!("Can't write the layout: {}", e), reply);
+                }
+            }
+            PtyInstruction::SerializeSession(serialization, tabs, scrollbacks, reply) => {
+                let session_layout = LayoutFromYaml {
+                    template: layout.template.clone(),
+                    tabs: pty.layouts_with_runs(tabs, scrollbacks),
+                    ..Default::default()
+                };
+                if let Err(e) = serialization.write(session_layout) {
+                    log::error!("Failed to write the session to disk: {}", e);
                 }
+                drop(reply);
+            }
+            PtyInstruction::GoToTab(tab_index) => {
+                pty.bus
+                    .senders
+                    .send_to_screen(ScreenInstruction::GoToTab(tab_index))
+                    .unwrap();
             }
             PtyInstruction::ClosePane(id) => {
                 pty.close_pane(id);
                 pty.bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(
+                        pty.bus.senders.client_id,
+                    ))
                     .unwrap();
             }
             PtyInstruction::CloseTab(ids) => {
                 pty.close_tab(ids);
                 pty.bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(
+                        pty.bus.senders.client_id,
+                    ))
                     .unwrap();
             }
+            // instructions for a client are unwrapped above
+            PtyInstruction::FromClient(..) => {}
             PtyInstruction::Exit => break,
         }
     }
+    pty.shut_down()
+}
+
+fn to_signal(signal: ShutdownSignal) -> Signal {
+    match signal {
+        ShutdownSignal::Hup => Signal::SIGHUP,
+        ShutdownSignal::Term => Signal::SIGTERM,
+        ShutdownSignal::Int => Signal::SIGINT,
+    }
+}
+
+// gives the panes of `layout` that have no parts the runs of `runs`, in the order the layout
+// spawns them
+fn set_leaf_runs(layout: &mut TabLayout, runs: &mut dyn Iterator<Item = Option<Run>>) {
+    if layout.parts.is_empty() {
+        layout.run = runs.next().flatten();
+    }
+    for part in layout.parts.iter_mut() {
+        set_leaf_runs(part, runs);
+    }
 }
 
 enum ReadResult {
@@ -234,27 +549,184 @@ fn stream_terminal_bytes(
 }
 
 impl Pty {
-    pub fn new(bus: Bus<PtyInstruction>, debug_to_file: bool) -> Self {
+    // tells whoever asked for the action, and the clients, why it could not be carried out
+    fn refuse(&self, reason: String, reply: Option<ActionReply>) {
+        if let Some(reply) = reply {
+            let _ = reply.send(ActionResult::Refused(reason.clone()));
+        }
+        self.bus
+            .senders
+            .send_to_server(ServerInstruction::Diagnostic(reason))
+            .unwrap();
+    }
+    pub fn new(
+        bus: Bus<PtyInstruction>,
+        debug_to_file: bool,
+        shutdown: ShutdownPolicy,
+        inherit_cwd: bool,
+    ) -> Self {
         Pty {
             active_pane: None,
             bus,
             id_to_child_pid: HashMap::new(),
+            id_to_terminal_action: HashMap::new(),
+            id_to_cwd: HashMap::new(),
+            id_to_temp_file: HashMap::new(),
             debug_to_file,
             task_handles: HashMap::new(),
+            shutdown,
+            inherit_cwd,
         }
     }
+    /// The `terminal_action`, or the default shell, started in the working directory of
+    /// `tab_layout` and with its environment, if it sets them.
+    fn shell_in_tab(
+        &self,
+        terminal_action: Option<TerminalAction>,
+        tab_layout: Option<&TabLayout>,
+    ) -> Option<TerminalAction> {
+        let tab_layout = match tab_layout {
+            Some(tab_layout) if tab_layout.cwd.is_some() || !tab_layout.env.is_empty() => {
+                tab_layout
+            }
+            _ => return terminal_action,
+        };
+        let mut shell = terminal_action.unwrap_or_else(|| self.get_default_terminal());
+        if let TerminalAction::RunCommand(command) = &mut shell {
+            if let Some(cwd) = &tab_layout.cwd {
+                command.cwd = Some(cwd.clone());
+            }
+            command.env.extend(tab_layout.env.clone());
+        }
+        Some(shell)
+    }
+    /// The shell, started in the working directory of the focused pane unless the `inherit_cwd`
+    /// option turns it off, and with the environment the focused pane was started with (eg. the
+    /// one of its tab).
     pub fn get_default_terminal(&self) -> TerminalAction {
+        let focused_id = match self.active_pane {
+            Some(PaneId::Terminal(id)) => Some(id),
+            _ => None,
+        };
+        let cwd = focused_id
+            .filter(|_| self.inherit_cwd)
+            .and_then(|id| self.terminal_cwd(id));
+        let env = focused_id
+            .and_then(|id| match self.id_to_terminal_action.get(&id) {
+                Some(TerminalAction::RunCommand(command)) => Some(command.env.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
         TerminalAction::RunCommand(RunCommand {
             args: vec![],
             command: PathBuf::from(env::var("SHELL").expect("Could not find the SHELL variable")),
-            cwd: self
-                .active_pane
-                .and_then(|pane| match pane {
-                    PaneId::Plugin(..) => None,
-                    PaneId::Terminal(id) => self.id_to_child_pid.get(&id).and_then(|id| id.shell),
-                })
-                .and_then(|id| self.bus.os_input.as_ref().map(|input| input.get_cwd(id)))
-                .flatten(),
+            cwd,
+            env,
+            ..Default::default()
+        })
+    }
+    /// The working directory of the terminal `id`: the one its shell reported with OSC 7 if it
+    /// still exists, or else the one of the process of the shell.
+    fn terminal_cwd(&self, id: RawFd) -> Option<PathBuf> {
+        if let Some(cwd) = self.id_to_cwd.get(&id).filter(|cwd| cwd.is_dir()) {
+            return Some(cwd.clone());
+        }
+        let shell_pid = self.id_to_child_pid.get(&id)?.shell?;
+        self.bus.os_input.as_ref()?.get_cwd(shell_pid)
+    }
+    /// What to run in the place of `pane_id` in a duplicate of its tab: its command with
+    /// `rerun`, or else `shell`, in the directory the pane is in.
+    fn run_duplicate_of(
+        &self,
+        pane_id: PaneId,
+        shell: Option<TerminalAction>,
+        rerun: bool,
+    ) -> Option<Run> {
+        let id = match pane_id {
+            PaneId::Terminal(id) => id,
+            // plugins of the tab are not loaded again, their places get shells
+            PaneId::Plugin(_) => return None,
+        };
+        let cwd = self.terminal_cwd(id);
+        let terminal_action = match self.id_to_terminal_action.get(&id) {
+            Some(terminal_action) if rerun => terminal_action.clone(),
+            _ => shell.unwrap_or_else(|| self.get_default_terminal()),
+        };
+        match terminal_action {
+            TerminalAction::RunCommand(mut command) => {
+                if cwd.is_some() {
+                    command.cwd = cwd;
+                }
+                Some(Run::Command(command))
+            }
+            // files are not opened again
+            TerminalAction::OpenFile(_)
+            | TerminalAction::PageFile(_)
+            | TerminalAction::PipeFile(..) => None,
+        }
+    }
+    /// The layouts of `tabs` running what their panes run, in their working directories. The
+    /// panes in `scrollbacks` show it again when the layout is loaded.
+    fn layouts_with_runs(
+        &self,
+        tabs: Vec<(TabLayout, Vec<PaneId>)>,
+        mut scrollbacks: HashMap<PaneId, String>,
+    ) -> Vec<TabLayout> {
+        tabs.into_iter()
+            .map(|(mut tab_layout, pane_ids)| {
+                let runs: Vec<Option<Run>> = pane_ids
+                    .into_iter()
+                    .map(|pane_id| {
+                        let mut run = self.run_duplicate_of(pane_id, None, true);
+                        if let Some(Run::Command(command)) = run.as_mut() {
+                            command.scrollback = scrollbacks.remove(&pane_id);
+                        }
+                        run
+                    })
+                    .collect();
+                set_leaf_runs(&mut tab_layout, &mut runs.into_iter());
+                tab_layout
+            })
+            .collect()
+    }
+    /// Opens `file` in the editor of the session in a new pane or, with `reuse`, in a pane
+    /// the editor already runs in if it is one that can be told to open it.
+    fn edit_file(&mut self, file: PathBuf, reuse: bool, reply: Option<ActionReply>) {
+        let editor = match env::var("EDITOR").or_else(|_| env::var("VISUAL")) {
+            Ok(editor) => editor,
+            Err(_) => {
+                return self.refuse(
+                    "Can't edit the file, neither EDITOR nor VISUAL is set".into(),
+                    reply,
+                )
+            }
+        };
+        if let Some((id, keys)) = self.editor_pane(&editor, &file).filter(|_| reuse) {
+            let pane_id = PaneId::Terminal(id);
+            self.bus
+                .senders
+                .send_to_screen(ScreenInstruction::FocusPane(pane_id))
+                .unwrap();
+            self.bus
+                .senders
+                .send_to_screen(ScreenInstruction::WriteToPane(pane_id, keys))
+                .unwrap();
+            return;
+        }
+        let pid = self.spawn_terminal(Some(TerminalAction::OpenFile(file)));
+        self.bus
+            .senders
+            .send_to_screen(ScreenInstruction::NewPane(PaneId::Terminal(pid), reply))
+            .unwrap();
+    }
+    // the first terminal running `editor` in its foreground, with the keys opening `file` there
+    fn editor_pane(&self, editor: &str, file: &Path) -> Option<(RawFd, Vec<u8>)> {
+        let os_input = self.bus.os_input.as_ref()?;
+        let mut ids: Vec<RawFd> = self.id_to_child_pid.keys().copied().collect();
+        ids.sort_unstable();
+        ids.into_iter().find_map(|id| {
+            let command = os_input.get_foreground_command(id)?;
+            open_file_keys(editor, &command, file).map(|keys| (id, keys))
         })
     }
     pub fn spawn_terminal(&mut self, terminal_action: Option<TerminalAction>) -> RawFd {
@@ -264,33 +736,54 @@ impl Pty {
             .os_input
             .as_mut()
             .unwrap()
-            .spawn_terminal(terminal_action);
+            .spawn_terminal(terminal_action.clone());
         let task_handle = stream_terminal_bytes(
             pid_primary,
-            self.bus.senders.clone(),
+            // the output of the pane is for no client in particular
+            self.bus.senders.for_client(None),
             self.bus.os_input.as_ref().unwrap().clone(),
             self.debug_to_file,
         );
         self.task_handles.insert(pid_primary, task_handle);
         self.id_to_child_pid.insert(pid_primary, child_id);
+        self.id_to_terminal_action
+            .insert(pid_primary, terminal_action);
         pid_primary
     }
     pub fn spawn_terminals_for_layout(
         &mut self,
         layout: Layout,
         default_shell: Option<TerminalAction>,
+        moved_pid: Option<RawFd>,
+        tab_name: Option<String>,
+        reply: Option<ActionReply>,
     ) {
         let default_shell = default_shell.unwrap_or_else(|| self.get_default_terminal());
         let extracted_run_instructions = layout.extract_run_instructions();
         let mut new_pane_pids = vec![];
+        let mut scrollbacks = vec![];
+        let mut pid_to_place = moved_pid;
         for run_instruction in extracted_run_instructions {
             match run_instruction {
-                Some(Run::Command(command)) => {
+                Some(Run::Command(mut command)) => {
+                    let scrollback = command.scrollback.take();
                     let cmd = TerminalAction::RunCommand(command);
-                    let (pid_primary, child_id): (RawFd, ChildId) =
-                        self.bus.os_input.as_mut().unwrap().spawn_terminal(cmd);
+                    let (pid_primary, child_id): (RawFd, ChildId) = self
+                        .bus
+                        .os_input
+                        .as_mut()
+                        .unwrap()
+                        .spawn_terminal(cmd.clone());
                     self.id_to_child_pid.insert(pid_primary, child_id);
+                    self.id_to_terminal_action.insert(pid_primary, cmd);
                     new_pane_pids.push(pid_primary);
+                    if let Some(scrollback) = scrollback {
+                        scrollbacks.push((pid_primary, scrollback));
+                    }
+                }
+                None if pid_to_place.is_some() => {
+                    // the moved terminal takes the place of the first shell, it already runs
+                    new_pane_pids.push(pid_to_place.take().unwrap());
                 }
                 None => {
                     let (pid_primary, child_id): (RawFd, ChildId) = self
@@ -300,6 +793,8 @@ impl Pty {
                         .unwrap()
                         .spawn_terminal(default_shell.clone());
                     self.id_to_child_pid.insert(pid_primary, child_id);
+                    self.id_to_terminal_action
+                        .insert(pid_primary, default_shell.clone());
                     new_pane_pids.push(pid_primary);
                 }
                 // Investigate moving plugin loading to here.
@@ -311,12 +806,27 @@ impl Pty {
             .send_to_screen(ScreenInstruction::ApplyLayout(
                 layout,
                 new_pane_pids.clone(),
+                tab_name,
+                reply,
             ))
             .unwrap();
+        // the panes of a resurrected session show what they last did before their programs start
+        for (id, scrollback) in scrollbacks {
+            self.bus
+                .senders
+                .send_to_screen(ScreenInstruction::PtyBytes(
+                    id,
+                    scrollback.replace('\n', "\r\n").into_bytes(),
+                ))
+                .unwrap();
+        }
         for id in new_pane_pids {
+            if Some(id) == moved_pid {
+                continue;
+            }
             let task_handle = stream_terminal_bytes(
                 id,
-                self.bus.senders.clone(),
+                self.bus.senders.for_client(None),
                 self.bus.os_input.as_ref().unwrap().clone(),
                 self.debug_to_file,
             );
@@ -327,6 +837,9 @@ impl Pty {
         match id {
             PaneId::Terminal(id) => {
                 let pids = self.id_to_child_pid.remove(&id).unwrap();
+                self.id_to_terminal_action.remove(&id);
+                self.id_to_cwd.remove(&id);
+                self.remove_temp_file(id);
                 let handle = self.task_handles.remove(&id).unwrap();
                 task::block_on(async {
                     self.bus
@@ -356,6 +869,123 @@ impl Pty {
             ),
         }
     }
+    /// Stops the programs running in the panes as the session quits: the program in the
+    /// foreground of each pane gets the signal of the pane, its shell SIGHUP once it is gone, and
+    /// whatever still runs at the end of the grace period is killed. Returns the panes that had
+    /// to be killed.
+    // deletes the file written for the terminal, unless another terminal still has it open
+    fn remove_temp_file(&mut self, id: RawFd) {
+        if let Some(temp_file) = self.id_to_temp_file.remove(&id) {
+            if !self.id_to_temp_file.values().any(|file| *file == temp_file) {
+                let _ = fs::remove_file(temp_file);
+            }
+        }
+    }
+    pub fn shut_down(&mut self) -> Vec<String> {
+        for (_, temp_file) in self.id_to_temp_file.drain() {
+            let _ = fs::remove_file(temp_file);
+        }
+        let os_input = match self.bus.os_input.clone() {
+            Some(os_input) => os_input,
+            None => return vec![],
+        };
+        // what the panes write no longer goes anywhere
+        for (_, handle) in self.task_handles.drain() {
+            task::block_on(handle.cancel());
+        }
+        let deadline = Instant::now() + self.shutdown.grace_period;
+        let mut ids: Vec<RawFd> = self.id_to_child_pid.keys().copied().collect();
+        ids.sort_unstable();
+        let mut panes: Vec<ExitingPane> = ids
+            .into_iter()
+            .map(|id| {
+                let child = self.id_to_child_pid.remove(&id).unwrap();
+                let program = os_input
+                    .get_foreground_process_group(id)
+                    .filter(|group| Some(*group) != child.shell);
+                let pane = ExitingPane {
+                    description: self.describe_pane(id),
+                    child,
+                    program,
+                };
+                let own_signal = self.shutdown_signal_of(id);
+                match pane.program {
+                    Some(group) => os_input.send_signal(
+                        Pid::from_raw(-group.as_raw()),
+                        to_signal(own_signal.unwrap_or(self.shutdown.signal)),
+                    ),
+                    // the shell is in the foreground, or the pane runs a command without one
+                    None => os_input.send_signal(
+                        pane.child.shell.unwrap_or(pane.child.primary),
+                        own_signal.map_or(Signal::SIGHUP, to_signal),
+                    ),
+                }
+                pane
+            })
+            .collect();
+        loop {
+            for pane in panes.iter_mut() {
+                let program_exited = pane.program.map_or(false, |group| {
+                    !os_input.is_running(Pid::from_raw(-group.as_raw()))
+                });
+                if program_exited {
+                    pane.program = None;
+                    if let Some(shell) = pane.child.shell {
+                        os_input.send_signal(shell, Signal::SIGHUP);
+                    }
+                }
+            }
+            // the first process of a pane exits with the shell (or the command) it started
+            panes.retain(|pane| os_input.is_running(pane.child.primary));
+            if panes.is_empty() || Instant::now() >= deadline {
+                break;
+            }
+            thread::sleep(SHUTDOWN_POLL_INTERVAL);
+        }
+        panes
+            .into_iter()
+            .map(|pane| {
+                let group = pane.program.map(|group| Pid::from_raw(-group.as_raw()));
+                let processes = group.into_iter().chain(pane.child.shell);
+                for pid in processes.chain(Some(pane.child.primary)) {
+                    os_input.send_signal(pid, Signal::SIGKILL);
+                }
+                log::warn!(
+                    "The {} didn't exit within the grace period, killed it",
+                    pane.description
+                );
+                pane.description
+            })
+            .collect()
+    }
+    // the signal the command of the pane gets when the session quits, if its layout sets one
+    fn shutdown_signal_of(&self, id: RawFd) -> Option<ShutdownSignal> {
+        match self.id_to_terminal_action.get(&id) {
+            Some(TerminalAction::RunCommand(command))
+            | Some(TerminalAction::PipeFile(_, command)) => command.shutdown_signal,
+            _ => None,
+        }
+    }
+    // eg. `pane running "npm run dev"`, after what runs in its foreground
+    fn describe_pane(&self, id: RawFd) -> String {
+        let command = self
+            .bus
+            .os_input
+            .as_ref()
+            .and_then(|os_input| os_input.get_foreground_command(id))
+            .map(|args| args.join(" "))
+            .or_else(|| match self.id_to_terminal_action.get(&id) {
+                Some(TerminalAction::RunCommand(command))
+                | Some(TerminalAction::PipeFile(_, command)) => {
+                    Some(command.command.display().to_string())
+                }
+                _ => None,
+            });
+        match command {
+            Some(command) => format!("pane running {:?}", command),
+            None => format!("pane {}", id),
+        }
+    }
     pub fn close_tab(&mut self, ids: Vec<PaneId>) {
         ids.iter().for_each(|&id| {
             self.close_pane(id);
@@ -374,3 +1004,7 @@ impl Drop for Pty {
         }
     }
 }
+
+#[cfg(test)]
+#[path = "./unit/pty_tests.rs"]
+mod pty_tests;
@@ -1,109 +1,205 @@
+use std::os::unix::io::RawFd;
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
 use zellij_utils::zellij_tile::data::Event;
 
 use crate::{
-    os_input_output::ServerOsApi, pty::PtyInstruction, screen::ScreenInstruction,
-    wasm_vm::PluginInstruction, ServerInstruction, SessionMetaData, SessionState,
+    clients::Clients,
+    os_input_output::{ClientId, ServerOsApi},
+    panes::{PaneId, SearchDirection, SearchOption},
+    pty::PtyInstruction,
+    screen::ScreenInstruction,
+    thread_bus::ActionReply,
+    wasm_vm::PluginInstruction,
+    ServerInstruction, SessionMetaData, SessionState,
 };
 use zellij_utils::{
-    channels::SenderWithContext,
+    action_token,
+    channels::{self, SenderWithContext},
     input::{
         actions::{Action, Direction},
         command::TerminalAction,
         get_mode_info,
+        options::Options,
     },
-    ipc::{ClientToServerMsg, ExitReason, ServerToClientMsg},
+    ipc::{ActionResult, ClientToServerMsg, ExitReason, ServerToClientMsg},
 };
 
+const WRONG_TOKEN: &str = "Missing or wrong action token for this session";
+const READ_ONLY: &str = "This client is read-only, it can only move its focus or detach";
+
+/// A budget of actions from the command line, refilled at a steady rate up to a burst of them,
+/// so that scripts flooding the session with actions can't keep it from responding to its user.
+pub(crate) struct ActionRateLimiter {
+    per_second: f64,
+    burst: f64,
+    budget: f64,
+    refilled_at: Instant,
+}
+
+impl ActionRateLimiter {
+    pub fn new(per_second: usize, burst: usize) -> Self {
+        ActionRateLimiter {
+            per_second: per_second as f64,
+            burst: burst.max(1) as f64,
+            budget: burst.max(1) as f64,
+            refilled_at: Instant::now(),
+        }
+    }
+    /// A limiter after the `cli_action_rate` and `cli_action_burst` options, if there is a rate.
+    pub fn from_options(options: &Options) -> Option<Self> {
+        let per_second = options.cli_action_rate?;
+        Some(ActionRateLimiter::new(
+            per_second,
+            options.cli_action_burst.unwrap_or(per_second),
+        ))
+    }
+    /// Takes an action out of the budget, returning whether there was one left.
+    pub fn try_take(&mut self) -> bool {
+        self.try_take_at(Instant::now())
+    }
+    fn try_take_at(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.budget = (self.budget + elapsed.as_secs_f64() * self.per_second).min(self.burst);
+        self.refilled_at = now;
+        if self.budget >= 1.0 {
+            self.budget -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
 fn route_action(
     action: Action,
     session: &SessionMetaData,
-    _os_input: &dyn ServerOsApi,
+    clients: &RwLock<Clients>,
+    os_input: &dyn ServerOsApi,
     to_server: &SenderWithContext<ServerInstruction>,
+    mut reply: Option<ActionReply>,
+    // the client sending the action, `None` for the command line
+    client_id: Option<ClientId>,
 ) -> bool {
     let mut should_break = false;
-    session
-        .senders
+    // the screen handles the actions of a client with its focus
+    let senders = session.senders.for_client(client_id);
+    senders
         .send_to_plugin(PluginInstruction::Update(None, Event::InputReceived))
         .unwrap();
     match action {
         Action::ToggleTab => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::ToggleTab)
                 .unwrap();
         }
+        Action::BreakPaneToNewTab => {
+            senders
+                .send_to_screen(ScreenInstruction::BreakPaneToNewTab)
+                .unwrap();
+        }
+        Action::BreakPaneLeft => {
+            senders
+                .send_to_screen(ScreenInstruction::BreakPaneLeft)
+                .unwrap();
+        }
+        Action::BreakPaneRight => {
+            senders
+                .send_to_screen(ScreenInstruction::BreakPaneRight)
+                .unwrap();
+        }
         Action::Write(val) => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::ClearScroll)
                 .unwrap();
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::WriteCharacter(val))
                 .unwrap();
         }
+        Action::Paste(val) => {
+            senders
+                .send_to_screen(ScreenInstruction::ClearScroll)
+                .unwrap();
+            senders
+                .send_to_screen(ScreenInstruction::Paste(val))
+                .unwrap();
+        }
+        Action::WriteToPane(pane_id, val) => {
+            senders
+                .send_to_screen(ScreenInstruction::WriteToPane(
+                    PaneId::Terminal(pane_id as RawFd),
+                    val,
+                ))
+                .unwrap();
+        }
         Action::SwitchToMode(mode) => {
             let palette = session.palette;
             // TODO: use the palette from the client and remove it from the server os api
             // this is left here as a stop gap measure until we shift some code around
             // to allow for this
-            session
-                .senders
+            senders
                 .send_to_plugin(PluginInstruction::Update(
                     None,
-                    Event::ModeUpdate(get_mode_info(mode, palette, session.capabilities)),
+                    Event::ModeUpdate(get_mode_info(
+                        mode,
+                        palette,
+                        session.capabilities,
+                        session.locale,
+                    )),
                 ))
                 .unwrap();
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::ChangeMode(get_mode_info(
                     mode,
                     palette,
                     session.capabilities,
+                    session.locale,
                 )))
                 .unwrap();
-            session
-                .senders
-                .send_to_screen(ScreenInstruction::Render)
-                .unwrap();
+            senders.send_to_screen(ScreenInstruction::Render).unwrap();
         }
-        Action::Resize(direction) => {
+        Action::Resize(direction, amount) => {
             let screen_instr = match direction {
-                Direction::Left => ScreenInstruction::ResizeLeft,
-                Direction::Right => ScreenInstruction::ResizeRight,
-                Direction::Up => ScreenInstruction::ResizeUp,
-                Direction::Down => ScreenInstruction::ResizeDown,
+                Direction::Left => ScreenInstruction::ResizeLeft(amount),
+                Direction::Right => ScreenInstruction::ResizeRight(amount),
+                Direction::Up => ScreenInstruction::ResizeUp(amount),
+                Direction::Down => ScreenInstruction::ResizeDown(amount),
+                Direction::Auto => return should_break, // panes only grow or shrink on one side
             };
-            session.senders.send_to_screen(screen_instr).unwrap();
+            senders.send_to_screen(screen_instr).unwrap();
         }
         Action::SwitchFocus => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::SwitchFocus)
                 .unwrap();
         }
         Action::FocusNextPane => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::FocusNextPane)
                 .unwrap();
         }
         Action::FocusPreviousPane => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::FocusPreviousPane)
                 .unwrap();
         }
+        Action::FocusPane(pane_id) => {
+            senders
+                .send_to_screen(ScreenInstruction::FocusPane(PaneId::Terminal(
+                    pane_id as RawFd,
+                )))
+                .unwrap();
+        }
         Action::MoveFocus(direction) => {
             let screen_instr = match direction {
                 Direction::Left => ScreenInstruction::MoveFocusLeft,
                 Direction::Right => ScreenInstruction::MoveFocusRight,
                 Direction::Up => ScreenInstruction::MoveFocusUp,
                 Direction::Down => ScreenInstruction::MoveFocusDown,
+                Direction::Auto => return should_break, // the focus has nowhere in particular to go
             };
-            session.senders.send_to_screen(screen_instr).unwrap();
+            senders.send_to_screen(screen_instr).unwrap();
         }
         Action::MoveFocusOrTab(direction) => {
             let screen_instr = match direction {
@@ -111,168 +207,561 @@ fn route_action(
                 Direction::Right => ScreenInstruction::MoveFocusRightOrNextTab,
                 _ => unreachable!(),
             };
-            session.senders.send_to_screen(screen_instr).unwrap();
+            senders.send_to_screen(screen_instr).unwrap();
+        }
+        Action::MovePane(direction) => {
+            let screen_instr = match direction {
+                Direction::Left => ScreenInstruction::MovePaneLeft,
+                Direction::Right => ScreenInstruction::MovePaneRight,
+                Direction::Up => ScreenInstruction::MovePaneUp,
+                Direction::Down => ScreenInstruction::MovePaneDown,
+                Direction::Auto => return should_break, // panes have nowhere in particular to go
+            };
+            senders.send_to_screen(screen_instr).unwrap();
+        }
+        Action::SwapPanes => {
+            senders
+                .send_to_screen(ScreenInstruction::SwapPanes)
+                .unwrap();
+        }
+        Action::BalancePanes => {
+            senders
+                .send_to_screen(ScreenInstruction::BalancePanes)
+                .unwrap();
         }
         Action::ScrollUp => {
-            session
-                .senders
-                .send_to_screen(ScreenInstruction::ScrollUp)
+            senders.send_to_screen(ScreenInstruction::ScrollUp).unwrap();
+        }
+        Action::ScrollUpPane(pane_id) => {
+            senders
+                .send_to_screen(ScreenInstruction::ScrollUpPane(PaneId::Terminal(
+                    pane_id as RawFd,
+                )))
                 .unwrap();
         }
         Action::ScrollUpAt(point) => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::ScrollUpAt(point))
                 .unwrap();
         }
         Action::ScrollDown => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::ScrollDown)
                 .unwrap();
         }
         Action::ScrollDownAt(point) => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::ScrollDownAt(point))
                 .unwrap();
         }
         Action::ScrollToBottom => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::ScrollToBottom)
                 .unwrap();
         }
+        Action::PageScrollback => {
+            senders
+                .send_to_screen(ScreenInstruction::PageScrollback)
+                .unwrap();
+        }
+        Action::EditScrollback => {
+            senders
+                .send_to_screen(ScreenInstruction::EditScrollback(reply.take()))
+                .unwrap();
+        }
+        Action::NewPaneWithStdinFromSelection(command) => {
+            senders
+                .send_to_screen(ScreenInstruction::NewPaneWithStdinFromSelection(
+                    command,
+                    reply.take(),
+                ))
+                .unwrap();
+        }
+        Action::ViewPane => {
+            senders
+                .send_to_screen(ScreenInstruction::ViewActivePane(reply.take()))
+                .unwrap();
+        }
+        Action::QuickCopy => {
+            senders
+                .send_to_screen(ScreenInstruction::QuickCopy)
+                .unwrap();
+        }
+        Action::LinkHints => {
+            senders
+                .send_to_screen(ScreenInstruction::LinkHints)
+                .unwrap();
+        }
+        Action::ClearScreen(clear_scrollback) => {
+            senders
+                .send_to_screen(ScreenInstruction::ClearScreen(clear_scrollback))
+                .unwrap();
+        }
+        Action::TogglePaneLink => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePaneLink)
+                .unwrap();
+        }
+        Action::TogglePaneReadOnly => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePaneReadOnly)
+                .unwrap();
+        }
+        Action::TogglePaneStacking => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePaneStacking)
+                .unwrap();
+        }
+        Action::TogglePanePresentation => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePanePresentation)
+                .unwrap();
+        }
+        Action::TogglePanePinned => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePanePinned)
+                .unwrap();
+        }
+        Action::SetPanePalette(palette) => {
+            senders
+                .send_to_screen(ScreenInstruction::SetPanePalette(palette))
+                .unwrap();
+        }
+        Action::SetPaneAmbiguousWidth(ambiguous_width) => {
+            senders
+                .send_to_screen(ScreenInstruction::SetPaneAmbiguousWidth(ambiguous_width))
+                .unwrap();
+        }
         Action::PageScrollUp => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::PageScrollUp)
                 .unwrap();
         }
         Action::PageScrollDown => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::PageScrollDown)
                 .unwrap();
         }
         Action::ToggleFocusFullscreen => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::ToggleActiveTerminalFullscreen)
                 .unwrap();
         }
+        Action::TogglePaneFullscreen(pane_id) => {
+            senders
+                .send_to_screen(ScreenInstruction::TogglePaneFullscreen(PaneId::Terminal(
+                    pane_id as RawFd,
+                )))
+                .unwrap();
+        }
         Action::TogglePaneFrames => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::TogglePaneFrames)
                 .unwrap();
         }
+        Action::ToggleCleanScreen => {
+            senders
+                .send_to_screen(ScreenInstruction::ToggleCleanScreen)
+                .unwrap();
+        }
+        Action::ToggleUI => {
+            senders.send_to_screen(ScreenInstruction::ToggleUI).unwrap();
+        }
         Action::NewPane(direction) => {
             let shell = session.default_shell.clone();
             let pty_instr = match direction {
-                Some(Direction::Left) => PtyInstruction::SpawnTerminalVertically(shell),
-                Some(Direction::Right) => PtyInstruction::SpawnTerminalVertically(shell),
-                Some(Direction::Up) => PtyInstruction::SpawnTerminalHorizontally(shell),
-                Some(Direction::Down) => PtyInstruction::SpawnTerminalHorizontally(shell),
-                // No direction specified - try to put it in the biggest available spot
-                None => PtyInstruction::SpawnTerminal(shell),
+                Some(Direction::Left) | Some(Direction::Right) => {
+                    PtyInstruction::SpawnTerminalVertically(shell, reply.take())
+                }
+                Some(Direction::Up) | Some(Direction::Down) => {
+                    PtyInstruction::SpawnTerminalHorizontally(shell, reply.take())
+                }
+                // No direction specified - split the focused pane along its longer side, or try to
+                // put it in the biggest available spot
+                Some(Direction::Auto) | None => {
+                    PtyInstruction::SpawnTerminalAutoSplit(shell, reply.take())
+                }
             };
-            session.senders.send_to_pty(pty_instr).unwrap();
+            senders.send_to_pty(pty_instr).unwrap();
         }
         Action::Run(command) => {
             let run_cmd = Some(TerminalAction::RunCommand(command.clone().into()));
             let pty_instr = match command.direction {
-                Some(Direction::Left) => PtyInstruction::SpawnTerminalVertically(run_cmd),
-                Some(Direction::Right) => PtyInstruction::SpawnTerminalVertically(run_cmd),
-                Some(Direction::Up) => PtyInstruction::SpawnTerminalHorizontally(run_cmd),
-                Some(Direction::Down) => PtyInstruction::SpawnTerminalHorizontally(run_cmd),
-                // No direction specified - try to put it in the biggest available spot
-                None => PtyInstruction::SpawnTerminal(run_cmd),
+                Some(Direction::Left) | Some(Direction::Right) => {
+                    PtyInstruction::SpawnTerminalVertically(run_cmd, reply.take())
+                }
+                Some(Direction::Up) | Some(Direction::Down) => {
+                    PtyInstruction::SpawnTerminalHorizontally(run_cmd, reply.take())
+                }
+                // No direction specified - split the focused pane along its longer side, or try to
+                // put it in the biggest available spot
+                Some(Direction::Auto) | None => {
+                    PtyInstruction::SpawnTerminalAutoSplit(run_cmd, reply.take())
+                }
             };
-            session.senders.send_to_pty(pty_instr).unwrap();
+            senders.send_to_pty(pty_instr).unwrap();
+        }
+        Action::NewSuppressedPane(command) => {
+            let run_cmd = command.map(|command| TerminalAction::RunCommand(command.into()));
+            senders
+                .send_to_pty(PtyInstruction::SpawnTerminalSuppressed(
+                    run_cmd,
+                    reply.take(),
+                ))
+                .unwrap();
+        }
+        Action::ShowSuppressedPane(pane_id) => {
+            senders
+                .send_to_screen(ScreenInstruction::ShowSuppressedPane(PaneId::Terminal(
+                    pane_id as RawFd,
+                )))
+                .unwrap();
         }
         Action::CloseFocus => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::CloseFocusedPane)
                 .unwrap();
         }
+        Action::ClosePane(pane_id) => {
+            senders
+                .send_to_screen(ScreenInstruction::ClosePaneWithId(PaneId::Terminal(
+                    pane_id as RawFd,
+                )))
+                .unwrap();
+        }
         Action::NewTab(tab_layout) => {
             let shell = session.default_shell.clone();
-            session
-                .senders
-                .send_to_pty(PtyInstruction::NewTab(shell, tab_layout))
+            senders
+                .send_to_pty(PtyInstruction::NewTab(shell, tab_layout, reply.take()))
+                .unwrap();
+        }
+        Action::DuplicateTab(rerun) => {
+            let shell = session.default_shell.clone();
+            senders
+                .send_to_screen(ScreenInstruction::DuplicateTab(shell, rerun, reply.take()))
+                .unwrap();
+        }
+        Action::DumpScreen(path, full, ansi) => {
+            senders
+                .send_to_screen(ScreenInstruction::DumpScreen(
+                    path,
+                    full,
+                    ansi,
+                    reply.take(),
+                ))
+                .unwrap();
+        }
+        Action::TagPane(tag) => {
+            senders
+                .send_to_screen(ScreenInstruction::TagFocusedPane(tag))
+                .unwrap();
+        }
+        Action::UntagPane(tag) => {
+            senders
+                .send_to_screen(ScreenInstruction::UntagFocusedPane(tag))
+                .unwrap();
+        }
+        Action::ClosePanesWithTag(tag) => {
+            senders
+                .send_to_screen(ScreenInstruction::ClosePanesWithTag(tag))
+                .unwrap();
+        }
+        Action::ListPanes(tag) => {
+            senders
+                .send_to_screen(ScreenInstruction::ListPanes(tag, reply.take()))
+                .unwrap();
+        }
+        Action::DumpLayout(path) => {
+            senders
+                .send_to_screen(ScreenInstruction::DumpLayout(path, reply.take()))
+                .unwrap();
+        }
+        Action::EditFile(file, reuse) => {
+            senders
+                .send_to_pty(PtyInstruction::EditFile(file, reuse, reply.take()))
+                .unwrap();
+        }
+        Action::NextSwapLayout => {
+            senders
+                .send_to_screen(ScreenInstruction::NextSwapLayout(reply.take()))
+                .unwrap();
+        }
+        Action::ApplyLayout(layout_path) => {
+            let shell = session.default_shell.clone();
+            senders
+                .send_to_screen(ScreenInstruction::ApplyLayoutToActiveTab(
+                    shell,
+                    layout_path,
+                    reply.take(),
+                ))
                 .unwrap();
         }
         Action::GoToNextTab => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::SwitchTabNext)
                 .unwrap();
         }
         Action::GoToPreviousTab => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::SwitchTabPrev)
                 .unwrap();
         }
         Action::ToggleActiveSyncTab => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::ToggleActiveSyncTab)
                 .unwrap();
         }
         Action::CloseTab => {
-            session
-                .senders
-                .send_to_screen(ScreenInstruction::CloseTab)
-                .unwrap();
+            senders.send_to_screen(ScreenInstruction::CloseTab).unwrap();
         }
         Action::GoToTab(i) => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::GoToTab(i))
                 .unwrap();
         }
         Action::TabNameInput(c) => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::UpdateTabName(c))
                 .unwrap();
         }
+        Action::SessionNameInput(c) => {
+            senders
+                .send_to_screen(ScreenInstruction::UpdateSessionName(c))
+                .unwrap();
+        }
+        Action::RenameSession(name) => {
+            to_server
+                .send(ServerInstruction::RenameSession(name, reply.take()))
+                .unwrap();
+        }
+        Action::PaneNameInput(c) => {
+            senders
+                .send_to_screen(ScreenInstruction::UpdatePaneName(c))
+                .unwrap();
+        }
+        Action::SearchInput(c) => {
+            senders
+                .send_to_screen(ScreenInstruction::UpdateSearch(c))
+                .unwrap();
+        }
+        Action::SearchNext => {
+            senders
+                .send_to_screen(ScreenInstruction::Search(SearchDirection::Down))
+                .unwrap();
+        }
+        Action::SearchPrev => {
+            senders
+                .send_to_screen(ScreenInstruction::Search(SearchDirection::Up))
+                .unwrap();
+        }
+        Action::SearchToggleCaseSensitivity => {
+            senders
+                .send_to_screen(ScreenInstruction::ToggleSearchOption(
+                    SearchOption::CaseSensitivity,
+                ))
+                .unwrap();
+        }
+        Action::SearchToggleWrap => {
+            senders
+                .send_to_screen(ScreenInstruction::ToggleSearchOption(SearchOption::Wrap))
+                .unwrap();
+        }
+        Action::TabSearchInput(c) => {
+            senders
+                .send_to_screen(ScreenInstruction::UpdateTabSearch(c))
+                .unwrap();
+        }
+        Action::TabSearchNext => {
+            senders
+                .send_to_screen(ScreenInstruction::TabSearch(SearchDirection::Down))
+                .unwrap();
+        }
+        Action::TabSearchPrev => {
+            senders
+                .send_to_screen(ScreenInstruction::TabSearch(SearchDirection::Up))
+                .unwrap();
+        }
+        Action::TabSearchJump => {
+            senders
+                .send_to_screen(ScreenInstruction::TabSearchJump)
+                .unwrap();
+        }
+        Action::CommandHistoryInput(c) => {
+            senders
+                .send_to_screen(ScreenInstruction::UpdateCommandHistory(c))
+                .unwrap();
+        }
+        Action::CommandHistoryNext => {
+            senders
+                .send_to_screen(ScreenInstruction::CommandHistory(SearchDirection::Down))
+                .unwrap();
+        }
+        Action::CommandHistoryPrev => {
+            senders
+                .send_to_screen(ScreenInstruction::CommandHistory(SearchDirection::Up))
+                .unwrap();
+        }
+        Action::CommandHistoryRun => {
+            senders
+                .send_to_screen(ScreenInstruction::CommandHistoryRun(false))
+                .unwrap();
+        }
+        Action::CommandHistoryRunInItsPane => {
+            senders
+                .send_to_screen(ScreenInstruction::CommandHistoryRun(true))
+                .unwrap();
+        }
+        Action::SessionManagerInput(c) => {
+            senders
+                .send_to_screen(ScreenInstruction::UpdateSessionManager(c))
+                .unwrap();
+        }
+        Action::SessionManagerNext => {
+            senders
+                .send_to_screen(ScreenInstruction::SessionManager(SearchDirection::Down))
+                .unwrap();
+        }
+        Action::SessionManagerPrev => {
+            senders
+                .send_to_screen(ScreenInstruction::SessionManager(SearchDirection::Up))
+                .unwrap();
+        }
+        Action::SessionManagerSwitch => {
+            senders
+                .send_to_screen(ScreenInstruction::SessionManagerSwitch)
+                .unwrap();
+        }
+        Action::SessionManagerRename => {
+            senders
+                .send_to_screen(ScreenInstruction::SessionManagerRename)
+                .unwrap();
+        }
+        Action::SessionManagerKill => {
+            senders
+                .send_to_screen(ScreenInstruction::SessionManagerKill)
+                .unwrap();
+        }
+        Action::CopyModeMove(motion) => {
+            senders
+                .send_to_screen(ScreenInstruction::CopyModeMove(motion))
+                .unwrap();
+        }
+        Action::CopyModeSelect(kind) => {
+            senders
+                .send_to_screen(ScreenInstruction::CopyModeSelect(kind))
+                .unwrap();
+        }
+        Action::CopyModeYank => {
+            senders
+                .send_to_screen(ScreenInstruction::CopyModeYank)
+                .unwrap();
+        }
+        Action::TabGroupNameInput(c) => {
+            senders
+                .send_to_screen(ScreenInstruction::UpdateTabGroupName(c))
+                .unwrap();
+        }
+        Action::MoveTabToNextGroup => {
+            senders
+                .send_to_screen(ScreenInstruction::MoveTabToNextGroup)
+                .unwrap();
+        }
+        Action::ToggleTabGroupCollapsed => {
+            senders
+                .send_to_screen(ScreenInstruction::ToggleTabGroupCollapsed)
+                .unwrap();
+        }
         Action::Quit => {
             to_server.send(ServerInstruction::ClientExit).unwrap();
             should_break = true;
         }
-        Action::Detach => {
-            to_server.send(ServerInstruction::DetachSession).unwrap();
-            should_break = true;
+        Action::Detach(target) => match target {
+            Some(target) if Some(target) != client_id => {
+                if clients.read().unwrap().ids().contains(&target) {
+                    // a client detached by another one is told so
+                    to_server
+                        .send(ServerInstruction::DetachSession(
+                            Some(target),
+                            ExitReason::DetachedByOtherClient,
+                        ))
+                        .unwrap();
+                } else {
+                    let reason = format!("There is no client {} to detach", target);
+                    match (reply.take(), client_id) {
+                        (Some(reply), _) => {
+                            let _ = reply.send(ActionResult::Refused(reason));
+                        }
+                        (None, Some(client_id)) => os_input
+                            .send_to_client(client_id, ServerToClientMsg::Diagnostic(reason)),
+                        (None, None) => {}
+                    }
+                }
+            }
+            _ => {
+                should_break = true;
+                to_server
+                    .send(ServerInstruction::DetachSession(
+                        client_id,
+                        ExitReason::Normal,
+                    ))
+                    .unwrap();
+            }
+        },
+        Action::DetachOtherClients => {
+            to_server
+                .send(ServerInstruction::DetachOtherClients(client_id))
+                .unwrap();
+        }
+        Action::SwitchSession(name, force) => {
+            to_server
+                .send(ServerInstruction::SwitchSession(name, force, client_id))
+                .unwrap();
         }
-        Action::LeftClick(point) => {
-            session
-                .senders
-                .send_to_screen(ScreenInstruction::LeftClick(point))
+        Action::LeftClick(point, modifiers) => {
+            senders
+                .send_to_screen(ScreenInstruction::LeftClick(point, modifiers))
+                .unwrap();
+        }
+        Action::MiddleClick(point, modifiers) => {
+            senders
+                .send_to_screen(ScreenInstruction::MiddleClick(point, modifiers))
                 .unwrap();
         }
         Action::MouseRelease(point) => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::MouseRelease(point))
                 .unwrap();
         }
         Action::MouseHold(point) => {
-            session
-                .senders
+            senders
                 .send_to_screen(ScreenInstruction::MouseHold(point))
                 .unwrap();
         }
+        Action::MouseMotion(point) => {
+            senders
+                .send_to_screen(ScreenInstruction::MouseMotion(point))
+                .unwrap();
+        }
         Action::Copy => {
-            session
-                .senders
-                .send_to_screen(ScreenInstruction::Copy)
+            senders.send_to_screen(ScreenInstruction::Copy).unwrap();
+        }
+        Action::StartPaneTimer(timer) => {
+            senders
+                .send_to_screen(ScreenInstruction::StartPaneTimer(timer))
+                .unwrap();
+        }
+        Action::StopPaneTimer => {
+            senders
+                .send_to_screen(ScreenInstruction::StopPaneTimer)
                 .unwrap();
         }
-        Action::NoOp => {}
+        // macros are expanded, and the clipboard read, by the client before any action is sent
+        // over
+        Action::NoOp | Action::Macro(_) | Action::PasteFromClipboard => {}
+    }
+    // actions that don't create anything have nothing to report back
+    if let Some(reply) = reply {
+        let _ = reply.send(ActionResult::Empty);
     }
     should_break
 }
@@ -280,49 +769,157 @@ fn route_action(
 pub(crate) fn route_thread_main(
     session_data: Arc<RwLock<Option<SessionMetaData>>>,
     session_state: Arc<RwLock<SessionState>>,
+    clients: Arc<RwLock<Clients>>,
     os_input: Box<dyn ServerOsApi>,
     to_server: SenderWithContext<ServerInstruction>,
+    action_token: Arc<String>,
 ) {
-    loop {
-        let (instruction, err_ctx) = os_input.recv_from_client();
+    // the id of the attached client this connection belongs to, rather than to a
+    // one-off command like `list-sessions`
+    let mut client_id = None;
+    // until the client goes away, or sends something that can't be read
+    while let Some((instruction, err_ctx)) = os_input.recv_from_client() {
         err_ctx.update_thread_ctx();
         let rlocked_sessions = session_data.read().unwrap();
 
         match instruction {
             ClientToServerMsg::Action(action) => {
+                // only attached clients, which sent the token when attaching, send bare actions
+                let id = match client_id {
+                    Some(id) => id,
+                    None => break,
+                };
+                let (action, allowed) = {
+                    let mut clients = clients.write().unwrap();
+                    let action = clients.action_in_session(id, action);
+                    let allowed = clients.allows(id, &action);
+                    (action, allowed)
+                };
+                if !allowed {
+                    // the client might wait for the action to be done before reading more input
+                    os_input.send_to_client(id, ServerToClientMsg::UnblockInputThread);
+                    os_input.send_to_client(id, ServerToClientMsg::Diagnostic(READ_ONLY.into()));
+                    continue;
+                }
                 if let Some(rlocked_sessions) = rlocked_sessions.as_ref() {
-                    if route_action(action, rlocked_sessions, &*os_input, &to_server) {
-                        break;
+                    if route_action(
+                        action,
+                        rlocked_sessions,
+                        &clients,
+                        &*os_input,
+                        &to_server,
+                        None,
+                        client_id,
+                    ) {
+                        // the client detached or quit the session, the server is done with it
+                        return;
                     }
                 }
             }
+            ClientToServerMsg::CliAction(action, token) => {
+                // the command line client waits for a single reply and disconnects
+                let mut result = ActionResult::Empty;
+                // actions without the token don't take from the budget of the others
+                let within_budget = || {
+                    rlocked_sessions
+                        .as_ref()
+                        .and_then(|session| session.cli_action_limiter.as_ref())
+                        .map_or(true, |limiter| limiter.lock().unwrap().try_take())
+                };
+                if !action_token::matches(&action_token, token.as_deref()) {
+                    result = ActionResult::Refused(WRONG_TOKEN.to_string());
+                } else if !within_budget() {
+                    result = ActionResult::Refused(
+                        "Too many actions from the command line, try again later".to_string(),
+                    );
+                } else if let Some(rlocked_sessions) = rlocked_sessions.as_ref() {
+                    let (reply, reply_receiver) = channels::bounded(1);
+                    route_action(
+                        action,
+                        rlocked_sessions,
+                        &clients,
+                        &*os_input,
+                        &to_server,
+                        Some(reply),
+                        None,
+                    );
+                    result = reply_receiver.recv().unwrap_or(ActionResult::Empty);
+                }
+                os_input.send_to_temp_client(ServerToClientMsg::ActionResult(result));
+                break;
+            }
             ClientToServerMsg::TerminalResize(new_size) => {
-                rlocked_sessions
-                    .as_ref()
-                    .unwrap()
-                    .senders
-                    .send_to_screen(ScreenInstruction::TerminalResize(new_size))
-                    .unwrap();
+                // the session takes the size of its smallest client
+                if let Some(client_id) = client_id {
+                    to_server
+                        .send(ServerInstruction::ResizeClient(client_id, new_size))
+                        .unwrap();
+                }
             }
-            ClientToServerMsg::NewClient(..) => {
+            ClientToServerMsg::TerminalFocus(focused) => {
+                if let Some(client_id) = client_id {
+                    to_server
+                        .send(ServerInstruction::FocusClient(client_id, focused))
+                        .unwrap();
+                }
+            }
+            ClientToServerMsg::NewClient(attrs, opts, options, layout) => {
                 if *session_state.read().unwrap() != SessionState::Uninitialized {
                     os_input.send_to_temp_client(ServerToClientMsg::Exit(ExitReason::Error(
                         "Cannot add new client".into(),
                     )));
                 } else {
-                    os_input.add_client_sender();
-                    to_server.send(instruction.into()).unwrap();
+                    let id = os_input.add_client_sender();
+                    client_id = Some(id);
+                    to_server
+                        .send(ServerInstruction::NewClient(
+                            attrs, opts, options, layout, id,
+                        ))
+                        .unwrap();
                 }
             }
-            ClientToServerMsg::AttachClient(_, force, _) => {
-                if *session_state.read().unwrap() == SessionState::Attached && !force {
-                    os_input.send_to_temp_client(ServerToClientMsg::Exit(ExitReason::CannotAttach));
+            ClientToServerMsg::AttachClient(attrs, force, options, token) => {
+                if !action_token::matches(&action_token, token.as_deref()) {
+                    os_input.send_to_temp_client(ServerToClientMsg::Exit(ExitReason::Error(
+                        WRONG_TOKEN.into(),
+                    )));
+                    break;
                 } else {
-                    os_input.add_client_sender();
-                    to_server.send(instruction.into()).unwrap();
+                    // attaches alongside the other clients, or instead of them when forced
+                    let id = os_input.add_client_sender();
+                    client_id = Some(id);
+                    to_server
+                        .send(ServerInstruction::AttachClient(attrs, force, options, id))
+                        .unwrap();
+                }
+            }
+            ClientToServerMsg::Ping => match client_id {
+                // attached clients ping to keep the connection alive, those that stop are detached
+                Some(client_id) => clients.write().unwrap().heard_from(client_id),
+                None => {
+                    os_input.send_to_temp_client(ServerToClientMsg::Pong);
+                    break;
+                }
+            },
+            ClientToServerMsg::SwitchSession(name) => {
+                if let Some(client_id) = client_id {
+                    to_server
+                        .send(ServerInstruction::LeaveForSession(name, client_id))
+                        .unwrap();
                 }
             }
             ClientToServerMsg::ClientExited => break,
         }
     }
+    // a client that is gone without detaching leaves the session to the others
+    if let Some(client_id) = client_id {
+        let _ = to_server.send(ServerInstruction::DetachSession(
+            Some(client_id),
+            ExitReason::Normal,
+        ));
+    }
 }
+
+#[cfg(test)]
+#[path = "./unit/route_tests.rs"]
+mod route_tests;
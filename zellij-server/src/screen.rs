@@ -1,52 +1,120 @@
 //! Things related to [`Screen`]s.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
 use std::os::unix::io::RawFd;
+use std::path::PathBuf;
 use std::str;
 use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use zellij_utils::pane_size::Size;
-use zellij_utils::{input::layout::Layout, position::Position, zellij_tile};
+use zellij_utils::{
+    input::layout::{Layout, SwapLayout, TabLayout},
+    position::Position,
+    zellij_tile,
+};
 
 use crate::{
-    panes::PaneId,
+    os_input_output::ClientId,
+    panes::{
+        compile_link_hint_patterns, compile_quick_copy_patterns, PaneId, SearchDirection,
+        SearchOption,
+    },
     pty::{PtyInstruction, VteBytes},
-    tab::Tab,
-    thread_bus::Bus,
+    serialization::{IdleExit, SessionSerialization},
+    session_manager::{
+        kill_other_session, other_sessions, rename_other_session, SessionStatus, SessionSummary,
+    },
+    tab::{
+        AutoSplitOptions, PaneClipboard, PaneFrameOptions, PaneGridOptions, PasteOptions, Tab,
+        TabClient,
+    },
+    thread_bus::{ActionReply, Bus},
     wasm_vm::PluginInstruction,
     ServerInstruction, SessionState,
 };
-use zellij_tile::data::{Event, ModeInfo, Palette, PluginCapabilities, TabInfo};
+use zellij_tile::data::{
+    Event, InputMode, ModeInfo, MouseModifiers, Palette, PluginCapabilities, TabInfo,
+};
 use zellij_utils::{
     errors::{ContextType, ScreenContext},
-    input::{get_mode_info, options::Options},
-    ipc::ClientAttributes,
+    input::{
+        actions::{CopyMotion, Direction, PaneTimer, ResizeAmount, SelectionKind},
+        command::{RunCommandAction, TerminalAction},
+        get_mode_info,
+        options::{AmbiguousWidth, Options, SmallPanesPolicy},
+        theme::PaletteOverride,
+    },
+    ipc::{ActionResult, ClientAttributes},
+    messages::Locale,
 };
 
+/// How often the screen renders while the terminal of the client is unfocused.
+const UNFOCUSED_RENDER_INTERVAL: Duration = Duration::from_secs(1);
+/// How often the timers of the panes tick while any of them runs.
+const PANE_TIMER_TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How much a session can hold, so that scripts sending actions from the command line can't
+/// exhaust the machine.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct SessionLimits {
+    pub max_panes_per_tab: Option<usize>,
+    pub max_tabs: Option<usize>,
+    /// In bytes, of the scrollback of all panes together.
+    pub max_scrollback_memory: Option<usize>,
+}
+
+impl SessionLimits {
+    pub fn from_options(options: &Options) -> Self {
+        SessionLimits {
+            max_panes_per_tab: options.max_panes_per_tab,
+            max_tabs: options.max_tabs,
+            max_scrollback_memory: options
+                .max_scrollback_memory
+                .map(|megabytes| megabytes * 1024 * 1024),
+        }
+    }
+}
+
 /// Instructions that can be sent to the [`Screen`].
 #[derive(Debug, Clone)]
 pub(crate) enum ScreenInstruction {
     PtyBytes(RawFd, VteBytes),
     Render,
-    NewPane(PaneId),
-    HorizontalSplit(PaneId),
-    VerticalSplit(PaneId),
+    NewPane(PaneId, Option<ActionReply>),
+    HorizontalSplit(PaneId, Option<ActionReply>),
+    VerticalSplit(PaneId, Option<ActionReply>),
+    AutoSplit(PaneId, Option<ActionReply>),
+    NewSuppressedPane(PaneId, Option<ActionReply>),
+    ShowSuppressedPane(PaneId),
     WriteCharacter(Vec<u8>),
-    ResizeLeft,
-    ResizeRight,
-    ResizeDown,
-    ResizeUp,
+    WriteToPane(PaneId, Vec<u8>),
+    Paste(Vec<u8>),
+    ResizeLeft(Option<ResizeAmount>),
+    ResizeRight(Option<ResizeAmount>),
+    ResizeDown(Option<ResizeAmount>),
+    ResizeUp(Option<ResizeAmount>),
     SwitchFocus,
     FocusNextPane,
     FocusPreviousPane,
+    FocusPane(PaneId),
     MoveFocusLeft,
     MoveFocusLeftOrPreviousTab,
     MoveFocusDown,
     MoveFocusUp,
     MoveFocusRight,
     MoveFocusRightOrNextTab,
+    MovePaneLeft,
+    MovePaneDown,
+    MovePaneUp,
+    MovePaneRight,
+    SwapPanes,
+    BalancePanes,
     Exit,
     ScrollUp,
+    ScrollUpPane(PaneId),
     ScrollUpAt(Position),
     ScrollDown,
     ScrollDownAt(Position),
@@ -54,25 +122,95 @@ pub(crate) enum ScreenInstruction {
     PageScrollUp,
     PageScrollDown,
     ClearScroll,
+    PageScrollback,
+    EditScrollback(Option<ActionReply>),
+    ViewActivePane(Option<ActionReply>),
+    NewPaneWithStdinFromSelection(RunCommandAction, Option<ActionReply>),
+    NewViewerPane(PaneId, PaneId, Option<ActionReply>), // the viewer, the pane it views
+    QuickCopy,
+    LinkHints,
+    ClearScreen(bool),
+    TogglePaneLink,
+    TogglePaneReadOnly,
+    TogglePaneStacking,
+    TogglePanePresentation,
+    TogglePanePinned,
+    SetPanePalette(Option<PaletteOverride>),
+    SetPaneAmbiguousWidth(Option<AmbiguousWidth>),
     CloseFocusedPane,
+    ClosePaneWithId(PaneId),
     ToggleActiveTerminalFullscreen,
+    TogglePaneFullscreen(PaneId),
     TogglePaneFrames,
+    ToggleCleanScreen,
+    ToggleUI,
     SetSelectable(PaneId, bool, usize),
     ClosePane(PaneId),
-    ApplyLayout(Layout, Vec<RawFd>),
+    ApplyLayout(Layout, Vec<RawFd>, Option<String>, Option<ActionReply>),
     SwitchTabNext,
     SwitchTabPrev,
     ToggleActiveSyncTab,
     CloseTab,
     GoToTab(u32),
+    CloseTabAt(u32),
     ToggleTab,
+    BreakPaneToNewTab,
+    BreakPaneLeft,
+    BreakPaneRight,
+    DuplicateTab(Option<TerminalAction>, bool, Option<ActionReply>),
+    ApplyLayoutToActiveTab(Option<TerminalAction>, PathBuf, Option<ActionReply>),
+    ApplyLayoutToTab(usize, Layout, Vec<RawFd>, Option<ActionReply>), // the index of the tab
+    NextSwapLayout(Option<ActionReply>),
+    DumpLayout(PathBuf, Option<ActionReply>),
+    SerializeSession(Option<ActionReply>), // the reply is dropped once the session is written
+    CheckIdle,
+    DumpScreen(PathBuf, bool, bool, Option<ActionReply>),
+    TagFocusedPane(String),
+    UntagFocusedPane(String),
+    ClosePanesWithTag(String),
+    ListPanes(Option<String>, Option<ActionReply>),
     UpdateTabName(Vec<u8>),
+    UpdatePaneName(Vec<u8>),
+    UpdateSearch(Vec<u8>),
+    Search(SearchDirection),
+    ToggleSearchOption(SearchOption),
+    UpdateTabSearch(Vec<u8>),
+    TabSearch(SearchDirection),
+    TabSearchJump,
+    UpdateSessionName(Vec<u8>),
+    RenameSession(String),
+    UpdateCommandHistory(Vec<u8>),
+    CommandHistory(SearchDirection),
+    CommandHistoryRun(bool),
+    UpdateSessionManager(Vec<u8>),
+    SessionManager(SearchDirection),
+    SessionManagerSwitch,
+    SessionManagerRename,
+    SessionManagerKill,
+    SessionManagerSessions(Vec<SessionSummary>),
+    CopyModeMove(CopyMotion),
+    CopyModeSelect(SelectionKind),
+    CopyModeYank,
+    UpdateTabGroupName(Vec<u8>),
+    MoveTabToNextGroup,
+    ToggleTabGroupCollapsed,
     TerminalResize(Size),
+    TerminalFocus(bool),
     ChangeMode(ModeInfo),
-    LeftClick(Position),
+    LeftClick(Position, MouseModifiers),
+    MiddleClick(Position, MouseModifiers),
     MouseRelease(Position),
     MouseHold(Position),
+    MouseMotion(Position),
     Copy,
+    StartPaneTimer(PaneTimer),
+    StopPaneTimer,
+    TickPaneTimers,
+    // handled with the focus of the client, its active tab and the active panes of its tabs
+    FromClient(ClientId, Box<ScreenInstruction>),
+    // with the mode the client starts in, taken when no other client is attached
+    AddClient(ClientId, Option<ModeInfo>),
+    RemoveClient(ClientId),
 }
 
 impl From<&ScreenInstruction> for ScreenContext {
@@ -80,17 +218,23 @@ impl From<&ScreenInstruction> for ScreenContext {
         match *screen_instruction {
             ScreenInstruction::PtyBytes(..) => ScreenContext::HandlePtyBytes,
             ScreenInstruction::Render => ScreenContext::Render,
-            ScreenInstruction::NewPane(_) => ScreenContext::NewPane,
-            ScreenInstruction::HorizontalSplit(_) => ScreenContext::HorizontalSplit,
-            ScreenInstruction::VerticalSplit(_) => ScreenContext::VerticalSplit,
+            ScreenInstruction::NewPane(..) => ScreenContext::NewPane,
+            ScreenInstruction::HorizontalSplit(..) => ScreenContext::HorizontalSplit,
+            ScreenInstruction::VerticalSplit(..) => ScreenContext::VerticalSplit,
+            ScreenInstruction::AutoSplit(..) => ScreenContext::AutoSplit,
+            ScreenInstruction::NewSuppressedPane(..) => ScreenContext::NewSuppressedPane,
+            ScreenInstruction::ShowSuppressedPane(_) => ScreenContext::ShowSuppressedPane,
             ScreenInstruction::WriteCharacter(_) => ScreenContext::WriteCharacter,
-            ScreenInstruction::ResizeLeft => ScreenContext::ResizeLeft,
-            ScreenInstruction::ResizeRight => ScreenContext::ResizeRight,
-            ScreenInstruction::ResizeDown => ScreenContext::ResizeDown,
-            ScreenInstruction::ResizeUp => ScreenContext::ResizeUp,
+            ScreenInstruction::WriteToPane(..) => ScreenContext::WriteToPane,
+            ScreenInstruction::Paste(_) => ScreenContext::Paste,
+            ScreenInstruction::ResizeLeft(_) => ScreenContext::ResizeLeft,
+            ScreenInstruction::ResizeRight(_) => ScreenContext::ResizeRight,
+            ScreenInstruction::ResizeDown(_) => ScreenContext::ResizeDown,
+            ScreenInstruction::ResizeUp(_) => ScreenContext::ResizeUp,
             ScreenInstruction::SwitchFocus => ScreenContext::SwitchFocus,
             ScreenInstruction::FocusNextPane => ScreenContext::FocusNextPane,
             ScreenInstruction::FocusPreviousPane => ScreenContext::FocusPreviousPane,
+            ScreenInstruction::FocusPane(_) => ScreenContext::FocusPane,
             ScreenInstruction::MoveFocusLeft => ScreenContext::MoveFocusLeft,
             ScreenInstruction::MoveFocusLeftOrPreviousTab => {
                 ScreenContext::MoveFocusLeftOrPreviousTab
@@ -99,18 +243,46 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::MoveFocusUp => ScreenContext::MoveFocusUp,
             ScreenInstruction::MoveFocusRight => ScreenContext::MoveFocusRight,
             ScreenInstruction::MoveFocusRightOrNextTab => ScreenContext::MoveFocusRightOrNextTab,
+            ScreenInstruction::MovePaneLeft => ScreenContext::MovePaneLeft,
+            ScreenInstruction::MovePaneDown => ScreenContext::MovePaneDown,
+            ScreenInstruction::MovePaneUp => ScreenContext::MovePaneUp,
+            ScreenInstruction::MovePaneRight => ScreenContext::MovePaneRight,
+            ScreenInstruction::SwapPanes => ScreenContext::SwapPanes,
+            ScreenInstruction::BalancePanes => ScreenContext::BalancePanes,
             ScreenInstruction::Exit => ScreenContext::Exit,
             ScreenInstruction::ScrollUp => ScreenContext::ScrollUp,
+            ScreenInstruction::ScrollUpPane(_) => ScreenContext::ScrollUpPane,
             ScreenInstruction::ScrollDown => ScreenContext::ScrollDown,
             ScreenInstruction::ScrollToBottom => ScreenContext::ScrollToBottom,
             ScreenInstruction::PageScrollUp => ScreenContext::PageScrollUp,
             ScreenInstruction::PageScrollDown => ScreenContext::PageScrollDown,
             ScreenInstruction::ClearScroll => ScreenContext::ClearScroll,
+            ScreenInstruction::PageScrollback => ScreenContext::PageScrollback,
+            ScreenInstruction::EditScrollback(_) => ScreenContext::EditScrollback,
+            ScreenInstruction::ViewActivePane(_) => ScreenContext::ViewActivePane,
+            ScreenInstruction::NewPaneWithStdinFromSelection(..) => {
+                ScreenContext::NewPaneWithStdinFromSelection
+            }
+            ScreenInstruction::NewViewerPane(..) => ScreenContext::NewViewerPane,
+            ScreenInstruction::QuickCopy => ScreenContext::QuickCopy,
+            ScreenInstruction::LinkHints => ScreenContext::LinkHints,
+            ScreenInstruction::ClearScreen(..) => ScreenContext::ClearScreen,
+            ScreenInstruction::TogglePaneLink => ScreenContext::TogglePaneLink,
+            ScreenInstruction::TogglePaneReadOnly => ScreenContext::TogglePaneReadOnly,
+            ScreenInstruction::TogglePaneStacking => ScreenContext::TogglePaneStacking,
+            ScreenInstruction::TogglePanePresentation => ScreenContext::TogglePanePresentation,
+            ScreenInstruction::TogglePanePinned => ScreenContext::TogglePanePinned,
+            ScreenInstruction::SetPanePalette(..) => ScreenContext::SetPanePalette,
+            ScreenInstruction::SetPaneAmbiguousWidth(_) => ScreenContext::SetPaneAmbiguousWidth,
             ScreenInstruction::CloseFocusedPane => ScreenContext::CloseFocusedPane,
+            ScreenInstruction::ClosePaneWithId(_) => ScreenContext::ClosePaneWithId,
             ScreenInstruction::ToggleActiveTerminalFullscreen => {
                 ScreenContext::ToggleActiveTerminalFullscreen
             }
+            ScreenInstruction::TogglePaneFullscreen(_) => ScreenContext::TogglePaneFullscreen,
             ScreenInstruction::TogglePaneFrames => ScreenContext::TogglePaneFrames,
+            ScreenInstruction::ToggleCleanScreen => ScreenContext::ToggleCleanScreen,
+            ScreenInstruction::ToggleUI => ScreenContext::ToggleUI,
             ScreenInstruction::SetSelectable(..) => ScreenContext::SetSelectable,
             ScreenInstruction::ClosePane(_) => ScreenContext::ClosePane,
             ScreenInstruction::ApplyLayout(..) => ScreenContext::ApplyLayout,
@@ -118,21 +290,87 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::SwitchTabPrev => ScreenContext::SwitchTabPrev,
             ScreenInstruction::CloseTab => ScreenContext::CloseTab,
             ScreenInstruction::GoToTab(_) => ScreenContext::GoToTab,
+            ScreenInstruction::CloseTabAt(_) => ScreenContext::CloseTabAt,
             ScreenInstruction::UpdateTabName(_) => ScreenContext::UpdateTabName,
+            ScreenInstruction::UpdatePaneName(_) => ScreenContext::UpdatePaneName,
+            ScreenInstruction::UpdateSearch(_) => ScreenContext::UpdateSearch,
+            ScreenInstruction::Search(_) => ScreenContext::Search,
+            ScreenInstruction::ToggleSearchOption(_) => ScreenContext::ToggleSearchOption,
+            ScreenInstruction::UpdateTabSearch(_) => ScreenContext::UpdateTabSearch,
+            ScreenInstruction::TabSearch(_) => ScreenContext::TabSearch,
+            ScreenInstruction::TabSearchJump => ScreenContext::TabSearchJump,
+            ScreenInstruction::UpdateSessionName(_) => ScreenContext::UpdateSessionName,
+            ScreenInstruction::RenameSession(_) => ScreenContext::RenameSession,
+            ScreenInstruction::UpdateCommandHistory(_) => ScreenContext::UpdateCommandHistory,
+            ScreenInstruction::CommandHistory(_) => ScreenContext::CommandHistory,
+            ScreenInstruction::CommandHistoryRun(_) => ScreenContext::CommandHistoryRun,
+            ScreenInstruction::UpdateSessionManager(_) => ScreenContext::UpdateSessionManager,
+            ScreenInstruction::SessionManager(_) => ScreenContext::SessionManager,
+            ScreenInstruction::SessionManagerSwitch => ScreenContext::SessionManagerSwitch,
+            ScreenInstruction::SessionManagerRename => ScreenContext::SessionManagerRename,
+            ScreenInstruction::SessionManagerKill => ScreenContext::SessionManagerKill,
+            ScreenInstruction::SessionManagerSessions(_) => ScreenContext::SessionManagerSessions,
+            ScreenInstruction::CopyModeMove(_) => ScreenContext::CopyModeMove,
+            ScreenInstruction::CopyModeSelect(_) => ScreenContext::CopyModeSelect,
+            ScreenInstruction::CopyModeYank => ScreenContext::CopyModeYank,
+            ScreenInstruction::UpdateTabGroupName(_) => ScreenContext::UpdateTabGroupName,
+            ScreenInstruction::MoveTabToNextGroup => ScreenContext::MoveTabToNextGroup,
+            ScreenInstruction::ToggleTabGroupCollapsed => ScreenContext::ToggleTabGroupCollapsed,
             ScreenInstruction::TerminalResize(_) => ScreenContext::TerminalResize,
+            ScreenInstruction::TerminalFocus(_) => ScreenContext::TerminalFocus,
             ScreenInstruction::ChangeMode(_) => ScreenContext::ChangeMode,
             ScreenInstruction::ToggleActiveSyncTab => ScreenContext::ToggleActiveSyncTab,
             ScreenInstruction::ScrollUpAt(_) => ScreenContext::ScrollUpAt,
             ScreenInstruction::ScrollDownAt(_) => ScreenContext::ScrollDownAt,
-            ScreenInstruction::LeftClick(_) => ScreenContext::LeftClick,
+            ScreenInstruction::LeftClick(..) => ScreenContext::LeftClick,
+            ScreenInstruction::MiddleClick(..) => ScreenContext::MiddleClick,
             ScreenInstruction::MouseRelease(_) => ScreenContext::MouseRelease,
             ScreenInstruction::MouseHold(_) => ScreenContext::MouseHold,
+            ScreenInstruction::MouseMotion(_) => ScreenContext::MouseMotion,
             ScreenInstruction::Copy => ScreenContext::Copy,
+            ScreenInstruction::StartPaneTimer(_) => ScreenContext::StartPaneTimer,
+            ScreenInstruction::StopPaneTimer => ScreenContext::StopPaneTimer,
+            ScreenInstruction::TickPaneTimers => ScreenContext::TickPaneTimers,
             ScreenInstruction::ToggleTab => ScreenContext::ToggleTab,
+            ScreenInstruction::BreakPaneToNewTab => ScreenContext::BreakPaneToNewTab,
+            ScreenInstruction::BreakPaneLeft => ScreenContext::BreakPaneLeft,
+            ScreenInstruction::BreakPaneRight => ScreenContext::BreakPaneRight,
+            ScreenInstruction::DuplicateTab(..) => ScreenContext::DuplicateTab,
+            ScreenInstruction::ApplyLayoutToActiveTab(..) => ScreenContext::ApplyLayoutToActiveTab,
+            ScreenInstruction::ApplyLayoutToTab(..) => ScreenContext::ApplyLayoutToTab,
+            ScreenInstruction::NextSwapLayout(_) => ScreenContext::NextSwapLayout,
+            ScreenInstruction::DumpLayout(..) => ScreenContext::DumpLayout,
+            ScreenInstruction::SerializeSession(_) => ScreenContext::SerializeSession,
+            ScreenInstruction::CheckIdle => ScreenContext::CheckIdle,
+            ScreenInstruction::DumpScreen(..) => ScreenContext::DumpScreen,
+            ScreenInstruction::TagFocusedPane(_) => ScreenContext::TagFocusedPane,
+            ScreenInstruction::UntagFocusedPane(_) => ScreenContext::UntagFocusedPane,
+            ScreenInstruction::ClosePanesWithTag(_) => ScreenContext::ClosePanesWithTag,
+            ScreenInstruction::ListPanes(..) => ScreenContext::ListPanes,
+            ScreenInstruction::FromClient(_, ref instruction) => (&**instruction).into(),
+            ScreenInstruction::AddClient(..) => ScreenContext::AddClient,
+            ScreenInstruction::RemoveClient(_) => ScreenContext::RemoveClient,
         }
     }
 }
 
+/// A label tabs can be grouped under in the tab bar, eg. to keep the tabs of a project together.
+#[derive(Debug, Default)]
+pub(crate) struct TabGroup {
+    pub name: String,
+    pub collapsed: bool,
+}
+
+/// The focus of a client attached to the session, kept while the [`Screen`] has the focus of
+/// another client.
+#[derive(Debug, Default, Clone)]
+struct ClientFocus {
+    slot: usize, // in the order the clients attached, it picks the colour of the client
+    active_tab_index: Option<usize>,
+    tab_history: Vec<Option<usize>>,
+    active_panes: HashMap<usize, PaneId>, // by the index of their tab
+}
+
 /// A [`Screen`] holds multiple [`Tab`]s, each one holding multiple [`panes`](crate::client::panes).
 /// It only directly controls which tab is active, delegating the rest to the individual `Tab`.
 pub(crate) struct Screen {
@@ -147,10 +385,37 @@ pub(crate) struct Screen {
     /// The index of this [`Screen`]'s active [`Tab`].
     active_tab_index: Option<usize>,
     tab_history: Vec<Option<usize>>,
+    tab_names: Vec<String>, // as of the last render, they follow the titles of their panes
+    /// The groups the tabs of this [`Screen`] can be put in, by their ID.
+    tab_groups: BTreeMap<usize, TabGroup>,
     mode_info: ModeInfo,
     colors: Palette,
     session_state: Arc<RwLock<SessionState>>,
     draw_pane_frames: bool,
+    clean_screen: bool,
+    ui_hidden: bool, // the tab and status bars, their rows go to the other panes
+    exit_fullscreen_on_focus_change: bool,
+    pane_clipboard: PaneClipboard,
+    pane_grid_options: PaneGridOptions,
+    pane_frame_options: PaneFrameOptions,
+    paste_options: PasteOptions,
+    auto_split_options: AutoSplitOptions,
+    small_panes: SmallPanesPolicy,
+    limits: SessionLimits,
+    /// Whether the terminal of the client has the focus, renders are spaced out while it doesn't.
+    terminal_focused: bool,
+    last_render: Instant,
+    render_pending: bool, // held back while the terminal is unfocused
+    timer_tick_scheduled: bool,
+    session_serialization: Option<SessionSerialization>,
+    idle_exit: Option<IdleExit>,
+    /// When a pane last printed something or the last client detached, for `idle_exit`.
+    active_at: Instant,
+    session_name_input: Option<String>, // the new name typed in `RenameSession` mode
+    /// The clients attached, the active tab and the active panes of the tabs being the focus of
+    /// `current_client`.
+    clients: BTreeMap<ClientId, ClientFocus>,
+    current_client: Option<ClientId>,
 }
 
 impl Screen {
@@ -162,6 +427,14 @@ impl Screen {
         mode_info: ModeInfo,
         session_state: Arc<RwLock<SessionState>>,
         draw_pane_frames: bool,
+        exit_fullscreen_on_focus_change: bool,
+        pane_clipboard: PaneClipboard,
+        pane_grid_options: PaneGridOptions,
+        pane_frame_options: PaneFrameOptions,
+        paste_options: PasteOptions,
+        auto_split_options: AutoSplitOptions,
+        small_panes: SmallPanesPolicy,
+        limits: SessionLimits,
     ) -> Self {
         Screen {
             bus,
@@ -171,9 +444,31 @@ impl Screen {
             active_tab_index: None,
             tabs: BTreeMap::new(),
             tab_history: Vec::with_capacity(32),
+            tab_names: vec![],
+            tab_groups: BTreeMap::new(),
             mode_info,
             session_state,
             draw_pane_frames,
+            clean_screen: false,
+            ui_hidden: false,
+            exit_fullscreen_on_focus_change,
+            pane_clipboard,
+            pane_grid_options,
+            pane_frame_options,
+            paste_options,
+            auto_split_options,
+            small_panes,
+            limits,
+            terminal_focused: true,
+            last_render: Instant::now(),
+            render_pending: false,
+            timer_tick_scheduled: false,
+            session_serialization: None,
+            idle_exit: None,
+            active_at: Instant::now(),
+            session_name_input: None,
+            clients: BTreeMap::new(),
+            current_client: None,
         }
     }
 
@@ -192,6 +487,7 @@ impl Screen {
     pub fn switch_tab_next(&mut self) {
         let active_tab_pos = self.get_active_tab().unwrap().position;
         let new_tab_pos = (active_tab_pos + 1) % self.tabs.len();
+        let previous_tab_index = self.active_tab_index;
 
         for tab in self.tabs.values_mut() {
             if tab.position == new_tab_pos {
@@ -202,6 +498,7 @@ impl Screen {
                 break;
             }
         }
+        self.bring_pinned_panes_from(previous_tab_index);
         self.update_tabs();
         self.render();
     }
@@ -214,6 +511,7 @@ impl Screen {
         } else {
             active_tab_pos - 1
         };
+        let previous_tab_index = self.active_tab_index;
         for tab in self.tabs.values_mut() {
             if tab.position == new_tab_pos {
                 tab.set_force_render();
@@ -223,6 +521,7 @@ impl Screen {
                 break;
             }
         }
+        self.bring_pinned_panes_from(previous_tab_index);
         self.update_tabs();
         self.render();
     }
@@ -236,18 +535,58 @@ impl Screen {
                 self.tab_history.retain(|&e| e != Some(t.index));
                 self.tab_history.push(self.active_tab_index);
                 self.active_tab_index = Some(t.index);
+                self.bring_pinned_panes_from(Some(active_tab_index));
                 self.update_tabs();
                 self.render();
             }
         }
     }
 
+    /// Moves the pinned panes of the [`Tab`] with the given index to the active tab, so that
+    /// they stay in view whichever tab is active.
+    fn bring_pinned_panes_from(&mut self, tab_index: Option<usize>) {
+        let tab_index = match tab_index {
+            Some(tab_index) if self.active_tab_index != Some(tab_index) => tab_index,
+            _ => return,
+        };
+        let pinned_panes = match self.tabs.get_mut(&tab_index) {
+            Some(tab) => tab.take_pinned_panes(),
+            None => return,
+        };
+        if let Some(active_tab) = self.get_active_tab_mut() {
+            for pane in pinned_panes {
+                active_tab.add_pinned_pane(pane);
+            }
+        }
+    }
+
     /// Closes this [`Screen`]'s active [`Tab`], exiting the application if it happens
     /// to be the last tab.
     pub fn close_tab(&mut self) {
         let active_tab_index = self.active_tab_index.unwrap();
-        let active_tab = self.tabs.remove(&active_tab_index).unwrap();
-        let pane_ids = active_tab.get_pane_ids();
+        self.close_tab_by_index(active_tab_index);
+    }
+
+    /// Closes the [`Tab`] at the given (1-based) position, exiting the application if it
+    /// happens to be the last tab.
+    pub fn close_tab_at(&mut self, position: usize) {
+        let tab_index = self
+            .tabs
+            .values()
+            .find(|t| t.position + 1 == position)
+            .map(|t| t.index);
+        if let Some(tab_index) = tab_index {
+            self.close_tab_by_index(tab_index);
+        }
+    }
+
+    /// Closes the [`Tab`] with the given index, exiting the application if it happens
+    /// to be the last tab.
+    pub fn close_tab_by_index(&mut self, tab_index: usize) {
+        let tab_to_close = self.tabs.remove(&tab_index).unwrap();
+        self.remove_empty_tab_groups();
+        let mut pane_ids = tab_to_close.get_pane_ids();
+        pane_ids.extend(tab_to_close.get_suppressed_pane_ids());
         // below we don't check the result of sending the CloseTab instruction to the pty thread
         // because this might be happening when the app is closing, at which point the pty thread
         // has already closed and this would result in an error
@@ -264,12 +603,16 @@ impl Screen {
                     .unwrap();
             }
         } else {
-            self.active_tab_index = self.tab_history.pop().unwrap();
+            if self.active_tab_index == Some(tab_index) {
+                self.active_tab_index = self.tab_history.pop().unwrap();
+            } else {
+                self.tab_history.retain(|&e| e != Some(tab_index));
+            }
             for t in self.tabs.values_mut() {
                 if t.index == self.active_tab_index.unwrap() {
                     t.set_force_render()
                 }
-                if t.position > active_tab.position {
+                if t.position > tab_to_close.position {
                     t.position -= 1;
                 }
             }
@@ -287,18 +630,348 @@ impl Screen {
         self.render();
     }
 
+    /// Shows `timer` in the frame of the focused pane, and keeps the timers ticking.
+    pub fn start_pane_timer(&mut self, timer: PaneTimer) {
+        if let Some(active_tab) = self.get_active_tab_mut() {
+            active_tab.start_active_pane_timer(timer);
+        }
+        self.schedule_pane_timer_tick();
+    }
+
+    /// Brings the timers of the panes of every tab up to date, and ticks them again later while
+    /// any still runs.
+    pub fn tick_pane_timers(&mut self) {
+        self.timer_tick_scheduled = false;
+        let now = Instant::now();
+        let mut ticking = false;
+        for tab in self.tabs.values_mut() {
+            ticking |= tab.tick_pane_timers(now);
+        }
+        if ticking {
+            self.schedule_pane_timer_tick();
+        }
+        self.render();
+    }
+
+    // a single tick is pending at a time, however many timers there are
+    fn schedule_pane_timer_tick(&mut self) {
+        if self.timer_tick_scheduled {
+            return;
+        }
+        self.timer_tick_scheduled = true;
+        let senders = self.bus.senders.clone();
+        thread::spawn(move || {
+            thread::sleep(PANE_TIMER_TICK_INTERVAL);
+            let _ = senders.send_to_screen(ScreenInstruction::TickPaneTimers);
+        });
+    }
+
     /// Renders this [`Screen`], which amounts to rendering its active [`Tab`].
     pub fn render(&mut self) {
         if *self.session_state.read().unwrap() != SessionState::Attached {
             return;
         }
-        if let Some(active_tab) = self.get_active_tab_mut() {
-            if active_tab.get_active_pane().is_some() {
-                active_tab.render();
-            } else {
+        if !self.terminal_focused && self.last_render.elapsed() < UNFOCUSED_RENDER_INTERVAL {
+            self.render_pending = true;
+            return;
+        }
+        self.render_pending = false;
+        self.last_render = Instant::now();
+        self.update_tab_clients();
+        let mut render_incomplete = match self.get_active_tab_mut() {
+            Some(active_tab) if active_tab.get_active_pane().is_some() => active_tab.render(),
+            Some(_) => {
                 self.close_tab();
+                false
+            }
+            None => false,
+        };
+        if self.clients.len() > 1 {
+            // the other clients might be on other tabs, those no client views don't render
+            let active_tab_index = self.active_tab_index;
+            for tab in self.tabs.values_mut() {
+                if Some(tab.index) != active_tab_index && tab.get_active_pane().is_some() {
+                    render_incomplete |= tab.render();
+                }
+            }
+        }
+        if render_incomplete {
+            // the panes left are drawn in a frame of their own, after what came in meanwhile
+            self.bus
+                .senders
+                .send_to_screen(ScreenInstruction::Render)
+                .unwrap();
+        }
+        let tab_names: Vec<String> = self.tabs.values().map(|tab| tab.display_name()).collect();
+        if tab_names != self.tab_names && self.active_tab_index.is_some() {
+            self.tab_names = tab_names;
+            self.update_tabs();
+        }
+    }
+
+    /// Gives a client that attached the focus the screen has at the moment, and the mode it
+    /// starts in unless other clients are attached, whose mode stays and is sent to it.
+    pub fn add_client(&mut self, client_id: ClientId, mode_info: Option<ModeInfo>) {
+        if self.clients.is_empty() {
+            if let Some(mode_info) = mode_info {
+                self.change_mode(mode_info.clone());
+                self.bus
+                    .senders
+                    .send_to_plugin(PluginInstruction::Update(
+                        None,
+                        Event::ModeUpdate(mode_info),
+                    ))
+                    .unwrap();
+            }
+        } else {
+            // its keys are handled in the mode of the others from the start
+            self.bus
+                .senders
+                .send_to_server(ServerInstruction::SwitchToMode(
+                    client_id,
+                    self.mode_info.mode,
+                ))
+                .unwrap();
+        }
+        self.save_client_focus();
+        let slot = (0..)
+            .find(|slot| self.clients.values().all(|focus| focus.slot != *slot))
+            .unwrap_or_default();
+        let focus = ClientFocus {
+            slot,
+            ..self.current_focus()
+        };
+        self.clients.insert(client_id, focus);
+        if self.current_client.is_none() {
+            self.current_client = Some(client_id);
+        }
+        self.render();
+    }
+
+    /// Forgets the focus of a client that detached, switching to that of another client if the
+    /// screen had its focus.
+    pub fn remove_client(&mut self, client_id: ClientId) {
+        self.clients.remove(&client_id);
+        self.active_at = Instant::now();
+        if self.current_client == Some(client_id) {
+            self.current_client = None;
+            if let Some(other_client) = self.clients.keys().next().copied() {
+                self.focus_client(other_client);
             }
+        }
+        self.render();
+    }
+
+    /// Switches to the focus of the client sending an instruction: its active tab and the active
+    /// panes of its tabs. The focus of the client the screen had is kept until then.
+    pub fn focus_client(&mut self, client_id: ClientId) {
+        if self.current_client == Some(client_id) {
+            return;
+        }
+        let focus = match self.clients.get(&client_id) {
+            Some(focus) => focus.clone(),
+            None => return,
         };
+        self.save_client_focus();
+        self.current_client = Some(client_id);
+        // the tabs closed meanwhile are gone from the focus of the client
+        let tabs = &self.tabs;
+        self.tab_history = focus
+            .tab_history
+            .into_iter()
+            .filter(|index| index.map_or(true, |index| tabs.contains_key(&index)))
+            .collect();
+        match focus.active_tab_index {
+            Some(tab_index) if tabs.contains_key(&tab_index) => {
+                self.active_tab_index = Some(tab_index);
+            }
+            // the tab of the client was closed by another one, it goes back to its previous tab
+            _ => {
+                if let Some(Some(tab_index)) = self.tab_history.last().copied() {
+                    self.tab_history.pop();
+                    self.active_tab_index = Some(tab_index);
+                }
+            }
+        }
+        for (tab_index, pane_id) in focus.active_panes {
+            if let Some(tab) = self.tabs.get_mut(&tab_index) {
+                tab.restore_active_pane(pane_id);
+            }
+        }
+        // new panes open from the pane the client focuses
+        if let Some(pane_id) = self
+            .get_active_tab()
+            .and_then(|tab| tab.get_active_pane_id())
+        {
+            self.bus
+                .senders
+                .send_to_pty(PtyInstruction::UpdateActivePane(Some(pane_id)))
+                .unwrap();
+        }
+        self.update_tab_clients();
+    }
+
+    fn current_focus(&self) -> ClientFocus {
+        ClientFocus {
+            slot: 0,
+            active_tab_index: self.active_tab_index,
+            tab_history: self.tab_history.clone(),
+            active_panes: self
+                .tabs
+                .iter()
+                .filter_map(|(index, tab)| Some((*index, tab.get_active_pane_id()?)))
+                .collect(),
+        }
+    }
+
+    fn save_client_focus(&mut self) {
+        let focus = self.current_focus();
+        if let Some(saved_focus) = self
+            .current_client
+            .and_then(|client_id| self.clients.get_mut(&client_id))
+        {
+            *saved_focus = ClientFocus {
+                slot: saved_focus.slot,
+                ..focus
+            };
+        }
+    }
+
+    /// Lets each [`Tab`] know of the clients viewing it while several are attached, those the
+    /// screen doesn't have the focus of on the active tab of their own focus.
+    fn update_tab_clients(&mut self) {
+        if self.clients.len() < 2 {
+            for tab in self.tabs.values_mut() {
+                tab.set_clients(None);
+            }
+            return;
+        }
+        let mut tab_clients: BTreeMap<usize, Vec<TabClient>> = self
+            .tabs
+            .keys()
+            .map(|tab_index| (*tab_index, vec![]))
+            .collect();
+        for (client_id, focus) in self.clients.iter() {
+            let (tab_index, active_pane) = if self.current_client == Some(*client_id) {
+                (self.active_tab_index, None)
+            } else {
+                let tab_index = focus
+                    .active_tab_index
+                    .filter(|tab_index| self.tabs.contains_key(tab_index))
+                    .or(self.active_tab_index);
+                let active_pane =
+                    tab_index.and_then(|tab_index| focus.active_panes.get(&tab_index));
+                (tab_index, active_pane.copied())
+            };
+            if let Some(clients) = tab_index.and_then(|tab_index| tab_clients.get_mut(&tab_index)) {
+                clients.push(TabClient {
+                    id: *client_id,
+                    slot: focus.slot,
+                    active_pane,
+                });
+            }
+        }
+        for (tab_index, mut clients) in tab_clients {
+            clients.sort_by_key(|client| client.slot);
+            if let Some(tab) = self.tabs.get_mut(&tab_index) {
+                tab.set_clients(Some(clients));
+            }
+        }
+    }
+
+    /// Renders at most every [`UNFOCUSED_RENDER_INTERVAL`] while the terminal of the client is
+    /// unfocused, catching up on what was held back when it gets the focus again.
+    pub fn set_terminal_focus(&mut self, focused: bool) {
+        self.terminal_focused = focused;
+        if focused && self.render_pending {
+            self.render();
+        }
+    }
+
+    /// Returns a mutable reference to the [`Tab`] holding the given pane, whether or not
+    /// it is the active one.
+    pub fn get_tab_with_pane_mut(&mut self, pane_id: PaneId) -> Option<&mut Tab> {
+        self.tabs.values_mut().find(|tab| tab.has_pane(&pane_id))
+    }
+
+    /// Focuses the given pane, switching to the [`Tab`] holding it if needed, and showing it in
+    /// place of the active pane of the tab if it is suppressed.
+    pub fn focus_pane(&mut self, pane_id: PaneId) {
+        let tab_position = self
+            .tabs
+            .values()
+            .find(|tab| tab.has_pane(&pane_id))
+            .map(|tab| tab.position);
+        if let Some(tab_position) = tab_position {
+            self.go_to_tab(tab_position + 1);
+            let tab = self.get_active_tab_mut().unwrap();
+            if tab.has_suppressed_pane(&pane_id) {
+                tab.show_suppressed_pane(pane_id);
+            } else {
+                tab.focus_pane(pane_id);
+            }
+            self.render();
+        }
+    }
+
+    /// Shows the given suppressed pane in place of the active pane of its [`Tab`], switching
+    /// to that tab if needed.
+    pub fn show_suppressed_pane(&mut self, pane_id: PaneId) {
+        let tab_position = self
+            .tabs
+            .values()
+            .find(|tab| tab.has_suppressed_pane(&pane_id))
+            .map(|tab| tab.position);
+        if let Some(tab_position) = tab_position {
+            self.go_to_tab(tab_position + 1);
+            self.get_active_tab_mut()
+                .unwrap()
+                .show_suppressed_pane(pane_id);
+        }
+    }
+
+    /// Closes the given pane wherever it is, closing its [`Tab`] too if it was the last
+    /// pane there.
+    pub fn close_pane_with_id(&mut self, pane_id: PaneId) {
+        if let Some(tab) = self.get_tab_with_pane_mut(pane_id) {
+            tab.close_pane(pane_id);
+            tab.senders
+                .send_to_pty(PtyInstruction::ClosePane(pane_id))
+                .unwrap();
+            if tab.get_active_pane().is_none() {
+                let tab_index = tab.index;
+                self.close_tab_by_index(tab_index);
+                return;
+            }
+        }
+        self.render();
+    }
+
+    /// Closes the terminals tagged `tag`, in whichever tab they are.
+    pub fn close_panes_with_tag(&mut self, tag: &str) {
+        let pane_ids: Vec<PaneId> = self
+            .tabs
+            .values()
+            .flat_map(|tab| tab.pane_summaries(Some(tag)))
+            .map(|pane| PaneId::Terminal(pane.id as RawFd))
+            .collect();
+        for pane_id in pane_ids {
+            self.close_pane_with_id(pane_id);
+        }
+    }
+
+    /// Lets whoever asked know of the terminals of the session, only those tagged `tag` if
+    /// given, tab by tab.
+    pub fn list_panes(&self, tag: Option<&str>, reply: Option<ActionReply>) {
+        if let Some(reply) = reply {
+            let mut tabs: Vec<&Tab> = self.tabs.values().collect();
+            tabs.sort_by_key(|tab| tab.position);
+            let panes = tabs
+                .into_iter()
+                .flat_map(|tab| tab.pane_summaries(tag))
+                .collect();
+            let _ = reply.send(ActionResult::Panes(panes));
+        }
     }
 
     /// Returns a mutable reference to this [`Screen`]'s tabs.
@@ -314,17 +987,6 @@ impl Screen {
         }
     }
 
-    /// Returns an immutable reference to this [`Screen`]'s previous active [`Tab`].
-    /// Consumes the last entry in tab history.
-    pub fn get_previous_tab(&mut self) -> Option<&Tab> {
-        let last = self.tab_history.pop();
-        last?;
-        match last.unwrap() {
-            Some(tab) => self.tabs.get(&tab),
-            None => None,
-        }
-    }
-
     /// Returns a mutable reference to this [`Screen`]'s active [`Tab`].
     pub fn get_active_tab_mut(&mut self) -> Option<&mut Tab> {
         match self.active_tab_index {
@@ -339,14 +1001,24 @@ impl Screen {
     }
 
     /// Creates a new [`Tab`] in this [`Screen`], applying the specified [`Layout`]
-    /// and switching to it.
-    pub fn apply_layout(&mut self, layout: Layout, new_pids: Vec<RawFd>) {
+    /// and switching to it. Panes of other tabs among the `new_pids` are moved to the new tab.
+    pub fn apply_layout(&mut self, layout: Layout, new_pids: Vec<RawFd>, name: String) {
+        let mut moved_panes = vec![];
+        let mut emptied_tab_indices = vec![];
+        for &pid in &new_pids {
+            if let Some(tab) = self.get_tab_with_pane_mut(PaneId::Terminal(pid)) {
+                moved_panes.extend(tab.extract_pane(PaneId::Terminal(pid)));
+                if tab.get_active_pane().is_none() {
+                    emptied_tab_indices.push(tab.index);
+                }
+            }
+        }
         let tab_index = self.get_new_tab_index();
         let position = self.tabs.len();
         let mut tab = Tab::new(
             tab_index,
             position,
-            String::new(),
+            name,
             self.size,
             self.bus.os_input.as_ref().unwrap().clone(),
             self.bus.senders.clone(),
@@ -355,29 +1027,499 @@ impl Screen {
             self.colors,
             self.session_state.clone(),
             self.draw_pane_frames,
+            self.exit_fullscreen_on_focus_change,
+            self.pane_clipboard.clone(),
+            self.pane_grid_options,
+            self.pane_frame_options,
+            self.paste_options,
+            self.auto_split_options,
+            self.small_panes,
         );
         tab.apply_layout(layout, new_pids, tab_index);
+        for pane in moved_panes {
+            tab.replace_pane(pane);
+        }
+        if self.clean_screen {
+            tab.set_clean_screen(true);
+        }
+        if self.ui_hidden {
+            tab.set_ui_hidden(true);
+        }
+        let previous_tab_index = self.active_tab_index;
         self.tab_history.push(self.active_tab_index);
         self.active_tab_index = Some(tab_index);
         self.tabs.insert(tab_index, tab);
+        self.bring_pinned_panes_from(previous_tab_index);
+        for emptied_tab_index in emptied_tab_indices {
+            self.close_tab_by_index(emptied_tab_index);
+        }
         self.update_tabs();
     }
 
+    /// Moves the focused pane of the active [`Tab`] to a new tab, without restarting it.
+    pub fn break_active_pane_to_new_tab(&mut self) {
+        if let Some(PaneId::Terminal(pid)) = self
+            .get_active_tab()
+            .and_then(|tab| tab.get_active_pane_id())
+        {
+            // the pty thread opens the new tab like any other, with this pane in the place of
+            // its first shell
+            self.bus
+                .senders
+                .send_to_pty(PtyInstruction::NewTabForPane(pid))
+                .unwrap();
+        }
+    }
+
+    /// Opens a new tab with the panes of the active [`Tab`] laid out the same way, running
+    /// `rerun` their commands again or else shells, in the directories the panes are in.
+    pub fn duplicate_active_tab(
+        &mut self,
+        shell: Option<TerminalAction>,
+        rerun: bool,
+        reply: Option<ActionReply>,
+    ) {
+        if let Some(tab) = self.get_active_tab() {
+            let (tab_layout, pane_ids) = tab.layout_of_panes();
+            self.bus
+                .senders
+                .send_to_pty(PtyInstruction::DuplicateTab(
+                    shell, tab_layout, pane_ids, rerun, reply,
+                ))
+                .unwrap();
+        }
+    }
+
+    /// Re-tiles the active [`Tab`] after the layout file at `layout_path`, once the pty thread
+    /// started the terminals of the places its panes don't fill.
+    pub fn apply_layout_to_active_tab(
+        &mut self,
+        shell: Option<TerminalAction>,
+        layout_path: PathBuf,
+        reply: Option<ActionReply>,
+    ) {
+        if let Some(tab) = self.get_active_tab_mut() {
+            tab.swap_layout = None;
+            let tab_index = tab.index;
+            let pane_count = tab.shown_terminal_count();
+            let tab_layout = TabLayout {
+                layout: Some(layout_path),
+                ..Default::default()
+            };
+            self.bus
+                .senders
+                .send_to_pty(PtyInstruction::ApplyLayoutToTab(
+                    shell, tab_layout, tab_index, pane_count, reply,
+                ))
+                .unwrap();
+        }
+    }
+
+    /// Saves the tabs of the session, with the arrangement of their panes, to a layout file at
+    /// `path`, once the pty thread filled in the commands and directories of the panes.
+    pub fn dump_layout(&self, path: PathBuf, reply: Option<ActionReply>) {
+        self.bus
+            .senders
+            .send_to_pty(PtyInstruction::DumpLayout(
+                path,
+                self.layouts_of_tabs(),
+                reply,
+            ))
+            .unwrap();
+    }
+
+    /// Writes the session to disk along with the end of the scrollback of its panes, unless
+    /// the `session_serialization` option turns it off.
+    pub fn serialize_session(&self, reply: Option<ActionReply>) {
+        let serialization = match &self.session_serialization {
+            Some(serialization) => serialization.clone(),
+            None => return,
+        };
+        let mut scrollbacks = HashMap::new();
+        if serialization.scrollback_lines > 0 {
+            for tab in self.tabs.values() {
+                scrollbacks.extend(tab.last_rows_of_panes(serialization.scrollback_lines));
+            }
+        }
+        self.bus
+            .senders
+            .send_to_pty(PtyInstruction::SerializeSession(
+                Box::new(serialization),
+                self.layouts_of_tabs(),
+                scrollbacks,
+                reply,
+            ))
+            .unwrap();
+    }
+
+    /// Quits the session, written to disk, once it has had no clients and no output from its
+    /// panes for the `idle_exit_hours` option.
+    pub fn exit_if_idle(&mut self) {
+        let session_name = self.mode_info.session_name.clone().unwrap_or_default();
+        let is_due = self.clients.is_empty()
+            && self.idle_exit.as_ref().map_or(false, |idle_exit| {
+                idle_exit.is_due(&session_name, self.active_at.elapsed())
+            });
+        if is_due {
+            log::info!("Session {:?} is idle, quitting", session_name);
+            // the session is written as it quits, once is enough
+            self.idle_exit = None;
+            self.bus
+                .senders
+                .send_to_server(ServerInstruction::ClientExit)
+                .unwrap();
+        }
+    }
+
+    // the layout of the panes of each tab in order, and its panes in the order the layout spawns
+    // them
+    fn layouts_of_tabs(&self) -> Vec<(TabLayout, Vec<PaneId>)> {
+        let mut tabs: Vec<&Tab> = self.tabs.values().collect();
+        tabs.sort_by_key(|tab| tab.position);
+        tabs.into_iter()
+            .map(|tab| {
+                let (mut tab_layout, pane_ids) = tab.layout_of_panes();
+                tab_layout.name = tab.name.clone();
+                tab_layout.focus = self.active_tab_index == Some(tab.index);
+                (tab_layout, pane_ids)
+            })
+            .collect()
+    }
+
+    /// Rearranges the panes of the active [`Tab`] after the [`SwapLayout`] that comes after the
+    /// one they were last cycled to, without opening or closing any terminal.
+    pub fn next_swap_layout(&mut self, reply: Option<ActionReply>) {
+        if let Some(tab) = self.get_active_tab_mut() {
+            let swap_layout = tab
+                .swap_layout
+                .map_or(SwapLayout::EvenHorizontal, SwapLayout::next);
+            tab.swap_layout = Some(swap_layout);
+            let tab_index = tab.index;
+            let pane_count = tab.shown_terminal_count();
+            self.bus
+                .senders
+                .send_to_pty(PtyInstruction::ApplyLayoutToTab(
+                    None,
+                    swap_layout.tab_layout(pane_count),
+                    tab_index,
+                    pane_count,
+                    reply,
+                ))
+                .unwrap();
+        }
+    }
+
+    /// Re-tiles the [`Tab`] with the given index after `layout`, unless it was closed in the
+    /// meantime or the `new_pids` would take it beyond the panes the session allows.
+    pub fn apply_layout_to_tab(
+        &mut self,
+        tab_index: usize,
+        layout: Layout,
+        new_pids: Vec<RawFd>,
+        reply: Option<ActionReply>,
+    ) {
+        let pane_count = match self.tabs.get(&tab_index) {
+            Some(tab) => tab.terminal_pane_count() + new_pids.len(),
+            None => {
+                self.close_terminals(&new_pids);
+                return;
+            }
+        };
+        match self.limits.max_panes_per_tab {
+            Some(max_panes) if pane_count > max_panes => {
+                self.close_terminals(&new_pids);
+                self.refuse(
+                    format!(
+                        "Can't apply the layout, the tab would hold {} panes out of the {} allowed",
+                        pane_count, max_panes
+                    ),
+                    reply,
+                );
+            }
+            _ => {
+                self.tabs
+                    .get_mut(&tab_index)
+                    .unwrap()
+                    .retile(layout, new_pids);
+                self.update_tabs();
+            }
+        }
+    }
+
+    fn close_terminals(&self, pids: &[RawFd]) {
+        for &pid in pids {
+            self.bus
+                .senders
+                .send_to_pty(PtyInstruction::ClosePane(PaneId::Terminal(pid)))
+                .unwrap();
+        }
+    }
+
+    /// Moves the focused pane of the active [`Tab`] to the previous or next tab, wrapping around
+    /// like switching tabs does, and closes the tab it leaves if it is left empty. With only one
+    /// tab, the pane is moved to a new tab instead.
+    pub fn break_active_pane_to_adjacent_tab(&mut self, forward: bool) {
+        if self.tabs.len() < 2 {
+            self.break_active_pane_to_new_tab();
+            return;
+        }
+        let active_tab = self.get_active_tab().unwrap();
+        let pane_id = match active_tab.get_active_pane_id() {
+            Some(pane_id @ PaneId::Terminal(_)) => pane_id,
+            _ => return,
+        };
+        let source_tab_index = active_tab.index;
+        let target_tab_position = if forward {
+            (active_tab.position + 1) % self.tabs.len()
+        } else if active_tab.position == 0 {
+            self.tabs.len() - 1
+        } else {
+            active_tab.position - 1
+        };
+        let source_tab = self.get_active_tab_mut().unwrap();
+        let pane = match source_tab.extract_pane(pane_id) {
+            Some(pane) => pane,
+            None => return,
+        };
+        let source_tab_is_empty = source_tab.get_active_pane().is_none();
+        if let Some(target_tab) = self
+            .tabs
+            .values_mut()
+            .find(|tab| tab.position == target_tab_position)
+        {
+            target_tab.add_pane(pane);
+        }
+        self.go_to_tab(target_tab_position + 1);
+        if source_tab_is_empty {
+            self.close_tab_by_index(source_tab_index);
+        } else {
+            self.render();
+        }
+    }
+
+    /// Drops the oldest lines of the scrollback of the panes, in proportion to how much each
+    /// one holds, once they take up more memory together than the session allows.
+    fn trim_scrollback(&mut self) {
+        if let Some(max_memory) = self.limits.max_scrollback_memory {
+            let total: usize = self.tabs.values().map(|tab| tab.scrollback_memory()).sum();
+            if total > max_memory {
+                for tab in self.tabs.values_mut() {
+                    tab.trim_scrollback(total - max_memory, total);
+                }
+            }
+        }
+    }
+
+    /// Why the active [`Tab`] can't take another pane, if it already holds as many as the
+    /// session allows.
+    fn pane_limit_reached(&self) -> Option<String> {
+        let max_panes = self.limits.max_panes_per_tab?;
+        let tab = self.get_active_tab()?;
+        if tab.terminal_pane_count() >= max_panes {
+            Some(format!(
+                "Can't open a pane, the tab already holds the most allowed ({})",
+                max_panes
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Why a new [`Tab`] with `new_pids` can't be opened, if the session already holds as many
+    /// tabs as it allows or the tab would hold too many panes. The first tab is never refused.
+    fn tab_limit_reached(&self, new_pids: &[RawFd]) -> Option<String> {
+        if self.tabs.is_empty() {
+            return None;
+        }
+        match (self.limits.max_tabs, self.limits.max_panes_per_tab) {
+            (Some(max_tabs), _) if self.tabs.len() >= max_tabs => Some(format!(
+                "Can't open a tab, the session already holds the most allowed ({})",
+                max_tabs
+            )),
+            (_, Some(max_panes)) if new_pids.len() > max_panes => Some(format!(
+                "Can't open a tab of {} panes, a tab can hold at most {}",
+                new_pids.len(),
+                max_panes
+            )),
+            _ => None,
+        }
+    }
+
+    /// Lets `open` put a pane the pty thread opened into the active [`Tab`], unless the tab
+    /// can't take another one, then the pane is closed again.
+    fn open_pane(
+        &mut self,
+        pid: PaneId,
+        reply: Option<ActionReply>,
+        open: impl FnOnce(&mut Tab, PaneId),
+    ) {
+        match self.pane_limit_reached() {
+            Some(reason) => {
+                self.bus
+                    .senders
+                    .send_to_pty(PtyInstruction::ClosePane(pid))
+                    .unwrap();
+                self.refuse(reason, reply);
+            }
+            None => {
+                open(self.get_active_tab_mut().unwrap(), pid);
+                self.report_new_pane(pid, reply);
+            }
+        }
+    }
+
+    /// Lets the user, and whoever sent the action, know why it was refused.
+    fn refuse(&self, reason: String, reply: Option<ActionReply>) {
+        if let Some(reply) = reply {
+            let _ = reply.send(ActionResult::Refused(reason.clone()));
+        }
+        self.bus
+            .senders
+            .send_to_server(ServerInstruction::Diagnostic(reason))
+            .unwrap();
+    }
+
+    /// Writes the screen of the focused pane, or its whole scrollback with `full`, to `path`.
+    fn dump_screen(&self, path: PathBuf, full: bool, ansi: bool, reply: Option<ActionReply>) {
+        if let Some(tab) = self.get_active_tab() {
+            if let Err(reason) = tab.dump_active_pane_screen(&path, full, ansi) {
+                log::error!("{}", reason);
+                self.refuse(reason, reply);
+            }
+        }
+    }
+
+    /// Opens the scrollback of the focused pane in the editor of the user, in a new pane.
+    fn edit_scrollback(&self, reply: Option<ActionReply>) {
+        // the pane is started with the environment of the server
+        if env::var("EDITOR").is_err() && env::var("VISUAL").is_err() {
+            self.refuse(
+                "Can't edit the scrollback, neither EDITOR nor VISUAL is set".into(),
+                reply,
+            );
+            return;
+        }
+        if let Some(tab) = self.get_active_tab() {
+            tab.edit_active_pane_scrollback(reply);
+        }
+    }
+
+    /// Opens a read only pane showing the output of the focused pane as it comes, which scrolls
+    /// on its own.
+    fn view_active_pane(&self, reply: Option<ActionReply>) {
+        match self
+            .get_active_tab()
+            .and_then(|tab| tab.get_active_pane_id())
+        {
+            Some(pane_id @ PaneId::Terminal(_)) => {
+                self.bus
+                    .senders
+                    .send_to_pty(PtyInstruction::SpawnViewer(pane_id, reply))
+                    .unwrap();
+            }
+            _ => self.refuse("Only terminal panes can be viewed".into(), reply),
+        }
+    }
+
+    /// Opens a pane running `command` with the text selected in the focused pane, or the output
+    /// of the last command it ran, as its standard input.
+    fn new_pane_with_stdin_from_selection(
+        &self,
+        command: RunCommandAction,
+        reply: Option<ActionReply>,
+    ) {
+        let input_file = match self
+            .get_active_tab()
+            .map(|tab| tab.write_active_pane_selection_to_file())
+        {
+            Some(Ok(input_file)) => input_file,
+            Some(Err(reason)) => return self.refuse(reason, reply),
+            None => return,
+        };
+        let direction = command.direction.clone();
+        let run_cmd = Some(TerminalAction::PipeFile(input_file, command.into()));
+        let pty_instr = match direction {
+            Some(Direction::Left) | Some(Direction::Right) => {
+                PtyInstruction::SpawnTerminalVertically(run_cmd, reply)
+            }
+            Some(Direction::Up) | Some(Direction::Down) => {
+                PtyInstruction::SpawnTerminalHorizontally(run_cmd, reply)
+            }
+            Some(Direction::Auto) | None => PtyInstruction::SpawnTerminalAutoSplit(run_cmd, reply),
+        };
+        self.bus.senders.send_to_pty(pty_instr).unwrap();
+    }
+
+    /// Reports the id of a newly opened pane to whoever asked for it, provided the active
+    /// [`Tab`] found room for it.
+    fn report_new_pane(&self, pid: PaneId, reply: Option<ActionReply>) {
+        if let (Some(reply), PaneId::Terminal(term_pid)) = (reply, pid) {
+            let result = match self.get_active_tab() {
+                Some(tab) if tab.has_terminal_pid(term_pid) => {
+                    ActionResult::NewPane(term_pid as u32)
+                }
+                _ => ActionResult::Empty,
+            };
+            let _ = reply.send(result);
+        }
+    }
+
     pub fn update_tabs(&self) {
         let mut tab_data = vec![];
         let active_tab_index = self.active_tab_index.unwrap();
         for tab in self.tabs.values() {
+            let group = tab.group.and_then(|id| self.tab_groups.get(&id));
             tab_data.push(TabInfo {
                 position: tab.position,
-                name: tab.name.clone(),
+                name: tab.display_name(),
                 active: active_tab_index == tab.index,
                 is_sync_panes_active: tab.is_sync_panes_active(),
+                group: group.map(|group| group.name.clone()),
+                is_group_collapsed: group.map_or(false, |group| group.collapsed),
+                cwd: tab.get_active_pane().and_then(|pane| pane.cwd()),
+                pane_tags: tab.pane_tags(),
             });
         }
         self.bus
             .senders
-            .send_to_plugin(PluginInstruction::Update(None, Event::TabUpdate(tab_data)))
+            .send_to_plugin(PluginInstruction::Update(
+                None,
+                Event::TabUpdate(tab_data.clone()),
+            ))
             .unwrap();
+        // with clients on other tabs, the plugins of those tabs show them as the active one
+        let other_viewed_tabs: Vec<usize> = self
+            .clients
+            .iter()
+            .filter(|(client_id, _)| Some(**client_id) != self.current_client)
+            .filter_map(|(_, focus)| focus.active_tab_index)
+            .filter(|tab_index| *tab_index != active_tab_index)
+            .collect();
+        for tab in self
+            .tabs
+            .values()
+            .filter(|tab| other_viewed_tabs.contains(&tab.index))
+        {
+            let tab_data: Vec<TabInfo> = tab_data
+                .iter()
+                .cloned()
+                .map(|mut tab_info| {
+                    tab_info.active = tab_info.position == tab.position;
+                    tab_info
+                })
+                .collect();
+            for pane_id in tab.get_pane_ids() {
+                if let PaneId::Plugin(plugin_id) = pane_id {
+                    self.bus
+                        .senders
+                        .send_to_plugin(PluginInstruction::Update(
+                            Some(plugin_id),
+                            Event::TabUpdate(tab_data.clone()),
+                        ))
+                        .unwrap();
+                }
+            }
+        }
     }
 
     pub fn update_active_tab_name(&mut self, buf: Vec<u8>) {
@@ -397,13 +1539,300 @@ impl Screen {
         }
         self.update_tabs();
     }
-    pub fn change_mode(&mut self, mode_info: ModeInfo) {
-        self.colors = mode_info.palette;
-        self.mode_info = mode_info;
-        for tab in self.tabs.values_mut() {
+    /// Types into the name of the group of the active [`Tab`]. A `0` puts an ungrouped tab into
+    /// a new group, or clears the name of the group the tab is in.
+    pub fn update_active_tab_group_name(&mut self, buf: Vec<u8>) {
+        let s = str::from_utf8(&buf).unwrap();
+        let new_group_id = self.tab_groups.keys().last().map_or(0, |id| id + 1);
+        let active_tab = self.get_active_tab_mut().unwrap();
+        let group_id = *active_tab.group.get_or_insert(new_group_id);
+        let group = self.tab_groups.entry(group_id).or_default();
+        match s {
+            "\0" => {
+                group.name = String::new();
+            }
+            "\u{007F}" | "\u{0008}" => {
+                //delete and backspace keys
+                group.name.pop();
+            }
+            c => {
+                group.name.push_str(c);
+            }
+        }
+        self.update_tabs();
+    }
+    /// Moves the active [`Tab`] to the group after its own, or out of the groups after the
+    /// last one.
+    pub fn move_active_tab_to_next_group(&mut self) {
+        let next_group_id = match self.get_active_tab().unwrap().group {
+            Some(group_id) => self
+                .tab_groups
+                .range(group_id + 1..)
+                .next()
+                .map(|(id, _)| *id),
+            None => self.tab_groups.keys().next().copied(),
+        };
+        self.get_active_tab_mut().unwrap().group = next_group_id;
+        self.remove_empty_tab_groups();
+        self.update_tabs();
+    }
+    /// Collapses the group of the active [`Tab`] into a single label in the tab bar, or expands
+    /// it again.
+    pub fn toggle_active_tab_group_collapsed(&mut self) {
+        let group_id = self.get_active_tab().unwrap().group;
+        if let Some(group) = group_id.and_then(|id| self.tab_groups.get_mut(&id)) {
+            group.collapsed = !group.collapsed;
+            self.update_tabs();
+        }
+    }
+    fn remove_empty_tab_groups(&mut self) {
+        let tabs = &self.tabs;
+        self.tab_groups
+            .retain(|id, _| tabs.values().any(|tab| tab.group == Some(*id)));
+    }
+    /// Types into the filter of the command history picker, which lists the commands run in the
+    /// panes of all the tabs. A `0` clears the filter.
+    pub fn update_command_history(&mut self, buf: Vec<u8>) {
+        let items = self
+            .tabs
+            .values()
+            .flat_map(|tab| tab.command_history())
+            .collect();
+        if let Some(tab) = self.get_active_tab_mut() {
+            tab.update_command_history(buf, items);
+        }
+    }
+    /// Runs the command selected in the command history picker again, in the focused pane, or
+    /// in the pane it was run in if `in_its_pane` (provided that pane is still there).
+    pub fn run_from_command_history(&mut self, in_its_pane: bool) {
+        let item = match self
+            .get_active_tab_mut()
+            .and_then(|tab| tab.take_command_history_selection())
+        {
+            Some(item) => item,
+            None => return,
+        };
+        let pane_id = if in_its_pane {
+            if !self.tabs.values().any(|tab| tab.has_pane(&item.pane_id)) {
+                return;
+            }
+            self.focus_pane(item.pane_id);
+            Some(item.pane_id)
+        } else {
+            self.get_active_tab()
+                .and_then(|tab| tab.get_active_pane_id())
+        };
+        let mut command = item.entry.command.into_bytes();
+        command.push(b'\r');
+        if let (Some(pane_id), Some(tab)) = (pane_id, self.get_active_tab_mut()) {
+            tab.write_input_to_pane_id(command, pane_id);
+        }
+    }
+    /// Types into the new name of the session, which the plugins show in place of its name
+    /// until the `RenameSession` mode is left. A `0` clears the new name, an Escape drops it.
+    pub fn update_session_name(&mut self, buf: Vec<u8>) {
+        match str::from_utf8(&buf).unwrap() {
+            "\u{1b}" => {
+                self.session_name_input = None;
+            }
+            s => {
+                let name = self.session_name_input.get_or_insert_with(String::new);
+                match s {
+                    "\0" => name.clear(),
+                    "\u{007F}" | "\u{0008}" => {
+                        //delete and backspace keys
+                        name.pop();
+                    }
+                    c => name.push_str(c),
+                }
+            }
+        }
+        let mut mode_info = self.mode_info.clone();
+        if let Some(name) = &self.session_name_input {
+            mode_info.session_name = Some(name.clone());
+        }
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(
+                None,
+                Event::ModeUpdate(mode_info),
+            ))
+            .unwrap();
+    }
+    /// Takes the new name of the session, once the server renamed it.
+    pub fn rename_session(&mut self, name: String) {
+        if let Some(session_serialization) = self.session_serialization.as_mut() {
+            session_serialization.rename(&name);
+        }
+        self.mode_info.session_name = Some(name);
+        for tab in self.tabs.values_mut() {
+            tab.mode_info = self.mode_info.clone();
+        }
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(
+                None,
+                Event::ModeUpdate(self.mode_info.clone()),
+            ))
+            .unwrap();
+        if self.mode_info.mode == InputMode::SessionManager {
+            self.list_sessions();
+        }
+    }
+    /// Types into the session manager, opening it over the active tab if need be. A `0` clears
+    /// what was typed.
+    pub fn update_session_manager(&mut self, buf: Vec<u8>) {
+        let opened = match self.get_active_tab_mut() {
+            Some(tab) => tab.update_session_manager(&String::from_utf8_lossy(&buf)),
+            None => return,
+        };
+        if opened {
+            self.list_sessions();
+        }
+    }
+    /// This session, with the titles of its terminals.
+    fn session_summary(&self) -> SessionSummary {
+        let mut tabs: Vec<&Tab> = self.tabs.values().collect();
+        tabs.sort_by_key(|tab| tab.position);
+        let panes: Vec<_> = tabs
+            .into_iter()
+            .flat_map(|tab| tab.pane_summaries(None))
+            .collect();
+        SessionSummary::new(
+            self.mode_info.session_name.clone().unwrap_or_default(),
+            SessionStatus::Current,
+            &panes,
+        )
+    }
+    /// Lists the sessions in the session manager again, once all of them answered.
+    fn list_sessions(&self) {
+        self.manage_sessions(|| Ok(()));
+    }
+    /// Does `task` to other sessions on another thread, as they may take a while to answer, and
+    /// lists the sessions again once it's done.
+    fn manage_sessions<F>(&self, task: F)
+    where
+        F: FnOnce() -> Result<(), String> + Send + 'static,
+    {
+        let current = self.session_summary();
+        let senders = self.bus.senders.clone();
+        thread::spawn(move || {
+            if let Err(reason) = task() {
+                let _ = senders.send_to_server(ServerInstruction::Diagnostic(reason));
+            }
+            let mut sessions = other_sessions(&current.name);
+            sessions.insert(0, current);
+            let _ = senders.send_to_screen(ScreenInstruction::SessionManagerSessions(sessions));
+        });
+    }
+    /// Attaches to the session selected in the session manager, resurrecting it if it exited.
+    pub fn switch_to_selected_session(&mut self) {
+        let (renaming, session) = match self
+            .get_active_tab_mut()
+            .and_then(|tab| tab.session_manager_mut())
+        {
+            Some(session_manager) => (
+                session_manager.new_name.is_some(),
+                session_manager.selected_session().cloned(),
+            ),
+            None => return,
+        };
+        if renaming {
+            // the session being renamed gets its new name instead
+            self.rename_selected_session();
+            return;
+        }
+        match session {
+            Some(session) if session.status == SessionStatus::NotResponding => {
+                self.refuse(format!("Session {:?} doesn't respond", session.name), None);
+            }
+            Some(session) if session.status != SessionStatus::Current => {
+                self.bus
+                    .senders
+                    .send_to_server(ServerInstruction::SwitchSession(session.name, false, None))
+                    .unwrap();
+            }
+            _ => {}
+        }
+    }
+    /// Starts typing a new name for the session selected in the session manager, or renames it
+    /// to the name typed.
+    pub fn rename_selected_session(&mut self) {
+        let (session, name) = match self
+            .get_active_tab_mut()
+            .and_then(|tab| tab.session_manager_mut())
+            .and_then(|session_manager| session_manager.toggle_renaming())
+        {
+            Some((session, name)) if !name.is_empty() && name != session.name => (session, name),
+            _ => return,
+        };
+        if session.status == SessionStatus::Current {
+            // the server lets the screen know of the new name, which lists the sessions again
+            self.bus
+                .senders
+                .send_to_server(ServerInstruction::RenameSession(name, None))
+                .unwrap();
+        } else {
+            self.manage_sessions(move || rename_other_session(&session, &name));
+        }
+    }
+    /// Kills the session selected in the session manager, unless it's this one.
+    pub fn kill_selected_session(&mut self) {
+        let session = match self
+            .get_active_tab_mut()
+            .and_then(|tab| tab.session_manager_mut())
+            .filter(|session_manager| session_manager.new_name.is_none())
+            .and_then(|session_manager| session_manager.selected_session().cloned())
+        {
+            Some(session) => session,
+            None => return,
+        };
+        if session.status == SessionStatus::Current {
+            self.refuse("Quit to kill the current session".to_string(), None);
+        } else {
+            self.manage_sessions(move || kill_other_session(&session));
+        }
+    }
+    pub fn change_mode(&mut self, mode_info: ModeInfo) {
+        // leaving the mode the new name of the session was typed in renames it
+        if mode_info.mode != InputMode::RenameSession {
+            if let Some(name) = self
+                .session_name_input
+                .take()
+                .filter(|name| !name.is_empty())
+            {
+                self.bus
+                    .senders
+                    .send_to_server(ServerInstruction::RenameSession(name, None))
+                    .unwrap();
+            }
+        }
+        self.colors = mode_info.palette;
+        self.mode_info = mode_info;
+        for tab in self.tabs.values_mut() {
             tab.mode_info = self.mode_info.clone();
+            if self.mode_info.mode != InputMode::Search {
+                tab.end_search();
+            }
+            if self.mode_info.mode != InputMode::TabSearch {
+                tab.end_tab_search();
+            }
+            if self.mode_info.mode != InputMode::CommandHistory {
+                tab.end_command_history();
+            }
+            if self.mode_info.mode != InputMode::SessionManager {
+                tab.end_session_manager();
+            }
+            if self.mode_info.mode != InputMode::Copy {
+                tab.end_copy_mode();
+            }
             tab.mark_active_pane_for_rerender();
         }
+        if self.mode_info.mode == InputMode::Copy {
+            if let Some(tab) = self.get_active_tab_mut() {
+                tab.start_copy_mode();
+            }
+        }
     }
     pub fn move_focus_left_or_previous_tab(&mut self) {
         if !self.get_active_tab_mut().unwrap().move_focus_left() {
@@ -415,12 +1844,16 @@ impl Screen {
             self.switch_tab_next();
         }
     }
+    /// Goes back to the tab focused before the active one, wherever it is, if there is one.
     pub fn toggle_tab(&mut self) {
-        let tab = self.get_previous_tab();
-        if let Some(t) = tab {
-            let position = t.position;
-            self.go_to_tab(position + 1);
+        let previous_position = match self.tab_history.last() {
+            Some(Some(tab_index)) => self.tabs.get(tab_index).map(|tab| tab.position),
+            _ => None,
         };
+        if let Some(position) = previous_position {
+            self.tab_history.pop();
+            self.go_to_tab(position + 1);
+        }
 
         self.update_tabs();
         self.render();
@@ -435,10 +1868,17 @@ pub(crate) fn screen_thread_main(
     max_panes: Option<usize>,
     client_attributes: ClientAttributes,
     config_options: Box<Options>,
+    locale: Locale,
     session_state: Arc<RwLock<SessionState>>,
+    session_serialization: Option<SessionSerialization>,
+    idle_exit: Option<IdleExit>,
 ) {
     let capabilities = config_options.simplified_ui;
     let draw_pane_frames = !config_options.no_pane_frames;
+    let quick_copy_patterns =
+        compile_quick_copy_patterns(config_options.quick_copy_patterns.as_deref());
+    let link_hint_patterns =
+        compile_link_hint_patterns(config_options.link_hint_patterns.as_deref());
 
     let mut screen = Screen::new(
         bus,
@@ -450,62 +1890,107 @@ pub(crate) fn screen_thread_main(
             PluginCapabilities {
                 arrow_fonts: capabilities,
             },
+            locale,
         ),
         session_state,
         draw_pane_frames,
+        config_options.exit_fullscreen_on_focus_change,
+        PaneClipboard::from_options(&config_options),
+        PaneGridOptions::from_options(&config_options),
+        PaneFrameOptions::from_options(&config_options),
+        PasteOptions::from_options(&config_options),
+        AutoSplitOptions::from_options(&config_options),
+        config_options.small_panes.unwrap_or_default(),
+        SessionLimits::from_options(&config_options),
     );
+    screen.session_serialization = session_serialization;
+    screen.idle_exit = idle_exit;
     loop {
         let (event, mut err_ctx) = screen
             .bus
             .recv()
             .expect("failed to receive event on channel");
         err_ctx.add_call(ContextType::Screen((&event).into()));
+        // the client sending the instruction, whose input thread waits for some of them
+        let (event, from_client) = match event {
+            ScreenInstruction::FromClient(client_id, instruction) => {
+                screen.focus_client(client_id);
+                (*instruction, Some(client_id))
+            }
+            event => (event, None),
+        };
         match event {
             ScreenInstruction::PtyBytes(pid, vte_bytes) => {
+                screen.active_at = Instant::now();
+                let mut cwd_changed = false;
                 let active_tab = screen.get_active_tab_mut().unwrap();
                 if active_tab.has_terminal_pid(pid) {
                     // it's most likely that this event is directed at the active tab
                     // look there first
-                    active_tab.handle_pty_bytes(pid, vte_bytes);
+                    cwd_changed = active_tab.handle_pty_bytes(pid, vte_bytes);
                 } else {
                     // if this event wasn't directed at the active tab, start looking
                     // in other tabs
                     let all_tabs = screen.get_tabs_mut();
                     for tab in all_tabs.values_mut() {
                         if tab.has_terminal_pid(pid) {
-                            tab.handle_pty_bytes(pid, vte_bytes);
+                            cwd_changed = tab.handle_pty_bytes(pid, vte_bytes);
                             break;
                         }
                     }
                 }
+                // the plugins are told the working directories of the tabs
+                if cwd_changed {
+                    screen.update_tabs();
+                }
             }
             ScreenInstruction::Render => {
+                screen.trim_scrollback();
                 screen.render();
             }
-            ScreenInstruction::NewPane(pid) => {
-                screen.get_active_tab_mut().unwrap().new_pane(pid);
+            ScreenInstruction::NewPane(pid, reply) => {
+                screen.open_pane(pid, reply, Tab::new_pane);
+                screen
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
+                    .unwrap();
+            }
+            ScreenInstruction::HorizontalSplit(pid, reply) => {
+                screen.open_pane(pid, reply, Tab::horizontal_split);
                 screen
                     .bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
                     .unwrap();
             }
-            ScreenInstruction::HorizontalSplit(pid) => {
-                screen.get_active_tab_mut().unwrap().horizontal_split(pid);
+            ScreenInstruction::VerticalSplit(pid, reply) => {
+                screen.open_pane(pid, reply, Tab::vertical_split);
                 screen
                     .bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
                     .unwrap();
             }
-            ScreenInstruction::VerticalSplit(pid) => {
-                screen.get_active_tab_mut().unwrap().vertical_split(pid);
+            ScreenInstruction::AutoSplit(pid, reply) => {
+                screen.open_pane(pid, reply, Tab::auto_split);
                 screen
                     .bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
                     .unwrap();
             }
+            ScreenInstruction::NewSuppressedPane(pid, reply) => {
+                screen.open_pane(pid, reply, Tab::suppress_pane);
+            }
+            ScreenInstruction::ShowSuppressedPane(pane_id) => {
+                screen.show_suppressed_pane(pane_id);
+            }
+            ScreenInstruction::WriteToPane(pane_id, bytes) => {
+                if let Some(tab) = screen.get_tab_with_pane_mut(pane_id) {
+                    tab.write_input_to_pane_id(bytes, pane_id);
+                }
+            }
             ScreenInstruction::WriteCharacter(bytes) => {
                 let active_tab = screen.get_active_tab_mut().unwrap();
                 match active_tab.is_sync_panes_active() {
@@ -513,17 +1998,20 @@ pub(crate) fn screen_thread_main(
                     false => active_tab.write_to_active_terminal(bytes),
                 }
             }
-            ScreenInstruction::ResizeLeft => {
-                screen.get_active_tab_mut().unwrap().resize_left();
+            ScreenInstruction::Paste(bytes) => {
+                screen.get_active_tab_mut().unwrap().paste(bytes);
             }
-            ScreenInstruction::ResizeRight => {
-                screen.get_active_tab_mut().unwrap().resize_right();
+            ScreenInstruction::ResizeLeft(amount) => {
+                screen.get_active_tab_mut().unwrap().resize_left_by(amount);
             }
-            ScreenInstruction::ResizeDown => {
-                screen.get_active_tab_mut().unwrap().resize_down();
+            ScreenInstruction::ResizeRight(amount) => {
+                screen.get_active_tab_mut().unwrap().resize_right_by(amount);
             }
-            ScreenInstruction::ResizeUp => {
-                screen.get_active_tab_mut().unwrap().resize_up();
+            ScreenInstruction::ResizeDown(amount) => {
+                screen.get_active_tab_mut().unwrap().resize_down_by(amount);
+            }
+            ScreenInstruction::ResizeUp(amount) => {
+                screen.get_active_tab_mut().unwrap().resize_up_by(amount);
             }
             ScreenInstruction::SwitchFocus => {
                 screen.get_active_tab_mut().unwrap().move_focus();
@@ -534,6 +2022,9 @@ pub(crate) fn screen_thread_main(
             ScreenInstruction::FocusPreviousPane => {
                 screen.get_active_tab_mut().unwrap().focus_previous_pane();
             }
+            ScreenInstruction::FocusPane(pane_id) => {
+                screen.focus_pane(pane_id);
+            }
             ScreenInstruction::MoveFocusLeft => {
                 screen.get_active_tab_mut().unwrap().move_focus_left();
             }
@@ -542,7 +2033,7 @@ pub(crate) fn screen_thread_main(
                 screen
                     .bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
                     .unwrap();
             }
             ScreenInstruction::MoveFocusDown => {
@@ -556,18 +2047,48 @@ pub(crate) fn screen_thread_main(
                 screen
                     .bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
                     .unwrap();
             }
             ScreenInstruction::MoveFocusUp => {
                 screen.get_active_tab_mut().unwrap().move_focus_up();
             }
+            ScreenInstruction::MovePaneLeft => {
+                screen.get_active_tab_mut().unwrap().move_active_pane_left();
+            }
+            ScreenInstruction::MovePaneDown => {
+                screen.get_active_tab_mut().unwrap().move_active_pane_down();
+            }
+            ScreenInstruction::MovePaneUp => {
+                screen.get_active_tab_mut().unwrap().move_active_pane_up();
+            }
+            ScreenInstruction::MovePaneRight => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .move_active_pane_right();
+            }
+            ScreenInstruction::SwapPanes => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .swap_active_pane_with_next();
+            }
+            ScreenInstruction::BalancePanes => {
+                screen.get_active_tab_mut().unwrap().balance_panes();
+            }
             ScreenInstruction::ScrollUp => {
                 screen
                     .get_active_tab_mut()
                     .unwrap()
                     .scroll_active_terminal_up();
             }
+            ScreenInstruction::ScrollUpPane(pane_id) => {
+                if let Some(tab) = screen.get_tab_with_pane_mut(pane_id) {
+                    tab.scroll_terminal_with_id_up(pane_id);
+                }
+                screen.render();
+            }
             ScreenInstruction::ScrollUpAt(point) => {
                 screen
                     .get_active_tab_mut()
@@ -610,10 +2131,94 @@ pub(crate) fn screen_thread_main(
                     .unwrap()
                     .clear_active_terminal_scroll();
             }
+            ScreenInstruction::PageScrollback => {
+                screen
+                    .get_active_tab()
+                    .unwrap()
+                    .page_active_pane_scrollback();
+            }
+            ScreenInstruction::EditScrollback(reply) => {
+                screen.edit_scrollback(reply);
+            }
+            ScreenInstruction::ViewActivePane(reply) => {
+                screen.view_active_pane(reply);
+            }
+            ScreenInstruction::NewPaneWithStdinFromSelection(command, reply) => {
+                screen.new_pane_with_stdin_from_selection(command, reply);
+            }
+            ScreenInstruction::NewViewerPane(pid, viewed_pane_id, reply) => {
+                screen.open_pane(pid, reply, |tab, pid| {
+                    tab.new_viewer_pane(pid, viewed_pane_id)
+                });
+                screen.render();
+            }
+            ScreenInstruction::QuickCopy => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .start_quick_copy(&quick_copy_patterns);
+            }
+            ScreenInstruction::LinkHints => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .start_link_hints(&link_hint_patterns);
+            }
+            ScreenInstruction::ClearScreen(clear_scrollback) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .clear_active_terminal_screen(clear_scrollback);
+            }
+            ScreenInstruction::TogglePaneLink => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .toggle_active_pane_link();
+            }
+            ScreenInstruction::TogglePaneReadOnly => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .toggle_active_pane_read_only();
+            }
+            ScreenInstruction::TogglePaneStacking => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .toggle_active_pane_stacking();
+            }
+            ScreenInstruction::TogglePanePresentation => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .toggle_active_pane_presentation();
+            }
+            ScreenInstruction::TogglePanePinned => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .toggle_active_pane_pinned();
+            }
+            ScreenInstruction::SetPanePalette(palette) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .set_active_pane_palette(palette);
+            }
+            ScreenInstruction::SetPaneAmbiguousWidth(ambiguous_width) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .set_active_pane_ambiguous_width(ambiguous_width);
+            }
             ScreenInstruction::CloseFocusedPane => {
                 screen.get_active_tab_mut().unwrap().close_focused_pane();
                 screen.render();
             }
+            ScreenInstruction::ClosePaneWithId(pane_id) => {
+                screen.close_pane_with_id(pane_id);
+            }
             ScreenInstruction::SetSelectable(id, selectable, tab_index) => {
                 screen.get_indexed_tab_mut(tab_index).map_or_else(
                     || {
@@ -627,7 +2232,10 @@ pub(crate) fn screen_thread_main(
                 );
             }
             ScreenInstruction::ClosePane(id) => {
-                screen.get_active_tab_mut().unwrap().close_pane(id);
+                // panes refused by the limits of the session never made it into a tab
+                if screen.get_tab_with_pane_mut(id).is_some() {
+                    screen.get_active_tab_mut().unwrap().close_pane(id);
+                }
                 screen.render();
             }
             ScreenInstruction::ToggleActiveTerminalFullscreen => {
@@ -636,6 +2244,14 @@ pub(crate) fn screen_thread_main(
                     .unwrap()
                     .toggle_active_pane_fullscreen();
             }
+            ScreenInstruction::TogglePaneFullscreen(pane_id) => {
+                screen.focus_pane(pane_id);
+                if let Some(tab) = screen.get_active_tab_mut() {
+                    if tab.get_active_pane_id() == Some(pane_id) {
+                        tab.toggle_active_pane_fullscreen();
+                    }
+                }
+            }
             ScreenInstruction::TogglePaneFrames => {
                 screen.draw_pane_frames = !screen.draw_pane_frames;
                 for (_, tab) in screen.tabs.iter_mut() {
@@ -643,12 +2259,26 @@ pub(crate) fn screen_thread_main(
                 }
                 screen.render();
             }
+            ScreenInstruction::ToggleCleanScreen => {
+                screen.clean_screen = !screen.clean_screen;
+                for (_, tab) in screen.tabs.iter_mut() {
+                    tab.set_clean_screen(screen.clean_screen);
+                }
+                screen.render();
+            }
+            ScreenInstruction::ToggleUI => {
+                screen.ui_hidden = !screen.ui_hidden;
+                for (_, tab) in screen.tabs.iter_mut() {
+                    tab.set_ui_hidden(screen.ui_hidden);
+                }
+                screen.render();
+            }
             ScreenInstruction::SwitchTabNext => {
                 screen.switch_tab_next();
                 screen
                     .bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
                     .unwrap();
             }
             ScreenInstruction::SwitchTabPrev => {
@@ -656,7 +2286,7 @@ pub(crate) fn screen_thread_main(
                 screen
                     .bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
                     .unwrap();
             }
             ScreenInstruction::CloseTab => {
@@ -664,15 +2294,40 @@ pub(crate) fn screen_thread_main(
                 screen
                     .bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
                     .unwrap();
             }
-            ScreenInstruction::ApplyLayout(layout, new_pane_pids) => {
-                screen.apply_layout(layout, new_pane_pids);
+            ScreenInstruction::ApplyLayout(layout, new_pane_pids, tab_name, reply) => {
+                match screen.tab_limit_reached(&new_pane_pids) {
+                    Some(reason) => {
+                        // panes being moved to the new tab stay where they are
+                        for &pid in &new_pane_pids {
+                            if screen
+                                .get_tab_with_pane_mut(PaneId::Terminal(pid))
+                                .is_none()
+                            {
+                                screen
+                                    .bus
+                                    .senders
+                                    .send_to_pty(PtyInstruction::ClosePane(PaneId::Terminal(pid)))
+                                    .unwrap();
+                            }
+                        }
+                        screen.refuse(reason, reply);
+                    }
+                    None => {
+                        let panes = new_pane_pids.iter().map(|&pid| pid as u32).collect();
+                        screen.apply_layout(layout, new_pane_pids, tab_name.unwrap_or_default());
+                        if let Some(reply) = reply {
+                            let position = screen.get_active_tab().unwrap().position + 1;
+                            let _ = reply.send(ActionResult::NewTab { position, panes });
+                        }
+                    }
+                }
                 screen
                     .bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
                     .unwrap();
             }
             ScreenInstruction::GoToTab(tab_index) => {
@@ -680,17 +2335,156 @@ pub(crate) fn screen_thread_main(
                 screen
                     .bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
                     .unwrap();
             }
+            ScreenInstruction::CloseTabAt(tab_index) => {
+                screen.close_tab_at(tab_index as usize);
+            }
             ScreenInstruction::UpdateTabName(c) => {
                 screen.update_active_tab_name(c);
             }
+            ScreenInstruction::UpdatePaneName(c) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .update_active_pane_name(c);
+                screen.render();
+            }
+            ScreenInstruction::UpdateSearch(c) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .update_active_pane_search(c);
+                screen.render();
+            }
+            ScreenInstruction::Search(direction) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .search_active_pane(direction);
+                screen.render();
+            }
+            ScreenInstruction::ToggleSearchOption(option) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .toggle_active_pane_search_option(option);
+                screen.render();
+            }
+            ScreenInstruction::UpdateTabSearch(c) => {
+                screen.get_active_tab_mut().unwrap().update_tab_search(c);
+                screen.render();
+            }
+            ScreenInstruction::TabSearch(direction) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .move_tab_search_selection(direction);
+                screen.render();
+            }
+            ScreenInstruction::TabSearchJump => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .jump_to_tab_search_result();
+                screen.render();
+            }
+            ScreenInstruction::UpdateSessionName(c) => {
+                screen.update_session_name(c);
+            }
+            ScreenInstruction::RenameSession(name) => {
+                screen.rename_session(name);
+            }
+            ScreenInstruction::UpdateCommandHistory(c) => {
+                screen.update_command_history(c);
+                screen.render();
+            }
+            ScreenInstruction::CommandHistory(direction) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .move_command_history_selection(direction);
+                screen.render();
+            }
+            ScreenInstruction::CommandHistoryRun(in_its_pane) => {
+                screen.run_from_command_history(in_its_pane);
+                screen.render();
+            }
+            ScreenInstruction::UpdateSessionManager(c) => {
+                screen.update_session_manager(c);
+                screen.render();
+            }
+            ScreenInstruction::SessionManager(direction) => {
+                if let Some(session_manager) = screen
+                    .get_active_tab_mut()
+                    .and_then(|tab| tab.session_manager_mut())
+                {
+                    session_manager.move_selection(direction);
+                }
+                screen.render();
+            }
+            ScreenInstruction::SessionManagerSwitch => {
+                screen.switch_to_selected_session();
+                screen.render();
+            }
+            ScreenInstruction::SessionManagerRename => {
+                screen.rename_selected_session();
+                screen.render();
+            }
+            ScreenInstruction::SessionManagerKill => {
+                screen.kill_selected_session();
+                screen.render();
+            }
+            ScreenInstruction::SessionManagerSessions(sessions) => {
+                if let Some(tab) = screen.get_active_tab_mut() {
+                    tab.set_sessions(sessions);
+                }
+                screen.render();
+            }
+            ScreenInstruction::CopyModeMove(motion) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .move_active_pane_copy_cursor(motion);
+                screen.render();
+            }
+            ScreenInstruction::CopyModeSelect(kind) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .toggle_active_pane_copy_selection(kind);
+                screen.render();
+            }
+            ScreenInstruction::CopyModeYank => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .yank_active_pane_copy_selection();
+                screen.render();
+            }
+            ScreenInstruction::UpdateTabGroupName(c) => {
+                screen.update_active_tab_group_name(c);
+            }
+            ScreenInstruction::MoveTabToNextGroup => {
+                screen.move_active_tab_to_next_group();
+            }
+            ScreenInstruction::ToggleTabGroupCollapsed => {
+                screen.toggle_active_tab_group_collapsed();
+            }
             ScreenInstruction::TerminalResize(new_size) => {
                 screen.resize_to_screen(new_size);
             }
+            ScreenInstruction::TerminalFocus(focused) => {
+                screen.set_terminal_focus(focused);
+            }
             ScreenInstruction::ChangeMode(mode_info) => {
                 screen.change_mode(mode_info);
+                screen
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
+                    .unwrap();
             }
             ScreenInstruction::ToggleActiveSyncTab => {
                 screen
@@ -699,11 +2493,17 @@ pub(crate) fn screen_thread_main(
                     .toggle_sync_panes_is_active();
                 screen.update_tabs();
             }
-            ScreenInstruction::LeftClick(point) => {
+            ScreenInstruction::LeftClick(point, modifiers) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .handle_left_click(&point, modifiers);
+            }
+            ScreenInstruction::MiddleClick(point, modifiers) => {
                 screen
                     .get_active_tab_mut()
                     .unwrap()
-                    .handle_left_click(&point);
+                    .handle_middle_click(&point, modifiers);
             }
             ScreenInstruction::MouseRelease(point) => {
                 screen
@@ -717,9 +2517,35 @@ pub(crate) fn screen_thread_main(
                     .unwrap()
                     .handle_mouse_hold(&point);
             }
+            ScreenInstruction::MouseMotion(point) => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .handle_mouse_motion(&point);
+            }
             ScreenInstruction::Copy => {
                 screen.get_active_tab().unwrap().copy_selection();
             }
+            ScreenInstruction::StartPaneTimer(timer) => {
+                screen.start_pane_timer(timer);
+            }
+            ScreenInstruction::StopPaneTimer => {
+                screen
+                    .get_active_tab_mut()
+                    .unwrap()
+                    .stop_active_pane_timer();
+            }
+            ScreenInstruction::TickPaneTimers => {
+                screen.tick_pane_timers();
+            }
+            ScreenInstruction::AddClient(client_id, mode_info) => {
+                screen.add_client(client_id, mode_info);
+            }
+            ScreenInstruction::RemoveClient(client_id) => {
+                screen.remove_client(client_id);
+            }
+            // instructions from a client are unwrapped above
+            ScreenInstruction::FromClient(..) => {}
             ScreenInstruction::Exit => {
                 break;
             }
@@ -728,9 +2554,60 @@ pub(crate) fn screen_thread_main(
                 screen
                     .bus
                     .senders
-                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .send_to_server(ServerInstruction::UnblockInputThread(from_client))
                     .unwrap();
             }
+            ScreenInstruction::BreakPaneToNewTab => {
+                screen.break_active_pane_to_new_tab();
+            }
+            ScreenInstruction::BreakPaneLeft => {
+                screen.break_active_pane_to_adjacent_tab(false);
+            }
+            ScreenInstruction::BreakPaneRight => {
+                screen.break_active_pane_to_adjacent_tab(true);
+            }
+            ScreenInstruction::DuplicateTab(shell, rerun, reply) => {
+                screen.duplicate_active_tab(shell, rerun, reply);
+            }
+            ScreenInstruction::ApplyLayoutToActiveTab(shell, layout_path, reply) => {
+                screen.apply_layout_to_active_tab(shell, layout_path, reply);
+            }
+            ScreenInstruction::ApplyLayoutToTab(tab_index, layout, new_pids, reply) => {
+                screen.apply_layout_to_tab(tab_index, layout, new_pids, reply);
+            }
+            ScreenInstruction::NextSwapLayout(reply) => {
+                screen.next_swap_layout(reply);
+            }
+            ScreenInstruction::DumpLayout(path, reply) => {
+                screen.dump_layout(path, reply);
+            }
+            ScreenInstruction::SerializeSession(reply) => {
+                screen.serialize_session(reply);
+            }
+            ScreenInstruction::CheckIdle => {
+                screen.exit_if_idle();
+            }
+            ScreenInstruction::DumpScreen(path, full, ansi, reply) => {
+                screen.dump_screen(path, full, ansi, reply);
+            }
+            ScreenInstruction::TagFocusedPane(tag) => {
+                if let Some(tab) = screen.get_active_tab_mut() {
+                    tab.tag_active_pane(tag);
+                }
+                screen.update_tabs();
+            }
+            ScreenInstruction::UntagFocusedPane(tag) => {
+                if let Some(tab) = screen.get_active_tab_mut() {
+                    tab.untag_active_pane(&tag);
+                }
+                screen.update_tabs();
+            }
+            ScreenInstruction::ClosePanesWithTag(tag) => {
+                screen.close_panes_with_tag(&tag);
+            }
+            ScreenInstruction::ListPanes(tag, reply) => {
+                screen.list_panes(tag.as_deref(), reply);
+            }
         }
     }
 }
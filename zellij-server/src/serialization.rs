@@ -0,0 +1,91 @@
+//! Writing sessions to disk as they run and when they quit, for `zellij resurrect` to start them
+//! again once their server is gone, and quitting sessions left idle so they don't pile up.
+use std::{fs, path::PathBuf, time::Duration};
+
+use zellij_utils::input::{
+    bundle::{resurrection_path, SessionBundle},
+    config::ConfigError,
+    layout::LayoutFromYaml,
+    options::Options,
+};
+
+const DEFAULT_SERIALIZATION_INTERVAL: Duration = Duration::from_secs(60);
+/// How long quitting waits for the session to be written.
+pub(crate) const SERIALIZATION_TIMEOUT: Duration = Duration::from_secs(2);
+/// How often a session that quits once idle checks whether it is.
+pub(crate) const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Where a session is written and what goes along with its layout.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionSerialization {
+    pub path: PathBuf,
+    pub options: Options,
+    /// How many lines of the scrollback of each pane are written
+    pub scrollback_lines: usize,
+    pub interval: Duration,
+}
+
+impl SessionSerialization {
+    /// How the session `name` is written, unless the `session_serialization` option turns it
+    /// off.
+    pub fn from_options(name: &str, options: &Options) -> Option<Self> {
+        if options.session_serialization == Some(false) {
+            return None;
+        }
+        Some(SessionSerialization {
+            path: resurrection_path(name),
+            options: options.clone(),
+            scrollback_lines: options.scrollback_lines_to_serialize.unwrap_or(0),
+            interval: options
+                .serialization_interval
+                .filter(|interval| *interval > 0)
+                .map_or(DEFAULT_SERIALIZATION_INTERVAL, Duration::from_secs),
+        })
+    }
+    /// Writes the session to the file of its new name from now on.
+    pub fn rename(&mut self, name: &str) {
+        self.path = resurrection_path(name);
+    }
+    /// Writes the session with the tabs of `layout`, a session without tabs is gone for good and
+    /// its file is removed.
+    pub fn write(&self, layout: LayoutFromYaml) -> Result<(), ConfigError> {
+        if layout.tabs.is_empty() {
+            drop(fs::remove_file(&self.path));
+            return Ok(());
+        }
+        SessionBundle::resurrectable(layout, self.options.clone()).write_privately_to(&self.path)
+    }
+}
+
+/// When a session without clients and without output from its panes quits, written to disk to
+/// be resurrected later.
+#[derive(Debug, Clone)]
+pub(crate) struct IdleExit {
+    pub after: Duration,
+    /// The sessions that never quit this way
+    pub exceptions: Vec<String>,
+}
+
+impl IdleExit {
+    /// How the session quits once idle for the `idle_exit_hours` option, a session that isn't
+    /// written to disk never does since it couldn't be resurrected.
+    pub fn from_options(options: &Options) -> Option<Self> {
+        if options.session_serialization == Some(false) {
+            return None;
+        }
+        let hours = options.idle_exit_hours.filter(|hours| *hours > 0)?;
+        Some(IdleExit {
+            // so many hours that they don't fit are as good as never
+            after: Duration::from_secs(hours.saturating_mul(60 * 60)),
+            exceptions: options.idle_exit_exceptions.clone().unwrap_or_default(),
+        })
+    }
+    /// Whether the session `name`, idle for `idle`, quits.
+    pub fn is_due(&self, name: &str, idle: Duration) -> bool {
+        idle >= self.after && !self.exceptions.iter().any(|exception| exception == name)
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/serialization_tests.rs"]
+mod serialization_tests;
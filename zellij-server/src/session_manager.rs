@@ -0,0 +1,313 @@
+//! The session manager, a list of the sessions of the user drawn over the current tab to switch
+//! to one of them, rename it or kill it without leaving zellij.
+use std::os::unix::fs::FileTypeExt;
+use std::{fs, thread, time::Duration};
+
+use crate::panes::{render_list, SearchDirection};
+use zellij_utils::{
+    action_token, channels,
+    consts::{ZELLIJ_RESURRECTION_DIR, ZELLIJ_SOCK_DIR},
+    input::{actions::Action, bundle::resurrection_path},
+    interprocess::local_socket::LocalSocketStream,
+    ipc::{ActionResult, ClientToServerMsg, IpcSenderWithContext, PaneSummary, ServerToClientMsg},
+    pane_size::Viewport,
+};
+
+/// How long another session has to answer before it is listed as not responding.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionStatus {
+    /// The session the manager is shown in
+    Current,
+    Running,
+    /// The socket of the session accepts connections but its server doesn't answer
+    NotResponding,
+    /// The server of the session is gone, it can be resurrected
+    Exited,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    pub name: String,
+    pub status: SessionStatus,
+    pub tabs: usize,
+    pub panes: usize,
+    /// The titles of the panes, searched along with the name
+    pub pane_titles: Vec<String>,
+}
+
+impl SessionSummary {
+    pub fn new(name: String, status: SessionStatus, panes: &[PaneSummary]) -> Self {
+        let mut tabs: Vec<usize> = panes.iter().map(|pane| pane.tab_position).collect();
+        tabs.sort_unstable();
+        tabs.dedup();
+        SessionSummary {
+            name,
+            status,
+            tabs: tabs.len(),
+            panes: panes.len(),
+            pane_titles: panes.iter().map(|pane| pane.title.clone()).collect(),
+        }
+    }
+}
+
+/// The sessions other than `current`: those listening in the socket directory along with what
+/// they run, then those that exited and can be resurrected.
+pub(crate) fn other_sessions(current: &str) -> Vec<SessionSummary> {
+    let mut running: Vec<String> = fs::read_dir(&*ZELLIJ_SOCK_DIR)
+        .map(|files| {
+            files
+                .filter_map(|file| {
+                    let file = file.ok()?;
+                    if !file.file_type().ok()?.is_socket() {
+                        return None;
+                    }
+                    file.file_name().into_string().ok()
+                })
+                .filter(|name| name != current)
+                .collect()
+        })
+        .unwrap_or_default();
+    running.sort();
+    let mut exited: Vec<String> = fs::read_dir(&*ZELLIJ_RESURRECTION_DIR)
+        .map(|files| {
+            files
+                .filter_map(|file| {
+                    let path = file.ok()?.path();
+                    if path.extension()? != "yaml" {
+                        return None;
+                    }
+                    path.file_stem()?.to_str().map(String::from)
+                })
+                .filter(|name| name != current && !running.contains(name))
+                .collect()
+        })
+        .unwrap_or_default();
+    exited.sort();
+    let running =
+        running
+            .into_iter()
+            .map(|name| match send_action(&name, Action::ListPanes(None)) {
+                Ok(ActionResult::Panes(panes)) => {
+                    SessionSummary::new(name, SessionStatus::Running, &panes)
+                }
+                _ => SessionSummary::new(name, SessionStatus::NotResponding, &[]),
+            });
+    let exited = exited
+        .into_iter()
+        .map(|name| SessionSummary::new(name, SessionStatus::Exited, &[]));
+    running.chain(exited).collect()
+}
+
+/// Sends `action` to the session `name` the way `zellij action` does, and waits a little
+/// while for its result.
+pub(crate) fn send_action(name: &str, action: Action) -> Result<ActionResult, String> {
+    let path = ZELLIJ_SOCK_DIR.join(name);
+    let stream = LocalSocketStream::connect(&*path).map_err(|e| e.to_string())?;
+    let token = action_token::read(name, &path);
+    // a hung server would block the thread forever, so it is talked to on another one
+    let (result_sender, result_receiver) = channels::bounded(1);
+    thread::spawn(move || {
+        let mut sender = IpcSenderWithContext::new(stream);
+        let mut receiver = sender.get_receiver::<ServerToClientMsg>();
+        sender.send(ClientToServerMsg::CliAction(action, token));
+        if let Some((ServerToClientMsg::ActionResult(result), _)) = receiver.try_recv() {
+            let _ = result_sender.send(result);
+        }
+    });
+    result_receiver
+        .recv_timeout(QUERY_TIMEOUT)
+        .map_err(|_| format!("Session {:?} doesn't respond", name))
+}
+
+/// Renames the session `session`, other than the current one, to `name`: a running session
+/// renames itself, the file of one that exited is moved.
+pub(crate) fn rename_other_session(session: &SessionSummary, name: &str) -> Result<(), String> {
+    match session.status {
+        SessionStatus::Exited => {
+            if name.is_empty() || name.contains('/') {
+                return Err(format!("{:?} can't name a session", name));
+            }
+            if ZELLIJ_SOCK_DIR.join(name).exists() || resurrection_path(name).exists() {
+                return Err(format!("A session named {:?} already exists", name));
+            }
+            fs::rename(resurrection_path(&session.name), resurrection_path(name))
+                .map_err(|e| format!("Failed to rename the session: {}", e))
+        }
+        _ => match send_action(&session.name, Action::RenameSession(name.to_string()))? {
+            ActionResult::Refused(reason) => Err(reason),
+            _ => Ok(()),
+        },
+    }
+}
+
+/// Kills the session `session`, other than the current one: a running session quits (and can
+/// be resurrected), the file of one that exited is removed.
+pub(crate) fn kill_other_session(session: &SessionSummary) -> Result<(), String> {
+    match session.status {
+        SessionStatus::Exited => fs::remove_file(resurrection_path(&session.name))
+            .map_err(|e| format!("Failed to delete the session: {}", e)),
+        _ => {
+            // the session may be gone before it answers
+            drop(send_action(&session.name, Action::Quit));
+            Ok(())
+        }
+    }
+}
+
+/// Whether the characters of `needle` come in `haystack` in the same order, ignoring case.
+fn fuzzy_matches(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars().flat_map(char::to_lowercase);
+    needle
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| haystack.any(|h| h == c))
+}
+
+/// The list of sessions, filtered by what is typed.
+#[derive(Debug, Clone, Default)]
+pub struct SessionManager {
+    pub filter: String,
+    /// The new name typed for the selected session, while renaming it
+    pub new_name: Option<String>,
+    // `None` until the sessions are listed
+    sessions: Option<Vec<SessionSummary>>,
+    // the indices of the sessions the filter matches
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+impl SessionManager {
+    pub fn set_sessions(&mut self, sessions: Vec<SessionSummary>) {
+        let selected_name = self.selected_session().map(|session| session.name.clone());
+        self.sessions = Some(sessions);
+        self.find_matches();
+        // the selection stays on the same session when the list is refreshed
+        if let Some(position) = selected_name.and_then(|name| {
+            self.matches
+                .iter()
+                .position(|index| self.session(*index).map(|s| &s.name) == Some(&name))
+        }) {
+            self.selected = position;
+        }
+    }
+    fn session(&self, index: usize) -> Option<&SessionSummary> {
+        self.sessions.as_ref()?.get(index)
+    }
+    /// Types into the new name of the selected session while renaming it, or else into the
+    /// filter. A `0` clears the text.
+    pub fn update_input(&mut self, input: &str) {
+        let text = self.new_name.as_mut().unwrap_or(&mut self.filter);
+        match input {
+            "\0" => text.clear(),
+            "\u{007F}" | "\u{0008}" => {
+                //delete and backspace keys
+                text.pop();
+            }
+            c => text.push_str(c),
+        }
+        if self.new_name.is_none() {
+            self.selected = 0;
+            self.find_matches();
+        }
+    }
+    fn find_matches(&mut self) {
+        let filter = &self.filter;
+        self.matches = self
+            .sessions
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, session)| {
+                fuzzy_matches(filter, &session.name)
+                    || session
+                        .pane_titles
+                        .iter()
+                        .any(|title| fuzzy_matches(filter, title))
+            })
+            .map(|(index, _)| index)
+            .collect();
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+    /// Selects the session above or below the selected one, wrapping around.
+    pub fn move_selection(&mut self, direction: SearchDirection) {
+        if self.new_name.is_some() {
+            return;
+        }
+        let count = self.matches.len();
+        self.selected = match direction {
+            SearchDirection::Up if self.selected > 0 => self.selected - 1,
+            SearchDirection::Up => count.saturating_sub(1),
+            SearchDirection::Down if self.selected + 1 < count => self.selected + 1,
+            SearchDirection::Down => 0,
+        };
+    }
+    /// Starts typing a new name for the selected session, or stops, returning that session
+    /// along with the name typed for it.
+    pub fn toggle_renaming(&mut self) -> Option<(SessionSummary, String)> {
+        match self.new_name.take() {
+            Some(new_name) => self
+                .selected_session()
+                .cloned()
+                .map(|session| (session, new_name)),
+            None => {
+                if self.selected_session().is_some() {
+                    self.new_name = Some(String::new());
+                }
+                None
+            }
+        }
+    }
+    pub fn selected_session(&self) -> Option<&SessionSummary> {
+        self.matches
+            .get(self.selected)
+            .and_then(|index| self.session(*index))
+    }
+    /// The filter (or the new name of the selected session) and as many of the matching
+    /// sessions around the selected one as fit, drawn over the top left corner of `viewport`.
+    pub fn render(&self, viewport: &Viewport) -> String {
+        let header = match (&self.new_name, self.selected_session()) {
+            (Some(new_name), Some(session)) => {
+                format!(" RENAME SESSION {}: {} ", session.name, new_name)
+            }
+            _ if self.sessions.is_none() => format!(" SESSIONS: {} ... ", self.filter),
+            _ => format!(
+                " SESSIONS: {} {}/{} ",
+                self.filter,
+                if self.matches.is_empty() {
+                    0
+                } else {
+                    self.selected + 1
+                },
+                self.matches.len()
+            ),
+        };
+        let rows: Vec<String> = self
+            .matches
+            .iter()
+            .filter_map(|index| self.session(*index))
+            .map(|session| {
+                let status = match session.status {
+                    SessionStatus::Current => " (current)",
+                    SessionStatus::NotResponding => " (not responding)",
+                    SessionStatus::Exited => " (exited)",
+                    SessionStatus::Running => "",
+                };
+                if session.tabs > 0 {
+                    format!(
+                        " {}{}  {} tabs, {} panes",
+                        session.name, status, session.tabs, session.panes
+                    )
+                } else {
+                    format!(" {}{}", session.name, status)
+                }
+            })
+            .collect();
+        render_list(viewport, &header, &rows, self.selected)
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/session_manager_tests.rs"]
+mod session_manager_tests;
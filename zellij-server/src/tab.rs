@@ -2,35 +2,61 @@
 //! as well as how they should be resized
 use crate::ui::pane_resizer::PaneResizer;
 use crate::{
-    os_input_output::ServerOsApi,
-    panes::{PaneId, PluginPane, TerminalPane},
+    os_input_output::{ClientId, ServerOsApi},
+    panes::{
+        run_on_elapsed, HistoryEntry, HistoryItem, HistoryPicker, PaneId, PluginPane,
+        QuickCopyInput, RunningTimer, Search, SearchDirection, SearchOption, TabSearch,
+        TabSearchResult, TerminalPane, DEFAULT_TABSTOP_WIDTH, SCROLL_BACK,
+    },
     pty::{PtyInstruction, VteBytes},
-    thread_bus::ThreadSenders,
+    session_manager::{SessionManager, SessionSummary},
+    thread_bus::{ActionReply, ThreadSenders},
     ui::boundaries::Boundaries,
+    ui::pane_boundaries_frame::PaneFrame,
+    ui::render_profile::RenderProfile,
     wasm_vm::PluginInstruction,
     ServerInstruction, SessionState,
 };
+use ansi_term::Colour::{Fixed, RGB};
+use ansi_term::Style;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::fs;
 use std::os::unix::io::RawFd;
+use std::path::{Path, PathBuf};
 use std::sync::{mpsc::channel, Arc, RwLock};
 use std::time::Instant;
 use std::{
     cmp::Reverse,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
 };
-use zellij_tile::data::{Event, InputMode, ModeInfo, Palette, PaletteColor};
+use zellij_tile::data::{Event, InputMode, ModeInfo, Mouse, MouseModifiers, Palette, PaletteColor};
 use zellij_utils::input::layout::Direction;
 use zellij_utils::pane_size::{Offset, Size, Viewport};
 use zellij_utils::{
+    consts::ZELLIJ_TMP_DIR,
     input::{
-        layout::{Layout, Run},
+        actions::{CopyMotion, PaneTimer, ResizeAmount, SelectionKind},
+        command::TerminalAction,
+        layout::{Layout, Run, RunPlugin, SplitSize, SwapLayout, TabLayout},
+        options::{AmbiguousWidth, Options, PaneClipboardPolicy, PaneFrameStyle, SmallPanesPolicy},
         parse_keys,
+        theme::PaletteOverride,
     },
+    ipc::PaneSummary,
+    messages::{Locale, Message},
     pane_size::{Dimension, PaneGeom},
     position::Position,
     serde, zellij_tile,
 };
 
+const DEFAULT_PANE_CLIPBOARD_MAX_SIZE: usize = 100_000;
+
+const BRACKETED_PASTE_START: &[u8] = b"\x1b[200~";
+const BRACKETED_PASTE_END: &[u8] = b"\x1b[201~";
+
+const PANE_LINK_LABELS: &str = "123456789abcdefghijklmnopqrstuvwxyz";
+
 const CURSOR_HEIGHT_WIDTH_RATIO: usize = 4; // this is not accurate and kind of a magic number, TODO: look into this
 
 // FIXME: This should be replaced by `RESIZE_PERCENT` at some point
@@ -39,6 +65,12 @@ const MIN_TERMINAL_WIDTH: usize = 5;
 
 const RESIZE_PERCENT: f64 = 5.0;
 
+// on terminals at least this wide, redrawing every pane at once (on attach, or when switching
+// tabs) can take seconds to reach a client over a slow link, so the panes are drawn a chunk of
+// about this many bytes per frame, the focused pane first
+const PROGRESSIVE_RENDER_MIN_COLS: usize = 300;
+const PROGRESSIVE_RENDER_CHUNK_BYTES: usize = 64 * 1024;
+
 type BorderAndPaneIds = (usize, Vec<PaneId>);
 
 fn split(direction: Direction, rect: &PaneGeom) -> Option<(PaneGeom, PaneGeom)> {
@@ -75,6 +107,218 @@ fn split(direction: Direction, rect: &PaneGeom) -> Option<(PaneGeom, PaneGeom)>
     }
 }
 
+// splits along the longer side of the pane (in how it looks, with the cells being higher than
+// they are wide), or the other one when that leaves either side with less than the minimum size
+fn auto_split_direction(pane: &dyn Pane, min_cols: usize, min_rows: usize) -> Option<Direction> {
+    let can_split_horizontally = pane.rows() >= min_rows * 2;
+    let can_split_vertically = pane.cols() >= min_cols * 2;
+    let prefers_horizontal_split = pane.rows() * CURSOR_HEIGHT_WIDTH_RATIO > pane.cols();
+    match (
+        prefers_horizontal_split,
+        can_split_horizontally,
+        can_split_vertically,
+    ) {
+        (true, true, _) | (false, true, false) => Some(Direction::Horizontal),
+        (false, _, true) | (true, false, true) => Some(Direction::Vertical),
+        _ => None,
+    }
+}
+
+// a column or row of panes, cut from the others from edge to edge:
+// (start, end, has fixed size, panes)
+type Section = (usize, usize, bool, Vec<PaneId>);
+
+// cuts the panes into columns from edge to edge, or else into rows
+fn cut_into_sections(
+    pane_ids: &[PaneId],
+    geoms: &HashMap<PaneId, PaneGeom>,
+) -> Option<(Direction, Vec<Section>)> {
+    for direction in [Direction::Vertical, Direction::Horizontal] {
+        let extent = |pane_id: &PaneId| {
+            let geom = &geoms[pane_id];
+            match direction {
+                Direction::Vertical => (geom.x, geom.cols),
+                Direction::Horizontal => (geom.y, geom.rows),
+            }
+        };
+        let mut sorted_pane_ids = pane_ids.to_vec();
+        sorted_pane_ids.sort_by_key(|pane_id| extent(pane_id).0);
+        let mut sections: Vec<Section> = vec![];
+        for pane_id in sorted_pane_ids {
+            let (start, size) = extent(&pane_id);
+            let end = start + size.as_usize();
+            match sections.last_mut() {
+                Some(section) if start < section.1 => {
+                    section.1 = section.1.max(end);
+                    section.2 |= size.is_fixed();
+                    section.3.push(pane_id);
+                }
+                _ => sections.push((start, end, size.is_fixed(), vec![pane_id])),
+            }
+        }
+        if sections.len() >= 2 {
+            return Some((direction, sections));
+        }
+    }
+    None
+}
+
+// shares `area` evenly between the columns of panes it is cut into from edge to edge, or else
+// between the rows, then balances the panes in each of them the same way - columns and rows with
+// fixed size panes keep their size
+fn balance_area(
+    area: Viewport,
+    pane_ids: Vec<PaneId>,
+    geoms: &HashMap<PaneId, PaneGeom>,
+    balanced: &mut HashMap<PaneId, Viewport>,
+) {
+    if pane_ids.len() == 1 {
+        balanced.insert(pane_ids[0], area);
+        return;
+    }
+    if let Some((direction, sections)) = cut_into_sections(&pane_ids, geoms) {
+        let (area_start, area_size) = match direction {
+            Direction::Vertical => (area.x, area.cols),
+            Direction::Horizontal => (area.y, area.rows),
+        };
+        let fixed_size: usize = sections
+            .iter()
+            .filter(|section| section.2)
+            .map(|section| section.1 - section.0)
+            .sum();
+        let flexible_count = sections.iter().filter(|section| !section.2).count();
+        let flexible_size = area_size.saturating_sub(fixed_size);
+        let mut position = area_start;
+        let mut flexible_index = 0;
+        for (start, end, fixed, section_pane_ids) in sections {
+            let size = if fixed {
+                end - start
+            } else {
+                // the columns or rows that don't divide evenly go to the first sections
+                let share = flexible_size / flexible_count
+                    + usize::from(flexible_index < flexible_size % flexible_count);
+                flexible_index += 1;
+                share
+            };
+            let section_area = match direction {
+                Direction::Vertical => Viewport {
+                    x: position,
+                    cols: size,
+                    ..area
+                },
+                Direction::Horizontal => Viewport {
+                    y: position,
+                    rows: size,
+                    ..area
+                },
+            };
+            balance_area(section_area, section_pane_ids, geoms, balanced);
+            position += size;
+        }
+        return;
+    }
+    // the panes can't be cut apart from edge to edge, they stay as they are
+    for pane_id in pane_ids {
+        let geom = geoms[&pane_id];
+        balanced.insert(
+            pane_id,
+            Viewport {
+                x: geom.x,
+                y: geom.y,
+                rows: geom.rows.as_usize(),
+                cols: geom.cols.as_usize(),
+            },
+        );
+    }
+}
+
+// the layout the panes are in, cut apart the same way they are balanced - the percents of the
+// flexible columns and rows are of the whole tab, as they are in layout files, and the panes
+// are added to `leaves` in the order the layout spawns them
+fn layout_of_panes(
+    pane_ids: Vec<PaneId>,
+    geoms: &HashMap<PaneId, PaneGeom>,
+    (cols_percent, rows_percent): (f64, f64),
+    leaves: &mut Vec<PaneId>,
+) -> TabLayout {
+    let (direction, sections) = match cut_into_sections(&pane_ids, geoms) {
+        Some(cut) => cut,
+        None => {
+            // a single pane, or panes that can't be cut apart, which take the place of the first
+            // of them together
+            leaves.push(pane_ids[0]);
+            return TabLayout::default();
+        }
+    };
+    let flexible_size: usize = sections
+        .iter()
+        .filter(|section| !section.2)
+        .map(|section| section.1 - section.0)
+        .sum();
+    let parts = sections
+        .into_iter()
+        .map(|(start, end, fixed, section_pane_ids)| {
+            let size = end - start;
+            let area_percent = match direction {
+                Direction::Vertical => cols_percent,
+                Direction::Horizontal => rows_percent,
+            };
+            let (split_size, section_percent) = if fixed {
+                (SplitSize::Fixed(size), area_percent)
+            } else {
+                let percent = area_percent * size as f64 / flexible_size as f64;
+                (SplitSize::Percent(percent), percent)
+            };
+            let mut part = match direction {
+                Direction::Vertical => layout_of_panes(
+                    section_pane_ids,
+                    geoms,
+                    (section_percent, rows_percent),
+                    leaves,
+                ),
+                Direction::Horizontal => layout_of_panes(
+                    section_pane_ids,
+                    geoms,
+                    (cols_percent, section_percent),
+                    leaves,
+                ),
+            };
+            part.split_size = Some(split_size);
+            part
+        })
+        .collect();
+    TabLayout {
+        direction,
+        parts,
+        ..Default::default()
+    }
+}
+
+// gives the panes of `layout` that have no parts the tags of `tags`, in the order the layout
+// spawns them
+fn set_leaf_tags<'a>(layout: &mut TabLayout, tags: &mut dyn Iterator<Item = &'a [String]>) {
+    if layout.parts.is_empty() {
+        layout.tags = tags.next().map(<[String]>::to_vec).unwrap_or_default();
+    }
+    for part in layout.parts.iter_mut() {
+        set_leaf_tags(part, tags);
+    }
+}
+
+/// The cursor of another client, a cell in the colour of that client.
+fn cursor_marker(x: usize, y: usize, color: PaletteColor) -> String {
+    let color = match color {
+        PaletteColor::Rgb((r, g, b)) => RGB(r, g, b),
+        PaletteColor::EightBit(color) => Fixed(color),
+    };
+    format!(
+        "\u{1b}[{};{}H\u{1b}[m{}",
+        y + 1,
+        x + 1,
+        Style::new().on(color).paint(" ")
+    ) // goto row/col, then draw a space on the colour
+}
+
 fn pane_content_offset(position_and_size: &PaneGeom, viewport: &Viewport) -> (usize, usize) {
     // (columns_offset, rows_offset)
     // if the pane is not on the bottom or right edge on the screen, we need to reserve one space
@@ -98,13 +342,17 @@ pub(crate) struct Tab {
     pub index: usize,
     pub position: usize,
     pub name: String,
+    pub group: Option<usize>, // the ID of the tab group on the screen
     panes: BTreeMap<PaneId, Box<dyn Pane>>,
+    suppressed_panes: BTreeMap<PaneId, Box<dyn Pane>>, // running, but not displayed
     panes_to_hide: HashSet<PaneId>,
     active_terminal: Option<PaneId>,
     max_panes: Option<usize>,
     viewport: Viewport, // includes all non-UI panes
     display_area: Size, // includes all panes (including eg. the status bar and tab bar in the default layout)
     fullscreen_is_active: bool,
+    geoms_before_fullscreen: Option<(Size, HashMap<PaneId, PaneGeom>)>, // display area, pane geoms
+    exit_fullscreen_on_focus_change: bool,
     os_api: Box<dyn ServerOsApi>,
     pub senders: ThreadSenders,
     synchronize_is_active: bool,
@@ -113,6 +361,168 @@ pub(crate) struct Tab {
     pub mode_info: ModeInfo,
     pub colors: Palette,
     draw_pane_frames: bool,
+    clean_screen: bool, // no frames, ui panes or overlays, eg. for screen recordings
+    ui_hidden: bool,
+    // the panes around the viewport, out of the layout while the ui is hidden
+    hidden_ui_panes: HashSet<PaneId>,
+    ui_margins: Offset, // the space the ui panes left around the viewport before being hidden
+    last_left_click: Option<(PaneId, Position)>, // relative to the clicked pane
+    hovered_pane: Option<PaneId>, // the pane under the mouse pointer, if motion is reported
+    linked_panes: HashMap<PaneId, PaneId>, // both ways, linked panes scroll together
+    viewed_panes: HashMap<PaneId, PaneId>, // viewer panes to the panes whose output they show
+    pane_link_picker: Option<(PaneId, Vec<(char, PaneId)>)>, // pane to link, labeled candidates
+    tab_search: Option<TabSearch>,
+    command_history_picker: Option<HistoryPicker>, // shown over this tab, lists the whole session
+    session_manager: Option<SessionManager>,
+    pane_clipboard: PaneClipboard,
+    pane_grid_options: PaneGridOptions,
+    pane_frame_options: PaneFrameOptions,
+    pane_frame_styles: HashMap<PaneId, PaneFrameStyle>, // set by the layout, over the default one
+    paste_options: PasteOptions,
+    pending_paste: Option<Vec<u8>>, // waiting for the user to confirm it
+    auto_split_options: AutoSplitOptions,
+    small_panes: SmallPanesPolicy,
+    hidden_small_panes: Vec<PaneId>, // hidden when the terminal got too small for them
+    pending_clipboard_write: Option<(PaneId, String)>, // waiting for the user to allow it
+    pane_stacks: Vec<Vec<PaneId>>,   // one pane of each stack is shown, the rest are suppressed
+    pub swap_layout: Option<SwapLayout>, // the arrangement the panes were last cycled to
+    clients: Option<Vec<TabClient>>, // viewing the tab while several clients are attached
+    cursor_markers: Vec<(PaneId, (usize, usize))>, // the cursors of the clients, as last drawn
+}
+
+/// A client viewing a [`Tab`] while several are attached to the session, the tab is drawn for
+/// each with its own cursor and the cursors of the others.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct TabClient {
+    pub id: ClientId,
+    /// In the order the clients attached, it picks the colour of the client
+    pub slot: usize,
+    /// The pane the client focuses, `None` for the active pane of the tab
+    pub active_pane: Option<PaneId>,
+}
+
+/// How applications running in panes may set the clipboard (OSC 52), and whether releasing the
+/// mouse copies the text selected with it.
+#[derive(Clone, Debug)]
+pub(crate) struct PaneClipboard {
+    pub policy: PaneClipboardPolicy,
+    pub max_size: usize,
+    pub locale: Locale, // of the prompt asking to allow a write, and of the notices
+    pub copy_on_select: bool,
+}
+
+impl PaneClipboard {
+    pub fn from_options(options: &Options) -> Self {
+        PaneClipboard {
+            policy: options.pane_clipboard_policy.unwrap_or_default(),
+            max_size: options
+                .pane_clipboard_max_size
+                .unwrap_or(DEFAULT_PANE_CLIPBOARD_MAX_SIZE),
+            locale: Locale::from_option(options.locale),
+            copy_on_select: !options.disable_copy_on_select,
+        }
+    }
+}
+
+/// How terminal panes lay out their text: where the default tab stops are, how the space tabs
+/// leave is copied, whether wrapped lines are re-wrapped when the pane is resized, whether
+/// applications can set the pane title and whether panes (and the screen) are redrawn in place
+/// rather than cleared first.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PaneGridOptions {
+    pub tab_width: usize,
+    pub copy_tabs_as_spaces: bool,
+    pub reflow_on_resize: bool,
+    pub accept_titles: bool,
+    pub reduced_motion: bool,
+    pub scroll_buffer_size: usize,
+    pub ligature_safe_boundaries: bool,
+    pub ambiguous_width: AmbiguousWidth,
+}
+
+impl PaneGridOptions {
+    pub fn from_options(options: &Options) -> Self {
+        PaneGridOptions {
+            tab_width: options
+                .tab_width
+                .filter(|&width| width > 0)
+                .unwrap_or(DEFAULT_TABSTOP_WIDTH),
+            copy_tabs_as_spaces: options.copy_tabs_as_spaces,
+            reflow_on_resize: !options.disable_reflow,
+            accept_titles: !options.disable_pane_titles,
+            reduced_motion: options.reduced_motion,
+            scroll_buffer_size: options.scroll_buffer_size.unwrap_or(SCROLL_BACK).max(1),
+            ligature_safe_boundaries: options.ligature_safe_boundaries,
+            ambiguous_width: options.ambiguous_width.unwrap_or_default(),
+        }
+    }
+}
+
+/// How pane frames are drawn: in which style, and whether they are left out when a pane is alone
+/// in its tab.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PaneFrameOptions {
+    pub style: PaneFrameStyle,
+    pub hide_single: bool,
+}
+
+impl PaneFrameOptions {
+    pub fn from_options(options: &Options) -> Self {
+        PaneFrameOptions {
+            style: options.pane_frames.unwrap_or_default(),
+            hide_single: options.auto_pane_frames,
+        }
+    }
+}
+
+/// What is done to text pasted into a pane: whether control characters are taken out of it, and
+/// whether the user is asked before several lines are pasted into a shell prompt.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PasteOptions {
+    pub sanitize: bool,
+    pub confirm_multiline: bool,
+}
+
+impl PasteOptions {
+    pub fn from_options(options: &Options) -> Self {
+        PasteOptions {
+            sanitize: options.sanitize_paste,
+            confirm_multiline: options.confirm_multiline_paste,
+        }
+    }
+}
+
+// takes out the control characters that could make a pasted text do more than insert itself, like
+// an escape sequence ending the bracketed paste early, leaving tabs and line breaks in place
+pub(crate) fn sanitize_paste(text: &[u8]) -> Vec<u8> {
+    String::from_utf8_lossy(text)
+        .chars()
+        .filter(|&c| !c.is_control() || c == '\t' || c == '\n' || c == '\r')
+        .collect::<String>()
+        .into_bytes()
+}
+
+/// The fewest columns and rows each side of a pane keeps when a new pane splits it in the
+/// automatic direction.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct AutoSplitOptions {
+    pub min_cols: usize,
+    pub min_rows: usize,
+}
+
+impl AutoSplitOptions {
+    pub fn from_options(options: &Options) -> Self {
+        AutoSplitOptions {
+            min_cols: options
+                .auto_split_min_columns
+                .unwrap_or(MIN_TERMINAL_WIDTH)
+                .max(MIN_TERMINAL_WIDTH),
+            min_rows: options
+                .auto_split_min_rows
+                .unwrap_or(MIN_TERMINAL_HEIGHT)
+                .max(MIN_TERMINAL_HEIGHT),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -182,6 +592,62 @@ pub trait Pane {
     fn get_selected_text(&self) -> Option<String> {
         None
     }
+    // the target of the hyperlink (OSC 8) at `position`
+    fn link_at(&self, _position: &Position) -> Option<String> {
+        None
+    }
+    // underlines the hyperlink under the mouse pointer, `None` when it isn't over the pane -
+    // returns whether that changed what is underlined
+    fn hover(&mut self, _position: Option<&Position>) -> bool {
+        false
+    }
+    fn dump_scrollback(&self) -> Option<String> {
+        None
+    }
+    fn dump_scrollback_text(&self) -> Option<String> {
+        None
+    }
+    fn dump_screen(&self, _full: bool, _ansi: bool) -> Option<String> {
+        None
+    }
+    fn dump_last_rows(&self, _count: usize) -> Option<String> {
+        None
+    }
+    fn last_command_output(&self) -> Option<String> {
+        None
+    }
+    fn command_history(&self) -> Vec<HistoryEntry> {
+        vec![]
+    }
+    fn scrollback_memory(&self) -> usize {
+        0
+    }
+    fn trim_scrollback(&mut self, _bytes: usize) -> usize {
+        0
+    }
+    fn clear_screen(&mut self, _clear_scrollback: bool) {}
+    fn start_quick_copy(&mut self, _patterns: &[Regex]) {}
+    // the hints of quick copy, for the links matching `patterns`
+    fn start_link_hints(&mut self, _patterns: &[Regex]) {}
+    fn handle_quick_copy_input(&mut self, _input_bytes: &[u8]) -> Option<QuickCopyInput> {
+        None
+    }
+    fn update_search(&mut self, _input: &str) {}
+    fn search(&mut self, _direction: SearchDirection) {}
+    fn toggle_search_option(&mut self, _option: SearchOption) {}
+    // the line of each match of `search` in the pane, for the search of all the panes of a tab
+    fn search_matches(&self, _search: &Search) -> Vec<String> {
+        vec![]
+    }
+    fn show_search_match(&mut self, _search: &Search, _index: usize) {}
+    fn end_search(&mut self) {}
+    fn start_copy_mode(&mut self) {}
+    fn end_copy_mode(&mut self) {}
+    fn move_copy_cursor(&mut self, _motion: CopyMotion) {}
+    fn toggle_copy_selection(&mut self, _kind: SelectionKind) {}
+    fn yank_copy_selection(&mut self) -> Option<String> {
+        None
+    }
 
     fn right_boundary_x_coords(&self) -> usize {
         self.x() + self.cols()
@@ -240,6 +706,18 @@ pub trait Pane {
         // we should probably refactor away from this trait at some point
         vec![]
     }
+    fn take_clipboard_write(&mut self) -> Option<String> {
+        None
+    }
+    /// The working directory the shell of the pane reported with OSC 7, if it changed since
+    /// the last time it was taken.
+    fn take_cwd_change(&mut self) -> Option<PathBuf> {
+        None
+    }
+    /// The working directory the shell of the pane reported last with OSC 7.
+    fn cwd(&self) -> Option<PathBuf> {
+        None
+    }
     fn render_full_viewport(&mut self) {}
     fn relative_position(&self, position_on_screen: &Position) -> Position {
         position_on_screen.relative_to(self.get_content_y(), self.get_content_x())
@@ -247,6 +725,41 @@ pub trait Pane {
     fn set_boundary_color(&mut self, _color: Option<PaletteColor>) {}
     fn set_borderless(&mut self, borderless: bool);
     fn borderless(&self) -> bool;
+    fn set_read_only(&mut self, read_only: bool);
+    fn read_only(&self) -> bool;
+    fn set_fullscreen(&mut self, fullscreen: bool);
+    fn set_presentation(&mut self, _presentation: bool) {}
+    fn presentation(&self) -> bool {
+        false
+    }
+    fn set_frame_style(&mut self, style: PaneFrameStyle);
+    fn set_pinned(&mut self, _pinned: bool) {}
+    fn pinned(&self) -> bool {
+        false
+    }
+    // freeform labels the pane can be listed and closed by, along with the others sharing them
+    fn set_tags(&mut self, _tags: Vec<String>) {}
+    fn tags(&self) -> &[String] {
+        &[]
+    }
+    // the countdown or stopwatch shown in the frame of the pane
+    fn set_timer(&mut self, _timer: Option<RunningTimer>) {}
+    fn timer_mut(&mut self) -> Option<&mut RunningTimer> {
+        None
+    }
+    // colors replacing those of the palette of the session in this pane
+    fn set_palette_override(&mut self, _palette: Option<PaletteOverride>) {}
+    /// Measures the characters of ambiguous width printed from now on as narrow or wide, or
+    /// after the option of the session with `None`.
+    fn set_ambiguous_width(&mut self, _ambiguous_width: Option<AmbiguousWidth>) {}
+    // whether the pane looks like a shell prompt that would run each line pasted into it
+    fn executes_pasted_lines(&self) -> bool {
+        false
+    }
+    fn title(&self) -> String;
+    // the name given by the user or the title set by the application, if any
+    fn custom_title(&self) -> Option<String>;
+    fn update_name(&mut self, name: &str);
 }
 
 impl Tab {
@@ -264,11 +777,18 @@ impl Tab {
         colors: Palette,
         session_state: Arc<RwLock<SessionState>>,
         draw_pane_frames: bool,
+        exit_fullscreen_on_focus_change: bool,
+        pane_clipboard: PaneClipboard,
+        pane_grid_options: PaneGridOptions,
+        pane_frame_options: PaneFrameOptions,
+        paste_options: PasteOptions,
+        auto_split_options: AutoSplitOptions,
+        small_panes: SmallPanesPolicy,
     ) -> Self {
         let panes = BTreeMap::new();
 
         let name = if name.is_empty() {
-            format!("Tab #{}", index + 1)
+            default_tab_name(index)
         } else {
             name
         };
@@ -277,13 +797,17 @@ impl Tab {
             index,
             position,
             panes,
+            suppressed_panes: BTreeMap::new(),
             name,
+            group: None,
             max_panes,
             panes_to_hide: HashSet::new(),
             active_terminal: None,
             viewport: display_area.into(),
             display_area,
             fullscreen_is_active: false,
+            geoms_before_fullscreen: None,
+            exit_fullscreen_on_focus_change,
             synchronize_is_active: false,
             os_api,
             senders,
@@ -292,6 +816,32 @@ impl Tab {
             colors,
             session_state,
             draw_pane_frames,
+            clean_screen: false,
+            ui_hidden: false,
+            hidden_ui_panes: HashSet::new(),
+            ui_margins: Offset::default(),
+            last_left_click: None,
+            hovered_pane: None,
+            linked_panes: HashMap::new(),
+            viewed_panes: HashMap::new(),
+            pane_link_picker: None,
+            tab_search: None,
+            command_history_picker: None,
+            session_manager: None,
+            pane_clipboard,
+            pane_grid_options,
+            pane_frame_options,
+            pane_frame_styles: HashMap::new(),
+            paste_options,
+            pending_paste: None,
+            auto_split_options,
+            small_panes,
+            hidden_small_panes: vec![],
+            pending_clipboard_write: None,
+            pane_stacks: vec![],
+            swap_layout: None,
+            clients: None,
+            cursor_markers: vec![],
         }
     }
 
@@ -309,6 +859,7 @@ impl Tab {
         let positions_in_layout = layout.position_panes_in_space(&free_space);
 
         let mut positions_and_size = positions_in_layout.iter();
+        let mut pane_ids_in_layout = vec![];
         for (pane_kind, terminal_pane) in self.panes.iter_mut() {
             // for now the layout only supports terminal panes
             if let PaneId::Terminal(pid) = pane_kind {
@@ -316,6 +867,7 @@ impl Tab {
                     Some(&(_, position_and_size)) => {
                         terminal_pane.reset_size_and_position_override();
                         terminal_pane.set_geom(position_and_size);
+                        pane_ids_in_layout.push(*pane_kind);
                     }
                     None => {
                         // we filled the entire layout, no room for this pane
@@ -330,45 +882,13 @@ impl Tab {
         for (layout, position_and_size) in positions_and_size {
             // A plugin pane
             if let Some(Run::Plugin(Some(plugin))) = &layout.run {
-                let (pid_tx, pid_rx) = channel();
-                self.senders
-                    .send_to_plugin(PluginInstruction::Load(
-                        pid_tx,
-                        plugin.path.clone(),
-                        tab_index,
-                        plugin._allow_exec_host_cmd,
-                    ))
-                    .unwrap();
-                let pid = pid_rx.recv().unwrap();
-                let title = String::from(plugin.path.as_path().as_os_str().to_string_lossy());
-                let mut new_plugin = PluginPane::new(
-                    pid,
-                    *position_and_size,
-                    self.senders.to_plugin.as_ref().unwrap().clone(),
-                    title,
-                );
-                new_plugin.set_borderless(layout.borderless);
-                self.panes.insert(PaneId::Plugin(pid), Box::new(new_plugin));
-                // Send an initial mode update to the newly loaded plugin only!
-                self.senders
-                    .send_to_plugin(PluginInstruction::Update(
-                        Some(pid),
-                        Event::ModeUpdate(self.mode_info.clone()),
-                    ))
-                    .unwrap();
+                let pane_id = self.load_plugin_pane(plugin, layout, *position_and_size, tab_index);
+                pane_ids_in_layout.push(pane_id);
             } else {
                 // there are still panes left to fill, use the pids we received in this method
                 let pid = new_pids.next().unwrap(); // if this crashes it means we got less pids than there are panes in this layout
-                let next_terminal_position = self.get_next_terminal_position();
-                let mut new_pane = TerminalPane::new(
-                    *pid,
-                    *position_and_size,
-                    self.colors,
-                    next_terminal_position,
-                );
-                new_pane.set_borderless(layout.borderless);
-                self.panes
-                    .insert(PaneId::Terminal(*pid), Box::new(new_pane));
+                self.insert_terminal_pane(*pid, layout, *position_and_size);
+                pane_ids_in_layout.push(PaneId::Terminal(*pid));
             }
         }
         for unused_pid in new_pids {
@@ -379,13 +899,186 @@ impl Tab {
                 .send_to_pty(PtyInstruction::ClosePane(PaneId::Terminal(*unused_pid)))
                 .unwrap();
         }
+        self.resize_to_layout();
+        self.stack_panes_of_layout(&layout, &pane_ids_in_layout);
+        self.set_pane_frames(self.draw_pane_frames);
+        // This is the end of the nasty viewport hack...
+        // FIXME: Active / new / current terminal, should be pane
+        self.set_active_terminal(self.panes.iter().map(|(id, _)| id.to_owned()).next());
+        self.render();
+    }
+    /// Re-tiles this tab after `layout`, the template of the session with the layout of the
+    /// tab in its body. The panes shown in the tab take the places of the layout in order,
+    /// terminals those of terminals and plugins those of plugins, and the `new_pids` the places
+    /// of terminals left. Terminals beyond the places of the layout join the stack of the last
+    /// one, plugins beyond them are closed.
+    pub fn retile(&mut self, layout: Layout, new_pids: Vec<RawFd>) {
+        if self.fullscreen_is_active {
+            self.toggle_active_pane_fullscreen();
+        }
+        // the ui panes are found again among the panes of the new layout
+        self.hidden_ui_panes.clear();
+        let mut terminals = self.shown_pane_ids(true).into_iter();
+        let mut plugins = self.shown_pane_ids(false).into_iter();
+        let mut new_pids = new_pids.into_iter();
+        let mut pane_ids_in_layout = vec![];
+        for (pane_layout, position_and_size) in layout.position_panes_in_space(&PaneGeom::default())
+        {
+            let pane_id = match &pane_layout.run {
+                Some(Run::Plugin(Some(plugin))) => match plugins.next() {
+                    Some(pane_id) => pane_id,
+                    None => {
+                        self.load_plugin_pane(plugin, &pane_layout, position_and_size, self.index)
+                    }
+                },
+                _ => match terminals.next() {
+                    Some(pane_id) => pane_id,
+                    None => match new_pids.next() {
+                        Some(pid) => {
+                            self.insert_terminal_pane(pid, &pane_layout, position_and_size);
+                            PaneId::Terminal(pid)
+                        }
+                        None => {
+                            log::error!("No pane left for a place of the layout");
+                            continue;
+                        }
+                    },
+                },
+            };
+            let pane = self.panes.get_mut(&pane_id).unwrap();
+            pane.reset_size_and_position_override();
+            pane.set_geom(position_and_size);
+            pane.set_borderless(pane_layout.borderless);
+            pane.set_palette_override(pane_layout.palette);
+            if pane_layout.ambiguous_width.is_some() {
+                pane.set_ambiguous_width(pane_layout.ambiguous_width);
+            }
+            // the panes keep the tags given to them since, the swap layouts have none
+            if !pane_layout.tags.is_empty() {
+                pane.set_tags(pane_layout.tags.clone());
+            }
+            match pane_layout.pane_frames {
+                Some(style) => self.pane_frame_styles.insert(pane_id, style),
+                None => self.pane_frame_styles.remove(&pane_id),
+            };
+            pane_ids_in_layout.push(pane_id);
+        }
+        for pid in new_pids {
+            // the tab gained panes since these terminals were started to fill its places
+            self.senders
+                .send_to_pty(PtyInstruction::ClosePane(PaneId::Terminal(pid)))
+                .unwrap();
+        }
+        for pane_id in plugins {
+            // nothing grows into their space, the layout already took it up
+            self.panes.remove(&pane_id);
+            self.pane_frame_styles.remove(&pane_id);
+            self.senders
+                .send_to_pty(PtyInstruction::ClosePane(pane_id))
+                .unwrap();
+        }
+        let last_terminal_id = pane_ids_in_layout
+            .iter()
+            .rev()
+            .find(|pane_id| matches!(pane_id, PaneId::Terminal(_)))
+            .copied();
+        if let Some(last_terminal_id) = last_terminal_id {
+            for pane_id in terminals {
+                self.add_to_stack(pane_id, last_terminal_id);
+            }
+        }
+        self.viewport = self.display_area.into();
+        self.resize_to_layout();
+        if self.ui_hidden {
+            self.hide_ui_panes();
+        }
+        self.stack_panes_of_layout(&layout, &pane_ids_in_layout);
+        self.set_pane_frames(self.draw_pane_frames);
+        let active_pane_id = self.get_active_pane_id();
+        if active_pane_id.map_or(true, |pane_id| !self.panes.contains_key(&pane_id)) {
+            self.set_active_terminal(pane_ids_in_layout.first().copied());
+        }
+        self.set_force_render();
+        self.render();
+    }
+    // the ids of the terminals (or of the plugins) shown in the tab, from the top left
+    fn shown_pane_ids(&self, terminals: bool) -> Vec<PaneId> {
+        let mut panes: Vec<(&PaneId, &Box<dyn Pane>)> = self
+            .panes
+            .iter()
+            .filter(|(pane_id, _)| matches!(pane_id, PaneId::Terminal(_)) == terminals)
+            .collect();
+        panes.sort_by_key(|(_, pane)| (pane.y(), pane.x()));
+        panes.into_iter().map(|(&pane_id, _)| pane_id).collect()
+    }
+    /// The number of terminals shown in this tab, those in the background of stacks or
+    /// suppressed left out.
+    pub fn shown_terminal_count(&self) -> usize {
+        self.shown_pane_ids(true).len()
+    }
+    fn load_plugin_pane(
+        &mut self,
+        plugin: &RunPlugin,
+        layout: &Layout,
+        position_and_size: PaneGeom,
+        tab_index: usize,
+    ) -> PaneId {
+        let (pid_tx, pid_rx) = channel();
+        self.senders
+            .send_to_plugin(PluginInstruction::Load(
+                pid_tx,
+                plugin.path.clone(),
+                tab_index,
+                plugin._allow_exec_host_cmd,
+            ))
+            .unwrap();
+        let pid = pid_rx.recv().unwrap();
+        let title = String::from(plugin.path.as_path().as_os_str().to_string_lossy());
+        let mut new_plugin = PluginPane::new(
+            pid,
+            position_and_size,
+            self.senders.to_plugin.as_ref().unwrap().clone(),
+            title,
+        );
+        new_plugin.set_borderless(layout.borderless);
+        if let Some(style) = layout.pane_frames {
+            self.pane_frame_styles.insert(PaneId::Plugin(pid), style);
+        }
+        self.panes.insert(PaneId::Plugin(pid), Box::new(new_plugin));
+        // Send an initial mode update to the newly loaded plugin only!
+        self.senders
+            .send_to_plugin(PluginInstruction::Update(
+                Some(pid),
+                Event::ModeUpdate(self.mode_info.clone()),
+            ))
+            .unwrap();
+        PaneId::Plugin(pid)
+    }
+    fn insert_terminal_pane(&mut self, pid: RawFd, layout: &Layout, position_and_size: PaneGeom) {
+        let next_terminal_position = self.get_next_terminal_position();
+        let mut new_pane =
+            TerminalPane::new(pid, position_and_size, self.colors, next_terminal_position);
+        new_pane.set_borderless(layout.borderless);
+        new_pane.set_grid_options(self.pane_grid_options);
+        new_pane.set_palette_override(layout.palette);
+        new_pane.set_ambiguous_width(layout.ambiguous_width);
+        new_pane.set_tags(layout.tags.clone());
+        if let Some(style) = layout.pane_frames {
+            self.pane_frame_styles.insert(PaneId::Terminal(pid), style);
+        }
+        self.panes.insert(PaneId::Terminal(pid), Box::new(new_pane));
+    }
+    // sizes the panes placed after a layout, and crops the viewport to the fixed-size ones
+    fn resize_to_layout(&mut self) {
         // FIXME: This is another hack to crop the viewport to fixed-size panes. Once you can have
         // non-fixed panes that are part of the viewport, get rid of this!
         self.resize_whole_tab(self.display_area);
+        let hidden_ui_panes = &self.hidden_ui_panes;
         let boundary_geom: Vec<_> = self
             .panes
-            .values()
-            .filter_map(|p| {
+            .iter()
+            .filter(|(pane_id, _)| !hidden_ui_panes.contains(pane_id))
+            .filter_map(|(_, p)| {
                 let geom = p.position_and_size();
                 if geom.cols.is_fixed() || geom.rows.is_fixed() {
                     Some(geom.into())
@@ -397,11 +1090,40 @@ impl Tab {
         for geom in boundary_geom {
             self.offset_viewport(&geom)
         }
-        self.set_pane_frames(self.draw_pane_frames);
-        // This is the end of the nasty viewport hack...
-        // FIXME: Active / new / current terminal, should be pane
-        self.set_active_terminal(self.panes.iter().map(|(id, _)| id.to_owned()).next());
-        self.render();
+    }
+    fn stack_panes_of_layout(&mut self, layout: &Layout, pane_ids_in_layout: &[PaneId]) {
+        for stacked_pane_range in layout.stacked_pane_ranges() {
+            if let Some(stacked_pane_ids) = pane_ids_in_layout.get(stacked_pane_range) {
+                // stack the panes from the last one back, so that each joins the stack after it
+                for pane_ids in stacked_pane_ids.windows(2).rev() {
+                    self.stack_pane_onto(pane_ids[0], pane_ids[1]);
+                }
+            }
+        }
+    }
+    /// Opens the viewer pane `pid`, read only, showing what `viewed_pane_id` showed so far and
+    /// then whatever it is sent next.
+    pub fn new_viewer_pane(&mut self, pid: PaneId, viewed_pane_id: PaneId) {
+        self.new_pane(pid);
+        let viewed_pane = self
+            .panes
+            .get(&viewed_pane_id)
+            .or_else(|| self.suppressed_panes.get(&viewed_pane_id));
+        let (scrollback, title) = match viewed_pane {
+            Some(viewed_pane) => (viewed_pane.dump_scrollback(), viewed_pane.title()),
+            None => return,
+        };
+        if let Some(viewer) = self.panes.get_mut(&pid) {
+            viewer.set_read_only(true);
+            viewer.update_name(&format!("view of {}", title));
+            if let Some(scrollback) = scrollback {
+                // the dump ends with a newline after the last line, where the cursor of the
+                // viewed pane most likely still is
+                let scrollback = scrollback.strip_suffix('\n').unwrap_or(&scrollback);
+                viewer.handle_pty_bytes(scrollback.replace('\n', "\r\n").into_bytes());
+            }
+            self.viewed_panes.insert(pid, viewed_pane_id);
+        }
     }
     pub fn new_pane(&mut self, pid: PaneId) {
         self.close_down_to_max_terminals();
@@ -410,24 +1132,7 @@ impl Tab {
         }
         // TODO: check minimum size of active terminal
 
-        let (_largest_terminal_size, terminal_id_to_split) = self.get_panes().fold(
-            (0, None),
-            |(current_largest_terminal_size, current_terminal_id_to_split),
-             id_and_terminal_to_check| {
-                let (id_of_terminal_to_check, terminal_to_check) = id_and_terminal_to_check;
-                let terminal_size = (terminal_to_check.rows() * CURSOR_HEIGHT_WIDTH_RATIO)
-                    * terminal_to_check.cols();
-                let terminal_can_be_split = terminal_to_check.cols() >= MIN_TERMINAL_WIDTH
-                    && terminal_to_check.rows() >= MIN_TERMINAL_HEIGHT
-                    && ((terminal_to_check.cols() > terminal_to_check.min_width() * 2)
-                        || (terminal_to_check.rows() > terminal_to_check.min_height() * 2));
-                if terminal_can_be_split && terminal_size > current_largest_terminal_size {
-                    (terminal_size, Some(*id_of_terminal_to_check))
-                } else {
-                    (current_largest_terminal_size, current_terminal_id_to_split)
-                }
-            },
-        );
+        let terminal_id_to_split = self.largest_splittable_pane_id();
         if terminal_id_to_split.is_none() {
             self.senders
                 .send_to_pty(PtyInstruction::ClosePane(pid)) // we can't open this pane, close the pty
@@ -445,12 +1150,13 @@ impl Tab {
                 if let Some((top_winsize, bottom_winsize)) =
                     split(Direction::Horizontal, &terminal_ws)
                 {
-                    let new_terminal = TerminalPane::new(
+                    let mut new_terminal = TerminalPane::new(
                         term_pid,
                         bottom_winsize,
                         self.colors,
                         next_terminal_position,
                     );
+                    new_terminal.set_grid_options(self.pane_grid_options);
                     terminal_to_split.set_geom(top_winsize);
                     self.panes.insert(pid, Box::new(new_terminal));
                     self.relayout_tab(Direction::Vertical);
@@ -461,126 +1167,1051 @@ impl Tab {
                 if let Some((left_winsize, right_winsize)) =
                     split(Direction::Vertical, &terminal_ws)
                 {
-                    let new_terminal = TerminalPane::new(
+                    let mut new_terminal = TerminalPane::new(
                         term_pid,
                         right_winsize,
                         self.colors,
                         next_terminal_position,
                     );
+                    new_terminal.set_grid_options(self.pane_grid_options);
                     terminal_to_split.set_geom(left_winsize);
                     self.panes.insert(pid, Box::new(new_terminal));
                     self.relayout_tab(Direction::Horizontal);
                 }
             }
         }
-        self.set_active_terminal(Some(pid));
-        self.render();
+        self.set_active_terminal(Some(pid));
+        self.render();
+    }
+    // splits the focused pane along its longer side, or opens the pane in the biggest available
+    // space when the focused pane is too small
+    pub fn auto_split(&mut self, pid: PaneId) {
+        if self.fullscreen_is_active {
+            self.toggle_active_pane_fullscreen();
+        }
+        let direction = self.get_active_pane().and_then(|active_pane| {
+            auto_split_direction(
+                active_pane,
+                self.auto_split_options.min_cols,
+                self.auto_split_options.min_rows,
+            )
+        });
+        match direction {
+            Some(Direction::Horizontal) => self.horizontal_split(pid),
+            Some(Direction::Vertical) => self.vertical_split(pid),
+            None => self.new_pane(pid),
+        }
+    }
+    fn largest_splittable_pane_id(&self) -> Option<PaneId> {
+        let (_largest_terminal_size, terminal_id_to_split) = self.get_panes().fold(
+            (0, None),
+            |(current_largest_terminal_size, current_terminal_id_to_split),
+             id_and_terminal_to_check| {
+                let (id_of_terminal_to_check, terminal_to_check) = id_and_terminal_to_check;
+                let terminal_size = (terminal_to_check.rows() * CURSOR_HEIGHT_WIDTH_RATIO)
+                    * terminal_to_check.cols();
+                let terminal_can_be_split = terminal_to_check.cols() >= MIN_TERMINAL_WIDTH
+                    && terminal_to_check.rows() >= MIN_TERMINAL_HEIGHT
+                    && ((terminal_to_check.cols() > terminal_to_check.min_width() * 2)
+                        || (terminal_to_check.rows() > terminal_to_check.min_height() * 2));
+                if terminal_can_be_split && terminal_size > current_largest_terminal_size {
+                    (terminal_size, Some(*id_of_terminal_to_check))
+                } else {
+                    (current_largest_terminal_size, current_terminal_id_to_split)
+                }
+            },
+        );
+        terminal_id_to_split
+    }
+    // puts a pane taken out of another tab into this one, splitting the largest pane like a new
+    // pane would - if there is no room for it, it is added as a suppressed pane instead
+    pub fn add_pane(&mut self, mut pane: Box<dyn Pane>) {
+        if self.fullscreen_is_active {
+            self.toggle_active_pane_fullscreen();
+        }
+        let pane_id = pane.pid();
+        let pane_id_to_split = match self.largest_splittable_pane_id() {
+            Some(pane_id_to_split) => pane_id_to_split,
+            None => {
+                self.suppressed_panes.insert(pane_id, pane);
+                return;
+            }
+        };
+        let pane_to_split = self.panes.get_mut(&pane_id_to_split).unwrap();
+        let geom_to_split = pane_to_split.position_and_size();
+        let direction = if pane_to_split.rows() * CURSOR_HEIGHT_WIDTH_RATIO > pane_to_split.cols()
+            && pane_to_split.rows() > pane_to_split.min_height() * 2
+        {
+            Direction::Horizontal
+        } else {
+            Direction::Vertical
+        };
+        match split(direction, &geom_to_split) {
+            Some((kept_geom, given_geom)) => {
+                pane_to_split.set_geom(kept_geom);
+                pane.set_geom(given_geom);
+                pane.set_should_render(true);
+                self.panes.insert(pane_id, pane);
+                self.relayout_tab(!direction);
+                self.set_pane_frames(self.draw_pane_frames);
+                self.set_active_terminal(Some(pane_id));
+            }
+            None => {
+                self.suppressed_panes.insert(pane_id, pane);
+            }
+        }
+    }
+    // puts a pane taken out of another tab in place of the pane with the same id the layout of
+    // this tab made for it
+    // the pinned panes, taken out to follow the user to another tab - except for the last pane
+    // of this tab, which can't be left empty
+    pub fn take_pinned_panes(&mut self) -> Vec<Box<dyn Pane>> {
+        let pinned_pane_ids: Vec<PaneId> = self
+            .panes
+            .iter()
+            .filter(|(_, pane)| pane.pinned())
+            .map(|(&pane_id, _)| pane_id)
+            .collect();
+        let mut pinned_panes = vec![];
+        for pane_id in pinned_pane_ids {
+            let has_other_selectable_panes = self
+                .panes
+                .iter()
+                .any(|(&id, pane)| id != pane_id && pane.selectable());
+            if has_other_selectable_panes {
+                pinned_panes.extend(self.extract_pane(pane_id));
+            }
+        }
+        pinned_panes
+    }
+    // like add_pane, but the focus stays where it was
+    pub fn add_pinned_pane(&mut self, pane: Box<dyn Pane>) {
+        let active_pane_id = self.get_active_pane_id();
+        self.add_pane(pane);
+        if let Some(active_pane_id) = active_pane_id {
+            self.focus_pane(active_pane_id);
+        }
+    }
+    pub fn replace_pane(&mut self, mut pane: Box<dyn Pane>) {
+        let pane_id = pane.pid();
+        if let Some(placeholder) = self.panes.remove(&pane_id) {
+            pane.set_geom(placeholder.position_and_size());
+            pane.set_should_render(true);
+            self.panes.insert(pane_id, pane);
+            self.set_pane_frames(self.draw_pane_frames);
+            self.set_active_terminal(Some(pane_id));
+        }
+    }
+    pub fn horizontal_split(&mut self, pid: PaneId) {
+        self.close_down_to_max_terminals();
+        if self.fullscreen_is_active {
+            self.toggle_active_pane_fullscreen();
+        }
+        if let PaneId::Terminal(term_pid) = pid {
+            let next_terminal_position = self.get_next_terminal_position();
+            let active_pane_id = &self.get_active_pane_id().unwrap();
+            let active_pane = self.panes.get_mut(active_pane_id).unwrap();
+            if active_pane.rows() < MIN_TERMINAL_HEIGHT * 2 {
+                self.senders
+                    .send_to_pty(PtyInstruction::ClosePane(pid)) // we can't open this pane, close the pty
+                    .unwrap();
+                return;
+            }
+            let terminal_ws = active_pane.position_and_size();
+            if let Some((top_winsize, bottom_winsize)) = split(Direction::Horizontal, &terminal_ws)
+            {
+                let mut new_terminal = TerminalPane::new(
+                    term_pid,
+                    bottom_winsize,
+                    self.colors,
+                    next_terminal_position,
+                );
+                new_terminal.set_grid_options(self.pane_grid_options);
+                active_pane.set_geom(top_winsize);
+                self.panes.insert(pid, Box::new(new_terminal));
+                self.set_active_terminal(Some(pid));
+                self.relayout_tab(Direction::Vertical);
+                self.render();
+            }
+        }
+    }
+    pub fn vertical_split(&mut self, pid: PaneId) {
+        self.close_down_to_max_terminals();
+        if self.fullscreen_is_active {
+            self.toggle_active_pane_fullscreen();
+        }
+        if let PaneId::Terminal(term_pid) = pid {
+            // TODO: check minimum size of active terminal
+            let next_terminal_position = self.get_next_terminal_position();
+            let active_pane_id = &self.get_active_pane_id().unwrap();
+            let active_pane = self.panes.get_mut(active_pane_id).unwrap();
+            if active_pane.cols() < MIN_TERMINAL_WIDTH * 2 {
+                self.senders
+                    .send_to_pty(PtyInstruction::ClosePane(pid)) // we can't open this pane, close the pty
+                    .unwrap();
+                return;
+            }
+            let terminal_ws = active_pane.position_and_size();
+            if let Some((left_winsize, right_winsize)) = split(Direction::Vertical, &terminal_ws) {
+                let mut new_terminal =
+                    TerminalPane::new(term_pid, right_winsize, self.colors, next_terminal_position);
+                new_terminal.set_grid_options(self.pane_grid_options);
+                active_pane.set_geom(left_winsize);
+                self.panes.insert(pid, Box::new(new_terminal));
+            }
+            self.set_active_terminal(Some(pid));
+            self.relayout_tab(Direction::Horizontal);
+            self.render();
+        }
+    }
+    pub fn suppress_pane(&mut self, pid: PaneId) {
+        // the pane is sized like the active one, which is the most likely pane it will replace
+        if let PaneId::Terminal(term_pid) = pid {
+            let geom = match self.get_active_pane() {
+                Some(active_pane) => active_pane.position_and_size(),
+                None => {
+                    self.senders
+                        .send_to_pty(PtyInstruction::ClosePane(pid)) // we can't open this pane, close the pty
+                        .unwrap();
+                    return;
+                }
+            };
+            let next_terminal_position = self.get_next_terminal_position();
+            let mut new_terminal =
+                TerminalPane::new(term_pid, geom, self.colors, next_terminal_position);
+            new_terminal.set_grid_options(self.pane_grid_options);
+            if self.draw_pane_frames {
+                new_terminal.set_frame(true);
+                new_terminal.set_content_offset(Offset::frame(1));
+            }
+            self.os_api.set_terminal_size_using_fd(
+                term_pid,
+                new_terminal.get_content_columns() as u16,
+                new_terminal.get_content_rows() as u16,
+            );
+            self.suppressed_panes.insert(pid, Box::new(new_terminal));
+        }
+    }
+    pub fn show_suppressed_pane(&mut self, pid: PaneId) {
+        // the suppressed pane takes the place of the active pane, which is suppressed in turn
+        if let (Some(mut pane), Some(active_pane_id)) = (
+            self.suppressed_panes.remove(&pid),
+            self.get_active_pane_id(),
+        ) {
+            if self.fullscreen_is_active {
+                self.toggle_active_pane_fullscreen();
+            }
+            let is_in_active_stack = self
+                .stack_of(active_pane_id)
+                .map_or(false, |stack| stack.contains(&pid));
+            if !is_in_active_stack {
+                // the pane leaves its own stack and takes the place of the active pane in its one
+                self.remove_pane_from_stack(pid);
+                for stacked_pane_id in self.pane_stacks.iter_mut().flatten() {
+                    if *stacked_pane_id == active_pane_id {
+                        *stacked_pane_id = pid;
+                    }
+                }
+            }
+            let active_pane = self.panes.remove(&active_pane_id).unwrap();
+            pane.set_geom(active_pane.position_and_size());
+            pane.set_should_render(true);
+            self.panes.insert(pid, pane);
+            self.suppressed_panes.insert(active_pane_id, active_pane);
+            self.set_active_terminal(Some(pid));
+            self.set_pane_frames(self.draw_pane_frames);
+            self.set_force_render();
+            self.render();
+        }
+    }
+    pub fn has_suppressed_pane(&self, pane_id: &PaneId) -> bool {
+        self.suppressed_panes.contains_key(pane_id)
+    }
+    pub fn get_suppressed_pane_ids(&self) -> Vec<PaneId> {
+        self.suppressed_panes.keys().copied().collect()
+    }
+    pub fn get_active_pane(&self) -> Option<&dyn Pane> {
+        self.get_active_pane_id()
+            .and_then(|ap| self.panes.get(&ap).map(Box::as_ref))
+    }
+    pub fn get_active_pane_id(&self) -> Option<PaneId> {
+        self.active_terminal
+    }
+    fn get_active_terminal_id(&self) -> Option<RawFd> {
+        if let Some(PaneId::Terminal(pid)) = self.active_terminal {
+            Some(pid)
+        } else {
+            None
+        }
+    }
+    pub fn has_terminal_pid(&self, pid: RawFd) -> bool {
+        self.panes.contains_key(&PaneId::Terminal(pid))
+            || self.suppressed_panes.contains_key(&PaneId::Terminal(pid))
+    }
+    /// Whether the pane is in this tab, shown or suppressed.
+    pub fn has_pane(&self, pane_id: &PaneId) -> bool {
+        self.panes.contains_key(pane_id) || self.suppressed_panes.contains_key(pane_id)
+    }
+    /// Returns whether the pane reported a new working directory.
+    pub fn handle_pty_bytes(&mut self, pid: RawFd, bytes: VteBytes) -> bool {
+        // if we don't have the terminal in self.terminals it's probably because
+        // of a race condition where the terminal was created in pty but has not
+        // yet been created in Screen. These events are currently not buffered, so
+        // if you're debugging seemingly randomly missing stdout data, this is
+        // the reason
+        let pane_id = PaneId::Terminal(pid);
+        if self.viewed_panes.contains_key(&pane_id) {
+            // a viewer shows the output of the pane it views rather than that of its own terminal
+            return false;
+        }
+        self.mirror_pty_bytes_to_viewers(pane_id, &bytes);
+        let terminal_output = match self.panes.get_mut(&pane_id) {
+            Some(pane) => Some(pane),
+            None => self.suppressed_panes.get_mut(&pane_id),
+        };
+        if let Some(terminal_output) = terminal_output {
+            terminal_output.handle_pty_bytes(bytes);
+            let messages_to_pty = terminal_output.drain_messages_to_pty();
+            let clipboard_write = terminal_output.take_clipboard_write();
+            let cwd_change = terminal_output.take_cwd_change();
+            for message in messages_to_pty {
+                self.write_to_pane_id(message, PaneId::Terminal(pid));
+            }
+            if let Some(text) = clipboard_write {
+                self.handle_clipboard_write_from_pane(pane_id, text);
+            }
+            if let Some(cwd) = cwd_change {
+                // new panes, duplicates and dumps of the session start there rather than where
+                // the process of the shell is
+                self.senders
+                    .send_to_pty(PtyInstruction::UpdatePaneCwd(pid, cwd))
+                    .unwrap();
+                return true;
+            }
+            // self.render();
+        }
+        false
+    }
+    fn mirror_pty_bytes_to_viewers(&mut self, pane_id: PaneId, bytes: &[u8]) {
+        let viewer_ids: Vec<PaneId> = self
+            .viewed_panes
+            .iter()
+            .filter(|(_, viewed_pane_id)| **viewed_pane_id == pane_id)
+            .map(|(viewer_id, _)| *viewer_id)
+            .collect();
+        for viewer_id in viewer_ids {
+            let viewer = match self.panes.get_mut(&viewer_id) {
+                Some(viewer) => Some(viewer),
+                None => self.suppressed_panes.get_mut(&viewer_id),
+            };
+            if let Some(viewer) = viewer {
+                viewer.handle_pty_bytes(bytes.to_vec());
+                // the application answers the queries of the viewed pane, not these
+                viewer.drain_messages_to_pty();
+                viewer.take_clipboard_write();
+                viewer.take_cwd_change();
+            }
+        }
+    }
+    fn handle_clipboard_write_from_pane(&mut self, pane_id: PaneId, text: String) {
+        if text.len() > self.pane_clipboard.max_size {
+            let reason = self
+                .pane_clipboard
+                .locale
+                .text(Message::ClipboardTooLarge)
+                .replace("{bytes}", &text.len().to_string())
+                .replace("{limit}", &self.pane_clipboard.max_size.to_string());
+            self.senders
+                .send_to_server(ServerInstruction::Diagnostic(reason))
+                .unwrap();
+            return;
+        }
+        match self.pane_clipboard.policy {
+            PaneClipboardPolicy::Allow => self.write_selection_to_clipboard(&text),
+            // the prompt is shown the next time this tab renders
+            PaneClipboardPolicy::Prompt => self.pending_clipboard_write = Some((pane_id, text)),
+            PaneClipboardPolicy::Deny => {}
+        }
+    }
+    pub fn write_to_terminals_on_current_tab(&mut self, input_bytes: Vec<u8>) {
+        if self.answer_pending_paste(&input_bytes) {
+            return;
+        }
+        let pane_ids = self.get_pane_ids();
+        pane_ids.iter().for_each(|&pane_id| {
+            self.write_input_to_pane_id(input_bytes.clone(), pane_id);
+        });
+    }
+    pub fn write_to_active_terminal(&mut self, input_bytes: Vec<u8>) {
+        let active_pane_id = self.get_active_pane_id().unwrap();
+        if let Some((_, text)) = self.pending_clipboard_write.take() {
+            // the clipboard prompt is shown, the input answers it
+            if let Some(b'y') | Some(b'Y') = input_bytes.first() {
+                self.write_selection_to_clipboard(&text);
+            }
+            self.set_force_render();
+            self.render();
+            return;
+        }
+        if self.answer_pending_paste(&input_bytes) {
+            return;
+        }
+        if let Some((pane_to_link, candidates)) = self.pane_link_picker.take() {
+            // the pane link picker is shown, the input picks the pane to link with
+            let picked_pane = input_bytes.first().and_then(|&byte| {
+                candidates
+                    .iter()
+                    .find(|(label, _)| *label == byte as char)
+                    .map(|(_, pane_id)| *pane_id)
+            });
+            if let Some(picked_pane) = picked_pane {
+                self.link_panes(pane_to_link, picked_pane);
+            }
+            self.set_force_render();
+            self.render();
+            return;
+        }
+        let quick_copy_input = self
+            .panes
+            .get_mut(&active_pane_id)
+            .and_then(|pane| pane.handle_quick_copy_input(&input_bytes));
+        if let Some(quick_copy_input) = quick_copy_input {
+            // the pane is showing quick copy hints, the input picks one of them
+            match quick_copy_input {
+                QuickCopyInput::Copy(text) => self.write_selection_to_clipboard(&text),
+                QuickCopyInput::Open(link) => self
+                    .senders
+                    .send_to_server(ServerInstruction::OpenLink(link))
+                    .unwrap(),
+                QuickCopyInput::Pending | QuickCopyInput::Cancel => {}
+            }
+            self.render();
+            return;
+        }
+        self.write_input_to_pane_id(input_bytes, active_pane_id);
+    }
+    pub fn paste(&mut self, text: Vec<u8>) {
+        let text = if self.paste_options.sanitize {
+            sanitize_paste(&text)
+        } else {
+            text
+        };
+        let has_line_breaks = text.iter().any(|&byte| byte == b'\n' || byte == b'\r');
+        let receiving_panes_execute_lines = if self.synchronize_is_active {
+            self.panes.values().any(|pane| pane.executes_pasted_lines())
+        } else {
+            self.get_active_pane()
+                .map_or(false, |pane| pane.executes_pasted_lines())
+        };
+        if self.paste_options.confirm_multiline && has_line_breaks && receiving_panes_execute_lines
+        {
+            // the confirmation is shown the next time this tab renders
+            self.pending_paste = Some(text);
+            self.render();
+            return;
+        }
+        self.write_paste(text);
+    }
+    // when the paste confirmation is shown, the input answers it
+    fn answer_pending_paste(&mut self, input_bytes: &[u8]) -> bool {
+        match self.pending_paste.take() {
+            Some(text) => {
+                if let Some(b'y') | Some(b'Y') = input_bytes.first() {
+                    self.write_paste(text);
+                }
+                self.set_force_render();
+                self.render();
+                true
+            }
+            None => false,
+        }
+    }
+    fn write_paste(&mut self, text: Vec<u8>) {
+        let pane_ids = if self.synchronize_is_active {
+            self.get_pane_ids()
+        } else {
+            self.get_active_pane_id().into_iter().collect()
+        };
+        for pane_id in pane_ids {
+            // the bracketed paste sequences are sent apart from the text, so that panes that
+            // don't ask for them can leave them out
+            self.write_input_to_pane_id(BRACKETED_PASTE_START.to_vec(), pane_id);
+            self.write_input_to_pane_id(text.clone(), pane_id);
+            self.write_input_to_pane_id(BRACKETED_PASTE_END.to_vec(), pane_id);
+        }
+    }
+    pub fn start_quick_copy(&mut self, patterns: &[Regex]) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.start_quick_copy(patterns);
+            }
+        }
+        self.render();
+    }
+    pub fn start_link_hints(&mut self, patterns: &[Regex]) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.start_link_hints(patterns);
+            }
+        }
+        self.render();
+    }
+    pub fn toggle_active_pane_read_only(&mut self) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                let read_only = active_pane.read_only();
+                active_pane.set_read_only(!read_only);
+                self.render();
+            }
+        }
+    }
+    // the name shown in the tab bar - tabs that weren't named go by the title of their active pane
+    pub fn display_name(&self) -> String {
+        if self.name == default_tab_name(self.index) {
+            if let Some(title) = self.get_active_pane().and_then(|pane| pane.custom_title()) {
+                return title;
+            }
+        }
+        self.name.clone()
+    }
+    pub fn update_active_pane_name(&mut self, buf: Vec<u8>) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.update_name(&String::from_utf8_lossy(&buf));
+            }
+        }
+    }
+    pub fn update_active_pane_search(&mut self, buf: Vec<u8>) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.update_search(&String::from_utf8_lossy(&buf));
+            }
+        }
+    }
+    pub fn search_active_pane(&mut self, direction: SearchDirection) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.search(direction);
+            }
+        }
+    }
+    pub fn toggle_active_pane_search_option(&mut self, option: SearchOption) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.toggle_search_option(option);
+            }
+        }
+    }
+    // the search only lasts as long as the search mode, wherever the focus went meanwhile
+    pub fn end_search(&mut self) {
+        for pane in self.panes.values_mut() {
+            pane.end_search();
+        }
+    }
+    /// Types into the text searched for in all the panes of this tab, listing the matches
+    /// pane by pane. A `0` clears the text.
+    pub fn update_tab_search(&mut self, buf: Vec<u8>) {
+        let mut tab_search = self.tab_search.take().unwrap_or_default();
+        tab_search
+            .search
+            .update_needle(&String::from_utf8_lossy(&buf));
+        let search = &tab_search.search;
+        tab_search.results = self
+            .panes
+            .iter()
+            .filter(|(pane_id, _)| !self.panes_to_hide.contains(*pane_id))
+            .flat_map(|(&pane_id, pane)| {
+                let pane_title = pane.title();
+                pane.search_matches(search)
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(index, line)| TabSearchResult {
+                        pane_id,
+                        pane_title: pane_title.clone(),
+                        index,
+                        line,
+                    })
+            })
+            .collect();
+        tab_search.selected = 0;
+        self.tab_search = Some(tab_search);
+        self.set_force_render();
+    }
+    pub fn move_tab_search_selection(&mut self, direction: SearchDirection) {
+        if let Some(tab_search) = self.tab_search.as_mut() {
+            tab_search.move_selection(direction);
+            self.set_force_render();
+        }
+    }
+    /// Focuses the pane of the selected match of the tab search and scrolls to the match, which
+    /// the pane highlights as if it was searched on its own.
+    pub fn jump_to_tab_search_result(&mut self) {
+        let tab_search = match self.tab_search.take() {
+            Some(tab_search) => tab_search,
+            None => return,
+        };
+        if let Some(result) = tab_search.selected_result() {
+            if let Some(pane) = self.panes.get_mut(&result.pane_id) {
+                pane.show_search_match(&tab_search.search, result.index);
+            }
+            self.focus_pane(result.pane_id);
+        }
+        self.set_force_render();
+    }
+    pub fn end_tab_search(&mut self) {
+        if self.tab_search.take().is_some() {
+            self.set_force_render();
+        }
+    }
+    /// The commands run in the panes of this tab, as their shells marked them.
+    pub fn command_history(&self) -> Vec<HistoryItem> {
+        self.panes
+            .iter()
+            .chain(self.suppressed_panes.iter())
+            .flat_map(|(&pane_id, pane)| {
+                let pane_title = pane.title();
+                pane.command_history()
+                    .into_iter()
+                    .map(move |entry| HistoryItem {
+                        pane_id,
+                        pane_title: pane_title.clone(),
+                        entry,
+                    })
+            })
+            .collect()
+    }
+    /// Types into the filter of the command history picker shown over this tab, which picks
+    /// one of `items`. A `0` clears the filter.
+    pub fn update_command_history(&mut self, buf: Vec<u8>, items: Vec<HistoryItem>) {
+        let mut command_history = self.command_history_picker.take().unwrap_or_default();
+        command_history.set_items(items);
+        command_history.update_filter(&String::from_utf8_lossy(&buf));
+        self.command_history_picker = Some(command_history);
+        self.set_force_render();
+    }
+    pub fn move_command_history_selection(&mut self, direction: SearchDirection) {
+        if let Some(command_history) = self.command_history_picker.as_mut() {
+            command_history.move_selection(direction);
+            self.set_force_render();
+        }
+    }
+    /// Closes the command history picker, returning the command it had selected.
+    pub fn take_command_history_selection(&mut self) -> Option<HistoryItem> {
+        let command_history = self.command_history_picker.take()?;
+        self.set_force_render();
+        command_history.selected_item().cloned()
+    }
+    pub fn end_command_history(&mut self) {
+        if self.command_history_picker.take().is_some() {
+            self.set_force_render();
+        }
+    }
+    /// Types into the session manager shown over this tab, opening it if need be, and returns
+    /// whether it was just opened (and has yet to be given the sessions).
+    pub(crate) fn update_session_manager(&mut self, input: &str) -> bool {
+        let opened = self.session_manager.is_none();
+        self.session_manager
+            .get_or_insert_with(SessionManager::default)
+            .update_input(input);
+        self.set_force_render();
+        opened
+    }
+    pub(crate) fn set_sessions(&mut self, sessions: Vec<SessionSummary>) {
+        if let Some(session_manager) = self.session_manager.as_mut() {
+            session_manager.set_sessions(sessions);
+            self.set_force_render();
+        }
+    }
+    pub(crate) fn session_manager_mut(&mut self) -> Option<&mut SessionManager> {
+        self.set_force_render();
+        self.session_manager.as_mut()
+    }
+    pub fn end_session_manager(&mut self) {
+        if self.session_manager.take().is_some() {
+            self.set_force_render();
+        }
+    }
+    pub fn start_copy_mode(&mut self) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.start_copy_mode();
+            }
+        }
+    }
+    pub fn end_copy_mode(&mut self) {
+        for pane in self.panes.values_mut() {
+            pane.end_copy_mode();
+        }
+    }
+    pub fn move_active_pane_copy_cursor(&mut self, motion: CopyMotion) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.move_copy_cursor(motion);
+            }
+        }
+    }
+    pub fn toggle_active_pane_copy_selection(&mut self, kind: SelectionKind) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.toggle_copy_selection(kind);
+            }
+        }
+    }
+    pub fn yank_active_pane_copy_selection(&mut self) {
+        let selected_text = self
+            .get_active_pane_id()
+            .and_then(|active_pane_id| self.panes.get_mut(&active_pane_id))
+            .and_then(|active_pane| active_pane.yank_copy_selection());
+        if let Some(selected_text) = selected_text {
+            self.write_selection_to_clipboard(&selected_text);
+        }
+    }
+    pub fn set_active_pane_palette(&mut self, palette: Option<PaletteOverride>) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.set_palette_override(palette);
+                self.render();
+            }
+        }
+    }
+    pub fn set_active_pane_ambiguous_width(&mut self, ambiguous_width: Option<AmbiguousWidth>) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.set_ambiguous_width(ambiguous_width);
+            }
+        }
+    }
+    pub fn toggle_active_pane_pinned(&mut self) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                let pinned = active_pane.pinned();
+                active_pane.set_pinned(!pinned);
+                self.render();
+            }
+        }
+    }
+    // the active pane goes fullscreen and is drawn at double size, or back to normal
+    pub fn toggle_active_pane_presentation(&mut self) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            let presentation = !self.panes[&active_pane_id].presentation();
+            if presentation && !self.fullscreen_is_active {
+                self.toggle_active_pane_fullscreen();
+            } else if !presentation && self.fullscreen_is_active {
+                // leaving fullscreen ends the presentation as well
+                self.toggle_active_pane_fullscreen();
+            }
+            if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
+                active_pane.set_presentation(presentation);
+            }
+            // clearing the display also makes its double size lines normal again
+            self.set_pane_frames(self.draw_pane_frames);
+            self.set_force_render();
+            self.render();
+        }
+    }
+    pub fn toggle_active_pane_stacking(&mut self) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            if self.fullscreen_is_active {
+                self.toggle_active_pane_fullscreen();
+            }
+            if self.stack_of(active_pane_id).is_some() {
+                self.unstack_pane(active_pane_id);
+            } else {
+                for (neighbour_id, _) in self.sole_neighbours(active_pane_id) {
+                    if self.stack_pane_onto(active_pane_id, neighbour_id) {
+                        break;
+                    }
+                }
+            }
+            self.set_pane_frames(self.draw_pane_frames);
+            self.set_force_render();
+            self.render();
+        }
     }
-    pub fn horizontal_split(&mut self, pid: PaneId) {
-        self.close_down_to_max_terminals();
-        if self.fullscreen_is_active {
-            self.toggle_active_pane_fullscreen();
+    fn stack_of(&self, pane_id: PaneId) -> Option<&[PaneId]> {
+        self.pane_stacks
+            .iter()
+            .find(|stack| stack.contains(&pane_id))
+            .map(Vec::as_slice)
+    }
+    // the panes collapsed into title bars above the contents of this one, if it is shown in a
+    // stack
+    fn collapsed_pane_ids(&self, pane_id: PaneId) -> Vec<PaneId> {
+        match self.stack_of(pane_id) {
+            Some(stack) if self.panes.contains_key(&pane_id) => stack
+                .iter()
+                .copied()
+                .filter(|&stacked_pane_id| stacked_pane_id != pane_id)
+                .collect(),
+            _ => vec![],
         }
-        if let PaneId::Terminal(term_pid) = pid {
-            let next_terminal_position = self.get_next_terminal_position();
-            let active_pane_id = &self.get_active_pane_id().unwrap();
-            let active_pane = self.panes.get_mut(active_pane_id).unwrap();
-            if active_pane.rows() < MIN_TERMINAL_HEIGHT * 2 {
-                self.senders
-                    .send_to_pty(PtyInstruction::ClosePane(pid)) // we can't open this pane, close the pty
-                    .unwrap();
-                return;
-            }
-            let terminal_ws = active_pane.position_and_size();
-            if let Some((top_winsize, bottom_winsize)) = split(Direction::Horizontal, &terminal_ws)
-            {
-                let new_terminal = TerminalPane::new(
-                    term_pid,
-                    bottom_winsize,
-                    self.colors,
-                    next_terminal_position,
-                );
-                active_pane.set_geom(top_winsize);
-                self.panes.insert(pid, Box::new(new_terminal));
-                self.set_active_terminal(Some(pid));
-                self.relayout_tab(Direction::Vertical);
-                self.render();
+    }
+    // the panes that would each take up all the space of this one if it left the layout
+    fn sole_neighbours(&self, pane_id: PaneId) -> Vec<(PaneId, Direction)> {
+        let neighbours = [
+            (
+                self.panes_below_between_aligning_borders(pane_id),
+                Direction::Vertical,
+            ),
+            (
+                self.panes_above_between_aligning_borders(pane_id),
+                Direction::Vertical,
+            ),
+            (
+                self.panes_to_the_right_between_aligning_borders(pane_id),
+                Direction::Horizontal,
+            ),
+            (
+                self.panes_to_the_left_between_aligning_borders(pane_id),
+                Direction::Horizontal,
+            ),
+        ];
+        neighbours
+            .iter()
+            .filter_map(|(pane_ids, direction)| match pane_ids.as_deref() {
+                Some(&[neighbour_id]) => Some((neighbour_id, *direction)),
+                _ => None,
+            })
+            .collect()
+    }
+    // the pane leaves the layout, its neighbour grows into its space and the two of them share
+    // that space as a stack, with the pane shown
+    fn stack_pane_onto(&mut self, pane_id: PaneId, neighbour_id: PaneId) -> bool {
+        let direction = match self
+            .sole_neighbours(pane_id)
+            .into_iter()
+            .find(|(sole_neighbour_id, _)| *sole_neighbour_id == neighbour_id)
+        {
+            Some((_, direction)) => direction,
+            None => return false,
+        };
+        let pane = &self.panes[&pane_id];
+        let neighbour = &self.panes[&neighbour_id];
+        let pane_comes_first = pane.y() < neighbour.y() || pane.x() < neighbour.x();
+        let freed_space = pane.position_and_size();
+        let (freed_percent, stack_rows) = match direction {
+            Direction::Vertical => (
+                freed_space.rows.as_percent(),
+                pane.rows() + neighbour.rows(),
+            ),
+            Direction::Horizontal => (freed_space.cols.as_percent(), neighbour.rows()),
+        };
+        let collapsed_panes = self.stack_of(neighbour_id).map_or(1, |stack| stack.len());
+        // leave room for the title bars, the frame and at least one row of content
+        if stack_rows < collapsed_panes + 3 {
+            return false;
+        }
+        let freed_percent = match freed_percent {
+            Some(freed_percent) => freed_percent,
+            None => return false,
+        };
+        match direction {
+            Direction::Vertical => self.increase_pane_height(&neighbour_id, freed_percent),
+            Direction::Horizontal => self.increase_pane_width(&neighbour_id, freed_percent),
+        }
+        let mut pane = self.panes.remove(&pane_id).unwrap();
+        self.relayout_tab(direction);
+        let neighbour = self.panes.remove(&neighbour_id).unwrap();
+        pane.set_geom(neighbour.position_and_size());
+        pane.set_should_render(true);
+        self.panes.insert(pane_id, pane);
+        self.suppressed_panes.insert(neighbour_id, neighbour);
+        match self
+            .pane_stacks
+            .iter_mut()
+            .find(|stack| stack.contains(&neighbour_id))
+        {
+            Some(stack) => {
+                let position = stack.iter().position(|&id| id == neighbour_id).unwrap();
+                let position = if pane_comes_first {
+                    position
+                } else {
+                    position + 1
+                };
+                stack.insert(position, pane_id);
             }
+            None if pane_comes_first => self.pane_stacks.push(vec![pane_id, neighbour_id]),
+            None => self.pane_stacks.push(vec![neighbour_id, pane_id]),
         }
+        true
     }
-    pub fn vertical_split(&mut self, pid: PaneId) {
-        self.close_down_to_max_terminals();
-        if self.fullscreen_is_active {
-            self.toggle_active_pane_fullscreen();
+    // the pane leaves the layout for the end of the stack of the shown one, along with the
+    // panes of its own stack
+    fn add_to_stack(&mut self, pane_id: PaneId, shown_pane_id: PaneId) {
+        if let Some(pane) = self.panes.remove(&pane_id) {
+            self.suppressed_panes.insert(pane_id, pane);
         }
-        if let PaneId::Terminal(term_pid) = pid {
-            // TODO: check minimum size of active terminal
-            let next_terminal_position = self.get_next_terminal_position();
-            let active_pane_id = &self.get_active_pane_id().unwrap();
-            let active_pane = self.panes.get_mut(active_pane_id).unwrap();
-            if active_pane.cols() < MIN_TERMINAL_WIDTH * 2 {
-                self.senders
-                    .send_to_pty(PtyInstruction::ClosePane(pid)) // we can't open this pane, close the pty
-                    .unwrap();
-                return;
-            }
-            let terminal_ws = active_pane.position_and_size();
-            if let Some((left_winsize, right_winsize)) = split(Direction::Vertical, &terminal_ws) {
-                let new_terminal =
-                    TerminalPane::new(term_pid, right_winsize, self.colors, next_terminal_position);
-                active_pane.set_geom(left_winsize);
-                self.panes.insert(pid, Box::new(new_terminal));
+        let mut stacked_pane_ids = match self
+            .pane_stacks
+            .iter()
+            .position(|stack| stack.contains(&pane_id))
+        {
+            Some(stack_index) => self.pane_stacks.remove(stack_index),
+            None => vec![pane_id],
+        };
+        match self
+            .pane_stacks
+            .iter_mut()
+            .find(|stack| stack.contains(&shown_pane_id))
+        {
+            Some(stack) => stack.append(&mut stacked_pane_ids),
+            None => {
+                stacked_pane_ids.insert(0, shown_pane_id);
+                self.pane_stacks.push(stacked_pane_ids);
             }
-            self.set_active_terminal(Some(pid));
-            self.relayout_tab(Direction::Horizontal);
-            self.render();
         }
     }
-    pub fn get_active_pane(&self) -> Option<&dyn Pane> {
-        self.get_active_pane_id()
-            .and_then(|ap| self.panes.get(&ap).map(Box::as_ref))
+    // the pane leaves its stack and gets half of its space back
+    fn unstack_pane(&mut self, pane_id: PaneId) {
+        let geom = match self.panes.get(&pane_id) {
+            Some(pane) if pane.rows() >= MIN_TERMINAL_HEIGHT * 2 => pane.position_and_size(),
+            _ => return,
+        };
+        let (top_geom, bottom_geom) = match split(Direction::Horizontal, &geom) {
+            Some(halves) => halves,
+            None => return,
+        };
+        let pane_comes_first =
+            self.stack_of(pane_id).and_then(|stack| stack.last()) != Some(&pane_id);
+        if let Some(replacement_id) = self.remove_pane_from_stack(pane_id) {
+            let (pane_geom, replacement_geom) = if pane_comes_first {
+                (top_geom, bottom_geom)
+            } else {
+                (bottom_geom, top_geom)
+            };
+            self.panes.get_mut(&pane_id).unwrap().set_geom(pane_geom);
+            self.panes
+                .get_mut(&replacement_id)
+                .unwrap()
+                .set_geom(replacement_geom);
+            self.relayout_tab(Direction::Vertical);
+        }
     }
-    fn get_active_pane_id(&self) -> Option<PaneId> {
-        self.active_terminal
+    // if the pane was the one shown in its stack, another pane of the stack is shown in its place
+    // and returned - the pane itself is left where it is
+    fn remove_pane_from_stack(&mut self, pane_id: PaneId) -> Option<PaneId> {
+        let stack_index = self
+            .pane_stacks
+            .iter()
+            .position(|stack| stack.contains(&pane_id))?;
+        let stack = &mut self.pane_stacks[stack_index];
+        let position = stack.iter().position(|&id| id == pane_id).unwrap();
+        stack.remove(position);
+        let replacement_id = stack[position.min(stack.len() - 1)];
+        if stack.len() == 1 {
+            self.pane_stacks.remove(stack_index);
+        }
+        let geom = self.panes.get(&pane_id)?.position_and_size();
+        let mut replacement = self.suppressed_panes.remove(&replacement_id)?;
+        replacement.set_geom(geom);
+        replacement.set_should_render(true);
+        self.panes.insert(replacement_id, replacement);
+        Some(replacement_id)
+    }
+    // shows the previous or next pane in the stack of the active one, if there is one
+    fn move_focus_within_stack(&mut self, forward: bool) -> bool {
+        let active_pane_id = match self.get_active_pane_id() {
+            Some(active_pane_id) => active_pane_id,
+            None => return false,
+        };
+        let stacked_pane_id = self.stack_of(active_pane_id).and_then(|stack| {
+            let position = stack.iter().position(|&id| id == active_pane_id)?;
+            let stacked_pane_position = if forward {
+                position + 1
+            } else {
+                position.checked_sub(1)?
+            };
+            stack.get(stacked_pane_position).copied()
+        });
+        match stacked_pane_id {
+            Some(stacked_pane_id) => {
+                self.show_suppressed_pane(stacked_pane_id);
+                true
+            }
+            None => false,
+        }
     }
-    fn get_active_terminal_id(&self) -> Option<RawFd> {
-        if let Some(PaneId::Terminal(pid)) = self.active_terminal {
-            Some(pid)
-        } else {
-            None
+    // the collapsed pane whose title bar is at this point, if any
+    fn collapsed_pane_id_at(&self, point: &Position) -> Option<PaneId> {
+        let pane = self.panes.get(&self.get_pane_id_at(point)?)?;
+        let collapsed_pane_ids = self.collapsed_pane_ids(pane.pid());
+        let first_title_bar_row = pane
+            .get_content_y()
+            .saturating_sub(collapsed_pane_ids.len());
+        let row = point.line.0 - first_title_bar_row as isize;
+        if row < 0 {
+            return None;
         }
+        collapsed_pane_ids.get(row as usize).copied()
     }
-    pub fn has_terminal_pid(&self, pid: RawFd) -> bool {
-        self.panes.contains_key(&PaneId::Terminal(pid))
+    pub fn toggle_active_pane_link(&mut self) {
+        let active_pane_id = match self.get_active_pane_id() {
+            Some(active_pane_id) => active_pane_id,
+            None => return,
+        };
+        if self.unlink_pane(active_pane_id) {
+            return;
+        }
+        let candidates: Vec<PaneId> = self
+            .panes
+            .iter()
+            .filter(|(pane_id, pane)| {
+                matches!(pane_id, PaneId::Terminal(_))
+                    && **pane_id != active_pane_id
+                    && pane.selectable()
+                    && !self.panes_to_hide.contains(pane_id)
+            })
+            .map(|(pane_id, _)| *pane_id)
+            .collect();
+        match candidates.as_slice() {
+            [] => {}
+            [only_candidate] => self.link_panes(active_pane_id, *only_candidate),
+            _ => {
+                let labeled_candidates = PANE_LINK_LABELS.chars().zip(candidates).collect();
+                self.pane_link_picker = Some((active_pane_id, labeled_candidates));
+                self.render();
+            }
+        }
     }
-    pub fn handle_pty_bytes(&mut self, pid: RawFd, bytes: VteBytes) {
-        // if we don't have the terminal in self.terminals it's probably because
-        // of a race condition where the terminal was created in pty but has not
-        // yet been created in Screen. These events are currently not buffered, so
-        // if you're debugging seemingly randomly missing stdout data, this is
-        // the reason
-        if let Some(terminal_output) = self.panes.get_mut(&PaneId::Terminal(pid)) {
-            terminal_output.handle_pty_bytes(bytes);
-            let messages_to_pty = terminal_output.drain_messages_to_pty();
-            for message in messages_to_pty {
-                self.write_to_pane_id(message, PaneId::Terminal(pid));
+    fn link_panes(&mut self, first: PaneId, second: PaneId) {
+        self.unlink_pane(first);
+        self.unlink_pane(second);
+        self.linked_panes.insert(first, second);
+        self.linked_panes.insert(second, first);
+    }
+    /// Returns whether the pane was linked.
+    fn unlink_pane(&mut self, pane_id: PaneId) -> bool {
+        match self.linked_panes.remove(&pane_id) {
+            Some(linked_pane_id) => {
+                self.linked_panes.remove(&linked_pane_id);
+                true
             }
-            // self.render();
+            None => false,
         }
     }
-    pub fn write_to_terminals_on_current_tab(&mut self, input_bytes: Vec<u8>) {
-        let pane_ids = self.get_pane_ids();
-        pane_ids.iter().for_each(|&pane_id| {
-            self.write_to_pane_id(input_bytes.clone(), pane_id);
-        });
+    fn get_linked_pane_mut(&mut self, pane_id: PaneId) -> Option<&mut Box<dyn Pane>> {
+        let linked_pane_id = *self.linked_panes.get(&pane_id)?;
+        self.panes.get_mut(&linked_pane_id)
     }
-    pub fn write_to_active_terminal(&mut self, input_bytes: Vec<u8>) {
-        self.write_to_pane_id(input_bytes, self.get_active_pane_id().unwrap());
+    /// Like [`Self::write_to_pane_id`], but for input from the user, which read only panes ignore.
+    pub fn write_input_to_pane_id(&mut self, input_bytes: Vec<u8>, pane_id: PaneId) {
+        let read_only = self
+            .panes
+            .get(&pane_id)
+            .or_else(|| self.suppressed_panes.get(&pane_id))
+            .map_or(false, |pane| pane.read_only());
+        if !read_only {
+            self.write_to_pane_id(input_bytes, pane_id);
+        }
     }
     pub fn write_to_pane_id(&mut self, input_bytes: Vec<u8>, pane_id: PaneId) {
         match pane_id {
             PaneId::Terminal(active_terminal_id) => {
-                let active_terminal = self.panes.get(&pane_id).unwrap();
+                let active_terminal = self
+                    .panes
+                    .get(&pane_id)
+                    .or_else(|| self.suppressed_panes.get(&pane_id))
+                    .unwrap();
                 let adjusted_input = active_terminal.adjust_input_to_terminal(input_bytes);
                 self.os_api
                     .write_to_tty_stdin(active_terminal_id, &adjusted_input)
@@ -598,17 +2229,83 @@ impl Tab {
             }
         }
     }
-    pub fn get_active_terminal_cursor_position(&self) -> Option<(usize, usize)> {
+    fn cursor_position_in(&self, pane_id: PaneId) -> Option<(usize, usize)> {
         // (x, y)
-        let active_terminal = &self.get_active_pane()?;
-        active_terminal
-            .cursor_coordinates()
+        let pane = self.panes.get(&pane_id)?;
+        pane.cursor_coordinates()
             .map(|(x_in_terminal, y_in_terminal)| {
-                let x = active_terminal.x() + x_in_terminal;
-                let y = active_terminal.y() + y_in_terminal;
+                let x = pane.x() + x_in_terminal;
+                let y = pane.y() + y_in_terminal;
                 (x, y)
             })
     }
+    /// Shows the cursor where it is in the pane, or hides it.
+    fn cursor_output(&self, pane_id: PaneId) -> String {
+        match (self.panes.get(&pane_id), self.cursor_position_in(pane_id)) {
+            (Some(pane), Some((cursor_position_x, cursor_position_y))) => {
+                let show_cursor = "\u{1b}[?25h";
+                format!(
+                    "{}\u{1b}[{};{}H\u{1b}[m{}",
+                    show_cursor,
+                    cursor_position_y + 1,
+                    cursor_position_x + 1,
+                    pane.cursor_shape_csi()
+                ) // goto row/col
+            }
+            _ => {
+                let hide_cursor = "\u{1b}[?25l";
+                hide_cursor.to_string()
+            }
+        }
+    }
+    /// Sets the clients viewing this tab while several are attached to the session, `None` to
+    /// draw it for every client. The tab is drawn again in full when the clients change.
+    pub fn set_clients(&mut self, clients: Option<Vec<TabClient>>) {
+        let ids = |clients: &Option<Vec<TabClient>>| {
+            clients
+                .as_ref()
+                .map(|clients| clients.iter().map(|client| client.id).collect::<Vec<_>>())
+        };
+        if ids(&clients) != ids(&self.clients) {
+            self.set_force_render();
+        }
+        self.clients = clients;
+    }
+    /// Makes the pane the active one again, as it was in the focus of the client the screen
+    /// switched to, unless it is gone or hidden since.
+    pub fn restore_active_pane(&mut self, pane_id: PaneId) {
+        let selectable = self
+            .panes
+            .get(&pane_id)
+            .map_or(false, |pane| pane.selectable());
+        if selectable && !self.panes_to_hide.contains(&pane_id) {
+            self.active_terminal = Some(pane_id);
+        }
+    }
+    // the pane a client viewing the tab focuses
+    fn client_pane(&self, client: &TabClient) -> Option<PaneId> {
+        client
+            .active_pane
+            .filter(|pane_id| {
+                self.panes.contains_key(pane_id) && !self.panes_to_hide.contains(pane_id)
+            })
+            .or(self.active_terminal)
+    }
+    /// The colour of a client, that of the mode for the first client to attach.
+    fn client_color(&self, slot: usize) -> PaletteColor {
+        let colors = [
+            self.colors.magenta,
+            self.colors.cyan,
+            self.colors.blue,
+            self.colors.yellow,
+            self.colors.red,
+        ];
+        match (slot, self.mode_info.mode) {
+            (0, InputMode::Normal) | (0, InputMode::Locked) => self.colors.green,
+            (0, _) => self.colors.orange,
+            (slot, _) => colors[(slot - 1) % colors.len()],
+        }
+    }
     pub fn toggle_active_pane_fullscreen(&mut self) {
         if let Some(active_pane_id) = self.get_active_pane_id() {
             if self.fullscreen_is_active {
@@ -617,18 +2314,20 @@ impl Tab {
                     pane.set_should_render(true);
                     pane.set_should_render_boundaries(true);
                 }
-                let viewport_pane_ids: Vec<_> = self
-                    .get_pane_ids()
-                    .into_iter()
-                    .filter(|id| !self.is_inside_viewport(id))
-                    .collect();
-                for pid in viewport_pane_ids {
-                    let viewport_pane = self.panes.get_mut(&pid).unwrap();
-                    viewport_pane.reset_size_and_position_override();
+                // the viewport covers the ui panes while the ui is hidden, and they may have
+                // been hidden or shown since going fullscreen
+                for pane in self.panes.values_mut() {
+                    pane.reset_size_and_position_override();
                 }
                 self.panes_to_hide.clear();
                 let active_terminal = self.panes.get_mut(&active_pane_id).unwrap();
+                if active_terminal.presentation() {
+                    active_terminal.set_presentation(false);
+                    // clearing the display also makes its double size lines normal again
+                    self.should_clear_display_before_rendering = true;
+                }
                 active_terminal.reset_size_and_position_override();
+                active_terminal.set_fullscreen(false);
             } else {
                 let panes = self.get_panes();
                 let pane_ids_to_hide = panes.filter_map(|(&id, _pane)| {
@@ -643,6 +2342,12 @@ impl Tab {
                     // nothing to do, pane is already as fullscreen as it can be, let's bail
                     return;
                 } else {
+                    let geoms = self
+                        .panes
+                        .iter()
+                        .map(|(&pane_id, pane)| (pane_id, pane.position_and_size()))
+                        .collect();
+                    self.geoms_before_fullscreen = Some((self.display_area, geoms));
                     // For all of the panes outside of the viewport staying on the fullscreen
                     // screen, switch them to using override positions as well so that the resize
                     // system doesn't get confused by viewport and old panes that no longer line up
@@ -662,14 +2367,44 @@ impl Tab {
                         ..Default::default()
                     };
                     active_terminal.get_geom_override(full_screen_geom);
+                    active_terminal.set_fullscreen(true);
                 }
             }
             self.set_force_render();
-            self.resize_whole_tab(self.display_area);
+            let restored = self.fullscreen_is_active && self.restore_geoms_before_fullscreen();
+            if !restored {
+                self.resize_whole_tab(self.display_area);
+            }
             self.render();
             self.toggle_fullscreen_is_active();
         }
     }
+    // puts the panes back exactly where they were before going fullscreen, as long as the tab
+    // wasn't resized and kept the same panes in the meantime
+    fn restore_geoms_before_fullscreen(&mut self) -> bool {
+        match self.geoms_before_fullscreen.take() {
+            Some((display_area, geoms))
+                if display_area == self.display_area
+                    && geoms.len() == self.panes.len()
+                    && geoms.keys().all(|pane_id| self.panes.contains_key(pane_id)) =>
+            {
+                for (pane_id, geom) in geoms {
+                    self.panes.get_mut(&pane_id).unwrap().set_geom(geom);
+                }
+                self.set_pane_frames(self.draw_pane_frames);
+                true
+            }
+            _ => false,
+        }
+    }
+    // the focus can't move away from a fullscreen pane, unless the tab leaves fullscreen for it -
+    // returns whether the focus can move
+    fn exit_fullscreen_for_focus_change(&mut self) -> bool {
+        if self.fullscreen_is_active && self.exit_fullscreen_on_focus_change {
+            self.toggle_active_pane_fullscreen();
+        }
+        !self.fullscreen_is_active
+    }
     pub fn toggle_fullscreen_is_active(&mut self) {
         self.fullscreen_is_active = !self.fullscreen_is_active;
     }
@@ -694,27 +2429,123 @@ impl Tab {
             active_terminal.set_should_render(true)
         }
     }
+    // the clipboard prompt stays, as it is waiting for an answer from the keyboard
+    pub fn set_clean_screen(&mut self, clean_screen: bool) {
+        self.clean_screen = clean_screen;
+        self.set_pane_frames(self.draw_pane_frames);
+        self.set_force_render();
+    }
+    /// Hides the panes around the viewport (eg. the tab and status bars) and lets the other
+    /// panes take their rows, or lays them out again.
+    pub fn set_ui_hidden(&mut self, ui_hidden: bool) {
+        if ui_hidden == self.ui_hidden {
+            return;
+        }
+        self.ui_hidden = ui_hidden;
+        // the geoms kept for leaving fullscreen have the ui panes in them, or not
+        self.geoms_before_fullscreen = None;
+        if ui_hidden {
+            self.hide_ui_panes();
+        } else {
+            self.show_ui_panes();
+        }
+        self.set_force_render();
+    }
+    // takes the panes around the viewport out of the layout, the other panes taking up the whole
+    // display
+    fn hide_ui_panes(&mut self) {
+        let viewport = self.viewport;
+        self.hidden_ui_panes = self
+            .panes
+            .iter()
+            .filter(|(_, pane)| !is_inside_viewport(&viewport, pane))
+            .map(|(&pane_id, _)| pane_id)
+            .collect();
+        self.ui_margins = Offset {
+            top: viewport.y,
+            bottom: self
+                .display_area
+                .rows
+                .saturating_sub(viewport.y + viewport.rows),
+            left: viewport.x,
+            right: self
+                .display_area
+                .cols
+                .saturating_sub(viewport.x + viewport.cols),
+        };
+        self.viewport = self.display_area.into();
+        self.resize_whole_tab(self.display_area);
+    }
+    // puts the panes around the viewport back, the other panes moving out of their way first so
+    // that none of them overlap when the tab is laid out again
+    fn show_ui_panes(&mut self) {
+        let margins = self.ui_margins;
+        let Size { rows, cols } = self.display_area;
+        let rows_left = rows.saturating_sub(margins.top + margins.bottom);
+        let cols_left = cols.saturating_sub(margins.left + margins.right);
+        let fit = |position: usize, space: usize, space_left: usize, margin: usize| {
+            margin + position * space_left / space.max(1)
+        };
+        let hidden_ui_panes = &self.hidden_ui_panes;
+        for (_, pane) in self
+            .panes
+            .iter_mut()
+            .filter(|(pane_id, _)| !hidden_ui_panes.contains(pane_id))
+        {
+            let geom = pane.position_and_size();
+            let (x, y) = (
+                fit(geom.x, cols, cols_left, margins.left),
+                fit(geom.y, rows, rows_left, margins.top),
+            );
+            let mut fitted_geom = PaneGeom { x, y, ..geom };
+            fitted_geom
+                .cols
+                .set_inner(fit(geom.x + geom.cols.as_usize(), cols, cols_left, margins.left) - x);
+            fitted_geom
+                .rows
+                .set_inner(fit(geom.y + geom.rows.as_usize(), rows, rows_left, margins.top) - y);
+            pane.set_geom(fitted_geom);
+        }
+        self.hidden_ui_panes.clear();
+        // crops the viewport to the ui panes again
+        self.resize_to_layout();
+    }
     pub fn set_pane_frames(&mut self, draw_pane_frames: bool) {
         self.draw_pane_frames = draw_pane_frames;
         self.should_clear_display_before_rendering = true;
+        let single_pane = self.get_selectable_panes().count() == 1;
+        let draw_pane_frames = draw_pane_frames
+            && !self.clean_screen
+            && !(self.pane_frame_options.hide_single && single_pane);
         let viewport = self.viewport;
+        let title_bar_rows: HashMap<PaneId, usize> = self
+            .panes
+            .keys()
+            .map(|&pane_id| (pane_id, self.collapsed_pane_ids(pane_id).len()))
+            .collect();
         for (pane_id, pane) in self.panes.iter_mut() {
             if !pane.borderless() {
                 pane.set_frame(draw_pane_frames);
             }
+            let frame_style = self
+                .pane_frame_styles
+                .get(pane_id)
+                .copied()
+                .unwrap_or(self.pane_frame_options.style);
+            pane.set_frame_style(frame_style);
 
             #[allow(clippy::if_same_then_else)]
-            if draw_pane_frames & !pane.borderless() {
+            let mut content_offset = if draw_pane_frames & !pane.borderless() {
                 // there's definitely a frame around this pane, offset its contents
-                pane.set_content_offset(Offset::frame(1));
+                Offset::frame(1)
             } else if draw_pane_frames && pane.borderless() {
                 // there's no frame around this pane, and the tab isn't handling the boundaries
                 // between panes (they each draw their own frames as they please)
                 // this one doesn't - do not offset its content
-                pane.set_content_offset(Offset::default());
+                Offset::default()
             } else if !is_inside_viewport(&viewport, pane) {
                 // this pane is outside the viewport and has no border - it should not have an offset
-                pane.set_content_offset(Offset::default());
+                Offset::default()
             } else {
                 // no draw_pane_frames and this pane should have a separation to other panes
                 // according to its position in the viewport (eg. no separation if its at the
@@ -722,8 +2553,11 @@ impl Tab {
                 let position_and_size = pane.current_geom();
                 let (pane_columns_offset, pane_rows_offset) =
                     pane_content_offset(&position_and_size, &self.viewport);
-                pane.set_content_offset(Offset::shift(pane_rows_offset, pane_columns_offset));
-            }
+                Offset::shift(pane_rows_offset, pane_columns_offset)
+            };
+            // the panes collapsed into its stack have their title bars above its contents
+            content_offset.top += title_bar_rows[pane_id];
+            pane.set_content_offset(content_offset);
 
             // FIXME: This, and all other `set_terminal_size_using_fd` calls, would be best in
             // `TerminalPane::reflow_lines`
@@ -736,37 +2570,104 @@ impl Tab {
             }
         }
     }
-    pub fn render(&mut self) {
+    /// Draws the panes that changed, returns whether some were left for the next frame (see
+    /// [`PROGRESSIVE_RENDER_MIN_COLS`]).
+    pub fn render(&mut self) -> bool {
         if self.active_terminal.is_none()
             || *self.session_state.read().unwrap() != SessionState::Attached
         {
             // we might not have an active terminal if we closed the last pane
             // in that case, we should not render as the app is exiting
             // or if this session is not attached to a client, we do not have to render
-            return;
+            return false;
+        }
+        if self
+            .clients
+            .as_ref()
+            .map_or(false, |clients| clients.is_empty())
+        {
+            // no client views this tab
+            return false;
         }
+        let mut profile = RenderProfile::start();
         let mut output = String::new();
         let mut boundaries = Boundaries::new(self.viewport);
+        let reduced_motion = self.pane_grid_options.reduced_motion;
+        if reduced_motion {
+            let begin_synchronized_update = "\u{1b}[?2026h";
+            output.push_str(begin_synchronized_update);
+        }
         let hide_cursor = "\u{1b}[?25l";
         output.push_str(hide_cursor);
-        if self.should_clear_display_before_rendering {
+        if self.should_clear_display_before_rendering && reduced_motion {
+            // every pane is drawn again over what was there, the lines only go back to single
+            // width (after presentation mode) rather than being erased
+            for row in 0..self.display_area.rows {
+                output.push_str(&format!("\u{1b}[{};1H\u{1b}#5", row + 1));
+            }
+            self.set_force_render();
+            self.should_clear_display_before_rendering = false;
+        } else if self.should_clear_display_before_rendering {
             let clear_display = "\u{1b}[2J";
             output.push_str(clear_display);
             self.should_clear_display_before_rendering = false;
         }
-        for (_kind, pane) in self.panes.iter_mut() {
+        let viewport = self.viewport;
+        let progressive =
+            self.display_area.cols >= PROGRESSIVE_RENDER_MIN_COLS && self.panes.len() > 1;
+        let mut render_incomplete = false;
+        let active_pane_id = self.active_terminal.unwrap();
+        // the panes the clients viewing the tab focus are framed in their colours, the first
+        // client to attach takes the pane it shares with others
+        let focus_colors: HashMap<PaneId, PaletteColor> = match &self.clients {
+            Some(clients) => clients
+                .iter()
+                .rev()
+                .filter_map(|client| {
+                    Some((self.client_pane(client)?, self.client_color(client.slot)))
+                })
+                .collect(),
+            None => vec![(active_pane_id, self.client_color(0))]
+                .into_iter()
+                .collect(),
+        };
+        // each client gets the cursors of the others drawn in their colours
+        let cursors: Vec<(ClientId, PaneId, (usize, usize), PaletteColor)> = self
+            .clients
+            .iter()
+            .flatten()
+            .filter_map(|client| {
+                let pane_id = self.client_pane(client)?;
+                let position = self.cursor_position_in(pane_id)?;
+                Some((client.id, pane_id, position, self.client_color(client.slot)))
+            })
+            .collect();
+        let cursor_markers: Vec<(PaneId, (usize, usize))> = cursors
+            .iter()
+            .map(|(_, pane_id, position, _)| (*pane_id, *position))
+            .collect();
+        for marker in std::mem::replace(&mut self.cursor_markers, cursor_markers) {
+            // the cell under a cursor that moved is drawn again with the rest of its pane
+            if !self.cursor_markers.contains(&marker) {
+                if let Some(pane) = self.panes.get_mut(&marker.0) {
+                    pane.set_should_render(true);
+                    pane.render_full_viewport();
+                }
+            }
+        }
+        let mut panes: Vec<(&PaneId, &mut Box<dyn Pane>)> = self.panes.iter_mut().collect();
+        panes.sort_by_key(|(pane_id, _)| **pane_id != active_pane_id);
+        for (_kind, pane) in panes {
+            if (self.clean_screen && !is_inside_viewport(&viewport, pane))
+                || self.hidden_ui_panes.contains(&pane.pid())
+            {
+                continue;
+            }
             if !self.panes_to_hide.contains(&pane.pid()) {
-                match self.active_terminal.unwrap() == pane.pid() {
-                    true => {
+                match focus_colors.get(&pane.pid()) {
+                    Some(color) => {
                         pane.set_active_at(Instant::now());
-                        match self.mode_info.mode {
-                            InputMode::Normal | InputMode::Locked => {
-                                pane.set_boundary_color(Some(self.colors.green));
-                            }
-                            _ => {
-                                pane.set_boundary_color(Some(self.colors.orange));
-                            }
-                        }
+                        pane.set_boundary_color(Some(*color));
                         if !self.draw_pane_frames {
                             boundaries.add_rect(
                                 pane.as_ref(),
@@ -775,14 +2676,18 @@ impl Tab {
                             )
                         }
                     }
-                    false => {
+                    None => {
                         pane.set_boundary_color(None);
                         if !self.draw_pane_frames {
                             boundaries.add_rect(pane.as_ref(), self.mode_info.mode, None);
                         }
                     }
                 }
-                if let Some(vte_output) = pane.render() {
+                let pane_render_started_at = Instant::now();
+                if progressive && output.len() >= PROGRESSIVE_RENDER_CHUNK_BYTES {
+                    // the pane still has to be rendered, it is drawn in the next frame
+                    render_incomplete = true;
+                } else if let Some(vte_output) = pane.render() {
                     // FIXME: Use Termion for cursor and style clearing?
                     output.push_str(&format!(
                         "\u{1b}[{};{}H\u{1b}[m{}",
@@ -791,35 +2696,167 @@ impl Tab {
                         vte_output
                     ));
                 }
+                profile.pane_rendered(pane.pid(), pane_render_started_at);
             }
         }
+        profile.end_stage("panes");
 
-        if !self.draw_pane_frames {
+        if !self.draw_pane_frames && !self.clean_screen {
             output.push_str(&boundaries.vte_output());
         }
+        profile.end_stage("boundaries");
 
-        match self.get_active_terminal_cursor_position() {
-            Some((cursor_position_x, cursor_position_y)) => {
-                let show_cursor = "\u{1b}[?25h";
-                let change_cursor_shape = self.get_active_pane().unwrap().cursor_shape_csi();
-                let goto_cursor_position = &format!(
-                    "\u{1b}[{};{}H\u{1b}[m{}",
-                    cursor_position_y + 1,
-                    cursor_position_x + 1,
-                    change_cursor_shape
-                ); // goto row/col
-                output.push_str(show_cursor);
-                output.push_str(goto_cursor_position);
+        for (pane_id, pane) in self.panes.iter() {
+            if self.panes_to_hide.contains(pane_id) || self.clean_screen {
+                continue;
             }
-            None => {
-                let hide_cursor = "\u{1b}[?25l";
-                output.push_str(hide_cursor);
+            let collapsed_pane_ids = self.collapsed_pane_ids(*pane_id);
+            let first_title_bar_row = pane
+                .get_content_y()
+                .saturating_sub(collapsed_pane_ids.len());
+            for (row, collapsed_pane_id) in (first_title_bar_row..).zip(collapsed_pane_ids) {
+                if let Some(collapsed_pane) = self.suppressed_panes.get(&collapsed_pane_id) {
+                    let title_bar = PaneFrame {
+                        geom: Viewport {
+                            x: pane.x(),
+                            y: row,
+                            rows: 1,
+                            cols: pane.cols(),
+                        },
+                        title: collapsed_pane.title(),
+                        ..Default::default()
+                    };
+                    output.push_str(&title_bar.render_collapsed());
+                }
             }
         }
 
-        self.senders
-            .send_to_server(ServerInstruction::Render(Some(output)))
-            .unwrap();
+        if let Some((_, candidates)) = self
+            .pane_link_picker
+            .as_ref()
+            .filter(|_| !self.clean_screen)
+        {
+            for (label, pane_id) in candidates {
+                if let Some(pane) = self.panes.get(pane_id) {
+                    output.push_str(&format!(
+                        "\u{1b}[{};{}H\u{1b}[m\u{1b}[1;30;43m {} \u{1b}[m",
+                        pane.get_content_y() + 1,
+                        pane.get_content_x() + 1,
+                        label
+                    )); // goto row/col, then draw the label in black on yellow
+                }
+            }
+        }
+
+        if let Some(tab_search) = self.tab_search.as_ref().filter(|_| !self.clean_screen) {
+            output.push_str(&tab_search.render(&self.viewport));
+        }
+        if let Some(command_history) = self
+            .command_history_picker
+            .as_ref()
+            .filter(|_| !self.clean_screen)
+        {
+            output.push_str(&command_history.render(&self.viewport));
+        }
+        if let Some(session_manager) = self.session_manager.as_ref().filter(|_| !self.clean_screen)
+        {
+            output.push_str(&session_manager.render(&self.viewport));
+        }
+
+        if let Some((pane_id, text)) = &self.pending_clipboard_write {
+            let prompt_position = self
+                .panes
+                .get(pane_id)
+                .filter(|pane| !self.panes_to_hide.contains(&pane.pid()))
+                .map(|pane| (pane.get_content_x(), pane.get_content_y()))
+                .unwrap_or((self.viewport.x, self.viewport.y));
+            let prompt = self
+                .pane_clipboard
+                .locale
+                .text(Message::ClipboardPrompt)
+                .replace("{bytes}", &text.len().to_string());
+            output.push_str(&format!(
+                "\u{1b}[{};{}H\u{1b}[m\u{1b}[1;30;43m {} \u{1b}[m",
+                prompt_position.1 + 1,
+                prompt_position.0 + 1,
+                prompt
+            )); // goto row/col, then draw the prompt in black on yellow
+        }
+
+        let hidden_pane_count = self
+            .hidden_small_panes
+            .iter()
+            .filter(|pane_id| self.suppressed_panes.contains_key(pane_id))
+            .count();
+        if hidden_pane_count > 0 && !self.clean_screen {
+            let marker = self
+                .pane_clipboard
+                .locale
+                .text(Message::HiddenPanes)
+                .replace("{panes}", &hidden_pane_count.to_string());
+            let marker_width = marker.chars().count() + 2;
+            output.push_str(&format!(
+                "\u{1b}[{};{}H\u{1b}[m\u{1b}[1;30;43m {} \u{1b}[m",
+                self.viewport.y + self.viewport.rows,
+                (self.viewport.x + self.viewport.cols).saturating_sub(marker_width) + 1,
+                marker
+            )); // goto the bottom right corner, then draw the marker in black on yellow
+        }
+
+        if let Some(text) = &self.pending_paste {
+            let prompt_position = self
+                .get_active_pane()
+                .map(|pane| (pane.get_content_x(), pane.get_content_y()))
+                .unwrap_or((self.viewport.x, self.viewport.y));
+            let lines = text
+                .split(|&byte| byte == b'\n' || byte == b'\r')
+                .filter(|line| !line.is_empty())
+                .count();
+            let prompt = self
+                .pane_clipboard
+                .locale
+                .text(Message::PastePrompt)
+                .replace("{lines}", &lines.to_string());
+            output.push_str(&format!(
+                "\u{1b}[{};{}H\u{1b}[m\u{1b}[1;30;43m {} \u{1b}[m",
+                prompt_position.1 + 1,
+                prompt_position.0 + 1,
+                prompt
+            )); // goto row/col, then draw the prompt in black on yellow
+        }
+
+        let end_synchronized_update = if reduced_motion { "\u{1b}[?2026l" } else { "" };
+        match &self.clients {
+            Some(clients) => {
+                profile.end_stage("overlays");
+                for client in clients {
+                    let mut client_output = output.clone();
+                    for (client_id, _, (x, y), color) in &cursors {
+                        if *client_id != client.id {
+                            client_output.push_str(&cursor_marker(*x, *y, *color));
+                        }
+                    }
+                    if let Some(pane_id) = self.client_pane(client) {
+                        client_output.push_str(&self.cursor_output(pane_id));
+                    }
+                    client_output.push_str(end_synchronized_update);
+                    self.senders
+                        .send_to_server(ServerInstruction::RenderClient(client.id, client_output))
+                        .unwrap();
+                }
+            }
+            None => {
+                output.push_str(&self.cursor_output(active_pane_id));
+                output.push_str(end_synchronized_update);
+                profile.end_stage("overlays");
+                self.senders
+                    .send_to_server(ServerInstruction::Render(Some(output)))
+                    .unwrap();
+            }
+        }
+        profile.end_stage("send");
+        profile.finish(&self.name);
+        render_incomplete
     }
     fn get_panes(&self) -> impl Iterator<Item = (&PaneId, &Box<dyn Pane>)> {
         self.panes.iter()
@@ -1667,6 +3704,36 @@ impl Tab {
                 !dimension.is_fixed()
             })
     }
+    // the percents of panes held at their minimum or maximum size drift away from the sizes
+    // they are shown at, so they are taken from the shown sizes before resizing
+    fn sync_percents_held_by_bounds(&mut self, direction: Direction) {
+        fn dimension(geom: &mut PaneGeom, direction: Direction) -> &mut Dimension {
+            match direction {
+                Direction::Horizontal => &mut geom.cols,
+                Direction::Vertical => &mut geom.rows,
+            }
+        }
+        let held_by_bounds = self
+            .panes
+            .values()
+            .any(|pane| dimension(&mut pane.position_and_size(), direction).is_at_bound());
+        if !held_by_bounds {
+            return;
+        }
+        let space = match direction {
+            Direction::Horizontal => self.viewport.cols,
+            Direction::Vertical => self.viewport.rows,
+        };
+        for pane in self.panes.values_mut() {
+            let mut geom = pane.position_and_size();
+            let size = dimension(&mut geom, direction);
+            if size.is_fixed() {
+                continue;
+            }
+            size.set_percent(size.as_usize() as f64 * 100.0 / space as f64);
+            pane.set_geom(geom);
+        }
+    }
     pub fn relayout_tab(&mut self, direction: Direction) {
         let mut resizer = PaneResizer::new(self.panes.iter_mut());
         let result = match direction {
@@ -1679,17 +3746,9 @@ impl Tab {
         self.set_pane_frames(self.draw_pane_frames);
     }
     pub fn resize_whole_tab(&mut self, new_screen_size: Size) {
-        // FIXME: I *think* that Rust 2021 will let me just write this:
-        // let panes = self.panes.iter_mut().filter(|(pid, _)| !self.panes_to_hide.contains(pid));
-        // In the meantime, let's appease our borrow-checker overlords:
-        let temp_panes_to_hide = &self.panes_to_hide;
-        let panes = self
-            .panes
-            .iter_mut()
-            .filter(|(pid, _)| !temp_panes_to_hide.contains(pid));
         let Size { rows, cols } = new_screen_size;
-        let mut resizer = PaneResizer::new(panes);
-        if resizer.layout(Direction::Horizontal, cols).is_ok() {
+        let grows = cols > self.display_area.cols || rows > self.display_area.rows;
+        if self.layout_shown_panes(Direction::Horizontal, cols) {
             let column_difference = cols as isize - self.display_area.cols as isize;
             // FIXME: Should the viewport be an Offset?
             self.viewport.cols = (self.viewport.cols as isize + column_difference) as usize;
@@ -1697,59 +3756,183 @@ impl Tab {
         } else {
             log::error!("Failed to horizontally resize the tab!!!");
         }
-        if resizer.layout(Direction::Vertical, rows).is_ok() {
+        if self.layout_shown_panes(Direction::Vertical, rows) {
             let row_difference = rows as isize - self.display_area.rows as isize;
             self.viewport.rows = (self.viewport.rows as isize + row_difference) as usize;
             self.display_area.rows = rows;
         } else {
             log::error!("Failed to vertically resize the tab!!!");
         }
+        if grows {
+            self.show_hidden_small_panes();
+        }
         self.should_clear_display_before_rendering = true;
         self.set_pane_frames(self.draw_pane_frames);
     }
-    pub fn resize_left(&mut self) {
+    // lays the panes shown in the tab out across `space` columns (or rows, vertically), taking
+    // panes out of the layout as the small panes policy says for as long as they don't fit
+    fn layout_shown_panes(&mut self, direction: Direction, space: usize) -> bool {
+        loop {
+            // FIXME: I *think* that Rust 2021 will let me just write this:
+            // let panes = self.panes.iter_mut().filter(|(pid, _)| !self.panes_to_hide.contains(pid));
+            // In the meantime, let's appease our borrow-checker overlords:
+            let temp_panes_to_hide = &self.panes_to_hide;
+            let hidden_ui_panes = &self.hidden_ui_panes;
+            let panes = self.panes.iter_mut().filter(|(pid, _)| {
+                !temp_panes_to_hide.contains(pid) && !hidden_ui_panes.contains(pid)
+            });
+            let laid_out = PaneResizer::new(panes).layout(direction, space).is_ok();
+            if laid_out && !self.has_too_small_panes(direction) {
+                return true;
+            }
+            if !self.make_room_for_panes() {
+                return laid_out;
+            }
+        }
+    }
+    // whether a pane shown in the tab got narrower (or lower, vertically) than it can be used at
+    fn has_too_small_panes(&self, direction: Direction) -> bool {
+        self.panes
+            .iter()
+            .filter(|(pane_id, pane)| {
+                pane.selectable()
+                    && !self.panes_to_hide.contains(pane_id)
+                    && !self.hidden_ui_panes.contains(pane_id)
+            })
+            .any(|(_, pane)| match direction {
+                Direction::Horizontal => pane.cols() < pane.min_width(),
+                Direction::Vertical => pane.rows() < pane.min_height(),
+            })
+    }
+    // brings back the panes hidden when the terminal got too small for them, the last one hidden
+    // first, for as long as a pane can be split to make room for them
+    fn show_hidden_small_panes(&mut self) {
+        // the panes closed or shown again since they were hidden
+        let suppressed_panes = &self.suppressed_panes;
+        self.hidden_small_panes
+            .retain(|pane_id| suppressed_panes.contains_key(pane_id));
+        while let Some(&pane_id) = self.hidden_small_panes.last() {
+            if self.largest_splittable_pane_id().is_none() {
+                break;
+            }
+            self.hidden_small_panes.pop();
+            if let Some(pane) = self.suppressed_panes.remove(&pane_id) {
+                self.add_pinned_pane(pane);
+            }
+        }
+    }
+    // takes the least recently focused pane that can go out of the layout, returning whether
+    // there was one
+    fn make_room_for_panes(&mut self) -> bool {
+        let active_pane_id = self.get_active_pane_id();
+        let mut pane_ids: Vec<(PaneId, Instant)> = self
+            .panes
+            .iter()
+            .filter(|(pane_id, pane)| {
+                pane.selectable()
+                    && Some(**pane_id) != active_pane_id
+                    && !self.panes_to_hide.contains(pane_id)
+            })
+            .map(|(&pane_id, pane)| (pane_id, pane.active_at()))
+            .collect();
+        pane_ids.sort_by_key(|(_, active_at)| *active_at);
+        match self.small_panes {
+            SmallPanesPolicy::Keep => false,
+            SmallPanesPolicy::Stack => pane_ids
+                .into_iter()
+                .any(|(pane_id, _)| self.stack_onto_neighbour(pane_id)),
+            SmallPanesPolicy::Hide => match pane_ids.first() {
+                Some(&(pane_id, _)) => match self.extract_pane(pane_id) {
+                    Some(pane) => {
+                        self.suppressed_panes.insert(pane_id, pane);
+                        self.hidden_small_panes.push(pane_id);
+                        true
+                    }
+                    None => false,
+                },
+                None => false,
+            },
+        }
+    }
+    // the pane joins the stack of a neighbour growing into its space, the neighbour stays shown
+    fn stack_onto_neighbour(&mut self, pane_id: PaneId) -> bool {
+        for (neighbour_id, _) in self.sole_neighbours(pane_id) {
+            if self.stack_pane_onto(pane_id, neighbour_id) {
+                let pane = self.panes.remove(&pane_id).unwrap();
+                let mut neighbour = self.suppressed_panes.remove(&neighbour_id).unwrap();
+                neighbour.set_geom(pane.position_and_size());
+                neighbour.set_should_render(true);
+                self.panes.insert(neighbour_id, neighbour);
+                self.suppressed_panes.insert(pane_id, pane);
+                return true;
+            }
+        }
+        false
+    }
+    // the share of the tab a resize by `amount` takes, across the columns for horizontal resizes
+    // and the rows for vertical ones
+    fn resize_percent(&self, amount: Option<ResizeAmount>, direction: Direction) -> f64 {
+        let space = match direction {
+            Direction::Horizontal => self.display_area.cols,
+            Direction::Vertical => self.display_area.rows,
+        };
+        match amount {
+            None => RESIZE_PERCENT,
+            Some(ResizeAmount::Percent(percent)) => percent as f64,
+            Some(ResizeAmount::Cells(cells)) => cells as f64 * 100.0 / space.max(1) as f64,
+        }
+    }
+    pub fn resize_left_by(&mut self, amount: Option<ResizeAmount>) {
         // TODO: find out by how much we actually reduced and only reduce by that much
+        self.sync_percents_held_by_bounds(Direction::Horizontal);
+        let percent = self.resize_percent(amount, Direction::Horizontal);
         if let Some(active_pane_id) = self.get_active_pane_id() {
-            if self.can_increase_pane_and_surroundings_left(&active_pane_id, RESIZE_PERCENT) {
-                self.increase_pane_and_surroundings_left(&active_pane_id, RESIZE_PERCENT);
-            } else if self.can_reduce_pane_and_surroundings_left(&active_pane_id, RESIZE_PERCENT) {
-                self.reduce_pane_and_surroundings_left(&active_pane_id, RESIZE_PERCENT);
+            if self.can_increase_pane_and_surroundings_left(&active_pane_id, percent) {
+                self.increase_pane_and_surroundings_left(&active_pane_id, percent);
+            } else if self.can_reduce_pane_and_surroundings_left(&active_pane_id, percent) {
+                self.reduce_pane_and_surroundings_left(&active_pane_id, percent);
             }
         }
         self.relayout_tab(Direction::Horizontal);
         self.render();
     }
-    pub fn resize_right(&mut self) {
+    pub fn resize_right_by(&mut self, amount: Option<ResizeAmount>) {
         // TODO: find out by how much we actually reduced and only reduce by that much
+        self.sync_percents_held_by_bounds(Direction::Horizontal);
+        let percent = self.resize_percent(amount, Direction::Horizontal);
         if let Some(active_pane_id) = self.get_active_pane_id() {
-            if self.can_increase_pane_and_surroundings_right(&active_pane_id, RESIZE_PERCENT) {
-                self.increase_pane_and_surroundings_right(&active_pane_id, RESIZE_PERCENT);
-            } else if self.can_reduce_pane_and_surroundings_right(&active_pane_id, RESIZE_PERCENT) {
-                self.reduce_pane_and_surroundings_right(&active_pane_id, RESIZE_PERCENT);
+            if self.can_increase_pane_and_surroundings_right(&active_pane_id, percent) {
+                self.increase_pane_and_surroundings_right(&active_pane_id, percent);
+            } else if self.can_reduce_pane_and_surroundings_right(&active_pane_id, percent) {
+                self.reduce_pane_and_surroundings_right(&active_pane_id, percent);
             }
         }
         self.relayout_tab(Direction::Horizontal);
         self.render();
     }
-    pub fn resize_down(&mut self) {
+    pub fn resize_down_by(&mut self, amount: Option<ResizeAmount>) {
         // TODO: find out by how much we actually reduced and only reduce by that much
+        self.sync_percents_held_by_bounds(Direction::Vertical);
+        let percent = self.resize_percent(amount, Direction::Vertical);
         if let Some(active_pane_id) = self.get_active_pane_id() {
-            if self.can_increase_pane_and_surroundings_down(&active_pane_id, RESIZE_PERCENT) {
-                self.increase_pane_and_surroundings_down(&active_pane_id, RESIZE_PERCENT);
-            } else if self.can_reduce_pane_and_surroundings_down(&active_pane_id, RESIZE_PERCENT) {
-                self.reduce_pane_and_surroundings_down(&active_pane_id, RESIZE_PERCENT);
+            if self.can_increase_pane_and_surroundings_down(&active_pane_id, percent) {
+                self.increase_pane_and_surroundings_down(&active_pane_id, percent);
+            } else if self.can_reduce_pane_and_surroundings_down(&active_pane_id, percent) {
+                self.reduce_pane_and_surroundings_down(&active_pane_id, percent);
             }
         }
         self.relayout_tab(Direction::Vertical);
         self.render();
     }
-    pub fn resize_up(&mut self) {
+    pub fn resize_up_by(&mut self, amount: Option<ResizeAmount>) {
         // TODO: find out by how much we actually reduced and only reduce by that much
+        self.sync_percents_held_by_bounds(Direction::Vertical);
+        let percent = self.resize_percent(amount, Direction::Vertical);
         if let Some(active_pane_id) = self.get_active_pane_id() {
-            if self.can_increase_pane_and_surroundings_up(&active_pane_id, RESIZE_PERCENT) {
-                self.increase_pane_and_surroundings_up(&active_pane_id, RESIZE_PERCENT);
-            } else if self.can_reduce_pane_and_surroundings_up(&active_pane_id, RESIZE_PERCENT) {
-                self.reduce_pane_and_surroundings_up(&active_pane_id, RESIZE_PERCENT);
+            if self.can_increase_pane_and_surroundings_up(&active_pane_id, percent) {
+                self.increase_pane_and_surroundings_up(&active_pane_id, percent);
+            } else if self.can_reduce_pane_and_surroundings_up(&active_pane_id, percent) {
+                self.reduce_pane_and_surroundings_up(&active_pane_id, percent);
             }
         }
         self.relayout_tab(Direction::Vertical);
@@ -1759,7 +3942,7 @@ impl Tab {
         if !self.has_selectable_panes() {
             return;
         }
-        if self.fullscreen_is_active {
+        if !self.exit_fullscreen_for_focus_change() {
             return;
         }
         let active_terminal_id = self.get_active_pane_id().unwrap();
@@ -1780,7 +3963,7 @@ impl Tab {
         if !self.has_selectable_panes() {
             return;
         }
-        if self.fullscreen_is_active {
+        if !self.exit_fullscreen_for_focus_change() {
             return;
         }
         let active_pane_id = self.get_active_pane_id().unwrap();
@@ -1809,7 +3992,7 @@ impl Tab {
         if !self.has_selectable_panes() {
             return;
         }
-        if self.fullscreen_is_active {
+        if !self.exit_fullscreen_for_focus_change() {
             return;
         }
         let active_pane_id = self.get_active_pane_id().unwrap();
@@ -1840,7 +4023,7 @@ impl Tab {
         if !self.has_selectable_panes() {
             return false;
         }
-        if self.fullscreen_is_active {
+        if !self.exit_fullscreen_for_focus_change() {
             return false;
         }
         let active_terminal = self.get_active_pane();
@@ -1879,7 +4062,10 @@ impl Tab {
         if !self.has_selectable_panes() {
             return;
         }
-        if self.fullscreen_is_active {
+        if !self.exit_fullscreen_for_focus_change() {
+            return;
+        }
+        if self.move_focus_within_stack(true) {
             return;
         }
         let active_terminal = self.get_active_pane();
@@ -1916,7 +4102,10 @@ impl Tab {
         if !self.has_selectable_panes() {
             return;
         }
-        if self.fullscreen_is_active {
+        if !self.exit_fullscreen_for_focus_change() {
+            return;
+        }
+        if self.move_focus_within_stack(false) {
             return;
         }
         let active_terminal = self.get_active_pane();
@@ -1954,7 +4143,7 @@ impl Tab {
         if !self.has_selectable_panes() {
             return false;
         }
-        if self.fullscreen_is_active {
+        if !self.exit_fullscreen_for_focus_change() {
             return false;
         }
         let active_terminal = self.get_active_pane();
@@ -1989,6 +4178,175 @@ impl Tab {
         self.set_active_terminal(updated_active_terminal);
         false
     }
+    pub fn move_active_pane_left(&mut self) {
+        self.move_active_pane(|pane, active| {
+            pane.is_directly_left_of(active) && pane.horizontally_overlaps_with(active)
+        });
+    }
+    pub fn move_active_pane_down(&mut self) {
+        self.move_active_pane(|pane, active| {
+            pane.is_directly_below(active) && pane.vertically_overlaps_with(active)
+        });
+    }
+    pub fn move_active_pane_up(&mut self) {
+        self.move_active_pane(|pane, active| {
+            pane.is_directly_above(active) && pane.vertically_overlaps_with(active)
+        });
+    }
+    pub fn move_active_pane_right(&mut self) {
+        self.move_active_pane(|pane, active| {
+            pane.is_directly_right_of(active) && pane.horizontally_overlaps_with(active)
+        });
+    }
+    // the active pane swaps places with the most recently focused of the panes next to it on the
+    // side picked by is_in_direction
+    fn move_active_pane(&mut self, is_in_direction: impl Fn(&dyn Pane, &dyn Pane) -> bool) {
+        if !self.has_selectable_panes() || self.fullscreen_is_active {
+            return;
+        }
+        let active = match self.get_active_pane() {
+            Some(active) => active,
+            None => return,
+        };
+        let active_pane_id = active.pid();
+        let neighbour_id = self
+            .get_selectable_panes()
+            .filter(|(_, pane)| is_in_direction(pane.as_ref(), active))
+            .max_by_key(|(_, pane)| pane.active_at())
+            .map(|(&pane_id, _)| pane_id);
+        if let Some(neighbour_id) = neighbour_id {
+            self.swap_panes(active_pane_id, neighbour_id);
+        }
+    }
+    pub fn swap_active_pane_with_next(&mut self) {
+        if !self.has_selectable_panes() || self.fullscreen_is_active {
+            return;
+        }
+        let active_pane_id = match self.get_active_pane_id() {
+            Some(active_pane_id) => active_pane_id,
+            None => return,
+        };
+        let mut panes: Vec<(&PaneId, &Box<dyn Pane>)> = self.get_selectable_panes().collect();
+        panes.sort_by(|(_a_id, a_pane), (_b_id, b_pane)| {
+            if a_pane.y() == b_pane.y() {
+                a_pane.x().cmp(&b_pane.x())
+            } else {
+                a_pane.y().cmp(&b_pane.y())
+            }
+        });
+        let active_pane_position = panes
+            .iter()
+            .position(|(id, _)| **id == active_pane_id)
+            .unwrap();
+        let next_pane_id = *panes
+            .get(active_pane_position + 1)
+            .or_else(|| panes.get(0))
+            .unwrap()
+            .0;
+        if next_pane_id != active_pane_id {
+            self.swap_panes(active_pane_id, next_pane_id);
+        }
+    }
+    /// The layout of the panes of this tab, and the panes in the order the layout spawns them.
+    pub fn layout_of_panes(&self) -> (TabLayout, Vec<PaneId>) {
+        let viewport = self.viewport;
+        let geoms: HashMap<PaneId, PaneGeom> = self
+            .panes
+            .iter()
+            .filter(|(_, pane)| is_inside_viewport(&viewport, pane))
+            .map(|(&pane_id, pane)| (pane_id, pane.position_and_size()))
+            .collect();
+        let mut leaves = vec![];
+        if geoms.is_empty() {
+            return (TabLayout::default(), leaves);
+        }
+        let mut layout = layout_of_panes(
+            geoms.keys().copied().collect(),
+            &geoms,
+            (100.0, 100.0),
+            &mut leaves,
+        );
+        set_leaf_tags(
+            &mut layout,
+            &mut leaves.iter().map(|pane_id| self.panes[pane_id].tags()),
+        );
+        (layout, leaves)
+    }
+    /// The last `count` lines of each pane, with their styles, as a resurrected session shows
+    /// them.
+    pub fn last_rows_of_panes(&self, count: usize) -> HashMap<PaneId, String> {
+        self.panes
+            .iter()
+            .filter_map(|(&pane_id, pane)| Some((pane_id, pane.dump_last_rows(count)?)))
+            .filter(|(_, rows)| !rows.is_empty())
+            .collect()
+    }
+    pub fn balance_panes(&mut self) {
+        if self.fullscreen_is_active {
+            self.toggle_active_pane_fullscreen();
+        }
+        let viewport = self.viewport;
+        let geoms: HashMap<PaneId, PaneGeom> = self
+            .panes
+            .iter()
+            .filter(|(_, pane)| is_inside_viewport(&viewport, pane))
+            .map(|(&pane_id, pane)| (pane_id, pane.position_and_size()))
+            .collect();
+        if geoms.is_empty() {
+            return;
+        }
+        let mut balanced = HashMap::new();
+        balance_area(
+            viewport,
+            geoms.keys().copied().collect(),
+            &geoms,
+            &mut balanced,
+        );
+        for (pane_id, area) in balanced {
+            let geom = geoms[&pane_id];
+            let balanced_dimension = |dimension: Dimension, size: usize, space: usize| {
+                if dimension.is_fixed() {
+                    return dimension;
+                }
+                let mut percent_dimension = dimension;
+                percent_dimension.set_percent(size as f64 * 100.0 / space as f64);
+                percent_dimension.set_inner(size);
+                percent_dimension
+            };
+            let pane = self.panes.get_mut(&pane_id).unwrap();
+            pane.set_geom(PaneGeom {
+                x: area.x,
+                y: area.y,
+                cols: balanced_dimension(geom.cols, area.cols, viewport.cols),
+                rows: balanced_dimension(geom.rows, area.rows, viewport.rows),
+            });
+            pane.set_should_render(true);
+        }
+        self.relayout_tab(Direction::Horizontal);
+        self.relayout_tab(Direction::Vertical);
+        self.render();
+    }
+    // the two panes trade places in the layout, along with their places in any stacks - the focus
+    // stays with the pane it was on
+    fn swap_panes(&mut self, first_id: PaneId, second_id: PaneId) {
+        let first_geom = self.panes[&first_id].position_and_size();
+        let second_geom = self.panes[&second_id].position_and_size();
+        for (pane_id, geom) in [(first_id, second_geom), (second_id, first_geom)] {
+            let pane = self.panes.get_mut(&pane_id).unwrap();
+            pane.set_geom(geom);
+            pane.set_should_render(true);
+        }
+        for stacked_pane_id in self.pane_stacks.iter_mut().flatten() {
+            if *stacked_pane_id == first_id {
+                *stacked_pane_id = second_id;
+            } else if *stacked_pane_id == second_id {
+                *stacked_pane_id = first_id;
+            }
+        }
+        self.set_pane_frames(self.draw_pane_frames);
+        self.set_force_render();
+        self.render();
+    }
     fn horizontal_borders(&self, terminals: &[PaneId]) -> HashSet<usize> {
         terminals.iter().fold(HashSet::new(), |mut borders, t| {
             let terminal = self.panes.get(t).unwrap();
@@ -2128,6 +4486,34 @@ impl Tab {
     pub fn get_pane_ids(&self) -> Vec<PaneId> {
         self.get_panes().map(|(&pid, _)| pid).collect()
     }
+    /// Roughly the memory the scrollback of the panes of this tab takes up, in bytes.
+    pub fn scrollback_memory(&self) -> usize {
+        self.panes
+            .values()
+            .chain(self.suppressed_panes.values())
+            .map(|pane| pane.scrollback_memory())
+            .sum()
+    }
+    /// Drops the oldest lines of the scrollback of the panes of this tab, each pane giving up
+    /// its share of the `excess` bytes the scrollback of the session (`total` bytes) holds.
+    pub fn trim_scrollback(&mut self, excess: usize, total: usize) {
+        for pane in self
+            .panes
+            .values_mut()
+            .chain(self.suppressed_panes.values_mut())
+        {
+            let share = (excess * pane.scrollback_memory() + total - 1) / total;
+            pane.trim_scrollback(share);
+        }
+    }
+    /// The number of terminals in this tab, suppressed ones included.
+    pub fn terminal_pane_count(&self) -> usize {
+        self.panes
+            .keys()
+            .chain(self.suppressed_panes.keys())
+            .filter(|pane_id| matches!(pane_id, PaneId::Terminal(_)))
+            .count()
+    }
     pub fn set_pane_selectable(&mut self, id: PaneId, selectable: bool) {
         if let Some(pane) = self.panes.get_mut(&id) {
             pane.set_selectable(selectable);
@@ -2138,9 +4524,45 @@ impl Tab {
         self.render();
     }
     pub fn close_pane(&mut self, id: PaneId) {
+        self.pane_frame_styles.remove(&id);
+        self.extract_pane(id);
+    }
+    // takes the pane out of the tab the same way closing it does, but leaves it running so that it
+    // can be put in another tab
+    pub fn extract_pane(&mut self, id: PaneId) -> Option<Box<dyn Pane>> {
+        self.unlink_pane(id);
+        self.hidden_small_panes.retain(|pane_id| *pane_id != id);
+        self.hidden_ui_panes.remove(&id);
+        // the viewers of the pane keep what it showed until then
+        self.viewed_panes.remove(&id);
+        self.viewed_panes
+            .retain(|_, viewed_pane_id| *viewed_pane_id != id);
+        if let Some((pane_id, _)) = &self.pending_clipboard_write {
+            if *pane_id == id {
+                self.pending_clipboard_write = None;
+            }
+        }
+        let is_stacked = self.stack_of(id).is_some();
+        if let Some(pane) = self.suppressed_panes.remove(&id) {
+            if is_stacked {
+                // one title bar less above the pane shown in the stack
+                self.remove_pane_from_stack(id);
+                self.set_pane_frames(self.draw_pane_frames);
+            }
+            return Some(pane);
+        }
         if self.fullscreen_is_active {
             self.toggle_active_pane_fullscreen();
         }
+        if let Some(replacement_id) = self.remove_pane_from_stack(id) {
+            // the pane is replaced by another one of its stack, the layout stays the same
+            let pane = self.panes.remove(&id);
+            if self.active_terminal == Some(id) {
+                self.set_active_terminal(Some(replacement_id));
+            }
+            self.set_pane_frames(self.draw_pane_frames);
+            return pane;
+        }
         if let Some(pane_to_close) = self.panes.get(&id) {
             let freed_space = pane_to_close.position_and_size();
             if let (Some(freed_width), Some(freed_height)) =
@@ -2150,56 +4572,58 @@ impl Tab {
                     for pane_id in panes.iter() {
                         self.increase_pane_width(pane_id, freed_width);
                     }
-                    self.panes.remove(&id);
+                    let pane = self.panes.remove(&id);
                     if self.active_terminal == Some(id) {
                         let next_active_pane = self.next_active_pane(&panes);
                         self.set_active_terminal(next_active_pane);
                     }
                     self.relayout_tab(Direction::Horizontal);
-                    return;
+                    return pane;
                 }
                 if let Some(panes) = self.panes_to_the_right_between_aligning_borders(id) {
                     for pane_id in panes.iter() {
                         self.increase_pane_width(pane_id, freed_width);
                     }
-                    self.panes.remove(&id);
+                    let pane = self.panes.remove(&id);
                     if self.active_terminal == Some(id) {
                         let next_active_pane = self.next_active_pane(&panes);
                         self.set_active_terminal(next_active_pane);
                     }
                     self.relayout_tab(Direction::Horizontal);
-                    return;
+                    return pane;
                 }
                 if let Some(panes) = self.panes_above_between_aligning_borders(id) {
                     for pane_id in panes.iter() {
                         self.increase_pane_height(pane_id, freed_height);
                     }
-                    self.panes.remove(&id);
+                    let pane = self.panes.remove(&id);
                     if self.active_terminal == Some(id) {
                         let next_active_pane = self.next_active_pane(&panes);
                         self.set_active_terminal(next_active_pane);
                     }
                     self.relayout_tab(Direction::Vertical);
-                    return;
+                    return pane;
                 }
                 if let Some(panes) = self.panes_below_between_aligning_borders(id) {
                     for pane_id in panes.iter() {
                         self.increase_pane_height(pane_id, freed_height);
                     }
-                    self.panes.remove(&id);
+                    let pane = self.panes.remove(&id);
                     if self.active_terminal == Some(id) {
                         let next_active_pane = self.next_active_pane(&panes);
                         self.set_active_terminal(next_active_pane);
                     }
                     self.relayout_tab(Direction::Vertical);
-                    return;
+                    return pane;
                 }
             }
             // if we reached here, this is either the last pane or there's some sort of
             // configuration error (eg. we're trying to close a pane surrounded by fixed panes)
-            self.panes.remove(&id);
+            let pane = self.panes.remove(&id);
             self.resize_whole_tab(self.display_area);
+            return pane;
         }
+        None
     }
     pub fn close_focused_pane(&mut self) {
         if let Some(active_pane_id) = self.get_active_pane_id() {
@@ -2216,9 +4640,23 @@ impl Tab {
                 .get_mut(&PaneId::Terminal(active_terminal_id))
                 .unwrap();
             active_terminal.scroll_up(1);
+            if let Some(linked_pane) =
+                self.get_linked_pane_mut(PaneId::Terminal(active_terminal_id))
+            {
+                linked_pane.scroll_up(1);
+            }
             self.render();
         }
     }
+    pub fn scroll_terminal_with_id_up(&mut self, pane_id: PaneId) {
+        // this pane might not be in the active tab, so rendering is left to the caller
+        if let Some(terminal) = self.panes.get_mut(&pane_id) {
+            terminal.scroll_up(1);
+            if let Some(linked_pane) = self.get_linked_pane_mut(pane_id) {
+                linked_pane.scroll_up(1);
+            }
+        }
+    }
     pub fn scroll_active_terminal_down(&mut self) {
         if let Some(active_terminal_id) = self.get_active_terminal_id() {
             let active_terminal = self
@@ -2226,6 +4664,11 @@ impl Tab {
                 .get_mut(&PaneId::Terminal(active_terminal_id))
                 .unwrap();
             active_terminal.scroll_down(1);
+            if let Some(linked_pane) =
+                self.get_linked_pane_mut(PaneId::Terminal(active_terminal_id))
+            {
+                linked_pane.scroll_down(1);
+            }
             self.render();
         }
     }
@@ -2238,6 +4681,11 @@ impl Tab {
             // prevent overflow when row == 0
             let scroll_columns = active_terminal.rows().max(1) - 1;
             active_terminal.scroll_up(scroll_columns);
+            if let Some(linked_pane) =
+                self.get_linked_pane_mut(PaneId::Terminal(active_terminal_id))
+            {
+                linked_pane.scroll_up(scroll_columns);
+            }
             self.render();
         }
     }
@@ -2250,6 +4698,11 @@ impl Tab {
             // prevent overflow when row == 0
             let scroll_columns = active_terminal.rows().max(1) - 1;
             active_terminal.scroll_down(scroll_columns);
+            if let Some(linked_pane) =
+                self.get_linked_pane_mut(PaneId::Terminal(active_terminal_id))
+            {
+                linked_pane.scroll_down(scroll_columns);
+            }
             self.render();
         }
     }
@@ -2260,6 +4713,11 @@ impl Tab {
                 .get_mut(&PaneId::Terminal(active_terminal_id))
                 .unwrap();
             active_terminal.clear_scroll();
+            if let Some(linked_pane) =
+                self.get_linked_pane_mut(PaneId::Terminal(active_terminal_id))
+            {
+                linked_pane.clear_scroll();
+            }
             self.render();
         }
     }
@@ -2272,15 +4730,33 @@ impl Tab {
             active_terminal.clear_scroll();
         }
     }
+    pub fn clear_active_terminal_screen(&mut self, clear_scrollback: bool) {
+        if let Some(active_terminal_id) = self.get_active_terminal_id() {
+            let active_terminal = self
+                .panes
+                .get_mut(&PaneId::Terminal(active_terminal_id))
+                .unwrap();
+            active_terminal.clear_screen(clear_scrollback);
+            self.render();
+        }
+    }
     pub fn scroll_terminal_up(&mut self, point: &Position, lines: usize) {
         if let Some(pane) = self.get_pane_at(point) {
             pane.scroll_up(lines);
+            let pane_id = pane.pid();
+            if let Some(linked_pane) = self.get_linked_pane_mut(pane_id) {
+                linked_pane.scroll_up(lines);
+            }
             self.render();
         }
     }
     pub fn scroll_terminal_down(&mut self, point: &Position, lines: usize) {
         if let Some(pane) = self.get_pane_at(point) {
             pane.scroll_down(lines);
+            let pane_id = pane.pid();
+            if let Some(linked_pane) = self.get_linked_pane_mut(pane_id) {
+                linked_pane.scroll_down(lines);
+            }
             self.render();
         }
     }
@@ -2297,42 +4773,160 @@ impl Tab {
         }
 
         self.get_selectable_panes()
-            .find(|(_, p)| p.contains(point))
+            .find(|(id, p)| !self.hidden_ui_panes.contains(id) && p.contains(point))
             .map(|(&id, _)| id)
     }
-    pub fn handle_left_click(&mut self, position: &Position) {
+    pub fn handle_left_click(&mut self, position: &Position, modifiers: MouseModifiers) {
+        self.send_mouse_event_to_plugin_at(position, Mouse::LeftClick, modifiers);
+        if modifiers.shift {
+            self.extend_selection_to(position);
+        } else if modifiers.ctrl {
+            self.open_link_at(position);
+        } else {
+            self.click(position);
+        }
+    }
+    fn click(&mut self, position: &Position) {
         self.focus_pane_at(position);
 
+        if let Some(collapsed_pane_id) = self.collapsed_pane_id_at(position) {
+            // the pane shown in the stack has just been focused, swap it for the clicked one
+            self.show_suppressed_pane(collapsed_pane_id);
+            return;
+        }
+
         if let Some(pane) = self.get_pane_at(position) {
             let relative_position = pane.relative_position(position);
             pane.start_selection(&relative_position);
+            self.last_left_click = Some((pane.pid(), relative_position));
             self.render();
         };
     }
+    pub fn handle_middle_click(&mut self, position: &Position, modifiers: MouseModifiers) {
+        self.send_mouse_event_to_plugin_at(position, Mouse::MiddleClick, modifiers);
+    }
+    /// Plugins handle their own clicks, even the ones that can't be focused (eg. the tab bar).
+    fn send_mouse_event_to_plugin_at(
+        &self,
+        position: &Position,
+        mouse_event: fn(isize, usize, MouseModifiers) -> Mouse,
+        modifiers: MouseModifiers,
+    ) {
+        let plugin_at_position = self.panes.iter().find(|(pane_id, pane)| {
+            matches!(pane_id, PaneId::Plugin(_))
+                && !self.panes_to_hide.contains(pane_id)
+                && !self.hidden_ui_panes.contains(pane_id)
+                && pane.contains(position)
+        });
+        if let Some((PaneId::Plugin(pid), pane)) = plugin_at_position {
+            let relative_position = pane.relative_position(position);
+            let mouse_event = mouse_event(
+                relative_position.line.0,
+                relative_position.column.0,
+                modifiers,
+            );
+            self.senders
+                .send_to_plugin(PluginInstruction::Update(
+                    Some(*pid),
+                    Event::Mouse(mouse_event),
+                ))
+                .unwrap();
+        }
+    }
+    fn extend_selection_to(&mut self, position: &Position) {
+        // select everything between the previous click and this one, as long as both are in
+        // the same pane - otherwise this is just a regular click
+        match self.last_left_click {
+            Some((pane_id, selection_start)) if self.get_pane_id_at(position) == Some(pane_id) => {
+                if let Some(pane) = self.panes.get_mut(&pane_id) {
+                    let relative_position = pane.relative_position(position);
+                    pane.start_selection(&selection_start);
+                    pane.update_selection(&relative_position);
+                    self.render();
+                }
+            }
+            _ => self.click(position),
+        }
+    }
+    pub fn focus_pane(&mut self, pane_id: PaneId) {
+        if self.get_active_pane_id() == Some(pane_id) || !self.panes.contains_key(&pane_id) {
+            return;
+        }
+        if self.fullscreen_is_active {
+            self.toggle_active_pane_fullscreen();
+        }
+        self.set_active_terminal(Some(pane_id));
+    }
     fn focus_pane_at(&mut self, point: &Position) {
         if let Some(clicked_pane) = self.get_pane_id_at(point) {
             self.set_active_terminal(Some(clicked_pane));
             self.render();
         }
     }
+    pub fn handle_mouse_motion(&mut self, position: &Position) {
+        let hover_changed = self.hover_link_at(position);
+        // motion events come in droves, only render when the focus actually moves
+        if self.get_pane_id_at(position) != self.get_active_pane_id() {
+            self.focus_pane_at(position);
+        } else if hover_changed {
+            self.render();
+        }
+    }
+    // underlines the hyperlink under the mouse pointer, returns whether that changed anything
+    fn hover_link_at(&mut self, position: &Position) -> bool {
+        let mut changed = false;
+        let pane_id = self.get_pane_id_at(position);
+        if pane_id != self.hovered_pane {
+            if let Some(pane) = self.hovered_pane.and_then(|id| self.panes.get_mut(&id)) {
+                changed |= pane.hover(None);
+            }
+            self.hovered_pane = pane_id;
+        }
+        if let Some(pane) = self.get_pane_at(position) {
+            let relative_position = pane.relative_position(position);
+            changed |= pane.hover(Some(&relative_position));
+        }
+        changed
+    }
+    /// Opens the hyperlink at `position` with the link opener of the client, or else clicks
+    /// there.
+    fn open_link_at(&mut self, position: &Position) {
+        let link = self.get_pane_at(position).and_then(|pane| {
+            let relative_position = pane.relative_position(position);
+            pane.link_at(&relative_position)
+        });
+        match link {
+            Some(link) => self
+                .senders
+                .send_to_server(ServerInstruction::OpenLink(link))
+                .unwrap(),
+            None => self.click(position),
+        }
+    }
     pub fn handle_mouse_release(&mut self, position: &Position) {
         let active_pane_id = self.get_active_pane_id();
-        // on release, get the selected text from the active pane, and reset it's selection
+        let copy_on_select = self.pane_clipboard.copy_on_select;
+        // on release, get the selected text from the active pane, and reset it's selection -
+        // unless it is kept for the Copy action
         let mut selected_text = None;
         if active_pane_id != self.get_pane_id_at(position) {
             if let Some(active_pane_id) = active_pane_id {
                 if let Some(active_pane) = self.panes.get_mut(&active_pane_id) {
                     active_pane.end_selection(None);
-                    selected_text = active_pane.get_selected_text();
-                    active_pane.reset_selection();
+                    if copy_on_select {
+                        selected_text = active_pane.get_selected_text();
+                        active_pane.reset_selection();
+                    }
                     self.render();
                 }
             }
         } else if let Some(pane) = self.get_pane_at(position) {
             let relative_position = pane.relative_position(position);
             pane.end_selection(Some(&relative_position));
-            selected_text = pane.get_selected_text();
-            pane.reset_selection();
+            if copy_on_select {
+                selected_text = pane.get_selected_text();
+                pane.reset_selection();
+            }
             self.render();
         }
 
@@ -2354,16 +4948,189 @@ impl Tab {
         let selected_text = self.get_active_pane().and_then(|p| p.get_selected_text());
         if let Some(selected_text) = selected_text {
             self.write_selection_to_clipboard(&selected_text);
+        }
+    }
+
+    pub fn page_active_pane_scrollback(&self) {
+        let scrollback = self.get_active_pane().and_then(|p| p.dump_scrollback());
+        if let (Some(scrollback), Some(pid)) = (scrollback, self.get_active_terminal_id()) {
+            let scrollback_file = ZELLIJ_TMP_DIR.join(format!("scrollback-{}", pid));
+            let written = fs::create_dir_all(&*ZELLIJ_TMP_DIR)
+                .and_then(|_| fs::write(&scrollback_file, scrollback));
+            if let Err(e) = written {
+                log::error!("Failed to write scrollback to {:?}: {}", scrollback_file, e);
+                return;
+            }
+            self.senders
+                .send_to_pty(PtyInstruction::SpawnTerminalOnTempFile(
+                    TerminalAction::PageFile(scrollback_file),
+                    None,
+                ))
+                .unwrap();
+        }
+    }
+
+    /// Opens the scrollback of the active pane as plain text in the editor of the user, in a
+    /// new pane.
+    pub fn edit_active_pane_scrollback(&self, reply: Option<ActionReply>) {
+        let scrollback = self
+            .get_active_pane()
+            .and_then(|p| p.dump_scrollback_text());
+        if let (Some(scrollback), Some(pid)) = (scrollback, self.get_active_terminal_id()) {
+            let scrollback_file = ZELLIJ_TMP_DIR.join(format!("scrollback-{}.txt", pid));
+            let written = fs::create_dir_all(&*ZELLIJ_TMP_DIR)
+                .and_then(|_| fs::write(&scrollback_file, scrollback));
+            if let Err(e) = written {
+                log::error!("Failed to write scrollback to {:?}: {}", scrollback_file, e);
+                return;
+            }
             self.senders
-                .send_to_plugin(PluginInstruction::Update(None, Event::CopyToClipboard))
+                .send_to_pty(PtyInstruction::SpawnTerminalOnTempFile(
+                    TerminalAction::OpenFile(scrollback_file),
+                    reply,
+                ))
                 .unwrap();
         }
     }
 
+    /// Writes the viewport of the active pane, or its whole scrollback with `full`, to `path`,
+    /// styled with escape sequences with `ansi`.
+    pub fn dump_active_pane_screen(
+        &self,
+        path: &Path,
+        full: bool,
+        ansi: bool,
+    ) -> Result<(), String> {
+        let screen = self
+            .get_active_pane()
+            .and_then(|active_pane| active_pane.dump_screen(full, ansi))
+            .ok_or_else(|| "The focused pane has no screen to dump".to_string())?;
+        fs::write(path, screen)
+            .map_err(|e| format!("Can't write the screen to {}: {}", path.display(), e))
+    }
+
+    /// Gives the active pane `tag`, unless it already has it.
+    pub fn tag_active_pane(&mut self, tag: String) {
+        if let Some(active_pane) = self.active_terminal.and_then(|id| self.panes.get_mut(&id)) {
+            if !active_pane.tags().contains(&tag) {
+                let mut tags = active_pane.tags().to_vec();
+                tags.push(tag);
+                active_pane.set_tags(tags);
+            }
+        }
+    }
+
+    pub fn untag_active_pane(&mut self, tag: &str) {
+        if let Some(active_pane) = self.active_terminal.and_then(|id| self.panes.get_mut(&id)) {
+            let mut tags = active_pane.tags().to_vec();
+            tags.retain(|t| t != tag);
+            active_pane.set_tags(tags);
+        }
+    }
+
+    /// The tags of the panes of this tab, suppressed ones included, each of them once.
+    pub fn pane_tags(&self) -> Vec<String> {
+        let tags: BTreeSet<&String> = self
+            .panes
+            .values()
+            .chain(self.suppressed_panes.values())
+            .flat_map(|pane| pane.tags())
+            .collect();
+        tags.into_iter().cloned().collect()
+    }
+
+    /// Shows `timer` in the frame of the active pane, replacing the one it had.
+    pub fn start_active_pane_timer(&mut self, timer: PaneTimer) {
+        if let Some(active_pane) = self.active_terminal.and_then(|id| self.panes.get_mut(&id)) {
+            active_pane.set_timer(Some(RunningTimer::new(timer, Instant::now())));
+            self.render();
+        }
+    }
+
+    pub fn stop_active_pane_timer(&mut self) {
+        if let Some(active_pane) = self.active_terminal.and_then(|id| self.panes.get_mut(&id)) {
+            active_pane.set_timer(None);
+            self.render();
+        }
+    }
+
+    /// Brings the timers of the panes up to `now`, running the commands of the countdowns that
+    /// elapsed. Returns whether any of them still ticks.
+    pub fn tick_pane_timers(&mut self, now: Instant) -> bool {
+        let mut ticking = false;
+        // the countdowns of suppressed panes elapse all the same
+        for pane in self
+            .panes
+            .values_mut()
+            .chain(self.suppressed_panes.values_mut())
+        {
+            let timer = match pane.timer_mut() {
+                Some(timer) if timer.is_ticking() => timer,
+                _ => continue,
+            };
+            if timer.tick(now) {
+                if let Some(command) = timer.on_elapsed() {
+                    run_on_elapsed(command);
+                }
+            }
+            ticking |= timer.is_ticking();
+            // the frame shows the new time
+            pane.set_should_render(true);
+        }
+        ticking
+    }
+
+    /// The terminals of this tab, suppressed ones included, only those tagged `tag` if given.
+    pub fn pane_summaries(&self, tag: Option<&str>) -> Vec<PaneSummary> {
+        let mut panes: Vec<PaneSummary> = self
+            .panes
+            .iter()
+            .chain(self.suppressed_panes.iter())
+            .filter(|(_, pane)| tag.map_or(true, |tag| pane.tags().iter().any(|t| t == tag)))
+            .filter_map(|(pane_id, pane)| match pane_id {
+                PaneId::Terminal(pid) => Some(PaneSummary {
+                    id: *pid as u32,
+                    tab_position: self.position + 1,
+                    title: pane.title(),
+                    tags: pane.tags().to_vec(),
+                }),
+                PaneId::Plugin(_) => None,
+            })
+            .collect();
+        panes.sort_by_key(|pane| pane.id);
+        panes
+    }
+
+    /// Writes the text selected in the active pane, or else the output of the last command it
+    /// ran, to a file for another command to read.
+    pub fn write_active_pane_selection_to_file(&self) -> Result<PathBuf, String> {
+        let text = self.get_active_pane().and_then(|active_pane| {
+            active_pane
+                .get_selected_text()
+                .or_else(|| active_pane.last_command_output())
+        });
+        let (text, pid) = match (text, self.get_active_terminal_id()) {
+            (Some(text), Some(pid)) => (text, pid),
+            _ => {
+                return Err(
+                    "Nothing is selected, and the shell marked no command output in this pane"
+                        .into(),
+                )
+            }
+        };
+        let input_file = ZELLIJ_TMP_DIR.join(format!("selection-{}.txt", pid));
+        // a command still reading the previous selection keeps it, rather than reading this one
+        let _ = fs::remove_file(&input_file);
+        fs::create_dir_all(&*ZELLIJ_TMP_DIR)
+            .and_then(|_| fs::write(&input_file, text))
+            .map_err(|e| format!("Failed to write the selection to {:?}: {}", input_file, e))?;
+        Ok(input_file)
+    }
+
     fn write_selection_to_clipboard(&self, selection: &str) {
-        let output = format!("\u{1b}]52;c;{}\u{1b}\\", base64::encode(selection));
+        // the client knows which clipboard to put it in
         self.senders
-            .send_to_server(ServerInstruction::Render(Some(output)))
+            .send_to_server(ServerInstruction::CopyToClipboard(selection.to_owned()))
             .unwrap();
         self.senders
             .send_to_plugin(PluginInstruction::Update(None, Event::CopyToClipboard))
@@ -2402,6 +5169,10 @@ impl Tab {
     }
 }
 
+fn default_tab_name(index: usize) -> String {
+    format!("Tab #{}", index + 1)
+}
+
 #[allow(clippy::borrowed_box)]
 fn is_inside_viewport(viewport: &Viewport, pane: &Box<dyn Pane>) -> bool {
     let pane_position_and_size = pane.current_geom();
@@ -1,10 +1,19 @@
 //! Definitions and helpers for sending and receiving messages between threads.
 
 use crate::{
-    os_input_output::ServerOsApi, pty::PtyInstruction, screen::ScreenInstruction,
-    wasm_vm::PluginInstruction, ServerInstruction,
+    os_input_output::{ClientId, ServerOsApi},
+    pty::PtyInstruction,
+    screen::ScreenInstruction,
+    wasm_vm::PluginInstruction,
+    ServerInstruction,
 };
-use zellij_utils::{channels, channels::SenderWithContext, errors::ErrorContext};
+use zellij_utils::{
+    channels, channels::SenderWithContext, errors::ErrorContext, ipc::ActionResult,
+};
+
+/// A one-shot channel through which the outcome of an action sent from the command line is
+/// reported back to the router thread that received it.
+pub(crate) type ActionReply = channels::Sender<ActionResult>;
 
 /// A container for senders to the different threads in zellij on the server side
 #[derive(Default, Clone)]
@@ -16,6 +25,9 @@ pub(crate) struct ThreadSenders {
     // this is a convenience for the unit tests
     // it's not advisable to set it to true in production code
     pub should_silently_fail: bool,
+    // the client the instructions to the screen and to the pty are for, if any, the screen
+    // handles them with its focus
+    pub client_id: Option<ClientId>,
 }
 
 impl ThreadSenders {
@@ -23,6 +35,10 @@ impl ThreadSenders {
         &self,
         instruction: ScreenInstruction,
     ) -> Result<(), channels::SendError<(ScreenInstruction, ErrorContext)>> {
+        let instruction = match self.client_id {
+            Some(client_id) => ScreenInstruction::FromClient(client_id, Box::new(instruction)),
+            None => instruction,
+        };
         if self.should_silently_fail {
             let _ = self
                 .to_screen
@@ -39,6 +55,10 @@ impl ThreadSenders {
         &self,
         instruction: PtyInstruction,
     ) -> Result<(), channels::SendError<(PtyInstruction, ErrorContext)>> {
+        let instruction = match self.client_id {
+            Some(client_id) => PtyInstruction::FromClient(client_id, Box::new(instruction)),
+            None => instruction,
+        };
         if self.should_silently_fail {
             let _ = self
                 .to_pty
@@ -82,6 +102,13 @@ impl ThreadSenders {
             self.to_server.as_ref().unwrap().send(instruction)
         }
     }
+    /// These senders, with the instructions to the screen and to the pty being for `client_id`.
+    pub fn for_client(&self, client_id: Option<ClientId>) -> Self {
+        ThreadSenders {
+            client_id,
+            ..self.clone()
+        }
+    }
     #[allow(unused)]
     pub fn silently_fail_on_send(mut self) -> Self {
         // this is mostly used for the tests, see struct
@@ -115,6 +142,7 @@ impl<T> Bus<T> {
                 to_plugin: to_plugin.cloned(),
                 to_server: to_server.cloned(),
                 should_silently_fail: false,
+                client_id: None,
             },
             os_input: os_input.clone(),
         }
@@ -130,6 +158,7 @@ impl<T> Bus<T> {
                 to_plugin: None,
                 to_server: None,
                 should_silently_fail: true,
+                client_id: None,
             },
             os_input: None,
         }
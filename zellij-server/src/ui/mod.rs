@@ -1,3 +1,4 @@
 pub mod boundaries;
 pub mod pane_boundaries_frame;
 pub mod pane_resizer;
+pub mod render_profile;
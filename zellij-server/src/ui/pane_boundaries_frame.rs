@@ -1,6 +1,7 @@
 use crate::ui::boundaries::boundary_type;
 use ansi_term::Colour::{Fixed, RGB};
 use ansi_term::Style;
+use zellij_utils::input::options::PaneFrameStyle;
 use zellij_utils::pane_size::Viewport;
 use zellij_utils::zellij_tile::prelude::PaletteColor;
 
@@ -20,16 +21,110 @@ fn color_string(character: &str, color: Option<PaletteColor>) -> String {
     }
 }
 
+// the characters a frame is drawn with
+struct FrameCharacters {
+    top_left: &'static str,
+    top_right: &'static str,
+    bottom_left: &'static str,
+    bottom_right: &'static str,
+    horizontal: &'static str,
+    vertical: &'static str,
+    vertical_left: &'static str,
+    vertical_right: &'static str,
+}
+
+impl FrameCharacters {
+    fn of(style: PaneFrameStyle) -> Self {
+        match style {
+            PaneFrameStyle::Normal => FrameCharacters {
+                top_left: boundary_type::TOP_LEFT,
+                top_right: boundary_type::TOP_RIGHT,
+                bottom_left: boundary_type::BOTTOM_LEFT,
+                bottom_right: boundary_type::BOTTOM_RIGHT,
+                horizontal: boundary_type::HORIZONTAL,
+                vertical: boundary_type::VERTICAL,
+                vertical_left: boundary_type::VERTICAL_LEFT,
+                vertical_right: boundary_type::VERTICAL_RIGHT,
+            },
+            PaneFrameStyle::Rounded => FrameCharacters {
+                top_left: "╭",
+                top_right: "╮",
+                bottom_left: "╰",
+                bottom_right: "╯",
+                ..FrameCharacters::of(PaneFrameStyle::Normal)
+            },
+            PaneFrameStyle::Thick => FrameCharacters {
+                top_left: "┏",
+                top_right: "┓",
+                bottom_left: "┗",
+                bottom_right: "┛",
+                horizontal: "━",
+                vertical: "┃",
+                vertical_left: "┫",
+                vertical_right: "┣",
+            },
+        }
+    }
+}
+
 #[derive(Default, PartialEq)]
 pub struct PaneFrame {
     pub geom: Viewport,
     pub title: String,
     pub scroll_position: (usize, usize), // (position, length)
     pub color: Option<PaletteColor>,
+    pub read_only: bool,
+    pub fullscreen: bool,
+    pub pinned: bool,
+    pub style: PaneFrameStyle,
+    pub search: Option<String>, // the searched text and the matches, while searching
+    pub timer: Option<String>,  // the countdown or stopwatch of the pane
 }
 
 impl PaneFrame {
+    fn characters(&self) -> FrameCharacters {
+        FrameCharacters::of(self.style)
+    }
     fn render_title_right_side(&self, max_length: usize) -> Option<String> {
+        let characters = self.characters();
+        let mut indications = String::new();
+        for (shown, indication) in [
+            (
+                self.search.is_some(),
+                self.search.as_deref().unwrap_or_default(),
+            ),
+            (
+                self.timer.is_some(),
+                self.timer.as_deref().unwrap_or_default(),
+            ),
+            (self.fullscreen, " FULLSCREEN "),
+            (self.pinned, " PINNED "),
+            (self.read_only, " READ ONLY "),
+        ] {
+            // 1 for the separator between two indications
+            let separator_width = if indications.is_empty() { 0 } else { 1 };
+            if !shown || indications.width() + separator_width + indication.width() > max_length {
+                continue;
+            }
+            if !indications.is_empty() {
+                indications.push_str(characters.horizontal);
+            }
+            indications.push_str(indication);
+        }
+        if indications.is_empty() {
+            return self.render_scroll_indication(max_length);
+        }
+        // 1 for the separator between the indications and the scroll position
+        let space_left = max_length.saturating_sub(indications.width() + 1);
+        match self.render_scroll_indication(space_left) {
+            Some(scroll_indication) => Some(format!(
+                "{}{}{}",
+                indications, characters.horizontal, scroll_indication
+            )),
+            None => Some(indications),
+        }
+    }
+    fn render_scroll_indication(&self, max_length: usize) -> Option<String> {
         if self.scroll_position.0 > 0 || self.scroll_position.1 > 0 {
             let prefix = " SCROLL: ";
             let full_indication =
@@ -93,9 +188,10 @@ impl PaneFrame {
         }
     }
     fn render_title(&self, vte_output: &mut String) {
+        let characters = self.characters();
         let total_title_length = self.geom.cols.saturating_sub(2); // 2 for the left and right corners
-        let left_boundary = boundary_type::TOP_LEFT;
-        let right_boundary = boundary_type::TOP_RIGHT;
+        let left_boundary = characters.top_left;
+        let right_boundary = characters.top_right;
         let left_side = self.render_title_left_side(total_title_length);
         let right_side = left_side.as_ref().and_then(|left_side| {
             let space_left = total_title_length.saturating_sub(left_side.width() + 1); // 1 for a middle separator
@@ -105,7 +201,7 @@ impl PaneFrame {
             (Some(left_side), Some(right_side)) => {
                 let mut middle = String::new();
                 for _ in (left_side.width() + right_side.width())..total_title_length {
-                    middle.push_str(boundary_type::HORIZONTAL);
+                    middle.push_str(characters.horizontal);
                 }
                 format!(
                     "{}{}{}{}{}",
@@ -115,7 +211,7 @@ impl PaneFrame {
             (Some(left_side), None) => {
                 let mut middle_padding = String::new();
                 for _ in left_side.width()..total_title_length {
-                    middle_padding.push_str(boundary_type::HORIZONTAL);
+                    middle_padding.push_str(characters.horizontal);
                 }
                 format!(
                     "{}{}{}{}",
@@ -125,7 +221,7 @@ impl PaneFrame {
             _ => {
                 let mut middle_padding = String::new();
                 for _ in 0..total_title_length {
-                    middle_padding.push_str(boundary_type::HORIZONTAL);
+                    middle_padding.push_str(characters.horizontal);
                 }
                 format!("{}{}{}", left_boundary, middle_padding, right_boundary)
             }
@@ -138,6 +234,7 @@ impl PaneFrame {
         )); // goto row/col + boundary character
     }
     pub fn render(&self) -> String {
+        let characters = self.characters();
         let mut vte_output = String::new();
         for row in self.geom.y..(self.geom.y + self.geom.rows) {
             if row == self.geom.y {
@@ -152,7 +249,7 @@ impl PaneFrame {
                             "\u{1b}[{};{}H\u{1b}[m{}",
                             row + 1, // +1 because goto is 1 indexed
                             col + 1,
-                            color_string(boundary_type::BOTTOM_LEFT, self.color),
+                            color_string(characters.bottom_left, self.color),
                         )); // goto row/col + boundary character
                     } else if col == self.geom.x + self.geom.cols - 1 {
                         // bottom right corner
@@ -160,14 +257,14 @@ impl PaneFrame {
                             "\u{1b}[{};{}H\u{1b}[m{}",
                             row + 1, // +1 because goto is 1 indexed
                             col + 1,
-                            color_string(boundary_type::BOTTOM_RIGHT, self.color),
+                            color_string(characters.bottom_right, self.color),
                         )); // goto row/col + boundary character
                     } else {
                         vte_output.push_str(&format!(
                             "\u{1b}[{};{}H\u{1b}[m{}",
                             row + 1, // +1 because goto is 1 indexed
                             col + 1,
-                            color_string(boundary_type::HORIZONTAL, self.color),
+                            color_string(characters.horizontal, self.color),
                         )); // goto row/col + boundary character
                     }
                 }
@@ -176,16 +273,39 @@ impl PaneFrame {
                     "\u{1b}[{};{}H\u{1b}[m{}",
                     row + 1, // +1 because goto is 1 indexed
                     self.geom.x + 1,
-                    color_string(boundary_type::VERTICAL, self.color),
+                    color_string(characters.vertical, self.color),
                 )); // goto row/col + boundary character
                 vte_output.push_str(&format!(
                     "\u{1b}[{};{}H\u{1b}[m{}",
                     row + 1, // +1 because goto is 1 indexed
                     self.geom.x + self.geom.cols,
-                    color_string(boundary_type::VERTICAL, self.color),
+                    color_string(characters.vertical, self.color),
                 )); // goto row/col + boundary character
             }
         }
         vte_output
     }
+    /// Renders only the title of the pane, on the top row of the frame, as the title bar of a pane
+    /// that is collapsed into a stack.
+    pub fn render_collapsed(&self) -> String {
+        let characters = self.characters();
+        let total_title_length = self.geom.cols.saturating_sub(2); // 2 for the left and right edges
+        let title = self
+            .render_title_left_side(total_title_length)
+            .unwrap_or_default();
+        let mut middle_padding = String::new();
+        for _ in title.width()..total_title_length {
+            middle_padding.push_str(characters.horizontal);
+        }
+        let title_bar = format!(
+            "{}{}{}{}",
+            characters.vertical_right, title, middle_padding, characters.vertical_left
+        );
+        format!(
+            "\u{1b}[{};{}H\u{1b}[m{}",
+            self.geom.y + 1, // +1 because goto is 1 indexed
+            self.geom.x + 1, // +1 because goto is 1 indexed
+            color_string(&title_bar, self.color),
+        ) // goto row/col + title bar
+    }
 }
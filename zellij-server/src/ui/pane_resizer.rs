@@ -1,8 +1,8 @@
 use crate::{panes::PaneId, tab::Pane};
 use cassowary::{
-    strength::{REQUIRED, STRONG},
+    strength::{self, REQUIRED, STRONG},
     Expression, Solver, Variable,
-    WeightedRelation::EQ,
+    WeightedRelation::{EQ, GE, LE},
 };
 use std::collections::{HashMap, HashSet};
 use zellij_utils::{
@@ -233,6 +233,18 @@ fn constrain_spans(space: usize, spans: &[Span]) -> HashSet<cassowary::Constrain
         };
     }
 
+    // Keep flexible sizes within their bounds, over their ratios but giving way when the
+    // space can't fit them
+    let bounds = strength::create(100.0, 0.0, 0.0, 1.0);
+    for span in spans.iter().filter(|s| !s.size.is_fixed()) {
+        if let Some(min) = span.size.min_size() {
+            constraints.insert(span.size_var | GE(bounds) | min as f64);
+        }
+        if let Some(max) = span.size.max_size() {
+            constraints.insert(span.size_var | LE(bounds) | max as f64);
+        }
+    }
+
     constraints
 }
 
@@ -0,0 +1,78 @@
+//! Timings of the stages of rendering a tab, to pinpoint what makes a frame slow.
+use crate::panes::PaneId;
+use std::time::{Duration, Instant};
+
+/// Frames taking longer than this to render are logged, with the timings of their stages.
+pub const SLOW_FRAME_THRESHOLD: Duration = Duration::from_millis(20);
+
+pub struct RenderProfile {
+    started_at: Instant,
+    stage_started_at: Instant,
+    stages: Vec<(&'static str, Duration)>,
+    slowest_pane: Option<(PaneId, Duration)>,
+}
+
+impl RenderProfile {
+    pub fn start() -> Self {
+        let now = Instant::now();
+        RenderProfile {
+            started_at: now,
+            stage_started_at: now,
+            stages: vec![],
+            slowest_pane: None,
+        }
+    }
+    /// Records the time since the previous stage ended as the time `stage` took.
+    pub fn end_stage(&mut self, stage: &'static str) {
+        let now = Instant::now();
+        self.stages.push((stage, now - self.stage_started_at));
+        self.stage_started_at = now;
+    }
+    pub fn pane_rendered(&mut self, pane_id: PaneId, started_at: Instant) {
+        let duration = started_at.elapsed();
+        if self
+            .slowest_pane
+            .map_or(true, |(_, slowest)| duration > slowest)
+        {
+            self.slowest_pane = Some((pane_id, duration));
+        }
+    }
+    /// Logs the timings of the frame if it took longer than [`SLOW_FRAME_THRESHOLD`].
+    pub fn finish(self, tab_name: &str) {
+        if let Some(report) = self.report(tab_name) {
+            log::warn!("{}", report);
+        }
+    }
+    /// Describes the timings of the frame, or `None` if it was not slow enough to report.
+    fn report(&self, tab_name: &str) -> Option<String> {
+        let total = self.started_at.elapsed();
+        if total < SLOW_FRAME_THRESHOLD {
+            return None;
+        }
+        let stages: Vec<String> = self
+            .stages
+            .iter()
+            .map(|(stage, duration)| format!("{} {:?}", stage, duration))
+            .collect();
+        Some(match self.slowest_pane {
+            Some((pane_id, duration)) => format!(
+                "Slow frame in tab {:?}: {:?} ({}), slowest pane {:?} took {:?}",
+                tab_name,
+                total,
+                stages.join(", "),
+                pane_id,
+                duration
+            ),
+            None => format!(
+                "Slow frame in tab {:?}: {:?} ({})",
+                tab_name,
+                total,
+                stages.join(", ")
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/render_profile_tests.rs"]
+mod render_profile_tests;
@@ -0,0 +1,57 @@
+use super::{RenderProfile, SLOW_FRAME_THRESHOLD};
+use crate::panes::PaneId;
+use std::time::{Duration, Instant};
+
+fn started_ago(ago: Duration) -> RenderProfile {
+    let mut profile = RenderProfile::start();
+    profile.started_at -= ago;
+    profile.stage_started_at -= ago;
+    profile
+}
+
+#[test]
+fn fast_frames_are_not_reported() {
+    let mut profile = RenderProfile::start();
+    profile.end_stage("render panes");
+    assert_eq!(profile.report("Tab #1"), None);
+}
+
+#[test]
+fn slow_frames_report_the_tab_and_every_stage_in_order() {
+    let mut profile = started_ago(SLOW_FRAME_THRESHOLD * 2);
+    profile.end_stage("render panes");
+    profile.end_stage("render boundaries");
+    let report = profile.report("Tab #1").unwrap();
+    assert!(
+        report.starts_with("Slow frame in tab \"Tab #1\": "),
+        "{}",
+        report
+    );
+    let panes = report.find("(render panes ").unwrap();
+    let boundaries = report.find(", render boundaries ").unwrap();
+    assert!(panes < boundaries, "{}", report);
+    assert!(!report.contains("slowest pane"), "{}", report);
+    assert_eq!(profile.stages[0].0, "render panes");
+    assert!(profile.stages[0].1 >= SLOW_FRAME_THRESHOLD * 2);
+    assert_eq!(profile.stages[1].0, "render boundaries");
+    assert!(profile.stages[1].1 < SLOW_FRAME_THRESHOLD * 2);
+}
+
+#[test]
+fn slow_frames_report_the_slowest_pane() {
+    let mut profile = started_ago(SLOW_FRAME_THRESHOLD * 2);
+    let now = Instant::now();
+    profile.pane_rendered(PaneId::Terminal(1), now - Duration::from_millis(1));
+    profile.pane_rendered(PaneId::Terminal(2), now - Duration::from_millis(10));
+    profile.pane_rendered(PaneId::Plugin(3), now - Duration::from_millis(5));
+    profile.end_stage("render panes");
+    let (slowest, duration) = profile.slowest_pane.unwrap();
+    assert_eq!(slowest, PaneId::Terminal(2));
+    assert!(duration >= Duration::from_millis(10));
+    let report = profile.report("Tab #1").unwrap();
+    assert!(
+        report.ends_with(&format!("), slowest pane Terminal(2) took {:?}", duration)),
+        "{}",
+        report
+    );
+}
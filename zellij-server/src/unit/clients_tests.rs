@@ -0,0 +1,128 @@
+use super::*;
+use zellij_utils::input::actions::Direction;
+
+fn clients(sizing: ClientSizing, sizes: &[(usize, usize)]) -> Clients {
+    let mut clients = Clients {
+        sizing,
+        ..Default::default()
+    };
+    for (client_id, (rows, cols)) in sizes.iter().enumerate() {
+        clients.attach(
+            client_id as ClientId,
+            Size {
+                rows: *rows,
+                cols: *cols,
+            },
+        );
+    }
+    clients
+}
+
+#[test]
+fn the_session_takes_the_fewest_rows_and_columns_of_the_clients() {
+    let mut clients = clients(ClientSizing::Smallest, &[(40, 100), (50, 80)]);
+    assert_eq!(clients.session_size(), Some(Size { rows: 40, cols: 80 }));
+    assert!(clients.detach(1));
+    assert!(!clients.detach(1), "the client is already detached");
+    assert_eq!(
+        clients.session_size(),
+        Some(Size {
+            rows: 40,
+            cols: 100
+        })
+    );
+    assert!(clients.detach(0));
+    assert_eq!(clients.session_size(), None);
+}
+
+#[test]
+fn the_session_is_focused_while_any_client_is() {
+    let mut clients = clients(ClientSizing::Smallest, &[(40, 100), (50, 80)]);
+    clients.set_focus(0, false);
+    assert!(clients.focused());
+    clients.set_focus(1, false);
+    assert!(!clients.focused());
+}
+
+#[test]
+fn larger_clients_draw_the_session_in_their_top_left_corner() {
+    let mut clients = clients(ClientSizing::Smallest, &[(10, 20), (30, 40)]);
+    let output = "\u{1b}[2J\u{1b}[1;1H\u{1b}[mabc";
+    assert_eq!(clients.render_for(1, output), output);
+    let click = Action::LeftClick(Position::new(5, 5), Default::default());
+    assert_eq!(clients.action_in_session(1, click.clone()), click);
+}
+
+#[test]
+fn letterboxed_clients_draw_the_session_in_their_middle() {
+    let clients = clients(ClientSizing::Letterbox, &[(10, 20), (30, 40)]);
+    assert_eq!(
+        clients.render_for(
+            1,
+            "\u{1b}[2J\u{1b}[1;1H\u{1b}[mabc\u{1b}[?25h\u{1b}[3;7H\u{1b}[H"
+        ),
+        "\u{1b}[2J\u{1b}[11;11H\u{1b}[mabc\u{1b}[?25h\u{1b}[13;17H\u{1b}[11;11H"
+    );
+    assert_eq!(
+        clients.render_for(0, "\u{1b}[1;1H"),
+        "\u{1b}[1;1H",
+        "the smallest client has no margins"
+    );
+}
+
+#[test]
+fn the_mouse_of_letterboxed_clients_points_into_the_session() {
+    let mut clients = clients(ClientSizing::Letterbox, &[(10, 20), (30, 40)]);
+    assert_eq!(
+        clients.action_in_session(
+            1,
+            Action::LeftClick(Position::new(12, 15), Default::default())
+        ),
+        Action::LeftClick(Position::new(2, 5), Default::default())
+    );
+    assert_eq!(
+        clients.action_in_session(1, Action::ScrollUp),
+        Action::ScrollUp
+    );
+}
+
+#[test]
+fn guests_only_move_their_focus() {
+    let mut clients = clients(ClientSizing::Smallest, &[(10, 20), (30, 40)]);
+    clients.set_read_only(1, true);
+    let write = Action::Write(b"ls".to_vec());
+    assert!(clients.allows(0, &write));
+    assert!(!clients.allows(1, &write));
+    assert!(!clients.allows(1, &Action::Quit));
+    assert!(clients.allows(1, &Action::Detach(None)));
+    assert!(!clients.allows(1, &Action::Detach(Some(0))));
+    assert!(!clients.allows(1, &Action::DetachOtherClients));
+    assert!(clients.allows(1, &Action::GoToNextTab));
+    assert!(clients.allows(1, &Action::MoveFocus(Direction::Left)));
+}
+
+#[test]
+fn the_client_of_the_latest_action_is_the_active_one() {
+    let mut clients = clients(ClientSizing::Smallest, &[(10, 20), (30, 40)]);
+    assert_eq!(clients.active(), None);
+    clients.action_in_session(1, Action::ScrollUp);
+    assert_eq!(clients.active(), Some(1));
+    clients.detach(1);
+    assert_eq!(clients.active(), None);
+}
+
+#[test]
+fn clients_that_stop_pinging_the_server_go_silent() {
+    let mut clients = clients(ClientSizing::Smallest, &[(10, 20), (30, 40)]);
+    let attached_at = Instant::now();
+    assert!(clients.silent(attached_at).is_empty());
+    assert_eq!(clients.silent(attached_at + CLIENT_TIMEOUT), vec![0, 1]);
+    clients.heard_from(1);
+    assert_eq!(
+        clients.silent(attached_at + CLIENT_TIMEOUT),
+        vec![0],
+        "the client pinged since"
+    );
+    clients.detach(0);
+    assert!(clients.silent(attached_at + CLIENT_TIMEOUT).is_empty());
+}
@@ -0,0 +1,41 @@
+use super::*;
+
+fn command(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn vim_opens_the_file_with_its_spaces_escaped() {
+    let keys = open_file_keys(
+        "vim",
+        &command(&["vim", "notes.md"]),
+        Path::new("/tmp/a b.md"),
+    );
+    assert_eq!(keys, Some(b"\x1b:e /tmp/a\\ b.md\r".to_vec()));
+}
+
+#[test]
+fn the_editor_is_recognized_by_its_program_name() {
+    let keys = open_file_keys(
+        "nvim -u NONE",
+        &command(&["/usr/bin/nvim"]),
+        Path::new("/tmp/a.rs"),
+    );
+    assert_eq!(keys, Some(b"\x1b:e /tmp/a.rs\r".to_vec()));
+}
+
+#[test]
+fn other_programs_are_not_reused() {
+    let file = Path::new("/tmp/a.rs");
+    assert_eq!(
+        open_file_keys("vim", &command(&["nvim"]), file),
+        None,
+        "not the editor of the session"
+    );
+    assert_eq!(
+        open_file_keys("nano", &command(&["nano"]), file),
+        None,
+        "an editor zellij can't drive"
+    );
+    assert_eq!(open_file_keys("vim", &[], file), None);
+}
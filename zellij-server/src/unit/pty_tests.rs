@@ -0,0 +1,397 @@
+use super::{pty_thread_main, Pty, PtyInstruction, ShutdownPolicy};
+use crate::{
+    os_input_output::{async_trait, AsyncReader, ChildId, ClientId, Pid, ServerOsApi},
+    panes::PaneId,
+    thread_bus::Bus,
+    ServerInstruction,
+};
+use std::collections::{HashMap, HashSet};
+use std::os::unix::io::RawFd;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+use zellij_utils::{
+    async_std::task,
+    channels::{self, ChannelWithContext, SenderWithContext},
+    errors::ErrorContext,
+    input::{
+        command::{RunCommand, TerminalAction},
+        layout::{LayoutFromYaml, Run, TabLayout},
+        options::ShutdownSignal,
+    },
+    interprocess::local_socket::LocalSocketStream,
+    ipc::{ClientToServerMsg, ServerToClientMsg},
+    nix::{self, sys::signal::Signal},
+    serde_yaml,
+    zellij_tile::data::Palette,
+};
+
+struct EmptyReader;
+
+#[async_trait]
+impl AsyncReader for EmptyReader {
+    async fn read(&mut self, _buf: &mut [u8]) -> Result<usize, std::io::Error> {
+        Ok(0)
+    }
+}
+
+/// Terminals whose processes only exit when they get one of the signals they are given, the
+/// signals sent and the terminals spawned are recorded.
+#[derive(Clone, Default)]
+struct FakeInputOutput {
+    spawned: Arc<Mutex<Vec<TerminalAction>>>,
+    cwds: HashMap<Pid, PathBuf>,
+    foreground_groups: HashMap<RawFd, Pid>,
+    // the signals each process exits on, SIGKILL always does it
+    exits_on: HashMap<Pid, Vec<Signal>>,
+    // the processes exiting along with another one, eg. the first process of a pane with its
+    // shell
+    exits_with: HashMap<Pid, Pid>,
+    running: Arc<Mutex<HashSet<Pid>>>,
+    signals: Arc<Mutex<Vec<(Pid, Signal)>>>,
+}
+
+impl FakeInputOutput {
+    fn start(&mut self, pid: i32, exits_on: &[Signal]) {
+        let pid = Pid::from_raw(pid);
+        self.running.lock().unwrap().insert(pid);
+        self.exits_on.insert(pid, exits_on.to_vec());
+    }
+}
+
+impl ServerOsApi for FakeInputOutput {
+    fn set_terminal_size_using_fd(&self, _fd: RawFd, _cols: u16, _rows: u16) {}
+    fn spawn_terminal(&self, terminal_action: TerminalAction) -> (RawFd, ChildId) {
+        let mut spawned = self.spawned.lock().unwrap();
+        spawned.push(terminal_action);
+        let id = 100 + spawned.len() as RawFd;
+        let child_id = ChildId {
+            primary: Pid::from_raw(id),
+            shell: None,
+        };
+        (id, child_id)
+    }
+    fn read_from_tty_stdout(&self, _fd: RawFd, _buf: &mut [u8]) -> Result<usize, nix::Error> {
+        unimplemented!()
+    }
+    fn async_file_reader(&self, _fd: RawFd) -> Box<dyn AsyncReader> {
+        Box::new(EmptyReader)
+    }
+    fn write_to_tty_stdin(&self, _fd: RawFd, _buf: &[u8]) -> Result<usize, nix::Error> {
+        unimplemented!()
+    }
+    fn tcdrain(&self, _fd: RawFd) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn box_clone(&self) -> Box<dyn ServerOsApi> {
+        Box::new((*self).clone())
+    }
+    fn force_kill(&self, _pid: Pid) -> Result<(), nix::Error> {
+        Ok(())
+    }
+    fn kill(&self, _pid: Pid) -> Result<(), nix::Error> {
+        Ok(())
+    }
+    fn recv_from_client(&self) -> Option<(ClientToServerMsg, ErrorContext)> {
+        unimplemented!()
+    }
+    fn send_to_client(&self, _client_id: ClientId, _msg: ServerToClientMsg) {
+        unimplemented!()
+    }
+    fn add_client_sender(&self) -> ClientId {
+        unimplemented!()
+    }
+    fn send_to_temp_client(&self, _msg: ServerToClientMsg) {
+        unimplemented!()
+    }
+    fn remove_client_sender(&self, _client_id: ClientId) {
+        unimplemented!()
+    }
+    fn update_receiver(&mut self, _stream: LocalSocketStream) {
+        unimplemented!()
+    }
+    fn load_palette(&self) -> Palette {
+        unimplemented!()
+    }
+    fn get_cwd(&self, pid: Pid) -> Option<PathBuf> {
+        self.cwds.get(&pid).cloned()
+    }
+    fn get_foreground_command(&self, _fd: RawFd) -> Option<Vec<String>> {
+        None
+    }
+    fn get_foreground_process_group(&self, fd: RawFd) -> Option<Pid> {
+        self.foreground_groups.get(&fd).copied()
+    }
+    fn send_signal(&self, pid: Pid, signal: Signal) {
+        self.signals.lock().unwrap().push((pid, signal));
+        let exits = signal == Signal::SIGKILL
+            || self
+                .exits_on
+                .get(&pid)
+                .map_or(false, |signals| signals.contains(&signal));
+        if exits {
+            let mut running = self.running.lock().unwrap();
+            running.remove(&pid);
+            for (process, parent) in self.exits_with.iter() {
+                if *parent == pid {
+                    running.remove(process);
+                }
+            }
+        }
+    }
+    fn is_running(&self, pid: Pid) -> bool {
+        self.running.lock().unwrap().contains(&pid)
+    }
+}
+
+fn shutdown_policy(grace_period: Duration) -> ShutdownPolicy {
+    ShutdownPolicy {
+        signal: ShutdownSignal::Hup,
+        grace_period,
+    }
+}
+
+fn create_pty(
+    os_input: &FakeInputOutput,
+    receivers: Vec<channels::Receiver<(PtyInstruction, ErrorContext)>>,
+) -> Pty {
+    let mut bus: Bus<PtyInstruction> = Bus::new(
+        receivers,
+        None,
+        None,
+        None,
+        None,
+        Some(Box::new(os_input.clone())),
+    );
+    bus.senders = bus.senders.silently_fail_on_send();
+    Pty::new(
+        bus,
+        false,
+        shutdown_policy(Duration::from_millis(100)),
+        true,
+    )
+}
+
+// a terminal whose shell has the pid `shell`, as if it was spawned
+fn add_terminal(pty: &mut Pty, id: RawFd, shell: Option<Pid>, terminal_action: TerminalAction) {
+    let child_id = ChildId {
+        primary: Pid::from_raw(id),
+        shell,
+    };
+    pty.id_to_child_pid.insert(id, child_id);
+    pty.id_to_terminal_action.insert(id, terminal_action);
+    pty.task_handles.insert(id, task::spawn(async {}));
+}
+
+fn shell() -> TerminalAction {
+    TerminalAction::RunCommand(Default::default())
+}
+
+// runs the pty thread until it has handled `instructions`
+fn run_pty_thread(
+    pty: Pty,
+    to_pty: SenderWithContext<PtyInstruction>,
+    instructions: Vec<PtyInstruction>,
+) -> Vec<String> {
+    for instruction in instructions {
+        to_pty.send(instruction).unwrap();
+    }
+    to_pty.send(PtyInstruction::Exit).unwrap();
+    pty_thread_main(pty, LayoutFromYaml::default(), None, vec![])
+}
+
+#[test]
+fn reported_working_directory_is_where_new_panes_start() {
+    let reported_dir = tempdir().unwrap();
+    let os_input = FakeInputOutput::default();
+    let (to_pty, pty_receiver): ChannelWithContext<PtyInstruction> = channels::unbounded();
+    let to_pty = SenderWithContext::new(to_pty);
+    let mut pty = create_pty(&os_input, vec![pty_receiver]);
+    add_terminal(&mut pty, 1, Some(Pid::from_raw(10)), shell());
+    let dump = reported_dir.path().join("layout.yaml");
+
+    run_pty_thread(
+        pty,
+        to_pty,
+        vec![
+            PtyInstruction::UpdateActivePane(Some(PaneId::Terminal(1))),
+            PtyInstruction::UpdatePaneCwd(1, reported_dir.path().to_path_buf()),
+            PtyInstruction::SpawnTerminal(None, None),
+            PtyInstruction::DumpLayout(
+                dump.clone(),
+                vec![(TabLayout::default(), vec![PaneId::Terminal(1)])],
+                None,
+            ),
+        ],
+    );
+
+    match &os_input.spawned.lock().unwrap()[..] {
+        [TerminalAction::RunCommand(command)] => assert_eq!(
+            command.cwd.as_deref(),
+            Some(reported_dir.path()),
+            "The new pane starts where the focused one is"
+        ),
+        spawned => panic!("unexpected terminals spawned: {:?}", spawned),
+    }
+    let dumped: LayoutFromYaml =
+        serde_yaml::from_str(&std::fs::read_to_string(&dump).unwrap()).unwrap();
+    match &dumped.tabs[0].run {
+        Some(Run::Command(command)) => assert_eq!(
+            command.cwd.as_deref(),
+            Some(reported_dir.path()),
+            "The pane of the layout starts where its shell is"
+        ),
+        run => panic!("unexpected run in the dumped layout: {:?}", run),
+    }
+}
+
+#[test]
+fn reported_working_directory_that_is_gone_is_left_out() {
+    let mut os_input = FakeInputOutput::default();
+    os_input
+        .cwds
+        .insert(Pid::from_raw(10), PathBuf::from("/shell/cwd"));
+    let mut pty = create_pty(&os_input, vec![]);
+    add_terminal(&mut pty, 1, Some(Pid::from_raw(10)), shell());
+    pty.id_to_cwd
+        .insert(1, PathBuf::from("/no/such/directory/on/this/machine"));
+
+    assert_eq!(
+        pty.terminal_cwd(1),
+        Some(PathBuf::from("/shell/cwd")),
+        "The working directory of the shell process is used instead"
+    );
+}
+
+#[test]
+fn closing_a_pane_unblocks_the_input_thread_of_the_client_that_asked_only() {
+    let os_input = FakeInputOutput::default();
+    let (to_pty, pty_receiver): ChannelWithContext<PtyInstruction> = channels::unbounded();
+    let to_pty = SenderWithContext::new(to_pty);
+    let (to_server, server_receiver) = channels::unbounded();
+    let mut pty = create_pty(&os_input, vec![pty_receiver]);
+    pty.bus.senders.to_server = Some(SenderWithContext::new(to_server));
+    add_terminal(&mut pty, 1, None, shell());
+    add_terminal(&mut pty, 2, None, shell());
+
+    run_pty_thread(
+        pty,
+        to_pty,
+        vec![
+            PtyInstruction::FromClient(7, Box::new(PtyInstruction::ClosePane(PaneId::Terminal(1)))),
+            PtyInstruction::ClosePane(PaneId::Terminal(2)),
+        ],
+    );
+
+    let unblocked: Vec<Option<ClientId>> = server_receiver
+        .try_iter()
+        .filter_map(|(instruction, _)| match instruction {
+            ServerInstruction::UnblockInputThread(client_id) => Some(client_id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        unblocked,
+        vec![Some(7), None],
+        "Only the client that closed the pane is unblocked"
+    );
+}
+
+#[test]
+fn panes_still_running_after_the_grace_period_are_killed_and_reported() {
+    let mut os_input = FakeInputOutput::default();
+    // a command that exits on SIGHUP
+    os_input.start(1, &[Signal::SIGHUP]);
+    // a command that ignores it
+    os_input.start(2, &[]);
+    // a shell exiting on SIGHUP, running a program in the foreground that exits on SIGTERM
+    os_input.start(3, &[]);
+    os_input.start(31, &[Signal::SIGHUP]);
+    os_input.start(-30, &[Signal::SIGTERM]);
+    os_input
+        .exits_with
+        .insert(Pid::from_raw(3), Pid::from_raw(31));
+    os_input.foreground_groups.insert(3, Pid::from_raw(30));
+    let (to_pty, pty_receiver): ChannelWithContext<PtyInstruction> = channels::unbounded();
+    let to_pty = SenderWithContext::new(to_pty);
+    let mut pty = create_pty(&os_input, vec![pty_receiver]);
+    pty.shutdown.signal = ShutdownSignal::Term;
+    let command = |command: &str| {
+        TerminalAction::RunCommand(RunCommand {
+            command: PathBuf::from(command),
+            ..Default::default()
+        })
+    };
+    add_terminal(&mut pty, 1, None, command("tail"));
+    add_terminal(&mut pty, 2, None, command("stubborn"));
+    add_terminal(&mut pty, 3, Some(Pid::from_raw(31)), shell());
+
+    let started_at = Instant::now();
+    let killed = run_pty_thread(pty, to_pty, vec![]);
+
+    assert_eq!(killed, vec!["pane running \"stubborn\"".to_string()]);
+    assert!(
+        started_at.elapsed() >= Duration::from_millis(100),
+        "The pane is killed once the grace period is over"
+    );
+    assert_eq!(
+        os_input.signals.lock().unwrap()[..],
+        [
+            (Pid::from_raw(1), Signal::SIGHUP),
+            (Pid::from_raw(2), Signal::SIGHUP),
+            (Pid::from_raw(-30), Signal::SIGTERM),
+            (Pid::from_raw(31), Signal::SIGHUP),
+            (Pid::from_raw(2), Signal::SIGKILL),
+        ],
+        "The programs get the signal of the session, their shells SIGHUP once they are gone"
+    );
+}
+
+#[test]
+fn file_written_for_a_pane_is_deleted_once_the_pane_closes() {
+    let temp_dir = tempdir().unwrap();
+    let scrollback = temp_dir.path().join("scrollback-1.txt");
+    std::fs::write(&scrollback, "scrollback").unwrap();
+    let os_input = FakeInputOutput::default();
+    let (to_pty, pty_receiver): ChannelWithContext<PtyInstruction> = channels::unbounded();
+    let to_pty = SenderWithContext::new(to_pty);
+    let pty = create_pty(&os_input, vec![pty_receiver]);
+
+    // the first terminal spawned gets the id 101
+    run_pty_thread(
+        pty,
+        to_pty,
+        vec![
+            PtyInstruction::SpawnTerminalOnTempFile(
+                TerminalAction::OpenFile(scrollback.clone()),
+                None,
+            ),
+            PtyInstruction::ClosePane(PaneId::Terminal(101)),
+        ],
+    );
+    assert!(!scrollback.exists());
+}
+
+#[test]
+fn file_written_for_panes_is_kept_until_the_last_of_them_closes() {
+    let temp_dir = tempdir().unwrap();
+    let scrollback = temp_dir.path().join("scrollback-1.txt");
+    std::fs::write(&scrollback, "scrollback").unwrap();
+    let os_input = FakeInputOutput::default();
+    let mut pty = create_pty(&os_input, vec![]);
+    for id in [1, 2].iter() {
+        add_terminal(
+            &mut pty,
+            *id,
+            None,
+            TerminalAction::OpenFile(scrollback.clone()),
+        );
+        pty.id_to_temp_file.insert(*id, scrollback.clone());
+    }
+
+    pty.close_pane(PaneId::Terminal(1));
+    assert!(scrollback.exists(), "Another pane still has the file open");
+    pty.shut_down();
+    assert!(!scrollback.exists(), "The session quitting deletes it");
+}
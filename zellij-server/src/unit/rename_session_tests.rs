@@ -0,0 +1,91 @@
+use super::rename_session_files;
+
+use std::fs;
+use tempfile::tempdir;
+use zellij_utils::{action_token::token_path, input::bundle::resurrection_path};
+
+// the resurrection files are in the cache of the user, so the names don't clash with theirs
+fn session_name(name: &str) -> String {
+    format!("rename-session-test-{}-{}", std::process::id(), name)
+}
+
+#[test]
+fn renaming_moves_the_socket_token_and_resurrection_file() {
+    let socket_dir = tempdir().unwrap();
+    let (old_name, name) = (session_name("old"), session_name("new"));
+    let socket_path = socket_dir.path().join(&old_name);
+    fs::write(&socket_path, "").unwrap();
+    fs::write(token_path(&socket_path), "token").unwrap();
+    fs::create_dir_all(resurrection_path(&old_name).parent().unwrap()).unwrap();
+    fs::write(resurrection_path(&old_name), "").unwrap();
+
+    let new_socket_path = rename_session_files(&socket_path, &old_name, &name);
+    let resurrected = resurrection_path(&name).exists();
+    let _ = fs::remove_file(resurrection_path(&name));
+    let _ = fs::remove_file(resurrection_path(&old_name));
+
+    assert_eq!(new_socket_path, Ok(socket_dir.path().join(&name)));
+    assert!(!socket_path.exists());
+    assert!(socket_dir.path().join(&name).exists());
+    assert_eq!(
+        fs::read_to_string(token_path(&socket_dir.path().join(&name))).unwrap(),
+        "token"
+    );
+    assert!(resurrected, "The session is resurrected under its new name");
+}
+
+#[test]
+fn renaming_to_the_name_of_another_session_is_refused() {
+    let socket_dir = tempdir().unwrap();
+    let (old_name, name) = (session_name("mine"), session_name("theirs"));
+    let socket_path = socket_dir.path().join(&old_name);
+    fs::write(&socket_path, "").unwrap();
+    fs::write(token_path(&socket_path), "mine").unwrap();
+    fs::write(socket_dir.path().join(&name), "").unwrap();
+    fs::write(token_path(&socket_dir.path().join(&name)), "theirs").unwrap();
+
+    assert!(rename_session_files(&socket_path, &old_name, &name).is_err());
+    assert!(socket_path.exists(), "The session keeps its socket");
+    assert_eq!(
+        fs::read_to_string(token_path(&socket_path)).unwrap(),
+        "mine"
+    );
+    assert_eq!(
+        fs::read_to_string(token_path(&socket_dir.path().join(&name))).unwrap(),
+        "theirs",
+        "The other session keeps its token"
+    );
+}
+
+#[test]
+fn renaming_to_the_name_of_a_resurrectable_session_is_refused() {
+    let socket_dir = tempdir().unwrap();
+    let (old_name, name) = (session_name("running"), session_name("exited"));
+    let socket_path = socket_dir.path().join(&old_name);
+    fs::write(&socket_path, "").unwrap();
+    fs::create_dir_all(resurrection_path(&name).parent().unwrap()).unwrap();
+    fs::write(resurrection_path(&name), "").unwrap();
+
+    let renamed = rename_session_files(&socket_path, &old_name, &name);
+    let _ = fs::remove_file(resurrection_path(&name));
+
+    assert!(renamed.is_err());
+    assert!(socket_path.exists(), "The session keeps its socket");
+}
+
+#[test]
+fn names_that_are_not_file_names_are_refused() {
+    let socket_dir = tempdir().unwrap();
+    let old_name = session_name("named");
+    let socket_path = socket_dir.path().join(&old_name);
+    fs::write(&socket_path, "").unwrap();
+
+    assert!(rename_session_files(&socket_path, &old_name, "").is_err());
+    assert!(rename_session_files(&socket_path, &old_name, "../elsewhere").is_err());
+    assert!(socket_path.exists());
+    assert_eq!(
+        rename_session_files(&socket_path, &old_name, &old_name),
+        Ok(socket_path.clone()),
+        "Keeping the name changes nothing"
+    );
+}
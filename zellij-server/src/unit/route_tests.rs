@@ -0,0 +1,333 @@
+use super::{route_thread_main, ActionRateLimiter, WRONG_TOKEN};
+use crate::{
+    clients::Clients,
+    os_input_output::{AsyncReader, ChildId, ClientId, Pid, ServerOsApi},
+    thread_bus::ThreadSenders,
+    ServerInstruction, SessionMetaData, SessionState,
+};
+use std::os::unix::io::RawFd;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use zellij_utils::{
+    channels::{self, ChannelWithContext, SenderWithContext},
+    errors::ErrorContext,
+    input::{actions::Action, command::TerminalAction, options::Options},
+    interprocess::local_socket::LocalSocketStream,
+    ipc::{ActionResult, ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg},
+    messages::Locale,
+    nix::{self, sys::signal::Signal},
+    pane_size::Size,
+    zellij_tile::data::{Palette, PluginCapabilities},
+};
+
+const TOKEN: &str = "the-token-of-the-session";
+
+/// A connection sending the given messages, keeping what is sent back to it.
+#[derive(Clone, Default)]
+struct FakeConnection {
+    from_client: Arc<Mutex<Vec<ClientToServerMsg>>>,
+    to_temp_client: Arc<Mutex<Vec<ServerToClientMsg>>>,
+    to_clients: Arc<Mutex<Vec<(ClientId, ServerToClientMsg)>>>,
+}
+
+impl ServerOsApi for FakeConnection {
+    fn set_terminal_size_using_fd(&self, _fd: RawFd, _cols: u16, _rows: u16) {
+        unimplemented!()
+    }
+    fn spawn_terminal(&self, _terminal_action: TerminalAction) -> (RawFd, ChildId) {
+        unimplemented!()
+    }
+    fn read_from_tty_stdout(&self, _fd: RawFd, _buf: &mut [u8]) -> Result<usize, nix::Error> {
+        unimplemented!()
+    }
+    fn async_file_reader(&self, _fd: RawFd) -> Box<dyn AsyncReader> {
+        unimplemented!()
+    }
+    fn write_to_tty_stdin(&self, _fd: RawFd, _buf: &[u8]) -> Result<usize, nix::Error> {
+        unimplemented!()
+    }
+    fn tcdrain(&self, _fd: RawFd) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn box_clone(&self) -> Box<dyn ServerOsApi> {
+        Box::new((*self).clone())
+    }
+    fn force_kill(&self, _pid: Pid) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn kill(&self, _pid: Pid) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn recv_from_client(&self) -> Option<(ClientToServerMsg, ErrorContext)> {
+        let mut from_client = self.from_client.lock().unwrap();
+        if from_client.is_empty() {
+            None
+        } else {
+            Some((from_client.remove(0), ErrorContext::new()))
+        }
+    }
+    fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) {
+        self.to_clients.lock().unwrap().push((client_id, msg));
+    }
+    fn add_client_sender(&self) -> ClientId {
+        1
+    }
+    fn send_to_temp_client(&self, msg: ServerToClientMsg) {
+        self.to_temp_client.lock().unwrap().push(msg);
+    }
+    fn remove_client_sender(&self, _client_id: ClientId) {
+        unimplemented!()
+    }
+    fn update_receiver(&mut self, _stream: LocalSocketStream) {
+        unimplemented!()
+    }
+    fn load_palette(&self) -> Palette {
+        unimplemented!()
+    }
+    fn get_cwd(&self, _pid: Pid) -> Option<PathBuf> {
+        unimplemented!()
+    }
+    fn get_foreground_command(&self, _fd: RawFd) -> Option<Vec<String>> {
+        unimplemented!()
+    }
+    fn get_foreground_process_group(&self, _fd: RawFd) -> Option<Pid> {
+        unimplemented!()
+    }
+    fn send_signal(&self, _pid: Pid, _signal: Signal) {
+        unimplemented!()
+    }
+    fn is_running(&self, _pid: Pid) -> bool {
+        unimplemented!()
+    }
+}
+
+/// Routes `message` from a new connection to a session without tabs, returning what was sent
+/// back to the connection and the instructions the server got.
+fn route(message: ClientToServerMsg) -> (Vec<ServerToClientMsg>, Vec<ServerInstruction>) {
+    let connection = FakeConnection::default();
+    connection.from_client.lock().unwrap().push(message);
+    let (to_server, server_receiver): ChannelWithContext<ServerInstruction> = channels::unbounded();
+    route_thread_main(
+        Arc::new(RwLock::new(None)),
+        Arc::new(RwLock::new(SessionState::Attached)),
+        Arc::new(RwLock::new(Clients::default())),
+        Box::new(connection.clone()),
+        SenderWithContext::new(to_server),
+        Arc::new(TOKEN.to_string()),
+    );
+    let to_temp_client = connection.to_temp_client.lock().unwrap().clone();
+    let instructions = server_receiver
+        .try_iter()
+        .map(|(instruction, _)| instruction)
+        .collect();
+    (to_temp_client, instructions)
+}
+
+/// Routes `messages` from a new connection to a session with the clients `client_ids` attached,
+/// returning the connection and the instructions the server got.
+fn route_in_session(
+    messages: Vec<ClientToServerMsg>,
+    client_ids: &[ClientId],
+) -> (FakeConnection, Vec<ServerInstruction>) {
+    let connection = FakeConnection::default();
+    connection.from_client.lock().unwrap().extend(messages);
+    let (to_server, server_receiver): ChannelWithContext<ServerInstruction> = channels::unbounded();
+    let mut clients = Clients::default();
+    for client_id in client_ids {
+        clients.attach(*client_id, Size::default());
+    }
+    let session = SessionMetaData {
+        senders: ThreadSenders::default().silently_fail_on_send(),
+        capabilities: PluginCapabilities::default(),
+        palette: Palette::default(),
+        default_shell: None,
+        locale: Locale::default(),
+        cli_action_limiter: None,
+        screen_thread: None,
+        pty_thread: None,
+        wasm_thread: None,
+    };
+    route_thread_main(
+        Arc::new(RwLock::new(Some(session))),
+        Arc::new(RwLock::new(SessionState::Attached)),
+        Arc::new(RwLock::new(clients)),
+        Box::new(connection.clone()),
+        SenderWithContext::new(to_server),
+        Arc::new(TOKEN.to_string()),
+    );
+    let instructions = server_receiver
+        .try_iter()
+        .map(|(instruction, _)| instruction)
+        .collect();
+    (connection, instructions)
+}
+
+fn attach(token: Option<&str>) -> ClientToServerMsg {
+    ClientToServerMsg::AttachClient(
+        ClientAttributes::default(),
+        false,
+        Options::default(),
+        token.map(String::from),
+    )
+}
+
+#[test]
+fn cli_actions_without_the_right_token_are_refused() {
+    for token in [None, Some("a-wrong-token")].iter() {
+        let (replies, instructions) = route(ClientToServerMsg::CliAction(
+            Action::NoOp,
+            token.map(String::from),
+        ));
+        match &replies[..] {
+            [ServerToClientMsg::ActionResult(ActionResult::Refused(reason))] => {
+                assert_eq!(reason, WRONG_TOKEN)
+            }
+            replies => panic!("unexpected replies for {:?}: {:?}", token, replies),
+        }
+        assert!(instructions.is_empty(), "Nothing reaches the session");
+    }
+}
+
+#[test]
+fn cli_action_with_the_right_token_is_let_through() {
+    let (replies, _) = route(ClientToServerMsg::CliAction(
+        Action::NoOp,
+        Some(TOKEN.to_string()),
+    ));
+    assert!(
+        matches!(
+            &replies[..],
+            [ServerToClientMsg::ActionResult(ActionResult::Empty)]
+        ),
+        "unexpected replies: {:?}",
+        replies
+    );
+}
+
+#[test]
+fn clients_without_the_right_token_cannot_attach() {
+    for token in [None, Some("a-wrong-token")].iter() {
+        let (replies, instructions) = route(attach(*token));
+        match &replies[..] {
+            [ServerToClientMsg::Exit(ExitReason::Error(reason))] => assert_eq!(reason, WRONG_TOKEN),
+            replies => panic!("unexpected replies for {:?}: {:?}", token, replies),
+        }
+        assert!(instructions.is_empty(), "The client isn't attached");
+    }
+}
+
+#[test]
+fn client_with_the_right_token_attaches() {
+    let (replies, instructions) = route(attach(Some(TOKEN)));
+    assert!(replies.is_empty(), "unexpected replies: {:?}", replies);
+    assert!(
+        matches!(
+            instructions.first(),
+            Some(ServerInstruction::AttachClient(_, false, _, 1))
+        ),
+        "The client is attached"
+    );
+}
+
+#[test]
+fn detaching_a_client_that_is_not_attached_is_refused() {
+    let (connection, instructions) = route_in_session(
+        vec![ClientToServerMsg::CliAction(
+            Action::Detach(Some(7)),
+            Some(TOKEN.to_string()),
+        )],
+        &[1, 2],
+    );
+    match &connection.to_temp_client.lock().unwrap()[..] {
+        [ServerToClientMsg::ActionResult(ActionResult::Refused(reason))] => {
+            assert_eq!(reason, "There is no client 7 to detach")
+        }
+        replies => panic!("unexpected replies: {:?}", replies),
+    }
+    assert!(instructions.is_empty(), "No client is detached");
+
+    let (connection, instructions) = route_in_session(
+        vec![
+            attach(Some(TOKEN)),
+            ClientToServerMsg::Action(Action::Detach(Some(7))),
+        ],
+        &[1, 2],
+    );
+    assert!(
+        matches!(
+            &connection.to_clients.lock().unwrap()[..],
+            [(1, ServerToClientMsg::Diagnostic(_))]
+        ),
+        "The client asking is told why"
+    );
+    assert!(
+        !instructions.iter().any(|instruction| matches!(
+            instruction,
+            ServerInstruction::DetachSession(_, ExitReason::DetachedByOtherClient)
+        )),
+        "No client is detached"
+    );
+}
+
+#[test]
+fn detaching_another_client_tells_it_so() {
+    let (connection, instructions) = route_in_session(
+        vec![ClientToServerMsg::CliAction(
+            Action::Detach(Some(2)),
+            Some(TOKEN.to_string()),
+        )],
+        &[1, 2],
+    );
+    assert!(matches!(
+        &connection.to_temp_client.lock().unwrap()[..],
+        [ServerToClientMsg::ActionResult(ActionResult::Empty)]
+    ));
+    assert!(matches!(
+        &instructions[..],
+        [ServerInstruction::DetachSession(
+            Some(2),
+            ExitReason::DetachedByOtherClient
+        )]
+    ));
+}
+
+#[test]
+fn actions_beyond_the_burst_are_rejected() {
+    let mut limiter = ActionRateLimiter::new(10, 3);
+    let now = Instant::now();
+    for _ in 0..3 {
+        assert!(limiter.try_take_at(now), "The burst is let through");
+    }
+    assert!(
+        !limiter.try_take_at(now),
+        "The action beyond it is rejected"
+    );
+}
+
+#[test]
+fn budget_refills_at_the_rate() {
+    let mut limiter = ActionRateLimiter::new(10, 3);
+    let now = Instant::now();
+    for _ in 0..3 {
+        limiter.try_take_at(now);
+    }
+    let later = now + Duration::from_millis(100);
+    assert!(
+        limiter.try_take_at(later),
+        "One action is back after a tenth of a second"
+    );
+    assert!(!limiter.try_take_at(later), "But no more than one");
+}
+
+#[test]
+fn budget_does_not_grow_beyond_the_burst() {
+    let mut limiter = ActionRateLimiter::new(10, 2);
+    let much_later = Instant::now() + Duration::from_secs(60);
+    assert!(limiter.try_take_at(much_later));
+    assert!(limiter.try_take_at(much_later));
+    assert!(
+        !limiter.try_take_at(much_later),
+        "An idle minute only refills the burst"
+    );
+}
@@ -1,14 +1,21 @@
-use super::{Screen, ScreenInstruction};
-use crate::zellij_tile::data::{ModeInfo, Palette};
+use super::{Screen, ScreenInstruction, SessionLimits};
+use crate::tab::{
+    AutoSplitOptions, PaneClipboard, PaneFrameOptions, PaneGridOptions, PasteOptions, Tab,
+};
+use crate::zellij_tile::data::{Event, InputMode, ModeInfo, Palette};
 use crate::{
-    os_input_output::{AsyncReader, ChildId, Pid, ServerOsApi},
+    os_input_output::{AsyncReader, ChildId, ClientId, Pid, ServerOsApi},
+    panes::{HistoryEntry, HistoryItem, PaneId},
     thread_bus::Bus,
-    SessionState,
+    wasm_vm::PluginInstruction,
+    ServerInstruction, SessionState,
 };
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
 use zellij_utils::input::command::TerminalAction;
 use zellij_utils::input::layout::LayoutTemplate;
+use zellij_utils::input::options::{Options, SmallPanesPolicy};
 use zellij_utils::pane_size::Size;
 
 use std::os::unix::io::RawFd;
@@ -17,13 +24,16 @@ use zellij_utils::ipc::ClientAttributes;
 use zellij_utils::nix;
 
 use zellij_utils::{
+    channels::{self, SenderWithContext},
     errors::ErrorContext,
     interprocess::local_socket::LocalSocketStream,
-    ipc::{ClientToServerMsg, ServerToClientMsg},
+    ipc::{ActionResult, ClientToServerMsg, ServerToClientMsg},
 };
 
-#[derive(Clone)]
-struct FakeInputOutput {}
+#[derive(Clone, Default)]
+struct FakeInputOutput {
+    written: Arc<Mutex<Vec<(RawFd, Vec<u8>)>>>,
+}
 
 impl ServerOsApi for FakeInputOutput {
     fn set_terminal_size_using_fd(&self, _fd: RawFd, _cols: u16, _rows: u16) {
@@ -38,11 +48,12 @@ impl ServerOsApi for FakeInputOutput {
     fn async_file_reader(&self, _fd: RawFd) -> Box<dyn AsyncReader> {
         unimplemented!()
     }
-    fn write_to_tty_stdin(&self, _fd: RawFd, _buf: &[u8]) -> Result<usize, nix::Error> {
-        unimplemented!()
+    fn write_to_tty_stdin(&self, fd: RawFd, buf: &[u8]) -> Result<usize, nix::Error> {
+        self.written.lock().unwrap().push((fd, buf.to_vec()));
+        Ok(buf.len())
     }
     fn tcdrain(&self, _fd: RawFd) -> Result<(), nix::Error> {
-        unimplemented!()
+        Ok(())
     }
     fn box_clone(&self) -> Box<dyn ServerOsApi> {
         Box::new((*self).clone())
@@ -53,19 +64,19 @@ impl ServerOsApi for FakeInputOutput {
     fn kill(&self, _pid: Pid) -> Result<(), nix::Error> {
         unimplemented!()
     }
-    fn recv_from_client(&self) -> (ClientToServerMsg, ErrorContext) {
+    fn recv_from_client(&self) -> Option<(ClientToServerMsg, ErrorContext)> {
         unimplemented!()
     }
-    fn send_to_client(&self, _msg: ServerToClientMsg) {
+    fn send_to_client(&self, _client_id: ClientId, _msg: ServerToClientMsg) {
         unimplemented!()
     }
-    fn add_client_sender(&self) {
+    fn add_client_sender(&self) -> ClientId {
         unimplemented!()
     }
     fn send_to_temp_client(&self, _msg: ServerToClientMsg) {
         unimplemented!()
     }
-    fn remove_client_sender(&self) {
+    fn remove_client_sender(&self, _client_id: ClientId) {
         unimplemented!()
     }
     fn update_receiver(&mut self, _stream: LocalSocketStream) {
@@ -77,11 +88,34 @@ impl ServerOsApi for FakeInputOutput {
     fn get_cwd(&self, _pid: Pid) -> Option<PathBuf> {
         unimplemented!()
     }
+    fn get_foreground_command(&self, _fd: RawFd) -> Option<Vec<String>> {
+        unimplemented!()
+    }
+    fn get_foreground_process_group(&self, _fd: RawFd) -> Option<Pid> {
+        unimplemented!()
+    }
+    fn send_signal(&self, _pid: Pid, _signal: nix::sys::signal::Signal) {
+        unimplemented!()
+    }
+    fn is_running(&self, _pid: Pid) -> bool {
+        unimplemented!()
+    }
 }
 
 fn create_new_screen(size: Size) -> Screen {
+    create_new_screen_with_limits(size, SessionLimits::default())
+}
+
+fn create_new_screen_with_limits(size: Size, limits: SessionLimits) -> Screen {
+    create_new_screen_with_os_input(size, limits, FakeInputOutput::default())
+}
+
+fn create_new_screen_with_os_input(
+    size: Size,
+    limits: SessionLimits,
+    fake_os_input: FakeInputOutput,
+) -> Screen {
     let mut bus: Bus<ScreenInstruction> = Bus::empty();
-    let fake_os_input = FakeInputOutput {};
     bus.os_input = Some(Box::new(fake_os_input));
     let client_attributes = ClientAttributes {
         size,
@@ -97,11 +131,19 @@ fn create_new_screen(size: Size) -> Screen {
         mode_info,
         session_state,
         false, // draw_pane_frames
+        false, // exit_fullscreen_on_focus_change
+        PaneClipboard::from_options(&Options::default()),
+        PaneGridOptions::from_options(&Options::default()),
+        PaneFrameOptions::from_options(&Options::default()),
+        PasteOptions::from_options(&Options::default()),
+        AutoSplitOptions::from_options(&Options::default()),
+        SmallPanesPolicy::default(),
+        limits,
     )
 }
 
 fn new_tab(screen: &mut Screen, pid: i32) {
-    screen.apply_layout(LayoutTemplate::default().into(), vec![pid]);
+    screen.apply_layout(LayoutTemplate::default().into(), vec![pid], String::new());
 }
 
 #[test]
@@ -142,6 +184,163 @@ pub fn switch_to_prev_tab() {
     );
 }
 
+#[test]
+pub fn each_client_has_its_own_active_tab() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    new_tab(&mut screen, 2);
+    screen.add_client(1, None);
+    screen.add_client(2, None);
+    screen.focus_client(2);
+    screen.switch_tab_prev();
+    screen.focus_client(1);
+    assert_eq!(
+        screen.get_active_tab().unwrap().position,
+        1,
+        "The first client stays on its tab"
+    );
+    screen.focus_client(2);
+    assert_eq!(
+        screen.get_active_tab().unwrap().position,
+        0,
+        "The second client is back on the tab it switched to"
+    );
+    screen.remove_client(2);
+    assert_eq!(
+        screen.get_active_tab().unwrap().position,
+        1,
+        "The screen has the focus of the client left"
+    );
+}
+
+#[test]
+pub fn a_client_joining_others_is_sent_their_mode() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+    let (to_server, server_receiver) = channels::unbounded();
+    screen.bus.senders.to_server = Some(SenderWithContext::new(to_server));
+    let mode = |mode| ModeInfo {
+        mode,
+        ..Default::default()
+    };
+    let modes_sent = || -> Vec<(ClientId, InputMode)> {
+        server_receiver
+            .try_iter()
+            .filter_map(|(instruction, _)| match instruction {
+                ServerInstruction::SwitchToMode(client_id, mode) => Some((client_id, mode)),
+                _ => None,
+            })
+            .collect()
+    };
+
+    new_tab(&mut screen, 1);
+    screen.add_client(1, Some(mode(InputMode::Locked)));
+    assert_eq!(
+        modes_sent(),
+        vec![],
+        "The first client starts in its own mode"
+    );
+
+    screen.add_client(2, Some(mode(InputMode::Normal)));
+    assert_eq!(
+        modes_sent(),
+        vec![(2, InputMode::Locked)],
+        "The second client is told the mode of the first one"
+    );
+    assert_eq!(screen.mode_info.mode, InputMode::Locked);
+}
+
+#[test]
+pub fn each_client_toggles_back_to_its_own_previous_tab() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    new_tab(&mut screen, 2);
+    new_tab(&mut screen, 3);
+    screen.add_client(1, None);
+    screen.add_client(2, None);
+    screen.focus_client(1);
+    screen.go_to_tab(1);
+    screen.focus_client(2);
+    screen.go_to_tab(2);
+    screen.go_to_tab(3);
+    screen.toggle_tab();
+    assert_eq!(screen.get_active_tab().unwrap().position, 1);
+    screen.focus_client(1);
+    screen.toggle_tab();
+    assert_eq!(
+        screen.get_active_tab().unwrap().position,
+        2,
+        "The first client goes back to the tab it was on before"
+    );
+    screen.go_to_tab(2);
+    screen.close_tab();
+    screen.focus_client(2);
+    assert_eq!(
+        screen.get_active_tab().unwrap().position,
+        1,
+        "The tab of the second client is closed, it is back on its previous one"
+    );
+}
+
+#[test]
+pub fn clients_attaching_keep_the_mode_of_those_attached() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+    let mode = |mode| ModeInfo {
+        mode,
+        ..Default::default()
+    };
+
+    new_tab(&mut screen, 1);
+    screen.add_client(1, Some(mode(InputMode::Locked)));
+    assert_eq!(
+        screen.mode_info.mode,
+        InputMode::Locked,
+        "The first client starts in its default mode"
+    );
+    screen.change_mode(mode(InputMode::Pane));
+    screen.add_client(2, Some(mode(InputMode::Normal)));
+    assert_eq!(
+        screen.mode_info.mode,
+        InputMode::Pane,
+        "The first client is still in pane mode"
+    );
+    assert_eq!(
+        screen.get_active_tab().unwrap().mode_info.mode,
+        InputMode::Pane
+    );
+}
+
+#[test]
+pub fn toggling_without_a_previous_tab_keeps_the_history() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    screen.toggle_tab();
+    assert_eq!(screen.get_active_tab().unwrap().position, 0);
+    assert_eq!(screen.tab_history, vec![None]);
+}
+
 #[test]
 pub fn switch_to_next_tab() {
     let size = Size {
@@ -434,3 +633,469 @@ pub fn toggle_to_previous_tab_delete() {
         "Tab history is invalid"
     );
 }
+
+#[test]
+pub fn report_new_pane_id() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    screen
+        .get_active_tab_mut()
+        .unwrap()
+        .new_pane(PaneId::Terminal(2));
+    let (reply, reply_receiver) = channels::bounded(1);
+    screen.report_new_pane(PaneId::Terminal(2), Some(reply));
+
+    assert_eq!(
+        reply_receiver.recv().unwrap(),
+        ActionResult::NewPane(2),
+        "Id of the new pane reported back"
+    );
+}
+
+#[test]
+pub fn report_no_pane_when_pane_was_not_opened() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    let (reply, reply_receiver) = channels::bounded(1);
+    screen.report_new_pane(PaneId::Terminal(2), Some(reply));
+
+    assert_eq!(
+        reply_receiver.recv().unwrap(),
+        ActionResult::Empty,
+        "Nothing reported for a pane that was never opened"
+    );
+}
+
+#[test]
+pub fn refuse_pane_beyond_max_panes_per_tab() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let limits = SessionLimits {
+        max_panes_per_tab: Some(2),
+        ..Default::default()
+    };
+    let mut screen = create_new_screen_with_limits(size, limits);
+
+    new_tab(&mut screen, 1);
+    screen.open_pane(PaneId::Terminal(2), None, Tab::vertical_split);
+    let (reply, reply_receiver) = channels::bounded(1);
+    screen.open_pane(PaneId::Terminal(3), Some(reply), Tab::vertical_split);
+
+    assert!(
+        matches!(reply_receiver.recv().unwrap(), ActionResult::Refused(_)),
+        "Third pane refused"
+    );
+    assert_eq!(
+        screen.get_active_tab().unwrap().terminal_pane_count(),
+        2,
+        "Tab still holds two panes"
+    );
+}
+
+#[test]
+pub fn refuse_tab_beyond_max_tabs() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let limits = SessionLimits {
+        max_tabs: Some(2),
+        ..Default::default()
+    };
+    let mut screen = create_new_screen_with_limits(size, limits);
+
+    assert!(
+        screen.tab_limit_reached(&[1]).is_none(),
+        "First tab allowed"
+    );
+    new_tab(&mut screen, 1);
+    assert!(
+        screen.tab_limit_reached(&[2]).is_none(),
+        "Second tab allowed"
+    );
+    new_tab(&mut screen, 2);
+    assert!(
+        screen.tab_limit_reached(&[3]).is_some(),
+        "Third tab refused"
+    );
+}
+
+#[test]
+pub fn focus_pane_in_another_tab() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    screen
+        .get_active_tab_mut()
+        .unwrap()
+        .new_pane(PaneId::Terminal(2));
+    new_tab(&mut screen, 3);
+    screen.focus_pane(PaneId::Terminal(1));
+
+    let active_tab = screen.get_active_tab().unwrap();
+    assert_eq!(
+        active_tab.position, 0,
+        "Switched to the tab holding the pane"
+    );
+    assert_eq!(
+        active_tab.get_active_pane_id(),
+        Some(PaneId::Terminal(1)),
+        "Pane focused in its tab"
+    );
+}
+
+#[test]
+pub fn close_last_pane_of_inactive_tab_by_id() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    new_tab(&mut screen, 2);
+    new_tab(&mut screen, 3);
+    screen.close_pane_with_id(PaneId::Terminal(2));
+
+    assert_eq!(screen.tabs.len(), 2, "Tab of the closed pane was closed");
+    assert_eq!(
+        screen.get_active_tab().unwrap().position,
+        1,
+        "Active tab did not change"
+    );
+    screen.switch_tab_prev();
+    assert_eq!(
+        screen.get_active_tab().unwrap().position,
+        0,
+        "Remaining tabs were renumbered"
+    );
+}
+
+#[test]
+pub fn close_inactive_tab_at_position() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    new_tab(&mut screen, 2);
+    new_tab(&mut screen, 3);
+    screen.close_tab_at(1);
+
+    assert_eq!(screen.tabs.len(), 2, "The first tab was closed");
+    assert_eq!(
+        screen.get_active_tab().unwrap().position,
+        1,
+        "Active tab did not change"
+    );
+    assert!(
+        screen.get_active_tab().unwrap().has_terminal_pid(3),
+        "The active tab is still the last one"
+    );
+}
+
+#[test]
+pub fn break_pane_to_previous_tab() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    new_tab(&mut screen, 2);
+    new_tab(&mut screen, 3);
+    screen.break_active_pane_to_adjacent_tab(false);
+
+    assert_eq!(screen.tabs.len(), 2, "The emptied tab was closed");
+    let active_tab = screen.get_active_tab().unwrap();
+    assert_eq!(active_tab.position, 1, "Switched to the previous tab");
+    assert!(
+        active_tab.has_terminal_pid(2) && active_tab.has_terminal_pid(3),
+        "The pane was added next to the one already there"
+    );
+    assert_eq!(
+        active_tab.get_active_pane_id(),
+        Some(PaneId::Terminal(3)),
+        "The moved pane keeps the focus"
+    );
+}
+
+#[test]
+pub fn new_tab_takes_pane_of_another_tab() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    new_tab(&mut screen, 2);
+    // the layout the pty thread sends when breaking out the pane of the first tab
+    new_tab(&mut screen, 1);
+
+    assert_eq!(screen.tabs.len(), 2, "The emptied tab was closed");
+    assert!(
+        screen.get_active_tab().unwrap().has_terminal_pid(1),
+        "The pane moved to the new tab"
+    );
+    assert_eq!(
+        screen.get_active_tab().unwrap().position,
+        1,
+        "The new tab is the last one"
+    );
+}
+
+#[test]
+pub fn pinned_pane_follows_tab_switches() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    new_tab(&mut screen, 2);
+    screen.switch_tab_prev();
+    let first_tab = screen.get_active_tab_mut().unwrap();
+    first_tab.new_pane(PaneId::Terminal(3));
+    first_tab.toggle_active_pane_pinned();
+    first_tab.focus_pane(PaneId::Terminal(1));
+    screen.switch_tab_next();
+
+    let active_tab = screen.get_active_tab().unwrap();
+    assert!(
+        active_tab.has_terminal_pid(2) && active_tab.has_terminal_pid(3),
+        "The pinned pane came along to the next tab"
+    );
+    assert_eq!(
+        active_tab.get_active_pane_id(),
+        Some(PaneId::Terminal(2)),
+        "The focus stays on the pane of the tab"
+    );
+}
+
+#[test]
+pub fn tabs_move_between_groups() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1);
+    new_tab(&mut screen, 2);
+    screen.update_active_tab_group_name(vec![0]);
+    screen.update_active_tab_group_name("project".as_bytes().to_vec());
+    screen.toggle_active_tab_group_collapsed();
+    screen.switch_tab_prev();
+    screen.move_active_tab_to_next_group();
+
+    assert_eq!(screen.tab_groups.len(), 1, "One group was created");
+    let group_id = *screen.tab_groups.keys().next().unwrap();
+    assert_eq!(
+        screen.tab_groups[&group_id].name, "project",
+        "Group is named"
+    );
+    assert!(screen.tab_groups[&group_id].collapsed, "Group is collapsed");
+    assert!(
+        screen.tabs.values().all(|tab| tab.group == Some(group_id)),
+        "Both tabs are in the group"
+    );
+
+    screen.move_active_tab_to_next_group();
+    screen.switch_tab_next();
+    screen.move_active_tab_to_next_group();
+
+    assert!(screen.tab_groups.is_empty(), "Empty group was removed");
+    assert!(
+        screen.tabs.values().all(|tab| tab.group.is_none()),
+        "Both tabs left the group"
+    );
+}
+
+#[test]
+fn leaving_the_rename_session_mode_renames_the_session() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+    let (to_server, server_receiver) = channels::unbounded();
+    screen.bus.senders.to_server = Some(SenderWithContext::new(to_server));
+    let mode = |mode| ModeInfo {
+        mode,
+        ..Default::default()
+    };
+
+    screen.change_mode(mode(InputMode::RenameSession));
+    screen.update_session_name(vec![0]);
+    screen.update_session_name("dropped".as_bytes().to_vec());
+    screen.update_session_name(vec![27]);
+    screen.change_mode(mode(InputMode::Session));
+    assert!(
+        server_receiver.try_recv().is_err(),
+        "An escaped name renames nothing"
+    );
+
+    screen.change_mode(mode(InputMode::RenameSession));
+    screen.update_session_name(vec![0]);
+    screen.update_session_name("work".as_bytes().to_vec());
+    screen.change_mode(mode(InputMode::Normal));
+    assert!(matches!(
+        server_receiver.try_recv(),
+        Ok((ServerInstruction::RenameSession(name, None), _)) if name == "work"
+    ));
+}
+
+#[test]
+fn renders_are_held_back_while_the_terminal_is_unfocused() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+    new_tab(&mut screen, 1);
+    screen.set_terminal_focus(false);
+    screen.render();
+    assert!(
+        screen.render_pending,
+        "The render waits while the terminal is unfocused"
+    );
+    screen.set_terminal_focus(true);
+    assert!(
+        !screen.render_pending,
+        "Getting the focus back renders what was held back"
+    );
+}
+
+#[test]
+pub fn plugins_are_told_the_tags_of_the_panes_of_each_tab() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+    let (to_plugin, plugin_receiver) = channels::unbounded();
+    screen.bus.senders.to_plugin = Some(SenderWithContext::new(to_plugin));
+
+    new_tab(&mut screen, 1);
+    let tab = screen.get_active_tab_mut().unwrap();
+    tab.tag_active_pane("server".into());
+    tab.tag_active_pane("logs".into());
+    screen.update_tabs();
+
+    let pane_tags = plugin_receiver
+        .try_iter()
+        .filter_map(|(instruction, _)| match instruction {
+            PluginInstruction::Update(_, Event::TabUpdate(tabs)) => Some(tabs[0].pane_tags.clone()),
+            _ => None,
+        })
+        .last();
+    assert_eq!(
+        pane_tags,
+        Some(vec!["logs".to_string(), "server".to_string()])
+    );
+}
+
+fn history_item(pane_id: PaneId, command: &str) -> HistoryItem {
+    HistoryItem {
+        pane_id,
+        pane_title: String::new(),
+        entry: HistoryEntry {
+            command: command.into(),
+            cwd: None,
+            exit_code: Some(0),
+            started: Instant::now(),
+        },
+    }
+}
+
+#[test]
+pub fn command_from_the_history_runs_in_its_pane_even_if_it_is_suppressed() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let os_input = FakeInputOutput::default();
+    let mut screen =
+        create_new_screen_with_os_input(size, SessionLimits::default(), os_input.clone());
+
+    new_tab(&mut screen, 1);
+    screen
+        .get_active_tab_mut()
+        .unwrap()
+        .suppress_pane(PaneId::Terminal(2));
+    new_tab(&mut screen, 3);
+    screen
+        .get_active_tab_mut()
+        .unwrap()
+        .update_command_history(vec![], vec![history_item(PaneId::Terminal(2), "make")]);
+    screen.run_from_command_history(true);
+
+    let tab = screen.get_active_tab().unwrap();
+    assert_eq!(tab.position, 0, "The tab of the pane is switched to");
+    assert_eq!(
+        tab.get_active_pane_id(),
+        Some(PaneId::Terminal(2)),
+        "The pane is shown and focused"
+    );
+    assert_eq!(
+        *os_input.written.lock().unwrap(),
+        vec![(2, b"make\r".to_vec())]
+    );
+}
+
+#[test]
+pub fn command_from_the_history_runs_in_the_focused_pane_unless_in_its_own() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let os_input = FakeInputOutput::default();
+    let mut screen =
+        create_new_screen_with_os_input(size, SessionLimits::default(), os_input.clone());
+
+    new_tab(&mut screen, 1);
+    new_tab(&mut screen, 2);
+    screen
+        .get_active_tab_mut()
+        .unwrap()
+        .update_command_history(vec![], vec![history_item(PaneId::Terminal(1), "make")]);
+    screen.run_from_command_history(false);
+
+    assert_eq!(screen.get_active_tab().unwrap().position, 1);
+    assert_eq!(
+        *os_input.written.lock().unwrap(),
+        vec![(2, b"make\r".to_vec())]
+    );
+
+    screen
+        .get_active_tab_mut()
+        .unwrap()
+        .update_command_history(vec![], vec![history_item(PaneId::Terminal(9), "make")]);
+    screen.run_from_command_history(true);
+    assert_eq!(
+        os_input.written.lock().unwrap().len(),
+        1,
+        "Nothing runs for a pane that is gone"
+    );
+}
@@ -0,0 +1,60 @@
+use super::*;
+
+#[test]
+fn sessions_are_written_every_minute_without_scrollback_by_default() {
+    let serialization = SessionSerialization::from_options("work", &Options::default()).unwrap();
+    assert_eq!(serialization.path, resurrection_path("work"));
+    assert_eq!(serialization.interval, Duration::from_secs(60));
+    assert_eq!(serialization.scrollback_lines, 0);
+}
+
+#[test]
+fn serialization_follows_the_options() {
+    let options = Options {
+        serialization_interval: Some(300),
+        scrollback_lines_to_serialize: Some(1000),
+        ..Default::default()
+    };
+    let serialization = SessionSerialization::from_options("work", &options).unwrap();
+    assert_eq!(serialization.interval, Duration::from_secs(300));
+    assert_eq!(serialization.scrollback_lines, 1000);
+
+    let options = Options {
+        session_serialization: Some(false),
+        ..Default::default()
+    };
+    assert!(SessionSerialization::from_options("work", &options).is_none());
+}
+
+#[test]
+fn idle_sessions_quit_after_the_hours_of_the_option_unless_excepted() {
+    assert!(IdleExit::from_options(&Options::default()).is_none());
+    let options = Options {
+        idle_exit_hours: Some(12),
+        idle_exit_exceptions: Some(vec!["build".to_string()]),
+        ..Default::default()
+    };
+    let idle_exit = IdleExit::from_options(&options).unwrap();
+    assert!(!idle_exit.is_due("work", Duration::from_secs(11 * 60 * 60)));
+    assert!(idle_exit.is_due("work", Duration::from_secs(12 * 60 * 60)));
+    assert!(!idle_exit.is_due("build", Duration::from_secs(48 * 60 * 60)));
+
+    let options = Options {
+        session_serialization: Some(false),
+        ..options
+    };
+    assert!(
+        IdleExit::from_options(&options).is_none(),
+        "a session that isn't written would be lost"
+    );
+}
+
+#[test]
+fn idle_exit_hours_too_many_for_the_clock_do_not_overflow() {
+    let options = Options {
+        idle_exit_hours: Some(u64::MAX),
+        ..Default::default()
+    };
+    let idle_exit = IdleExit::from_options(&options).unwrap();
+    assert!(!idle_exit.is_due("work", Duration::from_secs(100 * 365 * 24 * 60 * 60)));
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+fn session(name: &str, status: SessionStatus, pane_titles: &[&str]) -> SessionSummary {
+    let panes: Vec<PaneSummary> = pane_titles
+        .iter()
+        .enumerate()
+        .map(|(index, title)| PaneSummary {
+            id: index as u32,
+            tab_position: index / 2 + 1,
+            title: title.to_string(),
+            tags: vec![],
+        })
+        .collect();
+    SessionSummary::new(name.to_string(), status, &panes)
+}
+
+fn sessions() -> Vec<SessionSummary> {
+    vec![
+        session("work", SessionStatus::Current, &["vim", "cargo", "htop"]),
+        session("notes", SessionStatus::Running, &["nvim"]),
+        session("old-project", SessionStatus::Exited, &[]),
+    ]
+}
+
+fn names(session_manager: &SessionManager) -> Vec<String> {
+    let mut session_manager = session_manager.clone();
+    session_manager.selected = 0;
+    let mut names = vec![];
+    for _ in 0..session_manager.matches.len() {
+        names.push(session_manager.selected_session().unwrap().name.clone());
+        session_manager.move_selection(SearchDirection::Down);
+    }
+    names
+}
+
+#[test]
+fn sessions_are_summed_up_by_their_tabs_and_panes() {
+    let summary = session("work", SessionStatus::Current, &["vim", "cargo", "htop"]);
+    assert_eq!(summary.tabs, 2);
+    assert_eq!(summary.panes, 3);
+}
+
+#[test]
+fn the_filter_matches_names_and_pane_titles_fuzzily() {
+    let mut session_manager = SessionManager::default();
+    session_manager.set_sessions(sessions());
+    for c in "OPj".chars() {
+        session_manager.update_input(&c.to_string());
+    }
+    assert_eq!(names(&session_manager), vec!["old-project"]);
+    session_manager.update_input("\0");
+    session_manager.update_input("vim");
+    assert_eq!(
+        names(&session_manager),
+        vec!["work", "notes"],
+        "nvim has the characters of vim in order"
+    );
+}
+
+#[test]
+fn the_selection_stays_on_its_session_when_the_list_is_refreshed() {
+    let mut session_manager = SessionManager::default();
+    session_manager.set_sessions(sessions());
+    session_manager.move_selection(SearchDirection::Up);
+    assert_eq!(
+        session_manager.selected_session().unwrap().name,
+        "old-project"
+    );
+    let mut refreshed = sessions();
+    refreshed.insert(1, session("new", SessionStatus::Running, &[]));
+    session_manager.set_sessions(refreshed);
+    assert_eq!(
+        session_manager.selected_session().unwrap().name,
+        "old-project"
+    );
+}
+
+#[test]
+fn renaming_types_into_the_new_name_instead_of_the_filter() {
+    let mut session_manager = SessionManager::default();
+    session_manager.set_sessions(sessions());
+    session_manager.move_selection(SearchDirection::Down);
+    assert_eq!(session_manager.toggle_renaming(), None);
+    for c in "memo".chars() {
+        session_manager.update_input(&c.to_string());
+    }
+    session_manager.move_selection(SearchDirection::Down);
+    assert_eq!(session_manager.filter, "");
+    let (session, name) = session_manager.toggle_renaming().unwrap();
+    assert_eq!(
+        session.name, "notes",
+        "the selection doesn't move while renaming"
+    );
+    assert_eq!(name, "memo");
+    assert_eq!(session_manager.new_name, None);
+}
+
+#[test]
+fn the_list_shows_the_status_of_the_sessions() {
+    let mut session_manager = SessionManager::default();
+    let viewport = Viewport {
+        x: 0,
+        y: 0,
+        rows: 5,
+        cols: 50,
+    };
+    assert!(session_manager
+        .render(&viewport)
+        .contains(" SESSIONS:  ... "));
+    session_manager.set_sessions(sessions());
+    let list = session_manager.render(&viewport);
+    assert!(list.contains(" SESSIONS:  1/3 "));
+    assert!(list.contains("\u{1b}[1;30;43m work (current)  2 tabs, 3 panes "));
+    assert!(list.contains(" old-project (exited) "));
+}
@@ -1,21 +1,30 @@
-use super::Tab;
-use crate::zellij_tile::data::{ModeInfo, Palette};
+use super::{
+    sanitize_paste, AutoSplitOptions, PaneClipboard, PaneFrameOptions, PaneGridOptions,
+    PasteOptions, Tab,
+};
+use crate::zellij_tile::data::{Event, ModeInfo, Mouse, MouseModifiers, Palette};
 use crate::{
-    os_input_output::{AsyncReader, ChildId, Pid, ServerOsApi},
-    panes::PaneId,
+    os_input_output::{AsyncReader, ChildId, ClientId, Pid, ServerOsApi},
+    panes::{PaneId, PluginPane},
     thread_bus::ThreadSenders,
-    SessionState,
+    wasm_vm::PluginInstruction,
+    ServerInstruction, SessionState,
 };
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
-use zellij_utils::input::layout::LayoutTemplate;
+use zellij_utils::input::actions::ResizeAmount;
+use zellij_utils::input::layout::{Direction, LayoutTemplate, SplitSize, SwapLayout, TabLayout};
+use zellij_utils::input::options::{Options, PaneClipboardPolicy, SmallPanesPolicy};
+use zellij_utils::messages::Locale;
 use zellij_utils::pane_size::Size;
+use zellij_utils::position::Position;
 
 use std::os::unix::io::RawFd;
 
 use zellij_utils::nix;
 
 use zellij_utils::{
+    channels::{self, SenderWithContext},
     errors::ErrorContext,
     input::command::TerminalAction,
     interprocess::local_socket::LocalSocketStream,
@@ -52,19 +61,19 @@ impl ServerOsApi for FakeInputOutput {
     fn kill(&self, _pid: Pid) -> Result<(), nix::Error> {
         unimplemented!()
     }
-    fn recv_from_client(&self) -> (ClientToServerMsg, ErrorContext) {
+    fn recv_from_client(&self) -> Option<(ClientToServerMsg, ErrorContext)> {
         unimplemented!()
     }
-    fn send_to_client(&self, _msg: ServerToClientMsg) {
+    fn send_to_client(&self, _client_id: ClientId, _msg: ServerToClientMsg) {
         unimplemented!()
     }
-    fn add_client_sender(&self) {
+    fn add_client_sender(&self) -> ClientId {
         unimplemented!()
     }
     fn send_to_temp_client(&self, _msg: ServerToClientMsg) {
         unimplemented!()
     }
-    fn remove_client_sender(&self) {
+    fn remove_client_sender(&self, _client_id: ClientId) {
         unimplemented!()
     }
     fn update_receiver(&mut self, _stream: LocalSocketStream) {
@@ -76,6 +85,18 @@ impl ServerOsApi for FakeInputOutput {
     fn get_cwd(&self, _pid: Pid) -> Option<PathBuf> {
         unimplemented!()
     }
+    fn get_foreground_command(&self, _fd: RawFd) -> Option<Vec<String>> {
+        unimplemented!()
+    }
+    fn get_foreground_process_group(&self, _fd: RawFd) -> Option<Pid> {
+        unimplemented!()
+    }
+    fn send_signal(&self, _pid: Pid, _signal: nix::sys::signal::Signal) {
+        unimplemented!()
+    }
+    fn is_running(&self, _pid: Pid) -> bool {
+        unimplemented!()
+    }
 }
 
 fn create_new_tab(size: Size) -> Tab {
@@ -99,7 +120,14 @@ fn create_new_tab(size: Size) -> Tab {
         mode_info,
         colors,
         session_state,
-        true, // draw pane frames
+        true,  // draw pane frames
+        false, // exit fullscreen on focus change
+        PaneClipboard::from_options(&Options::default()),
+        PaneGridOptions::from_options(&Options::default()),
+        PaneFrameOptions::from_options(&Options::default()),
+        PasteOptions::from_options(&Options::default()),
+        AutoSplitOptions::from_options(&Options::default()),
+        SmallPanesPolicy::default(),
     );
     tab.apply_layout(LayoutTemplate::default().into(), vec![1], index);
     tab
@@ -283,6 +311,56 @@ fn split_panes_horizontally() {
     );
 }
 
+#[test]
+fn auto_split_splits_focused_pane_along_its_longer_side() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.auto_split(PaneId::Terminal(2));
+    tab.auto_split(PaneId::Terminal(3));
+    let second_pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert_eq!(
+        (
+            second_pane.x(),
+            second_pane.y(),
+            second_pane.cols(),
+            second_pane.rows()
+        ),
+        (61, 0, 60, 10),
+        "Wide first pane was split to the right, then the narrow second one was split down"
+    );
+    let third_pane = tab.panes.get(&PaneId::Terminal(3)).unwrap();
+    assert_eq!(
+        (
+            third_pane.x(),
+            third_pane.y(),
+            third_pane.cols(),
+            third_pane.rows()
+        ),
+        (61, 10, 60, 10),
+        "Third pane is below the second one"
+    );
+}
+
+#[test]
+fn auto_split_keeps_the_minimum_size() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.auto_split_options.min_cols = 70;
+    tab.auto_split(PaneId::Terminal(2));
+    let new_pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert_eq!(
+        (new_pane.x(), new_pane.y(), new_pane.cols(), new_pane.rows()),
+        (0, 10, 121, 10),
+        "Pane was split down, splitting it to the right would leave it too narrow"
+    );
+}
+
 #[test]
 fn split_largest_pane() {
     let size = Size {
@@ -569,6 +647,455 @@ pub fn move_focus_is_disabled_in_fullscreen() {
     );
 }
 
+#[test]
+pub fn balance_panes_evens_out_resized_panes() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.horizontal_split(PaneId::Terminal(3));
+    tab.resize_left_by(None);
+    tab.resize_left_by(None);
+    tab.resize_up_by(None);
+    tab.balance_panes();
+    let geom = |tab: &Tab, id| {
+        let pane = tab.panes.get(&PaneId::Terminal(id)).unwrap();
+        (pane.x(), pane.y(), pane.cols(), pane.rows())
+    };
+    assert_eq!(
+        geom(&tab, 1),
+        (0, 0, 61, 20),
+        "Left column takes half the tab"
+    );
+    assert_eq!(
+        geom(&tab, 2),
+        (61, 0, 60, 10),
+        "Top right pane takes half the column"
+    );
+    assert_eq!(
+        geom(&tab, 3),
+        (61, 10, 60, 10),
+        "Bottom right pane takes the other half"
+    );
+}
+
+#[test]
+pub fn layout_of_panes_follows_the_splits() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.horizontal_split(PaneId::Terminal(3));
+    let (layout, pane_ids) = tab.layout_of_panes();
+    assert_eq!(
+        pane_ids,
+        vec![
+            PaneId::Terminal(1),
+            PaneId::Terminal(2),
+            PaneId::Terminal(3)
+        ],
+        "Panes are in the order the layout spawns them"
+    );
+    assert_eq!(
+        layout.direction,
+        Direction::Vertical,
+        "Tab is cut into columns"
+    );
+    assert_eq!(layout.parts.len(), 2, "Tab has two columns");
+    assert_eq!(
+        layout.parts[0].split_size,
+        Some(SplitSize::Percent(100.0 * 61.0 / 121.0)),
+        "Left column keeps its share of the tab"
+    );
+    assert!(layout.parts[0].parts.is_empty(), "Left column is one pane");
+    let right_column = &layout.parts[1];
+    assert_eq!(
+        right_column.direction,
+        Direction::Horizontal,
+        "Right column is cut into rows"
+    );
+    assert_eq!(
+        right_column
+            .parts
+            .iter()
+            .map(|part| part.split_size)
+            .collect::<Vec<_>>(),
+        vec![
+            Some(SplitSize::Percent(50.0)),
+            Some(SplitSize::Percent(50.0))
+        ],
+        "Rows of the right column keep their share of the tab"
+    );
+}
+
+#[test]
+pub fn resizing_keeps_panes_within_their_bounds() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    let pane = tab.panes.get_mut(&PaneId::Terminal(1)).unwrap();
+    let mut geom = pane.position_and_size();
+    geom.cols.set_bounds(Some(20), Some(40));
+    pane.set_geom(geom);
+    tab.relayout_tab(Direction::Horizontal);
+    let cols = |tab: &Tab| tab.panes.get(&PaneId::Terminal(1)).unwrap().cols();
+    assert_eq!(cols(&tab), 40, "The pane is kept at its maximum width");
+
+    tab.move_focus_left();
+    tab.resize_left_by(None);
+    assert!(
+        cols(&tab) < 40,
+        "Shrinking the pane starts from the width it is shown at"
+    );
+
+    for _ in 0..5 {
+        tab.resize_right_by(None);
+    }
+    assert_eq!(
+        cols(&tab),
+        40,
+        "Growing the pane stops at its maximum width"
+    );
+
+    for _ in 0..10 {
+        tab.resize_left_by(None);
+    }
+    assert_eq!(
+        cols(&tab),
+        20,
+        "Shrinking the pane stops at its minimum width"
+    );
+}
+
+#[test]
+pub fn leaving_fullscreen_restores_pane_sizes() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.resize_left_by(None);
+    tab.resize_left_by(None);
+    let geom_before_fullscreen = tab
+        .panes
+        .get(&PaneId::Terminal(2))
+        .unwrap()
+        .position_and_size();
+    tab.toggle_active_pane_fullscreen();
+    tab.toggle_active_pane_fullscreen();
+    assert_eq!(
+        tab.panes
+            .get(&PaneId::Terminal(2))
+            .unwrap()
+            .position_and_size(),
+        geom_before_fullscreen,
+        "Pane is back to its size from before fullscreen"
+    );
+}
+
+#[test]
+pub fn move_focus_exits_fullscreen_when_configured() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.exit_fullscreen_on_focus_change = true;
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.toggle_active_pane_fullscreen();
+    tab.move_focus_left();
+    assert!(!tab.fullscreen_is_active, "Tab is no longer fullscreen");
+    assert_eq!(
+        tab.get_active_pane_id(),
+        Some(PaneId::Terminal(1)),
+        "Focus moved to the pane on the left"
+    );
+    assert_eq!(
+        tab.panes.get(&PaneId::Terminal(2)).unwrap().cols(),
+        60,
+        "Pane is back to its size from before fullscreen"
+    );
+}
+
+#[test]
+pub fn clean_screen_hides_pane_frames() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.set_clean_screen(true);
+    assert_eq!(
+        tab.panes
+            .get(&PaneId::Terminal(1))
+            .unwrap()
+            .get_content_columns(),
+        121,
+        "Pane content takes the place of its frame"
+    );
+    tab.set_clean_screen(false);
+    assert_eq!(
+        tab.panes
+            .get(&PaneId::Terminal(1))
+            .unwrap()
+            .get_content_columns(),
+        119,
+        "Pane frame is back"
+    );
+}
+
+#[test]
+pub fn hiding_the_ui_gives_its_rows_to_the_other_panes() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    let bar = TabLayout {
+        borderless: true,
+        split_size: Some(SplitSize::Fixed(1)),
+        ..Default::default()
+    };
+    let layout = TabLayout {
+        direction: Direction::Horizontal,
+        parts: vec![bar, TabLayout::default()],
+        ..Default::default()
+    };
+    // the pane of the tab takes the place of the bar
+    tab.apply_layout(layout.into(), vec![2], 0);
+    let pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert_eq!(
+        (pane.y(), pane.rows()),
+        (1, 19),
+        "The bar takes the first row"
+    );
+
+    tab.set_ui_hidden(true);
+    let pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert_eq!(
+        (pane.y(), pane.rows()),
+        (0, 20),
+        "The pane takes the row of the bar"
+    );
+
+    tab.set_ui_hidden(false);
+    let pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert_eq!((pane.y(), pane.rows()), (1, 19), "The bar is back");
+}
+
+// a tab with a one row bar at the top, and terminal 2 under it
+fn create_new_tab_with_bar(size: Size) -> Tab {
+    let mut tab = create_new_tab(size);
+    let bar = TabLayout {
+        borderless: true,
+        split_size: Some(SplitSize::Fixed(1)),
+        ..Default::default()
+    };
+    let layout = TabLayout {
+        direction: Direction::Horizontal,
+        parts: vec![bar, TabLayout::default()],
+        ..Default::default()
+    };
+    tab.apply_layout(layout.into(), vec![2], 0);
+    tab
+}
+
+#[test]
+pub fn hiding_the_ui_while_a_pane_is_fullscreen() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab_with_bar(size);
+    tab.focus_pane(PaneId::Terminal(2));
+    tab.horizontal_split(PaneId::Terminal(3));
+    tab.toggle_active_pane_fullscreen();
+    tab.set_ui_hidden(true);
+    let pane = tab.panes.get(&PaneId::Terminal(3)).unwrap();
+    assert_eq!(
+        (pane.y(), pane.rows()),
+        (0, 20),
+        "The fullscreen pane takes the row of the bar"
+    );
+
+    tab.toggle_active_pane_fullscreen();
+    let bar = tab.panes.get(&PaneId::Terminal(1)).unwrap();
+    assert!(
+        bar.geom_override().is_none(),
+        "The bar isn't left at its fullscreen position"
+    );
+    let rows: usize = [2, 3]
+        .iter()
+        .map(|&pid| tab.panes.get(&PaneId::Terminal(pid)).unwrap().rows())
+        .sum();
+    assert_eq!(rows, 20, "The panes share the rows of the bar");
+
+    tab.set_ui_hidden(false);
+    let bar = tab.panes.get(&PaneId::Terminal(1)).unwrap();
+    assert_eq!((bar.y(), bar.rows()), (0, 1), "The bar is back");
+    let first_pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    let second_pane = tab.panes.get(&PaneId::Terminal(3)).unwrap();
+    assert_eq!(first_pane.y(), 1, "The panes are under the bar");
+    assert_eq!(
+        first_pane.rows() + second_pane.rows(),
+        19,
+        "The panes share the rows left"
+    );
+}
+
+#[test]
+pub fn applying_a_layout_while_the_ui_is_hidden_hides_its_ui_panes() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab_with_bar(size);
+    tab.set_ui_hidden(true);
+    // the bar is at the bottom of the new layout
+    let bar = TabLayout {
+        borderless: true,
+        split_size: Some(SplitSize::Fixed(1)),
+        ..Default::default()
+    };
+    let layout = TabLayout {
+        direction: Direction::Horizontal,
+        parts: vec![TabLayout::default(), bar],
+        ..Default::default()
+    };
+    tab.retile(layout.into(), vec![]);
+
+    assert_eq!(
+        tab.hidden_ui_panes,
+        [PaneId::Terminal(2)].iter().copied().collect(),
+        "The pane taking the place of the bar is hidden"
+    );
+    let pane = tab.panes.get(&PaneId::Terminal(1)).unwrap();
+    assert_eq!((pane.y(), pane.rows()), (0, 20), "The other pane is shown");
+}
+
+#[test]
+pub fn closing_a_hidden_ui_pane_forgets_it() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab_with_bar(size);
+    tab.set_ui_hidden(true);
+    tab.close_pane(PaneId::Terminal(1));
+
+    assert!(tab.hidden_ui_panes.is_empty(), "The bar is forgotten");
+}
+
+#[test]
+pub fn auto_pane_frames_leave_out_the_frame_of_a_single_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.pane_frame_options.hide_single = true;
+    tab.set_pane_frames(true);
+    assert_eq!(
+        tab.panes
+            .get(&PaneId::Terminal(1))
+            .unwrap()
+            .get_content_columns(),
+        121,
+        "Single pane has no frame"
+    );
+    tab.vertical_split(PaneId::Terminal(2));
+    assert_eq!(
+        tab.panes
+            .get(&PaneId::Terminal(1))
+            .unwrap()
+            .get_content_columns(),
+        59,
+        "Frames are back once the tab is split"
+    );
+}
+
+#[test]
+pub fn sanitized_paste_keeps_only_printable_text_and_line_breaks() {
+    assert_eq!(
+        sanitize_paste(b"echo hi\x1b[201~\x07\trm -rf ~\r\n"),
+        b"echo hi[201~\trm -rf ~\r\n".to_vec()
+    );
+}
+
+#[test]
+pub fn multiline_paste_into_a_shell_waits_for_confirmation() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.paste_options.confirm_multiline = true;
+    tab.paste(b"cd /\nrm -rf *\n".to_vec());
+    assert_eq!(
+        tab.pending_paste,
+        Some(b"cd /\nrm -rf *\n".to_vec()),
+        "Paste is held back"
+    );
+    tab.write_to_active_terminal(b"n".to_vec());
+    assert_eq!(tab.pending_paste, None, "Paste was declined");
+}
+
+#[test]
+pub fn rename_active_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    let title = tab.panes.get(&PaneId::Terminal(1)).unwrap().title();
+    tab.update_active_pane_name(vec![0]);
+    for c in "logs!".bytes() {
+        tab.update_active_pane_name(vec![c]);
+    }
+    tab.update_active_pane_name(vec![127]); // backspace
+    assert_eq!(
+        tab.panes.get(&PaneId::Terminal(1)).unwrap().title(),
+        "logs",
+        "Pane shows its new name"
+    );
+    tab.update_active_pane_name(vec![0]);
+    assert_eq!(
+        tab.panes.get(&PaneId::Terminal(1)).unwrap().title(),
+        title,
+        "Pane is back to its title"
+    );
+}
+
+#[test]
+pub fn unnamed_tab_shows_the_title_set_in_its_active_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.handle_pty_bytes(1, b"\x1b]2;vim notes.txt\x07".to_vec());
+    assert_eq!(
+        tab.panes.get(&PaneId::Terminal(1)).unwrap().title(),
+        "vim notes.txt",
+        "Pane frame shows the title"
+    );
+    assert_eq!(
+        tab.display_name(),
+        "vim notes.txt",
+        "Tab bar shows the title"
+    );
+    tab.name = String::from("notes");
+    assert_eq!(tab.display_name(), "notes", "Tab bar shows the tab name");
+}
+
 #[test]
 pub fn close_pane_with_another_pane_above_it() {
     // ┌───────────┐            ┌───────────┐
@@ -1252,7 +1779,7 @@ pub fn close_pane_with_multiple_panes_above_it_away_from_screen_edges() {
     tab.horizontal_split(new_pane_id_5);
     tab.move_focus_left();
     tab.move_focus_up();
-    tab.resize_down();
+    tab.resize_down_by(None);
     tab.vertical_split(new_pane_id_6);
     tab.move_focus_down();
     tab.close_focused_pane();
@@ -1527,7 +2054,7 @@ pub fn close_pane_with_multiple_panes_below_it_away_from_screen_edges() {
     tab.move_focus_right();
     tab.horizontal_split(new_pane_id_5);
     tab.move_focus_left();
-    tab.resize_up();
+    tab.resize_up_by(None);
     tab.vertical_split(new_pane_id_6);
     tab.move_focus_up();
     tab.close_focused_pane();
@@ -1805,9 +2332,9 @@ pub fn close_pane_with_multiple_panes_to_the_left_away_from_screen_edges() {
     tab.vertical_split(new_pane_id_5);
     tab.move_focus_up();
     tab.move_focus_left();
-    tab.resize_right();
-    tab.resize_up();
-    tab.resize_up();
+    tab.resize_right_by(None);
+    tab.resize_up_by(None);
+    tab.resize_up_by(None);
     tab.horizontal_split(new_pane_id_6);
     tab.move_focus_right();
     tab.close_focused_pane();
@@ -2084,9 +2611,9 @@ pub fn close_pane_with_multiple_panes_to_the_right_away_from_screen_edges() {
     tab.move_focus_down();
     tab.vertical_split(new_pane_id_5);
     tab.move_focus_up();
-    tab.resize_left();
-    tab.resize_up();
-    tab.resize_up();
+    tab.resize_left_by(None);
+    tab.resize_up_by(None);
+    tab.resize_up_by(None);
     tab.horizontal_split(new_pane_id_6);
     tab.move_focus_left();
     tab.close_focused_pane();
@@ -2542,7 +3069,7 @@ pub fn resize_down_with_pane_above() {
     let mut tab = create_new_tab(size);
     let new_pane_id = PaneId::Terminal(2);
     tab.horizontal_split(new_pane_id);
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes.get(&new_pane_id).unwrap().position_and_size().x,
@@ -2633,7 +3160,7 @@ pub fn resize_down_with_pane_below() {
     let new_pane_id = PaneId::Terminal(2);
     tab.horizontal_split(new_pane_id);
     tab.move_focus_up();
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes.get(&new_pane_id).unwrap().position_and_size().x,
@@ -2731,7 +3258,7 @@ pub fn resize_down_with_panes_above_and_below() {
     tab.horizontal_split(new_pane_id_1);
     tab.horizontal_split(new_pane_id_2);
     tab.move_focus_up();
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes.get(&new_pane_id_1).unwrap().position_and_size().x,
@@ -2849,7 +3376,7 @@ pub fn resize_down_with_multiple_panes_above() {
     tab.move_focus_up();
     tab.vertical_split(new_pane_id_2);
     tab.move_focus_down();
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes.get(&new_pane_id_1).unwrap().position_and_size().x,
@@ -2969,7 +3496,7 @@ pub fn resize_down_with_panes_above_aligned_left_with_current_pane() {
     tab.move_focus_up();
     tab.vertical_split(pane_above);
     tab.move_focus_down();
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes.get(&focused_pane).unwrap().position_and_size().x,
@@ -3136,7 +3663,7 @@ pub fn resize_down_with_panes_below_aligned_left_with_current_pane() {
     tab.vertical_split(pane_below);
     tab.move_focus_up();
     tab.vertical_split(focused_pane);
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes.get(&focused_pane).unwrap().position_and_size().x,
@@ -3305,7 +3832,7 @@ pub fn resize_down_with_panes_above_aligned_right_with_current_pane() {
     tab.vertical_split(pane_above_and_right);
     tab.move_focus_down();
     tab.move_focus_left();
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes.get(&focused_pane).unwrap().position_and_size().x,
@@ -3473,7 +4000,7 @@ pub fn resize_down_with_panes_below_aligned_right_with_current_pane() {
     tab.move_focus_up();
     tab.vertical_split(pane_to_the_right);
     tab.move_focus_left();
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes.get(&focused_pane).unwrap().position_and_size().x,
@@ -3640,7 +4167,7 @@ pub fn resize_down_with_panes_above_aligned_left_and_right_with_current_pane() {
     tab.vertical_split(PaneId::Terminal(6));
     tab.move_focus_left();
     tab.move_focus_down();
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes
@@ -3900,7 +4427,7 @@ pub fn resize_down_with_panes_below_aligned_left_and_right_with_current_pane() {
     tab.vertical_split(PaneId::Terminal(5));
     tab.vertical_split(PaneId::Terminal(6));
     tab.move_focus_left();
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes
@@ -4164,7 +4691,7 @@ pub fn resize_down_with_panes_above_aligned_left_and_right_with_panes_to_the_lef
     tab.vertical_split(PaneId::Terminal(7));
     tab.vertical_split(PaneId::Terminal(8));
     tab.move_focus_left();
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes
@@ -4508,7 +5035,7 @@ pub fn resize_down_with_panes_below_aligned_left_and_right_with_to_the_left_and_
     tab.move_focus_left();
     tab.move_focus_up();
     tab.move_focus_left();
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes
@@ -4839,7 +5366,7 @@ pub fn cannot_resize_down_when_pane_below_is_at_minimum_height() {
     let mut tab = create_new_tab(size);
     tab.horizontal_split(PaneId::Terminal(2));
     tab.move_focus_up();
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes
@@ -4878,7 +5405,7 @@ pub fn resize_left_with_pane_to_the_left() {
     };
     let mut tab = create_new_tab(size);
     tab.vertical_split(PaneId::Terminal(2));
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -4974,7 +5501,7 @@ pub fn resize_left_with_pane_to_the_right() {
     let mut tab = create_new_tab(size);
     tab.vertical_split(PaneId::Terminal(2));
     tab.move_focus_left();
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -5072,7 +5599,7 @@ pub fn resize_left_with_panes_to_the_left_and_right() {
     tab.vertical_split(PaneId::Terminal(2));
     tab.vertical_split(PaneId::Terminal(3));
     tab.move_focus_left();
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -5209,7 +5736,7 @@ pub fn resize_left_with_multiple_panes_to_the_left() {
     tab.move_focus_left();
     tab.horizontal_split(PaneId::Terminal(3));
     tab.move_focus_right();
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -5348,7 +5875,7 @@ pub fn resize_left_with_panes_to_the_left_aligned_top_with_current_pane() {
     tab.move_focus_up();
     tab.vertical_split(PaneId::Terminal(4));
     tab.move_focus_down();
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -5527,7 +6054,7 @@ pub fn resize_left_with_panes_to_the_right_aligned_top_with_current_pane() {
     tab.vertical_split(PaneId::Terminal(4));
     tab.move_focus_down();
     tab.move_focus_left();
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -5704,7 +6231,7 @@ pub fn resize_left_with_panes_to_the_left_aligned_bottom_with_current_pane() {
     tab.vertical_split(PaneId::Terminal(3));
     tab.move_focus_up();
     tab.vertical_split(PaneId::Terminal(4));
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -5882,7 +6409,7 @@ pub fn resize_left_with_panes_to_the_right_aligned_bottom_with_current_pane() {
     tab.move_focus_up();
     tab.vertical_split(PaneId::Terminal(4));
     tab.move_focus_left();
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -6065,7 +6592,7 @@ pub fn resize_left_with_panes_to_the_left_aligned_top_and_bottom_with_current_pa
     tab.move_focus_up();
     tab.vertical_split(PaneId::Terminal(6));
     tab.move_focus_down();
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -6327,7 +6854,7 @@ pub fn resize_left_with_panes_to_the_right_aligned_top_and_bottom_with_current_p
     tab.vertical_split(PaneId::Terminal(6));
     tab.move_focus_down();
     tab.move_focus_left();
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -6587,12 +7114,12 @@ pub fn resize_left_with_panes_to_the_left_aligned_top_and_bottom_with_panes_abov
     tab.move_focus_up();
     tab.vertical_split(PaneId::Terminal(5));
     tab.move_focus_down();
-    tab.resize_down();
+    tab.resize_down_by(None);
     tab.vertical_split(PaneId::Terminal(6));
     tab.horizontal_split(PaneId::Terminal(7));
     tab.horizontal_split(PaneId::Terminal(8));
     tab.move_focus_up();
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -6930,13 +7457,13 @@ pub fn resize_left_with_panes_to_the_right_aligned_top_and_bottom_with_panes_abo
     tab.move_focus_up();
     tab.vertical_split(PaneId::Terminal(5));
     tab.move_focus_down();
-    tab.resize_down();
+    tab.resize_down_by(None);
     tab.vertical_split(PaneId::Terminal(6));
     tab.move_focus_left();
     tab.horizontal_split(PaneId::Terminal(7));
     tab.horizontal_split(PaneId::Terminal(8));
     tab.move_focus_up();
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -7263,7 +7790,7 @@ pub fn cannot_resize_left_when_pane_to_the_left_is_at_minimum_width() {
     let size = Size { cols: 10, rows: 20 };
     let mut tab = create_new_tab(size);
     tab.vertical_split(PaneId::Terminal(2));
-    tab.resize_left();
+    tab.resize_left_by(None);
 
     assert_eq!(
         tab.panes
@@ -7302,7 +7829,7 @@ pub fn resize_right_with_pane_to_the_left() {
     };
     let mut tab = create_new_tab(size);
     tab.vertical_split(PaneId::Terminal(2));
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -7399,7 +7926,7 @@ pub fn resize_right_with_pane_to_the_right() {
     let mut tab = create_new_tab(size);
     tab.vertical_split(PaneId::Terminal(2));
     tab.move_focus_left();
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -7497,7 +8024,7 @@ pub fn resize_right_with_panes_to_the_left_and_right() {
     tab.vertical_split(PaneId::Terminal(2));
     tab.vertical_split(PaneId::Terminal(3));
     tab.move_focus_left();
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -7635,7 +8162,7 @@ pub fn resize_right_with_multiple_panes_to_the_left() {
     tab.move_focus_left();
     tab.horizontal_split(PaneId::Terminal(3));
     tab.move_focus_right();
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -7774,7 +8301,7 @@ pub fn resize_right_with_panes_to_the_left_aligned_top_with_current_pane() {
     tab.horizontal_split(PaneId::Terminal(3));
     tab.move_focus_right();
     tab.horizontal_split(PaneId::Terminal(4));
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -7952,7 +8479,7 @@ pub fn resize_right_with_panes_to_the_right_aligned_top_with_current_pane() {
     tab.move_focus_right();
     tab.horizontal_split(PaneId::Terminal(4));
     tab.move_focus_left();
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -8131,7 +8658,7 @@ pub fn resize_right_with_panes_to_the_left_aligned_bottom_with_current_pane() {
     tab.move_focus_right();
     tab.horizontal_split(PaneId::Terminal(4));
     tab.move_focus_up();
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -8311,7 +8838,7 @@ pub fn resize_right_with_panes_to_the_right_aligned_bottom_with_current_pane() {
     tab.horizontal_split(PaneId::Terminal(4));
     tab.move_focus_up();
     tab.move_focus_left();
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -8494,7 +9021,7 @@ pub fn resize_right_with_panes_to_the_left_aligned_top_and_bottom_with_current_p
     tab.move_focus_up();
     tab.vertical_split(PaneId::Terminal(6));
     tab.move_focus_down();
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -8755,7 +9282,7 @@ pub fn resize_right_with_panes_to_the_right_aligned_top_and_bottom_with_current_
     tab.vertical_split(PaneId::Terminal(6));
     tab.move_focus_down();
     tab.move_focus_left();
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -9014,12 +9541,12 @@ pub fn resize_right_with_panes_to_the_left_aligned_top_and_bottom_with_panes_abo
     tab.move_focus_up();
     tab.vertical_split(PaneId::Terminal(5));
     tab.move_focus_down();
-    tab.resize_up();
+    tab.resize_up_by(None);
     tab.vertical_split(PaneId::Terminal(6));
     tab.horizontal_split(PaneId::Terminal(7));
     tab.horizontal_split(PaneId::Terminal(8));
     tab.move_focus_up();
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -9356,13 +9883,13 @@ pub fn resize_right_with_panes_to_the_right_aligned_top_and_bottom_with_panes_ab
     tab.move_focus_up();
     tab.vertical_split(PaneId::Terminal(5));
     tab.move_focus_down();
-    tab.resize_up();
+    tab.resize_up_by(None);
     tab.vertical_split(PaneId::Terminal(6));
     tab.move_focus_left();
     tab.horizontal_split(PaneId::Terminal(7));
     tab.horizontal_split(PaneId::Terminal(8));
     tab.move_focus_up();
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -9688,7 +10215,7 @@ pub fn cannot_resize_right_when_pane_to_the_left_is_at_minimum_width() {
     let size = Size { cols: 10, rows: 20 };
     let mut tab = create_new_tab(size);
     tab.vertical_split(PaneId::Terminal(2));
-    tab.resize_right();
+    tab.resize_right_by(None);
 
     assert_eq!(
         tab.panes
@@ -9728,7 +10255,7 @@ pub fn resize_up_with_pane_above() {
     };
     let mut tab = create_new_tab(size);
     tab.horizontal_split(PaneId::Terminal(2));
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -9826,7 +10353,7 @@ pub fn resize_up_with_pane_below() {
     let mut tab = create_new_tab(size);
     tab.horizontal_split(PaneId::Terminal(2));
     tab.move_focus_up();
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -9928,7 +10455,7 @@ pub fn resize_up_with_panes_above_and_below() {
     tab.horizontal_split(PaneId::Terminal(2));
     tab.horizontal_split(PaneId::Terminal(3));
     tab.move_focus_up();
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -10066,7 +10593,7 @@ pub fn resize_up_with_multiple_panes_above() {
     tab.move_focus_up();
     tab.vertical_split(PaneId::Terminal(3));
     tab.move_focus_down();
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -10204,7 +10731,7 @@ pub fn resize_up_with_panes_above_aligned_left_with_current_pane() {
     tab.vertical_split(PaneId::Terminal(3));
     tab.move_focus_down();
     tab.vertical_split(PaneId::Terminal(4));
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -10384,7 +10911,7 @@ pub fn resize_up_with_panes_below_aligned_left_with_current_pane() {
     tab.move_focus_down();
     tab.vertical_split(PaneId::Terminal(4));
     tab.move_focus_up();
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -10564,7 +11091,7 @@ pub fn resize_up_with_panes_above_aligned_right_with_current_pane() {
     tab.move_focus_down();
     tab.vertical_split(PaneId::Terminal(4));
     tab.move_focus_left();
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -10745,7 +11272,7 @@ pub fn resize_up_with_panes_below_aligned_right_with_current_pane() {
     tab.vertical_split(PaneId::Terminal(4));
     tab.move_focus_left();
     tab.move_focus_up();
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -10926,7 +11453,7 @@ pub fn resize_up_with_panes_above_aligned_left_and_right_with_current_pane() {
     tab.vertical_split(PaneId::Terminal(5));
     tab.vertical_split(PaneId::Terminal(6));
     tab.move_focus_left();
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -11186,7 +11713,7 @@ pub fn resize_up_with_panes_below_aligned_left_and_right_with_current_pane() {
     tab.vertical_split(PaneId::Terminal(6));
     tab.move_focus_left();
     tab.move_focus_up();
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -11449,7 +11976,7 @@ pub fn resize_up_with_panes_above_aligned_left_and_right_with_panes_to_the_left_
     tab.vertical_split(PaneId::Terminal(7));
     tab.vertical_split(PaneId::Terminal(8));
     tab.move_focus_left();
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -11791,7 +12318,7 @@ pub fn resize_up_with_panes_below_aligned_left_and_right_with_to_the_left_and_ri
     tab.vertical_split(PaneId::Terminal(7));
     tab.vertical_split(PaneId::Terminal(8));
     tab.move_focus_left();
-    tab.resize_up();
+    tab.resize_up_by(None);
 
     assert_eq!(
         tab.panes
@@ -12121,7 +12648,7 @@ pub fn cannot_resize_up_when_pane_above_is_at_minimum_height() {
     };
     let mut tab = create_new_tab(size);
     tab.horizontal_split(PaneId::Terminal(2));
-    tab.resize_down();
+    tab.resize_down_by(None);
 
     assert_eq!(
         tab.panes
@@ -12144,3 +12671,774 @@ pub fn cannot_resize_up_when_pane_above_is_at_minimum_height() {
         "pane 2 height stayed the same"
     );
 }
+
+#[test]
+fn suppressed_pane_is_not_displayed() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    let new_pane_id = PaneId::Terminal(2);
+    tab.suppress_pane(new_pane_id);
+    assert_eq!(tab.panes.len(), 1, "The tab still displays a single pane");
+    assert!(
+        tab.has_terminal_pid(2),
+        "The suppressed pane belongs to the tab"
+    );
+    assert_eq!(
+        tab.get_active_pane_id(),
+        Some(PaneId::Terminal(1)),
+        "Focus did not move to the suppressed pane"
+    );
+}
+
+#[test]
+fn show_suppressed_pane_swaps_it_with_the_active_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    let active_pane_geom = tab
+        .panes
+        .get(&PaneId::Terminal(2))
+        .unwrap()
+        .position_and_size();
+    tab.suppress_pane(PaneId::Terminal(3));
+    tab.show_suppressed_pane(PaneId::Terminal(3));
+    assert_eq!(tab.panes.len(), 2, "The tab still displays two panes");
+    assert_eq!(
+        tab.get_active_pane_id(),
+        Some(PaneId::Terminal(3)),
+        "The shown pane is focused"
+    );
+    assert_eq!(
+        tab.panes
+            .get(&PaneId::Terminal(3))
+            .unwrap()
+            .position_and_size(),
+        active_pane_geom,
+        "The shown pane took the place of the active pane"
+    );
+    assert!(
+        tab.has_suppressed_pane(&PaneId::Terminal(2)),
+        "The previously active pane is suppressed"
+    );
+}
+
+#[test]
+fn toggle_pane_link_with_a_single_other_pane_links_it() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.toggle_active_pane_link();
+    assert_eq!(
+        tab.linked_panes.get(&PaneId::Terminal(2)),
+        Some(&PaneId::Terminal(1)),
+        "The focused pane is linked to the other pane"
+    );
+    assert_eq!(
+        tab.linked_panes.get(&PaneId::Terminal(1)),
+        Some(&PaneId::Terminal(2)),
+        "Links go both ways"
+    );
+    tab.toggle_active_pane_link();
+    assert!(
+        tab.linked_panes.is_empty(),
+        "Toggling again unlinks the panes"
+    );
+}
+
+#[test]
+fn toggle_pane_link_picks_the_pane_to_link_with() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.horizontal_split(PaneId::Terminal(3));
+    tab.toggle_active_pane_link();
+    assert!(
+        tab.linked_panes.is_empty(),
+        "Nothing is linked before picking"
+    );
+    assert!(tab.pane_link_picker.is_some(), "The picker is shown");
+    tab.write_to_active_terminal("2".as_bytes().to_vec());
+    assert!(tab.pane_link_picker.is_none(), "Picking hides the picker");
+    assert_eq!(
+        tab.linked_panes.get(&PaneId::Terminal(3)),
+        Some(&PaneId::Terminal(2)),
+        "The focused pane is linked to the picked pane"
+    );
+    tab.close_pane(PaneId::Terminal(2));
+    assert!(
+        tab.linked_panes.is_empty(),
+        "Closing a linked pane unlinks it"
+    );
+}
+
+#[test]
+fn clipboard_write_from_pane_waits_for_the_user_when_prompting() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    let (to_server, server_receiver) = channels::unbounded();
+    tab.senders.to_server = Some(SenderWithContext::new(to_server));
+    tab.pane_clipboard = PaneClipboard {
+        policy: PaneClipboardPolicy::Prompt,
+        max_size: 10,
+        locale: Locale::En,
+        ..PaneClipboard::from_options(&Options::default())
+    };
+    tab.handle_clipboard_write_from_pane(PaneId::Terminal(1), "this is too long".into());
+    assert!(
+        tab.pending_clipboard_write.is_none(),
+        "Writes over the size limit are dropped"
+    );
+    match server_receiver.try_recv() {
+        Ok((ServerInstruction::Diagnostic(notice), _)) => assert_eq!(
+            notice,
+            "Not copying 16 bytes from a pane to the clipboard, over the limit of 10"
+        ),
+        _ => panic!("The user is told the write was dropped"),
+    }
+    tab.handle_clipboard_write_from_pane(PaneId::Terminal(1), "hello".into());
+    assert_eq!(
+        tab.pending_clipboard_write,
+        Some((PaneId::Terminal(1), String::from("hello"))),
+        "The write waits for the user to allow it"
+    );
+    tab.write_to_active_terminal("y".as_bytes().to_vec());
+    assert!(
+        tab.pending_clipboard_write.is_none(),
+        "Answering the prompt hides it"
+    );
+}
+
+#[test]
+fn read_only_pane_drops_typed_input() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.toggle_active_pane_read_only();
+    assert!(
+        tab.get_active_pane().unwrap().read_only(),
+        "The focused pane was made read only"
+    );
+    // the fake os api panics on writes to the pty
+    tab.write_to_active_terminal("hello".as_bytes().to_vec());
+    tab.toggle_active_pane_read_only();
+    assert!(
+        !tab.get_active_pane().unwrap().read_only(),
+        "The focused pane accepts input again"
+    );
+}
+
+#[test]
+fn stacked_panes_share_one_slot() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.horizontal_split(PaneId::Terminal(2));
+    tab.toggle_active_pane_stacking();
+    assert_eq!(tab.panes.len(), 1, "Only one pane of the stack is shown");
+    assert!(
+        tab.has_suppressed_pane(&PaneId::Terminal(1)),
+        "The other pane is collapsed"
+    );
+    let shown_pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert_eq!(shown_pane.y(), 0, "The stack takes the space of both panes");
+    assert_eq!(
+        shown_pane.rows(),
+        20,
+        "The stack takes the space of both panes"
+    );
+    assert_eq!(
+        shown_pane.get_content_y(),
+        2,
+        "The contents are below the frame and the title bar of the collapsed pane"
+    );
+
+    tab.move_focus_up();
+    assert_eq!(
+        tab.get_active_pane_id(),
+        Some(PaneId::Terminal(1)),
+        "Moving the focus up shows the previous pane of the stack"
+    );
+    assert!(
+        tab.has_suppressed_pane(&PaneId::Terminal(2)),
+        "The previously shown pane is collapsed"
+    );
+    assert_eq!(tab.panes.get(&PaneId::Terminal(1)).unwrap().rows(), 20);
+
+    tab.toggle_active_pane_stacking();
+    assert_eq!(tab.panes.len(), 2, "Both panes are shown again");
+    let top_pane = tab.panes.get(&PaneId::Terminal(1)).unwrap();
+    assert_eq!(top_pane.y(), 0, "The first pane of the stack is on top");
+    assert_eq!(top_pane.rows(), 10, "The panes split the stack's space");
+    assert_eq!(top_pane.get_content_y(), 1, "No title bars are left");
+    let bottom_pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert_eq!(bottom_pane.y(), 10, "The second pane of the stack is below");
+    assert_eq!(bottom_pane.rows(), 10, "The panes split the stack's space");
+}
+
+#[test]
+fn closing_the_shown_pane_of_a_stack_shows_another_one() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.horizontal_split(PaneId::Terminal(2));
+    tab.toggle_active_pane_stacking();
+    tab.close_pane(PaneId::Terminal(2));
+    assert_eq!(
+        tab.get_active_pane_id(),
+        Some(PaneId::Terminal(1)),
+        "The other pane of the stack is focused"
+    );
+    let pane = tab.panes.get(&PaneId::Terminal(1)).unwrap();
+    assert_eq!(pane.rows(), 20, "It takes the whole slot of the stack");
+    assert_eq!(pane.get_content_y(), 1, "It is no longer stacked");
+}
+
+#[test]
+fn move_pane_up() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.horizontal_split(PaneId::Terminal(2));
+    tab.move_active_pane_up();
+
+    assert_eq!(
+        tab.get_active_pane_id(),
+        Some(PaneId::Terminal(2)),
+        "The moved pane keeps the focus"
+    );
+    assert_eq!(
+        tab.get_active_pane().unwrap().y(),
+        0,
+        "The moved pane is the top one"
+    );
+    assert_eq!(
+        tab.panes.get(&PaneId::Terminal(1)).unwrap().y(),
+        10,
+        "The other pane took its place"
+    );
+}
+
+#[test]
+fn swap_panes_with_the_next_one() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.swap_active_pane_with_next();
+
+    assert_eq!(
+        tab.get_active_pane_id(),
+        Some(PaneId::Terminal(2)),
+        "The swapped pane keeps the focus"
+    );
+    assert_eq!(
+        tab.get_active_pane().unwrap().x(),
+        0,
+        "The last pane swapped places with the first one"
+    );
+    assert_eq!(
+        tab.panes.get(&PaneId::Terminal(1)).unwrap().x(),
+        61,
+        "The first pane took the place of the last one"
+    );
+}
+
+#[test]
+fn toggle_pane_presentation() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.toggle_active_pane_presentation();
+
+    assert!(tab.fullscreen_is_active, "The pane is shown fullscreen");
+    let pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert!(pane.presentation(), "The pane is in presentation mode");
+    assert_eq!(
+        pane.get_content_columns(),
+        60,
+        "Every character takes up two columns"
+    );
+    assert_eq!(pane.get_content_rows(), 10, "Every line takes up two rows");
+
+    tab.toggle_active_pane_presentation();
+    assert!(
+        !tab.fullscreen_is_active,
+        "The pane is no longer fullscreen"
+    );
+    let pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert!(!pane.presentation(), "The pane is back to normal");
+    assert_eq!(pane.cols(), 60, "The pane is back in its place");
+}
+
+#[test]
+fn retile_fills_the_places_left_with_new_panes() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    let layout = TabLayout {
+        direction: Direction::Vertical,
+        parts: vec![TabLayout::default(), TabLayout::default()],
+        ..Default::default()
+    };
+    tab.retile(layout.into(), vec![2]);
+
+    assert_eq!(tab.panes.len(), 2, "The tab holds two panes");
+    let existing_pane = tab.panes.get(&PaneId::Terminal(1)).unwrap();
+    let new_pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert_eq!(
+        existing_pane.x(),
+        0,
+        "The pane of the tab takes the first place"
+    );
+    assert_eq!(
+        new_pane.x(),
+        existing_pane.cols(),
+        "The new pane takes the place left"
+    );
+    assert_eq!(
+        existing_pane.cols() + new_pane.cols(),
+        121,
+        "The panes share the width of the tab"
+    );
+}
+
+#[test]
+fn retile_leaves_out_the_new_panes_beyond_the_layout() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    let layout = TabLayout {
+        parts: vec![TabLayout::default()],
+        ..Default::default()
+    };
+    tab.retile(layout.into(), vec![2]);
+
+    assert_eq!(tab.panes.len(), 1, "The pane of the tab fills the layout");
+    assert!(
+        !tab.panes.contains_key(&PaneId::Terminal(2)),
+        "The new pane has no place in the layout"
+    );
+}
+
+#[test]
+fn retile_stacks_the_panes_beyond_the_layout() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.horizontal_split(PaneId::Terminal(3));
+    let layout = TabLayout {
+        parts: vec![TabLayout::default()],
+        ..Default::default()
+    };
+    tab.retile(layout.into(), vec![]);
+
+    assert_eq!(tab.panes.len(), 1, "The layout has one place");
+    let pane = tab.panes.get(&PaneId::Terminal(1)).unwrap();
+    assert_eq!(pane.cols(), 121, "The first pane takes up the whole tab");
+    assert_eq!(
+        tab.pane_stacks,
+        vec![vec![
+            PaneId::Terminal(1),
+            PaneId::Terminal(2),
+            PaneId::Terminal(3)
+        ]],
+        "The other panes are stacked onto it"
+    );
+}
+
+#[test]
+fn swap_layout_rearranges_the_panes_without_closing_them() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.retile(SwapLayout::EvenVertical.tab_layout(2).into(), vec![]);
+
+    assert_eq!(tab.panes.len(), 2, "The tab still holds both panes");
+    let first_pane = tab.panes.get(&PaneId::Terminal(1)).unwrap();
+    let second_pane = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert_eq!(first_pane.x(), 0, "The first pane is on the left edge");
+    assert_eq!(second_pane.x(), 0, "The second pane is on the left edge");
+    assert_eq!(
+        first_pane.cols(),
+        121,
+        "The first pane takes the full width"
+    );
+    assert_eq!(
+        second_pane.y(),
+        first_pane.rows(),
+        "The second pane is below the first one"
+    );
+}
+
+#[test]
+fn hide_panes_that_no_longer_fit() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.small_panes = SmallPanesPolicy::Hide;
+    for pid in 2..5 {
+        tab.vertical_split(PaneId::Terminal(pid));
+    }
+    let active_pane_id = tab.get_active_pane_id().unwrap();
+    tab.resize_whole_tab(Size { cols: 12, rows: 20 });
+
+    assert!(
+        tab.panes.len() < 4,
+        "Some panes were taken out of the layout"
+    );
+    assert_eq!(
+        tab.panes.len() + tab.suppressed_panes.len(),
+        4,
+        "The hidden panes keep running"
+    );
+    assert_eq!(
+        tab.hidden_small_panes.len(),
+        tab.suppressed_panes.len(),
+        "The hidden panes are counted by the marker"
+    );
+    assert!(
+        tab.panes.contains_key(&active_pane_id),
+        "The focused pane is still shown"
+    );
+}
+
+#[test]
+fn show_hidden_panes_again_once_they_fit() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.small_panes = SmallPanesPolicy::Hide;
+    for pid in 2..5 {
+        tab.vertical_split(PaneId::Terminal(pid));
+    }
+    let active_pane_id = tab.get_active_pane_id().unwrap();
+    tab.resize_whole_tab(Size { cols: 12, rows: 20 });
+    let hidden_pane_ids: Vec<PaneId> = tab.suppressed_panes.keys().copied().collect();
+    assert!(!hidden_pane_ids.is_empty(), "Some panes were hidden");
+    tab.resize_whole_tab(size);
+
+    assert_eq!(tab.panes.len(), 4, "The hidden panes are shown again");
+    assert!(tab.suppressed_panes.is_empty(), "No pane is left hidden");
+    assert!(
+        tab.hidden_small_panes.is_empty(),
+        "The marker no longer counts them"
+    );
+    assert_eq!(
+        tab.get_active_pane_id(),
+        Some(active_pane_id),
+        "The focus stays where it was"
+    );
+    let total_area: usize = tab
+        .panes
+        .values()
+        .map(|pane| pane.cols() * pane.rows())
+        .sum();
+    assert_eq!(total_area, 121 * 20, "The panes share the tab");
+}
+
+#[test]
+fn closed_hidden_panes_are_not_counted() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.small_panes = SmallPanesPolicy::Hide;
+    for pid in 2..5 {
+        tab.vertical_split(PaneId::Terminal(pid));
+    }
+    tab.resize_whole_tab(Size { cols: 12, rows: 20 });
+    let hidden_pane_ids: Vec<PaneId> = tab.suppressed_panes.keys().copied().collect();
+    for pane_id in hidden_pane_ids {
+        tab.close_pane(pane_id);
+    }
+
+    assert!(
+        tab.hidden_small_panes.is_empty(),
+        "The closed panes are forgotten"
+    );
+}
+
+#[test]
+fn stack_panes_that_no_longer_fit() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.small_panes = SmallPanesPolicy::Stack;
+    for pid in 2..5 {
+        tab.vertical_split(PaneId::Terminal(pid));
+    }
+    let active_pane_id = tab.get_active_pane_id().unwrap();
+    tab.resize_whole_tab(Size { cols: 12, rows: 20 });
+
+    assert!(tab.panes.len() < 4, "Some panes were stacked");
+    assert!(!tab.pane_stacks.is_empty(), "The panes share stacks");
+    assert!(
+        tab.panes.contains_key(&active_pane_id),
+        "The focused pane is still shown"
+    );
+}
+
+#[test]
+fn viewer_pane_follows_the_output_of_the_viewed_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.handle_pty_bytes(1, b"hello\r\n".to_vec());
+    tab.new_viewer_pane(PaneId::Terminal(2), PaneId::Terminal(1));
+    tab.handle_pty_bytes(1, b"world".to_vec());
+    tab.handle_pty_bytes(2, b"ignored".to_vec());
+    let viewer = tab.panes.get(&PaneId::Terminal(2)).unwrap();
+    assert!(viewer.read_only(), "Viewer ignores the input of the user");
+    assert_eq!(
+        viewer.dump_scrollback_text(),
+        Some("hello\nworld\n".to_string()),
+        "Viewer shows what the viewed pane showed before and after it opened"
+    );
+
+    tab.close_pane(PaneId::Terminal(1));
+    assert!(
+        tab.viewed_panes.is_empty(),
+        "Viewer stops following the closed pane"
+    );
+}
+
+#[test]
+fn mouse_selection_is_kept_for_the_copy_action_without_copy_on_select() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.handle_pty_bytes(1, b"hello world".to_vec());
+    let select_hello = |tab: &mut Tab| {
+        tab.handle_left_click(&Position::new(1, 1), Default::default());
+        tab.handle_mouse_hold(&Position::new(1, 5));
+        tab.handle_mouse_release(&Position::new(1, 5));
+        tab.get_active_pane().unwrap().get_selected_text()
+    };
+    assert_eq!(
+        select_hello(&mut tab),
+        None,
+        "The selection is copied and cleared on release"
+    );
+
+    tab.pane_clipboard.copy_on_select = false;
+    assert!(
+        select_hello(&mut tab).is_some(),
+        "The selection is kept on release"
+    );
+}
+
+#[test]
+fn tags_pick_panes_and_are_kept_in_the_layout() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.tag_active_pane("scratch".into());
+    tab.tag_active_pane("scratch".into());
+    tab.tag_active_pane("db".into());
+    let tagged: Vec<u32> = tab
+        .pane_summaries(Some("scratch"))
+        .iter()
+        .map(|pane| pane.id)
+        .collect();
+    assert_eq!(tagged, vec![2], "Only the focused pane is tagged");
+    assert_eq!(tab.pane_summaries(None).len(), 2, "Both panes are listed");
+
+    let (layout, _) = tab.layout_of_panes();
+    assert!(layout.parts[0].tags.is_empty());
+    assert_eq!(
+        layout.parts[1].tags,
+        vec!["scratch".to_string(), "db".to_string()],
+        "Tags are given once, in order"
+    );
+
+    tab.untag_active_pane("scratch");
+    assert!(tab.pane_summaries(Some("scratch")).is_empty());
+}
+
+#[test]
+fn wide_redraws_are_split_across_frames_focused_pane_first() {
+    let size = Size {
+        cols: 400,
+        rows: 100,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    let colorful_line = "\u{1b}[31mx\u{1b}[32my".repeat(90) + "\n\r";
+    tab.handle_pty_bytes(2, colorful_line.repeat(95).into_bytes());
+    tab.set_force_render();
+    assert!(tab.render(), "Left pane is left for the next frame");
+    assert!(
+        !tab.panes[&PaneId::Terminal(2)].should_render(),
+        "Focused pane is drawn first"
+    );
+    assert!(tab.panes[&PaneId::Terminal(1)].should_render());
+    assert!(!tab.render(), "Left pane is drawn in the next frame");
+    assert!(!tab.panes[&PaneId::Terminal(1)].should_render());
+}
+
+#[test]
+fn tab_search_lists_the_matches_of_every_pane_and_jumps_to_one() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.handle_pty_bytes(1, b"error: disk full\n\rall good".to_vec());
+    tab.handle_pty_bytes(2, b"no error here".to_vec());
+    tab.update_tab_search(b"error".to_vec());
+    let results: Vec<(PaneId, usize)> = tab
+        .tab_search
+        .as_ref()
+        .unwrap()
+        .results
+        .iter()
+        .map(|result| (result.pane_id, result.index))
+        .collect();
+    assert_eq!(
+        results,
+        vec![(PaneId::Terminal(1), 0), (PaneId::Terminal(2), 0)]
+    );
+
+    tab.jump_to_tab_search_result();
+    assert!(
+        tab.tab_search.is_none(),
+        "The tab search ends with the jump"
+    );
+    assert_eq!(
+        tab.get_active_pane_id(),
+        Some(PaneId::Terminal(1)),
+        "The pane of the selected match is focused"
+    );
+}
+
+#[test]
+pub fn resize_by_cells_moves_the_border_by_that_many_cells() {
+    // ┌─────┬─────┐                        ┌──┬────────┐
+    // │     │█████│                        │  │████████│
+    // │     │█████│  ==resize=left=10==>   │  │████████│
+    // │     │█████│                        │  │████████│
+    // └─────┴─────┘                        └──┴────────┘
+    // █ == focused pane
+
+    let size = Size {
+        cols: 120,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    tab.resize_left_by(Some(ResizeAmount::Cells(10)));
+
+    let pane_1 = tab.panes[&PaneId::Terminal(1)].position_and_size();
+    let pane_2 = tab.panes[&PaneId::Terminal(2)].position_and_size();
+    assert_eq!(pane_1.cols.as_usize(), 50, "pane 1 column count");
+    assert_eq!(pane_2.x, 50, "pane 2 x position");
+    assert_eq!(pane_2.cols.as_usize(), 70, "pane 2 column count");
+
+    tab.horizontal_split(PaneId::Terminal(3));
+    tab.resize_up_by(Some(ResizeAmount::Cells(4)));
+
+    let pane_2 = tab.panes[&PaneId::Terminal(2)].position_and_size();
+    let pane_3 = tab.panes[&PaneId::Terminal(3)].position_and_size();
+    assert_eq!(pane_2.rows.as_usize(), 6, "pane 2 row count");
+    assert_eq!(pane_3.y, 6, "pane 3 y position");
+    assert_eq!(pane_3.rows.as_usize(), 14, "pane 3 row count");
+    assert_eq!(pane_3.x, 50, "pane 3 x position");
+    assert_eq!(pane_3.cols.as_usize(), 70, "pane 3 column count");
+}
+
+#[test]
+fn plugins_are_told_the_modifiers_of_clicks_on_them() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    tab.vertical_split(PaneId::Terminal(2));
+    let (to_plugin, plugin_receiver) = channels::unbounded();
+    let to_plugin = SenderWithContext::new(to_plugin);
+    tab.senders.to_plugin = Some(to_plugin.clone());
+    let geom = tab
+        .panes
+        .remove(&PaneId::Terminal(2))
+        .unwrap()
+        .position_and_size();
+    let plugin = PluginPane::new(2, geom, to_plugin, String::from("plugin"));
+    tab.panes.insert(PaneId::Plugin(2), Box::new(plugin));
+    // stands in for the plugin thread, which the pane waits on to render
+    let plugin_thread = std::thread::spawn(move || {
+        let mut mouse_events = vec![];
+        while let Ok((instruction, _)) = plugin_receiver.recv() {
+            match instruction {
+                PluginInstruction::Render(buf_tx, ..) => buf_tx.send(String::new()).unwrap(),
+                PluginInstruction::Update(Some(2), Event::Mouse(mouse)) => mouse_events.push(mouse),
+                _ => {}
+            }
+        }
+        mouse_events
+    });
+
+    let shift = MouseModifiers {
+        shift: true,
+        ..Default::default()
+    };
+    tab.handle_left_click(&Position::new(2, geom.x as u16 + 3), shift);
+    drop(tab);
+    assert_eq!(
+        plugin_thread.join().unwrap(),
+        vec![Mouse::LeftClick(2, 3, shift)],
+        "The plugin is sent the shift-click"
+    );
+}
@@ -1,6 +1,7 @@
 use log::{info, warn};
 use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::os::unix::io::RawFd;
 use std::path::PathBuf;
 use std::process;
 use std::str::FromStr;
@@ -182,6 +183,10 @@ pub(crate) fn zellij_exports(store: &Store, plugin_env: &PluginEnv) -> ImportObj
         host_open_file,
         host_set_timeout,
         host_exec_cmd,
+        host_write_to_pane,
+        host_focus_pane,
+        host_switch_tab_to,
+        host_close_tab_at,
     }
 }
 
@@ -221,9 +226,10 @@ fn host_open_file(plugin_env: &PluginEnv) {
     let path: PathBuf = wasi_read_object(&plugin_env.wasi_env);
     plugin_env
         .senders
-        .send_to_pty(PtyInstruction::SpawnTerminal(Some(
-            TerminalAction::OpenFile(path),
-        )))
+        .send_to_pty(PtyInstruction::SpawnTerminal(
+            Some(TerminalAction::OpenFile(path)),
+            None,
+        ))
         .unwrap();
 }
 
@@ -274,6 +280,40 @@ fn host_exec_cmd(plugin_env: &PluginEnv) {
         .unwrap();
 }
 
+fn host_write_to_pane(plugin_env: &PluginEnv, pane_id: u32) {
+    let bytes: Vec<u8> = wasi_read_object(&plugin_env.wasi_env);
+    plugin_env
+        .senders
+        .send_to_screen(ScreenInstruction::WriteToPane(
+            PaneId::Terminal(pane_id as RawFd),
+            bytes,
+        ))
+        .unwrap();
+}
+
+fn host_focus_pane(plugin_env: &PluginEnv, pane_id: u32) {
+    plugin_env
+        .senders
+        .send_to_screen(ScreenInstruction::FocusPane(PaneId::Terminal(
+            pane_id as RawFd,
+        )))
+        .unwrap();
+}
+
+fn host_switch_tab_to(plugin_env: &PluginEnv, tab_idx: u32) {
+    plugin_env
+        .senders
+        .send_to_screen(ScreenInstruction::GoToTab(tab_idx))
+        .unwrap();
+}
+
+fn host_close_tab_at(plugin_env: &PluginEnv, tab_idx: u32) {
+    plugin_env
+        .senders
+        .send_to_screen(ScreenInstruction::CloseTabAt(tab_idx))
+        .unwrap();
+}
+
 // Helper Functions ---------------------------------------------------------------------------------------------------
 
 pub fn wasi_read_string(wasi_env: &WasiEnv) -> String {
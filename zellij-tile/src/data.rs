@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::str::FromStr;
 use strum_macros::{EnumDiscriminants, EnumIter, EnumString, ToString};
 
@@ -35,6 +36,23 @@ pub enum Event {
     Timer(f64),
     CopyToClipboard,
     InputReceived,
+    Mouse(Mouse),
+}
+
+/// A mouse event over a plugin pane, with the (line, column) it happened at relative to the pane
+/// and the modifier keys held down.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Mouse {
+    LeftClick(isize, usize, MouseModifiers),
+    MiddleClick(isize, usize, MouseModifiers),
+}
+
+/// The modifier keys held down during a mouse event
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MouseModifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
 }
 
 /// Describes the different input modes, which change the way that keystrokes will be interpreted.
@@ -62,6 +80,30 @@ pub enum InputMode {
     Scroll,
     #[serde(alias = "renametab")]
     RenameTab,
+    #[serde(alias = "renamepane")]
+    RenamePane,
+    #[serde(alias = "renametabgroup")]
+    RenameTabGroup,
+    #[serde(alias = "renamesession")]
+    RenameSession,
+    /// `Search` mode allows searching within the scrollback of the focused pane.
+    #[serde(alias = "search")]
+    Search,
+    /// `TabSearch` mode allows searching within the scrollback of all the panes of the current
+    /// tab, and jumping to one of the matches.
+    #[serde(alias = "tabsearch")]
+    TabSearch,
+    /// `CommandHistory` mode allows picking one of the commands run in the panes of the session,
+    /// and running it again.
+    #[serde(alias = "commandhistory")]
+    CommandHistory,
+    /// `Copy` mode allows selecting text of the focused pane with the keyboard, and copying it.
+    #[serde(alias = "copy")]
+    Copy,
+    /// `SessionManager` mode lists the sessions of the user, to switch to one of them, rename it
+    /// or kill it.
+    #[serde(alias = "sessionmanager")]
+    SessionManager,
     /// `Session` mode allows detaching sessions
     #[serde(alias = "session")]
     Session,
@@ -107,6 +149,14 @@ impl FromStr for InputMode {
             "tab" => Ok(InputMode::Tab),
             "scroll" => Ok(InputMode::Scroll),
             "renametab" => Ok(InputMode::RenameTab),
+            "renamepane" => Ok(InputMode::RenamePane),
+            "renametabgroup" => Ok(InputMode::RenameTabGroup),
+            "renamesession" => Ok(InputMode::RenameSession),
+            "search" => Ok(InputMode::Search),
+            "tabsearch" => Ok(InputMode::TabSearch),
+            "commandhistory" => Ok(InputMode::CommandHistory),
+            "copy" => Ok(InputMode::Copy),
+            "sessionmanager" => Ok(InputMode::SessionManager),
             "session" => Ok(InputMode::Session),
             e => Err(e.to_string().into()),
         }
@@ -160,6 +210,14 @@ pub struct TabInfo {
     pub name: String,
     pub active: bool,
     pub is_sync_panes_active: bool,
+    /// The name of the group the tab is in, if any.
+    pub group: Option<String>,
+    /// Whether the group of the tab is collapsed into a single label in the tab bar.
+    pub is_group_collapsed: bool,
+    /// The working directory of the focused pane of the tab, as its shell reports it (OSC 7).
+    pub cwd: Option<PathBuf>,
+    /// The tags given to the panes of the tab, each of them once and in order.
+    pub pane_tags: Vec<String>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
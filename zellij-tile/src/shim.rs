@@ -34,6 +34,23 @@ pub fn open_file(path: &Path) {
     unsafe { host_open_file() };
 }
 
+pub fn write_to_pane(pane_id: u32, bytes: &[u8]) {
+    object_to_stdout(&bytes);
+    unsafe { host_write_to_pane(pane_id) };
+}
+
+pub fn focus_pane(pane_id: u32) {
+    unsafe { host_focus_pane(pane_id) };
+}
+
+pub fn switch_tab_to(tab_idx: u32) {
+    unsafe { host_switch_tab_to(tab_idx) };
+}
+
+pub fn close_tab_at(tab_idx: u32) {
+    unsafe { host_close_tab_at(tab_idx) };
+}
+
 pub fn set_timeout(secs: f64) {
     unsafe { host_set_timeout(secs) };
 }
@@ -65,4 +82,8 @@ extern "C" {
     fn host_open_file();
     fn host_set_timeout(secs: f64);
     fn host_exec_cmd();
+    fn host_write_to_pane(pane_id: u32);
+    fn host_focus_pane(pane_id: u32);
+    fn host_switch_tab_to(tab_idx: u32);
+    fn host_close_tab_at(tab_idx: u32);
 }
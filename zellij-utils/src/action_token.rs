@@ -0,0 +1,74 @@
+//! The per-session token that has to come along with the actions sent to a session, so that only
+//! the processes able to read it (the user who started the session and the panes of the session)
+//! can drive it, even if the permissions of the socket let others connect to it.
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::{env, fmt::Write as _};
+
+use crate::consts::ZELLIJ_ACTION_TOKEN_ENV;
+
+const TOKEN_BYTES: usize = 16;
+
+/// The file holding the token of the session listening on `socket_path`, next to the socket.
+pub fn token_path(socket_path: &Path) -> PathBuf {
+    let mut path = OsString::from(socket_path);
+    path.push(".token");
+    PathBuf::from(path)
+}
+
+/// A new random token, as hex.
+pub fn generate() -> io::Result<String> {
+    let mut bytes = [0; TOKEN_BYTES];
+    fs::File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    let mut token = String::with_capacity(TOKEN_BYTES * 2);
+    for byte in bytes.iter() {
+        let _ = write!(token, "{:02x}", byte);
+    }
+    Ok(token)
+}
+
+/// Writes `token` to `path`, readable by the owner only.
+pub fn write(path: &Path, token: &str) -> io::Result<()> {
+    drop(fs::remove_file(path));
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(token.as_bytes())
+}
+
+/// The token of the session `name` listening on `socket_path`: the one in the environment when
+/// running in a pane of that session, or else the one in its token file.
+pub fn read(name: &str, socket_path: &Path) -> Option<String> {
+    let in_session = env::var("ZELLIJ_SESSION_NAME").map_or(false, |session| session == name);
+    match env::var(ZELLIJ_ACTION_TOKEN_ENV) {
+        Ok(token) if in_session => Some(token),
+        _ => fs::read_to_string(token_path(socket_path))
+            .ok()
+            .map(|token| token.trim().to_string()),
+    }
+}
+
+/// Compares the tokens without stopping at the first difference, so that how long it takes
+/// says nothing about how much of `given` is right.
+pub fn matches(expected: &str, given: Option<&str>) -> bool {
+    let given = match given {
+        Some(given) => given,
+        None => return false,
+    };
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+// The unit test location.
+#[cfg(test)]
+#[path = "./unit/action_token_test.rs"]
+mod action_token_test;
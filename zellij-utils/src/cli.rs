@@ -1,5 +1,7 @@
 use crate::consts::{ZELLIJ_CONFIG_DIR_ENV, ZELLIJ_CONFIG_FILE_ENV};
-use crate::input::options::Options;
+use crate::input::actions::{Direction, ResizeAmount};
+use crate::input::options::{AmbiguousWidth, Options};
+use crate::input::theme::PaletteColorFromYaml;
 use crate::setup::Setup;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -32,6 +34,10 @@ pub struct CliArgs {
     #[structopt(long, parse(from_os_str))]
     pub layout_path: Option<PathBuf>,
 
+    /// Value of a `{{name}}` variable of the layout, as `name=value`
+    #[structopt(long = "layout-var", parse(try_from_str = parse_name_value))]
+    pub layout_vars: Vec<(String, String)>,
+
     /// Change where zellij looks for the configuration file
     #[structopt(short, long, env=ZELLIJ_CONFIG_FILE_ENV, parse(from_os_str))]
     pub config: Option<PathBuf>,
@@ -75,6 +81,28 @@ pub enum Sessions {
     #[structopt(alias = "ls")]
     ListSessions,
 
+    /// Remove what's left of a session that no longer responds
+    #[structopt(alias = "d")]
+    DeleteSession {
+        /// Name of the session to delete
+        session_name: String,
+    },
+
+    /// Quit a running session, as if its client quit
+    #[structopt(alias = "k")]
+    KillSession {
+        /// Name of the session to kill
+        session_name: String,
+    },
+
+    /// Quit all the running sessions
+    #[structopt(alias = "ka")]
+    KillAllSessions {
+        /// Kill them without asking first
+        #[structopt(long, short)]
+        yes: bool,
+    },
+
     /// Attach to session
     #[structopt(alias = "a")]
     Attach {
@@ -82,11 +110,286 @@ pub enum Sessions {
         session_name: Option<String>,
 
         /// Force attach- session will detach from the other
-        /// zellij client (if any) and attach to this.
+        /// zellij clients (if any) and attach to this, rather
+        /// than alongside them.
         #[structopt(long, short)]
         force: bool,
+        /// Attach as a guest, who sees the session and moves a focus
+        /// of their own around it, but can't type into it or change it.
+        #[structopt(long)]
+        read_only: bool,
         /// Change the behaviour of zellij
         #[structopt(subcommand, name = "options")]
         options: Option<SessionCommand>,
     },
+
+    /// Send an action to a running session
+    #[structopt(alias = "ac")]
+    Action {
+        /// Print the result of the action as json
+        #[structopt(long)]
+        json: bool,
+        #[structopt(subcommand)]
+        action: CliAction,
+    },
+
+    /// Pack the tabs, options and theme of a session into a file, to start it again elsewhere
+    #[structopt(alias = "ex")]
+    ExportSession {
+        /// Name of the session to export
+        session_name: String,
+        /// File to write the bundle to
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
+
+    /// Open a file in the editor of the session (EDITOR or VISUAL) in a new pane
+    #[structopt(alias = "e")]
+    Edit {
+        /// Open it in a pane the editor already runs in instead, if there is one
+        #[structopt(long)]
+        reuse: bool,
+        /// File to edit
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+
+    /// Start a new session from a file written by `export-session`
+    #[structopt(alias = "im")]
+    ImportSession {
+        /// File to read the bundle from
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Name of the new session
+        session_name: Option<String>,
+    },
+
+    /// Start a session whose server is gone again, the way it was last written to disk
+    #[structopt(alias = "r")]
+    Resurrect {
+        /// Name of the session to resurrect
+        session_name: String,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone, Serialize, Deserialize)]
+pub enum CliAction {
+    /// Write raw bytes to the focused pane, eg. `write 27` to send an Escape
+    Write {
+        /// Bytes to write, in decimal or in hex with a leading `0x`
+        #[structopt(required = true, parse(try_from_str = parse_byte))]
+        bytes: Vec<u8>,
+        /// Id of the pane to write to instead of the focused one
+        #[structopt(long)]
+        pane: Option<u32>,
+    },
+    /// Write characters to the focused pane
+    WriteChars {
+        chars: String,
+        /// Id of the pane to write to instead of the focused one
+        #[structopt(long)]
+        pane: Option<u32>,
+    },
+    /// Scroll up one line in the focused pane
+    ScrollUp {
+        /// Id of the pane to scroll instead of the focused one
+        #[structopt(long)]
+        pane: Option<u32>,
+    },
+    /// Close the focused pane
+    ClosePane {
+        /// Id of the pane to close instead of the focused one
+        #[structopt(long)]
+        pane: Option<u32>,
+        /// Close every pane with this tag instead of the focused one
+        #[structopt(long, conflicts_with = "pane")]
+        tag: Option<String>,
+    },
+    /// Give the focused pane a tag, see `list-panes --tag` and `close-pane --tag`
+    TagPane { tag: String },
+    /// Take a tag away from the focused pane
+    UntagPane { tag: String },
+    /// List the panes of the session with their ids, tabs, titles and tags
+    ListPanes {
+        /// List only the panes with this tag
+        #[structopt(long)]
+        tag: Option<String>,
+    },
+    /// Toggle the focused pane fullscreen
+    ToggleFullscreen {
+        /// Id of the pane to focus and toggle instead of the focused one
+        #[structopt(long)]
+        pane: Option<u32>,
+    },
+    /// Focus the pane with the given id, switching to its tab if needed
+    FocusPane { pane_id: u32 },
+    /// Focus a tab or pane of a session, taking the terminal this runs in to that session
+    Focus {
+        /// Session of the tab or pane, detaching the terminal from the session it is in
+        #[structopt(long)]
+        session: Option<String>,
+        /// Position of the tab to focus
+        #[structopt(long)]
+        tab: Option<u32>,
+        /// Id of the pane to focus, switching to its tab
+        #[structopt(long)]
+        pane: Option<u32>,
+        /// Detach any other client from the session
+        #[structopt(long, short)]
+        force: bool,
+    },
+    /// Resize the focused pane
+    Resize {
+        /// Direction to resize the pane in
+        direction: Direction,
+        /// Cells (eg. `5`) or percent of the tab (eg. `10%`) to resize by, instead of the
+        /// built-in step
+        amount: Option<ResizeAmount>,
+    },
+    /// Open a new pane and print its id
+    NewPane {
+        /// Direction to open the pane in, relative to the focused pane
+        #[structopt(short, long)]
+        direction: Option<Direction>,
+        /// Keep the pane running in the background until it is shown
+        #[structopt(long, conflicts_with = "direction")]
+        suppressed: bool,
+    },
+    /// Show a suppressed pane in place of the focused pane
+    ShowSuppressedPane { pane_id: u32 },
+    /// Open a new tab and print its position and the ids of its panes
+    NewTab {
+        /// Name of the new tab
+        #[structopt(short, long)]
+        name: Option<String>,
+        /// Layout of the new tab, from the layout directory or by its path
+        #[structopt(short, long, parse(from_os_str))]
+        layout: Option<PathBuf>,
+        /// Working directory of the shells and commands of the new tab
+        #[structopt(long, parse(from_os_str))]
+        cwd: Option<PathBuf>,
+        /// Environment variable of the shells and commands of the new tab, as `name=value`
+        #[structopt(long, parse(try_from_str = parse_name_value))]
+        env: Vec<(String, String)>,
+    },
+    /// Re-tile the current tab after a layout, keeping its panes and opening new ones in the
+    /// places they don't fill
+    ApplyLayout {
+        /// Layout to apply, from the layout directory or by its path
+        #[structopt(parse(from_os_str))]
+        layout: PathBuf,
+    },
+    /// Open the scrollback of the focused pane in the editor, in a new pane, and print its id
+    EditScrollback,
+    /// Open a read only pane following the output of the focused pane, and print its id
+    ViewPane,
+    /// Draw the focused pane in other colors, or in those of the session again without any
+    SetPanePalette {
+        /// Foreground color, as `#rrggbb`, `r,g,b` or the index of a terminal color
+        #[structopt(long)]
+        fg: Option<PaletteColorFromYaml>,
+        /// Background color, as `#rrggbb`, `r,g,b` or the index of a terminal color
+        #[structopt(long)]
+        bg: Option<PaletteColorFromYaml>,
+    },
+    /// Measure the characters of ambiguous East Asian width printed in the focused pane from now
+    /// on as narrow or wide, or as the `ambiguous_width` option says without a width
+    SetPaneAmbiguousWidth {
+        /// `narrow` or `wide`
+        width: Option<AmbiguousWidth>,
+    },
+    /// Rearrange the panes of the current tab after the next of the predefined arrangements
+    NextSwapLayout,
+    /// Save the tabs of the session and the arrangement of their panes to a layout file
+    DumpLayout {
+        /// Where to write the layout
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
+    /// Write the screen of the focused pane to a file
+    DumpScreen {
+        /// Where to write the screen
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+        /// Write the whole scrollback rather than only what is on the screen
+        #[structopt(long)]
+        full: bool,
+        /// Keep the colors and styles of the text, as ANSI escape sequences
+        #[structopt(long)]
+        ansi: bool,
+    },
+    /// Run a command in a new pane and print the pane's id
+    Run {
+        /// Direction to open the pane in, relative to the focused pane
+        #[structopt(short, long)]
+        direction: Option<Direction>,
+        /// Working directory of the command
+        #[structopt(long, parse(from_os_str))]
+        cwd: Option<PathBuf>,
+        /// Keep the pane running in the background until it is shown
+        #[structopt(long, conflicts_with = "direction")]
+        suppressed: bool,
+        /// The command to run, followed by its arguments
+        #[structopt(required = true)]
+        command: Vec<String>,
+    },
+    /// Show a stopwatch, or a countdown, in the frame of the focused pane
+    StartTimer {
+        /// Count down from this many seconds, or minutes or hours with an `m` or `h` suffix
+        /// (eg. `90`, `25m`, `1h`)
+        #[structopt(long, parse(try_from_str = parse_seconds))]
+        countdown: Option<u64>,
+        /// Command to run with `sh -c` when the countdown elapses
+        #[structopt(long, requires = "countdown")]
+        on_elapsed: Option<String>,
+    },
+    /// Take the timer away from the focused pane
+    StopTimer,
+    /// Rename the session, the panes opened from now on get the new `ZELLIJ_SESSION_NAME`
+    RenameSession { name: String },
+    /// Switch the client of the session over to another session, without leaving the terminal
+    SwitchSession {
+        name: String,
+        /// Detach any other client from the other session
+        #[structopt(long, short)]
+        force: bool,
+    },
+    /// Detach a client from the session, the one that sent the latest action unless given
+    Detach {
+        /// Id of the client to detach, as told when it attached
+        #[structopt(long)]
+        client: Option<u32>,
+    },
+    /// Detach the clients other than the one that sent the latest action, eg. one left attached
+    /// on another machine that keeps the session small
+    DetachOtherClients,
+}
+
+fn parse_name_value(src: &str) -> Result<(String, String), String> {
+    match src.find('=') {
+        Some(position) => Ok((src[..position].to_string(), src[position + 1..].to_string())),
+        None => Err(format!("expected `name=value`, got {:?}", src)),
+    }
+}
+
+fn parse_seconds(src: &str) -> Result<u64, String> {
+    let (number, unit) = if let Some(hours) = src.strip_suffix('h') {
+        (hours, 3600)
+    } else if let Some(minutes) = src.strip_suffix('m') {
+        (minutes, 60)
+    } else {
+        (src.strip_suffix('s').unwrap_or(src), 1)
+    };
+    number
+        .parse::<u64>()
+        .map_err(|e| e.to_string())?
+        .checked_mul(unit)
+        .ok_or_else(|| format!("{} is too long", src))
+}
+
+fn parse_byte(src: &str) -> Result<u8, std::num::ParseIntError> {
+    match src.strip_prefix("0x").or_else(|| src.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => src.parse(),
+    }
 }
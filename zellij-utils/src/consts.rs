@@ -11,6 +11,8 @@ use std::{env, fs};
 pub const ZELLIJ_CONFIG_FILE_ENV: &str = "ZELLIJ_CONFIG_FILE";
 pub const ZELLIJ_CONFIG_DIR_ENV: &str = "ZELLIJ_CONFIG_DIR";
 pub const ZELLIJ_LAYOUT_DIR_ENV: &str = "ZELLIJ_LAYOUT_DIR";
+pub const ZELLIJ_SOCKET_DIR_ENV: &str = "ZELLIJ_SOCKET_DIR";
+pub const ZELLIJ_ACTION_TOKEN_ENV: &str = "ZELLIJ_ACTION_TOKEN";
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub const SYSTEM_DEFAULT_CONFIG_DIR: &str = "/etc/zellij";
@@ -27,17 +29,15 @@ const fn system_default_data_dir() -> &'static str {
 lazy_static! {
     static ref UID: Uid = Uid::current();
     pub static ref SESSION_NAME: OnceCell<String> = OnceCell::new();
+    pub static ref SOCKET_DIR_OPTION: OnceCell<PathBuf> = OnceCell::new();
     pub static ref ZELLIJ_PROJ_DIR: ProjectDirs =
         ProjectDirs::from("org", "Zellij Contributors", "Zellij").unwrap();
     pub static ref ZELLIJ_SOCK_DIR: PathBuf = {
-        let mut ipc_dir = env::var("ZELLIJ_SOCKET_DIR").map_or_else(
-            |_| {
-                ZELLIJ_PROJ_DIR
-                    .runtime_dir()
-                    .map_or_else(|| ZELLIJ_TMP_DIR.clone(), |p| p.to_owned())
-            },
-            PathBuf::from,
-        );
+        let mut ipc_dir = custom_socket_dir().unwrap_or_else(|| {
+            ZELLIJ_PROJ_DIR
+                .runtime_dir()
+                .map_or_else(|| ZELLIJ_TMP_DIR.clone(), |p| p.to_owned())
+        });
         ipc_dir.push(VERSION);
         ipc_dir
     };
@@ -48,12 +48,25 @@ lazy_static! {
         sock_dir.push(SESSION_NAME.get().unwrap());
         sock_dir
     };
+    /// Where sessions are written as they run, for `zellij resurrect` to start them again once
+    /// their server is gone.
+    pub static ref ZELLIJ_RESURRECTION_DIR: PathBuf =
+        ZELLIJ_PROJ_DIR.cache_dir().join("resurrection");
     pub static ref ZELLIJ_TMP_DIR: PathBuf =
         PathBuf::from("/tmp/zellij-".to_string() + &format!("{}", *UID));
     pub static ref ZELLIJ_TMP_LOG_DIR: PathBuf = ZELLIJ_TMP_DIR.join("zellij-log");
     pub static ref ZELLIJ_TMP_LOG_FILE: PathBuf = ZELLIJ_TMP_LOG_DIR.join("log.txt");
 }
 
+/// The socket directory set by the user, in the `ZELLIJ_SOCKET_DIR` environment variable or
+/// else with the `socket_dir` option, if any.
+pub fn custom_socket_dir() -> Option<PathBuf> {
+    env::var(ZELLIJ_SOCKET_DIR_ENV)
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| SOCKET_DIR_OPTION.get().cloned())
+}
+
 pub const FEATURES: &[&str] = &[
     #[cfg(feature = "disable_automatic_asset_installation")]
     "disable_automatic_asset_installation",
@@ -182,7 +182,12 @@ pub enum ScreenContext {
     NewPane,
     HorizontalSplit,
     VerticalSplit,
+    AutoSplit,
+    NewSuppressedPane,
+    ShowSuppressedPane,
     WriteCharacter,
+    WriteToPane,
+    Paste,
     ResizeLeft,
     ResizeRight,
     ResizeDown,
@@ -191,14 +196,22 @@ pub enum ScreenContext {
     FocusNextPane,
     FocusPreviousPane,
     FocusPaneAt,
+    FocusPane,
     MoveFocusLeft,
     MoveFocusLeftOrPreviousTab,
     MoveFocusDown,
     MoveFocusUp,
     MoveFocusRight,
     MoveFocusRightOrNextTab,
+    MovePaneLeft,
+    MovePaneDown,
+    MovePaneUp,
+    MovePaneRight,
+    SwapPanes,
+    BalancePanes,
     Exit,
     ScrollUp,
+    ScrollUpPane,
     ScrollUpAt,
     ScrollDown,
     ScrollDownAt,
@@ -206,10 +219,29 @@ pub enum ScreenContext {
     PageScrollUp,
     PageScrollDown,
     ClearScroll,
+    PageScrollback,
+    EditScrollback,
+    ViewActivePane,
+    NewPaneWithStdinFromSelection,
+    NewViewerPane,
+    QuickCopy,
+    LinkHints,
+    ClearScreen,
+    TogglePaneLink,
+    TogglePaneReadOnly,
+    TogglePaneStacking,
+    TogglePanePresentation,
+    TogglePanePinned,
+    SetPanePalette,
+    SetPaneAmbiguousWidth,
     CloseFocusedPane,
+    ClosePaneWithId,
     ToggleActiveSyncTab,
     ToggleActiveTerminalFullscreen,
+    TogglePaneFullscreen,
     TogglePaneFrames,
+    ToggleCleanScreen,
+    ToggleUI,
     SetSelectable,
     SetInvisibleBorders,
     SetFixedHeight,
@@ -221,24 +253,84 @@ pub enum ScreenContext {
     SwitchTabPrev,
     CloseTab,
     GoToTab,
+    CloseTabAt,
     UpdateTabName,
+    UpdatePaneName,
+    UpdateSearch,
+    Search,
+    ToggleSearchOption,
+    UpdateTabSearch,
+    TabSearch,
+    TabSearchJump,
+    UpdateSessionName,
+    RenameSession,
+    UpdateCommandHistory,
+    CommandHistory,
+    CommandHistoryRun,
+    UpdateSessionManager,
+    SessionManager,
+    SessionManagerSwitch,
+    SessionManagerRename,
+    SessionManagerKill,
+    SessionManagerSessions,
+    CopyModeMove,
+    CopyModeSelect,
+    CopyModeYank,
+    UpdateTabGroupName,
+    MoveTabToNextGroup,
+    ToggleTabGroupCollapsed,
     TerminalResize,
+    TerminalFocus,
     ChangeMode,
     LeftClick,
+    MiddleClick,
     MouseRelease,
     MouseHold,
+    MouseMotion,
     Copy,
+    StartPaneTimer,
+    StopPaneTimer,
+    TickPaneTimers,
     ToggleTab,
+    BreakPaneToNewTab,
+    BreakPaneLeft,
+    BreakPaneRight,
+    DuplicateTab,
+    ApplyLayoutToActiveTab,
+    ApplyLayoutToTab,
+    NextSwapLayout,
+    DumpLayout,
+    SerializeSession,
+    CheckIdle,
+    DumpScreen,
+    TagFocusedPane,
+    UntagFocusedPane,
+    ClosePanesWithTag,
+    ListPanes,
+    AddClient,
+    RemoveClient,
 }
 
 /// Stack call representations corresponding to the different types of [`PtyInstruction`]s.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PtyContext {
     SpawnTerminal,
+    SpawnTerminalOnTempFile,
     SpawnTerminalVertically,
     SpawnTerminalHorizontally,
+    SpawnTerminalAutoSplit,
+    SpawnTerminalSuppressed,
+    SpawnViewer,
     UpdateActivePane,
+    UpdatePaneCwd,
     NewTab,
+    NewTabForPane,
+    DuplicateTab,
+    ApplyLayoutToTab,
+    DumpLayout,
+    SerializeSession,
+    EditFile,
+    GoToTab,
     ClosePane,
     CloseTab,
     Exit,
@@ -262,6 +354,12 @@ pub enum ClientContext {
     UnblockInputThread,
     Render,
     ServerError,
+    Diagnostic,
+    CopyToClipboard,
+    OpenLink,
+    TerminalFocus,
+    SwitchSession,
+    SwitchToMode,
 }
 
 /// Stack call representations corresponding to the different types of [`ServerInstruction`]s.
@@ -269,9 +367,20 @@ pub enum ClientContext {
 pub enum ServerContext {
     NewClient,
     Render,
+    RenderClient,
     UnblockInputThread,
     ClientExit,
     Error,
     DetachSession,
+    DetachOtherClients,
+    SwitchSession,
+    LeaveForSession,
     AttachClient,
+    ResizeClient,
+    FocusClient,
+    Diagnostic,
+    CopyToClipboard,
+    OpenLink,
+    RenameSession,
+    SwitchToMode,
 }
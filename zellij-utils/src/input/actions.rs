@@ -2,19 +2,126 @@
 
 use super::command::RunCommandAction;
 use super::layout::TabLayout;
-use crate::input::options::OnForceClose;
-use serde::{Deserialize, Serialize};
-use zellij_tile::data::InputMode;
+use super::theme::PaletteOverride;
+use crate::cli::CliAction;
+use crate::input::options::{AmbiguousWidth, OnForceClose};
+use crate::ipc::ClientId;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::path::PathBuf;
+use std::str::FromStr;
+use zellij_tile::data::{InputMode, MouseModifiers};
 
 use crate::position::Position;
 
-/// The four directions (left, right, up, down).
+/// The four directions (left, right, up, down), and the automatic one for new panes, which split
+/// the focused pane along its longer side.
 #[derive(Eq, Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub enum Direction {
     Left,
     Right,
     Up,
     Down,
+    Auto,
+}
+
+impl FromStr for Direction {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Left" | "left" => Ok(Self::Left),
+            "Right" | "right" => Ok(Self::Right),
+            "Up" | "up" => Ok(Self::Up),
+            "Down" | "down" => Ok(Self::Down),
+            "Auto" | "auto" => Ok(Self::Auto),
+            e => Err(e.to_string().into()),
+        }
+    }
+}
+
+/// Where [`Action::CopyModeMove`] moves the cursor of the copy mode to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CopyMotion {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// The start of the next word on the line
+    NextWord,
+    /// The start of the word the cursor is in, or of the one before it
+    PreviousWord,
+    LineStart,
+    /// The last character of the line that isn't blank
+    LineEnd,
+}
+
+/// What the selection [`Action::CopyModeSelect`] starts at the cursor of the copy mode takes in,
+/// until wherever the cursor is moved.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SelectionKind {
+    /// The characters in between
+    Character,
+    /// The words in between, whole
+    Word,
+    /// The lines in between, whole
+    Line,
+    /// The rectangle in between
+    Block,
+}
+
+/// How much [`Action::Resize`] grows or shrinks the focused pane by: a number of cells, or a
+/// percentage of the tab.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ResizeAmount {
+    Cells(usize),
+    Percent(usize),
+}
+
+impl FromStr for ResizeAmount {
+    type Err = Box<dyn std::error::Error>;
+
+    // `5` is five cells, `10%` ten percent of the tab
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_suffix('%') {
+            Some(percent) => Ok(Self::Percent(percent.parse()?)),
+            None => Ok(Self::Cells(s.parse()?)),
+        }
+    }
+}
+
+/// The timer [`Action::StartPaneTimer`] shows in the frame of the focused pane: a countdown, or a
+/// stopwatch without one.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PaneTimer {
+    /// Seconds to count down from
+    #[serde(default)]
+    pub countdown: Option<u64>,
+    /// Command run with `sh -c` on the machine of the session when the countdown elapses
+    #[serde(default)]
+    pub on_elapsed: Option<String>,
+}
+
+// resizes are written as `Resize: Left` in the config, or `Resize: [Left, Cells: 5]` with an
+// amount, while the binary encoding between the client and the server keeps both fields
+fn deserialize_resize<'de, D>(
+    deserializer: D,
+) -> Result<(Direction, Option<ResizeAmount>), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ResizeInConfig {
+        Direction(Direction),
+        DirectionAndAmount(Direction, ResizeAmount),
+    }
+    if !deserializer.is_human_readable() {
+        return Deserialize::deserialize(deserializer);
+    }
+    Ok(match ResizeInConfig::deserialize(deserializer)? {
+        ResizeInConfig::Direction(direction) => (direction, None),
+        ResizeInConfig::DirectionAndAmount(direction, amount) => (direction, Some(amount)),
+    })
 }
 
 // As these actions are bound to the default config, please
@@ -28,10 +135,18 @@ pub enum Action {
     Quit,
     /// Write to the terminal.
     Write(Vec<u8>),
+    /// Write to the terminal with the given id, regardless of focus.
+    WriteToPane(u32, Vec<u8>),
+    /// Paste text into the terminal, as sent by the terminal between bracketed paste sequences.
+    Paste(Vec<u8>),
+    /// Paste the text in the clipboard into the terminal, read by the client from the terminal
+    /// it runs in or from the `paste_command` option.
+    PasteFromClipboard,
     /// Switch to the specified input mode.
     SwitchToMode(InputMode),
-    /// Resize focus pane in specified direction.
-    Resize(Direction),
+    /// Resize focus pane in specified direction, by the given amount or by the built-in step.
+    #[serde(deserialize_with = "deserialize_resize")]
+    Resize(Direction, Option<ResizeAmount>),
     /// Switch focus to next pane in specified direction.
     FocusNextPane,
     FocusPreviousPane,
@@ -41,8 +156,16 @@ pub enum Action {
     /// Tries to move the focus pane in specified direction.
     /// If there is no pane in the direction, move to previous/next Tab.
     MoveFocusOrTab(Direction),
+    /// Move the focus pane in specified direction, swapping places with the pane there.
+    MovePane(Direction),
+    /// Swap the places of the focus pane and the next pane, keeping the focus on the former.
+    SwapPanes,
+    /// Share the space of the tab evenly between its panes, keeping them where they are.
+    BalancePanes,
     /// Scroll up in focus pane.
     ScrollUp,
+    /// Scroll up in the terminal with the given id.
+    ScrollUpPane(u32),
     /// Scroll up at point
     ScrollUpAt(Position),
     /// Scroll down in focus pane.
@@ -51,25 +174,105 @@ pub enum Action {
     ScrollDownAt(Position),
     /// Scroll down to bottom in focus pane.
     ScrollToBottom,
+    /// Open the scrollback of the focus pane in a pager, in a new pane.
+    PageScrollback,
+    /// Open the scrollback of the focus pane as plain text in the editor (`$EDITOR` or
+    /// `$VISUAL`), in a new pane.
+    EditScrollback,
+    /// Open a read only pane showing the output of the focus pane as it comes, which can be
+    /// scrolled without scrolling the focus pane.
+    ViewPane,
+    /// Show hints over the text matching the quick copy patterns in the focus pane, typing the
+    /// label of a hint copies its text.
+    QuickCopy,
+    /// Show hints over the links (urls and paths) in the focus pane, typing the label of a hint
+    /// opens its link, typing it in capitals copies it.
+    LinkHints,
+    /// Clear the screen of the focus pane, and its scrollback as well if `true`.
+    ClearScreen(bool),
     /// Scroll up one page in focus pane.
     PageScrollUp,
     /// Scroll down one page in focus pane.
     PageScrollDown,
     /// Toggle between fullscreen focus pane and normal layout.
     ToggleFocusFullscreen,
+    /// Focus the terminal with the given id and toggle it fullscreen.
+    TogglePaneFullscreen(u32),
+    /// Link the focus pane with another one, picked by typing the label shown over it, so that
+    /// they scroll together. Unlinks the focus pane if it is already linked.
+    TogglePaneLink,
+    /// Make the focus pane ignore typed input, or accept it again.
+    TogglePaneReadOnly,
+    /// Stack the focus pane with the pane next to it, so that they share one slot, or take it
+    /// back out of its stack.
+    TogglePaneStacking,
+    /// Show the focus pane fullscreen at double size, for presenting, or back to normal. Relies
+    /// on the terminal supporting double size lines (DECDHL), like xterm does.
+    TogglePanePresentation,
+    /// Pin the focus pane, so that it follows the focus from tab to tab and stays in view, or
+    /// unpin it.
+    TogglePanePinned,
+    /// Draw the focus pane with some colors of the palette replaced, eg. a red background for a
+    /// pane that shouldn't be typed into by mistake, or with the palette of the session again.
+    SetPanePalette(Option<PaletteOverride>),
+    /// Measure the characters of ambiguous East Asian width printed in the focus pane from now
+    /// on as narrow or wide, eg. for a program drawing with them in a font of its own, or as the
+    /// `ambiguous_width` option does again.
+    SetPaneAmbiguousWidth(Option<AmbiguousWidth>),
     /// Toggle frames around panes in the UI
     TogglePaneFrames,
+    /// Hide pane frames, the tab and status bars and overlays like link labels, eg. while
+    /// recording the screen, or show them again.
+    ToggleCleanScreen,
+    /// Hide the tab and status bars, giving their rows to the panes, or show them again.
+    ToggleUI,
     /// Toggle between sending text commands to all panes on the current tab and normal mode.
     ToggleActiveSyncTab,
     /// Open a new pane in the specified direction (relative to focus).
-    /// If no direction is specified, will try to use the biggest available space.
+    /// If no direction is specified, or the automatic one, the focused pane is split along its
+    /// longer side, or the biggest available space is used when it is too small.
     NewPane(Option<Direction>),
     /// Close the focus pane.
     CloseFocus,
-    /// Create a new tab, optionally with a specified tab layout.
+    /// Close the terminal with the given id.
+    ClosePane(u32),
+    /// Focus the terminal with the given id, switching to its tab if needed.
+    FocusPane(u32),
+    /// Create a new tab, optionally with a specified tab layout. Its `name`, `layout` file and
+    /// `cwd` name the tab, take its parts from a layout file and set where its terminals start.
     NewTab(Option<TabLayout>),
+    /// Create a new tab with the panes of the current one, in the same places and directories.
+    /// With `true`, the panes run their commands again instead of starting shells.
+    DuplicateTab(bool),
+    /// Re-tile the current tab after a layout file, from the layout directory or by its path.
+    /// The panes of the tab take the places of the layout in order, new panes are opened in the
+    /// places left.
+    ApplyLayout(PathBuf),
+    /// Rearrange the panes of the current tab after the next of the predefined arrangements
+    /// (even-horizontal, even-vertical, main-vertical and tiled), keeping them running.
+    NextSwapLayout,
+    /// Save the tabs of the session and the arrangement of their panes, with the commands and
+    /// directories the panes run in, to a layout file at the given path.
+    DumpLayout(PathBuf),
+    /// Open a file in the editor of the session in a new pane or, if the flag is set, in a pane
+    /// the editor already runs in when there is one.
+    EditFile(PathBuf, bool),
+    /// Write the screen of the focused pane to a file at the given path, with its whole
+    /// scrollback if the first flag is set and with its styles as ANSI escape sequences if the
+    /// second one is.
+    DumpScreen(PathBuf, bool, bool),
+    /// Give the focused pane a tag, which other actions can pick panes by.
+    TagPane(String),
+    /// Take a tag away from the focused pane.
+    UntagPane(String),
+    /// Close every pane with the given tag, in whichever tab it is.
+    ClosePanesWithTag(String),
+    /// List the terminals of the session, only those with the given tag if any.
+    ListPanes(Option<String>),
     /// Do nothing.
     NoOp,
+    /// Run the actions of the user-defined macro with the given name.
+    Macro(String),
     /// Go to the next tab.
     GoToNextTab,
     /// Go to the previous tab.
@@ -77,23 +280,253 @@ pub enum Action {
     /// Close the current tab.
     CloseTab,
     GoToTab(u32),
+    /// Go back to the tab focused before the current one, wherever it is, each client going
+    /// back through its own tabs.
+    #[serde(alias = "GoToPreviousFocusedTab")]
     ToggleTab,
+    /// Move the focus pane to a new tab, keeping its process running.
+    BreakPaneToNewTab,
+    /// Move the focus pane to the previous tab, closing its tab if it was the last pane there.
+    BreakPaneLeft,
+    /// Move the focus pane to the next tab, closing its tab if it was the last pane there.
+    BreakPaneRight,
     TabNameInput(Vec<u8>),
+    /// Type into the name of the focus pane, shown in its frame instead of its title. A `0`
+    /// clears the name.
+    PaneNameInput(Vec<u8>),
+    /// Type into the name of the group of the focused tab. A `0` puts an ungrouped tab into a
+    /// new group, or clears the name of the group the tab is in.
+    TabGroupNameInput(Vec<u8>),
+    /// Type into the new name of the session, shown in place of its name until the
+    /// `RenameSession` mode is left, which renames the session. A `0` clears the new name, an
+    /// Escape (`27`) drops it.
+    SessionNameInput(Vec<u8>),
+    /// Rename the session, its socket and the `ZELLIJ_SESSION_NAME` of the panes opened from now
+    /// on.
+    RenameSession(String),
+    /// Type into the text searched for in the focus pane, scrollback included. A `0` clears the
+    /// text.
+    SearchInput(Vec<u8>),
+    /// Scroll to the next match of the search further down.
+    SearchNext,
+    /// Scroll to the next match of the search further up.
+    SearchPrev,
+    /// Toggle whether the search tells upper and lower case apart.
+    SearchToggleCaseSensitivity,
+    /// Toggle whether moving past the last match of the search goes back to the first one.
+    SearchToggleWrap,
+    /// Type into the text searched for in all the panes of the current tab, listing the matches.
+    /// A `0` clears the text.
+    TabSearchInput(Vec<u8>),
+    /// Select the next match of the tab search further down the list.
+    TabSearchNext,
+    /// Select the next match of the tab search further up the list.
+    TabSearchPrev,
+    /// Focus the pane of the selected match of the tab search, and scroll to the match.
+    TabSearchJump,
+    /// Type into the filter of the commands run in the panes of the session, listing those it
+    /// matches. A `0` clears the filter.
+    CommandHistoryInput(Vec<u8>),
+    /// Select the next command of the command history further down the list.
+    CommandHistoryNext,
+    /// Select the next command of the command history further up the list.
+    CommandHistoryPrev,
+    /// Run the selected command of the command history again in the focused pane.
+    CommandHistoryRun,
+    /// Run the selected command of the command history again in the pane it was run in,
+    /// focusing that pane.
+    CommandHistoryRunInItsPane,
+    /// Type into the filter of the sessions of the user, listing those whose name or pane titles
+    /// have its characters in order, or into the new name of the selected session while
+    /// renaming it. A `0` clears the text.
+    SessionManagerInput(Vec<u8>),
+    /// Select the next session of the session manager further down the list.
+    SessionManagerNext,
+    /// Select the next session of the session manager further up the list.
+    SessionManagerPrev,
+    /// Attach to the selected session of the session manager, resurrecting it if it exited.
+    SessionManagerSwitch,
+    /// Start typing a new name for the selected session of the session manager, or rename it
+    /// to the name typed.
+    SessionManagerRename,
+    /// Kill the selected session of the session manager, deleting it if it exited.
+    SessionManagerKill,
+    /// Move the cursor of the copy mode, extending the selection if one was started.
+    CopyModeMove(CopyMotion),
+    /// Start a selection at the cursor of the copy mode, or stop it if it is of the same kind.
+    CopyModeSelect(SelectionKind),
+    /// Copy the text selected in copy mode to the clipboard.
+    CopyModeYank,
+    /// Move the focused tab to the next tab group, or out of the groups after the last one.
+    MoveTabToNextGroup,
+    /// Collapse the group of the focused tab into a single label in the tab bar, or expand it.
+    ToggleTabGroupCollapsed,
     /// Run speficied command in new pane.
     Run(RunCommandAction),
-    /// Detach session and exit
-    Detach,
-    LeftClick(Position),
+    /// Run the specified command in a new pane, with the text selected in the focus pane as its
+    /// standard input, or the output of the last command run there if nothing is selected.
+    NewPaneWithStdinFromSelection(RunCommandAction),
+    /// Open a pane that keeps running in the background without being displayed, running the
+    /// specified command or the default shell.
+    NewSuppressedPane(Option<RunCommandAction>),
+    /// Swap the suppressed terminal with the given id into view, in place of the focused pane.
+    ShowSuppressedPane(u32),
+    /// Detach a client from the session, this one (which exits) unless the id of another is
+    /// given. The other clients stay attached.
+    Detach(Option<ClientId>),
+    /// Detach the clients other than this one from the session, eg. one left attached on
+    /// another machine that keeps the session small.
+    DetachOtherClients,
+    /// Switch the client over to the session with the given name, detaching the other clients
+    /// of that session if the flag is set. The client stays in the terminal when
+    /// the session runs, and starts the session again when it exited.
+    SwitchSession(String, bool),
+    /// Click at this point. With shift held down, extend the selection from the previous click
+    /// to it; with ctrl, open the hyperlink there with the link opener, if there is one.
+    LeftClick(Position, MouseModifiers),
+    MiddleClick(Position, MouseModifiers),
     MouseRelease(Position),
     MouseHold(Position),
+    /// Focus the pane under the mouse pointer at this point.
+    MouseMotion(Position),
+    /// Copy the text selected in the focus pane to the clipboard.
     Copy,
+    /// Show a countdown or a stopwatch in the frame of the focused pane, replacing any it has.
+    StartPaneTimer(PaneTimer),
+    /// Take the timer away from the focused pane.
+    StopPaneTimer,
 }
 
 impl From<OnForceClose> for Action {
     fn from(ofc: OnForceClose) -> Action {
         match ofc {
             OnForceClose::Quit => Action::Quit,
-            OnForceClose::Detach => Action::Detach,
+            OnForceClose::Detach => Action::Detach(None),
         }
     }
 }
+
+impl From<CliAction> for Action {
+    fn from(cli_action: CliAction) -> Action {
+        match cli_action {
+            CliAction::Write { bytes, pane } => match pane {
+                Some(pane_id) => Action::WriteToPane(pane_id, bytes),
+                None => Action::Write(bytes),
+            },
+            CliAction::WriteChars { chars, pane } => match pane {
+                Some(pane_id) => Action::WriteToPane(pane_id, chars.into_bytes()),
+                None => Action::Write(chars.into_bytes()),
+            },
+            CliAction::ScrollUp { pane } => pane.map_or(Action::ScrollUp, Action::ScrollUpPane),
+            CliAction::ClosePane { pane, tag } => match (pane, tag) {
+                (Some(pane_id), _) => Action::ClosePane(pane_id),
+                (None, Some(tag)) => Action::ClosePanesWithTag(tag),
+                (None, None) => Action::CloseFocus,
+            },
+            CliAction::ToggleFullscreen { pane } => {
+                pane.map_or(Action::ToggleFocusFullscreen, Action::TogglePaneFullscreen)
+            }
+            CliAction::FocusPane { pane_id } => Action::FocusPane(pane_id),
+            // pane ids are unique in a session, the tab only matters without one
+            CliAction::Focus { tab, pane, .. } => match (tab, pane) {
+                (_, Some(pane_id)) => Action::FocusPane(pane_id),
+                (Some(tab_index), None) => Action::GoToTab(tab_index),
+                (None, None) => Action::NoOp,
+            },
+            CliAction::Resize { direction, amount } => Action::Resize(direction, amount),
+            CliAction::NewPane {
+                suppressed: true, ..
+            } => Action::NewSuppressedPane(None),
+            CliAction::NewPane { direction, .. } => Action::NewPane(direction),
+            CliAction::ShowSuppressedPane { pane_id } => Action::ShowSuppressedPane(pane_id),
+            CliAction::NewTab {
+                name,
+                layout,
+                cwd,
+                env,
+            } => {
+                if name.is_none() && layout.is_none() && cwd.is_none() && env.is_empty() {
+                    Action::NewTab(None)
+                } else {
+                    Action::NewTab(Some(TabLayout {
+                        name: name.unwrap_or_default(),
+                        layout: layout.map(layout_path_from_current_dir),
+                        cwd: cwd.map(path_from_current_dir),
+                        env: env.into_iter().collect(),
+                        ..Default::default()
+                    }))
+                }
+            }
+            CliAction::ApplyLayout { layout } => {
+                Action::ApplyLayout(layout_path_from_current_dir(layout))
+            }
+            CliAction::NextSwapLayout => Action::NextSwapLayout,
+            CliAction::EditScrollback => Action::EditScrollback,
+            CliAction::ViewPane => Action::ViewPane,
+            CliAction::SetPanePalette { fg: None, bg: None } => Action::SetPanePalette(None),
+            CliAction::SetPanePalette { fg, bg } => Action::SetPanePalette(Some(PaletteOverride {
+                fg,
+                bg,
+                ..Default::default()
+            })),
+            CliAction::SetPaneAmbiguousWidth { width } => Action::SetPaneAmbiguousWidth(width),
+            CliAction::DumpLayout { path } => Action::DumpLayout(path_from_current_dir(path)),
+            CliAction::DumpScreen { path, full, ansi } => {
+                Action::DumpScreen(path_from_current_dir(path), full, ansi)
+            }
+            CliAction::TagPane { tag } => Action::TagPane(tag),
+            CliAction::UntagPane { tag } => Action::UntagPane(tag),
+            CliAction::ListPanes { tag } => Action::ListPanes(tag),
+            CliAction::Run {
+                direction,
+                cwd,
+                suppressed,
+                mut command,
+            } => {
+                let args = command.split_off(1);
+                let run_command_action = RunCommandAction {
+                    command: command.remove(0).into(),
+                    args,
+                    cwd,
+                    direction,
+                };
+                if suppressed {
+                    Action::NewSuppressedPane(Some(run_command_action))
+                } else {
+                    Action::Run(run_command_action)
+                }
+            }
+            CliAction::StartTimer {
+                countdown,
+                on_elapsed,
+            } => Action::StartPaneTimer(PaneTimer {
+                countdown,
+                on_elapsed,
+            }),
+            CliAction::StopTimer => Action::StopPaneTimer,
+            CliAction::RenameSession { name } => Action::RenameSession(name),
+            CliAction::SwitchSession { name, force } => Action::SwitchSession(name, force),
+            CliAction::Detach { client } => Action::Detach(client),
+            CliAction::DetachOtherClients => Action::DetachOtherClients,
+        }
+    }
+}
+
+// the paths given to the CLI are taken from where the command is run, rather than where the
+// server runs
+fn path_from_current_dir(path: PathBuf) -> PathBuf {
+    std::env::current_dir()
+        .map(|cwd| cwd.join(&path))
+        .unwrap_or(path)
+}
+
+// a layout that isn't a file from where the command is run is left to be looked for by name in
+// the layout directory
+fn layout_path_from_current_dir(layout: PathBuf) -> PathBuf {
+    let from_current_dir = path_from_current_dir(layout.clone());
+    if from_current_dir.is_file() || from_current_dir.with_extension("yaml").is_file() {
+        from_current_dir
+    } else {
+        layout
+    }
+}
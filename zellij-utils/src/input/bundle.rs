@@ -0,0 +1,134 @@
+//! Sessions packed into a single file, to start them again on another machine, or on this one
+//! after their server is gone.
+use super::config::ConfigError;
+use super::layout::{LayoutFromYaml, LayoutTemplate, Run, TabLayout};
+use super::options::Options;
+use super::theme::ThemesFromYaml;
+use crate::consts::ZELLIJ_RESURRECTION_DIR;
+use crate::serde_yaml;
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+/// The file the session `name` is written to as it runs, to resurrect it.
+pub fn resurrection_path(name: &str) -> PathBuf {
+    ZELLIJ_RESURRECTION_DIR.join(format!("{}.yaml", name))
+}
+
+/// What it takes to start a session again: the tabs and panes it had, the options and themes
+/// it ran with, and the plugins its layout loads.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SessionBundle {
+    pub layout: LayoutFromYaml,
+    #[serde(default)]
+    pub options: Options,
+    #[serde(default)]
+    pub themes: Option<ThemesFromYaml>,
+    #[serde(default)]
+    pub plugins: Vec<PathBuf>,
+}
+
+impl SessionBundle {
+    /// Bundles `layout` with `options` and `themes`, leaving out the options that only make
+    /// sense on this machine (its directories, default shell and copy command).
+    pub fn new(
+        layout: LayoutFromYaml,
+        mut options: Options,
+        themes: Option<ThemesFromYaml>,
+    ) -> Self {
+        options.default_shell = None;
+        options.layout_dir = None;
+        options.socket_dir = None;
+        options.copy_command = None;
+        options.paste_command = None;
+        SessionBundle {
+            themes,
+            ..SessionBundle::resurrectable(layout, options)
+        }
+    }
+
+    /// Bundles `layout` with all the `options` of a session, to start it again on this machine.
+    /// The themes are those of the client resurrecting it.
+    pub fn resurrectable(layout: LayoutFromYaml, options: Options) -> Self {
+        let mut plugins = vec![];
+        template_plugins(&layout.template, &mut plugins);
+        for tab in &layout.tabs {
+            tab_plugins(tab, &mut plugins);
+        }
+        plugins.sort();
+        plugins.dedup();
+        SessionBundle {
+            layout,
+            options,
+            themes: None,
+            plugins,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self, ConfigError> {
+        let bundle = fs::read_to_string(path).map_err(|e| ConfigError::IoPath(e, path.into()))?;
+        serde_yaml::from_str(&bundle).map_err(|e| ConfigError::SerdePath(e, path.into()))
+    }
+
+    pub fn write_to(&self, path: &Path) -> Result<(), ConfigError> {
+        let bundle = serde_yaml::to_string(self)?;
+        fs::write(path, bundle).map_err(|e| ConfigError::IoPath(e, path.into()))
+    }
+
+    /// Writes the bundle to `path` readable by the owner only, as it can hold the scrollback of
+    /// the panes. The file is replaced at once, a bundle is never read half written.
+    pub fn write_privately_to(&self, path: &Path) -> Result<(), ConfigError> {
+        let bundle = serde_yaml::to_string(self)?;
+        let mut partial_path = path.as_os_str().to_owned();
+        partial_path.push(".partial");
+        let partial_path = PathBuf::from(partial_path);
+        let io_error = |e| ConfigError::IoPath(e, path.into());
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        drop(fs::remove_file(&partial_path));
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&partial_path)
+            .map_err(io_error)?;
+        file.write_all(bundle.as_bytes()).map_err(io_error)?;
+        fs::rename(&partial_path, path).map_err(io_error)
+    }
+
+    /// The plugins of the bundle given by a path that does not exist here. Plugins given by
+    /// name are looked up in the data directory when the session starts.
+    pub fn missing_plugins(&self) -> Vec<&Path> {
+        self.plugins
+            .iter()
+            .filter(|plugin| plugin.is_absolute() && !plugin.exists())
+            .map(PathBuf::as_path)
+            .collect()
+    }
+}
+
+fn template_plugins(template: &LayoutTemplate, plugins: &mut Vec<PathBuf>) {
+    if let Some(Run::Plugin(Some(plugin))) = &template.run {
+        plugins.push(plugin.path.clone());
+    }
+    for part in &template.parts {
+        template_plugins(part, plugins);
+    }
+}
+
+fn tab_plugins(tab: &TabLayout, plugins: &mut Vec<PathBuf>) {
+    if let Some(Run::Plugin(Some(plugin))) = &tab.run {
+        plugins.push(plugin.path.clone());
+    }
+    for part in &tab.parts {
+        tab_plugins(part, plugins);
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/bundle_test.rs"]
+mod bundle_test;
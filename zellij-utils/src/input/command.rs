@@ -1,12 +1,17 @@
 //! Trigger a command
 use super::actions::Direction;
+use super::options::ShutdownSignal;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub enum TerminalAction {
     OpenFile(PathBuf),
+    PageFile(PathBuf),
     RunCommand(RunCommand),
+    /// Runs the command with the contents of the file as its standard input.
+    PipeFile(PathBuf, RunCommand),
 }
 
 #[derive(Clone, Debug, Deserialize, Default, Serialize, PartialEq, Eq)]
@@ -17,12 +22,23 @@ pub struct RunCommand {
     pub args: Vec<String>,
     #[serde(default)]
     pub cwd: Option<PathBuf>,
+    // set in the environment of the command, on top of the one zellij runs in
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    // the signal the command gets when the session quits, instead of the `shutdown_signal` option
+    #[serde(default)]
+    pub shutdown_signal: Option<ShutdownSignal>,
+    // shown in the pane before the output of the command, the scrollback the pane had in a
+    // resurrected session
+    #[serde(default)]
+    pub scrollback: Option<String>,
 }
 
-/// Intermediate representation
+/// Intermediate representation, used by the `Run` action to open a pane running
+/// `command` instead of the default shell
 #[derive(Clone, Debug, Deserialize, Default, Serialize, PartialEq, Eq)]
 pub struct RunCommandAction {
-    #[serde(rename = "cmd")]
+    #[serde(rename = "cmd", alias = "command")]
     pub command: PathBuf,
     #[serde(default)]
     pub args: Vec<String>,
@@ -38,6 +54,7 @@ impl From<RunCommandAction> for RunCommand {
             command: action.command,
             args: action.args,
             cwd: action.cwd,
+            ..Default::default()
         }
     }
 }
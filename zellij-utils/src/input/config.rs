@@ -6,6 +6,8 @@ use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 
 use super::keybinds::{Keybinds, KeybindsFromYaml};
+use super::keyboard_layout::KeyboardLayouts;
+use super::macros::Macros;
 use super::options::Options;
 use super::theme::ThemesFromYaml;
 use crate::cli::{CliArgs, Command};
@@ -25,6 +27,8 @@ pub struct ConfigFromYaml {
     pub options: Option<Options>,
     pub keybinds: Option<KeybindsFromYaml>,
     pub themes: Option<ThemesFromYaml>,
+    pub macros: Option<Macros>,
+    pub keyboard_layouts: Option<KeyboardLayouts>,
 }
 
 /// Main configuration.
@@ -33,12 +37,16 @@ pub struct Config {
     pub keybinds: Keybinds,
     pub options: Options,
     pub themes: Option<ThemesFromYaml>,
+    pub macros: Macros,
+    pub keyboard_layouts: KeyboardLayouts,
 }
 
 #[derive(Debug)]
 pub enum ConfigError {
     // Deserialization error
     Serde(serde_yaml::Error),
+    // Deserialization error with path context
+    SerdePath(serde_yaml::Error, PathBuf),
     // Io error
     Io(io::Error),
     // Io error with path context
@@ -48,6 +56,8 @@ pub enum ConfigError {
     // Missing the tab section in the layout.
     Layout(LayoutMissingTabSectionError),
     LayoutPartAndTab(LayoutPartAndTabError),
+    // An unfilled variable or an include cycle in the layout.
+    LayoutTemplate(LayoutTemplateError),
 }
 
 impl Default for Config {
@@ -55,11 +65,15 @@ impl Default for Config {
         let keybinds = Keybinds::default();
         let options = Options::default();
         let themes = None;
+        let macros = Macros::default();
+        let keyboard_layouts = KeyboardLayouts::default();
 
         Config {
             keybinds,
             options,
             themes,
+            macros,
+            keyboard_layouts,
         }
     }
 }
@@ -107,10 +121,17 @@ impl Config {
                 let keybinds = Keybinds::get_default_keybinds_with_config(config.keybinds);
                 let options = Options::from_yaml(config.options);
                 let themes = config.themes;
+                let macros = config.macros.unwrap_or_default();
+                let keyboard_layouts = match config.keyboard_layouts {
+                    Some(layouts) => KeyboardLayouts::default().merge(layouts),
+                    None => KeyboardLayouts::default(),
+                };
                 Ok(Config {
                     keybinds,
                     options,
                     themes,
+                    macros,
+                    keyboard_layouts,
                 })
             }
         }
@@ -123,7 +144,7 @@ impl Config {
                 let mut yaml_config = String::new();
                 file.read_to_string(&mut yaml_config)
                     .map_err(|e| ConfigError::IoPath(e, path.to_path_buf()))?;
-                Ok(Config::from_yaml(&yaml_config)?)
+                Config::from_yaml(&yaml_config).map_err(|e| e.with_path(path))
             }
             Err(e) => Err(ConfigError::IoPath(e, path.into())),
         }
@@ -142,6 +163,13 @@ impl Config {
 pub struct LayoutMissingTabSectionError;
 #[derive(Debug, Clone)]
 pub struct LayoutPartAndTabError;
+#[derive(Debug, Clone)]
+pub enum LayoutTemplateError {
+    /// A `{{name}}` variable that was given no value.
+    UndefinedVariable(String),
+    /// A layout including itself, through the layouts it includes.
+    IncludeCycle(PathBuf),
+}
 
 impl fmt::Display for LayoutMissingTabSectionError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -206,6 +234,35 @@ impl std::error::Error for LayoutPartAndTabError {
     }
 }
 
+impl fmt::Display for LayoutTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LayoutTemplateError::UndefinedVariable(name) => write!(
+                f,
+                "the variable `{{{{{}}}}}` has no value, give it one with `--layout-var {}=<value>` or an environment variable",
+                name, name
+            ),
+            LayoutTemplateError::IncludeCycle(path) => write!(
+                f,
+                "the layout {} includes itself through the layouts it includes",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutTemplateError {}
+
+impl ConfigError {
+    /// Names the file a deserialization error was found in.
+    pub fn with_path(self, path: &Path) -> Self {
+        match self {
+            ConfigError::Serde(err) => ConfigError::SerdePath(err, path.into()),
+            err => err,
+        }
+    }
+}
+
 impl Display for ConfigError {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -214,6 +271,12 @@ impl Display for ConfigError {
                 write!(formatter, "IoError: {}, File: {}", err, path.display(),)
             }
             ConfigError::Serde(ref err) => write!(formatter, "Deserialization error: {}", err),
+            ConfigError::SerdePath(ref err, ref path) => write!(
+                formatter,
+                "Deserialization error: {}, File: {}",
+                err,
+                path.display()
+            ),
             ConfigError::FromUtf8(ref err) => write!(formatter, "FromUtf8Error: {}", err),
             ConfigError::Layout(ref err) => {
                 write!(formatter, "There was an error in the layout file, {}", err)
@@ -221,6 +284,9 @@ impl Display for ConfigError {
             ConfigError::LayoutPartAndTab(ref err) => {
                 write!(formatter, "There was an error in the layout file, {}", err)
             }
+            ConfigError::LayoutTemplate(ref err) => {
+                write!(formatter, "There was an error in the layout file, {}", err)
+            }
         }
     }
 }
@@ -231,9 +297,11 @@ impl std::error::Error for ConfigError {
             ConfigError::Io(ref err) => Some(err),
             ConfigError::IoPath(ref err, _) => Some(err),
             ConfigError::Serde(ref err) => Some(err),
+            ConfigError::SerdePath(ref err, _) => Some(err),
             ConfigError::FromUtf8(ref err) => Some(err),
             ConfigError::Layout(ref err) => Some(err),
             ConfigError::LayoutPartAndTab(ref err) => Some(err),
+            ConfigError::LayoutTemplate(ref err) => Some(err),
         }
     }
 }
@@ -316,6 +384,19 @@ mod config_test {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn invalid_config_names_its_file() {
+        let tmp = tempdir().unwrap();
+        let path = tmp.path().join(DEFAULT_CONFIG_FILE_NAME);
+        File::create(&path)
+            .unwrap()
+            .write_all(b"keybinds: invalid\n")
+            .unwrap();
+        let error = Config::new(&path).unwrap_err();
+        assert!(matches!(error, ConfigError::SerdePath(_, ref p) if *p == path));
+        assert!(error.to_string().contains(&path.display().to_string()));
+    }
+
     #[test]
     fn try_from_cli_args_with_config_dir_without_config() {
         let mut opts = CliArgs::default();
@@ -331,4 +412,19 @@ mod config_test {
         let result = Config::try_from(&opts);
         assert_eq!(result.unwrap(), Config::default());
     }
+
+    #[test]
+    fn from_yaml_with_macros() {
+        use crate::input::actions::{Action, Direction};
+        let config = Config::from_yaml("macros:\n  split: [NewPane: Right, NewPane: Down,]\n");
+        let mut expected = Macros::new();
+        expected.insert(
+            "split".into(),
+            vec![
+                Action::NewPane(Some(Direction::Right)),
+                Action::NewPane(Some(Direction::Down)),
+            ],
+        );
+        assert_eq!(config.unwrap().macros, expected);
+    }
 }
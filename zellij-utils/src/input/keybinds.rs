@@ -3,8 +3,12 @@ use std::collections::HashMap;
 
 use super::actions::Action;
 use super::config;
+use super::keyboard_layout::KeyAliases;
+use super::macros::Macros;
+use super::mouse::{MouseButton, MouseEvent};
+use super::options::Options;
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use strum::IntoEnumIterator;
 use zellij_tile::data::*;
 
@@ -60,11 +64,71 @@ enum Unbind {
     // This is the correct order, don't rearrange!
     // Suspected Bug in the untagged macro.
     // 1. Keys
-    Keys(Vec<Key>),
+    Keys(#[serde(deserialize_with = "deserialize_unbind_keys")] Vec<Key>),
     // 2. All
     All(bool),
 }
 
+/// Keys to unbind can also be written as strings, eg. `"Ctrl-g"` or `"F1"`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyOrShorthand {
+    Key(Key),
+    Shorthand(String),
+}
+
+fn deserialize_unbind_keys<'de, D>(deserializer: D) -> Result<Vec<Key>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<KeyOrShorthand>::deserialize(deserializer)?
+        .into_iter()
+        .map(|key| match key {
+            KeyOrShorthand::Key(key) => Ok(key),
+            KeyOrShorthand::Shorthand(shorthand) => key_from_shorthand(&shorthand)
+                .ok_or_else(|| de::Error::custom(format!("unknown key: {:?}", shorthand))),
+        })
+        .collect()
+}
+
+/// Parses keys written like `Ctrl-g`, `Alt-n`, `F1`, `PageUp` or `x`
+fn key_from_shorthand(shorthand: &str) -> Option<Key> {
+    let single_char = |s: &str| {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    };
+    if let Some(c) = shorthand.strip_prefix("Ctrl-") {
+        return single_char(c).map(Key::Ctrl);
+    }
+    if let Some(c) = shorthand.strip_prefix("Alt-") {
+        return single_char(c).map(Key::Alt);
+    }
+    if let Some(n) = shorthand.strip_prefix('F') {
+        if let Ok(n) = n.parse() {
+            return Some(Key::F(n));
+        }
+    }
+    match shorthand {
+        "Backspace" => Some(Key::Backspace),
+        "Left" => Some(Key::Left),
+        "Right" => Some(Key::Right),
+        "Up" => Some(Key::Up),
+        "Down" => Some(Key::Down),
+        "Home" => Some(Key::Home),
+        "End" => Some(Key::End),
+        "PageUp" => Some(Key::PageUp),
+        "PageDown" => Some(Key::PageDown),
+        "BackTab" => Some(Key::BackTab),
+        "Delete" => Some(Key::Delete),
+        "Insert" => Some(Key::Insert),
+        "Esc" => Some(Key::Esc),
+        _ => single_char(shorthand).map(Key::Char),
+    }
+}
+
 impl Default for Keybinds {
     // Use once per codepath
     // TODO investigate why
@@ -112,7 +176,11 @@ impl Keybinds {
                 for keybind in keybinds.iter() {
                     match keybind {
                         KeyActionUnbind::Unbind(unbind) => {
-                            unbind_config.insert(mode, unbind.unbind.clone());
+                            let merged = match unbind_config.remove(&mode) {
+                                Some(previous) => previous.merge(unbind.unbind.clone()),
+                                None => unbind.unbind.clone(),
+                            };
+                            unbind_config.insert(mode, merged);
                         }
                         KeyActionUnbind::KeyAction(key_action_from_yaml) => {
                             keybind_config
@@ -187,28 +255,93 @@ impl Keybinds {
     }
 
     /// Converts a [`Key`] terminal event to a sequence of [`Action`]s according to the current
-    /// [`InputMode`] and [`Keybinds`].
+    /// [`InputMode`] and [`Keybinds`], expanding any [`Macros`] the keybind refers to.
+    ///
+    /// A key without a keybind of its own falls back to the keybind of its alias in the
+    /// keyboard layout, if any.
     pub fn key_to_actions(
         key: &Key,
         input: Vec<u8>,
         mode: &InputMode,
         keybinds: &Keybinds,
+        macros: &Macros,
+        aliases: &KeyAliases,
     ) -> Vec<Action> {
+        // a mode has no table at all if every one of its keybinds was unbound
         let mode_keybind_or_action = |action: Action| {
             keybinds
                 .0
                 .get(mode)
-                .unwrap_or_else(|| unreachable!("Unrecognized mode: {:?}", mode))
-                .0
-                .get(key)
+                .and_then(|mode_keybinds| {
+                    mode_keybinds.0.get(key).or_else(|| {
+                        aliases
+                            .alias(key)
+                            .and_then(|alias| mode_keybinds.0.get(&alias))
+                    })
+                })
                 .cloned()
                 .unwrap_or_else(|| vec![action])
         };
-        match *mode {
+        let actions = match *mode {
             InputMode::Normal | InputMode::Locked => mode_keybind_or_action(Action::Write(input)),
             InputMode::RenameTab => mode_keybind_or_action(Action::TabNameInput(input)),
+            InputMode::RenamePane => mode_keybind_or_action(Action::PaneNameInput(input)),
+            InputMode::RenameTabGroup => mode_keybind_or_action(Action::TabGroupNameInput(input)),
+            InputMode::RenameSession => mode_keybind_or_action(Action::SessionNameInput(input)),
+            InputMode::Search => mode_keybind_or_action(Action::SearchInput(input)),
+            InputMode::TabSearch => mode_keybind_or_action(Action::TabSearchInput(input)),
+            InputMode::CommandHistory => mode_keybind_or_action(Action::CommandHistoryInput(input)),
+            InputMode::SessionManager => mode_keybind_or_action(Action::SessionManagerInput(input)),
             _ => mode_keybind_or_action(Action::NoOp),
-        }
+        };
+        macros.expand(actions)
+    }
+
+    /// Converts a [`MouseEvent`] to a sequence of [`Action`]s according to the current
+    /// [`InputMode`].
+    ///
+    /// The wheel scrolls in every mode. While a pane or tab is being renamed the other events
+    /// are dropped, since a click would move the focus and the name being typed with it; in
+    /// `Scroll` mode the focus does not follow the mouse away from the pane being scrolled.
+    pub fn mouse_to_actions(
+        mouse_event: &MouseEvent,
+        mode: &InputMode,
+        options: &Options,
+    ) -> Vec<Action> {
+        let renaming = matches!(
+            mode,
+            InputMode::RenameTab
+                | InputMode::RenamePane
+                | InputMode::RenameTabGroup
+                | InputMode::RenameSession
+        );
+        let action = match *mouse_event {
+            MouseEvent::Press(MouseButton::WheelUp, point, _) if options.reverse_scroll => {
+                Action::ScrollDownAt(point)
+            }
+            MouseEvent::Press(MouseButton::WheelDown, point, _) if options.reverse_scroll => {
+                Action::ScrollUpAt(point)
+            }
+            MouseEvent::Press(MouseButton::WheelUp, point, _) => Action::ScrollUpAt(point),
+            MouseEvent::Press(MouseButton::WheelDown, point, _) => Action::ScrollDownAt(point),
+            _ if renaming => return vec![],
+            MouseEvent::Press(MouseButton::Left, point, modifiers) => {
+                Action::LeftClick(point, modifiers)
+            }
+            MouseEvent::Press(MouseButton::Middle, point, modifiers) => {
+                Action::MiddleClick(point, modifiers)
+            }
+            MouseEvent::Press(..) => return vec![],
+            MouseEvent::Release(point) => Action::MouseRelease(point),
+            MouseEvent::Hold(point) => Action::MouseHold(point),
+            MouseEvent::Motion(point)
+                if options.focus_follows_mouse && *mode != InputMode::Scroll =>
+            {
+                Action::MouseMotion(point)
+            }
+            MouseEvent::Motion(_) => return vec![],
+        };
+        vec![action]
     }
 }
 
@@ -289,6 +422,20 @@ impl From<Vec<KeyActionFromYaml>> for ModeKeybinds {
     }
 }
 
+impl Unbind {
+    /// Combines several unbinds of the same mode, so that none of them is lost
+    fn merge(self, other: Unbind) -> Unbind {
+        match (self, other) {
+            (Unbind::All(true), _) | (_, Unbind::All(true)) => Unbind::All(true),
+            (Unbind::Keys(mut keys), Unbind::Keys(other_keys)) => {
+                keys.extend(other_keys);
+                Unbind::Keys(keys)
+            }
+            (unbind, Unbind::All(false)) | (Unbind::All(false), unbind) => unbind,
+        }
+    }
+}
+
 impl Default for Unbind {
     fn default() -> Unbind {
         Unbind::All(false)
@@ -0,0 +1,87 @@
+//! Aliases letting keybinds written for a US keyboard work on other keyboard layouts.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use zellij_tile::data::Key;
+
+/// Maps the characters a keyboard layout types to the ones a US layout types from the same
+/// physical keys, eg. `ü` to `[` on a QWERTZ keyboard.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct KeyAliases(HashMap<char, char>);
+
+/// Keyboard layouts as defined in the `keyboard_layouts` section of the config file, on top of
+/// the built-in `azerty` and `qwertz` ones.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct KeyboardLayouts(HashMap<String, KeyAliases>);
+
+impl KeyAliases {
+    /// The key at the same physical position on a US layout, if `key` is a character or
+    /// `Alt` with a character that has an alias.
+    ///
+    /// `Ctrl` keys are left alone, the terminal already sends most of them as control codes
+    /// regardless of the layout.
+    pub fn alias(&self, key: &Key) -> Option<Key> {
+        match *key {
+            Key::Char(c) => self.0.get(&c).copied().map(Key::Char),
+            Key::Alt(c) => self.0.get(&c).copied().map(Key::Alt),
+            _ => None,
+        }
+    }
+}
+
+impl From<&[(char, char)]> for KeyAliases {
+    fn from(aliases: &[(char, char)]) -> Self {
+        KeyAliases(aliases.iter().copied().collect())
+    }
+}
+
+// Only keys that don't type a letter are aliased, so that a letter never falls back to the
+// keybind of the letter that happens to share its position (eg. `Ctrl-a` to `Ctrl-q` on AZERTY).
+const AZERTY: &[(char, char)] = &[
+    ('^', '['),
+    ('$', ']'),
+    ('¨', '{'),
+    ('£', '}'),
+    ('ù', '\''),
+    (')', '-'),
+    ('²', '`'),
+];
+
+const QWERTZ: &[(char, char)] = &[
+    ('ü', '['),
+    ('+', ']'),
+    ('Ü', '{'),
+    ('*', '}'),
+    ('ö', ';'),
+    ('ä', '\''),
+    ('ß', '-'),
+    ('´', '='),
+    ('^', '`'),
+];
+
+impl Default for KeyboardLayouts {
+    fn default() -> Self {
+        let mut layouts = HashMap::new();
+        layouts.insert("azerty".into(), KeyAliases::from(AZERTY));
+        layouts.insert("qwertz".into(), KeyAliases::from(QWERTZ));
+        KeyboardLayouts(layouts)
+    }
+}
+
+impl KeyboardLayouts {
+    /// Adds the layouts of `other`, replacing the ones of the same name.
+    pub fn merge(self, other: KeyboardLayouts) -> KeyboardLayouts {
+        let mut layouts = self.0;
+        layouts.extend(other.0);
+        KeyboardLayouts(layouts)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&KeyAliases> {
+        self.0.get(name)
+    }
+}
+
+// The unit test location.
+#[cfg(test)]
+#[path = "./unit/keyboard_layout_test.rs"]
+mod keyboard_layout_test;
@@ -9,18 +9,25 @@
 //  If plugins should be able to depend on the layout system
 //  then [`zellij-utils`] could be a proper place.
 use crate::{
-    input::{command::RunCommand, config::ConfigError},
+    input::{
+        command::RunCommand,
+        config::{ConfigError, LayoutTemplateError},
+        options::{AmbiguousWidth, PaneFrameStyle},
+        theme::PaletteOverride,
+    },
     pane_size::{Dimension, PaneGeom},
     setup,
 };
 use crate::{serde, serde_yaml};
 
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
 use std::vec::Vec;
 use std::{
     cmp::max,
-    ops::Not,
+    collections::BTreeMap,
+    ops::{Not, Range},
     path::{Path, PathBuf},
+    str::FromStr,
 };
 use std::{fs::File, io::prelude::*};
 
@@ -31,6 +38,12 @@ pub enum Direction {
     Vertical,
 }
 
+impl Default for Direction {
+    fn default() -> Self {
+        Direction::Horizontal
+    }
+}
+
 impl Not for Direction {
     type Output = Self;
 
@@ -49,6 +62,48 @@ pub enum SplitSize {
     Fixed(usize), // An absolute number of columns or rows
 }
 
+impl FromStr for SplitSize {
+    type Err = Box<dyn std::error::Error>;
+
+    // `40` is forty columns or rows, `30%` thirty percent of the space of the parent
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.strip_suffix('%') {
+            Some(percent) => Ok(Self::Percent(percent.trim().parse()?)),
+            None => Ok(Self::Fixed(s.parse()?)),
+        }
+    }
+}
+
+// sizes are written as `size: 30%` or `size: 40` in layouts, next to the older
+// `split_size: {Percent: 30}`, while the binary encoding between the client and the server
+// keeps the enum
+fn deserialize_split_size<'de, D>(deserializer: D) -> Result<Option<SplitSize>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(crate = "self::serde")]
+    #[serde(untagged)]
+    enum SizeInLayout {
+        SplitSize(SplitSize),
+        Cells(usize),
+        Text(String),
+    }
+    if !deserializer.is_human_readable() {
+        return Deserialize::deserialize(deserializer);
+    }
+    Ok(match Option::<SizeInLayout>::deserialize(deserializer)? {
+        None => None,
+        Some(SizeInLayout::SplitSize(size)) => Some(size),
+        Some(SizeInLayout::Cells(cells)) => Some(SplitSize::Fixed(cells)),
+        Some(SizeInLayout::Text(text)) => Some(
+            text.parse()
+                .map_err(|e| D::Error::custom(format!("invalid size {:?}: {}", text, e)))?,
+        ),
+    })
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(crate = "self::serde")]
 pub enum Run {
@@ -73,10 +128,31 @@ pub struct Layout {
     pub direction: Direction,
     #[serde(default)]
     pub parts: Vec<Layout>,
+    #[serde(default, alias = "size", deserialize_with = "deserialize_split_size")]
     pub split_size: Option<SplitSize>,
     pub run: Option<Run>,
     #[serde(default)]
     pub borderless: bool,
+    // the panes of this part share one slot, with only one of them shown at a time
+    #[serde(default)]
+    pub stacked: bool,
+    // overrides the frame style of the pane
+    #[serde(default)]
+    pub pane_frames: Option<PaneFrameStyle>,
+    // overrides colors of the palette the pane is drawn with
+    #[serde(default)]
+    pub palette: Option<PaletteOverride>,
+    // overrides the `ambiguous_width` option in the pane
+    #[serde(default)]
+    pub ambiguous_width: Option<AmbiguousWidth>,
+    // the bounds the size of the part is kept in when it is flexible, in rows or columns
+    #[serde(default)]
+    pub min_size: Option<usize>,
+    #[serde(default)]
+    pub max_size: Option<usize>,
+    // freeform labels given to the pane, see `zellij action tag-pane`
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 // The struct that is used to deserialize the layout from
@@ -91,31 +167,89 @@ pub struct LayoutFromYaml {
     pub borderless: bool,
     #[serde(default)]
     pub tabs: Vec<TabLayout>,
+    // layout files, relative to this one, whose tabs open before the tabs of this layout and
+    // whose template is used if this layout has none
+    #[serde(default)]
+    pub include: Vec<PathBuf>,
 }
 
 type LayoutFromYamlResult = Result<LayoutFromYaml, ConfigError>;
 
 impl LayoutFromYaml {
     pub fn new(layout_path: &Path) -> LayoutFromYamlResult {
-        let mut layout_file = File::open(&layout_path)
-            .or_else(|_| File::open(&layout_path.with_extension("yaml")))
+        Self::with_variables(layout_path, &[])
+    }
+
+    /// Reads the layout at `layout_path`, along with the layouts it includes, filling in the
+    /// `{{name}}` variables of their files from `variables`, or else from the environment.
+    pub fn with_variables(
+        layout_path: &Path,
+        variables: &[(String, String)],
+    ) -> LayoutFromYamlResult {
+        Self::read(layout_path, variables, &mut vec![])
+    }
+
+    // `including` holds the layouts that include this one, to catch layouts including themselves
+    fn read(
+        layout_path: &Path,
+        variables: &[(String, String)],
+        including: &mut Vec<PathBuf>,
+    ) -> LayoutFromYamlResult {
+        let with_extension = layout_path.with_extension("yaml");
+        let (mut layout_file, layout_path) = File::open(&layout_path)
+            .map(|file| (file, layout_path))
+            .or_else(|_| File::open(&with_extension).map(|file| (file, with_extension.as_path())))
             .map_err(|e| ConfigError::IoPath(e, layout_path.into()))?;
 
         let mut layout = String::new();
         layout_file.read_to_string(&mut layout)?;
-        let layout: Option<LayoutFromYaml> = serde_yaml::from_str(&layout)?;
+        let layout = substitute_variables(&layout, variables)?;
+        let layout: Option<LayoutFromYaml> = serde_yaml::from_str(&layout)
+            .map_err(|e| ConfigError::SerdePath(e, layout_path.into()))?;
+        let mut layout = layout.unwrap_or_default();
+        if layout.include.is_empty() {
+            return Ok(layout);
+        }
+
+        let canonical_path = layout_path
+            .canonicalize()
+            .unwrap_or_else(|_| layout_path.to_path_buf());
+        if including.contains(&canonical_path) {
+            return Err(ConfigError::LayoutTemplate(
+                LayoutTemplateError::IncludeCycle(layout_path.into()),
+            ));
+        }
+        including.push(canonical_path);
+        let layout_dir = layout_path.parent().unwrap_or_else(|| Path::new(""));
+        let mut tabs = vec![];
+        let mut template = None;
+        for include in layout.include.drain(..) {
+            let included = Self::read(&layout_dir.join(&include), variables, including)
+                .or_else(|e| Self::from_default_assets(&include).map_err(|_| e))?;
+            if template.is_none() && included.template != LayoutTemplate::default() {
+                template = Some(included.template);
+            }
+            tabs.extend(included.tabs);
+        }
+        including.pop();
 
-        match layout {
-            Some(layout) => Ok(layout),
-            None => Ok(LayoutFromYaml::default()),
+        if layout.template == LayoutTemplate::default() {
+            layout.template = template.unwrap_or_default();
         }
+        tabs.append(&mut layout.tabs);
+        layout.tabs = tabs;
+        Ok(layout)
     }
 
     // It wants to use Path here, but that doesn't compile.
     #[allow(clippy::ptr_arg)]
-    pub fn from_dir(layout: &PathBuf, layout_dir: Option<&PathBuf>) -> LayoutFromYamlResult {
+    pub fn from_dir(
+        layout: &PathBuf,
+        layout_dir: Option<&PathBuf>,
+        variables: &[(String, String)],
+    ) -> LayoutFromYamlResult {
         match layout_dir {
-            Some(dir) => Self::new(&dir.join(layout))
+            Some(dir) => Self::with_variables(&dir.join(layout), variables)
                 .or_else(|_| Self::from_default_assets(layout.as_path())),
             None => Self::from_default_assets(layout.as_path()),
         }
@@ -125,14 +259,16 @@ impl LayoutFromYaml {
         layout: Option<&PathBuf>,
         layout_path: Option<&PathBuf>,
         layout_dir: Option<PathBuf>,
+        variables: &[(String, String)],
     ) -> Option<LayoutFromYamlResult> {
         layout
-            .map(|p| LayoutFromYaml::from_dir(p, layout_dir.as_ref()))
-            .or_else(|| layout_path.map(|p| LayoutFromYaml::new(p)))
+            .map(|p| LayoutFromYaml::from_dir(p, layout_dir.as_ref(), variables))
+            .or_else(|| layout_path.map(|p| LayoutFromYaml::with_variables(p, variables)))
             .or_else(|| {
                 Some(LayoutFromYaml::from_dir(
                     &std::path::PathBuf::from("default"),
                     layout_dir.as_ref(),
+                    variables,
                 ))
             })
     }
@@ -184,8 +320,28 @@ pub struct LayoutTemplate {
     pub parts: Vec<LayoutTemplate>,
     #[serde(default)]
     pub body: bool,
+    #[serde(default, alias = "size", deserialize_with = "deserialize_split_size")]
     pub split_size: Option<SplitSize>,
     pub run: Option<Run>,
+    #[serde(default)]
+    pub stacked: bool,
+    // overrides the frame style of the pane
+    #[serde(default)]
+    pub pane_frames: Option<PaneFrameStyle>,
+    // overrides colors of the palette the pane is drawn with
+    #[serde(default)]
+    pub palette: Option<PaletteOverride>,
+    // overrides the `ambiguous_width` option in the pane
+    #[serde(default)]
+    pub ambiguous_width: Option<AmbiguousWidth>,
+    // the bounds the size of the part is kept in when it is flexible, in rows or columns
+    #[serde(default)]
+    pub min_size: Option<usize>,
+    #[serde(default)]
+    pub max_size: Option<usize>,
+    // freeform labels given to the pane, see `zellij action tag-pane`
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl LayoutTemplate {
@@ -220,15 +376,181 @@ impl LayoutTemplate {
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(crate = "self::serde")]
 pub struct TabLayout {
+    #[serde(default)]
     pub direction: Direction,
     #[serde(default)]
     pub borderless: bool,
     #[serde(default)]
     pub parts: Vec<TabLayout>,
+    #[serde(default, alias = "size", deserialize_with = "deserialize_split_size")]
     pub split_size: Option<SplitSize>,
     pub run: Option<Run>,
     #[serde(default)]
     pub name: String,
+    #[serde(default)]
+    pub stacked: bool,
+    // overrides the frame style of the pane
+    #[serde(default)]
+    pub pane_frames: Option<PaneFrameStyle>,
+    // overrides colors of the palette the pane is drawn with
+    #[serde(default)]
+    pub palette: Option<PaletteOverride>,
+    // overrides the `ambiguous_width` option in the pane
+    #[serde(default)]
+    pub ambiguous_width: Option<AmbiguousWidth>,
+    // the bounds the size of the part is kept in when it is flexible, in rows or columns
+    #[serde(default)]
+    pub min_size: Option<usize>,
+    #[serde(default)]
+    pub max_size: Option<usize>,
+    // freeform labels given to the pane, see `zellij action tag-pane`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // a layout file, whose first tab replaces the parts of this one
+    #[serde(default)]
+    pub layout: Option<PathBuf>,
+    // the working directory of the shells of this tab, and of its commands that don't set one
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    // set in the environment of the shells of this tab and of its commands, on top of what they
+    // set themselves
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    // whether the session starts in this tab, when the layout opens several
+    #[serde(default)]
+    pub focus: bool,
+}
+
+impl TabLayout {
+    /// Takes the parts of this tab from the first tab of its `layout` file, if it has one,
+    /// keeping the name and working directory of this tab when they are set, its environment on
+    /// top of the one of the file, and its focus.
+    ///
+    /// The file is looked for at its path first, and then by name in the `layout_dir` and among
+    /// the layouts zellij comes with, and its `{{name}}` variables are filled in from `variables`
+    /// or else from the environment.
+    pub fn load_layout_file(
+        self,
+        layout_dir: Option<&PathBuf>,
+        variables: &[(String, String)],
+    ) -> Result<Self, ConfigError> {
+        let layout_path = match &self.layout {
+            Some(layout_path) => layout_path,
+            None => return Ok(self),
+        };
+        let layout = if layout_path.is_file() || layout_path.with_extension("yaml").is_file() {
+            LayoutFromYaml::with_variables(layout_path, variables)?
+        } else {
+            LayoutFromYaml::from_dir(layout_path, layout_dir, variables)?
+        };
+        let mut tab = layout.tabs.into_iter().next().unwrap_or_default();
+        if !self.name.is_empty() {
+            tab.name = self.name;
+        }
+        tab.cwd = self.cwd.or(tab.cwd);
+        tab.env.extend(self.env);
+        tab.focus = self.focus;
+        Ok(tab)
+    }
+
+    /// Runs the commands of this tab that don't set a working directory in the one of the tab,
+    /// with the environment of the tab under their own.
+    pub fn pass_cwd_and_env_to_commands(&mut self) {
+        let cwd = self.cwd.clone();
+        let env = self.env.clone();
+        self.set_default_command_cwd_and_env(cwd.as_deref(), &env);
+    }
+
+    fn set_default_command_cwd_and_env(
+        &mut self,
+        cwd: Option<&Path>,
+        env: &BTreeMap<String, String>,
+    ) {
+        if let Some(Run::Command(command)) = &mut self.run {
+            if let Some(cwd) = cwd {
+                command.cwd.get_or_insert_with(|| cwd.to_path_buf());
+            }
+            for (name, value) in env {
+                command
+                    .env
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+        for part in self.parts.iter_mut() {
+            part.set_default_command_cwd_and_env(cwd, env);
+        }
+    }
+
+    // a part splitting its space evenly between `parts`
+    fn even_split(direction: Direction, parts: Vec<TabLayout>) -> Self {
+        TabLayout {
+            direction,
+            parts,
+            ..Default::default()
+        }
+    }
+}
+
+/// The predefined arrangements the panes of a tab are cycled through, after those of tmux.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapLayout {
+    /// The panes side by side, with the same width
+    EvenHorizontal,
+    /// The panes one above the other, with the same height
+    EvenVertical,
+    /// The first pane on the left half, the others one above the other on the right half
+    MainVertical,
+    /// The panes in a grid of rows as even as possible
+    Tiled,
+}
+
+impl SwapLayout {
+    /// The arrangement that comes after this one, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            SwapLayout::EvenHorizontal => SwapLayout::EvenVertical,
+            SwapLayout::EvenVertical => SwapLayout::MainVertical,
+            SwapLayout::MainVertical => SwapLayout::Tiled,
+            SwapLayout::Tiled => SwapLayout::EvenHorizontal,
+        }
+    }
+
+    /// A tab layout with `pane_count` terminal places arranged this way.
+    pub fn tab_layout(self, pane_count: usize) -> TabLayout {
+        let panes = |count: usize| vec![TabLayout::default(); count];
+        let pane_count = pane_count.max(1);
+        match self {
+            SwapLayout::EvenHorizontal => {
+                TabLayout::even_split(Direction::Vertical, panes(pane_count))
+            }
+            SwapLayout::EvenVertical => {
+                TabLayout::even_split(Direction::Horizontal, panes(pane_count))
+            }
+            SwapLayout::MainVertical if pane_count == 1 => TabLayout::default(),
+            SwapLayout::MainVertical => TabLayout::even_split(
+                Direction::Vertical,
+                vec![
+                    TabLayout::default(),
+                    TabLayout::even_split(Direction::Horizontal, panes(pane_count - 1)),
+                ],
+            ),
+            SwapLayout::Tiled => {
+                let columns = (1..=pane_count)
+                    .find(|columns| columns * columns >= pane_count)
+                    .unwrap();
+                let rows = (pane_count + columns - 1) / columns;
+                // the rows fill up from the top, the last one takes what is left
+                let rows = (0..rows)
+                    .map(|row| {
+                        let count = columns.min(pane_count - row * columns);
+                        TabLayout::even_split(Direction::Vertical, panes(count))
+                    })
+                    .collect();
+                TabLayout::even_split(Direction::Horizontal, rows)
+            }
+        }
+    }
 }
 
 impl Layout {
@@ -270,6 +592,32 @@ impl Layout {
         split_space(space, self)
     }
 
+    /// The panes of each stacked part, as ranges of indices into
+    /// [`Self::position_panes_in_space`].
+    pub fn stacked_pane_ranges(&self) -> Vec<Range<usize>> {
+        fn pane_count(layout: &Layout) -> usize {
+            if layout.parts.is_empty() {
+                1
+            } else {
+                layout.parts.iter().map(pane_count).sum()
+            }
+        }
+        fn collect_ranges(layout: &Layout, first_pane: usize, ranges: &mut Vec<Range<usize>>) {
+            if layout.stacked && !layout.parts.is_empty() {
+                ranges.push(first_pane..first_pane + pane_count(layout));
+                return;
+            }
+            let mut next_pane = first_pane;
+            for part in layout.parts.iter() {
+                collect_ranges(part, next_pane, ranges);
+                next_pane += pane_count(part);
+            }
+        }
+        let mut ranges = vec![];
+        collect_ranges(self, 0, &mut ranges);
+        ranges
+    }
+
     pub fn merge_tab_layout(&mut self, tab: TabLayout) {
         self.parts.push(tab.into());
     }
@@ -328,7 +676,7 @@ fn split_space(space_to_split: &PaneGeom, layout: &Layout) -> Vec<(Layout, PaneG
     let flex_parts = sizes.iter().filter(|s| s.is_none()).count();
 
     for (&size, part) in sizes.iter().zip(&layout.parts) {
-        let split_dimension = match size {
+        let mut split_dimension = match size {
             Some(SplitSize::Percent(percent)) => Dimension::percent(percent),
             Some(SplitSize::Fixed(size)) => Dimension::fixed(size),
             None => {
@@ -349,6 +697,8 @@ fn split_space(space_to_split: &PaneGeom, layout: &Layout) -> Vec<(Layout, PaneG
                 Dimension::percent(free_percent / flex_parts as f64)
             }
         };
+        // the bounds across the split are passed on through the inherited dimension of the parts
+        split_dimension.set_bounds(part.min_size, part.max_size);
         inherited_dimension.set_inner(
             layout
                 .parts
@@ -395,6 +745,13 @@ impl From<TabLayout> for Layout {
             parts: Self::from_vec_tab_layout(tab.parts),
             split_size: tab.split_size,
             run: tab.run,
+            stacked: tab.stacked,
+            pane_frames: tab.pane_frames,
+            palette: tab.palette,
+            ambiguous_width: tab.ambiguous_width,
+            min_size: tab.min_size,
+            max_size: tab.max_size,
+            tags: tab.tags,
         }
     }
 }
@@ -408,6 +765,13 @@ impl From<TabLayout> for LayoutTemplate {
             body: false,
             split_size: tab.split_size,
             run: tab.run,
+            stacked: tab.stacked,
+            pane_frames: tab.pane_frames,
+            palette: tab.palette,
+            ambiguous_width: tab.ambiguous_width,
+            min_size: tab.min_size,
+            max_size: tab.max_size,
+            tags: tab.tags,
         }
     }
 }
@@ -420,6 +784,13 @@ impl From<LayoutTemplate> for Layout {
             parts: Self::from_vec_template_layout(template.parts),
             split_size: template.split_size,
             run: template.run,
+            stacked: template.stacked,
+            pane_frames: template.pane_frames,
+            palette: template.palette,
+            ambiguous_width: template.ambiguous_width,
+            min_size: template.min_size,
+            max_size: template.max_size,
+            tags: template.tags,
         }
     }
 }
@@ -433,6 +804,17 @@ impl Default for TabLayout {
             split_size: None,
             run: None,
             name: String::new(),
+            stacked: false,
+            pane_frames: None,
+            palette: None,
+            ambiguous_width: None,
+            min_size: None,
+            max_size: None,
+            tags: vec![],
+            layout: None,
+            cwd: None,
+            env: BTreeMap::new(),
+            focus: false,
         }
     }
 }
@@ -450,9 +832,23 @@ impl Default for LayoutTemplate {
                 split_size: None,
                 run: None,
                 parts: vec![],
+                stacked: false,
+                pane_frames: None,
+                palette: None,
+                ambiguous_width: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
             }],
             split_size: None,
             run: None,
+            stacked: false,
+            pane_frames: None,
+            palette: None,
+            ambiguous_width: None,
+            min_size: None,
+            max_size: None,
+            tags: vec![],
         }
     }
 }
@@ -463,8 +859,91 @@ impl Default for LayoutFromYaml {
             template: LayoutTemplate::default(),
             borderless: false,
             tabs: vec![],
+            include: vec![],
+        }
+    }
+}
+
+// fills in the `{{name}}` variables of a layout file, from `variables` or else from the
+// environment - the last value given for a name in `variables` is the one used. Values are
+// escaped for the quoted scalar the variable is in, or quoted when it isn't in one, so they
+// can't add to the structure of the layout.
+fn substitute_variables(
+    layout: &str,
+    variables: &[(String, String)],
+) -> Result<String, ConfigError> {
+    let mut substituted = String::with_capacity(layout.len());
+    let mut rest = layout;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+        let name = rest[start + 2..end].trim();
+        let value = match variables
+            .iter()
+            .rev()
+            .find(|(variable, _)| variable == name)
+        {
+            Some((_, value)) => value.clone(),
+            None => std::env::var(name).map_err(|_| {
+                ConfigError::LayoutTemplate(LayoutTemplateError::UndefinedVariable(name.into()))
+            })?,
+        };
+        substituted.push_str(&rest[..start]);
+        let line_start = substituted.rfind('\n').map_or(0, |newline| newline + 1);
+        match open_quote(&substituted[line_start..]) {
+            Some('\'') => substituted.push_str(&value.replace('\'', "''")),
+            Some(_) => substituted.push_str(&escape_double_quoted(&value)),
+            None => {
+                substituted.push('"');
+                substituted.push_str(&escape_double_quoted(&value));
+                substituted.push('"');
+            }
+        }
+        rest = &rest[end + 2..];
+    }
+    substituted.push_str(rest);
+    Ok(substituted)
+}
+
+// the quote of the scalar that is still open at the end of `line`, if any
+fn open_quote(line: &str) -> Option<char> {
+    let mut quote = None;
+    let mut previous = ' ';
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, '#') if previous.is_whitespace() => return None,
+            (None, '"') | (None, '\'') if " :-[{,".contains(previous) => quote = Some(c),
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            (Some('"'), '"') => quote = None,
+            (Some('\''), '\'') if chars.peek() == Some(&'\'') => {
+                chars.next();
+            }
+            (Some('\''), '\'') => quote = None,
+            _ => {}
+        }
+        previous = c;
+    }
+    quote
+}
+
+fn escape_double_quoted(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
         }
     }
+    escaped
 }
 
 // The unit test location.
@@ -0,0 +1,63 @@
+//! User-defined macros, named sequences of actions that can be bound to a single key.
+use std::collections::HashMap;
+
+use super::actions::Action;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+/// Macros as defined in the `macros` section of the config file.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Macros(HashMap<String, Vec<Action>>);
+
+impl Macros {
+    pub fn new() -> Macros {
+        Macros(HashMap::new())
+    }
+
+    pub fn insert(&mut self, name: String, actions: Vec<Action>) {
+        self.0.insert(name, actions);
+    }
+
+    /// Replaces every [`Action::Macro`] in `actions` with the actions it stands for,
+    /// expanding nested macros as well.
+    ///
+    /// Unknown macros expand to nothing, as does a macro that (directly or not) refers to
+    /// itself, so a cycle in the config can't hang the client.
+    pub fn expand(&self, actions: Vec<Action>) -> Vec<Action> {
+        let mut expanded = vec![];
+        self.expand_into(actions, &mut vec![], &mut expanded);
+        expanded
+    }
+
+    fn expand_into<'a>(
+        &'a self,
+        actions: Vec<Action>,
+        expanding: &mut Vec<&'a str>,
+        expanded: &mut Vec<Action>,
+    ) {
+        for action in actions {
+            match action {
+                Action::Macro(name) => {
+                    if let Some((name, macro_actions)) = self.0.get_key_value(&name) {
+                        if expanding.contains(&name.as_str()) {
+                            warn!("Macro '{}' refers to itself, skipping it", name);
+                            continue;
+                        }
+                        expanding.push(name);
+                        self.expand_into(macro_actions.clone(), expanding, expanded);
+                        expanding.pop();
+                    } else {
+                        warn!("Unknown macro '{}'", name);
+                    }
+                }
+                action => expanded.push(action),
+            }
+        }
+    }
+}
+
+// The unit test location.
+#[cfg(test)]
+#[path = "./unit/macros_test.rs"]
+mod macros_test;
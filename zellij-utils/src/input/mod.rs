@@ -1,51 +1,136 @@
 //! The way terminal input is handled.
 
 pub mod actions;
+pub mod bundle;
 pub mod command;
 pub mod config;
 pub mod keybinds;
+pub mod keyboard_layout;
 pub mod layout;
+pub mod macros;
 pub mod mouse;
 pub mod options;
 pub mod theme;
 
+use crate::messages::{Locale, Message};
+use std::error;
+use std::fmt::{self, Display};
+use std::io;
 use termion::input::TermRead;
 use zellij_tile::data::{InputMode, Key, ModeInfo, Palette, PluginCapabilities};
 
+/// Something in the terminal input we couldn't make sense of.
+#[derive(Debug)]
+pub enum InputError {
+    // A key termion knows about but we don't
+    UnknownKey(termion::event::Key),
+    // An escape sequence termion can't parse
+    MalformedSequence(Vec<u8>),
+    // Reading or parsing the input failed
+    Io(io::Error),
+}
+
+impl Display for InputError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::UnknownKey(ref key) => write!(formatter, "Unknown key: {:?}", key),
+            InputError::MalformedSequence(ref bytes) => write!(
+                formatter,
+                "Malformed escape sequence: {:?}",
+                String::from_utf8_lossy(bytes)
+            ),
+            InputError::Io(ref err) => write!(formatter, "Could not read input: {}", err),
+        }
+    }
+}
+
+impl error::Error for InputError {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            InputError::UnknownKey(_) | InputError::MalformedSequence(_) => None,
+            InputError::Io(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for InputError {
+    fn from(err: io::Error) -> InputError {
+        InputError::Io(err)
+    }
+}
+
 /// Creates a [`ModeInfo`] struct indicating the current [`InputMode`] and its keybinds
-/// (as pairs of [`String`]s).
+/// (as pairs of [`String`]s), with the descriptions of the keybinds in the given [`Locale`].
 pub fn get_mode_info(
     mode: InputMode,
     palette: Palette,
     capabilities: PluginCapabilities,
+    locale: Locale,
 ) -> ModeInfo {
+    let hint = |key: &str, message: Message| (key.to_string(), locale.text(message).to_string());
     let keybinds = match mode {
         InputMode::Normal | InputMode::Locked => Vec::new(),
-        InputMode::Resize => vec![("←↓↑→".to_string(), "Resize".to_string())],
+        InputMode::Resize => vec![hint("←↓↑→", Message::Resize)],
         InputMode::Pane => vec![
-            ("←↓↑→".to_string(), "Move focus".to_string()),
-            ("p".to_string(), "Next".to_string()),
-            ("n".to_string(), "New".to_string()),
-            ("d".to_string(), "Down split".to_string()),
-            ("r".to_string(), "Right split".to_string()),
-            ("x".to_string(), "Close".to_string()),
-            ("f".to_string(), "Fullscreen".to_string()),
-            ("z".to_string(), "Frames".to_string()),
+            hint("←↓↑→", Message::MoveFocus),
+            hint("p", Message::NextPane),
+            hint("n", Message::NewPane),
+            hint("d", Message::DownSplit),
+            hint("r", Message::RightSplit),
+            hint("x", Message::ClosePane),
+            hint("f", Message::Fullscreen),
+            hint("z", Message::PaneFrames),
         ],
         InputMode::Tab => vec![
-            ("←↓↑→".to_string(), "Move focus".to_string()),
-            ("n".to_string(), "New".to_string()),
-            ("x".to_string(), "Close".to_string()),
-            ("r".to_string(), "Rename".to_string()),
-            ("s".to_string(), "Sync".to_string()),
-            ("Tab".to_string(), "Toggle".to_string()),
+            hint("←↓↑→", Message::MoveFocus),
+            hint("n", Message::NewTab),
+            hint("x", Message::CloseTab),
+            hint("r", Message::RenameTab),
+            hint("s", Message::SyncTab),
+            hint("Tab", Message::ToggleTab),
         ],
         InputMode::Scroll => vec![
-            ("↓↑".to_string(), "Scroll".to_string()),
-            ("PgUp/PgDn".to_string(), "Scroll Page".to_string()),
+            hint("↓↑", Message::Scroll),
+            hint("PgUp/PgDn", Message::ScrollPage),
+        ],
+        InputMode::RenameTab
+        | InputMode::RenamePane
+        | InputMode::RenameTabGroup
+        | InputMode::RenameSession => {
+            vec![hint("Enter", Message::WhenDone)]
+        }
+        InputMode::Search => vec![
+            hint("↓↑", Message::SearchMatch),
+            hint("Alt+c", Message::SearchCase),
+            hint("Alt+w", Message::SearchWrap),
+            hint("Enter", Message::WhenDone),
+        ],
+        InputMode::TabSearch => vec![
+            hint("↓↑", Message::SearchMatch),
+            hint("Enter", Message::JumpToMatch),
+        ],
+        InputMode::CommandHistory => vec![
+            hint("↓↑", Message::SelectCommand),
+            hint("Enter", Message::RunCommand),
+            hint("Tab", Message::RunInItsPane),
+        ],
+        InputMode::Copy => vec![
+            hint("hjkl", Message::MoveFocus),
+            hint("v/V", Message::CopySelect),
+            hint("y", Message::CopyYank),
+        ],
+        InputMode::Session => vec![
+            hint("d", Message::Detach),
+            hint("o", Message::DetachOtherClients),
+            hint("r", Message::RenameSession),
+            hint("w", Message::SessionManager),
+        ],
+        InputMode::SessionManager => vec![
+            hint("↓↑", Message::SelectSession),
+            hint("Enter", Message::SwitchSession),
+            hint("Ctrl+r", Message::RenameSession),
+            hint("Ctrl+x", Message::KillSession),
         ],
-        InputMode::RenameTab => vec![("Enter".to_string(), "when done".to_string())],
-        InputMode::Session => vec![("d".to_string(), "Detach".to_string())],
     };
 
     let session_name = std::env::var("ZELLIJ_SESSION_NAME").ok();
@@ -60,13 +145,17 @@ pub fn get_mode_info(
 }
 
 pub fn parse_keys(input_bytes: &[u8]) -> Vec<Key> {
-    input_bytes.keys().flatten().map(cast_termion_key).collect()
+    input_bytes
+        .keys()
+        .flatten()
+        .filter_map(|key| cast_termion_key(key).ok())
+        .collect()
 }
 
 // FIXME: This is an absolutely cursed function that should be destroyed as soon
 // as an alternative that doesn't touch zellij-tile can be developed...
-pub fn cast_termion_key(event: termion::event::Key) -> Key {
-    match event {
+pub fn cast_termion_key(event: termion::event::Key) -> Result<Key, InputError> {
+    let key = match event {
         termion::event::Key::Backspace => Key::Backspace,
         termion::event::Key::Left => Key::Left,
         termion::event::Key::Right => Key::Right,
@@ -85,8 +174,7 @@ pub fn cast_termion_key(event: termion::event::Key) -> Key {
         termion::event::Key::Ctrl(c) => Key::Ctrl(c),
         termion::event::Key::Null => Key::Null,
         termion::event::Key::Esc => Key::Esc,
-        _ => {
-            unimplemented!("Encountered an unknown key!")
-        }
-    }
+        _ => return Err(InputError::UnknownKey(event)),
+    };
+    Ok(key)
 }
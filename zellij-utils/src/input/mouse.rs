@@ -1,14 +1,16 @@
 use serde::{Deserialize, Serialize};
+use std::str;
 
 use crate::position::Position;
+pub use zellij_tile::data::MouseModifiers;
 
 /// A mouse related event
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum MouseEvent {
-    /// A mouse button was pressed.
+    /// A mouse button was pressed, possibly while holding down modifier keys.
     ///
     /// The coordinates are zero-based.
-    Press(MouseButton, Position),
+    Press(MouseButton, Position, MouseModifiers),
     /// A mouse button was released.
     ///
     /// The coordinates are zero-based.
@@ -17,6 +19,10 @@ pub enum MouseEvent {
     ///
     /// The coordinates are zero-based.
     Hold(Position),
+    /// The mouse moved over the given coordinates without any button held.
+    ///
+    /// The coordinates are zero-based.
+    Motion(Position),
 }
 
 impl From<termion::event::MouseEvent> for MouseEvent {
@@ -25,6 +31,7 @@ impl From<termion::event::MouseEvent> for MouseEvent {
             termion::event::MouseEvent::Press(button, x, y) => Self::Press(
                 MouseButton::from(button),
                 Position::new((y.saturating_sub(1)) as i32, x.saturating_sub(1)),
+                MouseModifiers::default(),
             ),
             termion::event::MouseEvent::Release(x, y) => Self::Release(Position::new(
                 (y.saturating_sub(1)) as i32,
@@ -38,6 +45,101 @@ impl From<termion::event::MouseEvent> for MouseEvent {
     }
 }
 
+impl MouseEvent {
+    /// Termion does not report the modifiers of a mouse event, so they are read from
+    /// the raw sequence and attached here.
+    pub fn with_modifiers(self, modifiers: MouseModifiers) -> Self {
+        match self {
+            Self::Press(button, position, _) => Self::Press(button, position, modifiers),
+            event => event,
+        }
+    }
+
+    /// Termion does not parse the SGR button presses made while holding down a modifier key,
+    /// so they are read from the raw sequence here.
+    pub fn press_from_raw_sequence(raw_bytes: &[u8]) -> Option<Self> {
+        let sequence = match raw_bytes {
+            // SGR: \u{1b}[<Cb;Cx;CyM
+            [27, b'[', b'<', rest @ .., b'M'] => str::from_utf8(rest).ok()?,
+            _ => return None,
+        };
+        let mut numbers = sequence.split(';').map(|n| n.parse::<u16>().ok());
+        let (button_code, x, y) = (numbers.next()??, numbers.next()??, numbers.next()??);
+        if numbers.next().is_some() {
+            return None;
+        }
+        let modifier_bits = SHIFT | ALT | CTRL;
+        let button = match button_code & !modifier_bits {
+            0 => MouseButton::Left,
+            1 => MouseButton::Middle,
+            2 => MouseButton::Right,
+            64 => MouseButton::WheelUp,
+            65 => MouseButton::WheelDown,
+            _ => return None,
+        };
+        Some(Self::Press(
+            button,
+            Position::new((y.saturating_sub(1)) as i32, x.saturating_sub(1)),
+            modifiers_from_raw_sequence(raw_bytes),
+        ))
+    }
+
+    /// Termion does not parse the events reported in any-event tracking mode (1003) when no
+    /// button is held, so they are read from the raw SGR sequence here.
+    pub fn motion_from_raw_sequence(raw_bytes: &[u8]) -> Option<Self> {
+        const MOTION_WITHOUT_BUTTON: u16 = 35;
+        let sequence = match raw_bytes {
+            // SGR: \u{1b}[<Cb;Cx;CyM
+            [27, b'[', b'<', rest @ .., b'M'] => str::from_utf8(rest).ok()?,
+            _ => return None,
+        };
+        let mut numbers = sequence.split(';').map(|n| n.parse::<u16>().ok());
+        let (button_code, x, y) = (numbers.next()??, numbers.next()??, numbers.next()??);
+        let modifier_bits = SHIFT | ALT | CTRL;
+        if button_code & !modifier_bits != MOTION_WITHOUT_BUTTON || numbers.next().is_some() {
+            return None;
+        }
+        Some(Self::Motion(Position::new(
+            (y.saturating_sub(1)) as i32,
+            x.saturating_sub(1),
+        )))
+    }
+}
+
+const SHIFT: u16 = 4;
+const ALT: u16 = 8;
+const CTRL: u16 = 16;
+
+/// Reads the modifiers from the raw bytes of a mouse sequence, in any of the
+/// X10, SGR (1006) or urxvt (1015) encodings.
+pub fn modifiers_from_raw_sequence(raw_bytes: &[u8]) -> MouseModifiers {
+    let button_code = match raw_bytes {
+        // SGR: \u{1b}[<Cb;Cx;CyM
+        [27, b'[', b'<', rest @ ..] => parse_leading_number(rest),
+        // X10: \u{1b}[MCbCxCy, the button byte is offset by 32
+        [27, b'[', b'M', button, ..] => Some(button.saturating_sub(32) as u16),
+        // urxvt: \u{1b}[Cb;Cx;CyM, the button code is offset by 32
+        [27, b'[', rest @ ..] => parse_leading_number(rest).map(|b| b.saturating_sub(32)),
+        _ => None,
+    };
+    button_code
+        .map(|code| MouseModifiers {
+            shift: code & SHIFT != 0,
+            alt: code & ALT != 0,
+            ctrl: code & CTRL != 0,
+        })
+        .unwrap_or_default()
+}
+
+fn parse_leading_number(bytes: &[u8]) -> Option<u16> {
+    let digits: String = bytes
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .map(|&b| b as char)
+        .collect();
+    digits.parse().ok()
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum MouseButton {
     /// The left mouse button.
@@ -1,5 +1,6 @@
 //! Handles cli and configuration options
 use crate::cli::Command;
+use crate::messages::Locale;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -32,6 +33,220 @@ impl FromStr for OnForceClose {
     }
 }
 
+/// What to do when an application running in a pane sets the clipboard (OSC 52).
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum PaneClipboardPolicy {
+    #[serde(alias = "allow")]
+    Allow,
+    #[serde(alias = "prompt")]
+    Prompt,
+    #[serde(alias = "deny")]
+    Deny,
+}
+
+impl Default for PaneClipboardPolicy {
+    fn default() -> Self {
+        Self::Allow
+    }
+}
+
+impl FromStr for PaneClipboardPolicy {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Self::Allow),
+            "prompt" => Ok(Self::Prompt),
+            "deny" => Ok(Self::Deny),
+            e => Err(e.to_string().into()),
+        }
+    }
+}
+
+/// Where the text copied in Zellij goes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Clipboard {
+    #[serde(alias = "system")]
+    System,
+    #[serde(alias = "primary")]
+    Primary,
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::System
+    }
+}
+
+impl FromStr for Clipboard {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "system" => Ok(Self::System),
+            "primary" => Ok(Self::Primary),
+            e => Err(e.to_string().into()),
+        }
+    }
+}
+
+/// The characters pane frames are drawn with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PaneFrameStyle {
+    #[serde(alias = "normal")]
+    Normal,
+    #[serde(alias = "rounded")]
+    Rounded,
+    #[serde(alias = "thick")]
+    Thick,
+}
+
+impl Default for PaneFrameStyle {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl FromStr for PaneFrameStyle {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "rounded" => Ok(Self::Rounded),
+            "thick" => Ok(Self::Thick),
+            e => Err(e.to_string().into()),
+        }
+    }
+}
+
+/// How many columns the characters of ambiguous East Asian width (eg. `○`, `§` or some box
+/// drawing characters) take up, which depends on the font and locale of the terminal.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum AmbiguousWidth {
+    /// One column, as in most terminals and western locales
+    #[serde(alias = "narrow")]
+    Narrow,
+    /// Two columns, as in terminals set up for CJK locales
+    #[serde(alias = "wide")]
+    Wide,
+}
+
+impl Default for AmbiguousWidth {
+    fn default() -> Self {
+        Self::Narrow
+    }
+}
+
+impl FromStr for AmbiguousWidth {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "narrow" => Ok(Self::Narrow),
+            "wide" => Ok(Self::Wide),
+            e => Err(e.to_string().into()),
+        }
+    }
+}
+
+/// What is done with the panes of a tab when the terminal gets too small to show all of them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SmallPanesPolicy {
+    /// The panes stay where they are, cut off by the edges of the terminal
+    #[serde(alias = "keep")]
+    Keep,
+    /// The least recently focused panes join the stacks of their neighbours
+    #[serde(alias = "stack")]
+    Stack,
+    /// The least recently focused panes are hidden, and keep running in the background
+    #[serde(alias = "hide")]
+    Hide,
+}
+
+impl Default for SmallPanesPolicy {
+    fn default() -> Self {
+        Self::Keep
+    }
+}
+
+impl FromStr for SmallPanesPolicy {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(Self::Keep),
+            "stack" => Ok(Self::Stack),
+            "hide" => Ok(Self::Hide),
+            e => Err(e.to_string().into()),
+        }
+    }
+}
+
+/// How the session is sized when clients of different sizes are attached to it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ClientSizing {
+    /// The session takes the fewest rows and columns of the clients, larger clients show it in
+    /// their top left corner
+    #[serde(alias = "smallest")]
+    Smallest,
+    /// Like `Smallest`, but larger clients show it in their middle with blank margins around it
+    #[serde(alias = "letterbox")]
+    Letterbox,
+}
+
+impl Default for ClientSizing {
+    fn default() -> Self {
+        Self::Smallest
+    }
+}
+
+impl FromStr for ClientSizing {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "smallest" => Ok(Self::Smallest),
+            "letterbox" => Ok(Self::Letterbox),
+            e => Err(e.to_string().into()),
+        }
+    }
+}
+
+/// The signal the program running in a pane gets when the session quits, before it is killed
+/// if it still runs at the end of the grace period.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ShutdownSignal {
+    /// What a program gets when the terminal it runs in is closed, shells exit on it
+    #[serde(alias = "hup")]
+    Hup,
+    /// What `kill` sends by default, interactive shells ignore it
+    #[serde(alias = "term")]
+    Term,
+    /// What Ctrl-c sends
+    #[serde(alias = "int")]
+    Int,
+}
+
+impl Default for ShutdownSignal {
+    fn default() -> Self {
+        Self::Hup
+    }
+}
+
+impl FromStr for ShutdownSignal {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hup" => Ok(Self::Hup),
+            "term" => Ok(Self::Term),
+            "int" => Ok(Self::Int),
+            e => Err(e.to_string().into()),
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug, PartialEq, Deserialize, Serialize, StructOpt)]
 /// Options that can be set either through the config file,
 /// or cli flags - cli flags should take precedence over the config file
@@ -54,6 +269,10 @@ pub struct Options {
     /// subdirectory of config dir
     #[structopt(long, parse(from_os_str))]
     pub layout_dir: Option<PathBuf>,
+    /// Set the directory to keep the session sockets in, the ZELLIJ_SOCKET_DIR environment
+    /// variable takes precedence
+    #[structopt(long, parse(from_os_str))]
+    pub socket_dir: Option<PathBuf>,
     #[structopt(long)]
     #[serde(default)]
     /// Disable handling of mouse events
@@ -61,9 +280,192 @@ pub struct Options {
     #[structopt(long)]
     #[serde(default)]
     pub no_pane_frames: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Swap the scrolling direction of the mouse wheel (natural scrolling)
+    pub reverse_scroll: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Focus the pane under the mouse pointer, without having to click it
+    pub focus_follows_mouse: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Render less often and hold back copies to the clipboard while the terminal Zellij runs in
+    /// isn't focused, for terminals reporting focus changes
+    pub pause_rendering_when_unfocused: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Copy the space tab characters leave in panes as spaces rather than tabs
+    pub copy_tabs_as_spaces: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Keep the wrapping of the lines in panes as it is when they are resized, cutting off what
+    /// no longer fits, rather than re-wrapping them to the new width
+    pub disable_reflow: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Ignore the titles applications in panes set (OSC 0 and 2), rather than showing them in
+    /// the pane frames and the tab bar
+    pub disable_pane_titles: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Leave out the frame of a pane when it is alone in its tab
+    pub auto_pane_frames: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Take control characters, like escape sequences, out of pasted text
+    pub sanitize_paste: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Ask before pasting several lines into a pane that looks like it would run each of them
+    pub confirm_multiline_paste: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Leave fullscreen when the focus moves to another pane, rather than keeping the focus on
+    /// the fullscreen pane
+    pub exit_fullscreen_on_focus_change: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Redraw in place rather than clearing the screen or panes first, and paint each frame at
+    /// once on terminals that support it, for slow connections and photosensitive users
+    pub reduced_motion: bool,
+    #[structopt(long)]
+    #[serde(default)]
+    /// End each row a pane draws with a style reset and an explicit cursor position at the edge
+    /// of the pane, for terminals that shape ligatures across panes and their borders otherwise
+    pub ligature_safe_boundaries: bool,
+    /// Set how many columns characters of ambiguous East Asian width take up in the panes
+    /// (narrow or wide), as many as the terminal draws them with
+    #[structopt(long)]
+    pub ambiguous_width: Option<AmbiguousWidth>,
+    /// Regular expressions for the text quick copy offers to copy,
+    /// replacing the default ones (urls, uuids, ip addresses and hashes)
+    #[structopt(long)]
+    pub quick_copy_patterns: Option<Vec<String>>,
+    /// Regular expressions for the links link hints offer to open, replacing the default ones
+    /// (urls and absolute paths), the text of a link is the `hint` group of its pattern (eg.
+    /// `(?P<hint>...)`) if it has one
+    #[structopt(long)]
+    pub link_hint_patterns: Option<Vec<String>>,
     /// Set behaviour on force close (quit or detach)
     #[structopt(long)]
     pub on_force_close: Option<OnForceClose>,
+    /// Set what happens when an application in a pane sets the clipboard (allow, prompt or deny)
+    #[structopt(long)]
+    pub pane_clipboard_policy: Option<PaneClipboardPolicy>,
+    /// Set the largest text (in bytes) an application in a pane can put in the clipboard
+    #[structopt(long)]
+    pub pane_clipboard_max_size: Option<usize>,
+    #[structopt(long)]
+    #[serde(default)]
+    /// Keep the text selected with the mouse selected when the button is released, for the Copy
+    /// action to copy it, rather than copying it right away
+    pub disable_copy_on_select: bool,
+    /// Pipe copied text to this command (eg. `wl-copy`, `xclip -selection clipboard` or `pbcopy`)
+    /// rather than sending it to the terminal to put in the clipboard (OSC 52)
+    #[structopt(long)]
+    pub copy_command: Option<String>,
+    /// Set the clipboard the terminal puts copied text in (system or primary)
+    #[structopt(long)]
+    pub copy_clipboard: Option<Clipboard>,
+    /// Read the text to paste with the PasteFromClipboard action from the output of this command
+    /// (eg. `wl-paste`, `xclip -out -selection clipboard` or `pbpaste`) rather than asking the
+    /// terminal for the clipboard (OSC 52)
+    #[structopt(long)]
+    pub paste_command: Option<String>,
+    /// Open the target of a hyperlink (OSC 8) Ctrl-clicked in a pane with this command, which
+    /// gets it as its last argument (`xdg-open` by default, `open` on macOS)
+    #[structopt(long)]
+    pub link_opener: Option<String>,
+    /// Set the style of the pane frames (normal, rounded or thick)
+    #[structopt(long)]
+    pub pane_frames: Option<PaneFrameStyle>,
+    /// Set the distance between the default tab stops in panes
+    #[structopt(long)]
+    pub tab_width: Option<usize>,
+    /// Set how many lines of scrollback each pane keeps (at least one), the oldest are dropped
+    /// beyond it
+    #[structopt(long)]
+    pub scroll_buffer_size: Option<usize>,
+    /// Set what happens to the panes of a tab when the terminal gets too small for all of them
+    /// (keep, stack or hide)
+    #[structopt(long)]
+    pub small_panes: Option<SmallPanesPolicy>,
+    /// Set the fewest columns each side of a pane split in the automatic direction keeps
+    #[structopt(long)]
+    pub auto_split_min_columns: Option<usize>,
+    /// Set the fewest rows each side of a pane split in the automatic direction keeps
+    #[structopt(long)]
+    pub auto_split_min_rows: Option<usize>,
+    /// Set the language of the keybinding hints and prompts (en or de), instead of the one in
+    /// the ZELLIJ_LOCALE environment variable
+    #[structopt(long)]
+    pub locale: Option<Locale>,
+    /// Set the keyboard layout (eg. azerty or qwertz) whose keys fall back to the keybinds of
+    /// the US keys at the same position, when they have none of their own
+    #[structopt(long)]
+    pub keyboard_layout: Option<String>,
+    /// Template for generated session names, `{adjective}`, `{noun}` and `{number}`
+    /// are filled in
+    #[structopt(long)]
+    pub session_name_template: Option<String>,
+    /// Adjectives to generate session names from, replacing the built-in ones
+    #[structopt(long)]
+    pub session_name_adjectives: Option<Vec<String>>,
+    /// Nouns to generate session names from, replacing the built-in ones
+    #[structopt(long)]
+    pub session_name_nouns: Option<Vec<String>>,
+    /// Set the most panes a tab of the session can hold, opening more is refused
+    #[structopt(long)]
+    pub max_panes_per_tab: Option<usize>,
+    /// Set the most tabs the session can hold, opening more is refused
+    #[structopt(long)]
+    pub max_tabs: Option<usize>,
+    /// Set the most memory (in megabytes) the scrollback of all panes of the session can
+    /// take up together, the oldest lines of the panes are dropped beyond it
+    #[structopt(long)]
+    pub max_scrollback_memory: Option<usize>,
+    /// Set how many actions a second the command line can send to the session, the ones
+    /// beyond it are rejected
+    #[structopt(long)]
+    pub cli_action_rate: Option<usize>,
+    /// Set how many actions the command line can send to the session at once before
+    /// `cli_action_rate` applies, by default as many as the rate
+    #[structopt(long)]
+    pub cli_action_burst: Option<usize>,
+    /// Set the signal the programs running in the shells of the panes get when the session quits
+    /// (hup, term or int), the shells get SIGHUP once they are gone
+    #[structopt(long)]
+    pub shutdown_signal: Option<ShutdownSignal>,
+    /// Set how long (in milliseconds) the programs running in the panes have to exit when the
+    /// session quits, before they are killed
+    #[structopt(long)]
+    pub shutdown_grace_period: Option<u64>,
+    /// Write the session to disk as it runs and when it quits, to resurrect it with `zellij
+    /// resurrect` once its server is gone [default: true]
+    #[structopt(long)]
+    pub session_serialization: Option<bool>,
+    /// Set how often (in seconds) the session is written to disk
+    #[structopt(long)]
+    pub serialization_interval: Option<u64>,
+    /// Set how many lines of the scrollback of each pane are written along with the session
+    #[structopt(long)]
+    pub scrollback_lines_to_serialize: Option<usize>,
+    /// Quit the session, written to disk, once it has had no clients attached and no output
+    /// from its panes for this many hours
+    #[structopt(long)]
+    pub idle_exit_hours: Option<u64>,
+    /// Sessions that never quit for `idle_exit_hours`
+    #[structopt(long)]
+    pub idle_exit_exceptions: Option<Vec<String>>,
+    /// Start new panes and tabs in the working directory of the focused pane, rather than in
+    /// the one the session started in [default: true]
+    #[structopt(long)]
+    pub inherit_cwd: Option<bool>,
+    /// Set how clients of different sizes attached to the session show it (smallest or
+    /// letterbox)
+    #[structopt(long)]
+    pub client_sizing: Option<ClientSizing>,
 }
 
 impl Options {
@@ -84,12 +486,89 @@ impl Options {
         let simplified_ui = merge_bool(other.simplified_ui, self.simplified_ui);
         let disable_mouse_mode = merge_bool(other.disable_mouse_mode, self.disable_mouse_mode);
         let no_pane_frames = merge_bool(other.no_pane_frames, self.no_pane_frames);
+        let reverse_scroll = merge_bool(other.reverse_scroll, self.reverse_scroll);
+        let focus_follows_mouse = merge_bool(other.focus_follows_mouse, self.focus_follows_mouse);
+        let pause_rendering_when_unfocused = merge_bool(
+            other.pause_rendering_when_unfocused,
+            self.pause_rendering_when_unfocused,
+        );
+        let copy_tabs_as_spaces = merge_bool(other.copy_tabs_as_spaces, self.copy_tabs_as_spaces);
+        let disable_reflow = merge_bool(other.disable_reflow, self.disable_reflow);
+        let disable_pane_titles = merge_bool(other.disable_pane_titles, self.disable_pane_titles);
+        let auto_pane_frames = merge_bool(other.auto_pane_frames, self.auto_pane_frames);
+        let sanitize_paste = merge_bool(other.sanitize_paste, self.sanitize_paste);
+        let confirm_multiline_paste =
+            merge_bool(other.confirm_multiline_paste, self.confirm_multiline_paste);
+        let exit_fullscreen_on_focus_change = merge_bool(
+            other.exit_fullscreen_on_focus_change,
+            self.exit_fullscreen_on_focus_change,
+        );
+        let reduced_motion = merge_bool(other.reduced_motion, self.reduced_motion);
+        let ligature_safe_boundaries = merge_bool(
+            other.ligature_safe_boundaries,
+            self.ligature_safe_boundaries,
+        );
+        let disable_copy_on_select =
+            merge_bool(other.disable_copy_on_select, self.disable_copy_on_select);
 
         let default_mode = other.default_mode.or(self.default_mode);
         let default_shell = other.default_shell.or_else(|| self.default_shell.clone());
         let layout_dir = other.layout_dir.or_else(|| self.layout_dir.clone());
+        let socket_dir = other.socket_dir.or_else(|| self.socket_dir.clone());
         let theme = other.theme.or_else(|| self.theme.clone());
         let on_force_close = other.on_force_close.or(self.on_force_close);
+        let pane_clipboard_policy = other.pane_clipboard_policy.or(self.pane_clipboard_policy);
+        let pane_clipboard_max_size = other
+            .pane_clipboard_max_size
+            .or(self.pane_clipboard_max_size);
+        let copy_command = other.copy_command.or_else(|| self.copy_command.clone());
+        let copy_clipboard = other.copy_clipboard.or(self.copy_clipboard);
+        let paste_command = other.paste_command.or_else(|| self.paste_command.clone());
+        let link_opener = other.link_opener.or_else(|| self.link_opener.clone());
+        let tab_width = other.tab_width.or(self.tab_width);
+        let scroll_buffer_size = other.scroll_buffer_size.or(self.scroll_buffer_size);
+        let small_panes = other.small_panes.or(self.small_panes);
+        let auto_split_min_columns = other.auto_split_min_columns.or(self.auto_split_min_columns);
+        let auto_split_min_rows = other.auto_split_min_rows.or(self.auto_split_min_rows);
+        let pane_frames = other.pane_frames.or(self.pane_frames);
+        let locale = other.locale.or(self.locale);
+        let keyboard_layout = other
+            .keyboard_layout
+            .or_else(|| self.keyboard_layout.clone());
+        let ambiguous_width = other.ambiguous_width.or(self.ambiguous_width);
+        let quick_copy_patterns = other
+            .quick_copy_patterns
+            .or_else(|| self.quick_copy_patterns.clone());
+        let link_hint_patterns = other
+            .link_hint_patterns
+            .or_else(|| self.link_hint_patterns.clone());
+        let session_name_template = other
+            .session_name_template
+            .or_else(|| self.session_name_template.clone());
+        let session_name_adjectives = other
+            .session_name_adjectives
+            .or_else(|| self.session_name_adjectives.clone());
+        let session_name_nouns = other
+            .session_name_nouns
+            .or_else(|| self.session_name_nouns.clone());
+        let max_panes_per_tab = other.max_panes_per_tab.or(self.max_panes_per_tab);
+        let max_tabs = other.max_tabs.or(self.max_tabs);
+        let max_scrollback_memory = other.max_scrollback_memory.or(self.max_scrollback_memory);
+        let cli_action_rate = other.cli_action_rate.or(self.cli_action_rate);
+        let cli_action_burst = other.cli_action_burst.or(self.cli_action_burst);
+        let shutdown_signal = other.shutdown_signal.or(self.shutdown_signal);
+        let shutdown_grace_period = other.shutdown_grace_period.or(self.shutdown_grace_period);
+        let session_serialization = other.session_serialization.or(self.session_serialization);
+        let serialization_interval = other.serialization_interval.or(self.serialization_interval);
+        let scrollback_lines_to_serialize = other
+            .scrollback_lines_to_serialize
+            .or(self.scrollback_lines_to_serialize);
+        let idle_exit_hours = other.idle_exit_hours.or(self.idle_exit_hours);
+        let idle_exit_exceptions = other
+            .idle_exit_exceptions
+            .or_else(|| self.idle_exit_exceptions.clone());
+        let inherit_cwd = other.inherit_cwd.or(self.inherit_cwd);
+        let client_sizing = other.client_sizing.or(self.client_sizing);
 
         Options {
             simplified_ui,
@@ -97,9 +576,57 @@ impl Options {
             default_mode,
             default_shell,
             layout_dir,
+            socket_dir,
             disable_mouse_mode,
             no_pane_frames,
+            reverse_scroll,
+            focus_follows_mouse,
+            pause_rendering_when_unfocused,
+            copy_tabs_as_spaces,
+            disable_reflow,
+            disable_pane_titles,
+            auto_pane_frames,
+            sanitize_paste,
+            confirm_multiline_paste,
+            exit_fullscreen_on_focus_change,
+            reduced_motion,
+            ligature_safe_boundaries,
             on_force_close,
+            pane_clipboard_policy,
+            pane_clipboard_max_size,
+            disable_copy_on_select,
+            copy_command,
+            copy_clipboard,
+            paste_command,
+            link_opener,
+            tab_width,
+            scroll_buffer_size,
+            small_panes,
+            auto_split_min_columns,
+            auto_split_min_rows,
+            pane_frames,
+            locale,
+            keyboard_layout,
+            ambiguous_width,
+            quick_copy_patterns,
+            link_hint_patterns,
+            session_name_template,
+            session_name_adjectives,
+            session_name_nouns,
+            max_panes_per_tab,
+            max_tabs,
+            max_scrollback_memory,
+            cli_action_rate,
+            cli_action_burst,
+            shutdown_signal,
+            shutdown_grace_period,
+            session_serialization,
+            serialization_interval,
+            scrollback_lines_to_serialize,
+            idle_exit_hours,
+            idle_exit_exceptions,
+            inherit_cwd,
+            client_sizing,
         }
     }
 
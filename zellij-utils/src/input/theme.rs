@@ -1,5 +1,6 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::str::FromStr;
 
 use super::options::Options;
 use zellij_tile::data::{Palette, PaletteColor};
@@ -32,19 +33,110 @@ pub struct PaletteFromYaml {
 
 /// Intermediate deserialization enum
 // This is here in order to make the untagged enum work
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
-#[serde(untagged)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PaletteColorFromYaml {
     Rgb((u8, u8, u8)),
     EightBit(u8),
 }
 
+// colors are written as `[r, g, b]` or a color index in the config and layouts, while the
+// binary encoding between the client and the server needs the variant to tell them apart
+impl Serialize for PaletteColorFromYaml {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return PaletteColor::from(*self).serialize(serializer);
+        }
+        match self {
+            PaletteColorFromYaml::Rgb(color) => color.serialize(serializer),
+            PaletteColorFromYaml::EightBit(color) => color.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PaletteColorFromYaml {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum ColorInConfig {
+            Rgb((u8, u8, u8)),
+            EightBit(u8),
+        }
+        if !deserializer.is_human_readable() {
+            return PaletteColor::deserialize(deserializer).map(PaletteColorFromYaml::from);
+        }
+        Ok(match ColorInConfig::deserialize(deserializer)? {
+            ColorInConfig::Rgb(color) => PaletteColorFromYaml::Rgb(color),
+            ColorInConfig::EightBit(color) => PaletteColorFromYaml::EightBit(color),
+        })
+    }
+}
+
 impl Default for PaletteColorFromYaml {
     fn default() -> Self {
         PaletteColorFromYaml::EightBit(0)
     }
 }
 
+impl FromStr for PaletteColorFromYaml {
+    type Err = Box<dyn std::error::Error>;
+
+    // `#400000` or `64,0,0` is a color in rgb, `52` one of the 256 colors of the terminal
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(hex) = s.strip_prefix('#') {
+            if hex.len() != 6 {
+                return Err(format!("expected `#rrggbb`, got {:?}", s).into());
+            }
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+            return Ok(Self::Rgb((channel(0)?, channel(2)?, channel(4)?)));
+        }
+        match s.split(',').collect::<Vec<&str>>()[..] {
+            [r, g, b] => Ok(Self::Rgb((
+                r.trim().parse()?,
+                g.trim().parse()?,
+                b.trim().parse()?,
+            ))),
+            [index] => Ok(Self::EightBit(index.trim().parse()?)),
+            _ => Err(format!("expected `#rrggbb`, `r,g,b` or a color index, got {:?}", s).into()),
+        }
+    }
+}
+
+/// Colors replacing those of the palette in a single pane. `fg` and `bg` stand in for the
+/// default colors of the terminal, the others for the eight colors of the terminal and their
+/// bright variants. The colors left out are kept.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PaletteOverride {
+    pub fg: Option<PaletteColorFromYaml>,
+    pub bg: Option<PaletteColorFromYaml>,
+    pub black: Option<PaletteColorFromYaml>,
+    pub red: Option<PaletteColorFromYaml>,
+    pub green: Option<PaletteColorFromYaml>,
+    pub yellow: Option<PaletteColorFromYaml>,
+    pub blue: Option<PaletteColorFromYaml>,
+    pub magenta: Option<PaletteColorFromYaml>,
+    pub cyan: Option<PaletteColorFromYaml>,
+    pub white: Option<PaletteColorFromYaml>,
+}
+
+impl PaletteOverride {
+    /// The color replacing the one of the terminal with the given index, from `0` (black) to
+    /// `15` (bright white).
+    pub fn color(&self, index: u8) -> Option<PaletteColorFromYaml> {
+        match index {
+            0 | 8 => self.black,
+            1 | 9 => self.red,
+            2 | 10 => self.green,
+            3 | 11 => self.yellow,
+            4 | 12 => self.blue,
+            5 | 13 => self.magenta,
+            6 | 14 => self.cyan,
+            7 | 15 => self.white,
+            _ => None,
+        }
+    }
+}
+
 impl ThemesFromYaml {
     pub fn theme_config(self, opts: &Options) -> Option<Palette> {
         let mut from_yaml = self;
@@ -54,6 +146,13 @@ impl ThemesFromYaml {
         }
     }
 
+    /// Merges two themes collections, where the themes of `other` take precedence over the
+    /// ones of the same name in `self`.
+    pub fn merge(mut self, other: ThemesFromYaml) -> Self {
+        self.0.extend(other.0);
+        self
+    }
+
     fn get_theme(&mut self, theme: String) -> Option<Theme> {
         self.0.remove(&theme)
     }
@@ -84,6 +183,15 @@ impl From<PaletteFromYaml> for Palette {
     }
 }
 
+impl From<PaletteColor> for PaletteColorFromYaml {
+    fn from(color: PaletteColor) -> Self {
+        match color {
+            PaletteColor::Rgb(color) => PaletteColorFromYaml::Rgb(color),
+            PaletteColor::EightBit(color) => PaletteColorFromYaml::EightBit(color),
+        }
+    }
+}
+
 impl From<PaletteColorFromYaml> for PaletteColor {
     fn from(yaml: PaletteColorFromYaml) -> Self {
         match yaml {
@@ -0,0 +1,72 @@
+use super::super::bundle::*;
+use super::super::layout::LayoutFromYaml;
+use super::super::options::Options;
+use std::path::{Path, PathBuf};
+
+fn default_layout() -> LayoutFromYaml {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    LayoutFromYaml::new(&root.join("assets/layouts/default.yaml")).unwrap()
+}
+
+#[test]
+fn bundle_lists_the_plugins_of_its_layout() {
+    let bundle = SessionBundle::new(default_layout(), Options::default(), None);
+
+    assert_eq!(
+        bundle.plugins,
+        vec![PathBuf::from("status-bar"), PathBuf::from("tab-bar")]
+    );
+    assert!(bundle.missing_plugins().is_empty());
+}
+
+#[test]
+fn bundle_leaves_out_the_options_of_this_machine() {
+    let options = Options {
+        default_shell: Some("/usr/local/bin/fish".into()),
+        layout_dir: Some("/home/me/layouts".into()),
+        copy_command: Some("wl-copy".into()),
+        paste_command: Some("wl-paste".into()),
+        reduced_motion: true,
+        ..Default::default()
+    };
+    let bundle = SessionBundle::new(default_layout(), options, None);
+
+    assert_eq!(bundle.options.default_shell, None);
+    assert_eq!(bundle.options.layout_dir, None);
+    assert_eq!(bundle.options.copy_command, None);
+    assert_eq!(bundle.options.paste_command, None);
+    assert!(bundle.options.reduced_motion);
+}
+
+#[test]
+fn bundle_survives_the_trip_through_a_file() {
+    let bundle = SessionBundle::new(default_layout(), Options::default(), None);
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("work.zellij");
+
+    bundle.write_to(&path).unwrap();
+    assert_eq!(SessionBundle::from_path(&path).unwrap(), bundle);
+}
+
+#[test]
+fn resurrectable_bundle_keeps_the_options_and_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let options = Options {
+        default_shell: Some("/usr/local/bin/fish".into()),
+        ..Default::default()
+    };
+    let bundle = SessionBundle::resurrectable(default_layout(), options);
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("resurrection").join("work.yaml");
+
+    bundle.write_privately_to(&path).unwrap();
+    bundle.write_privately_to(&path).unwrap();
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    let read = SessionBundle::from_path(&path).unwrap();
+    assert_eq!(
+        read.options.default_shell,
+        Some(PathBuf::from("/usr/local/bin/fish"))
+    );
+}
@@ -1,5 +1,9 @@
 use super::super::actions::*;
 use super::super::keybinds::*;
+use super::super::keyboard_layout::{KeyAliases, KeyboardLayouts};
+use super::super::mouse::{MouseButton, MouseEvent};
+use super::super::options::Options;
+use crate::position::Position;
 use zellij_tile::data::Key;
 
 #[test]
@@ -799,3 +803,211 @@ fn uppercase_and_lowercase_are_distinct() {
     assert!(result_n.is_some());
     assert!(result_large_n.is_some());
 }
+
+#[test]
+fn key_to_actions_expands_macros() {
+    let mut mode_keybinds = ModeKeybinds::new();
+    mode_keybinds
+        .0
+        .insert(Key::F(1), vec![Action::Macro("two-panes".into())]);
+    let mut keybinds = Keybinds::new();
+    keybinds.0.insert(InputMode::Normal, mode_keybinds);
+    let mut macros = Macros::new();
+    macros.insert(
+        "two-panes".into(),
+        vec![Action::NewPane(None), Action::NewPane(None)],
+    );
+
+    let actions = Keybinds::key_to_actions(
+        &Key::F(1),
+        vec![],
+        &InputMode::Normal,
+        &keybinds,
+        &macros,
+        &KeyAliases::default(),
+    );
+
+    assert_eq!(actions, vec![Action::NewPane(None), Action::NewPane(None)]);
+}
+
+#[test]
+fn key_to_actions_falls_back_to_keyboard_layout_alias() {
+    let mut mode_keybinds = ModeKeybinds::new();
+    mode_keybinds
+        .0
+        .insert(Key::Alt('['), vec![Action::GoToPreviousTab]);
+    mode_keybinds.0.insert(Key::Alt('+'), vec![Action::NoOp]);
+    let mut keybinds = Keybinds::new();
+    keybinds.0.insert(InputMode::Normal, mode_keybinds);
+    let layouts = KeyboardLayouts::default();
+    let qwertz = layouts.get("qwertz").unwrap();
+    let to_actions = |key| {
+        Keybinds::key_to_actions(
+            &key,
+            vec![],
+            &InputMode::Normal,
+            &keybinds,
+            &Macros::new(),
+            qwertz,
+        )
+    };
+
+    assert_eq!(to_actions(Key::Alt('ü')), vec![Action::GoToPreviousTab]);
+    // a key with a keybind of its own keeps it
+    assert_eq!(to_actions(Key::Alt('+')), vec![Action::NoOp]);
+    assert_eq!(to_actions(Key::Ctrl('ü')), vec![Action::Write(vec![])]);
+}
+
+#[test]
+fn run_action_from_yaml() {
+    use super::super::command::RunCommandAction;
+    let keyaction: KeyActionFromYaml = serde_yaml::from_str(
+        "action: [Run: {command: git, args: [status], direction: Down},]\nkey: [F: 1,]",
+    )
+    .unwrap();
+
+    let expected = Action::Run(RunCommandAction {
+        command: "git".into(),
+        args: vec!["status".into()],
+        cwd: None,
+        direction: Some(Direction::Down),
+    });
+    assert_eq!(keyaction.action, vec![expected]);
+}
+
+#[test]
+fn resize_action_from_yaml_with_and_without_amount() {
+    let keyaction: KeyActionFromYaml = serde_yaml::from_str(
+        "action: [Resize: Left, Resize: [Up, Cells: 5], Resize: [Down, Percent: 10],]\nkey: [F: 1,]",
+    )
+    .unwrap();
+
+    let expected = vec![
+        Action::Resize(Direction::Left, None),
+        Action::Resize(Direction::Up, Some(ResizeAmount::Cells(5))),
+        Action::Resize(Direction::Down, Some(ResizeAmount::Percent(10))),
+    ];
+    assert_eq!(keyaction.action, expected);
+}
+
+#[test]
+fn resize_action_survives_the_trip_between_client_and_server() {
+    let action = Action::Resize(Direction::Right, Some(ResizeAmount::Cells(3)));
+    let bytes = bincode::serialize(&action).unwrap();
+    let decoded: Action = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(decoded, action);
+}
+
+#[test]
+fn unbind_keys_from_shorthand() {
+    let from_yaml: KeybindsFromYaml =
+        serde_yaml::from_str("normal:\n    - unbind: [\"Ctrl-g\", Alt: 'n', \"F1\", PageUp]")
+            .unwrap();
+
+    let unbind = match &from_yaml.keybinds[&InputMode::Normal][0] {
+        KeyActionUnbind::Unbind(unbind) => unbind.unbind.clone(),
+        KeyActionUnbind::KeyAction(_) => panic!("expected an unbind"),
+    };
+    assert_eq!(
+        unbind,
+        Unbind::Keys(vec![Key::Ctrl('g'), Key::Alt('n'), Key::F(1), Key::PageUp])
+    );
+}
+
+#[test]
+fn unbinds_of_the_same_mode_add_up() {
+    let from_yaml: KeybindsFromYaml =
+        serde_yaml::from_str("normal:\n    - unbind: [\"Ctrl-g\"]\n    - unbind: [\"Ctrl-p\"]")
+            .unwrap();
+
+    let keybinds = Keybinds::get_default_keybinds_with_config(Some(from_yaml));
+    let normal = keybinds.0.get(&InputMode::Normal).unwrap();
+    assert!(normal.0.get(&Key::Ctrl('g')).is_none());
+    assert!(normal.0.get(&Key::Ctrl('p')).is_none());
+    assert!(normal.0.get(&Key::Ctrl('t')).is_some());
+}
+
+#[test]
+fn keys_of_a_fully_unbound_mode_are_written() {
+    let from_yaml: KeybindsFromYaml = serde_yaml::from_str("normal:\n    - unbind: true").unwrap();
+
+    let keybinds = Keybinds::get_default_keybinds_with_config(Some(from_yaml));
+    let actions = Keybinds::key_to_actions(
+        &Key::Ctrl('g'),
+        vec![7],
+        &InputMode::Normal,
+        &keybinds,
+        &Macros::new(),
+        &KeyAliases::default(),
+    );
+
+    assert_eq!(actions, vec![Action::Write(vec![7])]);
+}
+
+#[test]
+fn new_tab_action_with_name_layout_and_cwd_from_yaml() {
+    use super::super::layout::TabLayout;
+    let keyaction: KeyActionFromYaml = serde_yaml::from_str(
+        "action: [NewTab: {name: scratch, layout: scratch, cwd: /tmp},]\nkey: [Alt: 't',]",
+    )
+    .unwrap();
+
+    let expected = Action::NewTab(Some(TabLayout {
+        name: "scratch".into(),
+        layout: Some("scratch".into()),
+        cwd: Some("/tmp".into()),
+        ..Default::default()
+    }));
+    assert_eq!(keyaction.action, vec![expected]);
+}
+
+#[test]
+fn switch_session_action_from_yaml() {
+    let keyaction: KeyActionFromYaml =
+        serde_yaml::from_str("action: [SwitchSession: [work, true],]\nkey: [Alt: 's',]").unwrap();
+
+    let expected = Action::SwitchSession("work".into(), true);
+    assert_eq!(keyaction.action, vec![expected]);
+}
+
+#[test]
+fn mouse_to_actions_scrolls_in_every_mode() {
+    let wheel = MouseEvent::Press(
+        MouseButton::WheelUp,
+        Position::new(2, 3),
+        Default::default(),
+    );
+    let options = Options::default();
+    for mode in &[InputMode::Normal, InputMode::Scroll, InputMode::RenamePane] {
+        let actions = Keybinds::mouse_to_actions(&wheel, mode, &options);
+        assert_eq!(actions, vec![Action::ScrollUpAt(Position::new(2, 3))]);
+    }
+}
+
+#[test]
+fn mouse_to_actions_ignores_clicks_while_renaming() {
+    let click = MouseEvent::Press(MouseButton::Left, Position::new(2, 3), Default::default());
+    let options = Options::default();
+
+    let actions = Keybinds::mouse_to_actions(&click, &InputMode::RenameTab, &options);
+    assert!(actions.is_empty());
+    let actions = Keybinds::mouse_to_actions(&click, &InputMode::Scroll, &options);
+    assert_eq!(
+        actions,
+        vec![Action::LeftClick(Position::new(2, 3), Default::default())]
+    );
+}
+
+#[test]
+fn mouse_to_actions_keeps_the_focus_while_scrolling() {
+    let motion = MouseEvent::Motion(Position::new(2, 3));
+    let options = Options {
+        focus_follows_mouse: true,
+        ..Default::default()
+    };
+
+    let actions = Keybinds::mouse_to_actions(&motion, &InputMode::Scroll, &options);
+    assert!(actions.is_empty());
+    let actions = Keybinds::mouse_to_actions(&motion, &InputMode::Normal, &options);
+    assert_eq!(actions, vec![Action::MouseMotion(Position::new(2, 3))]);
+}
@@ -0,0 +1,18 @@
+use super::*;
+
+#[test]
+fn merge_replaces_layouts_of_the_same_name() {
+    let other: KeyboardLayouts =
+        serde_yaml::from_str("azerty: {'^': '['}\ncolemak: {'ö': ';'}").unwrap();
+
+    let layouts = KeyboardLayouts::default().merge(other);
+
+    let azerty = layouts.get("azerty").unwrap();
+    assert_eq!(azerty.alias(&Key::Alt('^')), Some(Key::Alt('[')));
+    assert_eq!(azerty.alias(&Key::Alt('$')), None);
+    assert!(layouts.get("qwertz").is_some());
+    assert_eq!(
+        layouts.get("colemak").unwrap().alias(&Key::Char('ö')),
+        Some(Key::Char(';'))
+    );
+}
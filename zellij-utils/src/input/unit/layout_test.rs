@@ -1,4 +1,6 @@
 use super::super::layout::*;
+use super::super::options::{AmbiguousWidth, ShutdownSignal};
+use super::super::theme::{PaletteColorFromYaml, PaletteOverride};
 
 fn layout_test_dir(layout: String) -> PathBuf {
     let root = Path::new(env!("CARGO_MANIFEST_DIR"));
@@ -39,10 +41,24 @@ fn default_layout_merged_correctly() {
     let merged_layout = Layout {
         direction: Direction::Horizontal,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
         parts: vec![
             Layout {
                 direction: Direction::Vertical,
                 borderless: true,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Fixed(1)),
                 run: Some(Run::Plugin(Some(RunPlugin {
@@ -53,6 +69,13 @@ fn default_layout_merged_correctly() {
             Layout {
                 direction: Direction::Vertical,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: None,
                 run: None,
@@ -60,6 +83,13 @@ fn default_layout_merged_correctly() {
             Layout {
                 direction: Direction::Vertical,
                 borderless: true,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Fixed(2)),
                 run: Some(Run::Plugin(Some(RunPlugin {
@@ -83,10 +113,24 @@ fn default_layout_new_tab_correct() {
     let merged_layout = Layout {
         direction: Direction::Horizontal,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
         parts: vec![
             Layout {
                 direction: Direction::Vertical,
                 borderless: true,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Fixed(1)),
                 run: Some(Run::Plugin(Some(RunPlugin {
@@ -97,6 +141,13 @@ fn default_layout_new_tab_correct() {
             Layout {
                 direction: Direction::Horizontal,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: None,
                 run: None,
@@ -104,6 +155,13 @@ fn default_layout_new_tab_correct() {
             Layout {
                 direction: Direction::Vertical,
                 borderless: true,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Fixed(2)),
                 run: Some(Run::Plugin(Some(RunPlugin {
@@ -167,13 +225,34 @@ fn three_panes_with_tab_merged_correctly() {
     let merged_layout = Layout {
         direction: Direction::Horizontal,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
         parts: vec![Layout {
             direction: Direction::Vertical,
             borderless: false,
+            stacked: false,
+            pane_frames: None,
+            min_size: None,
+            max_size: None,
+            tags: vec![],
+            palette: None,
+            ambiguous_width: None,
             parts: vec![
                 Layout {
                     direction: Direction::Horizontal,
                     borderless: false,
+                    stacked: false,
+                    pane_frames: None,
+                    min_size: None,
+                    max_size: None,
+                    tags: vec![],
+                    palette: None,
+                    ambiguous_width: None,
                     parts: vec![],
                     split_size: Some(SplitSize::Percent(50.0)),
                     run: None,
@@ -181,10 +260,24 @@ fn three_panes_with_tab_merged_correctly() {
                 Layout {
                     direction: Direction::Horizontal,
                     borderless: false,
+                    stacked: false,
+                    pane_frames: None,
+                    min_size: None,
+                    max_size: None,
+                    tags: vec![],
+                    palette: None,
+                    ambiguous_width: None,
                     parts: vec![
                         Layout {
                             direction: Direction::Vertical,
                             borderless: false,
+                            stacked: false,
+                            pane_frames: None,
+                            min_size: None,
+                            max_size: None,
+                            tags: vec![],
+                            palette: None,
+                            ambiguous_width: None,
                             parts: vec![],
                             split_size: Some(SplitSize::Percent(50.0)),
                             run: None,
@@ -192,6 +285,13 @@ fn three_panes_with_tab_merged_correctly() {
                         Layout {
                             direction: Direction::Vertical,
                             borderless: false,
+                            stacked: false,
+                            pane_frames: None,
+                            min_size: None,
+                            max_size: None,
+                            tags: vec![],
+                            palette: None,
+                            ambiguous_width: None,
                             parts: vec![],
                             split_size: Some(SplitSize::Percent(50.0)),
                             run: None,
@@ -219,9 +319,23 @@ fn three_panes_with_tab_new_tab_is_correct() {
     let merged_layout = Layout {
         direction: Direction::Horizontal,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
         parts: vec![Layout {
             direction: Direction::Horizontal,
             borderless: false,
+            stacked: false,
+            pane_frames: None,
+            min_size: None,
+            max_size: None,
+            tags: vec![],
+            palette: None,
+            ambiguous_width: None,
             parts: vec![],
             split_size: None,
             run: None,
@@ -259,10 +373,24 @@ fn three_panes_with_tab_and_default_plugins_merged_correctly() {
     let merged_layout = Layout {
         direction: Direction::Horizontal,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
         parts: vec![
             Layout {
                 direction: Direction::Vertical,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Fixed(1)),
                 run: Some(Run::Plugin(Some(RunPlugin {
@@ -273,10 +401,24 @@ fn three_panes_with_tab_and_default_plugins_merged_correctly() {
             Layout {
                 direction: Direction::Vertical,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![
                     Layout {
                         direction: Direction::Horizontal,
                         borderless: false,
+                        stacked: false,
+                        pane_frames: None,
+                        min_size: None,
+                        max_size: None,
+                        tags: vec![],
+                        palette: None,
+                        ambiguous_width: None,
                         parts: vec![],
                         split_size: Some(SplitSize::Percent(50.0)),
                         run: None,
@@ -284,10 +426,24 @@ fn three_panes_with_tab_and_default_plugins_merged_correctly() {
                     Layout {
                         direction: Direction::Horizontal,
                         borderless: false,
+                        stacked: false,
+                        pane_frames: None,
+                        min_size: None,
+                        max_size: None,
+                        tags: vec![],
+                        palette: None,
+                        ambiguous_width: None,
                         parts: vec![
                             Layout {
                                 direction: Direction::Vertical,
                                 borderless: false,
+                                stacked: false,
+                                pane_frames: None,
+                                min_size: None,
+                                max_size: None,
+                                tags: vec![],
+                                palette: None,
+                                ambiguous_width: None,
                                 parts: vec![],
                                 split_size: Some(SplitSize::Percent(50.0)),
                                 run: None,
@@ -295,6 +451,13 @@ fn three_panes_with_tab_and_default_plugins_merged_correctly() {
                             Layout {
                                 direction: Direction::Vertical,
                                 borderless: false,
+                                stacked: false,
+                                pane_frames: None,
+                                min_size: None,
+                                max_size: None,
+                                tags: vec![],
+                                palette: None,
+                                ambiguous_width: None,
                                 parts: vec![],
                                 split_size: Some(SplitSize::Percent(50.0)),
                                 run: None,
@@ -310,6 +473,13 @@ fn three_panes_with_tab_and_default_plugins_merged_correctly() {
             Layout {
                 direction: Direction::Vertical,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Fixed(2)),
                 run: Some(Run::Plugin(Some(RunPlugin {
@@ -333,10 +503,24 @@ fn three_panes_with_tab_and_default_plugins_new_tab_is_correct() {
     let merged_layout = Layout {
         direction: Direction::Horizontal,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
         parts: vec![
             Layout {
                 direction: Direction::Vertical,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Fixed(1)),
                 run: Some(Run::Plugin(Some(RunPlugin {
@@ -347,6 +531,13 @@ fn three_panes_with_tab_and_default_plugins_new_tab_is_correct() {
             Layout {
                 direction: Direction::Horizontal,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: None,
                 run: None,
@@ -354,6 +545,13 @@ fn three_panes_with_tab_and_default_plugins_new_tab_is_correct() {
             Layout {
                 direction: Direction::Vertical,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Fixed(2)),
                 run: Some(Run::Plugin(Some(RunPlugin {
@@ -395,14 +593,35 @@ fn deeply_nested_tab_merged_correctly() {
     let merged_layout = Layout {
         direction: Direction::Horizontal,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
         parts: vec![
             Layout {
                 direction: Direction::Vertical,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![
                     Layout {
                         direction: Direction::Horizontal,
                         borderless: false,
+                        stacked: false,
+                        pane_frames: None,
+                        min_size: None,
+                        max_size: None,
+                        tags: vec![],
+                        palette: None,
+                        ambiguous_width: None,
                         parts: vec![],
                         split_size: Some(SplitSize::Percent(21.0)),
                         run: None,
@@ -410,10 +629,24 @@ fn deeply_nested_tab_merged_correctly() {
                     Layout {
                         direction: Direction::Vertical,
                         borderless: false,
+                        stacked: false,
+                        pane_frames: None,
+                        min_size: None,
+                        max_size: None,
+                        tags: vec![],
+                        palette: None,
+                        ambiguous_width: None,
                         parts: vec![
                             Layout {
                                 direction: Direction::Horizontal,
                                 borderless: false,
+                                stacked: false,
+                                pane_frames: None,
+                                min_size: None,
+                                max_size: None,
+                                tags: vec![],
+                                palette: None,
+                                ambiguous_width: None,
                                 parts: vec![],
                                 split_size: Some(SplitSize::Percent(22.0)),
                                 run: None,
@@ -421,10 +654,24 @@ fn deeply_nested_tab_merged_correctly() {
                             Layout {
                                 direction: Direction::Horizontal,
                                 borderless: false,
+                                stacked: false,
+                                pane_frames: None,
+                                min_size: None,
+                                max_size: None,
+                                tags: vec![],
+                                palette: None,
+                                ambiguous_width: None,
                                 parts: vec![
                                     Layout {
                                         direction: Direction::Horizontal,
                                         borderless: false,
+                                        stacked: false,
+                                        pane_frames: None,
+                                        min_size: None,
+                                        max_size: None,
+                                        tags: vec![],
+                                        palette: None,
+                                        ambiguous_width: None,
                                         parts: vec![],
                                         split_size: Some(SplitSize::Percent(23.0)),
                                         run: None,
@@ -432,6 +679,13 @@ fn deeply_nested_tab_merged_correctly() {
                                     Layout {
                                         direction: Direction::Horizontal,
                                         borderless: false,
+                                        stacked: false,
+                                        pane_frames: None,
+                                        min_size: None,
+                                        max_size: None,
+                                        tags: vec![],
+                                        palette: None,
+                                        ambiguous_width: None,
                                         parts: vec![],
                                         split_size: Some(SplitSize::Percent(24.0)),
                                         run: None,
@@ -451,6 +705,13 @@ fn deeply_nested_tab_merged_correctly() {
             Layout {
                 direction: Direction::Vertical,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Percent(15.0)),
                 run: None,
@@ -458,6 +719,13 @@ fn deeply_nested_tab_merged_correctly() {
             Layout {
                 direction: Direction::Vertical,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Percent(15.0)),
                 run: None,
@@ -465,6 +733,13 @@ fn deeply_nested_tab_merged_correctly() {
             Layout {
                 direction: Direction::Vertical,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Percent(15.0)),
                 run: None,
@@ -503,10 +778,24 @@ fn three_tabs_tab_one_merged_correctly() {
     let merged_layout = Layout {
         direction: Direction::Vertical,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
         parts: vec![
             Layout {
                 direction: Direction::Horizontal,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: Some(SplitSize::Percent(50.0)),
                 run: None,
@@ -514,6 +803,13 @@ fn three_tabs_tab_one_merged_correctly() {
             Layout {
                 direction: Direction::Horizontal,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: None,
                 run: None,
@@ -538,14 +834,35 @@ fn three_tabs_tab_two_merged_correctly() {
     let merged_layout = Layout {
         direction: Direction::Vertical,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
         parts: vec![
             Layout {
                 direction: Direction::Horizontal,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![
                     Layout {
                         direction: Direction::Horizontal,
                         borderless: false,
+                        stacked: false,
+                        pane_frames: None,
+                        min_size: None,
+                        max_size: None,
+                        tags: vec![],
+                        palette: None,
+                        ambiguous_width: None,
                         parts: vec![],
                         split_size: Some(SplitSize::Percent(50.0)),
                         run: None,
@@ -553,6 +870,13 @@ fn three_tabs_tab_two_merged_correctly() {
                     Layout {
                         direction: Direction::Horizontal,
                         borderless: false,
+                        stacked: false,
+                        pane_frames: None,
+                        min_size: None,
+                        max_size: None,
+                        tags: vec![],
+                        palette: None,
+                        ambiguous_width: None,
                         parts: vec![],
                         split_size: None,
                         run: None,
@@ -564,6 +888,13 @@ fn three_tabs_tab_two_merged_correctly() {
             Layout {
                 direction: Direction::Horizontal,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: None,
                 run: None,
@@ -588,14 +919,35 @@ fn three_tabs_tab_three_merged_correctly() {
     let merged_layout = Layout {
         direction: Direction::Vertical,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
         parts: vec![
             Layout {
                 direction: Direction::Vertical,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![
                     Layout {
                         direction: Direction::Vertical,
                         borderless: false,
+                        stacked: false,
+                        pane_frames: None,
+                        min_size: None,
+                        max_size: None,
+                        tags: vec![],
+                        palette: None,
+                        ambiguous_width: None,
                         parts: vec![],
                         split_size: Some(SplitSize::Percent(50.0)),
                         run: None,
@@ -603,6 +955,13 @@ fn three_tabs_tab_three_merged_correctly() {
                     Layout {
                         direction: Direction::Horizontal,
                         borderless: false,
+                        stacked: false,
+                        pane_frames: None,
+                        min_size: None,
+                        max_size: None,
+                        tags: vec![],
+                        palette: None,
+                        ambiguous_width: None,
                         parts: vec![],
                         split_size: None,
                         run: None,
@@ -614,6 +973,13 @@ fn three_tabs_tab_three_merged_correctly() {
             Layout {
                 direction: Direction::Horizontal,
                 borderless: false,
+                stacked: false,
+                pane_frames: None,
+                min_size: None,
+                max_size: None,
+                tags: vec![],
+                palette: None,
+                ambiguous_width: None,
                 parts: vec![],
                 split_size: None,
                 run: None,
@@ -649,9 +1015,23 @@ fn no_tabs_merged_correctly() {
     let merged_layout = Layout {
         direction: Direction::Horizontal,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
         parts: vec![Layout {
             direction: Direction::Horizontal,
             borderless: false,
+            stacked: false,
+            pane_frames: None,
+            min_size: None,
+            max_size: None,
+            tags: vec![],
+            palette: None,
+            ambiguous_width: None,
             parts: vec![],
             split_size: None,
             run: None,
@@ -698,6 +1078,13 @@ fn no_layout_template_merged_correctly() {
                     split_size: None,
                     run: None,
                     borderless: false,
+                    stacked: false,
+                    pane_frames: None,
+                    min_size: None,
+                    max_size: None,
+                    tags: vec![],
+                    palette: None,
+                    ambiguous_width: None,
                 },
                 Layout {
                     direction: Direction::Horizontal,
@@ -705,16 +1092,404 @@ fn no_layout_template_merged_correctly() {
                     split_size: None,
                     run: None,
                     borderless: false,
+                    stacked: false,
+                    pane_frames: None,
+                    min_size: None,
+                    max_size: None,
+                    tags: vec![],
+                    palette: None,
+                    ambiguous_width: None,
                 },
             ],
             split_size: None,
             run: None,
             borderless: false,
+            stacked: false,
+            pane_frames: None,
+            min_size: None,
+            max_size: None,
+            tags: vec![],
+            palette: None,
+            ambiguous_width: None,
         }],
         split_size: None,
         run: None,
         borderless: false,
+        stacked: false,
+        pane_frames: None,
+        min_size: None,
+        max_size: None,
+        tags: vec![],
+        palette: None,
+        ambiguous_width: None,
     };
 
     assert_eq!(merged_layout, tab_layout.into());
 }
+
+#[test]
+fn stacked_part_panes_are_found() {
+    let path = layout_test_dir("three-panes-with-stacked-part.yaml".into());
+    let layout = LayoutFromYaml::new(&path);
+    let layout_template = layout.as_ref().unwrap();
+    let tab_layout: Layout = layout_template
+        .template
+        .clone()
+        .insert_tab_layout(Some(layout_template.tabs[0].clone()))
+        .into();
+    assert_eq!(
+        tab_layout
+            .position_panes_in_space(&PaneGeom::default())
+            .len(),
+        3
+    );
+    assert_eq!(tab_layout.stacked_pane_ranges(), vec![1..3]);
+}
+
+#[test]
+fn new_tab_takes_its_parts_from_a_layout_file() {
+    let tab_layout = TabLayout {
+        name: "scratch".into(),
+        layout: Some(layout_test_dir(
+            "three-panes-with-tab-and-command.yaml".into(),
+        )),
+        cwd: Some(PathBuf::from("/tmp")),
+        env: vec![("RUST_LOG".to_string(), "info".to_string())]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    let mut tab_layout = tab_layout.load_layout_file(None, &[]).unwrap();
+    tab_layout.pass_cwd_and_env_to_commands();
+
+    assert_eq!(tab_layout.name, "scratch");
+    assert_eq!(tab_layout.direction, Direction::Vertical);
+    let commands = &tab_layout.parts[1].parts;
+    for command in commands {
+        match &command.run {
+            Some(Run::Command(command)) => {
+                assert_eq!(command.cwd, Some(PathBuf::from("/tmp")));
+                assert_eq!(
+                    command.env.get("RUST_LOG").map(String::as_str),
+                    Some("info")
+                );
+            }
+            _ => panic!("expected a command"),
+        }
+    }
+}
+
+#[test]
+fn new_tab_takes_a_layout_that_is_not_a_file_by_name() {
+    let tab_layout = TabLayout {
+        layout: Some(PathBuf::from("strider")),
+        ..Default::default()
+    };
+    let tab_layout = tab_layout.load_layout_file(None, &[]).unwrap();
+    let expected = LayoutFromYaml::strider_from_assets().unwrap();
+    assert_eq!(
+        tab_layout,
+        expected.tabs.into_iter().next().unwrap_or_default()
+    );
+}
+
+#[test]
+fn commands_are_read_with_their_args_cwd_and_env() {
+    let path = layout_test_dir("three-panes-with-commands-and-env.yaml".into());
+    let layout_from_yaml = LayoutFromYaml::new(&path).unwrap();
+    let tab_layout = &layout_from_yaml.tabs[0];
+    let command = |part: &TabLayout| match &part.run {
+        Some(Run::Command(command)) => command.clone(),
+        _ => panic!("expected a command"),
+    };
+
+    let editor = command(&tab_layout.parts[0]);
+    assert_eq!(editor.command, PathBuf::from("vim"));
+    assert_eq!(editor.cwd, Some(PathBuf::from("/tmp")));
+    assert!(editor.env.is_empty());
+
+    let server = command(&tab_layout.parts[1].parts[0]);
+    assert_eq!(server.args, vec!["run".to_string()]);
+    assert_eq!(
+        server.env.get("RUST_LOG").map(String::as_str),
+        Some("debug")
+    );
+    assert_eq!(server.shutdown_signal, Some(ShutdownSignal::Int));
+
+    let log_tail = command(&tab_layout.parts[1].parts[1]);
+    assert_eq!(
+        log_tail.args,
+        vec!["-f".to_string(), "server.log".to_string()]
+    );
+    assert_eq!(log_tail.cwd, None);
+    assert_eq!(log_tail.shutdown_signal, None);
+}
+
+#[test]
+fn tabs_are_read_with_their_names_and_focus() {
+    let path = layout_test_dir("three-tabs-with-names-and-focus.yaml".into());
+    let layout_from_yaml = LayoutFromYaml::new(&path).unwrap();
+    let names: Vec<&str> = layout_from_yaml
+        .tabs
+        .iter()
+        .map(|tab_layout| tab_layout.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["editor", "server", "logs"]);
+    assert_eq!(
+        layout_from_yaml
+            .tabs
+            .iter()
+            .position(|tab_layout| tab_layout.focus),
+        Some(1)
+    );
+    assert_eq!(layout_from_yaml.tabs[1].parts.len(), 2);
+    assert_eq!(layout_from_yaml.tabs[2].cwd, Some(PathBuf::from("/tmp")));
+}
+
+#[test]
+fn variables_are_filled_in_from_the_given_values() {
+    let path = layout_test_dir("tab-with-variables.yaml".into());
+    let variables = vec![
+        ("project".to_string(), "zellij".to_string()),
+        ("project_dir".to_string(), "/tmp".to_string()),
+    ];
+    let layout_from_yaml = LayoutFromYaml::with_variables(&path, &variables).unwrap();
+    assert_eq!(layout_from_yaml.tabs[0].name, "zellij");
+    assert_eq!(layout_from_yaml.tabs[0].cwd, Some(PathBuf::from("/tmp")));
+}
+
+#[test]
+fn variables_without_a_value_are_an_error() {
+    let path = layout_test_dir("tab-with-variables.yaml".into());
+    let variables = vec![("project".to_string(), "zellij".to_string())];
+    let layout_from_yaml = LayoutFromYaml::with_variables(&path, &variables);
+    assert!(layout_from_yaml.is_err());
+}
+
+#[test]
+fn variables_are_filled_in_as_text_wherever_they_are() {
+    let path = layout_test_dir("tab-with-variables-in-every-scalar.yaml".into());
+    let project = "x\"]}\ntabs: [{name: 'injected'}] # \\";
+    let project_dir = "/tmp/it's here";
+    let variables = vec![
+        ("project".to_string(), project.to_string()),
+        ("project_dir".to_string(), project_dir.to_string()),
+    ];
+    let layout_from_yaml = LayoutFromYaml::with_variables(&path, &variables).unwrap();
+    assert_eq!(layout_from_yaml.tabs.len(), 1);
+    let tab_layout = &layout_from_yaml.tabs[0];
+    assert_eq!(tab_layout.name, project);
+    assert_eq!(tab_layout.cwd, Some(PathBuf::from(project_dir)));
+    match &tab_layout.run {
+        Some(Run::Command(command)) => {
+            assert_eq!(
+                command.args,
+                vec!["--cmd".to_string(), format!("echo {}", project)]
+            );
+        }
+        _ => panic!("expected a command"),
+    }
+}
+
+#[test]
+fn new_tab_fills_in_the_variables_of_its_layout_file() {
+    let tab_layout = TabLayout {
+        layout: Some(layout_test_dir("tab-with-variables.yaml".into())),
+        ..Default::default()
+    };
+    let variables = vec![
+        ("project".to_string(), "zellij".to_string()),
+        ("project_dir".to_string(), "/tmp".to_string()),
+    ];
+    let tab_layout = tab_layout.load_layout_file(None, &variables).unwrap();
+    assert_eq!(tab_layout.name, "zellij");
+    assert_eq!(tab_layout.cwd, Some(PathBuf::from("/tmp")));
+}
+
+#[test]
+fn included_layouts_give_their_tabs_and_template() {
+    let path = layout_test_dir("including-other-layouts.yaml".into());
+    let variables = vec![
+        ("project".to_string(), "zellij".to_string()),
+        ("project_dir".to_string(), "/tmp".to_string()),
+    ];
+    let layout_from_yaml = LayoutFromYaml::with_variables(&path, &variables).unwrap();
+    let included = LayoutFromYaml::new(&layout_test_dir(
+        "three-panes-with-tab-and-default-plugins.yaml".into(),
+    ))
+    .unwrap();
+    assert_eq!(layout_from_yaml.template, included.template);
+    let names: Vec<&str> = layout_from_yaml
+        .tabs
+        .iter()
+        .map(|tab_layout| tab_layout.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["", "zellij", "logs"]);
+    assert!(layout_from_yaml.include.is_empty());
+}
+
+#[test]
+fn layouts_including_themselves_are_an_error() {
+    let path = layout_test_dir("including-itself.yaml".into());
+    let layout_from_yaml = LayoutFromYaml::new(&path);
+    assert!(layout_from_yaml.is_err());
+}
+
+#[test]
+fn sizes_are_read_as_percents_or_cells_with_bounds() {
+    let tab_layout: TabLayout = serde_yaml::from_str(
+        "direction: Vertical\nparts:\n  - size: 30%\n    min_size: 20\n  - size: 40\n  - split_size: {Percent: 20}\n    max_size: 10",
+    )
+    .unwrap();
+
+    assert_eq!(
+        tab_layout.parts[0].split_size,
+        Some(SplitSize::Percent(30.0))
+    );
+    assert_eq!(tab_layout.parts[0].min_size, Some(20));
+    assert_eq!(tab_layout.parts[1].split_size, Some(SplitSize::Fixed(40)));
+    assert_eq!(
+        tab_layout.parts[2].split_size,
+        Some(SplitSize::Percent(20.0))
+    );
+    assert_eq!(tab_layout.parts[2].max_size, Some(10));
+}
+
+#[test]
+fn bounds_of_a_part_are_kept_by_its_panes() {
+    let tab_layout: TabLayout = serde_yaml::from_str(
+        "direction: Vertical\nparts:\n  - direction: Horizontal\n    max_size: 40\n    parts:\n      - direction: Vertical\n      - direction: Vertical\n  - direction: Vertical",
+    )
+    .unwrap();
+    let layout: Layout = tab_layout.into();
+
+    let panes = layout.position_panes_in_space(&PaneGeom::default());
+    assert_eq!(panes.len(), 3);
+    for (_, geom) in &panes[..2] {
+        assert_eq!(geom.cols.max_size(), Some(40));
+        assert_eq!(geom.rows.max_size(), None);
+    }
+    assert_eq!(panes[2].1.cols.max_size(), None);
+}
+
+#[test]
+fn sizes_survive_the_trip_between_client_and_server() {
+    let tab_layout = TabLayout {
+        split_size: Some(SplitSize::Percent(30.0)),
+        min_size: Some(20),
+        ..Default::default()
+    };
+    let bytes = bincode::serialize(&tab_layout).unwrap();
+    let decoded: TabLayout = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(decoded, tab_layout);
+}
+
+#[test]
+fn palettes_of_panes_are_read_in_the_colors_of_themes() {
+    let tab_layout: TabLayout = serde_yaml::from_str(
+        "direction: Vertical\nparts:\n  - palette: {bg: [64, 0, 0], red: 9}\n  - direction: Vertical",
+    )
+    .unwrap();
+
+    let palette = tab_layout.parts[0].palette.unwrap();
+    assert_eq!(palette.bg, Some(PaletteColorFromYaml::Rgb((64, 0, 0))));
+    assert_eq!(palette.color(9), Some(PaletteColorFromYaml::EightBit(9)));
+    assert_eq!(palette.fg, None);
+    assert_eq!(tab_layout.parts[1].palette, None);
+}
+
+#[test]
+fn panes_can_override_the_ambiguous_width() {
+    let tab_layout: TabLayout = serde_yaml::from_str(
+        "direction: Vertical\nparts:\n  - ambiguous_width: wide\n  - direction: Vertical",
+    )
+    .unwrap();
+
+    let layout: Layout = tab_layout.into();
+    assert_eq!(layout.parts[0].ambiguous_width, Some(AmbiguousWidth::Wide));
+    assert_eq!(layout.parts[1].ambiguous_width, None);
+}
+
+#[test]
+fn palettes_survive_the_trip_between_client_and_server() {
+    let tab_layout = TabLayout {
+        palette: Some(PaletteOverride {
+            bg: Some(PaletteColorFromYaml::Rgb((64, 0, 0))),
+            fg: Some(PaletteColorFromYaml::EightBit(15)),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let bytes = bincode::serialize(&tab_layout).unwrap();
+    let decoded: TabLayout = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(decoded, tab_layout);
+}
+
+#[test]
+fn tiled_swap_layout_fills_its_rows_from_the_top() {
+    let layout: Layout = SwapLayout::Tiled.tab_layout(5).into();
+
+    let row_sizes: Vec<usize> = layout.parts.iter().map(|row| row.parts.len()).collect();
+    assert_eq!(row_sizes, vec![3, 2]);
+    assert_eq!(
+        layout.position_panes_in_space(&PaneGeom::default()).len(),
+        5
+    );
+}
+
+#[test]
+fn main_vertical_swap_layout_stacks_the_other_panes_on_the_right() {
+    let layout: Layout = SwapLayout::MainVertical.tab_layout(3).into();
+
+    assert_eq!(layout.direction, Direction::Vertical);
+    assert!(layout.parts[0].parts.is_empty());
+    assert_eq!(layout.parts[1].direction, Direction::Horizontal);
+    assert_eq!(layout.parts[1].parts.len(), 2);
+}
+
+#[test]
+fn dumped_layout_can_be_read_back() {
+    let command = RunCommand {
+        command: "htop".into(),
+        cwd: Some("/tmp".into()),
+        ..Default::default()
+    };
+    let dumped = LayoutFromYaml {
+        tabs: vec![TabLayout {
+            name: "work".into(),
+            direction: Direction::Vertical,
+            parts: vec![
+                TabLayout {
+                    split_size: Some(SplitSize::Percent(100.0 / 3.0)),
+                    run: Some(Run::Command(command)),
+                    ..Default::default()
+                },
+                TabLayout {
+                    split_size: Some(SplitSize::Fixed(40)),
+                    ..Default::default()
+                },
+            ],
+            focus: true,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let yaml = serde_yaml::to_string(&dumped).unwrap();
+    let read_back: LayoutFromYaml = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(read_back, dumped);
+}
+
+#[test]
+fn invalid_layout_names_its_file() {
+    let path = layout_test_dir("invalid-tabs.yaml".into());
+    let error = LayoutFromYaml::new(&path).unwrap_err();
+    assert!(error.to_string().contains(&path.display().to_string()));
+}
+
+#[test]
+fn invalid_included_layout_names_the_included_file() {
+    let path = layout_test_dir("including-an-invalid-layout.yaml".into());
+    let error = LayoutFromYaml::new(&path).unwrap_err().to_string();
+    assert!(error.contains("invalid-tabs.yaml"), "{}", error);
+}
@@ -0,0 +1,70 @@
+use super::super::actions::*;
+use super::super::macros::*;
+
+#[test]
+fn expand_macro_to_its_actions() {
+    let mut macros = Macros::new();
+    macros.insert(
+        "ide-layout".into(),
+        vec![
+            Action::NewPane(Some(Direction::Right)),
+            Action::Resize(Direction::Left, None),
+            Action::NewPane(Some(Direction::Down)),
+        ],
+    );
+
+    let expanded = macros.expand(vec![Action::Macro("ide-layout".into()), Action::NoOp]);
+
+    assert_eq!(
+        expanded,
+        vec![
+            Action::NewPane(Some(Direction::Right)),
+            Action::Resize(Direction::Left, None),
+            Action::NewPane(Some(Direction::Down)),
+            Action::NoOp,
+        ]
+    );
+}
+
+#[test]
+fn expand_nested_macros() {
+    let mut macros = Macros::new();
+    macros.insert("inner".into(), vec![Action::Copy]);
+    macros.insert(
+        "outer".into(),
+        vec![Action::Macro("inner".into()), Action::Macro("inner".into())],
+    );
+
+    let expanded = macros.expand(vec![Action::Macro("outer".into())]);
+
+    assert_eq!(expanded, vec![Action::Copy, Action::Copy]);
+}
+
+#[test]
+fn unknown_macro_expands_to_nothing() {
+    let macros = Macros::new();
+
+    let expanded = macros.expand(vec![Action::Macro("nope".into()), Action::NoOp]);
+
+    assert_eq!(expanded, vec![Action::NoOp]);
+}
+
+#[test]
+fn recursive_macro_does_not_loop() {
+    let mut macros = Macros::new();
+    macros.insert(
+        "ping".into(),
+        vec![Action::FocusNextPane, Action::Macro("pong".into())],
+    );
+    macros.insert(
+        "pong".into(),
+        vec![Action::FocusPreviousPane, Action::Macro("ping".into())],
+    );
+
+    let expanded = macros.expand(vec![Action::Macro("ping".into())]);
+
+    assert_eq!(
+        expanded,
+        vec![Action::FocusNextPane, Action::FocusPreviousPane]
+    );
+}
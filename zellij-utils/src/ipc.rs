@@ -14,9 +14,10 @@ use std::{
     io::{self, Write},
     marker::PhantomData,
     os::unix::io::{AsRawFd, FromRawFd},
+    time::Duration,
 };
 
-use zellij_tile::data::Palette;
+use zellij_tile::data::{InputMode, Palette};
 
 type SessionId = u64;
 
@@ -37,10 +38,16 @@ pub enum ClientType {
     Writer,
 }
 
+/// Tells apart the clients attached to a session, ids aren't reused.
+pub type ClientId = u32;
+
 #[derive(Default, Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct ClientAttributes {
     pub size: Size,
     pub palette: Palette,
+    /// Whether the client attaches as a guest, moving its focus around the session without
+    /// changing it
+    pub read_only: bool,
 }
 
 // Types of messages sent from the client to the server
@@ -58,12 +65,25 @@ pub enum ClientToServerMsg {
     // Disconnect from the session we're connected to
     DisconnectFromSession,*/
     TerminalResize(Size),
+    // Whether the terminal the client runs in has the focus
+    TerminalFocus(bool),
     NewClient(ClientAttributes, Box<CliArgs>, Box<Options>, LayoutFromYaml),
-    AttachClient(ClientAttributes, bool, Options),
+    // Attaching and sending actions from the command line take the token of the session
+    AttachClient(ClientAttributes, bool, Options, Option<String>),
     Action(Action),
+    CliAction(Action, Option<String>),
     ClientExited,
+    // The client leaves for the session of the given name, over a new connection, without
+    // exiting: the session it leaves is detached and goes on running
+    SwitchSession(String),
+    // Sent every `HEARTBEAT_INTERVAL` by attached clients to keep the connection alive, and
+    // by `list-sessions` to check whether a session still responds
+    Ping,
 }
 
+/// How often an attached client pings the server to keep the connection alive.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
 // Types of messages sent from the server to the client
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ServerToClientMsg {
@@ -74,14 +94,62 @@ pub enum ServerToClientMsg {
     Render(String),
     UnblockInputThread,
     Exit(ExitReason),
+    ActionResult(ActionResult),
+    // A status message for the user, like an action being refused
+    Diagnostic(String),
+    // Text copied in the session, for the client to put in the clipboard
+    CopyToClipboard(String),
+    // A hyperlink clicked in the session, for the client to open with its link opener
+    OpenLink(String),
+    // Asks the client to attach to the session of the given name instead, forcibly if the flag
+    // is set, which it does if the session is there
+    SwitchSession(String, bool),
+    // Reply to a `Ping` from a client that isn't attached to the session
+    Pong,
+    // The input mode of the session, for a client joining the others in it
+    SwitchToMode(InputMode),
+}
+
+/// The outcome of an action sent from the command line, reported back so that
+/// scripts can target whatever the action created.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ActionResult {
+    /// A pane was opened, with the given terminal id.
+    NewPane(u32),
+    /// A tab was opened at the given (1-based) position, containing the given terminal ids.
+    NewTab { position: usize, panes: Vec<u32> },
+    /// The action does not produce a value (or could not be carried out).
+    Empty,
+    /// The action was refused, eg. because of one of the limits of the session, for the
+    /// given reason.
+    Refused(String),
+    /// The terminals of the session, as listed by `ListPanes`.
+    Panes(Vec<PaneSummary>),
+}
+
+/// A terminal of the session, where it is and the tags it was given.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PaneSummary {
+    pub id: u32,
+    /// The (1-based) position of the tab holding the pane.
+    pub tab_position: usize,
+    pub title: String,
+    pub tags: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ExitReason {
     Normal,
     ForceDetached,
-    CannotAttach,
+    // another client of the session detached this one
+    DetachedByOtherClient,
+    // the client stopped pinging the server
+    TimedOut,
     Error(String),
+    // the client left for this session
+    SwitchSession(String),
+    // the session quit, but these panes didn't exit in time and had to be killed
+    KilledPanes(Vec<String>),
 }
 
 impl Display for ExitReason {
@@ -90,13 +158,22 @@ impl Display for ExitReason {
             Self::Normal => write!(f, "Bye from Zellij!"),
             Self::ForceDetached => write!(
                 f,
-                "Session was detach from this client (possibly because another client connected)"
+                "Session was detached from this client (another client attached with --force)"
             ),
-            Self::CannotAttach => write!(
+            Self::DetachedByOtherClient => {
+                write!(f, "Session was detached from this client by another client")
+            }
+            Self::TimedOut => write!(
                 f,
-                "Session attached to another client. Use --force flag to force connect."
+                "Session was detached from this client, which stopped responding"
             ),
             Self::Error(e) => write!(f, "Error occured in server:\n{}", e),
+            Self::SwitchSession(name) => write!(f, "Switching to session {:?}", name),
+            Self::KilledPanes(panes) => write!(
+                f,
+                "Bye from Zellij! These panes didn't exit within the grace period and were killed:\n{}",
+                panes.join("\n")
+            ),
         }
     }
 }
@@ -123,6 +200,14 @@ impl<T: Serialize> IpcSenderWithContext<T> {
         self.sender.flush().unwrap();
     }
 
+    /// Like [`send`](Self::send), but returns `false` instead of panicking if the
+    /// other end went away.
+    pub fn try_send(&mut self, msg: T) -> bool {
+        let err_ctx = get_current_ctx();
+        bincode::serialize_into(&mut self.sender, &(msg, err_ctx)).is_ok()
+            && self.sender.flush().is_ok()
+    }
+
     /// Returns an [`IpcReceiverWithContext`] with the same socket as this sender.
     pub fn get_receiver<F>(&self) -> IpcReceiverWithContext<F>
     where
@@ -158,6 +243,12 @@ where
         bincode::deserialize_from(&mut self.receiver).unwrap()
     }
 
+    /// Like [`recv`](Self::recv), but returns `None` instead of panicking if the
+    /// other end went away or sent something we can't read.
+    pub fn try_recv(&mut self) -> Option<(T, ErrorContext)> {
+        bincode::deserialize_from(&mut self.receiver).ok()
+    }
+
     /// Returns an [`IpcSenderWithContext`] with the same socket as this receiver.
     pub fn get_sender<F: Serialize>(&self) -> IpcSenderWithContext<F> {
         let sock_fd = self.receiver.get_ref().as_raw_fd();
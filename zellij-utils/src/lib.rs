@@ -1,3 +1,4 @@
+pub mod action_token;
 pub mod channels;
 pub mod cli;
 pub mod consts;
@@ -5,6 +6,7 @@ pub mod errors;
 pub mod input;
 pub mod ipc;
 pub mod logging;
+pub mod messages;
 pub mod pane_size;
 pub mod position;
 pub mod setup;
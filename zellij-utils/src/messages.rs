@@ -0,0 +1,196 @@
+//! Translations of the text zellij shows to the user, like the keybinding hints in the status
+//! bar and the prompts in panes.
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Environment variable picking the [`Locale`], when no `locale` option is set.
+pub const ZELLIJ_LOCALE_ENV_KEY: &str = "ZELLIJ_LOCALE";
+
+/// A language zellij has a message catalog for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Locale {
+    #[serde(alias = "en")]
+    En,
+    #[serde(alias = "de")]
+    De,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::En
+    }
+}
+
+impl FromStr for Locale {
+    type Err = Box<dyn std::error::Error>;
+
+    // takes the language out of posix style locales as well, eg. `de_DE.UTF-8`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language = s.split(|c| c == '_' || c == '-' || c == '.').next();
+        match language {
+            Some("en") | Some("C") | Some("POSIX") => Ok(Self::En),
+            Some("de") => Ok(Self::De),
+            _ => Err(s.to_string().into()),
+        }
+    }
+}
+
+impl Locale {
+    /// The locale set in the [`ZELLIJ_LOCALE_ENV_KEY`] environment variable, or the default
+    /// one if it is unset or has no catalog.
+    pub fn from_env() -> Self {
+        std::env::var(ZELLIJ_LOCALE_ENV_KEY)
+            .ok()
+            .and_then(|locale| locale.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// The locale of the `locale` option if it is set, or else the one of the environment.
+    pub fn from_option(locale: Option<Locale>) -> Self {
+        locale.unwrap_or_else(Self::from_env)
+    }
+
+    /// Looks up the text of `message` in the catalog of this locale.
+    pub fn text(self, message: Message) -> &'static str {
+        match self {
+            Locale::En => english(message),
+            Locale::De => german(message),
+        }
+    }
+}
+
+/// The text zellij shows to the user, to be looked up in the catalog of a [`Locale`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    MoveFocus,
+    NextPane,
+    NewPane,
+    DownSplit,
+    RightSplit,
+    ClosePane,
+    Fullscreen,
+    PaneFrames,
+    NewTab,
+    CloseTab,
+    RenameTab,
+    SyncTab,
+    ToggleTab,
+    Resize,
+    Scroll,
+    ScrollPage,
+    SearchMatch,
+    SearchCase,
+    SearchWrap,
+    JumpToMatch,
+    SelectCommand,
+    RunCommand,
+    RunInItsPane,
+    CopySelect,
+    CopyYank,
+    WhenDone,
+    Detach,
+    DetachOtherClients,
+    RenameSession,
+    SessionManager,
+    SelectSession,
+    SwitchSession,
+    KillSession,
+    /// Asks whether a pane may copy `{bytes}` bytes to the clipboard, answered with y or n.
+    ClipboardPrompt,
+    /// Tells that a pane tried to copy `{bytes}` bytes to the clipboard, over the `{limit}`.
+    ClipboardTooLarge,
+    /// Asks whether `{lines}` pasted lines may be sent to a shell prompt, answered with y or n.
+    PastePrompt,
+    /// Marks that `{panes}` panes were hidden because the terminal got too small for them.
+    HiddenPanes,
+}
+
+fn english(message: Message) -> &'static str {
+    match message {
+        Message::MoveFocus => "Move focus",
+        Message::NextPane => "Next",
+        Message::NewPane | Message::NewTab => "New",
+        Message::DownSplit => "Down split",
+        Message::RightSplit => "Right split",
+        Message::ClosePane | Message::CloseTab => "Close",
+        Message::Fullscreen => "Fullscreen",
+        Message::PaneFrames => "Frames",
+        Message::RenameTab => "Rename",
+        Message::SyncTab => "Sync",
+        Message::ToggleTab => "Toggle",
+        Message::Resize => "Resize",
+        Message::Scroll => "Scroll",
+        Message::ScrollPage => "Scroll Page",
+        Message::SearchMatch => "Match",
+        Message::SearchCase => "Case",
+        Message::SearchWrap => "Wrap",
+        Message::JumpToMatch => "Jump to match",
+        Message::SelectCommand => "Command",
+        Message::RunCommand => "Run",
+        Message::RunInItsPane => "Run in its pane",
+        Message::CopySelect => "Select",
+        Message::CopyYank => "Copy",
+        Message::WhenDone => "when done",
+        Message::Detach => "Detach",
+        Message::DetachOtherClients => "Detach others",
+        Message::RenameSession => "Rename",
+        Message::SessionManager => "Sessions",
+        Message::SelectSession => "Session",
+        Message::SwitchSession => "Switch",
+        Message::KillSession => "Kill",
+        Message::ClipboardPrompt => "Allow this pane to copy {bytes} bytes to the clipboard? (y/n)",
+        Message::ClipboardTooLarge => {
+            "Not copying {bytes} bytes from a pane to the clipboard, over the limit of {limit}"
+        }
+        Message::PastePrompt => "Paste {lines} lines into this shell? (y/n)",
+        Message::HiddenPanes => "+{panes} hidden",
+    }
+}
+
+fn german(message: Message) -> &'static str {
+    match message {
+        Message::MoveFocus => "Fokus bewegen",
+        Message::NextPane => "Nächster",
+        Message::NewPane | Message::NewTab => "Neu",
+        Message::DownSplit => "Unten teilen",
+        Message::RightSplit => "Rechts teilen",
+        Message::ClosePane | Message::CloseTab => "Schließen",
+        Message::Fullscreen => "Vollbild",
+        Message::PaneFrames => "Rahmen",
+        Message::RenameTab => "Umbenennen",
+        Message::SyncTab => "Synchron",
+        Message::ToggleTab => "Wechseln",
+        Message::Resize => "Größe ändern",
+        Message::Scroll => "Scrollen",
+        Message::ScrollPage => "Seitenweise",
+        Message::SearchMatch => "Treffer",
+        Message::SearchCase => "Groß/klein",
+        Message::SearchWrap => "Umlauf",
+        Message::JumpToMatch => "Zum Treffer springen",
+        Message::SelectCommand => "Befehl",
+        Message::RunCommand => "Ausführen",
+        Message::RunInItsPane => "In seinem Bereich ausführen",
+        Message::CopySelect => "Auswählen",
+        Message::CopyYank => "Kopieren",
+        Message::WhenDone => "wenn fertig",
+        Message::Detach => "Trennen",
+        Message::DetachOtherClients => "Andere trennen",
+        Message::RenameSession => "Umbenennen",
+        Message::SessionManager => "Sitzungen",
+        Message::SelectSession => "Sitzung",
+        Message::SwitchSession => "Wechseln",
+        Message::KillSession => "Beenden",
+        Message::ClipboardPrompt => {
+            "Darf dieser Bereich {bytes} Bytes in die Zwischenablage kopieren? (y/n)"
+        }
+        Message::ClipboardTooLarge => {
+            "{bytes} Bytes aus einem Bereich werden nicht kopiert, die Grenze ist {limit}"
+        }
+        Message::PastePrompt => "{lines} Zeilen in diese Shell einfügen? (y/n)",
+        Message::HiddenPanes => "+{panes} ausgeblendet",
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/messages_test.rs"]
+mod messages_test;
@@ -38,6 +38,9 @@ pub struct Size {
 pub struct Dimension {
     pub constraint: Constraint,
     inner: usize,
+    // the bounds a flexible size is kept in, in rows or columns
+    min: Option<usize>,
+    max: Option<usize>,
 }
 
 impl Default for Dimension {
@@ -51,6 +54,8 @@ impl Dimension {
         Self {
             constraint: Constraint::Fixed(size),
             inner: 1,
+            min: None,
+            max: None,
         }
     }
 
@@ -58,6 +63,8 @@ impl Dimension {
         Self {
             constraint: Constraint::Percent(percent),
             inner: 1,
+            min: None,
+            max: None,
         }
     }
 
@@ -77,6 +84,29 @@ impl Dimension {
         self.inner = inner;
     }
 
+    /// Changes the percent of a flexible dimension, keeping its bounds.
+    pub fn set_percent(&mut self, percent: f64) {
+        self.constraint = Constraint::Percent(percent);
+    }
+
+    /// Keeps the size of a flexible dimension between `min` and `max` rows or columns.
+    pub fn set_bounds(&mut self, min: Option<usize>, max: Option<usize>) {
+        self.min = min;
+        self.max = max;
+    }
+
+    pub fn min_size(&self) -> Option<usize> {
+        self.min
+    }
+
+    pub fn max_size(&self) -> Option<usize> {
+        self.max
+    }
+
+    pub fn is_at_bound(&self) -> bool {
+        !self.is_fixed() && (Some(self.inner) == self.min || Some(self.inner) == self.max)
+    }
+
     pub fn is_fixed(&self) -> bool {
         matches!(self.constraint, Constraint::Fixed(_))
     }
@@ -180,6 +180,7 @@ impl Setup {
             opts.layout.as_ref(),
             opts.layout_path.as_ref(),
             layout_dir,
+            &opts.layout_vars,
         );
         let layout = match layout_result {
             None => None,
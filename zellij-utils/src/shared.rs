@@ -3,7 +3,8 @@
 use std::{iter, str::from_utf8};
 
 use colors_transform::{Color, Rgb};
-use std::os::unix::fs::PermissionsExt;
+use nix::unistd::Uid;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
 use std::path::Path;
 use std::{fs, io};
 use strip_ansi_escapes::strip;
@@ -18,6 +19,27 @@ pub fn set_permissions(path: &Path) -> io::Result<()> {
     fs::set_permissions(path, permissions)
 }
 
+/// Makes sure no other user can place or replace sockets in `path`: it has to be owned by the
+/// current user and not writable by its group or others. It is created if it is missing.
+pub fn check_socket_dir(path: &Path) -> io::Result<()> {
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(UNIX_PERMISSIONS)
+        .create(path)?;
+    let metadata = fs::metadata(path)?;
+    let refusal = if metadata.uid() != Uid::current().as_raw() {
+        "is owned by another user"
+    } else if metadata.mode() & 0o022 != 0 {
+        "is writable by other users"
+    } else {
+        return Ok(());
+    };
+    Err(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("{} {}", path.display(), refusal),
+    ))
+}
+
 pub fn ansi_len(s: &str) -> usize {
     from_utf8(&strip(s.as_bytes()).unwrap()).unwrap().width()
 }
@@ -114,3 +136,7 @@ pub fn version_number(mut version: &str) -> usize {
 
     version_number
 }
+
+#[cfg(test)]
+#[path = "./unit/shared_test.rs"]
+mod shared_test;
@@ -0,0 +1,32 @@
+use super::*;
+use std::os::unix::fs::PermissionsExt;
+use tempfile::tempdir;
+
+#[test]
+fn token_file_is_only_readable_by_its_owner() {
+    let dir = tempdir().unwrap();
+    let path = token_path(&dir.path().join("session"));
+    assert_eq!(path, dir.path().join("session.token"));
+
+    let token = generate().unwrap();
+    assert_eq!(token.len(), 32);
+    write(&path, &token).unwrap();
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    assert_eq!(fs::read_to_string(&path).unwrap(), token);
+
+    // a new session of the same name replaces the token
+    let other = generate().unwrap();
+    assert_ne!(token, other);
+    write(&path, &other).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), other);
+}
+
+#[test]
+fn only_the_same_token_matches() {
+    assert!(matches("0123abcd", Some("0123abcd")));
+    assert!(!matches("0123abcd", Some("0123abce")));
+    assert!(!matches("0123abcd", Some("0123abc")));
+    assert!(!matches("0123abcd", Some("")));
+    assert!(!matches("0123abcd", None));
+}
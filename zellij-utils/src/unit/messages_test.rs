@@ -0,0 +1,53 @@
+use super::*;
+use crate::input::options::Options;
+
+#[test]
+fn locales_are_read_with_or_without_their_region_and_encoding() {
+    assert_eq!("de".parse::<Locale>().unwrap(), Locale::De);
+    assert_eq!("de_DE.UTF-8".parse::<Locale>().unwrap(), Locale::De);
+    assert_eq!("en-GB".parse::<Locale>().unwrap(), Locale::En);
+    assert_eq!("C".parse::<Locale>().unwrap(), Locale::En);
+    assert_eq!("POSIX".parse::<Locale>().unwrap(), Locale::En);
+    assert!("fr_FR.UTF-8".parse::<Locale>().is_err());
+    assert!("".parse::<Locale>().is_err());
+}
+
+#[test]
+fn locale_option_is_read_from_the_config() {
+    let options: Options = serde_yaml::from_str("locale: de").unwrap();
+    assert_eq!(options.locale, Some(Locale::De));
+    let options: Options = serde_yaml::from_str("locale: De").unwrap();
+    assert_eq!(options.locale, Some(Locale::De));
+    assert!(serde_yaml::from_str::<Options>("locale: fr").is_err());
+}
+
+#[test]
+fn locale_of_the_command_line_comes_before_the_one_of_the_config() {
+    let config = Options {
+        locale: Some(Locale::De),
+        ..Default::default()
+    };
+    let merged = config.merge(Options {
+        locale: Some(Locale::En),
+        ..Default::default()
+    });
+    assert_eq!(merged.locale, Some(Locale::En));
+    let merged = config.merge(Options::default());
+    assert_eq!(merged.locale, Some(Locale::De));
+}
+
+// the only test of the crate setting the environment variable, the others don't read it
+#[test]
+fn locale_option_comes_before_the_environment_which_falls_back_to_english() {
+    std::env::set_var(ZELLIJ_LOCALE_ENV_KEY, "de_DE.UTF-8");
+    assert_eq!(Locale::from_env(), Locale::De);
+    assert_eq!(Locale::from_option(None), Locale::De);
+    assert_eq!(Locale::from_option(Some(Locale::En)), Locale::En);
+
+    std::env::set_var(ZELLIJ_LOCALE_ENV_KEY, "tlh");
+    assert_eq!(Locale::from_env(), Locale::En, "A locale without a catalog");
+
+    std::env::remove_var(ZELLIJ_LOCALE_ENV_KEY);
+    assert_eq!(Locale::from_env(), Locale::En);
+    assert_eq!(Locale::from_option(Some(Locale::De)), Locale::De);
+}
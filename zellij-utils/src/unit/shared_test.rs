@@ -0,0 +1,28 @@
+use super::*;
+use tempfile::tempdir;
+
+#[test]
+fn missing_socket_dir_is_created_for_the_owner_only() {
+    let dir = tempdir().unwrap();
+    let socket_dir = dir.path().join("sockets").join("nested");
+    check_socket_dir(&socket_dir).unwrap();
+    let mode = fs::metadata(&socket_dir).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o700);
+}
+
+#[test]
+fn socket_dir_writable_by_others_is_refused() {
+    let dir = tempdir().unwrap();
+    let socket_dir = dir.path().join("sockets");
+    fs::create_dir(&socket_dir).unwrap();
+    fs::set_permissions(&socket_dir, fs::Permissions::from_mode(0o777)).unwrap();
+    let error = check_socket_dir(&socket_dir).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    assert!(error.to_string().contains("writable by other users"));
+
+    fs::set_permissions(&socket_dir, fs::Permissions::from_mode(0o755)).unwrap();
+    assert!(
+        check_socket_dir(&socket_dir).is_ok(),
+        "others can only read"
+    );
+}